@@ -0,0 +1,140 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ * SPDX-FileCopyrightText: 2023 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+//! Command-line tool to inspect and convert files serialized with ε-serde.
+
+use anyhow::{Context, Result};
+use clap::{ArgGroup, Parser, Subcommand};
+use epserde::dynamic::DynamicValue;
+use epserde::inspect;
+use epserde::ser::Schema;
+use std::path::{Path, PathBuf};
+
+#[derive(Parser)]
+#[command(version, about = "Inspect and convert files serialized with ε-serde")]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Print the information in the header of a file.
+    Header {
+        /// The serialized file.
+        file: PathBuf,
+    },
+    /// Print the schema of a file stored with a sidecar schema file.
+    Schema {
+        /// The serialized file.
+        file: PathBuf,
+        /// The JSON schema file (default: FILE.schema.json).
+        #[arg(short, long)]
+        schema: Option<PathBuf>,
+        /// Print also the bytes of each piece of data.
+        #[arg(short, long)]
+        data: bool,
+    },
+    /// Print a hexadecimal dump of a field of a file stored with a sidecar schema file.
+    Hexdump {
+        /// The serialized file.
+        file: PathBuf,
+        /// The path of the field in the schema (e.g., ROOT.values.zero).
+        field: String,
+        /// The JSON schema file (default: FILE.schema.json).
+        #[arg(short, long)]
+        schema: Option<PathBuf>,
+    },
+    /// Convert a file between the native format of 64-bit architectures and the portable format.
+    #[command(group(ArgGroup::new("format").required(true).args(["portable", "native"])))]
+    Convert {
+        /// The source file.
+        src: PathBuf,
+        /// The destination file.
+        dst: PathBuf,
+        /// Convert to the portable format.
+        #[arg(long)]
+        portable: bool,
+        /// Convert to the native format.
+        #[arg(long)]
+        native: bool,
+    },
+}
+
+fn read_schema(file: &Path, schema: Option<PathBuf>) -> Result<Schema> {
+    let path = schema.unwrap_or_else(|| inspect::schema_path(file));
+    let json = std::fs::read_to_string(&path)
+        .with_context(|| format!("Cannot read schema file {}", path.display()))?;
+    Ok(Schema::from_json(&json)?)
+}
+
+/// Print `bytes`, starting at `offset` in the file, in the style of `xxd`.
+fn hexdump(bytes: &[u8], offset: usize) {
+    for (i, chunk) in bytes.chunks(16).enumerate() {
+        let hex = chunk
+            .iter()
+            .map(|byte| format!("{:02x}", byte))
+            .collect::<Vec<_>>()
+            .join(" ");
+        let ascii = chunk
+            .iter()
+            .map(|&byte| {
+                if byte.is_ascii_graphic() || byte == b' ' {
+                    byte as char
+                } else {
+                    '.'
+                }
+            })
+            .collect::<String>();
+        println!("{:08x}: {:<47}  {}", offset + i * 16, hex, ascii);
+    }
+}
+
+fn main() -> Result<()> {
+    match Cli::parse().command {
+        Command::Header { file } => {
+            print!("{}", inspect::read_header(&file)?);
+        }
+        Command::Schema { file, schema, data } => {
+            let schema = read_schema(&file, schema)?;
+            if data {
+                print!("{}", schema.debug(&std::fs::read(&file)?));
+            } else {
+                print!("{}", schema.to_csv());
+            }
+        }
+        Command::Hexdump {
+            file,
+            field,
+            schema,
+        } => {
+            let schema = read_schema(&file, schema)?;
+            let data = std::fs::read(&file)?;
+            let value = DynamicValue::new(&schema, &data, &field)
+                .with_context(|| format!("No field {} in the schema", field))?;
+            println!(
+                "{} ({}): {} bytes at offset {}",
+                value.path(),
+                value.ty(),
+                value.size(),
+                value.offset()
+            );
+            hexdump(value.bytes()?, value.offset());
+        }
+        Command::Convert {
+            src,
+            dst,
+            portable,
+            native: _,
+        } => {
+            let mut data = std::fs::read(&src)?;
+            inspect::set_portable(&mut data, portable)?;
+            std::fs::write(&dst, data)?;
+        }
+    }
+    Ok(())
+}
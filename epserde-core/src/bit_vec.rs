@@ -0,0 +1,303 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ * SPDX-FileCopyrightText: 2023 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+/*!
+
+A compact vector of booleans.
+
+A `Vec<bool>` is serialized using one byte per boolean. [`BitVec`] stores
+instead booleans as bits packed into `u64` words, and it is serialized as its
+length in bits followed by the vector of words. ε-copy deserialization
+returns a `BitVec<&[u64]>` whose words refer directly to the serialized data,
+so no allocation happens at all.
+
+```rust
+use epserde::prelude::*;
+use epserde::bit_vec::BitVec;
+
+let flags: BitVec = [true, false, true].into_iter().collect();
+
+let mut cursor = epserde::new_aligned_cursor();
+flags.serialize(&mut cursor)?;
+let buf = cursor.into_inner();
+
+let view = <BitVec>::deserialize_eps(&buf)?;
+assert_eq!(view.len(), 3);
+assert_eq!(view.get(2), Some(true));
+assert_eq!(view.iter().collect::<Vec<_>>(), vec![true, false, true]);
+assert_eq!(view, flags);
+# Ok::<(), Box<dyn std::error::Error>>(())
+```
+
+Unused bits of the last word are always zero: deserialization reports
+[`Error::InvalidValue`] if they are not.
+
+*/
+
+use crate::prelude::*;
+use core::hash::Hash;
+use deser::*;
+use ser::*;
+
+#[cfg(all(feature = "alloc", not(feature = "std")))]
+use alloc::{vec, vec::Vec};
+
+/// The number of bits in a word.
+const BITS: usize = u64::BITS as usize;
+
+/// A compact vector of booleans stored as bits packed into `u64` words.
+///
+/// See the [module documentation](self) for more information.
+///
+/// The backend `B` is `Vec<u64>` for owned vectors, and `&[u64]` for
+/// ε-copy deserialized vectors.
+#[derive(Debug, Clone, Copy, Default, Hash)]
+pub struct BitVec<B = Vec<u64>> {
+    words: B,
+    len: usize,
+}
+
+impl BitVec {
+    /// Create an empty bit vector.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create an empty bit vector with space for at least `capacity` bits.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            words: Vec::with_capacity(capacity.div_ceil(BITS)),
+            len: 0,
+        }
+    }
+
+    /// Create a bit vector of `len` bits with the given value.
+    pub fn from_value(value: bool, len: usize) -> Self {
+        let mut words = vec![if value { u64::MAX } else { 0 }; len.div_ceil(BITS)];
+        if let Some(last) = words.last_mut() {
+            if !len.is_multiple_of(BITS) {
+                *last &= (1 << (len % BITS)) - 1;
+            }
+        }
+        Self { words, len }
+    }
+
+    /// Append a bit.
+    pub fn push(&mut self, value: bool) {
+        if self.len.is_multiple_of(BITS) {
+            self.words.push(0);
+        }
+        if let (true, Some(word)) = (value, self.words.last_mut()) {
+            *word |= 1 << (self.len % BITS);
+        }
+        self.len += 1;
+    }
+
+    /// Remove the last bit and return it, or `None` if the bit vector is
+    /// empty.
+    pub fn pop(&mut self) -> Option<bool> {
+        if self.len == 0 {
+            return None;
+        }
+        let word = self.words.last_mut()?;
+        self.len -= 1;
+        let bit = self.len % BITS;
+        let value = *word & (1 << bit) != 0;
+        *word &= !(1 << bit);
+        if bit == 0 {
+            self.words.pop();
+        }
+        Some(value)
+    }
+
+    /// Set the bit of given index to `value`.
+    ///
+    /// # Panics
+    ///
+    /// This method panics if `index` is not smaller than the length.
+    pub fn set(&mut self, index: usize, value: bool) {
+        assert!(
+            index < self.len,
+            "Index {} out of bounds for bit vector of length {}",
+            index,
+            self.len
+        );
+        if let Some(word) = self.words.get_mut(index / BITS) {
+            if value {
+                *word |= 1 << (index % BITS);
+            } else {
+                *word &= !(1 << (index % BITS));
+            }
+        }
+    }
+
+    /// Remove all bits.
+    pub fn clear(&mut self) {
+        self.words.clear();
+        self.len = 0;
+    }
+
+    /// Return the underlying words.
+    pub fn into_words(self) -> Vec<u64> {
+        self.words
+    }
+}
+
+impl<B: AsRef<[u64]>> BitVec<B> {
+    /// Return the number of bits.
+    #[inline(always)]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Return whether there are no bits.
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Return the bit of given index, or `None` if `index` is not smaller
+    /// than the length.
+    #[inline(always)]
+    pub fn get(&self, index: usize) -> Option<bool> {
+        if index >= self.len {
+            return None;
+        }
+        let word = self.words.as_ref().get(index / BITS)?;
+        Some(word & (1 << (index % BITS)) != 0)
+    }
+
+    /// Return the number of bits set to one.
+    pub fn count_ones(&self) -> usize {
+        self.words
+            .as_ref()
+            .iter()
+            .map(|word| word.count_ones() as usize)
+            .sum()
+    }
+
+    /// Return an iterator over the bits.
+    pub fn iter(&self) -> impl ExactSizeIterator<Item = bool> + '_ {
+        let words = self.words.as_ref();
+        (0..self.len).map(move |index| {
+            words
+                .get(index / BITS)
+                .is_some_and(|word| word & (1 << (index % BITS)) != 0)
+        })
+    }
+
+    /// Return the words containing the bits, least significant bit first.
+    #[inline(always)]
+    pub fn as_words(&self) -> &[u64] {
+        self.words.as_ref()
+    }
+
+    /// Return an owned copy of this bit vector.
+    pub fn to_bit_vec(&self) -> BitVec {
+        BitVec {
+            words: self.words.as_ref().to_vec(),
+            len: self.len,
+        }
+    }
+}
+
+impl<'a> BitVec<&'a [u64]> {
+    /// Create a bit vector of length `len` from the given words, checking
+    /// that the number of words is correct and that unused bits are zero.
+    fn from_words(words: &'a [u64], len: usize) -> deser::Result<Self> {
+        if words.len() != len.div_ceil(BITS)
+            || (!len.is_multiple_of(BITS)
+                && words.last().is_some_and(|last| last >> (len % BITS) != 0))
+        {
+            return Err(deser::Error::InvalidValue("BitVec"));
+        }
+        Ok(Self { words, len })
+    }
+}
+
+impl<B: AsRef<[u64]>, C: AsRef<[u64]>> PartialEq<BitVec<C>> for BitVec<B> {
+    fn eq(&self, other: &BitVec<C>) -> bool {
+        self.len == other.len && self.words.as_ref() == other.words.as_ref()
+    }
+}
+
+impl<B: AsRef<[u64]>> Eq for BitVec<B> {}
+
+impl From<&[bool]> for BitVec {
+    fn from(bits: &[bool]) -> Self {
+        bits.iter().copied().collect()
+    }
+}
+
+impl From<Vec<bool>> for BitVec {
+    fn from(bits: Vec<bool>) -> Self {
+        bits.into_iter().collect()
+    }
+}
+
+impl FromIterator<bool> for BitVec {
+    fn from_iter<I: IntoIterator<Item = bool>>(iter: I) -> Self {
+        let mut bit_vec = Self::new();
+        bit_vec.extend(iter);
+        bit_vec
+    }
+}
+
+impl Extend<bool> for BitVec {
+    fn extend<I: IntoIterator<Item = bool>>(&mut self, iter: I) {
+        for value in iter {
+            self.push(value);
+        }
+    }
+}
+
+impl CopyType for BitVec {
+    type Copy = Deep;
+}
+
+impl TypeHash for BitVec {
+    fn type_hash(hasher: &mut impl core::hash::Hasher) {
+        "BitVec".hash(hasher);
+    }
+}
+
+impl ReprHash for BitVec {
+    fn repr_hash(hasher: &mut impl core::hash::Hasher, offset_of: &mut usize) {
+        *offset_of = 0;
+        u64::repr_hash(hasher, offset_of);
+    }
+}
+
+impl SerializeInner for BitVec {
+    const IS_ZERO_COPY: bool = false;
+    const ZERO_COPY_MISMATCH: bool = false;
+    const MAX_FIELD_ALIGN: usize = <Vec<u64>>::MAX_FIELD_ALIGN;
+
+    fn _serialize_inner(&self, backend: &mut impl WriteWithNames) -> ser::Result<()> {
+        backend.write("len", &self.len)?;
+        backend.write("words", &self.words)
+    }
+}
+
+impl DeserializeInner for BitVec {
+    type DeserType<'a> = BitVec<&'a [u64]>;
+
+    fn _deserialize_full_inner(backend: &mut impl ReadWithPos) -> deser::Result<Self> {
+        let len = usize::_deserialize_full_inner(backend)?;
+        let words = Vec::<u64>::_deserialize_full_inner(backend)?;
+        BitVec::from_words(&words, len)?;
+        Ok(Self { words, len })
+    }
+
+    fn _deserialize_eps_inner<'a>(
+        backend: &mut SliceWithPos<'a>,
+    ) -> deser::Result<Self::DeserType<'a>> {
+        let len = usize::_deserialize_full_inner(backend)?;
+        let words = Vec::<u64>::_deserialize_eps_inner(backend)?;
+        BitVec::from_words(words, len)
+    }
+}
@@ -0,0 +1,355 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ * SPDX-FileCopyrightText: 2023 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+/*!
+
+Structural compatibility checks using an embedded schema.
+
+Normally, deserialization requires that the [type hash](crate::traits::TypeHash)
+and the [representation hash](crate::traits::ReprHash) of the deserialized type
+are equal to those of the serialized type. This makes it impossible for a
+producer to add fields to a structure without breaking all consumers.
+
+Data serialized with
+[`Serialize::serialize_with_embedded_schema`](crate::ser::Serialize::serialize_with_embedded_schema)
+(or [`Serialize::store_with_embedded_schema`](crate::ser::Serialize::store_with_embedded_schema))
+is followed by a description of the [shape](crate::traits::TypeShape) of the
+serialized type, and by the position of each structure field. Such data can be
+read using the usual deserialization methods, as the embedded schema is simply
+ignored, but it can also be read using
+[`Deserialize::deserialize_full_compatible`]
+or [`Deserialize::deserialize_eps_compatible`],
+which accept data whose type is a *structural supertype* of the deserialized
+type.
+
+The rules are as follows:
+
+- if the hashes in the header match, the data is accepted, whether or not
+  it contains an embedded schema;
+
+- otherwise, the data must contain an embedded schema, and the shape of the
+  deserialized type must be compatible with that of the serialized type, where:
+
+  - two deep-copy structures are compatible if they have the same name, the
+    fields of the deserialized structure are a prefix of the fields of the
+    serialized structure, with the same names in the same order, and
+    corresponding fields are compatible; extra trailing fields of the
+    serialized structure are ignored;
+
+  - all other types (zero-copy structures, enums, tuples, vectors, options,
+    primitive types, etc.) are compatible only if they have the same type
+    hash and the same representation hash: in particular, structures can
+    evolve only if they are reached from the root through fields of
+    deep-copy structures, and not, say, if they are elements of a vector.
+
+In other words, a producer can add fields at the end of deep-copy structures
+without breaking consumers reading with the compatible methods; the trailing
+fields are skipped, and all other data is read exactly as usual.
+The embedded schema is found by looking for a trailer at the end of the
+data, so the compatible methods must be passed exactly the bytes that have
+been serialized.
+
+```rust
+use epserde::prelude::*;
+
+mod v1 {
+    use epserde::prelude::*;
+    #[derive(Epserde, Debug, PartialEq)]
+    pub struct Config {
+        pub name: String,
+        pub weights: Vec<f64>,
+    }
+}
+
+mod v2 {
+    use epserde::prelude::*;
+    #[derive(Epserde, Debug, PartialEq)]
+    pub struct Config {
+        pub name: String,
+        pub weights: Vec<f64>,
+        pub tags: Vec<String>,
+    }
+}
+
+let config = v2::Config {
+    name: "default".into(),
+    weights: vec![0.5, 0.25],
+    tags: vec!["new".into()],
+};
+let mut cursor = epserde::new_aligned_cursor();
+config.serialize_with_embedded_schema(&mut cursor).unwrap();
+let bytes = cursor.into_inner();
+
+// The strict check fails...
+assert!(v1::Config::deserialize_full(&mut std::io::Cursor::new(&bytes)).is_err());
+// ...but the structural check accepts the data
+let old = v1::Config::deserialize_eps_compatible(&bytes).unwrap();
+assert_eq!(old.name, "default");
+assert_eq!(old.weights, [0.5, 0.25]);
+```
+
+*/
+
+use crate::deser::{self, read_header, Deserialize, ReadWithPos, SliceWithPos};
+use crate::ser::{self, Schema, WriteWithPos};
+use crate::traits::*;
+
+#[cfg(all(feature = "alloc", not(feature = "std")))]
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+
+/// Magic cookie closing data with an embedded schema.
+pub const SCHEMA_MAGIC: u64 = u64::from_ne_bytes(*b"epschema");
+
+/// The maximum nesting depth of the structures described by an embedded
+/// schema.
+///
+/// The schema is read recursively, so its depth is bounded to avoid
+/// overflowing the stack on malicious data.
+pub const MAX_SCHEMA_DEPTH: usize = 128;
+
+/// A [shape](TypeShape) flattened in preorder: each node is represented by
+/// the name of the field containing it, the name of the structure (empty for
+/// leaves), the hash (zero for structures), and the number of fields.
+type FlatShape = Vec<(String, String, u64, usize)>;
+
+/// The embedded schema: the flattened type and representation shapes, and
+/// the path, offset, and size of the data of the root and of all fields
+/// reachable from the root through structure fields.
+type EmbeddedSchema = (FlatShape, FlatShape, Vec<(String, usize, usize)>);
+
+fn flatten(field: &str, shape: &TypeShape, flat: &mut FlatShape) {
+    match shape {
+        TypeShape::Leaf(hash) => flat.push((field.to_string(), String::new(), *hash, 0)),
+        TypeShape::Struct { name, fields } => {
+            flat.push((field.to_string(), name.clone(), 0, fields.len()));
+            for (field, shape) in fields {
+                flatten(field, shape, flat);
+            }
+        }
+    }
+}
+
+/// Rebuild a shape flattened by [`flatten`] whose root has the given depth.
+fn unflatten(
+    flat: &mut impl Iterator<Item = (String, String, u64, usize)>,
+    depth: usize,
+) -> deser::Result<(String, TypeShape)> {
+    let (field, name, hash, len) = flat.next().ok_or(deser::Error::ReadError)?;
+    if name.is_empty() {
+        return Ok((field, TypeShape::Leaf(hash)));
+    }
+    if depth >= MAX_SCHEMA_DEPTH {
+        return Err(deser::Error::SchemaTooDeep {
+            max_depth: MAX_SCHEMA_DEPTH,
+        });
+    }
+    let fields = (0..len)
+        .map(|_| unflatten(flat, depth + 1))
+        .collect::<deser::Result<Vec<_>>>()?;
+    Ok((field, TypeShape::Struct { name, fields }))
+}
+
+/// Add to `paths` the paths of all structure fields reachable from `path`.
+fn struct_paths(path: String, shape: &TypeShape, paths: &mut Vec<String>) {
+    if let TypeShape::Struct { fields, .. } = shape {
+        for (field, shape) in fields {
+            struct_paths(format!("{}.{}", path, field), shape, paths);
+        }
+    }
+    paths.push(path);
+}
+
+/// Write after the data whose [schema](Schema) is given the embedded schema
+/// of `T`, followed by its starting position and by [`SCHEMA_MAGIC`].
+pub(crate) fn write_embedded_schema<T: TypeHash + ReprHash + ?Sized>(
+    backend: &mut impl WriteWithPos,
+    schema: &Schema,
+) -> ser::Result<()> {
+    let type_shape = T::type_shape();
+    let mut flat_type_shape = Vec::new();
+    flatten("ROOT", &type_shape, &mut flat_type_shape);
+    let mut flat_repr_shape = Vec::new();
+    flatten("ROOT", &T::repr_shape(), &mut flat_repr_shape);
+
+    let mut paths = Vec::new();
+    struct_paths("ROOT".to_string(), &type_shape, &mut paths);
+    paths.sort_unstable();
+    let rows = schema
+        .0
+        .iter()
+        .filter(|row| paths.binary_search(&row.field).is_ok())
+        .map(|row| (row.field.clone(), row.offset, row.size))
+        .collect::<Vec<_>>();
+
+    let start = backend.pos() as u64;
+    let embedded_schema: EmbeddedSchema = (flat_type_shape, flat_repr_shape, rows);
+    ser::Serialize::serialize(&embedded_schema, backend)?;
+    backend.write_all(&start.to_ne_bytes())?;
+    backend.write_all(&SCHEMA_MAGIC.to_ne_bytes())?;
+    backend.flush()
+}
+
+/// Return the embedded schema at the end of `data` and its starting position,
+/// or `None` if `data` does not end with [`SCHEMA_MAGIC`].
+fn read_embedded_schema(data: &[u8]) -> deser::Result<Option<(EmbeddedSchema, usize)>> {
+    let Some(trailer_start) = data.len().checked_sub(16) else {
+        return Ok(None);
+    };
+    let Some((start, magic)) = data
+        .get(trailer_start..)
+        .and_then(|trailer| trailer.split_first_chunk::<8>())
+        .and_then(|(start, magic)| Some((*start, *magic.first_chunk::<8>()?)))
+    else {
+        return Ok(None);
+    };
+    if u64::from_ne_bytes(magic) != SCHEMA_MAGIC {
+        return Ok(None);
+    }
+    let start = u64::from_ne_bytes(start);
+    let start = usize::try_from(start)
+        .ok()
+        .filter(|&start| start <= trailer_start)
+        .ok_or(deser::Error::ReadError)?;
+    let mut backend = SliceWithPos::new(
+        data.get(start..trailer_start)
+            .ok_or(deser::Error::ReadError)?,
+    );
+    Ok(Some((
+        EmbeddedSchema::deserialize_full(&mut backend)?,
+        start,
+    )))
+}
+
+/// Check that `shape` is compatible with `ser_shape`, the shape of the
+/// serialized data at `path`, adding to `skips` the ranges of data of extra
+/// trailing fields.
+fn check_shape(
+    path: &str,
+    shape: &TypeShape,
+    ser_shape: &TypeShape,
+    rows: &[(String, usize, usize)],
+    skips: &mut Vec<(usize, usize)>,
+) -> deser::Result<()> {
+    let incompatible = |reason: &str| {
+        Err(deser::Error::IncompatibleSchema {
+            field: path.to_string(),
+            reason: reason.to_string(),
+        })
+    };
+    match (shape, ser_shape) {
+        (TypeShape::Leaf(hash), TypeShape::Leaf(ser_hash)) => {
+            if hash != ser_hash {
+                return incompatible("the types are different");
+            }
+        }
+        (
+            TypeShape::Struct { name, fields },
+            TypeShape::Struct {
+                name: ser_name,
+                fields: ser_fields,
+            },
+        ) => {
+            if name != ser_name {
+                return incompatible(&format!(
+                    "the structure {} has been renamed {}",
+                    name, ser_name
+                ));
+            }
+            if fields.len() > ser_fields.len() {
+                return incompatible(&format!(
+                    "the structure {} has {} fields, but the serialized one has only {}",
+                    name,
+                    fields.len(),
+                    ser_fields.len()
+                ));
+            }
+            for ((field, shape), (ser_field, ser_shape)) in fields.iter().zip(ser_fields) {
+                if field != ser_field {
+                    return incompatible(&format!(
+                        "the field {} of the structure {} is named {} in the serialized data",
+                        field, name, ser_field
+                    ));
+                }
+                check_shape(
+                    &format!("{}.{}", path, field),
+                    shape,
+                    ser_shape,
+                    rows,
+                    skips,
+                )?;
+            }
+            if let Some((extra_field, _)) = ser_fields.get(fields.len()) {
+                let row = |path: &str| {
+                    rows.iter()
+                        .find(|row| row.0 == path)
+                        .ok_or(deser::Error::ReadError)
+                };
+                let start = row(&format!("{}.{}", path, extra_field))?.1;
+                let (_, offset, size) = row(path)?;
+                skips.push((start, offset + size));
+            }
+        }
+        (TypeShape::Leaf(_), TypeShape::Struct { name, .. }) => {
+            return incompatible(&format!("the serialized type is the structure {}", name));
+        }
+        (TypeShape::Struct { name, .. }, TypeShape::Leaf(_)) => {
+            return incompatible(&format!(
+                "the serialized type is not the structure {}",
+                name
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Check the header of `data` against `T` using structural compatibility
+/// rules, returning a backend positioned after the header that
+/// skips extra fields.
+pub(crate) fn compatible_backend<T: Deserialize>(data: &[u8]) -> deser::Result<SliceWithPos<'_>> {
+    let mut backend = SliceWithPos::new(data);
+    let header = read_header(&mut backend)?;
+    let usize_size = backend.usize_size();
+    let error = match header.check::<T>() {
+        Ok(()) => return Ok(backend),
+        Err(error) => error,
+    };
+    let Some(((flat_type_shape, flat_repr_shape, rows), start)) = read_embedded_schema(data)?
+    else {
+        return Err(error);
+    };
+
+    let ser_type_shape = unflatten(&mut flat_type_shape.into_iter(), 0)?.1;
+    let ser_repr_shape = unflatten(&mut flat_repr_shape.into_iter(), 0)?.1;
+    let mut skips = Vec::new();
+    check_shape("ROOT", &T::type_shape(), &ser_type_shape, &rows, &mut skips)?;
+    check_shape(
+        "ROOT",
+        &T::repr_shape(),
+        &ser_repr_shape,
+        &rows,
+        &mut Vec::new(),
+    )?;
+    skips.sort_unstable();
+    if skips
+        .iter()
+        .any(|&(skip_start, skip_end)| skip_start > skip_end || skip_end > start)
+    {
+        return Err(deser::Error::ReadError);
+    }
+
+    // Restart on the data only
+    let pos = backend.pos();
+    let mut backend = SliceWithPos::new(data.get(..start).ok_or(deser::Error::ReadError)?);
+    backend.set_usize_size(usize_size);
+    backend.skip(pos)?;
+    backend.set_skips(skips);
+    Ok(backend)
+}
@@ -0,0 +1,215 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ * SPDX-FileCopyrightText: 2023 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+/*!
+
+Compression of individual fields of deep-copy structures.
+
+Large fields that are rarely accessed (e.g., debugging information, or
+metadata used only when building a structure) can be compressed by
+annotating them with `#[epserde(compress = "C")]`, where `C` is a type
+implementing [`Codec`]. Only the annotated fields are compressed: all other
+fields are serialized and deserialized as usual.
+
+A compressed field is serialized normally into a buffer, which is then
+compressed and written as its length before compression followed by a
+`Vec<u8>` containing the compressed data. On deserialization, the data is
+decompressed into a heap-allocated buffer, from which the field is fully
+deserialized: thus, a compressed field is owned even in an ε-copy
+deserialized structure, and its type is not replaced with its
+[deserialization type](crate::deser::DeserializeInner::DeserType). For
+the same reason, a type parameter used as the type of a compressed field
+cannot be used for the type of other fields.
+
+The name of the codec contributes to the [type hash](crate::traits::TypeHash)
+of the structure, so the same structure compressed with different codecs, or
+uncompressed, has different type hashes.
+
+A codec can also be used to compress the whole payload of a structure for
+full-copy use cases (e.g., archival or network transfer) using
+[`Serialize::serialize_compressed`](crate::ser::Serialize::serialize_compressed)
+and [`Deserialize::deserialize_full_compressed`](crate::deser::Deserialize::deserialize_full_compressed).
+The header is left uncompressed and records the name of the codec, so that
+the type of the data is checked before decompressing it. Compression is not
+streaming: the whole payload is serialized into a buffer and passed to
+[`Codec::compress`] as a single block, and on deserialization it is read
+and decompressed in memory. The compressed block is stored with its length
+before compression, so the data is not a zstd or lz4 frame, even when the
+codec is built on top of such a library.
+
+ε-serde does not depend on any compression library, and it does not provide
+any codec; a codec can be implemented with a few lines of code on top of the
+library of choice, such as, in the case of [zstd](https://crates.io/crates/zstd):
+
+```ignore
+use epserde::prelude::*;
+use epserde::compress::Codec;
+
+struct Zstd;
+
+impl Codec for Zstd {
+    const NAME: &'static str = "zstd";
+
+    fn compress(data: &[u8]) -> ser::Result<Vec<u8>> {
+        zstd::bulk::compress(data, 3).map_err(|_| ser::Error::WriteError)
+    }
+
+    fn decompress(data: &[u8], buf: &mut [u8]) -> deser::Result<()> {
+        match zstd::bulk::decompress_to_buffer(data, buf) {
+            Ok(len) if len == buf.len() => Ok(()),
+            _ => Err(deser::Error::ReadError),
+        }
+    }
+}
+
+#[derive(Epserde)]
+struct Index<O> {
+    offsets: O,
+    #[epserde(compress = "Zstd")]
+    names: Vec<String>,
+}
+```
+
+*/
+
+use crate::deser::{self, DeserializeInner, ReadWithPos, ReaderWithPos};
+use crate::ser::{self, SerializeInner, WriteWithNames, WriterWithPos};
+use crate::traits::*;
+use core::hash::{Hash, Hasher};
+use core::marker::PhantomData;
+
+/// The default maximum ratio between the length of decompressed data and
+/// the length of the compressed data (see [`Codec::max_decompressed_len`]).
+pub const DEFAULT_MAX_RATIO: usize = 1 << 16;
+
+/// A compression codec for fields annotated with
+/// `#[epserde(compress = "...")]`.
+pub trait Codec {
+    /// The name of the codec, which contributes to the
+    /// [type hash](TypeHash) of structures using it.
+    const NAME: &'static str;
+
+    /// Compress `data`.
+    fn compress(data: &[u8]) -> ser::Result<Vec<u8>>;
+
+    /// Decompress `data` into `buf`, whose length is exactly the length
+    /// of the data before compression.
+    fn decompress(data: &[u8], buf: &mut [u8]) -> deser::Result<()>;
+
+    /// Return the maximum length of the decompression of `data`.
+    ///
+    /// The length of the decompressed buffer is read from the serialized
+    /// data before decompressing it, and this bound avoids allocating
+    /// huge buffers if such length is corrupted; data exceeding the bound
+    /// cannot be serialized. The default implementation multiplies the
+    /// length of `data` by [`DEFAULT_MAX_RATIO`]: codecs that can achieve
+    /// larger ratios, or that store the decompressed length in their
+    /// format, should override this method.
+    fn max_decompressed_len(data: &[u8]) -> usize {
+        data.len().saturating_mul(DEFAULT_MAX_RATIO)
+    }
+}
+
+/// A value of type `V` that will be compressed with the codec `C`.
+pub(crate) struct Compressed<'a, C, V>(&'a V, PhantomData<C>);
+
+impl<'a, C, V> Compressed<'a, C, V> {
+    pub(crate) fn new(value: &'a V) -> Self {
+        Self(value, PhantomData)
+    }
+}
+
+impl<C: Codec, V: SerializeInner> SerializeInner for Compressed<'_, C, V> {
+    const IS_ZERO_COPY: bool = false;
+    const ZERO_COPY_MISMATCH: bool = false;
+    const MAX_FIELD_ALIGN: usize = 1;
+
+    fn _serialize_inner(&self, backend: &mut impl WriteWithNames) -> ser::Result<()> {
+        let mut data = Vec::new();
+        let mut writer = if backend.is_portable() {
+            WriterWithPos::new_portable(&mut data)
+        } else {
+            WriterWithPos::new(&mut data)
+        };
+        writer.write("data", self.0)?;
+        let compressed = C::compress(&data)?;
+        let max_len = C::max_decompressed_len(&compressed);
+        if data.len() > max_len {
+            return Err(ser::Error::CompressionRatio {
+                len: data.len(),
+                max_len,
+            });
+        }
+        backend.write("len", &data.len())?;
+        backend.write("data", &compressed)
+    }
+}
+
+/// Serialize the field `value` compressed with the codec `C`.
+///
+/// This function is used by the code generated for fields annotated with
+/// `#[epserde(compress = "...")]`.
+pub fn serialize_compressed<C: Codec, V: SerializeInner>(
+    backend: &mut impl WriteWithNames,
+    field_name: &str,
+    value: &V,
+) -> ser::Result<()> {
+    backend.write(field_name, &Compressed::<C, V>::new(value))
+}
+
+/// Fully deserialize a field compressed with the codec `C`.
+///
+/// This function is used by the code generated for fields annotated with
+/// `#[epserde(compress = "...")]`, both for full and for ε-copy
+/// deserialization.
+///
+/// If the length of the decompressed data is larger than
+/// [`Codec::max_decompressed_len`], [`Error::InvalidValue`](deser::Error::InvalidValue)
+/// is returned.
+pub fn deserialize_compressed<C: Codec, V: DeserializeInner>(
+    backend: &mut impl ReadWithPos,
+) -> deser::Result<V> {
+    let len = usize::_deserialize_full_inner(backend)?;
+    let compressed = Vec::<u8>::_deserialize_full_inner(backend)?;
+    if len > C::max_decompressed_len(&compressed) {
+        return Err(deser::Error::InvalidValue("Compressed"));
+    }
+    let mut data = Vec::new();
+    data.try_reserve_exact(len)
+        .map_err(|_| deser::Error::ReadError)?;
+    data.resize(len, 0);
+    C::decompress(&compressed, &mut data)?;
+
+    let mut slice = &data[..];
+    let mut reader = ReaderWithPos::new(&mut slice);
+    reader.set_usize_size(backend.usize_size());
+    reader.set_swap_bytes(backend.swap_bytes());
+    V::_deserialize_full_inner(&mut reader)
+}
+
+/// Hash the type of a field of type `V` compressed with the codec `C`.
+pub fn compressed_type_hash<C: Codec, V: TypeHash>(hasher: &mut impl Hasher) {
+    "Compressed".hash(hasher);
+    C::NAME.hash(hasher);
+    V::type_hash(hasher);
+}
+
+/// Return the [shape](TypeShape) of the type of a field of type `V`
+/// compressed with the codec `C`.
+///
+/// Compressed fields are leaves, even if `V` is a deep-copy structure.
+pub fn compressed_type_shape<C: Codec, V: TypeHash>() -> TypeShape {
+    let mut hasher = xxhash_rust::xxh3::Xxh3::new();
+    compressed_type_hash::<C, V>(&mut hasher);
+    TypeShape::Leaf(hasher.finish())
+}
+
+/// Return the [shape](TypeShape) of the representation of a field of
+/// type `V` compressed with any codec.
+pub fn compressed_repr_shape<V: ReprHash>() -> TypeShape {
+    TypeShape::Leaf(repr_hash_of::<V>())
+}
@@ -0,0 +1,137 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ * SPDX-FileCopyrightText: 2023 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+/*!
+
+Slices of zero-copy types compared by content.
+
+*/
+
+use crate::traits::*;
+use core::cmp::Ordering;
+use core::hash::{Hash, Hasher};
+
+/// A slice of a zero-copy type implementing [`Eq`], [`Hash`], and [`Ord`]
+/// by comparing the bytes of its content.
+///
+/// Slices returned by ε-copy deserialization (e.g., from a
+/// [memory-mapped](crate::deser::Deserialize::mmap) file) can be wrapped
+/// in a `ByteView` and used as keys of hash maps or ordered maps without
+/// copying them, even if their elements do not implement the traits above
+/// (e.g., floating-point numbers, or zero-copy structures).
+/// Comparisons and hashing use a single `memcmp` or [`Hasher::write`] on the
+/// whole content.
+///
+/// Note that the semantics is bitwise: for example, `0.0` and `-0.0` are
+/// different, whereas NaNs with the same bit pattern are equal; moreover,
+/// the [order](Ord) is the lexicographical order of the bytes, which, for
+/// example, is not the numerical order of little-endian integers.
+///
+/// ```rust
+/// use epserde::prelude::*;
+/// use epserde::deser::ByteView;
+/// use std::collections::HashMap;
+///
+/// let mut cursor = epserde::new_aligned_cursor();
+/// vec![vec![0.5_f64, 1.0], vec![2.0]].serialize(&mut cursor).unwrap();
+/// let bytes = cursor.into_inner();
+/// let keys = <Vec<Vec<f64>>>::deserialize_eps(&bytes).unwrap();
+///
+/// let mut map = HashMap::new();
+/// for (i, &key) in keys.iter().enumerate() {
+///     map.insert(ByteView::new(key), i);
+/// }
+/// assert_eq!(map[&ByteView::new(&[2.0][..])], 1);
+/// ```
+#[derive(Debug)]
+pub struct ByteView<'a, T>(&'a [T]);
+
+impl<'a, T: ZeroCopy + bytemuck::NoUninit> ByteView<'a, T> {
+    /// Wrap a slice whose elements have no uninitialized bytes.
+    ///
+    /// For slices of other zero-copy types (e.g., structures containing
+    /// padding), use [`ByteView::new_unchecked`].
+    pub fn new(slice: &'a [T]) -> Self {
+        Self(slice)
+    }
+}
+
+impl<'a, T: ZeroCopy> ByteView<'a, T> {
+    /// Wrap a slice of a zero-copy type.
+    ///
+    /// # Safety
+    ///
+    /// All bytes of the content of the slice must be initialized. This
+    /// is true for slices returned by ε-copy deserialization, as the
+    /// padding of zero-copy types is serialized, but it might not be true for
+    /// other slices of types containing padding.
+    ///
+    /// Note that padding bytes take part in comparisons and hashing, and
+    /// that their content is not specified, so equal values containing
+    /// padding might have different views.
+    pub unsafe fn new_unchecked(slice: &'a [T]) -> Self {
+        Self(slice)
+    }
+
+    /// Return the wrapped slice.
+    pub fn as_slice(&self) -> &'a [T] {
+        self.0
+    }
+
+    /// Return the bytes of the content of the slice.
+    pub fn as_bytes(&self) -> &'a [u8] {
+        // SAFETY: the content is initialized by the contract of the constructors.
+        unsafe {
+            core::slice::from_raw_parts(
+                self.0.as_ptr() as *const u8,
+                core::mem::size_of_val(self.0),
+            )
+        }
+    }
+}
+
+impl<T> Clone for ByteView<'_, T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for ByteView<'_, T> {}
+
+impl<T> core::ops::Deref for ByteView<'_, T> {
+    type Target = [T];
+
+    fn deref(&self) -> &Self::Target {
+        self.0
+    }
+}
+
+impl<T: ZeroCopy> PartialEq for ByteView<'_, T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_bytes() == other.as_bytes()
+    }
+}
+
+impl<T: ZeroCopy> Eq for ByteView<'_, T> {}
+
+impl<T: ZeroCopy> Hash for ByteView<'_, T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.as_bytes().hash(state);
+    }
+}
+
+impl<T: ZeroCopy> PartialOrd for ByteView<'_, T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T: ZeroCopy> Ord for ByteView<'_, T> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.as_bytes().cmp(other.as_bytes())
+    }
+}
@@ -0,0 +1,116 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ * SPDX-FileCopyrightText: 2023 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+/*!
+
+Deserialization contexts.
+
+A deserialization [`Context`] is a type-erased value attached to the backend
+of a deserialization, so that custom implementations of [`DeserializeInner`]
+can access external resources (e.g., dictionaries, allocators, or registries)
+without resorting to global state. The context is set by
+[`Deserialize::deserialize_full_with_context`] or
+[`Deserialize::deserialize_eps_with_context`], and it is available to all
+nested deserializations through [`ReadWithPos::context`], or, more
+conveniently, through [`ReadWithPos::context_as`], which downcasts it to a
+concrete type. Implementations that do not use the context need not be
+aware of it, so implementations generated by the
+[derive macro](https://docs.rs/epserde-derive/latest/epserde_derive/derive.Epserde.html)
+forward the context to their fields.
+
+The field attribute `#[epserde(ignore_context)]` of the derive macro
+deserializes a field without context (see [`without_context`]).
+
+```rust
+use epserde::prelude::*;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// Names resolved through a registry passed as a context.
+#[derive(Debug, PartialEq)]
+struct Symbol(String);
+
+impl CopyType for Symbol {
+    type Copy = Deep;
+}
+
+impl TypeHash for Symbol {
+    fn type_hash(hasher: &mut impl core::hash::Hasher) {
+        use core::hash::Hash;
+        "Symbol".hash(hasher);
+    }
+}
+
+impl ReprHash for Symbol {
+    fn repr_hash(_hasher: &mut impl core::hash::Hasher, _offset_of: &mut usize) {}
+}
+
+impl SerializeInner for Symbol {
+    const IS_ZERO_COPY: bool = false;
+    const ZERO_COPY_MISMATCH: bool = false;
+    const MAX_FIELD_ALIGN: usize = String::MAX_FIELD_ALIGN;
+    fn _serialize_inner(&self, backend: &mut impl ser::WriteWithNames) -> ser::Result<()> {
+        backend.write("name", &self.0)
+    }
+}
+
+impl DeserializeInner for Symbol {
+    type DeserType<'a> = Symbol;
+
+    fn _deserialize_full_inner(backend: &mut impl deser::ReadWithPos) -> deser::Result<Self> {
+        let name = String::_deserialize_full_inner(backend)?;
+        // Use the registry to canonicalize the name, if available
+        let name = match backend.context_as::<HashMap<String, String>>() {
+            Some(registry) => registry.get(&name).cloned().unwrap_or(name),
+            None => name,
+        };
+        Ok(Symbol(name))
+    }
+
+    fn _deserialize_eps_inner<'a>(
+        backend: &mut deser::SliceWithPos<'a>,
+    ) -> deser::Result<Self::DeserType<'a>> {
+        Self::_deserialize_full_inner(backend)
+    }
+}
+
+let mut cursor = epserde::new_aligned_cursor();
+vec![Symbol("x".to_string())].serialize(&mut cursor)?;
+let buf = cursor.into_inner();
+
+let registry = HashMap::from([("x".to_string(), "ex".to_string())]);
+let symbols = Vec::<Symbol>::deserialize_eps_with_context(&buf, Arc::new(registry))?;
+assert_eq!(symbols, vec![Symbol("ex".to_string())]);
+
+let symbols = Vec::<Symbol>::deserialize_eps(&buf)?;
+assert_eq!(symbols, vec![Symbol("x".to_string())]);
+# Ok::<(), Box<dyn std::error::Error>>(())
+```
+
+*/
+
+use super::*;
+
+/// A type-erased deserialization context.
+///
+/// See the [module documentation](self) for more information.
+pub type Context = dyn core::any::Any + Send + Sync;
+
+/// Run the given deserialization function after removing the
+/// [context](Context) from the backend, which is restored afterwards.
+///
+/// This function is used by the field attribute `#[epserde(ignore_context)]`
+/// of the [derive macro](https://docs.rs/epserde-derive/latest/epserde_derive/derive.Epserde.html).
+pub fn without_context<B: ReadWithPos, T>(
+    backend: &mut B,
+    f: impl FnOnce(&mut B) -> Result<T>,
+) -> Result<T> {
+    let context = backend.replace_context(None);
+    let result = f(backend);
+    backend.replace_context(context);
+    result
+}
@@ -0,0 +1,130 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ * SPDX-FileCopyrightText: 2023 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+/*!
+
+Copy-on-write access to ε-copy deserialized structures.
+
+*/
+
+use super::*;
+use std::path::Path;
+
+enum CowState<T: DeserializeInner + 'static> {
+    Borrowed(MemCase<DeserType<'static, T>>),
+    Owned(T),
+}
+
+/// A structure that is ε-copy deserialized until it is mutated.
+///
+/// A `CowCase` starts as a [`MemCase`] containing the ε-copy deserialized
+/// structure, so reads through [`CowCase::as_borrowed`] hit the loaded or
+/// mapped data. The first call to [`CowCase::to_mut`] fully deserializes
+/// the structure from the memory of the [`MemCase`], which is then
+/// released, and from that point on the structure is owned and mutable. In
+/// this way, a snapshot on disk can be treated as a mutable structure that
+/// is materialized lazily.
+///
+/// ```rust
+/// use epserde::prelude::*;
+/// use epserde::deser::CowCase;
+///
+/// let tmp_file = std::env::temp_dir().join("cow_case_doc.eps");
+/// vec![0_u32, 1, 2].store(&tmp_file)?;
+///
+/// let mut data = CowCase::<Vec<u32>>::load_mem(&tmp_file)?;
+/// assert_eq!(data.as_borrowed().unwrap(), &[0, 1, 2]);
+///
+/// data.to_mut()?.push(3);
+/// assert!(data.is_owned());
+/// assert_eq!(data.as_owned().unwrap(), &vec![0, 1, 2, 3]);
+/// # std::fs::remove_file(&tmp_file)?;
+/// # Ok::<(), anyhow::Error>(())
+/// ```
+pub struct CowCase<T: DeserializeInner + 'static>(CowState<T>);
+
+impl<T: Deserialize + 'static> CowCase<T> {
+    /// Create a `CowCase` by [loading a file in memory](Deserialize::load_mem).
+    pub fn load_mem(path: impl AsRef<Path>) -> Result<Self> {
+        Ok(Self(CowState::Borrowed(T::load_mem(path)?)))
+    }
+
+    /// Create a `CowCase` by [loading a file in `mmap()`-allocated
+    /// memory](Deserialize::load_mmap).
+    #[cfg(feature = "mmap-rs")]
+    pub fn load_mmap(path: impl AsRef<Path>, flags: Flags) -> Result<Self> {
+        Ok(Self(CowState::Borrowed(T::load_mmap(path, flags)?)))
+    }
+
+    /// Create a `CowCase` by [memory mapping a file](Deserialize::mmap).
+    #[cfg(feature = "mmap-rs")]
+    pub fn mmap(path: impl AsRef<Path>, flags: Flags) -> Result<Self> {
+        Ok(Self(CowState::Borrowed(T::mmap(path, flags)?)))
+    }
+
+    /// Create a `CowCase` containing an owned structure.
+    pub fn from_owned(value: T) -> Self {
+        Self(CowState::Owned(value))
+    }
+
+    /// Return whether the structure has been materialized.
+    pub fn is_owned(&self) -> bool {
+        matches!(self.0, CowState::Owned(_))
+    }
+
+    /// Return the ε-copy deserialized structure, or `None` if the structure
+    /// has been materialized.
+    pub fn as_borrowed(&self) -> Option<&DeserType<'_, T>> {
+        match &self.0 {
+            // SAFETY: the memory backing the structure lives as long as self.
+            CowState::Borrowed(case) => Some(unsafe {
+                &*(&case.0 as *const DeserType<'static, T> as *const DeserType<'_, T>)
+            }),
+            CowState::Owned(_) => None,
+        }
+    }
+
+    /// Return the owned structure, or `None` if the structure has not been
+    /// materialized yet.
+    pub fn as_owned(&self) -> Option<&T> {
+        match &self.0 {
+            CowState::Borrowed(_) => None,
+            CowState::Owned(value) => Some(value),
+        }
+    }
+
+    /// Return a mutable reference to the structure, materializing it
+    /// if necessary.
+    ///
+    /// Materialization fully deserializes the structure from the memory
+    /// of the underlying [`MemCase`], which is then released.
+    pub fn to_mut(&mut self) -> Result<&mut T> {
+        if let CowState::Borrowed(case) = &self.0 {
+            self.0 = CowState::Owned(materialize(case)?);
+        }
+        match &mut self.0 {
+            CowState::Owned(value) => Ok(value),
+            // Materialization above replaced the state
+            CowState::Borrowed(_) => Err(Error::ReadError),
+        }
+    }
+
+    /// Return the structure, materializing it if necessary.
+    pub fn into_owned(self) -> Result<T> {
+        match self.0 {
+            CowState::Borrowed(case) => materialize(&case),
+            CowState::Owned(value) => Ok(value),
+        }
+    }
+}
+
+/// Fully deserialize a structure from the memory of a [`MemCase`].
+fn materialize<T: Deserialize + 'static>(case: &MemCase<DeserType<'static, T>>) -> Result<T> {
+    // The MemCase of a CowCase always has a backend
+    let mut bytes = case.1.as_ref().ok_or(Error::ReadError)?;
+    T::deserialize_full(&mut bytes)
+}
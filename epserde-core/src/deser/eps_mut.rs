@@ -0,0 +1,254 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ * SPDX-FileCopyrightText: 2023 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+/*!
+
+Mutable ε-copy deserialization.
+
+[`DeserializeEpsMut`] makes it possible to ε-copy deserialize
+vectors, boxed slices, and arrays of zero-copy types as mutable
+slices (or arrays) referring directly to the serialized data, so
+that in-place updates, such as flipping the bits of a bit vector or
+updating counters, modify the data.
+
+[`DeserializeEpsMut`] is implemented only for such types, and only when they
+are the type of the serialized data: it is not implemented for deep-copy
+tuples or structures, and the
+[derive macro](https://docs.rs/epserde-derive/latest/epserde_derive/derive.Epserde.html)
+does not implement it, so zero-copy fields of a structure cannot be modified
+in place. Data that must be updated in place can be serialized separately
+(e.g., in a different file of a [snapshot](crate::snapshot)).
+
+When the data is [memory mapped in shared mode](DeserializeEpsMut::mmap_mut),
+updates are persisted to the file:
+
+```rust
+use epserde::prelude::*;
+
+let tmp_file = std::env::temp_dir().join("eps_mut_doc.eps");
+vec![0_u64; 100].store(&tmp_file)?;
+
+let mut counters = <Vec<u64>>::mmap_mut(&tmp_file, Flags::empty())?;
+counters[42] += 1;
+counters.flush()?;
+drop(counters);
+
+assert_eq!(<Vec<u64>>::load_full(&tmp_file)?[42], 1);
+# std::fs::remove_file(&tmp_file)?;
+# Ok::<(), Box<dyn std::error::Error>>(())
+```
+
+Only the content of zero-copy data can be modified: lengths, and thus the
+structure of the data, cannot change. Note that modifications invalidate the
+[checksum](crate::ser::Serialize::serialize_with_checksum) and the
+[checksum tree](crate::merkle) of the data, if present, so modified data with
+a checksum can be loaded only skipping its verification (e.g., using
+[`Flags::SKIP_CHECKSUM`]).
+
+*/
+
+use super::*;
+#[cfg(feature = "mmap-rs")]
+use core::ops::{Deref, DerefMut};
+
+#[cfg(all(feature = "alloc", not(feature = "std")))]
+use alloc::{boxed::Box, vec::Vec};
+
+/// Mutable ε-copy deserialization of vectors, boxed slices, and arrays of
+/// zero-copy types.
+///
+/// This trait is not implemented for other types, and in particular it is
+/// not implemented by the derive macro.
+///
+/// See the [module documentation](self) for more information.
+pub trait DeserializeEpsMut: Deserialize {
+    /// The mutable ε-copy deserialization type, which refers to the
+    /// serialized data.
+    type DeserTypeMut<'a>;
+
+    /// ε-copy deserialize a structure of this type from the given backend,
+    /// returning a view through which the zero-copy data in the backend
+    /// can be modified.
+    ///
+    /// The backend is checked as in the case of
+    /// [`deserialize_eps`](Deserialize::deserialize_eps), including the
+    /// [checksum](crate::ser::Serialize::serialize_with_checksum), if present.
+    fn deserialize_eps_mut(backend: &mut [u8]) -> Result<Self::DeserTypeMut<'_>> {
+        Self::deserialize_eps(backend)?;
+        Self::deserialize_eps_mut_unverified(backend)
+    }
+
+    /// Like [`deserialize_eps_mut`](DeserializeEpsMut::deserialize_eps_mut),
+    /// but without verifying the
+    /// [checksum](crate::ser::Serialize::serialize_with_checksum), if present.
+    ///
+    /// This is the only way to access mutably data with a checksum that
+    /// has been already modified.
+    fn deserialize_eps_mut_unverified(backend: &mut [u8]) -> Result<Self::DeserTypeMut<'_>>;
+
+    /// Memory map a file in read-write mode and ε-deserialize mutably a
+    /// data structure from it, returning a [`MemCaseMut`] containing the
+    /// data structure and the memory mapping.
+    ///
+    /// The file is mapped in shared mode, so modifications are written back
+    /// to the file, unless [`Flags::COPY_ON_WRITE`] is specified, in which
+    /// case modifications are private to the mapping and they are lost when
+    /// the mapping is dropped. [`Flags::SKIP_CHECKSUM`] must be specified to
+    /// map data with a checksum that has been already modified. The remaining
+    /// flags are applied as in the case of [`mmap`](Deserialize::mmap).
+    #[cfg(feature = "mmap-rs")]
+    fn mmap_mut(
+        path: impl AsRef<Path>,
+        flags: Flags,
+    ) -> Result<MemCaseMut<Self::DeserTypeMut<'static>>> {
+        let path = path.as_ref();
+        let copy_on_write = flags.contains(Flags::COPY_ON_WRITE);
+        let file = if copy_on_write {
+            flags.open(path)?
+        } else {
+            std::fs::OpenOptions::new()
+                .read(true)
+                .write(true)
+                .open(path)
+                .map_err(Error::FileOpenError)?
+        };
+        let file_len = file.metadata()?.len() as usize;
+        // Mapping an empty region fails with platform-dependent errors
+        if file_len == 0 {
+            return Err(Error::EmptyMapping);
+        }
+        let mmap_flags = if copy_on_write {
+            flags.mmap_flags()
+        } else {
+            flags.mmap_flags() | mmap_rs::MmapFlags::SHARED
+        };
+        let mut mmap = unsafe {
+            mmap_rs::MmapOptions::new(file_len)?
+                .with_flags(mmap_flags)
+                .with_file(&file, 0)
+                .map_mut()?
+        };
+        MemStorage::advise(&mmap, flags & Flags::WILL_NEED);
+        // SAFETY: the mapped region does not move when the mapping is moved,
+        // and the MemCaseMut drops the structure before the mapping.
+        let bytes: &'static mut [u8] = unsafe { &mut *(mmap.as_mut_slice() as *mut [u8]) };
+        crate::deser::deserialize_eps_with_flags::<Self>(&mut SliceWithPos::new(bytes), flags)?;
+        let s = Self::deserialize_eps_mut_unverified(bytes)?;
+        Ok(MemCaseMut(s, mmap))
+    }
+}
+
+/// Return the offset of `ptr` within `backend`.
+fn offset_in(backend: &[u8], ptr: *const u8) -> usize {
+    ptr as usize - backend.as_ptr() as usize
+}
+
+impl<T: ZeroCopy + DeserializeInner + TypeHash + ReprHash + 'static> DeserializeEpsMut for Vec<T> {
+    type DeserTypeMut<'a> = &'a mut [T];
+
+    fn deserialize_eps_mut_unverified(backend: &mut [u8]) -> Result<Self::DeserTypeMut<'_>> {
+        let slice: &[T] = Self::deserialize_eps_unverified(backend)?;
+        let (offset, len) = (offset_in(backend, slice.as_ptr().cast()), slice.len());
+        // SAFETY: the slice is within the backend, which we borrow mutably,
+        // and it is properly aligned.
+        Ok(
+            unsafe {
+                core::slice::from_raw_parts_mut(backend.as_mut_ptr().add(offset).cast(), len)
+            },
+        )
+    }
+}
+
+impl<T: ZeroCopy + DeserializeInner + TypeHash + ReprHash + 'static> DeserializeEpsMut
+    for Box<[T]>
+{
+    type DeserTypeMut<'a> = &'a mut [T];
+
+    fn deserialize_eps_mut_unverified(backend: &mut [u8]) -> Result<Self::DeserTypeMut<'_>> {
+        let slice: &[T] = Self::deserialize_eps_unverified(backend)?;
+        let (offset, len) = (offset_in(backend, slice.as_ptr().cast()), slice.len());
+        // SAFETY: the slice is within the backend, which we borrow mutably,
+        // and it is properly aligned.
+        Ok(
+            unsafe {
+                core::slice::from_raw_parts_mut(backend.as_mut_ptr().add(offset).cast(), len)
+            },
+        )
+    }
+}
+
+impl<T: ZeroCopy + DeserializeInner + TypeHash + ReprHash + 'static, const N: usize>
+    DeserializeEpsMut for [T; N]
+{
+    type DeserTypeMut<'a> = &'a mut [T; N];
+
+    fn deserialize_eps_mut_unverified(backend: &mut [u8]) -> Result<Self::DeserTypeMut<'_>> {
+        let array: &[T; N] = Self::deserialize_eps_unverified(backend)?;
+        let offset = offset_in(backend, (array as *const [T; N]).cast());
+        // SAFETY: the array is within the backend, which we borrow mutably,
+        // and it is properly aligned.
+        Ok(unsafe { &mut *backend.as_mut_ptr().add(offset).cast() })
+    }
+}
+
+/// A wrapper keeping together a mutable structure and the read-write memory
+/// mapping it was deserialized from.
+///
+/// Instances are returned by [`DeserializeEpsMut::mmap_mut`]. Similarly to
+/// [`MemCase`], [`MemCaseMut`] implements [`Deref`], and also
+/// [`DerefMut`], to the slice or array referred to by the wrapped type. Modifications are written back to the
+/// file by the operating system at unspecified times (and anyway when the
+/// mapping is dropped), or explicitly by [`MemCaseMut::flush`].
+#[cfg(feature = "mmap-rs")]
+pub struct MemCaseMut<S>(S, mmap_rs::MmapMut);
+
+#[cfg(feature = "mmap-rs")]
+impl<S> MemCaseMut<S> {
+    /// Write back synchronously to the file the modifications made to the
+    /// structure.
+    ///
+    /// This method does nothing if the file was mapped with
+    /// [`Flags::COPY_ON_WRITE`].
+    pub fn flush(&self) -> Result<()> {
+        Ok(self.1.flush(0..self.1.len())?)
+    }
+
+    /// Return the memory mapping containing the structure.
+    pub fn mmap(&self) -> &mmap_rs::MmapMut {
+        &self.1
+    }
+}
+
+// The structure is a mutable reference with a 'static lifetime, so we
+// dereference to its target, reborrowing it with the lifetime of the
+// MemCaseMut: dereferencing to the structure would make it possible to
+// move the reference out (e.g., with core::mem::take) and use it after the
+// mapping has been dropped.
+
+#[cfg(feature = "mmap-rs")]
+impl<S: DerefMut> Deref for MemCaseMut<S> {
+    type Target = S::Target;
+    #[inline(always)]
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+#[cfg(feature = "mmap-rs")]
+impl<S: DerefMut> DerefMut for MemCaseMut<S> {
+    #[inline(always)]
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+#[cfg(feature = "mmap-rs")]
+impl<S: core::fmt::Debug> core::fmt::Debug for MemCaseMut<S> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_tuple("MemCaseMut").field(&self.0).finish()
+    }
+}
@@ -0,0 +1,336 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ * SPDX-FileCopyrightText: 2023 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+/*!
+
+Helpers for deserialization.
+
+*/
+
+use super::SliceWithPos;
+use super::{read::*, DeserializeInner};
+use crate::deser;
+use crate::traits::*;
+use core::mem::MaybeUninit;
+
+#[cfg(all(feature = "alloc", not(feature = "std")))]
+use alloc::vec::Vec;
+
+/// Read the memory representation of a sequence of zero-copy values of
+/// `size` bytes, swapping the bytes of each value using `byte_swap` if
+/// [`ReadWithPos::swap_bytes`] is true, and checking each value
+/// using `check_bytes`.
+///
+/// This function does not depend on the type of the values, so it is
+/// instantiated only once for each backend; with the feature `small-code` it
+/// is never inlined.
+#[cfg_attr(not(feature = "small-code"), inline(always))]
+#[cfg_attr(feature = "small-code", inline(never))]
+fn read_zero_bytes(
+    backend: &mut impl ReadWithPos,
+    bytes: &mut [u8],
+    size: usize,
+    byte_swap: fn(&mut [u8]) -> deser::Result<()>,
+    check_bytes: fn(&[u8]) -> deser::Result<()>,
+) -> deser::Result<()> {
+    backend.read_exact(bytes)?;
+    if backend.swap_bytes() && size != 0 {
+        for item in bytes.chunks_exact_mut(size) {
+            byte_swap(item)?;
+        }
+    }
+    check_zero_bytes(bytes, size, check_bytes)
+}
+
+/// Check the memory representation of a sequence of zero-copy values of
+/// `size` bytes using `check_bytes`.
+#[inline(always)]
+fn check_zero_bytes(
+    bytes: &[u8],
+    size: usize,
+    check_bytes: fn(&[u8]) -> deser::Result<()>,
+) -> deser::Result<()> {
+    if size != 0 {
+        for item in bytes.chunks_exact(size) {
+            check_bytes(item)?;
+        }
+    }
+    Ok(())
+}
+
+/// Check that the memory representation of the zero-copy type `T` does not
+/// depend on the pointer width, unless the data has been written on an
+/// architecture with the same pointer width.
+///
+/// Return [`deser::Error::UsizeSizeMismatch`] otherwise.
+#[inline(always)]
+fn check_width<T: MaxSizeOf>(backend: &impl ReadWithPos) -> deser::Result<()> {
+    if T::WIDTH_DEPENDENT && backend.usize_size() != core::mem::size_of::<usize>() {
+        Err(deser::Error::UsizeSizeMismatch(backend.usize_size()))
+    } else {
+        Ok(())
+    }
+}
+
+/// Return the memory representation of `len` zero-copy values of `size`
+/// bytes padded to alignment `align`, skipping it.
+///
+/// Return [`deser::Error::ReadError`] if there are not enough bytes, and
+/// [`deser::Error::AlignmentError`] if the bytes are not aligned in memory
+/// to `align` and `mem_align`. If the [validation](deser::Validation) of the
+/// backend is [`Checked`](deser::Validation::Checked), each value is checked
+/// using `check_bytes`.
+///
+/// This function does not depend on the type of the values, so it is
+/// instantiated only once; with the feature `small-code` it is never inlined.
+#[cfg_attr(not(feature = "small-code"), inline(always))]
+#[cfg_attr(feature = "small-code", inline(never))]
+fn take_zero_bytes<'a>(
+    backend: &mut SliceWithPos<'a>,
+    len: usize,
+    size: usize,
+    align: usize,
+    mem_align: usize,
+    check_bytes: fn(&[u8]) -> deser::Result<()>,
+) -> deser::Result<&'a [u8]> {
+    let bytes = len.checked_mul(size).ok_or(deser::Error::ReadError)?;
+    backend.align_to(align)?;
+    let bytes = backend.take(bytes)?;
+    // The padding alignment given by MaxSizeOf should be a multiple of the
+    // memory alignment, but we do not rely on it, as the bytes are going
+    // to be reinterpreted as values.
+    if !(bytes.as_ptr() as usize).is_multiple_of(mem_align) {
+        return Err(deser::Error::AlignmentError);
+    }
+    if backend.validation() == deser::Validation::Checked {
+        check_zero_bytes(bytes, size, check_bytes)?;
+    }
+    Ok(bytes)
+}
+
+/// Full-copy deserialize a zero-copy structure.
+///
+/// If [`ReadWithPos::swap_bytes`] is true, the bytes are swapped
+/// using [`ByteSwap::byte_swap`] before being interpreted as a `T`.
+/// The bytes are then checked using [`CheckBytes::check_bytes`].
+pub fn deserialize_full_zero<T: ZeroCopy>(backend: &mut impl ReadWithPos) -> deser::Result<T> {
+    check_width::<T>(backend)?;
+    backend.align::<T>()?;
+    let mut buf = MaybeUninit::<T>::uninit();
+    // SAFETY: read_exact guarantees that the buffer will be filled with data.
+    unsafe {
+        let slice =
+            core::slice::from_raw_parts_mut(buf.as_mut_ptr() as *mut u8, core::mem::size_of::<T>());
+        read_zero_bytes(
+            backend,
+            slice,
+            core::mem::size_of::<T>(),
+            T::byte_swap,
+            T::check_bytes,
+        )?;
+        Ok(buf.assume_init())
+    }
+}
+
+/// Full-copy deserialize a vector of zero-copy structures.
+///
+/// Note that this method uses a single [`ReadNoStd::read_exact`]
+/// call to read the entire vector directly into the spare capacity of
+/// a newly allocated vector, whose length is set only after the data
+/// has been read (and possibly byte-swapped) and checked. If the length is too large
+/// to be allocated (e.g., because the data is corrupted),
+/// [`deser::Error::ReadError`] is returned.
+pub fn deserialize_full_vec_zero<T: DeserializeInner + ZeroCopy>(
+    backend: &mut impl ReadWithPos,
+) -> deser::Result<Vec<T>> {
+    let mut res = Vec::new();
+    deserialize_full_vec_zero_into(&mut res, backend)?;
+    Ok(res)
+}
+
+/// The number of bytes of a vector of zero-copy structures read at a time by
+/// [`deserialize_full_vec_zero_into`].
+const VEC_CHUNK_SIZE: usize = 1 << 20;
+
+/// Full-copy deserialize a vector of zero-copy structures into an existing
+/// vector, reusing its buffer if its capacity is sufficient.
+///
+/// See [`deserialize_full_vec_zero`].
+pub fn deserialize_full_vec_zero_into<T: DeserializeInner + ZeroCopy>(
+    res: &mut Vec<T>,
+    backend: &mut impl ReadWithPos,
+) -> deser::Result<()> {
+    check_width::<T>(backend)?;
+    let len = usize::_deserialize_full_inner(backend)?;
+    backend.align::<T>()?;
+    res.clear();
+    res.try_reserve_exact(len)
+        .map_err(|_| deser::Error::ReadError)?;
+    let spare = res
+        .spare_capacity_mut()
+        .get_mut(..len)
+        .ok_or(deser::Error::ReadError)?;
+    let size = core::mem::size_of::<T>();
+    // We zero the memory of the elements before reading into it, as
+    // read_exact might read its buffer, and we do it in chunks, so that
+    // a malformed length makes us fail before touching a large allocation.
+    let chunk_len = VEC_CHUNK_SIZE
+        .checked_div(size)
+        .map_or(usize::MAX, |chunk_len| chunk_len.max(1));
+    for chunk in spare.chunks_mut(chunk_len) {
+        // SAFETY: we are zeroing exactly the memory of the elements of
+        // the chunk.
+        unsafe { core::ptr::write_bytes(chunk.as_mut_ptr(), 0, chunk.len()) };
+        // SAFETY: MaybeUninit<T> has the same layout of T, so we are viewing
+        // exactly the memory of the elements of the chunk as bytes, which
+        // have been initialized above.
+        let bytes = unsafe {
+            core::slice::from_raw_parts_mut(
+                chunk.as_mut_ptr() as *mut u8,
+                core::mem::size_of_val(chunk),
+            )
+        };
+        read_zero_bytes(backend, bytes, size, T::byte_swap, T::check_bytes)?;
+    }
+    // SAFETY: read_zero_bytes filled the first len elements with data
+    // whose bit patterns have been checked by T::check_bytes.
+    unsafe { res.set_len(len) };
+    Ok(())
+}
+
+/// Full-copy deserialize a vector of deep-copy structures.
+pub fn deserialize_full_vec_deep<T: DeserializeInner + DeepCopy>(
+    backend: &mut impl ReadWithPos,
+) -> deser::Result<Vec<T>> {
+    let len = usize::_deserialize_full_inner(backend)?;
+    let mut res = Vec::new();
+    res.try_reserve_exact(len)
+        .map_err(|_| deser::Error::ReadError)?;
+    for index in 0..len {
+        res.push(in_item(backend, index, |backend| {
+            T::_deserialize_full_inner(backend)
+        })?);
+    }
+    Ok(res)
+}
+
+/// Full-copy deserialize a vector of deep-copy structures into an existing
+/// vector, deserializing in place into the elements already present.
+pub fn deserialize_full_vec_deep_into<T: DeserializeInner + DeepCopy>(
+    res: &mut Vec<T>,
+    backend: &mut impl ReadWithPos,
+) -> deser::Result<()> {
+    let len = usize::_deserialize_full_inner(backend)?;
+    res.truncate(len);
+    for (index, item) in res.iter_mut().enumerate() {
+        in_item(backend, index, |backend| {
+            item._deserialize_full_into_inner(backend)
+        })?;
+    }
+    res.try_reserve_exact(len - res.len())
+        .map_err(|_| deser::Error::ReadError)?;
+    for index in res.len()..len {
+        res.push(in_item(backend, index, |backend| {
+            T::_deserialize_full_inner(backend)
+        })?);
+    }
+    Ok(())
+}
+
+/// ε-copy deserialize a reference to a zero-copy structure
+/// backed by the `data` field of `backend`.
+pub fn deserialize_eps_zero<'a, T: ZeroCopy>(
+    backend: &mut SliceWithPos<'a>,
+) -> deser::Result<&'a T> {
+    check_width::<T>(backend)?;
+    let bytes = take_zero_bytes(
+        backend,
+        1,
+        core::mem::size_of::<T>(),
+        T::max_size_of(),
+        core::mem::align_of::<T>(),
+        T::check_bytes,
+    )?;
+    // SAFETY: the bytes are enough and properly aligned.
+    Ok(unsafe { &*(bytes.as_ptr() as *const T) })
+}
+
+/// ε-copy deserialize a reference to a slice of zero-copy structures
+/// backed by the `data` field of `backend`.
+pub fn deserialize_eps_slice_zero<'a, T: ZeroCopy>(
+    backend: &mut SliceWithPos<'a>,
+) -> deser::Result<&'a [T]> {
+    check_width::<T>(backend)?;
+    let len = usize::_deserialize_full_inner(backend)?;
+    let bytes = take_zero_bytes(
+        backend,
+        len,
+        core::mem::size_of::<T>(),
+        T::max_size_of(),
+        core::mem::align_of::<T>(),
+        T::check_bytes,
+    )?;
+    // SAFETY: the bytes are enough for len elements and properly aligned.
+    Ok(unsafe { core::slice::from_raw_parts(bytes.as_ptr() as *const T, len) })
+}
+
+/// ε-copy deserialize a vector of deep-copy structures.
+///
+/// The initial capacity of the vector is bounded by the number of remaining
+/// bytes, so a corrupted length cannot cause a large allocation.
+pub fn deserialize_eps_vec_deep<'a, T: DeepCopy + DeserializeInner>(
+    backend: &mut SliceWithPos<'a>,
+) -> deser::Result<Vec<<T as DeserializeInner>::DeserType<'a>>> {
+    let len = usize::_deserialize_full_inner(backend)?;
+    let mut res = Vec::new();
+    res.try_reserve_exact(len.min(backend.data.len()))
+        .map_err(|_| deser::Error::ReadError)?;
+    for index in 0..len {
+        res.push(in_item(backend, index, |backend| {
+            T::_deserialize_eps_inner(backend)
+        })?);
+    }
+    Ok(res)
+}
+
+/// Run the given deserialization function, annotating errors with the name
+/// of the field being deserialized and its offset (see
+/// [`Error::InField`](deser::Error::InField)) if the backend
+/// [requires it](ReadWithPos::error_paths).
+///
+/// This function is used by the [derive
+/// macro](https://docs.rs/epserde-derive/latest/epserde_derive/derive.Epserde.html)
+/// for the fields of deep-copy types.
+#[inline(always)]
+pub fn in_field<B: ReadWithPos, T>(
+    backend: &mut B,
+    name: &str,
+    f: impl FnOnce(&mut B) -> deser::Result<T>,
+) -> deser::Result<T> {
+    if !backend.error_paths() {
+        return f(backend);
+    }
+    let offset = backend.pos();
+    f(backend).map_err(|error| error.in_field(name, offset))
+}
+
+/// Run the given deserialization function, annotating errors with the index
+/// of the item of a sequence being deserialized and its offset (see
+/// [`Error::InField`](deser::Error::InField)) if the backend
+/// [requires it](ReadWithPos::error_paths).
+#[inline(always)]
+pub fn in_item<B: ReadWithPos, T>(
+    backend: &mut B,
+    index: usize,
+    f: impl FnOnce(&mut B) -> deser::Result<T>,
+) -> deser::Result<T> {
+    if !backend.error_paths() {
+        return f(backend);
+    }
+    let offset = backend.pos();
+    f(backend).map_err(|error| error.in_item(index, offset))
+}
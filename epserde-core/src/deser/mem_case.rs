@@ -0,0 +1,952 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+use bitflags::bitflags;
+use core::ops::{Deref, Range};
+
+#[cfg(all(feature = "alloc", not(feature = "std")))]
+use alloc::{boxed::Box, sync::Arc, vec::Vec};
+#[cfg(feature = "std")]
+use std::sync::Arc;
+
+bitflags! {
+    /// Flags for [`map`] and [`load_mmap`].
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+    pub struct Flags: u32 {
+        /// Suggest to map a region using transparent huge pages. This flag
+        /// is only a suggestion, and it is ignored if the kernel does not
+        /// support transparent huge pages. It is mainly useful to support
+        /// `madvise()`-based huge pages on Linux. Note that at the time
+        /// of this writing Linux does not support transparent huge pages
+        /// in file-based memory mappings.
+        const TRANSPARENT_HUGE_PAGES = 1 << 0;
+        /// Suggest that the mapped region will be accessed sequentially.
+        ///
+        /// This flag is only a suggestion, and it is ignored if the kernel does
+        /// not support it. It is mainly useful to support `madvise()` on Linux.
+        const SEQUENTIAL = 1 << 1;
+        /// Suggest that the mapped region will be accessed randomly.
+        ///
+        /// This flag is only a suggestion, and it is ignored if the kernel does
+        /// not support it. It is mainly useful to support `madvise()` on Linux.
+        const RANDOM_ACCESS = 1 << 2;
+        /// Do not verify the [checksum](crate::ser::Serialize::serialize_with_checksum)
+        /// of the payload, if present.
+        ///
+        /// Verification requires reading the whole payload, so this flag is
+        /// useful to keep memory mapping lazy on trusted storage.
+        const SKIP_CHECKSUM = 1 << 3;
+        /// Map the file in shared mode, so that changes made to the file by
+        /// other processes are visible in the mapping (`MAP_SHARED` on Unix,
+        /// `FILE_MAP_READ` on Windows).
+        ///
+        /// This flag affects only [`mmap`](crate::deser::Deserialize::mmap),
+        /// and it cannot be combined with [`Flags::COPY_ON_WRITE`].
+        const SHARED = 1 << 4;
+        /// Map the file in copy-on-write mode (`MAP_PRIVATE` on Unix,
+        /// `FILE_MAP_COPY` on Windows). This is the default, but the flag
+        /// makes the choice explicit.
+        ///
+        /// This flag affects only [`mmap`](crate::deser::Deserialize::mmap),
+        /// and it cannot be combined with [`Flags::SHARED`].
+        const COPY_ON_WRITE = 1 << 5;
+        /// Prevent other processes from writing to, renaming, or deleting
+        /// the file while it is mapped.
+        ///
+        /// On Windows, the file is opened with share mode `FILE_SHARE_READ`,
+        /// which remains in effect as long as the mapping exists; on other
+        /// systems, in which there is no equivalent mandatory mechanism,
+        /// this flag is ignored. This flag affects only
+        /// [`mmap`](crate::deser::Deserialize::mmap).
+        const DENY_WRITE = 1 << 6;
+        /// Exclude the mapped region from core dumps (`MADV_DONTDUMP` on
+        /// Linux), which is useful to keep core dumps of processes mapping
+        /// large indices of manageable size.
+        ///
+        /// This flag is ignored if the operating system does not support it.
+        const NO_CORE_DUMP = 1 << 7;
+        /// Suggest that the mapped region will be accessed soon, so that the
+        /// kernel can start reading it in advance (`MADV_WILLNEED`).
+        ///
+        /// The advice is given after mapping. This flag is only a suggestion,
+        /// and it is ignored if the kernel does not support it. It affects
+        /// only [`mmap`](crate::deser::Deserialize::mmap), as the other
+        /// loading methods read the whole file anyway.
+        const WILL_NEED = 1 << 8;
+        /// Populate the page tables of the mapping when mapping, reading
+        /// the whole file into the page cache (`MAP_POPULATE` on Linux).
+        ///
+        /// Mapping takes longer, but first accesses to the structure will not
+        /// cause page faults, which is useful for latency-critical services
+        /// that want to warm up at startup. This flag is ignored if the
+        /// operating system does not support it; see also
+        /// [`MemCase::prefault`] for a portable alternative.
+        const POPULATE = 1 << 9;
+        /// Validate strings and check the bit patterns of zero-copy values,
+        /// as [`Deserialize::deserialize_eps_untrusted`](crate::deser::Deserialize::deserialize_eps_untrusted)
+        /// does, so that data coming from untrusted sources cannot cause
+        /// undefined behavior.
+        ///
+        /// Checking requires reading the whole data, so this flag makes
+        /// memory mapping eager.
+        const UNTRUSTED = 1 << 10;
+        /// Annotate errors with the path and the offset of the field whose
+        /// deserialization failed, as
+        /// [`Deserialize::deserialize_eps_with_paths`](crate::deser::Deserialize::deserialize_eps_with_paths)
+        /// does (see [`Error::InField`](crate::deser::Error::InField)).
+        const ERROR_PATHS = 1 << 11;
+    }
+}
+
+/// Empty flags.
+impl core::default::Default for Flags {
+    fn default() -> Self {
+        Flags::empty()
+    }
+}
+
+impl Flags {
+    /// Translates internal flags to `mmap_rs` flags.
+    #[cfg(feature = "mmap-rs")]
+    pub(crate) fn mmap_flags(&self) -> mmap_rs::MmapFlags {
+        let mut flags: mmap_rs::MmapFlags = mmap_rs::MmapFlags::empty();
+        if self.contains(Self::SEQUENTIAL) {
+            flags |= mmap_rs::MmapFlags::SEQUENTIAL;
+        }
+        if self.contains(Self::RANDOM_ACCESS) {
+            flags |= mmap_rs::MmapFlags::RANDOM_ACCESS;
+        }
+        if self.contains(Self::TRANSPARENT_HUGE_PAGES) {
+            flags |= mmap_rs::MmapFlags::TRANSPARENT_HUGE_PAGES;
+        }
+        if self.contains(Self::SHARED) {
+            flags |= mmap_rs::MmapFlags::SHARED;
+        }
+        if self.contains(Self::NO_CORE_DUMP) {
+            flags |= mmap_rs::MmapFlags::NO_CORE_DUMP;
+        }
+        if self.contains(Self::POPULATE) {
+            flags |= mmap_rs::MmapFlags::POPULATE;
+        }
+
+        flags
+    }
+
+    /// Open a file to be mapped, applying the share mode.
+    #[cfg(feature = "mmap-rs")]
+    pub(crate) fn open(&self, path: &std::path::Path) -> crate::deser::Result<std::fs::File> {
+        if self.contains(Self::SHARED | Self::COPY_ON_WRITE) {
+            return Err(crate::deser::Error::FileOpenError(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "Flags SHARED and COPY_ON_WRITE cannot be used together",
+            )));
+        }
+        let mut options = std::fs::OpenOptions::new();
+        options.read(true);
+        #[cfg(windows)]
+        if self.contains(Self::DENY_WRITE) {
+            use std::os::windows::fs::OpenOptionsExt;
+            const FILE_SHARE_READ: u32 = 0x1;
+            options.share_mode(FILE_SHARE_READ);
+        }
+        options
+            .open(path)
+            .map_err(crate::deser::Error::FileOpenError)
+    }
+}
+
+/// Placement hints for [`load_mmap_with_hints`](crate::deser::Deserialize::load_mmap_with_hints)
+/// and [`mmap_with_hints`](crate::deser::Deserialize::mmap_with_hints).
+///
+/// Differently from [`Flags`], hints carry a value. All hints are best-effort:
+/// if they cannot be satisfied, the memory is mapped and loaded as if no hint
+/// had been given.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct MmapHints {
+    address: Option<usize>,
+    numa_node: Option<u32>,
+}
+
+impl MmapHints {
+    /// Return empty hints.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Suggest the address at which the memory should be mapped.
+    ///
+    /// The address is passed to `mmap()` as a hint, without `MAP_FIXED`, so it
+    /// will never replace existing mappings; the kernel may choose a
+    /// different address, and if mapping fails at the suggested address
+    /// we try again without hint.
+    pub fn with_address(mut self, address: usize) -> Self {
+        self.address = Some(address);
+        self
+    }
+
+    /// Suggest to bind the mapped pages to the given NUMA node.
+    ///
+    /// On Linux, the mapped region is bound to the node using `mbind()`; on
+    /// other systems, or if the call fails (e.g., because the node does not
+    /// exist), the hint is ignored. Binding is effective on memory allocated by
+    /// [`load_mmap_with_hints`](crate::deser::Deserialize::load_mmap_with_hints),
+    /// as the binding happens before the file is read into memory;
+    /// in the case of [`mmap_with_hints`](crate::deser::Deserialize::mmap_with_hints),
+    /// the kernel may ignore the binding for pages of the page cache shared with
+    /// other processes.
+    pub fn with_numa_node(mut self, numa_node: u32) -> Self {
+        self.numa_node = Some(numa_node);
+        self
+    }
+
+    /// Return the suggested address, if any.
+    pub fn address(&self) -> Option<usize> {
+        self.address
+    }
+
+    /// Return the suggested NUMA node, if any.
+    pub fn numa_node(&self) -> Option<u32> {
+        self.numa_node
+    }
+
+    /// Map memory using the given options, first at the suggested address, if any.
+    #[cfg(feature = "mmap-rs")]
+    pub(crate) fn map<'f, T>(
+        &self,
+        options: impl Fn() -> Result<mmap_rs::MmapOptions<'f>, mmap_rs::Error>,
+        map: impl Fn(mmap_rs::MmapOptions<'f>) -> Result<T, mmap_rs::Error>,
+    ) -> Result<T, mmap_rs::Error> {
+        if let Some(address) = self.address {
+            if let Ok(mmap) = map(options()?.with_address(address)) {
+                return Ok(mmap);
+            }
+        }
+        map(options()?)
+    }
+
+    /// Bind the given memory region to the suggested NUMA node, if any.
+    ///
+    /// If `move_pages` is true, pages already allocated are moved to the node.
+    /// Return whether the binding was successful.
+    #[cfg(feature = "mmap-rs")]
+    pub(crate) fn bind(&self, mem: &[u8], move_pages: bool) -> bool {
+        match self.numa_node {
+            Some(numa_node) if !mem.is_empty() => mbind(mem, numa_node, move_pages),
+            _ => false,
+        }
+    }
+}
+
+#[cfg(all(feature = "mmap-rs", target_os = "linux"))]
+fn mbind(mem: &[u8], numa_node: u32, move_pages: bool) -> bool {
+    const MPOL_BIND: libc::c_long = 2;
+    const MPOL_MF_MOVE: libc::c_long = 1 << 1;
+    const BITS: usize = u64::BITS as usize;
+
+    let numa_node = numa_node as usize;
+    let mut node_mask = vec![0_u64; numa_node / BITS + 1];
+    if let Some(word) = node_mask.last_mut() {
+        *word |= 1 << (numa_node % BITS);
+    }
+    // The kernel ignores the last bit of the mask
+    let max_node = node_mask.len() * BITS + 1;
+
+    // SAFETY: mem is a valid region of memory, and the mask contains max_node - 1 bits.
+    let result = unsafe {
+        libc::syscall(
+            libc::SYS_mbind,
+            mem.as_ptr(),
+            mem.len(),
+            MPOL_BIND,
+            node_mask.as_ptr(),
+            max_node,
+            if move_pages { MPOL_MF_MOVE } else { 0 },
+        )
+    };
+    result == 0
+}
+
+#[cfg(all(feature = "mmap-rs", not(target_os = "linux")))]
+fn mbind(_mem: &[u8], _numa_node: u32, _move_pages: bool) -> bool {
+    false
+}
+
+/// A memory region backing a [`MemCase`].
+///
+/// ε-serde provides implementations for heap-allocated vectors and boxed
+/// slices, for shared buffers ([`Arc<[u8]>`](Arc), and `bytes::Bytes` with the
+/// feature `bytes`), for static slices, and for `mmap()`-based regions (both
+/// from `mmap-rs` and, with the feature `memmap2`, from `memmap2`), but
+/// data can be ε-copy deserialized from any kind of memory (e.g., huge-page
+/// segments, pinned memory, or shared-memory regions managed by other
+/// frameworks) by implementing this trait and passing the region to
+/// [`Deserialize::deserialize_eps_in`] or [`Deserialize::load_in`]. The
+/// region is released when the implementation is dropped, that is, when the
+/// [`MemCase`] containing it is dropped.
+///
+/// In this way, ε-copy structures can be attached to buffers coming from
+/// arbitrary sources, such as those of network frameworks, without copying:
+///
+/// ```rust
+/// use epserde::prelude::*;
+/// use std::sync::Arc;
+///
+/// let mut cursor = epserde::new_aligned_cursor();
+/// vec![1_u32, 2, 3].serialize(&mut cursor)?;
+/// // A buffer shared with other parts of the application
+/// let buffer: Arc<[u8]> = cursor.into_inner().into();
+/// let case = <Vec<u32>>::deserialize_eps_in(buffer.clone())?;
+/// assert_eq!(*case, [1, 2, 3]);
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+///
+/// The memory of the region must be suitably aligned (see
+/// [`Deserialize::deserialize_eps_owned`]): the alignment of buffers
+/// allocated by third parties is not guaranteed, and misaligned data will
+/// cause an [`AlignmentError`](crate::deser::Error::AlignmentError).
+///
+/// # Safety
+///
+/// [`MemStorage::bytes`] must return the same region at each call, and the
+/// content of the region must not change as long as the implementation is
+/// alive, as ε-copy deserialized structures refer to it.
+///
+/// [`Deserialize::deserialize_eps_owned`]: crate::deser::Deserialize::deserialize_eps_owned
+/// [`Deserialize::deserialize_eps_in`]: crate::deser::Deserialize::deserialize_eps_in
+/// [`Deserialize::load_in`]: crate::deser::Deserialize::load_in
+pub unsafe trait MemStorage: Send + Sync {
+    /// Return the content of the region.
+    fn bytes(&self) -> &[u8];
+
+    /// Return the size in bytes of the region.
+    fn len(&self) -> usize {
+        self.bytes().len()
+    }
+
+    /// Return whether the region is empty.
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Advise the operating system about the expected use of the region.
+    ///
+    /// Only the flags [`Flags::SEQUENTIAL`], [`Flags::RANDOM_ACCESS`],
+    /// [`Flags::TRANSPARENT_HUGE_PAGES`], [`Flags::WILL_NEED`], and
+    /// [`Flags::NO_CORE_DUMP`] are meaningful. Advice is
+    /// best-effort: this method returns whether it was applied, and the
+    /// default implementation does nothing and returns false.
+    fn advise(&self, _flags: Flags) -> bool {
+        false
+    }
+
+    /// Suggest the operating system to reclaim the memory of the pages
+    /// entirely contained in the given range of the region.
+    ///
+    /// The content of the region is preserved: released pages are read
+    /// again (from the file or from swap) if accessed. Release is
+    /// best-effort: this method returns whether it was applied to at
+    /// least one page, and the default implementation does nothing and
+    /// returns false.
+    ///
+    /// # Panics
+    ///
+    /// If the range is out of bounds.
+    fn release(&self, _range: Range<usize>) -> bool {
+        false
+    }
+}
+
+// SAFETY: the content of a vector is on the heap, so it does not move when
+// the vector is moved.
+unsafe impl MemStorage for Vec<u8> {
+    fn bytes(&self) -> &[u8] {
+        self
+    }
+}
+
+// SAFETY: the content of a boxed slice is on the heap, so it does not move
+// when the box is moved.
+unsafe impl MemStorage for Box<[u8]> {
+    fn bytes(&self) -> &[u8] {
+        self
+    }
+}
+
+// SAFETY: the content of an Arc is on the heap, so it does not move when
+// the Arc is moved, and it cannot be modified while it is shared.
+unsafe impl MemStorage for Arc<[u8]> {
+    fn bytes(&self) -> &[u8] {
+        self
+    }
+}
+
+// SAFETY: a static slice is never freed or modified.
+unsafe impl MemStorage for &'static [u8] {
+    fn bytes(&self) -> &[u8] {
+        self
+    }
+}
+
+// SAFETY: the content of a Bytes instance is immutable, and it does not move
+// when the instance is moved.
+#[cfg(feature = "bytes")]
+unsafe impl MemStorage for bytes::Bytes {
+    fn bytes(&self) -> &[u8] {
+        self
+    }
+}
+
+// SAFETY: the mapped region does not move when the mapping is moved.
+#[cfg(feature = "mmap-rs")]
+unsafe impl MemStorage for mmap_rs::Mmap {
+    fn bytes(&self) -> &[u8] {
+        self
+    }
+
+    fn advise(&self, flags: Flags) -> bool {
+        madvise(self, flags)
+    }
+    fn release(&self, range: Range<usize>) -> bool {
+        self.get(range).is_some_and(release_pages)
+    }
+}
+
+// SAFETY: the mapped region does not move when the mapping is moved.
+#[cfg(feature = "mmap-rs")]
+unsafe impl MemStorage for mmap_rs::MmapMut {
+    fn bytes(&self) -> &[u8] {
+        self
+    }
+
+    fn advise(&self, flags: Flags) -> bool {
+        madvise(self, flags)
+    }
+    fn release(&self, range: Range<usize>) -> bool {
+        self.get(range).is_some_and(release_pages)
+    }
+}
+
+// SAFETY: the mapped region does not move when the mapping is moved.
+#[cfg(feature = "memmap2")]
+unsafe impl MemStorage for memmap2::Mmap {
+    fn bytes(&self) -> &[u8] {
+        self
+    }
+
+    #[cfg(unix)]
+    fn advise(&self, flags: Flags) -> bool {
+        memmap2_advise(flags, |advice| memmap2::Mmap::advise(self, advice))
+    }
+}
+
+// SAFETY: the mapped region does not move when the mapping is moved.
+#[cfg(feature = "memmap2")]
+unsafe impl MemStorage for memmap2::MmapMut {
+    fn bytes(&self) -> &[u8] {
+        self
+    }
+
+    #[cfg(unix)]
+    fn advise(&self, flags: Flags) -> bool {
+        memmap2_advise(flags, |advice| memmap2::MmapMut::advise(self, advice))
+    }
+}
+
+/// Apply the advice corresponding to `flags` using `advise`, returning
+/// whether all advice was applied.
+#[cfg(all(feature = "memmap2", unix))]
+fn memmap2_advise(flags: Flags, advise: impl Fn(memmap2::Advice) -> std::io::Result<()>) -> bool {
+    let advice = [
+        (Flags::SEQUENTIAL, Some(memmap2::Advice::Sequential)),
+        (Flags::RANDOM_ACCESS, Some(memmap2::Advice::Random)),
+        (Flags::WILL_NEED, Some(memmap2::Advice::WillNeed)),
+        #[cfg(target_os = "linux")]
+        (
+            Flags::TRANSPARENT_HUGE_PAGES,
+            Some(memmap2::Advice::HugePage),
+        ),
+        #[cfg(target_os = "linux")]
+        (Flags::NO_CORE_DUMP, Some(memmap2::Advice::DontDump)),
+        #[cfg(not(target_os = "linux"))]
+        (Flags::TRANSPARENT_HUGE_PAGES, None),
+        #[cfg(not(target_os = "linux"))]
+        (Flags::NO_CORE_DUMP, None),
+    ];
+    advice
+        .into_iter()
+        .filter(|(flag, _)| flags.contains(*flag))
+        .all(|(_, advice)| advice.is_some_and(|advice| advise(advice).is_ok()))
+}
+
+#[cfg(all(feature = "mmap-rs", target_os = "linux"))]
+fn madvise(mem: &[u8], flags: Flags) -> bool {
+    let advice = [
+        (Flags::SEQUENTIAL, libc::MADV_SEQUENTIAL),
+        (Flags::RANDOM_ACCESS, libc::MADV_RANDOM),
+        (Flags::TRANSPARENT_HUGE_PAGES, libc::MADV_HUGEPAGE),
+        (Flags::WILL_NEED, libc::MADV_WILLNEED),
+        (Flags::NO_CORE_DUMP, libc::MADV_DONTDUMP),
+    ];
+    !mem.is_empty()
+        && advice
+            .into_iter()
+            .filter(|(flag, _)| flags.contains(*flag))
+            // SAFETY: mem is a mapped region, so it is page aligned.
+            .all(|(_, advice)| unsafe {
+                libc::madvise(mem.as_ptr() as *mut libc::c_void, mem.len(), advice) == 0
+            })
+}
+
+#[cfg(all(feature = "mmap-rs", not(target_os = "linux")))]
+fn madvise(_mem: &[u8], _flags: Flags) -> bool {
+    false
+}
+
+/// Reclaim the pages entirely contained in `mem` with `MADV_PAGEOUT`.
+///
+/// We do not use `MADV_DONTNEED`, which would zero the content of
+/// anonymous mappings such as those of
+/// [`load_mmap`](crate::deser::Deserialize::load_mmap).
+#[cfg(all(feature = "mmap-rs", target_os = "linux"))]
+fn release_pages(mem: &[u8]) -> bool {
+    let page_size = mmap_rs::MmapOptions::page_size();
+    let start = (mem.as_ptr() as usize).next_multiple_of(page_size);
+    let end = (mem.as_ptr() as usize + mem.len()) / page_size * page_size;
+    // SAFETY: the pages are within a mapped region, and paging out
+    // preserves their content.
+    start < end
+        && unsafe {
+            libc::madvise(start as *mut libc::c_void, end - start, libc::MADV_PAGEOUT) == 0
+        }
+}
+
+#[cfg(all(feature = "mmap-rs", not(target_os = "linux")))]
+fn release_pages(_mem: &[u8]) -> bool {
+    false
+}
+
+/// Possible backends of a [`MemCase`]. The `None` variant is used when the data structure is
+/// created in memory; the `Memory` variant is used when the data structure is deserialized
+/// from a file loaded into a heap-allocated memory region; the `Mmap` variant is used when
+/// the data structure is deserialized from a `mmap()`-based region, either coming from
+/// an allocation or a from mapping a file; the `Custom` variant is used when the data
+/// structure is deserialized from a user-provided [`MemStorage`].
+pub enum MemBackend {
+    /// No backend. The data structure is a standard Rust data structure.
+    /// This variant is returned by [`MemCase::encase`].
+    None,
+    /// The backend is a heap-allocated in a memory region aligned to 4096 bytes.
+    /// This variant is returned by [`crate::deser::Deserialize::load_mem`].
+    Memory(Vec<u8>),
+    /// The backend is the result to a call to `mmap()`.
+    /// This variant is returned by [`crate::deser::Deserialize::load_mmap`] and [`crate::deser::Deserialize::mmap`].
+    #[cfg(feature = "mmap-rs")]
+    Mmap(mmap_rs::Mmap),
+    /// The backend is a user-provided memory region.
+    /// This variant is returned by [`crate::deser::Deserialize::deserialize_eps_in`]
+    /// and [`crate::deser::Deserialize::load_in`].
+    Custom(Box<dyn MemStorage>),
+}
+
+impl core::fmt::Debug for MemBackend {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let variant = match self {
+            MemBackend::None => return f.write_str("None"),
+            MemBackend::Memory(_) => "Memory",
+            #[cfg(feature = "mmap-rs")]
+            MemBackend::Mmap(_) => "Mmap",
+            MemBackend::Custom(_) => "Custom",
+        };
+        f.debug_struct(variant).field("size", &self.size()).finish()
+    }
+}
+
+/// The stride used by [`MemBackend::prefault`], which is the smallest page
+/// size in common use.
+const PREFAULT_STRIDE: usize = 4096;
+
+impl MemBackend {
+    pub fn as_ref(&self) -> Option<&[u8]> {
+        match self {
+            MemBackend::None => None,
+            MemBackend::Memory(mem) => Some(mem),
+            #[cfg(feature = "mmap-rs")]
+            MemBackend::Mmap(mmap) => Some(mmap),
+            MemBackend::Custom(storage) => Some(storage.bytes()),
+        }
+    }
+
+    /// Return the memory region of the backend, or
+    /// [`Error::NoBackend`](crate::deser::Error::NoBackend) for the
+    /// [`None`](`MemBackend#variant.None`) variant.
+    pub(crate) fn bytes(&self) -> crate::deser::Result<&[u8]> {
+        self.as_ref().ok_or(crate::deser::Error::NoBackend)
+    }
+
+    /// Return the size in bytes of the memory region of the backend,
+    /// which is zero for the [`None`](`MemBackend#variant.None`) variant.
+    pub fn size(&self) -> usize {
+        self.as_ref().map_or(0, <[u8]>::len)
+    }
+
+    /// Advise the operating system about the expected use of the memory
+    /// region of the backend, returning whether the advice was applied.
+    ///
+    /// See [`MemStorage::advise`].
+    pub fn advise(&self, flags: Flags) -> bool {
+        match self {
+            MemBackend::None => false,
+            MemBackend::Memory(mem) => mem.advise(flags),
+            #[cfg(feature = "mmap-rs")]
+            MemBackend::Mmap(mmap) => mmap.advise(flags),
+            MemBackend::Custom(storage) => storage.advise(flags),
+        }
+    }
+
+    /// Suggest the operating system to reclaim the memory of the pages
+    /// entirely contained in the given range of the memory region of the
+    /// backend, returning whether the suggestion was applied.
+    ///
+    /// See [`MemStorage::release`].
+    pub fn release(&self, range: Range<usize>) -> bool {
+        match self {
+            MemBackend::None | MemBackend::Memory(_) => false,
+            #[cfg(feature = "mmap-rs")]
+            MemBackend::Mmap(mmap) => mmap.release(range),
+            MemBackend::Custom(storage) => storage.release(range),
+        }
+    }
+
+    /// Touch each page of the memory region of the backend, so that
+    /// subsequent accesses will not cause page faults.
+    ///
+    /// Differently from [`Flags::WILL_NEED`], which just suggests the kernel
+    /// to read the region in advance, this method returns only when the
+    /// whole region has been read; differently from [`Flags::POPULATE`], it
+    /// works on all operating systems and it can be called at any time (e.g.,
+    /// after the pages of the region have been evicted). Note that the pages
+    /// might be evicted again under memory pressure.
+    pub fn prefault(&self) {
+        if let Some(mem) = self.as_ref() {
+            for byte in mem.iter().step_by(PREFAULT_STRIDE) {
+                core::hint::black_box(*byte);
+            }
+        }
+    }
+
+    /// Notify the [observer](MemObserver), if any, that the backend has been
+    /// created, and wrap it so that the observer will be notified when it is
+    /// dropped. Must be called exactly once for each backend other than
+    /// [`None`](`MemBackend#variant.None`).
+    pub(crate) fn created(self) -> ObservedBackend {
+        #[cfg(feature = "std")]
+        if !matches!(self, MemBackend::None) {
+            if let Some(observer) = mem_observer() {
+                observer.on_create(&self);
+            }
+        }
+        ObservedBackend(self)
+    }
+}
+
+/// The backend of a [`MemCase`], whose drop is notified to the
+/// [observer](MemObserver), if any.
+///
+/// Notifications are not sent by [`MemBackend`] itself, as implementing
+/// [`Drop`] on it would make it impossible to move out of its variants.
+pub(crate) struct ObservedBackend(MemBackend);
+
+impl ObservedBackend {
+    /// Notify the [observer](MemObserver), if any, that the backend is no
+    /// longer used by a [`MemCase`].
+    fn notify_drop(&self) {
+        #[cfg(feature = "std")]
+        if !matches!(self.0, MemBackend::None) {
+            if let Some(observer) = mem_observer() {
+                observer.on_drop(&self.0);
+            }
+        }
+    }
+
+    /// Return the backend, notifying the [observer](MemObserver), if any,
+    /// as if it had been dropped.
+    fn into_inner(mut self) -> MemBackend {
+        self.notify_drop();
+        core::mem::replace(&mut self.0, MemBackend::None)
+    }
+}
+
+impl Deref for ObservedBackend {
+    type Target = MemBackend;
+    #[inline(always)]
+    fn deref(&self) -> &MemBackend {
+        &self.0
+    }
+}
+
+impl Drop for ObservedBackend {
+    fn drop(&mut self) {
+        self.notify_drop();
+    }
+}
+
+/// An observer of the memory backing [`MemCase`] instances.
+///
+/// An observer can be installed with [`set_mem_observer`]; it will be
+/// notified whenever a backend other than [`None`](`MemBackend#variant.None`)
+/// is created (e.g., by [`load_mem`](crate::deser::Deserialize::load_mem) or
+/// [`mmap`](crate::deser::Deserialize::mmap)) and whenever it is freed or
+/// unmapped (or taken out of its [`MemCase`] with
+/// [`into_backend`](MemCase::into_backend)), making it possible to integrate the memory used by ε-serde
+/// in the accounting or metrics system of an application, or to detect leaked
+/// mappings in long-running services. The size of the memory region is
+/// available through [`MemBackend::size`].
+///
+/// Notifications happen in the thread creating or dropping the backend, so
+/// methods should be fast and must not panic.
+#[cfg(feature = "std")]
+pub trait MemObserver: Send + Sync {
+    /// Called after a backend has been created.
+    fn on_create(&self, _backend: &MemBackend) {}
+    /// Called before a backend is freed or unmapped, or when it is taken
+    /// out of its [`MemCase`].
+    fn on_drop(&self, _backend: &MemBackend) {}
+}
+
+#[cfg(feature = "std")]
+static MEM_OBSERVER: std::sync::RwLock<Option<std::sync::Arc<dyn MemObserver>>> =
+    std::sync::RwLock::new(None);
+
+/// Install a process-wide [`MemObserver`], or remove the current one
+/// if `observer` is `None`, returning the previous observer.
+///
+/// Note that backends created before installing an observer will be
+/// reported to the observer when dropped.
+#[cfg(feature = "std")]
+pub fn set_mem_observer(
+    observer: Option<std::sync::Arc<dyn MemObserver>>,
+) -> Option<std::sync::Arc<dyn MemObserver>> {
+    let mut current = MEM_OBSERVER.write().unwrap_or_else(|err| err.into_inner());
+    core::mem::replace(&mut *current, observer)
+}
+
+#[cfg(feature = "std")]
+fn mem_observer() -> Option<std::sync::Arc<dyn MemObserver>> {
+    MEM_OBSERVER
+        .read()
+        .unwrap_or_else(|err| err.into_inner())
+        .clone()
+}
+
+/// A wrapper keeping together an immutable structure and the memory
+/// it was deserialized from. [`MemCase`] instances can not be cloned, as the
+/// structure refers to the memory of the backend, but references to such
+/// instances can be shared freely (e.g., using an [`Arc`]).
+///
+/// [`MemCase`] implements [`Deref`] and [`AsRef`] to the
+/// wrapped type, so it can be used almost transparently and
+/// with no performance cost. The structure can be wrapped in a
+/// user-defined type, keeping the backend alive, using the unsafe
+/// methods [`map`](MemCase::map) and [`try_map`](MemCase::try_map):
+///
+/// ```rust
+/// use epserde::prelude::*;
+///
+/// struct Stats<'a> {
+///     values: &'a [u64],
+///     sum: u64,
+/// }
+///
+/// let tmp_file = std::env::temp_dir().join("mem_case_map_doc.eps");
+/// vec![1_u64, 2, 3].store(&tmp_file)?;
+/// let case = <Vec<u64>>::load_mem(&tmp_file)?;
+/// // SAFETY: the slice is stored only in the result
+/// let stats = unsafe {
+///     case.map(|values| Stats {
+///         values,
+///         sum: values.iter().sum(),
+///     })
+/// };
+/// assert_eq!(stats.values, [1, 2, 3]);
+/// assert_eq!(stats.sum, 6);
+/// # std::fs::remove_file(&tmp_file)?;
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+///
+/// However,
+/// if you need to use a memory-mapped structure as a field in
+/// a struct and you want to avoid `dyn`, you will have
+/// to use [`MemCase`] as the type of the field.
+/// [`MemCase`] implements [`From`] for the
+/// wrapped type, using the no-op [`None`](`MemBackend#variant.None`) variant
+/// of [`MemBackend`], so a structure can be [encased](MemCase::encase)
+/// almost transparently.
+#[cfg_attr(not(feature = "std"), allow(dead_code))]
+pub struct MemCase<S>(pub(crate) S, pub(crate) ObservedBackend);
+
+impl<S> MemCase<S> {
+    /// Encases a data structure in a [`MemCase`] with no backend.
+    pub fn encase(s: S) -> MemCase<S> {
+        MemCase(s, ObservedBackend(MemBackend::None))
+    }
+
+    /// Advise the operating system about the expected use of the memory
+    /// backing the structure, returning whether the advice was applied.
+    ///
+    /// See [`MemStorage::advise`].
+    pub fn advise(&self, flags: Flags) -> bool {
+        self.1.advise(flags)
+    }
+
+    /// Suggest the operating system to reclaim the memory of the pages
+    /// entirely contained in the given range of the memory backing the
+    /// structure, returning whether the suggestion was applied.
+    ///
+    /// See [`MemStorage::release`] and [`MemCase::release_fields`].
+    pub fn release(&self, range: Range<usize>) -> bool {
+        self.1.release(range)
+    }
+
+    /// Suggest the operating system to reclaim the memory of the given
+    /// fields (e.g., `ROOT.labels`), as described by the
+    /// [schema](crate::ser::Serialize::serialize_with_schema) of the
+    /// serialized data, returning whether the suggestion was applied to at
+    /// least one page.
+    ///
+    /// This method makes it possible to reclaim the memory used by
+    /// fields of a wide structure that the application will not use, without
+    /// reopening the file; the fields will be read again if accessed. Only
+    /// whole pages are released, so small fields, or fields sharing pages
+    /// with other data, might not be released at all. Memory is released
+    /// only for memory-mapped backends.
+    ///
+    /// The offsets of the schema must be relative to the start of the memory
+    /// backing the structure, which is true if the schema has been obtained
+    /// by serializing the whole file.
+    pub fn release_fields(&self, schema: &crate::ser::Schema, fields: &[&str]) -> bool {
+        let mut ranges = fields
+            .iter()
+            .flat_map(|field| schema.ranges(field))
+            .collect::<Vec<_>>();
+        ranges.sort_unstable_by_key(|range| range.start);
+        // Merge adjacent ranges, so that more pages are entirely contained
+        let mut merged: Vec<Range<usize>> = Vec::with_capacity(ranges.len());
+        for range in ranges {
+            match merged.last_mut() {
+                Some(last) if range.start <= last.end => last.end = last.end.max(range.end),
+                _ => merged.push(range),
+            }
+        }
+        let mut released = false;
+        for range in merged {
+            released |= self.release(range);
+        }
+        released
+    }
+
+    /// Return a [`ChunkVerifier`](crate::merkle::ChunkVerifier) for the memory
+    /// backing the structure, or `None` if the data is not followed by a
+    /// [checksum tree](crate::merkle).
+    ///
+    /// The verifier makes it possible to verify only the chunks of the
+    /// fields that will be accessed (e.g., with
+    /// [`ChunkVerifier::verify_fields`](crate::merkle::ChunkVerifier::verify_fields)),
+    /// so that memory-mapped structures remain lazy. The offsets of the
+    /// verifier are relative to the start of the serialized data. If there is
+    /// no backend, this method returns
+    /// [`Error::NoBackend`](crate::deser::Error::NoBackend).
+    pub fn chunk_verifier(&self) -> crate::deser::Result<Option<crate::merkle::ChunkVerifier<'_>>> {
+        crate::merkle::ChunkVerifier::in_region(self.1.bytes()?, true)
+    }
+
+    /// Touch each page of the memory backing the structure, so that
+    /// subsequent accesses will not cause page faults.
+    ///
+    /// See [`MemBackend::prefault`].
+    pub fn prefault(&self) {
+        self.1.prefault()
+    }
+
+    /// Return the backend containing the memory of the structure.
+    pub fn backend(&self) -> &MemBackend {
+        &self.1
+    }
+
+    /// Drop the structure and return the backend containing its memory.
+    ///
+    /// The [observer](MemObserver), if any, is notified as if the backend
+    /// had been dropped, as it is no longer used by a [`MemCase`].
+    pub fn into_backend(self) -> MemBackend {
+        let MemCase(s, backend) = self;
+        // The structure might refer to the memory of the backend
+        drop(s);
+        backend.into_inner()
+    }
+
+    /// Wrap the structure in another type, keeping the backend alive.
+    ///
+    /// The result of `f` can refer to the memory of the backend (e.g., it
+    /// can contain the structure), and it will be dropped before the backend.
+    ///
+    /// # Safety
+    ///
+    /// The structure refers to the memory of the backend with a `'static`
+    /// lifetime, so the compiler cannot check that it does not outlive the
+    /// backend: `f` must not store the structure, or references to the memory
+    /// of the backend, anywhere but in its result (e.g., in variables
+    /// captured by `f`).
+    pub unsafe fn map<T>(self, f: impl FnOnce(S) -> T) -> MemCase<T> {
+        let MemCase(s, backend) = self;
+        MemCase(f(s), backend)
+    }
+
+    /// Fallible version of [`map`](MemCase::map).
+    ///
+    /// If `f` returns an error, the backend is dropped and the error is
+    /// returned.
+    ///
+    /// # Safety
+    ///
+    /// The same conditions of [`map`](MemCase::map) apply; moreover, the
+    /// error returned by `f` must not refer to the memory of the backend.
+    pub unsafe fn try_map<T, E>(self, f: impl FnOnce(S) -> Result<T, E>) -> Result<MemCase<T>, E> {
+        let MemCase(s, backend) = self;
+        Ok(MemCase(f(s)?, backend))
+    }
+}
+
+impl<S: core::fmt::Debug> core::fmt::Debug for MemCase<S> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_tuple("MemCase")
+            .field(&self.0)
+            .field(&*self.1)
+            .finish()
+    }
+}
+
+unsafe impl<S: Send> Send for MemCase<S> {}
+unsafe impl<S: Sync> Sync for MemCase<S> {}
+
+impl<S> Deref for MemCase<S> {
+    type Target = S;
+    #[inline(always)]
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<S> AsRef<S> for MemCase<S> {
+    #[inline(always)]
+    fn as_ref(&self) -> &S {
+        &self.0
+    }
+}
+
+impl<S: Send + Sync> From<S> for MemCase<S> {
+    fn from(s: S) -> Self {
+        MemCase::encase(s)
+    }
+}
@@ -0,0 +1,1535 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ * SPDX-FileCopyrightText: 2023 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+/*!
+
+Deserialization traits and types
+
+[`Deserialize`] is the main deserialization trait, providing methods
+[`Deserialize::deserialize_eps`] and [`Deserialize::deserialize_full`]
+which implement ε-copy and full-copy deserialization, respectively.
+The implementation of this trait is based on [`DeserializeInner`],
+which is automatically derived with `#[derive(Deserialize)]`.
+
+*/
+
+use crate::traits::*;
+use crate::{
+    CHECKSUM_FLAG, CHUNK_CHECKSUMS_FLAG, COMPRESSED_FLAG, MAGIC, MAGIC_REV, PORTABLE_FLAG,
+    SIGNATURE_FLAG, VERSION,
+};
+#[cfg(feature = "mmap-rs")]
+use core::{mem::MaybeUninit, ptr::addr_of_mut};
+#[cfg(feature = "std")]
+use std::{io::BufReader, path::Path};
+
+#[cfg(all(feature = "alloc", not(feature = "std")))]
+use alloc::{
+    boxed::Box,
+    format,
+    string::{String, ToString},
+    sync::Arc,
+    vec::Vec,
+};
+#[cfg(feature = "std")]
+use std::sync::Arc;
+
+pub mod byte_view;
+pub use byte_view::*;
+pub mod context;
+pub use context::*;
+pub mod eps_mut;
+pub use eps_mut::*;
+pub mod helpers;
+pub use helpers::*;
+pub mod mem_case;
+pub use mem_case::*;
+pub mod read;
+pub use read::*;
+pub mod reader_with_pos;
+pub use reader_with_pos::*;
+pub mod slice_with_pos;
+pub use slice_with_pos::*;
+pub mod str_policy;
+pub use str_policy::*;
+pub mod validation;
+pub use validation::*;
+#[cfg(feature = "std")]
+pub mod snapshot_cache;
+#[cfg(feature = "std")]
+pub use snapshot_cache::*;
+#[cfg(feature = "std")]
+pub mod cow_case;
+#[cfg(feature = "std")]
+pub use cow_case::*;
+
+pub type Result<T> = core::result::Result<T, Error>;
+
+/// A shorthand for the [deserialized type associated with a type](DeserializeInner::DeserType).
+pub type DeserType<'a, T> = <T as DeserializeInner>::DeserType<'a>;
+
+/// Main deserialization trait. It is separated from [`DeserializeInner`] to
+/// avoid that the user modify its behavior, and hide internal serialization
+/// methods.
+///
+/// It provides several convenience methods to load or map into memory
+/// structures that have been previously serialized. See, for example,
+/// [`Deserialize::load_full`], [`Deserialize::load_mem`], and [`Deserialize::mmap`].
+pub trait Deserialize: TypeHash + ReprHash + DeserializeInner + 'static {
+    /// Fully deserialize a structure of this type from the given backend.
+    fn deserialize_full(backend: &mut impl ReadNoStd) -> Result<Self>;
+    /// Fully deserialize a structure of this type from the given backend
+    /// into `self`, reusing its memory.
+    ///
+    /// This method is useful when loading repeatedly data of the same type
+    /// (e.g., in a reload loop), as vectors and strings reuse their buffers
+    /// if they are large enough, avoiding allocations. If an error is
+    /// returned, `self` is valid but its content is unspecified.
+    fn deserialize_full_into(&mut self, backend: &mut impl ReadNoStd) -> Result<()>;
+    /// Fully deserialize a structure of this type from the given backend,
+    /// skipping first `offset` bytes.
+    ///
+    /// This method is useful when the ε-serde payload is embedded
+    /// in another container (e.g., after a custom header) and the backend
+    /// cannot seek: alignment is computed relative to the start of the payload.
+    fn deserialize_full_at(backend: &mut impl ReadNoStd, offset: usize) -> Result<Self>;
+    /// Fully deserialize a structure of this type from the given backend,
+    /// making the given [context](Context) available to the deserialization
+    /// of the structure and of its fields through [`ReadWithPos::context`].
+    fn deserialize_full_with_context(
+        backend: &mut impl ReadNoStd,
+        context: Arc<Context>,
+    ) -> Result<Self>;
+    /// Fully deserialize a structure of this type from the given backend,
+    /// assuming that it was serialized on an architecture with the opposite
+    /// endianness.
+    ///
+    /// This method can be used to recover data when [`Deserialize::deserialize_full`]
+    /// returns [`Error::EndiannessError`]; the bytes of each value are swapped
+    /// using [`ByteSwap`]. Note that ε-copy deserialization of such data is
+    /// not possible. If the data has the same endianness of the current
+    /// architecture, this method returns [`Error::EndiannessError`].
+    fn deserialize_full_swapped(backend: &mut impl ReadNoStd) -> Result<Self>;
+    /// Fully deserialize a structure of this type from the given backend,
+    /// annotating errors with the path and the offset of the field whose
+    /// deserialization failed (see [`Error::InField`]).
+    ///
+    /// Other deserialization methods return the underlying error, so this
+    /// method is useful to find out which field of a deeply nested
+    /// structure is corrupted.
+    fn deserialize_full_with_paths(backend: &mut impl ReadNoStd) -> Result<Self>;
+    /// Fully deserialize a structure of this type from the given backend,
+    /// decompressing its payload with the [codec](crate::compress::Codec) `C`.
+    ///
+    /// The data must have been serialized by
+    /// [`Serialize::serialize_compressed`](crate::ser::Serialize::serialize_compressed)
+    /// with the same codec; otherwise, [`Error::WrongCodec`] is returned.
+    /// The type of the data is checked before decompressing it.
+    #[cfg(feature = "std")]
+    fn deserialize_full_compressed<C: crate::compress::Codec>(
+        backend: &mut impl ReadNoStd,
+    ) -> Result<Self>;
+    /// ε-copy deserialize a structure of this type from the given backend.
+    ///
+    /// Strings and zero-copy values are not validated: see
+    /// [`Deserialize::deserialize_eps_untrusted`].
+    fn deserialize_eps(backend: &'_ [u8]) -> Result<Self::DeserType<'_>>;
+    /// ε-copy deserialize a structure of this type from the given backend,
+    /// validating strings using the given [policy](StrPolicy).
+    ///
+    /// Zero-copy values are not checked: data from untrusted sources should
+    /// be deserialized using [`Deserialize::deserialize_eps_untrusted`].
+    fn deserialize_eps_with_policy(
+        backend: &'_ [u8],
+        str_policy: StrPolicy,
+    ) -> Result<Self::DeserType<'_>>;
+    /// ε-copy deserialize a structure of this type from a backend whose
+    /// content comes from an untrusted source.
+    ///
+    /// Besides lengths and tags, which are always checked, strings are
+    /// validated using [`StrPolicy::Validated`] and the bit patterns of
+    /// zero-copy values are [checked](crate::traits::CheckBytes) using
+    /// [`Validation::Checked`]. As a result, no
+    /// sequence of bytes can cause a panic or undefined behavior, and
+    /// invalid data yields an error. This property is tested by the fuzz
+    /// targets in the `fuzz` directory of the repository.
+    ///
+    /// Note that the types must be implemented through the derive macro
+    /// and the implementations provided by ε-serde: functions specified by
+    /// the `deser_with` attribute must be equally careful.
+    fn deserialize_eps_untrusted(backend: &'_ [u8]) -> Result<Self::DeserType<'_>>;
+    /// ε-copy deserialize a structure of this type from a backend whose
+    /// content comes from an untrusted source, as
+    /// [`Deserialize::deserialize_eps_untrusted`] does, annotating errors
+    /// with the path and the offset of the field whose deserialization
+    /// failed (see [`Error::InField`]).
+    fn deserialize_eps_with_paths(backend: &'_ [u8]) -> Result<Self::DeserType<'_>>;
+    /// ε-copy deserialize a structure of this type from the given backend,
+    /// making the given [context](Context) available to the deserialization
+    /// of the structure and of its fields through [`ReadWithPos::context`].
+    fn deserialize_eps_with_context(
+        backend: &'_ [u8],
+        context: Arc<Context>,
+    ) -> Result<Self::DeserType<'_>>;
+    /// ε-copy deserialize a structure of this type from the given backend
+    /// without verifying the [checksum](crate::ser::Serialize::serialize_with_checksum)
+    /// of the payload.
+    ///
+    /// This method does not need to read the whole payload, which is useful
+    /// for memory-mapped data (see [`Flags::SKIP_CHECKSUM`]).
+    fn deserialize_eps_unverified(backend: &'_ [u8]) -> Result<Self::DeserType<'_>>;
+    /// Fully deserialize a structure of this type from the given backend,
+    /// accepting data whose type is a structural supertype of this type.
+    ///
+    /// The backend must contain exactly the bytes serialized by
+    /// [`Serialize::serialize_with_embedded_schema`](crate::ser::Serialize::serialize_with_embedded_schema).
+    /// See the [`compat`](crate::compat) module for the compatibility rules.
+    fn deserialize_full_compatible(backend: &[u8]) -> Result<Self>;
+    /// ε-copy deserialize a structure of this type from the given backend,
+    /// accepting data whose type is a structural supertype of this type.
+    ///
+    /// The backend must contain exactly the bytes serialized by
+    /// [`Serialize::serialize_with_embedded_schema`](crate::ser::Serialize::serialize_with_embedded_schema).
+    /// See the [`compat`](crate::compat) module for the compatibility rules.
+    fn deserialize_eps_compatible(backend: &'_ [u8]) -> Result<Self::DeserType<'_>>;
+
+    /// ε-deserialize a data structure from a buffer of bytes, returning a
+    /// [`MemCase`] containing the data structure and the buffer.
+    ///
+    /// This method is useful when the serialized data does not come from a
+    /// file (e.g., it has been downloaded from the network). The buffer must
+    /// be suitably aligned (16 bytes is sufficient for all types without
+    /// `repr(align)` attributes requiring larger alignment, and in general
+    /// [`SerializeInner::MAX_FIELD_ALIGN`](crate::ser::SerializeInner::MAX_FIELD_ALIGN)
+    /// bytes are sufficient), or [`Error::AlignmentError`] will be returned.
+    fn deserialize_eps_owned<'a>(
+        bytes: Vec<u8>,
+    ) -> Result<MemCase<<Self as DeserializeInner>::DeserType<'a>>> {
+        let backend = MemBackend::Memory(bytes).created();
+        // SAFETY: the content of the vector is on the heap, so it does not
+        // move when the backend is moved into the MemCase, which keeps it
+        // alive as long as the deserialized structure.
+        let mem: &'a [u8] = unsafe { &*(backend.bytes()? as *const [u8]) };
+        let s = Self::deserialize_eps(mem)?;
+        Ok(MemCase(s, backend))
+    }
+
+    /// ε-deserialize a data structure from a user-provided
+    /// [memory region](MemStorage), returning a [`MemCase`] containing the
+    /// data structure and the region.
+    ///
+    /// The region must be suitably aligned, as in the case of
+    /// [`Deserialize::deserialize_eps_owned`].
+    fn deserialize_eps_in<'a>(
+        storage: impl MemStorage + 'static,
+    ) -> Result<MemCase<<Self as DeserializeInner>::DeserType<'a>>> {
+        let backend = MemBackend::Custom(Box::new(storage)).created();
+        // SAFETY: the region does not move or change as long as the storage
+        // is alive (see MemStorage), and the MemCase keeps the storage alive
+        // as long as the deserialized structure.
+        let mem: &'a [u8] = unsafe { &*(backend.bytes()? as *const [u8]) };
+        let s = Self::deserialize_eps(mem)?;
+        Ok(MemCase(s, backend))
+    }
+
+    /// Load a file into a user-provided [memory region](MemStorage) and
+    /// ε-deserialize a data structure from it, returning a [`MemCase`]
+    /// containing the data structure and the region.
+    ///
+    /// `alloc` is called with the length of the file rounded up to a
+    /// multiple of 16, and must return a suitably aligned region of at least
+    /// that length (see [`Deserialize::deserialize_eps_owned`]). Excess bytes
+    /// are zeroed out.
+    #[cfg(feature = "std")]
+    fn load_in<'a, S: MemStorage + AsMut<[u8]> + 'static>(
+        path: impl AsRef<Path>,
+        alloc: impl FnOnce(usize) -> Result<S>,
+    ) -> Result<MemCase<<Self as DeserializeInner>::DeserType<'a>>> {
+        let file_len = path.as_ref().metadata()?.len() as usize;
+        let mut file = std::fs::File::open(path).map_err(Error::FileOpenError)?;
+        // Round up to u128 size
+        let capacity = file_len + crate::pad_align_to(file_len, 16);
+
+        let mut storage = alloc(capacity)?;
+        let bytes = storage.as_mut();
+        if bytes.len() < capacity {
+            return Err(Error::RegionTooShort {
+                len: bytes.len(),
+                needed: capacity,
+            });
+        }
+        let (data, padding) = bytes.split_at_mut(file_len);
+        std::io::Read::read_exact(&mut file, data)?;
+        // Fixes the last bytes to guarantee zero-extension semantics
+        // for bit vectors and full-vector initialization.
+        padding.fill(0);
+        Self::deserialize_eps_in(storage)
+    }
+
+    /// Commodity method to fully deserialize from a file.
+    #[cfg(feature = "std")]
+    fn load_full(path: impl AsRef<Path>) -> Result<Self> {
+        crate::fs::load_full(&crate::fs::StdFileSystem, path)
+    }
+
+    /// Commodity method to fully deserialize from a file
+    /// [into an existing value](Deserialize::deserialize_full_into).
+    #[cfg(feature = "std")]
+    fn load_full_into(&mut self, path: impl AsRef<Path>) -> Result<()> {
+        let file = std::fs::File::open(path).map_err(Error::FileOpenError)?;
+        let mut buf_reader = BufReader::new(file);
+        self.deserialize_full_into(&mut buf_reader)
+    }
+
+    /// Commodity method to fully deserialize from a file
+    /// [compressed](Deserialize::deserialize_full_compressed) with the codec `C`.
+    #[cfg(feature = "std")]
+    fn load_full_compressed<C: crate::compress::Codec>(path: impl AsRef<Path>) -> Result<Self> {
+        let file = std::fs::File::open(path).map_err(Error::FileOpenError)?;
+        let mut buf_reader = BufReader::new(file);
+        Self::deserialize_full_compressed::<C>(&mut buf_reader)
+    }
+
+    /// Load a file into heap-allocated memory and ε-deserialize a data structure from it,
+    /// returning a [`MemCase`] containing the data structure and the
+    /// memory. Excess bytes are zeroed out.
+    ///
+    /// Strings and zero-copy values are not validated: see
+    /// [`Deserialize::load_mem_with_policy`], and [`Flags::UNTRUSTED`] for
+    /// [`Deserialize::load_mmap`].
+    #[cfg(feature = "std")]
+    fn load_mem<'a>(
+        path: impl AsRef<Path>,
+    ) -> Result<MemCase<<Self as DeserializeInner>::DeserType<'a>>> {
+        Self::load_mem_with_policy(path, StrPolicy::Trusted)
+    }
+
+    /// Like [`Deserialize::load_mem`], but validating strings using the
+    /// given [policy](StrPolicy).
+    #[cfg(feature = "std")]
+    fn load_mem_with_policy<'a>(
+        path: impl AsRef<Path>,
+        str_policy: StrPolicy,
+    ) -> Result<MemCase<<Self as DeserializeInner>::DeserType<'a>>> {
+        crate::fs::load_mem::<Self>(&crate::fs::StdFileSystem, path, str_policy)
+    }
+
+    /// Load a file into `mmap()`-allocated memory and ε-deserialize a data structure from it,
+    /// returning a [`MemCase`] containing the data structure and the
+    /// memory. Excess bytes are zeroed out.
+    ///
+    /// The behavior of `mmap()` can be modified by passing some [`Flags`]; otherwise,
+    /// just pass `Flags::empty()`.
+    #[cfg(feature = "mmap-rs")]
+    fn load_mmap<'a>(
+        path: impl AsRef<Path>,
+        flags: Flags,
+    ) -> Result<MemCase<<Self as DeserializeInner>::DeserType<'a>>> {
+        Self::load_mmap_with_hints(path, flags, MmapHints::new())
+    }
+
+    /// Like [`Deserialize::load_mmap`], but using the given [placement hints](MmapHints).
+    ///
+    /// If a NUMA node is suggested, the memory is bound to the node before
+    /// reading the file, so that all pages will be allocated on the node.
+    #[cfg(feature = "mmap-rs")]
+    fn load_mmap_with_hints<'a>(
+        path: impl AsRef<Path>,
+        flags: Flags,
+        hints: MmapHints,
+    ) -> Result<MemCase<<Self as DeserializeInner>::DeserType<'a>>> {
+        load_mmap_range_with_hints::<Self>(path.as_ref(), None, flags, hints)
+    }
+
+    /// Like [`Deserialize::load_mmap`], but loading only the `len` bytes
+    /// of the file starting at `offset`.
+    ///
+    /// This method makes it possible to load individually structures
+    /// serialized one after the other in the same file. Since
+    /// the data is copied into fresh memory, `offset` can be arbitrary.
+    #[cfg(feature = "mmap-rs")]
+    fn load_mmap_range<'a>(
+        path: impl AsRef<Path>,
+        offset: u64,
+        len: usize,
+        flags: Flags,
+    ) -> Result<MemCase<<Self as DeserializeInner>::DeserType<'a>>> {
+        load_mmap_range_with_hints::<Self>(
+            path.as_ref(),
+            Some((offset, len)),
+            flags,
+            MmapHints::new(),
+        )
+    }
+
+    /// Memory map a file and ε-deserialize a data structure from it,
+    /// returning a [`MemCase`] containing the data structure and the
+    /// memory mapping.
+    ///
+    /// The behavior of `mmap()` can be modified by passing some [`Flags`]; otherwise,
+    /// just pass `Flags::empty()`.
+    #[cfg(feature = "mmap-rs")]
+    fn mmap<'a>(
+        path: impl AsRef<Path>,
+        flags: Flags,
+    ) -> Result<MemCase<<Self as DeserializeInner>::DeserType<'a>>> {
+        Self::mmap_with_hints(path, flags, MmapHints::new())
+    }
+
+    /// Like [`Deserialize::mmap`], but using the given [placement hints](MmapHints).
+    #[cfg(feature = "mmap-rs")]
+    fn mmap_with_hints<'a>(
+        path: impl AsRef<Path>,
+        flags: Flags,
+        hints: MmapHints,
+    ) -> Result<MemCase<<Self as DeserializeInner>::DeserType<'a>>> {
+        mmap_range_with_hints::<Self>(path.as_ref(), None, flags, hints)
+    }
+
+    /// Like [`Deserialize::mmap`], but mapping only the `len` bytes
+    /// of the file starting at `offset`.
+    ///
+    /// This method makes it possible to map individually structures
+    /// serialized one after the other in the same file. The mapping
+    /// starts at the largest multiple of the
+    /// [allocation granularity](mmap_rs::MmapOptions::allocation_granularity)
+    /// not larger than `offset`, so zero-copy data will be properly aligned
+    /// only if `offset` is a multiple of 16 (or of the larger alignment required
+    /// by some `repr(align)` attribute); otherwise, [`Error::AlignmentError`]
+    /// will be returned.
+    #[cfg(feature = "mmap-rs")]
+    fn mmap_range<'a>(
+        path: impl AsRef<Path>,
+        offset: u64,
+        len: usize,
+        flags: Flags,
+    ) -> Result<MemCase<<Self as DeserializeInner>::DeserType<'a>>> {
+        mmap_range_with_hints::<Self>(path.as_ref(), Some((offset, len)), flags, MmapHints::new())
+    }
+}
+
+/// Return the offset and the length of the given range of a file, or of
+/// the whole file if no range is specified.
+#[cfg(feature = "mmap-rs")]
+fn file_range(path: &Path, range: Option<(u64, usize)>) -> Result<(u64, usize)> {
+    let file_len = path.metadata()?.len();
+    // Mapping an empty region fails with platform-dependent errors
+    if range.map_or(file_len, |(_, len)| len as u64) == 0 {
+        return Err(Error::EmptyMapping);
+    }
+    match range {
+        None => Ok((0, file_len as usize)),
+        Some((offset, len)) => {
+            if offset
+                .checked_add(len as u64)
+                .is_none_or(|end| end > file_len)
+            {
+                return Err(Error::RangeOutOfBounds {
+                    offset,
+                    len,
+                    file_len,
+                });
+            }
+            Ok((offset, len))
+        }
+    }
+}
+
+/// Implementation of [`Deserialize::load_mmap_with_hints`] and
+/// [`Deserialize::load_mmap_range`].
+#[cfg(feature = "mmap-rs")]
+fn load_mmap_range_with_hints<'a, T: Deserialize>(
+    path: &Path,
+    range: Option<(u64, usize)>,
+    flags: Flags,
+    hints: MmapHints,
+) -> Result<MemCase<<T as DeserializeInner>::DeserType<'a>>> {
+    use std::io::{Seek, SeekFrom};
+
+    let (offset, len) = file_range(path, range)?;
+    let mut file = std::fs::File::open(path).map_err(Error::FileOpenError)?;
+    file.seek(SeekFrom::Start(offset))?;
+    let capacity = len + crate::pad_align_to(len, 16);
+
+    let mut uninit: MaybeUninit<MemCase<<T as DeserializeInner>::DeserType<'_>>> =
+        MaybeUninit::uninit();
+    let ptr = uninit.as_mut_ptr();
+
+    let mut mmap = hints.map(
+        // Sharing anonymous memory would be pointless
+        || {
+            Ok(mmap_rs::MmapOptions::new(capacity)?
+                .with_flags(flags.difference(Flags::SHARED).mmap_flags()))
+        },
+        |options| options.map_mut(),
+    )?;
+    // Pages are not touched yet, so they will be allocated on the node
+    hints.bind(&mmap, false);
+    let (data, padding) = mmap.split_at_mut(len);
+    file.read_exact(data)?;
+    // Fixes the last few bytes to guarantee zero-extension semantics
+    // for bit vectors.
+    padding.fill(0);
+
+    let backend = MemBackend::Mmap(mmap.make_read_only().map_err(|(_, err)| err)?).created();
+
+    // store the backend inside the MemCase
+    unsafe {
+        addr_of_mut!((*ptr).1).write(backend);
+    }
+    // deserialize the data structure
+    let result = unsafe { (*ptr).1.bytes() }
+        .and_then(|mem| deserialize_eps_with_flags::<T>(&mut SliceWithPos::new(mem), flags));
+    let s = match result {
+        Ok(s) => s,
+        Err(err) => {
+            // Drop the backend, which would otherwise be leaked
+            unsafe { addr_of_mut!((*ptr).1).drop_in_place() };
+            return Err(err);
+        }
+    };
+    // write the deserialized struct in the MemCase
+    unsafe {
+        addr_of_mut!((*ptr).0).write(s);
+    }
+    // finish init
+    Ok(unsafe { uninit.assume_init() })
+}
+
+/// Implementation of [`Deserialize::mmap_with_hints`] and
+/// [`Deserialize::mmap_range`].
+#[cfg(feature = "mmap-rs")]
+fn mmap_range_with_hints<'a, T: Deserialize>(
+    path: &Path,
+    range: Option<(u64, usize)>,
+    flags: Flags,
+    hints: MmapHints,
+) -> Result<MemCase<<T as DeserializeInner>::DeserType<'a>>> {
+    let (offset, len) = file_range(path, range)?;
+    let file = flags.open(path)?;
+    // The offset of a mapping must be a multiple of the allocation granularity
+    let delta = (offset % mmap_rs::MmapOptions::allocation_granularity() as u64) as usize;
+
+    let mut uninit: MaybeUninit<MemCase<<T as DeserializeInner>::DeserType<'_>>> =
+        MaybeUninit::uninit();
+    let ptr = uninit.as_mut_ptr();
+
+    let mmap = hints.map(
+        || unsafe {
+            Ok(mmap_rs::MmapOptions::new(delta + len)?
+                .with_flags(flags.mmap_flags())
+                .with_file(&file, offset - delta as u64))
+        },
+        |options| options.map(),
+    )?;
+    hints.bind(&mmap, true);
+    // Best effort, as in the case of the flags applied by mmap-rs
+    mmap.advise(flags & Flags::WILL_NEED);
+
+    // store the backend inside the MemCase
+    unsafe {
+        addr_of_mut!((*ptr).1).write(MemBackend::Mmap(mmap).created());
+    }
+
+    // deserialize the data structure
+    let result = unsafe { (*ptr).1.bytes() }.and_then(|mmap| {
+        let mmap = mmap.get(delta..).ok_or(Error::ReadError)?;
+        deserialize_eps_with_flags::<T>(&mut SliceWithPos::new(mmap), flags)
+    });
+    let s = match result {
+        Ok(s) => s,
+        Err(err) => {
+            // Drop the backend, which would otherwise be leaked
+            unsafe { addr_of_mut!((*ptr).1).drop_in_place() };
+            return Err(err);
+        }
+    };
+    // write the deserialized struct in the MemCase
+    unsafe {
+        addr_of_mut!((*ptr).0).write(s);
+    }
+    // finish init
+    Ok(unsafe { uninit.assume_init() })
+}
+
+/// Inner trait to implement deserialization of a type. This trait exists
+/// to separate the user-facing [`Deserialize`] trait from the low-level
+/// deserialization mechanisms of [`DeserializeInner::_deserialize_full_inner`]
+/// and [`DeserializeInner::_deserialize_eps_inner`]. Moreover,
+/// it makes it possible to behave slighly differently at the top
+/// of the recursion tree (e.g., to check the endianness marker), and to prevent
+/// the user from modifying the methods in [`Deserialize`].
+///
+/// The user should not implement this trait directly, but rather derive it.
+/// Types that cannot be derived can be supported by hand using the stable
+/// extension API described in the [`crate::extending`] module.
+pub trait DeserializeInner: Sized {
+    /// The deserialization type associated with this type. It can be
+    /// retrieved conveniently with the alias [`DeserType`].
+    type DeserType<'a>;
+    fn _deserialize_full_inner(backend: &mut impl ReadWithPos) -> Result<Self>;
+
+    fn _deserialize_eps_inner<'a>(backend: &mut SliceWithPos<'a>) -> Result<Self::DeserType<'a>>;
+
+    /// Fully deserialize into an existing value, reusing its memory.
+    ///
+    /// The default implementation replaces the value with the result of
+    /// [`DeserializeInner::_deserialize_full_inner`]. Vectors and strings
+    /// reuse their buffers, and derived deep-copy structures deserialize
+    /// their fields in place. If an error is returned, the value is valid
+    /// but its content is unspecified.
+    fn _deserialize_full_into_inner(&mut self, backend: &mut impl ReadWithPos) -> Result<()> {
+        *self = Self::_deserialize_full_inner(backend)?;
+        Ok(())
+    }
+}
+
+/// Blanket implementation that prevents the user from overwriting the
+/// methods in [`Deserialize`].
+///
+/// This implementation [checks the header](`check_header`) written
+/// by the blanket implementation of [`crate::ser::Serialize`] and then delegates to
+/// [`DeserializeInner::_deserialize_full_inner`] or
+/// [`DeserializeInner::_deserialize_eps_inner`].
+impl<T: TypeHash + ReprHash + DeserializeInner + 'static> Deserialize for T {
+    fn deserialize_full(backend: &mut impl ReadNoStd) -> Result<Self> {
+        let mut backend = ReaderWithPos::new(backend);
+        deserialize_full_checked::<Self>(&mut backend)
+    }
+
+    fn deserialize_full_into(&mut self, backend: &mut impl ReadNoStd) -> Result<()> {
+        let mut backend = ReaderWithPos::new(backend);
+        check_full::<Self, _, _>(&mut backend, |backend| {
+            in_field(backend, "ROOT", |backend| {
+                self._deserialize_full_into_inner(backend)
+            })
+        })
+    }
+
+    fn deserialize_full_at(backend: &mut impl ReadNoStd, offset: usize) -> Result<Self> {
+        skip_bytes(backend, offset)?;
+        let mut backend = ReaderWithPos::with_initial_offset(backend, offset);
+        deserialize_full_checked::<Self>(&mut backend)
+    }
+
+    fn deserialize_full_with_context(
+        backend: &mut impl ReadNoStd,
+        context: Arc<Context>,
+    ) -> Result<Self> {
+        let mut backend = ReaderWithPos::new(backend);
+        backend.replace_context(Some(context));
+        deserialize_full_checked::<Self>(&mut backend)
+    }
+
+    fn deserialize_full_swapped(backend: &mut impl ReadNoStd) -> Result<Self> {
+        let mut backend = ReaderWithPos::new(backend);
+        backend.set_swap_bytes(true);
+        // The magic cookie is swapped, too, so the header check
+        // will accept only data with the opposite endianness
+        deserialize_full_checked::<Self>(&mut backend)
+    }
+
+    fn deserialize_full_with_paths(backend: &mut impl ReadNoStd) -> Result<Self> {
+        let mut backend = ReaderWithPos::new(backend);
+        backend.set_error_paths(true);
+        deserialize_full_checked::<Self>(&mut backend)
+    }
+
+    #[cfg(feature = "std")]
+    fn deserialize_full_compressed<C: crate::compress::Codec>(
+        backend: &mut impl ReadNoStd,
+    ) -> Result<Self> {
+        let mut backend = ReaderWithPos::new(backend);
+        read_header(&mut backend)?.check_codec::<Self>(Some(C::NAME))?;
+        in_field(&mut backend, "ROOT", |backend| {
+            crate::compress::deserialize_compressed::<C, Self>(backend)
+        })
+    }
+
+    fn deserialize_eps(backend: &'_ [u8]) -> Result<Self::DeserType<'_>> {
+        Self::deserialize_eps_with_policy(backend, StrPolicy::Trusted)
+    }
+
+    fn deserialize_eps_with_policy(
+        backend: &'_ [u8],
+        str_policy: StrPolicy,
+    ) -> Result<Self::DeserType<'_>> {
+        let mut backend = SliceWithPos::new(backend);
+        backend.set_str_policy(str_policy);
+        deserialize_eps_checked::<Self>(&mut backend, true)
+    }
+
+    fn deserialize_eps_untrusted(backend: &'_ [u8]) -> Result<Self::DeserType<'_>> {
+        let mut backend = SliceWithPos::new(backend);
+        backend.set_str_policy(StrPolicy::Validated);
+        backend.set_validation(Validation::Checked);
+        deserialize_eps_checked::<Self>(&mut backend, true)
+    }
+
+    fn deserialize_eps_with_paths(backend: &'_ [u8]) -> Result<Self::DeserType<'_>> {
+        let mut backend = SliceWithPos::new(backend);
+        backend.set_str_policy(StrPolicy::Validated);
+        backend.set_validation(Validation::Checked);
+        backend.set_error_paths(true);
+        deserialize_eps_checked::<Self>(&mut backend, true)
+    }
+
+    fn deserialize_eps_with_context(
+        backend: &'_ [u8],
+        context: Arc<Context>,
+    ) -> Result<Self::DeserType<'_>> {
+        let mut backend = SliceWithPos::new(backend);
+        backend.replace_context(Some(context));
+        deserialize_eps_checked::<Self>(&mut backend, true)
+    }
+
+    fn deserialize_eps_unverified(backend: &'_ [u8]) -> Result<Self::DeserType<'_>> {
+        deserialize_eps_checked::<Self>(&mut SliceWithPos::new(backend), false)
+    }
+
+    fn deserialize_full_compatible(backend: &[u8]) -> Result<Self> {
+        let mut backend = crate::compat::compatible_backend::<Self>(backend)?;
+        in_field(&mut backend, "ROOT", |backend| {
+            Self::_deserialize_full_inner(backend)
+        })
+    }
+
+    fn deserialize_eps_compatible(backend: &'_ [u8]) -> Result<Self::DeserType<'_>> {
+        let mut backend = crate::compat::compatible_backend::<Self>(backend)?;
+        in_field(&mut backend, "ROOT", |backend| {
+            Self::_deserialize_eps_inner(backend)
+        })
+    }
+}
+
+/// Check the header and fully deserialize the payload, verifying its
+/// [checksum](crate::ser::Serialize::serialize_with_checksum), if present.
+fn deserialize_full_checked<T: Deserialize>(
+    backend: &mut ReaderWithPos<'_, impl ReadNoStd>,
+) -> Result<T> {
+    check_full::<T, _, _>(backend, |backend| {
+        in_field(backend, "ROOT", |backend| {
+            T::_deserialize_full_inner(backend)
+        })
+    })
+}
+
+/// Check the header for type `T` and fully deserialize the payload using
+/// `deserialize`, verifying its
+/// [checksum](crate::ser::Serialize::serialize_with_checksum), if present.
+fn check_full<T: Deserialize, R, B: ReadNoStd>(
+    backend: &mut ReaderWithPos<'_, B>,
+    deserialize: impl FnOnce(&mut ReaderWithPos<'_, B>) -> Result<R>,
+) -> Result<R> {
+    let (type_hash, repr_hash) = type_hashes::<T>();
+    let header = read_header(backend)?;
+    let checksum = header.checksum;
+    header.check_raw(type_hash, repr_hash, core::any::type_name::<T>(), None)?;
+    let Some((expected, len)) = checksum else {
+        return deserialize(backend);
+    };
+    backend.start_checksum();
+    let start = backend.pos();
+    let value = deserialize(backend)?;
+    // The checksum covers also data following the structure, if any
+    skip_bytes(backend, len.saturating_sub(backend.pos() - start))?;
+    let got = backend.finish_checksum().ok_or(Error::ReadError)?;
+    if got != expected {
+        return Err(Error::ChecksumMismatch { expected, got });
+    }
+    Ok(value)
+}
+
+/// Check the header and ε-copy deserialize the payload, verifying its
+/// [checksum](crate::ser::Serialize::serialize_with_checksum), if present
+/// and `verify_checksum` is true.
+fn deserialize_eps_checked<'a, T: Deserialize>(
+    backend: &mut SliceWithPos<'a>,
+    verify_checksum: bool,
+) -> Result<T::DeserType<'a>> {
+    let (type_hash, repr_hash) = type_hashes::<T>();
+    check_eps_header(
+        backend,
+        type_hash,
+        repr_hash,
+        core::any::type_name::<T>(),
+        verify_checksum,
+    )?;
+    in_field(backend, "ROOT", |backend| {
+        T::_deserialize_eps_inner(backend)
+    })
+}
+
+/// ε-copy deserialize a structure from the given backend, verifying the
+/// [checksum](crate::ser::Serialize::serialize_with_checksum) unless `flags`
+/// contains [`Flags::SKIP_CHECKSUM`], validating strings and zero-copy
+/// values if `flags` contains [`Flags::UNTRUSTED`], and annotating errors
+/// with field paths if `flags` contains [`Flags::ERROR_PATHS`].
+#[cfg(feature = "std")]
+pub(crate) fn deserialize_eps_with_flags<'a, T: Deserialize>(
+    backend: &mut SliceWithPos<'a>,
+    flags: Flags,
+) -> Result<T::DeserType<'a>> {
+    if flags.contains(Flags::UNTRUSTED) {
+        backend.set_str_policy(StrPolicy::Validated);
+        backend.set_validation(Validation::Checked);
+    }
+    backend.set_error_paths(flags.contains(Flags::ERROR_PATHS));
+    deserialize_eps_checked::<T>(backend, !flags.contains(Flags::SKIP_CHECKSUM))
+}
+
+/// Check the header against the given type information, verifying the
+/// [checksum](crate::ser::Serialize::serialize_with_checksum) of the payload,
+/// if present, and the chunks of the [checksum tree](crate::merkle)
+/// containing the header, if present, when `verify_checksum` is true.
+///
+/// This function does not depend on the deserialized type, so it is
+/// instantiated only once.
+fn check_eps_header(
+    backend: &mut SliceWithPos<'_>,
+    type_hash: u64,
+    repr_hash: u64,
+    type_name: &str,
+    verify_checksum: bool,
+) -> Result<()> {
+    let data = backend.data;
+    let header = read_header(backend)?;
+    let checksum = header.checksum;
+    let chunk_checksums = header.chunk_checksums;
+    header.check_raw(type_hash, repr_hash, type_name, None)?;
+    if !verify_checksum {
+        return Ok(());
+    }
+    if let Some((expected, len)) = checksum {
+        let payload = backend.data.get(..len).ok_or(Error::ReadError)?;
+        let got = xxhash_rust::xxh3::xxh3_64(payload);
+        if got != expected {
+            return Err(Error::ChecksumMismatch { expected, got });
+        }
+    }
+    if chunk_checksums {
+        // The rest of the data is verified on demand
+        crate::merkle::ChunkVerifier::in_region(data, false)?
+            .ok_or(Error::MissingChunkChecksums)?
+            .verify_range(0..data.len() - backend.data.len())?;
+    }
+    Ok(())
+}
+
+/// Skip `bytes` bytes of a backend that cannot seek.
+fn skip_bytes(backend: &mut impl ReadNoStd, mut bytes: usize) -> Result<()> {
+    let mut buf = [0_u8; 4096];
+    while bytes > 0 {
+        let len = bytes.min(buf.len());
+        let (chunk, _) = buf.split_at_mut(len);
+        backend.read_exact(chunk)?;
+        bytes -= chunk.len();
+    }
+    Ok(())
+}
+
+/// Common header check code for both ε-copy and full-copy deserialization.
+///
+/// Must be kept in sync with [`crate::ser::write_header`].
+pub fn check_header<T: Deserialize>(backend: &mut impl ReadWithPos) -> Result<()> {
+    read_header(backend)?.check::<T>()
+}
+
+/// Check that `data` contains the serialization of a value of type `T`,
+/// checking the header and verifying the
+/// [checksum](crate::ser::Serialize::serialize_with_checksum) of the payload
+/// and all chunks of the [checksum tree](crate::merkle), if present, but
+/// without deserializing the payload.
+pub fn check_data<T: Deserialize>(data: &[u8]) -> Result<()> {
+    let (type_hash, repr_hash) = type_hashes::<T>();
+    check_eps_header(
+        &mut SliceWithPos::new(data),
+        type_hash,
+        repr_hash,
+        core::any::type_name::<T>(),
+        true,
+    )?;
+    if let Some(mut verifier) = crate::merkle::ChunkVerifier::new(data)? {
+        verifier.verify_all()?;
+    }
+    Ok(())
+}
+
+/// The information stored in the header of serialized data.
+///
+/// A header can be read without knowing the serialized type by
+/// [`read_header`], or, more conveniently, by the functions of the
+/// [`inspect`](crate::inspect) module.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Header {
+    /// The (major, minor) version of the file format.
+    pub version: (u16, u16),
+    /// The size in bytes of serialized `usize` and `isize` values.
+    pub usize_size: usize,
+    /// Whether the data is [portable](crate::ser::Serialize::serialize_portable).
+    pub portable: bool,
+    /// The type hash of the serialized type.
+    pub type_hash: u64,
+    /// The representation hash of the serialized type.
+    pub repr_hash: u64,
+    /// The name of the serialized type.
+    pub type_name: String,
+    /// The [signature](crate::ser::Serialize::serialize_with_signature) of the
+    /// serialized type, if present.
+    pub type_signature: Option<Vec<String>>,
+    /// The [checksum](crate::ser::Serialize::serialize_with_checksum) and the
+    /// length of the payload, if present.
+    pub checksum: Option<(u64, usize)>,
+    /// The name of the [codec](crate::compress::Codec) compressing the
+    /// payload, if any.
+    pub codec: Option<String>,
+    /// Whether the data is followed by a [checksum tree](crate::merkle).
+    pub chunk_checksums: bool,
+}
+
+impl core::fmt::Display for Header {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        writeln!(f, "Version: {}.{}", self.version.0, self.version.1)?;
+        writeln!(
+            f,
+            "Usize size: {}{}",
+            self.usize_size,
+            if self.portable { " (portable)" } else { "" }
+        )?;
+        writeln!(f, "Type name: {}", self.type_name)?;
+        writeln!(f, "Type hash: 0x{:016x}", self.type_hash)?;
+        writeln!(f, "Representation hash: 0x{:016x}", self.repr_hash)?;
+        if let Some(type_signature) = &self.type_signature {
+            writeln!(f, "Type signature: {:?}", type_signature)?;
+        }
+        if let Some((checksum, len)) = self.checksum {
+            writeln!(f, "Checksum: 0x{:016x} ({} bytes)", checksum, len)?;
+        }
+        if let Some(codec) = &self.codec {
+            writeln!(f, "Codec: {}", codec)?;
+        }
+        if self.chunk_checksums {
+            writeln!(f, "Checksum tree: yes")?;
+        }
+        Ok(())
+    }
+}
+
+impl Header {
+    /// Check that the payload is not compressed, and that the hashes in the
+    /// header match those of `T`.
+    pub(crate) fn check<T: Deserialize>(self) -> Result<()> {
+        self.check_codec::<T>(None)
+    }
+
+    /// Check that the payload is compressed with the given codec (or not
+    /// compressed, if `codec` is `None`), and that the hashes in the header
+    /// match those of `T`.
+    pub(crate) fn check_codec<T: Deserialize>(self, codec: Option<&'static str>) -> Result<()> {
+        let (type_hash, repr_hash) = type_hashes::<T>();
+        self.check_raw(type_hash, repr_hash, core::any::type_name::<T>(), codec)
+    }
+
+    /// Check the header against the given type information.
+    ///
+    /// This method does not depend on the deserialized type, so it is
+    /// instantiated only once.
+    fn check_raw(
+        self,
+        type_hash: u64,
+        repr_hash: u64,
+        type_name: &str,
+        codec: Option<&'static str>,
+    ) -> Result<()> {
+        if self.codec.as_deref() != codec {
+            return Err(Error::WrongCodec {
+                expected: self.codec,
+                got: codec,
+            });
+        }
+
+        if self.type_hash != type_hash {
+            return Err(Error::WrongTypeHash {
+                got_type_name: type_name.to_string(),
+                got: type_hash,
+                expected_type_name: self.type_name,
+                expected: self.type_hash,
+            });
+        }
+        // The representation hash does not depend on the pointer width (see
+        // repr_hash_of), so it is checked also for data written on an
+        // architecture with a different one.
+        if self.repr_hash != repr_hash {
+            return Err(Error::WrongTypeReprHash {
+                got_type_name: type_name.to_string(),
+                got: repr_hash,
+                expected_type_name: self.type_name,
+                expected: self.repr_hash,
+            });
+        }
+
+        Ok(())
+    }
+}
+
+/// Read the header of serialized data, checking everything but the type
+/// information.
+///
+/// This function makes it possible to validate and catalog serialized data
+/// without attempting a typed deserialization. An error is returned if the
+/// magic cookie is wrong (e.g., because the data has been written on an
+/// architecture with a different endianness), if the data has been written
+/// with an incompatible version of the file format, or if `usize` values
+/// cannot be read on the current architecture.
+///
+/// After the call, `backend` is positioned at the start of the payload.
+pub fn read_header(backend: &mut impl ReadWithPos) -> Result<Header> {
+    let magic = u64::_deserialize_full_inner(backend)?;
+    match magic {
+        MAGIC => Ok(()),
+        MAGIC_REV => Err(Error::EndiannessError),
+        magic => Err(Error::MagicCookieError(magic)),
+    }?;
+
+    let major = u16::_deserialize_full_inner(backend)?;
+    if major != VERSION.0 {
+        return Err(Error::MajorVersionMismatch(major));
+    }
+    let minor = u16::_deserialize_full_inner(backend)?;
+    if minor > VERSION.1 {
+        return Err(Error::MinorVersionMismatch(minor));
+    };
+
+    let usize_size = u8::_deserialize_full_inner(backend)?;
+    let portable = usize_size & PORTABLE_FLAG != 0;
+    let has_checksum = usize_size & CHECKSUM_FLAG != 0;
+    let compressed = usize_size & COMPRESSED_FLAG != 0;
+    let has_signature = usize_size & SIGNATURE_FLAG != 0;
+    let chunk_checksums = usize_size & CHUNK_CHECKSUMS_FLAG != 0;
+    let usize_size = (usize_size
+        & !(PORTABLE_FLAG
+            | CHECKSUM_FLAG
+            | COMPRESSED_FLAG
+            | SIGNATURE_FLAG
+            | CHUNK_CHECKSUMS_FLAG)) as usize;
+    // Data written on 32-bit or 64-bit architectures can be read on
+    // architectures with a different pointer width, provided that it
+    // contains no width-dependent zero-copy data (see MaxSizeOf::WIDTH_DEPENDENT)
+    if usize_size != core::mem::size_of::<usize>() && usize_size != 4 && usize_size != 8 {
+        return Err(Error::UsizeSizeMismatch(usize_size));
+    };
+    backend.set_usize_size(usize_size);
+    // The backend might not support a different size (see ReadWithPos::set_usize_size)
+    if backend.usize_size() != usize_size {
+        return Err(Error::UsizeSizeMismatch(usize_size));
+    }
+
+    let type_hash = u64::_deserialize_full_inner(backend)?;
+    let repr_hash = u64::_deserialize_full_inner(backend)?;
+    let type_name = String::_deserialize_full_inner(backend)?;
+    let type_signature = if has_signature {
+        Some(Vec::<String>::_deserialize_full_inner(backend)?)
+    } else {
+        None
+    };
+    let checksum = if has_checksum {
+        let checksum = u64::_deserialize_full_inner(backend)?;
+        let len = u64::_deserialize_full_inner(backend)?;
+        Some((
+            checksum,
+            usize::try_from(len).map_err(|_| Error::ReadError)?,
+        ))
+    } else {
+        None
+    };
+    let codec = if compressed {
+        Some(String::_deserialize_full_inner(backend)?)
+    } else {
+        None
+    };
+
+    Ok(Header {
+        version: (major, minor),
+        usize_size,
+        portable,
+        type_hash,
+        repr_hash,
+        type_name,
+        type_signature,
+        checksum,
+        codec,
+        chunk_checksums,
+    })
+}
+
+/// A helper trait that makes it possible to implement differently
+/// deserialization for [`crate::traits::ZeroCopy`] and [`crate::traits::DeepCopy`] types.
+/// See [`crate::traits::CopyType`] for more information.
+pub trait DeserializeHelper<T: CopySelector> {
+    type FullType;
+    type DeserType<'a>;
+
+    fn _deserialize_full_inner_impl(backend: &mut impl ReadWithPos) -> Result<Self::FullType>;
+
+    fn _deserialize_eps_inner_impl<'a>(
+        backend: &mut SliceWithPos<'a>,
+    ) -> Result<Self::DeserType<'a>>;
+
+    /// Fully deserialize into an existing value, reusing its memory.
+    ///
+    /// See [`DeserializeInner::_deserialize_full_into_inner`].
+    fn _deserialize_full_into_inner_impl(
+        value: &mut Self::FullType,
+        backend: &mut impl ReadWithPos,
+    ) -> Result<()> {
+        *value = Self::_deserialize_full_inner_impl(backend)?;
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+/// Errors that can happen during deserialization.
+///
+/// New variants might be added in minor releases: use
+/// [`Error::root_cause`] to match on the underlying error of errors
+/// annotated with a field path.
+#[non_exhaustive]
+pub enum Error {
+    /// The file to load or map could not be opened.
+    #[cfg(feature = "std")]
+    FileOpenError(std::io::Error),
+    /// An I/O error happened while loading a file in memory.
+    #[cfg(feature = "std")]
+    IoError(std::io::Error),
+    /// An error happened while memory-mapping a file.
+    #[cfg(feature = "mmap-rs")]
+    MmapError(mmap_rs::Error),
+    /// The size and alignment of the memory needed to load a file do not
+    /// form a valid layout.
+    LayoutError(core::alloc::LayoutError),
+    /// The memory region provided for loading is too short.
+    RegionTooShort { len: usize, needed: usize },
+    /// An empty file or range cannot be memory-mapped.
+    #[cfg(feature = "mmap-rs")]
+    EmptyMapping,
+    /// The range of a file to map exceeds the length of the file.
+    #[cfg(feature = "mmap-rs")]
+    RangeOutOfBounds {
+        offset: u64,
+        len: usize,
+        file_len: u64,
+    },
+    /// A [`MemBackend`] without a memory region was used where one is
+    /// needed.
+    NoBackend,
+    /// The underlying reader returned an error.
+    ReadError,
+    /// The file is from ε-serde but the endianess is wrong.
+    EndiannessError,
+    /// Some fields are not properly aligned.
+    AlignmentError,
+    /// The file was serialized with a version of ε-serde that is not compatible.
+    MajorVersionMismatch(u16),
+    /// The file was serialized with a compatible, but too new version of ε-serde
+    /// so we might be missing features.
+    MinorVersionMismatch(u16),
+    /// The the `pointer_width` of the serialized file is different from the
+    /// `pointer_width` of the current architecture, and either it is neither
+    /// 32 nor 64 bits, or the data contains zero-copy values whose
+    /// representation depends on it (see [`MaxSizeOf::WIDTH_DEPENDENT`]).
+    /// For example, the file was serialized on a 64-bit machine and we are
+    /// trying to deserialize a `Vec<usize>` on a 32-bit machine.
+    UsizeSizeMismatch(usize),
+    /// A `usize` or `isize` value written on an architecture with a different
+    /// pointer width does not fit the native type.
+    UsizeOutOfRange(i128),
+    /// The magic coookie is wrong. The byte sequence does not come from ε-serde.
+    MagicCookieError(u64),
+    /// A tag is wrong (e.g., for [`Option`]).
+    InvalidTag(usize),
+    /// A value is not valid for the type with the given name (e.g., it is
+    /// zero for a [nonzero integer](core::num::NonZeroUsize)).
+    InvalidValue(&'static str),
+    /// The endianness of a zero-copy type with the given name cannot be
+    /// reversed (e.g., because it is an enum or a union).
+    ByteSwapUnsupported(&'static str),
+    /// The type hash is wrong. Probably the user is trying to deserialize a
+    /// file with the wrong type.
+    WrongTypeHash {
+        got_type_name: String,
+        expected_type_name: String,
+        expected: u64,
+        got: u64,
+    },
+    /// The type representation hash is wrong. Probabliy the user is trying to
+    /// deserialize a file with some zero-copy type that has different
+    /// in-memory representations on the serialization arch and on the current one,
+    /// usually because of alignment issues.
+    WrongTypeReprHash {
+        got_type_name: String,
+        expected_type_name: String,
+        expected: u64,
+        got: u64,
+    },
+    /// The schema embedded in the data is not
+    /// [structurally compatible](crate::compat) with the deserialized type.
+    IncompatibleSchema { field: String, reason: String },
+    /// The shape described by the schema embedded in the data is nested more
+    /// deeply than [`MAX_SCHEMA_DEPTH`](crate::compat::MAX_SCHEMA_DEPTH).
+    SchemaTooDeep { max_depth: usize },
+    /// A [schema](crate::ser::Schema) passed to a function does not describe
+    /// the data it has been used with (e.g., by
+    /// [`transform_field`](crate::transform::transform_field)).
+    #[cfg(feature = "std")]
+    SchemaMismatch { field: String, reason: String },
+    /// A piece of data described by a [schema](crate::ser::Schema) was read
+    /// by a [dynamic value](crate::dynamic::DynamicValue) using a type
+    /// different from the one recorded in the schema.
+    WrongSchemaType {
+        field: String,
+        ty: String,
+        requested: &'static str,
+    },
+    /// A string is not valid UTF-8.
+    InvalidUtf8,
+    /// The [checksum](crate::ser::Serialize::serialize_with_checksum) of the
+    /// payload does not match the one stored in the header.
+    ChecksumMismatch { expected: u64, got: u64 },
+    /// The checksum of the given chunk of data, or of a node on the path
+    /// from the chunk to the root of a [checksum tree](crate::merkle), does
+    /// not match the one stored in the tree.
+    ChunkChecksumMismatch {
+        chunk: usize,
+        expected: u64,
+        got: u64,
+    },
+    /// The header states that the data is followed by a
+    /// [checksum tree](crate::merkle), but the tree is missing (e.g.,
+    /// because the data has been truncated).
+    MissingChunkChecksums,
+    /// The files of a [snapshot](crate::snapshot) do not match its manifest
+    /// (e.g., because a commit has been interrupted).
+    #[cfg(feature = "std")]
+    InconsistentSnapshot(String),
+    /// A [`Resolver`](crate::stable_id::Resolver) does not contain a
+    /// structure with the given identifier.
+    UnknownStableId(u128),
+    /// The structure referenced by a [`StableId`](crate::stable_id::StableId)
+    /// has a type hash different from the expected one.
+    WrongStableIdType { id: u128, expected: u64, got: u64 },
+    /// The payload is [compressed](crate::ser::Serialize::serialize_compressed)
+    /// with a codec different from the one used for deserialization (`None`
+    /// means that the payload is not compressed).
+    WrongCodec {
+        expected: Option<String>,
+        got: Option<&'static str>,
+    },
+    /// An error happened while deserializing the field with the given path
+    /// (e.g., `ROOT.nodes[3].label`), which starts at the given byte offset
+    /// of the data.
+    ///
+    /// The path uses the field names of the [schema](crate::ser::Schema),
+    /// with indices of items of sequences in square brackets. Offsets
+    /// of fields inside [compressed](crate::compress) data refer to the
+    /// decompressed data.
+    ///
+    /// This variant is returned only by backends that
+    /// [require it](ReadWithPos::error_paths) (e.g., by
+    /// [`Deserialize::deserialize_full_with_paths`],
+    /// [`Deserialize::deserialize_eps_with_paths`], or with
+    /// [`Flags::ERROR_PATHS`]). Use [`Error::root_cause`] to match on the
+    /// underlying error.
+    InField {
+        path: String,
+        offset: usize,
+        error: Box<Error>,
+    },
+}
+
+impl Error {
+    /// Add to the error the name of the field being deserialized, which
+    /// starts at the given byte offset.
+    ///
+    /// The name is prepended to the current path, if any, but the offset
+    /// of the innermost field is kept.
+    pub fn in_field(self, name: &str, offset: usize) -> Self {
+        self.prepend(name, offset)
+    }
+
+    /// Add to the error the index of the item of a sequence being
+    /// deserialized, which starts at the given byte offset.
+    pub fn in_item(self, index: usize, offset: usize) -> Self {
+        self.prepend(&format!("[{}]", index), offset)
+    }
+
+    fn prepend(self, segment: &str, offset: usize) -> Self {
+        match self {
+            Self::InField {
+                path,
+                offset,
+                error,
+            } => {
+                let sep = if path.starts_with('[') { "" } else { "." };
+                Self::InField {
+                    path: format!("{}{}{}", segment, sep, path),
+                    offset,
+                    error,
+                }
+            }
+            error => Self::InField {
+                path: segment.to_string(),
+                offset,
+                error: Box::new(error),
+            },
+        }
+    }
+
+    /// Return the path of the field whose deserialization caused the
+    /// error, if known.
+    pub fn path(&self) -> Option<&str> {
+        match self {
+            Self::InField { path, .. } => Some(path),
+            _ => None,
+        }
+    }
+
+    /// Return the byte offset of the field whose deserialization caused the
+    /// error, if known.
+    pub fn offset(&self) -> Option<usize> {
+        match self {
+            Self::InField { offset, .. } => Some(*offset),
+            _ => None,
+        }
+    }
+
+    /// Return the underlying error, without the path and offset of
+    /// [`Error::InField`].
+    pub fn root_cause(&self) -> &Self {
+        match self {
+            Self::InField { error, .. } => error,
+            error => error,
+        }
+    }
+
+    /// Return the underlying error by value, without the path and offset of
+    /// [`Error::InField`].
+    pub fn into_root_cause(self) -> Self {
+        match self {
+            Self::InField { error, .. } => *error,
+            error => error,
+        }
+    }
+}
+
+impl core::error::Error for Error {}
+
+#[cfg(feature = "std")]
+impl From<std::io::Error> for Error {
+    fn from(error: std::io::Error) -> Self {
+        Self::IoError(error)
+    }
+}
+
+#[cfg(feature = "mmap-rs")]
+impl From<mmap_rs::Error> for Error {
+    fn from(error: mmap_rs::Error) -> Self {
+        Self::MmapError(error)
+    }
+}
+
+impl From<core::alloc::LayoutError> for Error {
+    fn from(error: core::alloc::LayoutError) -> Self {
+        Self::LayoutError(error)
+    }
+}
+
+impl core::fmt::Display for Error {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        match self {
+            Self::ReadError => write!(f, "Read error during ε-serde deserialization"),
+            #[cfg(feature = "std")]
+            Self::FileOpenError(error) => {
+                write!(f, "Error opening file during ε-serde deserialization: {}", error)
+            }
+            #[cfg(feature = "std")]
+            Self::IoError(error) => {
+                write!(f, "I/O error during ε-serde deserialization: {}", error)
+            }
+            #[cfg(feature = "mmap-rs")]
+            Self::MmapError(error) => {
+                write!(f, "Memory-mapping error during ε-serde deserialization: {}", error)
+            }
+            Self::LayoutError(error) => write!(f, "Invalid memory layout: {}", error),
+            Self::RegionTooShort { len, needed } => write!(
+                f,
+                "The memory region has length {}, but at least {} bytes are needed",
+                len, needed
+            ),
+            #[cfg(feature = "mmap-rs")]
+            Self::EmptyMapping => write!(f, "Cannot map an empty region"),
+            #[cfg(feature = "mmap-rs")]
+            Self::RangeOutOfBounds {
+                offset,
+                len,
+                file_len,
+            } => write!(
+                f,
+                "The range of {} bytes starting at {} exceeds the length {} of the file",
+                len, offset, file_len
+            ),
+            Self::NoBackend => write!(f, "The backend has no memory region"),
+            Self::EndiannessError => write!(
+                f,
+                "The current arch is {}-endian but the data is {}-endian.",
+                if cfg!(target_endian = "little") {
+                    "little"
+                } else {
+                    "big"
+                },
+                if cfg!(target_endian = "little") {
+                    "big"
+                } else {
+                    "little"
+                }
+            ),
+            Self::MagicCookieError(magic) => write!(
+                f,
+                "Wrong magic cookie 0x{:016x}. The byte stream does not come from ε-serde.",
+                magic,
+            ),
+            Self::MajorVersionMismatch(found_major) => write!(
+                f,
+                "Major version mismatch. Expected {} but got {}.",
+                VERSION.0, found_major,
+            ),
+            Self::MinorVersionMismatch(found_minor) => write!(
+                f,
+                "Minor version mismatch. Expected {} but got {}.",
+                VERSION.1, found_minor,
+            ),
+            Self::UsizeSizeMismatch(usize_size) => write!(
+                f,
+                "The file was serialized on an architecture where a usize has size {}, but on the current architecture it has size {}.",
+                usize_size,
+                core::mem::size_of::<usize>()
+            ),
+            Self::UsizeOutOfRange(value) => write!(
+                f,
+                "The value {} does not fit in a {}-byte usize or isize.",
+                value,
+                core::mem::size_of::<usize>()
+            ),
+            Self::AlignmentError => write!(f, "Alignment error. Most likely you are deserializing from a memory region with insufficient alignment."),
+            Self::InvalidTag(tag) => write!(f, "Invalid tag: 0x{:02x}", tag),
+            Self::InvalidValue(type_name) => write!(f, "Invalid value for type {}", type_name),
+            Self::InvalidUtf8 => write!(f, "A string does not contain valid UTF-8."),
+            Self::ChecksumMismatch { expected, got } => write!(
+                f,
+                "Checksum mismatch. Expected: 0x{:016x} Actual: 0x{:016x}. The data is corrupted.",
+                expected, got
+            ),
+            Self::ChunkChecksumMismatch {
+                chunk,
+                expected,
+                got,
+            } => write!(
+                f,
+                "Checksum mismatch for chunk {}. Expected: 0x{:016x} Actual: 0x{:016x}. The data is corrupted.",
+                chunk, expected, got
+            ),
+            Self::MissingChunkChecksums => write!(
+                f,
+                "The header states that the data is followed by a checksum tree, but the tree is missing"
+            ),
+            #[cfg(feature = "std")]
+            Self::InconsistentSnapshot(reason) => {
+                write!(f, "Inconsistent snapshot: {}.", reason)
+            }
+            Self::UnknownStableId(id) => write!(f, "Unknown stable id 0x{:032x}.", id),
+            Self::WrongStableIdType { id, expected, got } => write!(
+                f,
+                "Wrong type hash for stable id 0x{:032x}. Expected: 0x{:016x} Actual: 0x{:016x}.",
+                id, expected, got
+            ),
+            Self::WrongCodec { expected, got } => write!(
+                f,
+                "Codec mismatch. Expected: {} Actual: {}.",
+                expected.as_deref().unwrap_or("none"),
+                got.unwrap_or("none")
+            ),
+            Self::ByteSwapUnsupported(type_name) => write!(
+                f,
+                "Cannot reverse the endianness of the zero-copy type {}.",
+                type_name
+            ),
+            Self::WrongTypeHash {
+                got_type_name,
+                expected_type_name,
+                expected,
+                got,
+            } => {
+                write!(
+                    f,
+                    concat!(
+                        "Wrong type hash. Expected: 0x{:016x} Actual: 0x{:016x}.\n",
+                        "You are trying to deserialize a file with the wrong type.\n",
+                        "The serialized type is '{}' and the deserialized type is '{}'.",
+                    ),
+                    expected, got, expected_type_name, got_type_name,
+                )
+            },
+            Self::WrongTypeReprHash {
+                got_type_name,
+                expected_type_name,
+                expected,
+                got,
+            } => {
+                write!(
+                    f,
+                    concat!(
+                        "Wrong type repr hash. Expected: 0x{:016x} Actual: 0x{:016x}.\n",
+                        "You might be trying to deserialize a file that was serialized on ",
+                        "an architecture with different alignment requirements, or some ",
+                        "of the fields of the type have changed their copy type (zero or deep).\n",
+                        "The serialized type is '{}' and the deserialized type is '{}'.",
+                    ),
+                    expected, got, expected_type_name, got_type_name,
+                )
+            }
+            Self::IncompatibleSchema { field, reason } => write!(
+                f,
+                "The embedded schema is incompatible with the deserialized type at {}: {}.",
+                field, reason
+            ),
+            Self::SchemaTooDeep { max_depth } => write!(
+                f,
+                "The embedded schema is nested more than {} levels deep.",
+                max_depth
+            ),
+            #[cfg(feature = "std")]
+            Self::SchemaMismatch { field, reason } => write!(
+                f,
+                "The schema does not describe the data at {}: {}.",
+                field, reason
+            ),
+            Self::WrongSchemaType {
+                field,
+                ty,
+                requested,
+            } => write!(
+                f,
+                "The field {} has type {} in the schema, but it was read as {}.",
+                field, ty, requested
+            ),
+            Self::InField {
+                path,
+                offset,
+                error,
+            } => write!(f, "{} (field {} at offset {})", error, path, offset),
+        }
+    }
+}
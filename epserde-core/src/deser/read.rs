@@ -0,0 +1,118 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ * SPDX-FileCopyrightText: 2023 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+/*!
+
+No-std support for reading while keeping track of the current position.
+
+ */
+
+use crate::prelude::*;
+
+#[cfg(all(feature = "alloc", not(feature = "std")))]
+use alloc::sync::Arc;
+#[cfg(feature = "std")]
+use std::sync::Arc;
+
+/// [`std::io::Read`]-like trait for serialization that does not
+/// depend on [`std`].
+///
+/// In an [`std`] context, the user does not need to use directly
+/// this trait as we provide a blanket
+/// implementation that implements [`ReadNoStd`] for all types that implement
+/// [`std::io::Read`]. In particular, in such a context you can use [`std::io::Cursor`]
+/// for in-memory deserialization.
+pub trait ReadNoStd {
+    /// Read some bytes
+    fn read_exact(&mut self, buf: &mut [u8]) -> deser::Result<()>;
+}
+
+#[cfg(feature = "std")]
+use std::io::Read;
+#[cfg(feature = "std")]
+impl<W: Read> ReadNoStd for W {
+    #[inline(always)]
+    fn read_exact(&mut self, buf: &mut [u8]) -> deser::Result<()> {
+        Read::read_exact(self, buf).map_err(|_| deser::Error::ReadError)
+    }
+}
+
+/// A trait for [`ReadNoStd`] that also keeps track of the current position.
+///
+/// This is needed because the [`Read`] trait doesn't have a `seek` method and
+/// [`std::io::Seek`] would be a requirement much stronger than needed.
+pub trait ReadWithPos: ReadNoStd + Sized {
+    /// Return the current position.
+    fn pos(&self) -> usize;
+
+    /// Pad the cursor to the next multiple of [`MaxSizeOf::max_size_of`] 'T'.
+    fn align<T: MaxSizeOf>(&mut self) -> deser::Result<()>;
+
+    /// Return the size in bytes of serialized `usize` and `isize` values.
+    ///
+    /// This is the native size, unless we are reading data written on an
+    /// architecture with a different pointer width (e.g.,
+    /// [portable](crate::ser::Serialize::serialize_portable) data).
+    ///
+    /// The default implementation returns the native size.
+    fn usize_size(&self) -> usize {
+        core::mem::size_of::<usize>()
+    }
+
+    /// Set the size in bytes of serialized `usize` and `isize` values.
+    ///
+    /// This method is called by [`check_header`](crate::deser::check_header) when reading the header.
+    ///
+    /// The default implementation does nothing: backends using the default
+    /// implementations of this method and of [`ReadWithPos::usize_size`]
+    /// can read only data written on an architecture with the same pointer
+    /// width, and [`read_header`](crate::deser::read_header) returns
+    /// [`Error::UsizeSizeMismatch`](crate::deser::Error::UsizeSizeMismatch)
+    /// otherwise.
+    fn set_usize_size(&mut self, _usize_size: usize) {}
+
+    /// Return whether the data has the opposite endianness of the current
+    /// architecture, and thus the bytes of every value read must be swapped.
+    ///
+    /// The default implementation returns false.
+    fn swap_bytes(&self) -> bool {
+        false
+    }
+
+    /// Return whether errors must be annotated with the path and the offset
+    /// of the field being deserialized (see
+    /// [`Error::InField`](deser::Error::InField)).
+    ///
+    /// The default implementation returns false.
+    fn error_paths(&self) -> bool {
+        false
+    }
+
+    /// Return the deserialization [context](deser::Context), if any.
+    ///
+    /// The default implementation returns `None`.
+    fn context(&self) -> Option<&deser::Context> {
+        None
+    }
+
+    /// Replace the deserialization [context](deser::Context), returning
+    /// the previous one.
+    ///
+    /// The default implementation ignores the new context and returns `None`.
+    fn replace_context(
+        &mut self,
+        _context: Option<Arc<deser::Context>>,
+    ) -> Option<Arc<deser::Context>> {
+        None
+    }
+
+    /// Return the deserialization [context](deser::Context), if any and if
+    /// it has type `C`.
+    fn context_as<C: core::any::Any>(&self) -> Option<&C> {
+        self.context()?.downcast_ref()
+    }
+}
@@ -0,0 +1,158 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ * SPDX-FileCopyrightText: 2023 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+use crate::prelude::*;
+
+use super::{Context, ReadNoStd};
+use core::hash::Hasher;
+use xxhash_rust::xxh3::Xxh3;
+
+#[cfg(all(feature = "alloc", not(feature = "std")))]
+use alloc::sync::Arc;
+#[cfg(feature = "std")]
+use std::sync::Arc;
+
+/// A wrapper for a [`ReadNoStd`] that implements [`ReadWithPos`]
+/// by keeping track of the current position.
+pub struct ReaderWithPos<'a, F: ReadNoStd> {
+    /// What we actually readfrom
+    backend: &'a mut F,
+    /// How many bytes we have read from the start
+    pos: usize,
+    /// The position at which the ε-serde payload starts
+    initial_offset: usize,
+    /// Whether the data has the opposite endianness
+    swap_bytes: bool,
+    /// The size of serialized `usize` values
+    usize_size: usize,
+    /// Whether errors are annotated with field paths
+    error_paths: bool,
+    /// The hasher computing the checksum of the payload, if any
+    checksum: Option<Xxh3>,
+    /// The deserialization context
+    context: Option<Arc<Context>>,
+}
+
+impl<'a, F: ReadNoStd> ReaderWithPos<'a, F> {
+    #[inline(always)]
+    /// Create a new [`ReadWithPos`] on top of a generic [`ReadNoStd`].
+    pub fn new(backend: &'a mut F) -> Self {
+        Self::with_initial_offset(backend, 0)
+    }
+
+    #[inline(always)]
+    /// Create a new [`ReadWithPos`] on top of a generic [`ReadNoStd`]
+    /// that has already consumed `initial_offset` bytes.
+    ///
+    /// This is useful when the ε-serde payload is embedded in another
+    /// container (e.g., after a custom header): [`ReadWithPos::pos`]
+    /// will start from `initial_offset`, but alignment will be computed
+    /// relative to the start of the payload.
+    pub fn with_initial_offset(backend: &'a mut F, initial_offset: usize) -> Self {
+        Self {
+            backend,
+            pos: initial_offset,
+            initial_offset,
+            swap_bytes: false,
+            usize_size: core::mem::size_of::<usize>(),
+            error_paths: false,
+            checksum: None,
+            context: None,
+        }
+    }
+
+    #[inline(always)]
+    /// Set whether the data has the opposite endianness of the
+    /// current architecture.
+    ///
+    /// When set, all values will be byte-swapped after being read.
+    /// See [`ReadWithPos::swap_bytes`].
+    pub fn set_swap_bytes(&mut self, swap_bytes: bool) {
+        self.swap_bytes = swap_bytes;
+    }
+
+    #[inline(always)]
+    /// Set whether errors are annotated with the path and the offset of the
+    /// field being deserialized.
+    ///
+    /// See [`ReadWithPos::error_paths`].
+    pub fn set_error_paths(&mut self, error_paths: bool) {
+        self.error_paths = error_paths;
+    }
+
+    /// Return the position at which the ε-serde payload starts.
+    pub fn initial_offset(&self) -> usize {
+        self.initial_offset
+    }
+
+    /// Start computing the checksum of the bytes read from now on.
+    pub(crate) fn start_checksum(&mut self) {
+        self.checksum = Some(Xxh3::new());
+    }
+
+    /// Return the checksum of the bytes read since the last call to
+    /// [`start_checksum`](ReaderWithPos::start_checksum), and stop
+    /// computing it.
+    pub(crate) fn finish_checksum(&mut self) -> Option<u64> {
+        self.checksum.take().map(|hasher| hasher.finish())
+    }
+}
+
+impl<'a, F: ReadNoStd> ReadNoStd for ReaderWithPos<'a, F> {
+    fn read_exact(&mut self, buf: &mut [u8]) -> deser::Result<()> {
+        self.backend.read_exact(buf)?;
+        if let Some(hasher) = &mut self.checksum {
+            hasher.update(buf);
+        }
+        self.pos += buf.len();
+        Ok(())
+    }
+}
+
+impl<'a, F: ReadNoStd> ReadWithPos for ReaderWithPos<'a, F> {
+    fn pos(&self) -> usize {
+        self.pos
+    }
+
+    fn align<T: MaxSizeOf>(&mut self) -> deser::Result<()> {
+        // Skip bytes as needed, relative to the start of the payload
+        let mut padding = crate::pad_align_to(self.pos - self.initial_offset, T::max_size_of());
+        let mut buf = [0_u8; 64];
+        while padding > 0 {
+            let len = padding.min(buf.len());
+            let (chunk, _) = buf.split_at_mut(len);
+            self.read_exact(chunk)?;
+            padding -= chunk.len();
+        }
+        // No alignment check, we are fully deserializing
+        Ok(())
+    }
+
+    fn swap_bytes(&self) -> bool {
+        self.swap_bytes
+    }
+
+    fn usize_size(&self) -> usize {
+        self.usize_size
+    }
+
+    fn error_paths(&self) -> bool {
+        self.error_paths
+    }
+
+    fn set_usize_size(&mut self, usize_size: usize) {
+        self.usize_size = usize_size;
+    }
+
+    fn context(&self) -> Option<&Context> {
+        self.context.as_deref()
+    }
+
+    fn replace_context(&mut self, context: Option<Arc<Context>>) -> Option<Arc<Context>> {
+        core::mem::replace(&mut self.context, context)
+    }
+}
@@ -0,0 +1,203 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ * SPDX-FileCopyrightText: 2023 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+use super::*;
+use crate::prelude::*;
+
+#[cfg(all(feature = "alloc", not(feature = "std")))]
+use alloc::sync::Arc;
+#[cfg(feature = "std")]
+use std::sync::Arc;
+
+/// [`std::io::Cursor`]-like trait for deserialization that does not
+/// depend on [`std`].
+#[derive(Debug)]
+pub struct SliceWithPos<'a> {
+    pub data: &'a [u8],
+    pub pos: usize,
+    usize_size: usize,
+    /// The validation policy for strings.
+    str_policy: StrPolicy,
+    /// The validation of zero-copy values.
+    validation: Validation,
+    /// Whether errors are annotated with field paths.
+    error_paths: bool,
+    /// Ranges of positions to jump over, in decreasing order.
+    skips: Vec<(usize, usize)>,
+    /// The deserialization context.
+    context: Option<Arc<Context>>,
+}
+
+impl<'a> SliceWithPos<'a> {
+    pub fn new(backend: &'a [u8]) -> Self {
+        Self {
+            data: backend,
+            pos: 0,
+            usize_size: core::mem::size_of::<usize>(),
+            str_policy: StrPolicy::Trusted,
+            validation: Validation::Trusted,
+            error_paths: false,
+            skips: Vec::new(),
+            context: None,
+        }
+    }
+
+    /// Jump over the given ranges of positions as soon as the position
+    /// reaches their start.
+    ///
+    /// This method is used by [structural compatibility checks](crate::compat)
+    /// to ignore fields that are unknown to the type being deserialized.
+    /// The ranges must be disjoint and sorted in increasing order.
+    pub(crate) fn set_skips(&mut self, mut skips: Vec<(usize, usize)>) {
+        skips.reverse();
+        self.skips = skips;
+        self.jump();
+    }
+
+    /// Return a new [`SliceWithPos`] on `data`, which starts at position `pos`,
+    /// with the same settings and context of this one.
+    ///
+    /// This method makes it possible to ε-copy deserialize lazily parts of
+    /// the data (see [`IndexedView`](crate::indexed::IndexedView)).
+    pub(crate) fn fork(&self, data: &'a [u8], pos: usize) -> Self {
+        Self {
+            data,
+            pos,
+            usize_size: self.usize_size,
+            str_policy: self.str_policy,
+            validation: self.validation,
+            error_paths: self.error_paths,
+            skips: Vec::new(),
+            context: self.context.clone(),
+        }
+    }
+
+    /// Return the [validation policy](StrPolicy) for strings.
+    pub fn str_policy(&self) -> StrPolicy {
+        self.str_policy
+    }
+
+    /// Set the [validation policy](StrPolicy) for strings.
+    pub fn set_str_policy(&mut self, str_policy: StrPolicy) {
+        self.str_policy = str_policy;
+    }
+
+    /// Return the [validation](Validation) of zero-copy values.
+    pub fn validation(&self) -> Validation {
+        self.validation
+    }
+
+    /// Set the [validation](Validation) of zero-copy values.
+    pub fn set_validation(&mut self, validation: Validation) {
+        self.validation = validation;
+    }
+
+    /// Set whether errors are annotated with the path and the offset of the
+    /// field being deserialized (see [`ReadWithPos::error_paths`]).
+    pub fn set_error_paths(&mut self, error_paths: bool) {
+        self.error_paths = error_paths;
+    }
+
+    /// Skip the given number of bytes.
+    ///
+    /// Return [`Error::ReadError`] if there are not enough bytes.
+    pub fn skip(&mut self, bytes: usize) -> deser::Result<()> {
+        self.take(bytes).map(|_| ())
+    }
+
+    /// Return the given number of bytes, skipping them.
+    ///
+    /// The bytes are borrowed from the underlying data, so this method
+    /// can be used to ε-copy deserialize raw byte ranges without copying
+    /// them (e.g., in a function specified by the `deser_with` attribute
+    /// of the [derive macro](https://docs.rs/epserde-derive/latest/epserde_derive/derive.Epserde.html)).
+    ///
+    /// Return [`Error::ReadError`] if there are not enough bytes.
+    pub fn take(&mut self, bytes: usize) -> deser::Result<&'a [u8]> {
+        let Some((head, tail)) = self.data.split_at_checked(bytes) else {
+            return Err(Error::ReadError);
+        };
+        self.data = tail;
+        self.pos += bytes;
+        self.jump();
+        Ok(head)
+    }
+
+    /// Pad the cursor to the given alignment, checking that the
+    /// absolute memory position is properly aligned.
+    ///
+    /// This is the non-generic implementation of [`ReadWithPos::align`].
+    pub(crate) fn align_to(&mut self, align: usize) -> deser::Result<()> {
+        // Skip bytes as needed
+        let padding = crate::pad_align_to(self.pos, align);
+        self.skip(padding)?;
+        // Check that the ptr is indeed aligned (zero-sized types have
+        // alignment zero)
+        if !(self.data.as_ptr() as usize).is_multiple_of(align.max(1)) {
+            Err(Error::AlignmentError)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Apply the ranges set with [`set_skips`](SliceWithPos::set_skips).
+    #[inline(always)]
+    fn jump(&mut self) {
+        while let Some(&(start, end)) = self.skips.last() {
+            if self.pos < start {
+                break;
+            }
+            if self.pos == start {
+                // Skips past the end of the data make further reads fail
+                self.data = self.data.get(end - start..).unwrap_or_default();
+                self.pos = end;
+            }
+            self.skips.pop();
+        }
+    }
+}
+
+impl<'a> ReadNoStd for SliceWithPos<'a> {
+    fn read_exact(&mut self, buf: &mut [u8]) -> deser::Result<()> {
+        buf.copy_from_slice(self.take(buf.len())?);
+        Ok(())
+    }
+}
+
+impl<'a> ReadWithPos for SliceWithPos<'a> {
+    fn pos(&self) -> usize {
+        self.pos
+    }
+
+    /// Pad the cursor to the correct alignment.
+    ///
+    /// Note that this method also checks that
+    /// the absolute memory position is properly aligned.
+    fn align<T: MaxSizeOf>(&mut self) -> deser::Result<()> {
+        self.align_to(T::max_size_of())
+    }
+
+    fn usize_size(&self) -> usize {
+        self.usize_size
+    }
+
+    fn error_paths(&self) -> bool {
+        self.error_paths
+    }
+
+    fn set_usize_size(&mut self, usize_size: usize) {
+        self.usize_size = usize_size;
+    }
+
+    fn context(&self) -> Option<&Context> {
+        self.context.as_deref()
+    }
+
+    fn replace_context(&mut self, context: Option<Arc<Context>>) -> Option<Arc<Context>> {
+        core::mem::replace(&mut self.context, context)
+    }
+}
@@ -0,0 +1,248 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ * SPDX-FileCopyrightText: 2023 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+/*!
+
+A cache sharing the ε-copy deserialized structures loaded from the same file.
+
+*/
+
+use super::*;
+use core::any::{Any, TypeId};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex, OnceLock, Weak};
+use std::time::SystemTime;
+
+/// The method used to load a file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum LoadMode {
+    Mem,
+    #[cfg(feature = "mmap-rs")]
+    LoadMmap(Flags),
+    #[cfg(feature = "mmap-rs")]
+    Mmap(Flags),
+}
+
+type Case = Weak<dyn Any + Send + Sync>;
+
+/// The metadata identifying a version of a file.
+///
+/// Files replaced atomically by renaming (e.g., by [`crate::save`] or by a
+/// [`SnapshotWriter`](crate::snapshot::SnapshotWriter)) might have the same
+/// modification time as the previous version, as its granularity depends
+/// on the filesystem, so we also use the length and, on Unix, the device
+/// and inode numbers, which change when a file is replaced.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct FileVersion {
+    modified: SystemTime,
+    len: u64,
+    id: Option<(u64, u64)>,
+}
+
+impl FileVersion {
+    fn new(metadata: &std::fs::Metadata) -> Result<Self> {
+        #[cfg(unix)]
+        let id = {
+            use std::os::unix::fs::MetadataExt;
+            Some((metadata.dev(), metadata.ino()))
+        };
+        #[cfg(not(unix))]
+        let id = None;
+        Ok(Self {
+            modified: metadata.modified()?,
+            len: metadata.len(),
+            id,
+        })
+    }
+}
+
+/// The structures loaded from a given version of a file.
+struct PathEntry {
+    version: FileVersion,
+    cases: HashMap<(TypeId, LoadMode), Case>,
+}
+
+/// A cache of [`MemCase`] instances keyed by canonical path and
+/// version of the file.
+///
+/// The loading methods of a `SnapshotCache` return an [`Arc`] of a
+/// [`MemCase`]: if a structure of the same type has already been loaded from
+/// the same file in the same way, and it is still in use, the same [`Arc`]
+/// is returned, so subsystems opening the same dataset share one mapping
+/// (or one allocation) instead of mapping the file several times.
+///
+/// The cache has two levels: the first one is keyed by the canonical path of
+/// the file, and the second one by the deserialized type and the loading
+/// method. If the modification time, the length, or (on Unix) the inode of
+/// a file change, all structures loaded from the file are forgotten, and the
+/// next request will load the file again.
+///
+/// The cache does not keep structures alive: they are released as soon as
+/// the last [`Arc`] is dropped. A process-wide cache is available through
+/// [`SnapshotCache::global`], but caches can also be created and owned by
+/// the user.
+///
+/// ```rust
+/// use epserde::prelude::*;
+/// use epserde::deser::SnapshotCache;
+/// use std::sync::Arc;
+///
+/// let tmp_file = std::env::temp_dir().join("snapshot_cache_doc.eps");
+/// vec![0_u64, 1, 2].store(&tmp_file)?;
+///
+/// let cache = SnapshotCache::new();
+/// let a = cache.load_mem::<Vec<u64>>(&tmp_file)?;
+/// let b = cache.load_mem::<Vec<u64>>(&tmp_file)?;
+/// assert!(Arc::ptr_eq(&a, &b));
+/// assert_eq!(**a, [0, 1, 2]);
+/// # std::fs::remove_file(&tmp_file)?;
+/// # Ok::<(), anyhow::Error>(())
+/// ```
+#[derive(Default)]
+pub struct SnapshotCache {
+    entries: Mutex<HashMap<PathBuf, PathEntry>>,
+}
+
+/// The result of a cached loading.
+pub type SharedCase<T> = Arc<MemCase<DeserType<'static, T>>>;
+
+impl SnapshotCache {
+    /// Create an empty cache.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Return the process-wide cache.
+    pub fn global() -> &'static SnapshotCache {
+        static GLOBAL: OnceLock<SnapshotCache> = OnceLock::new();
+        GLOBAL.get_or_init(SnapshotCache::new)
+    }
+
+    /// Return a shared structure [loaded in memory](Deserialize::load_mem)
+    /// from the given file.
+    pub fn load_mem<T: Deserialize + 'static>(
+        &self,
+        path: impl AsRef<Path>,
+    ) -> Result<SharedCase<T>>
+    where
+        DeserType<'static, T>: Send + Sync,
+    {
+        self.get_or_load::<T>(path.as_ref(), LoadMode::Mem, |path| T::load_mem(path))
+    }
+
+    /// Return a shared structure [loaded in `mmap()`-allocated
+    /// memory](Deserialize::load_mmap) from the given file.
+    #[cfg(feature = "mmap-rs")]
+    pub fn load_mmap<T: Deserialize + 'static>(
+        &self,
+        path: impl AsRef<Path>,
+        flags: Flags,
+    ) -> Result<SharedCase<T>>
+    where
+        DeserType<'static, T>: Send + Sync,
+    {
+        self.get_or_load::<T>(path.as_ref(), LoadMode::LoadMmap(flags), |path| {
+            T::load_mmap(path, flags)
+        })
+    }
+
+    /// Return a shared structure [memory mapped](Deserialize::mmap) from the
+    /// given file.
+    #[cfg(feature = "mmap-rs")]
+    pub fn mmap<T: Deserialize + 'static>(
+        &self,
+        path: impl AsRef<Path>,
+        flags: Flags,
+    ) -> Result<SharedCase<T>>
+    where
+        DeserType<'static, T>: Send + Sync,
+    {
+        self.get_or_load::<T>(path.as_ref(), LoadMode::Mmap(flags), |path| {
+            T::mmap(path, flags)
+        })
+    }
+
+    /// Return the number of structures in the cache that are still in use.
+    pub fn len(&self) -> usize {
+        self.lock()
+            .values()
+            .flat_map(|entry| entry.cases.values())
+            .filter(|case| case.strong_count() > 0)
+            .count()
+    }
+
+    /// Return whether no structure in the cache is still in use.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Forget all structures. Structures still in use are not affected, but
+    /// they will not be returned by the cache anymore.
+    pub fn clear(&self) {
+        self.lock().clear();
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<PathBuf, PathEntry>> {
+        self.entries.lock().unwrap_or_else(|err| err.into_inner())
+    }
+
+    fn get_or_load<T: Deserialize + 'static>(
+        &self,
+        path: &Path,
+        mode: LoadMode,
+        load: impl FnOnce(&Path) -> Result<MemCase<DeserType<'static, T>>>,
+    ) -> Result<SharedCase<T>>
+    where
+        DeserType<'static, T>: Send + Sync,
+    {
+        let path = std::fs::canonicalize(path)?;
+        // We read the metadata before loading, so if the file is modified
+        // during loading the next request will load it again.
+        let version = FileVersion::new(&path.metadata()?)?;
+        let key = (TypeId::of::<T>(), mode);
+
+        let lookup = |entries: &HashMap<PathBuf, PathEntry>| {
+            entries
+                .get(&path)
+                .filter(|entry| entry.version == version)
+                .and_then(|entry| entry.cases.get(&key))
+                .and_then(Weak::upgrade)
+                .and_then(|case| case.downcast().ok())
+        };
+
+        if let Some(case) = lookup(&self.lock()) {
+            return Ok(case);
+        }
+
+        // We do not hold the lock while loading, so other files can be
+        // loaded concurrently.
+        let case: SharedCase<T> = Arc::new(load(&path)?);
+
+        let mut entries = self.lock();
+        // Another thread might have loaded the same file in the meantime
+        if let Some(other) = lookup(&entries) {
+            return Ok(other);
+        }
+        // Forget structures no longer in use
+        entries.retain(|_, entry| {
+            entry.cases.retain(|_, case| case.strong_count() > 0);
+            !entry.cases.is_empty()
+        });
+        let entry = entries.entry(path).or_insert_with(|| PathEntry {
+            version,
+            cases: HashMap::new(),
+        });
+        if entry.version != version {
+            entry.version = version;
+            entry.cases.clear();
+        }
+        let any: Arc<dyn Any + Send + Sync> = case.clone();
+        entry.cases.insert(key, Arc::downgrade(&any));
+        Ok(case)
+    }
+}
@@ -0,0 +1,170 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ * SPDX-FileCopyrightText: 2023 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+/*!
+
+Validation of strings during ε-copy deserialization.
+
+With the feature `simdutf8`, UTF-8 validation uses the SIMD implementation
+of the [`simdutf8`](https://crates.io/crates/simdutf8) crate, which is
+several times faster than [`core::str::from_utf8`] on non-ASCII text, so
+validating strings in multi-gigabyte memory-mapped files takes milliseconds
+rather than seconds. With the feature `std`, the best implementation for the
+current CPU is selected at runtime; otherwise, the implementation is selected
+at compile time depending on the enabled target features.
+
+*/
+
+use super::*;
+use core::sync::atomic::{AtomicU8, Ordering};
+
+/// The policy used to validate the UTF-8 content of strings
+/// during ε-copy deserialization.
+///
+/// The policy is set on a [`SliceWithPos`] with
+/// [`SliceWithPos::set_str_policy`], usually through
+/// [`Deserialize::deserialize_eps_with_policy`] or
+/// [`Deserialize::load_mem_with_policy`]. All other ε-copy
+/// deserialization methods use [`StrPolicy::Trusted`].
+///
+/// The policy applies only to strings: the bit patterns of
+/// [zero-copy](crate::traits::ZeroCopy) values are checked depending on the
+/// [validation](Validation) of the backend. Both are set for data coming from
+/// untrusted sources by [`Deserialize::deserialize_eps_untrusted`] and by
+/// [`Flags::UNTRUSTED`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum StrPolicy {
+    /// Strings are not validated. This policy is appropriate only for data
+    /// coming from a trusted source (e.g., files serialized by the same
+    /// application), as a string view on invalid UTF-8 data is undefined
+    /// behavior.
+    #[default]
+    Trusted,
+    /// Strings are validated once, during deserialization, and
+    /// [`Error::InvalidUtf8`] is returned if some string is not valid UTF-8.
+    Validated,
+    /// The validation of [`LazyStr`] views is postponed to the first access,
+    /// and its result is cached. Since `&str` views must always be valid, they
+    /// are validated during deserialization as with [`StrPolicy::Validated`].
+    Lazy,
+}
+
+/// Validate the UTF-8 content of a string.
+#[inline(always)]
+fn validate_utf8(bytes: &[u8]) -> Result<&str> {
+    #[cfg(feature = "simdutf8")]
+    let result = simdutf8::basic::from_utf8(bytes);
+    #[cfg(not(feature = "simdutf8"))]
+    let result = core::str::from_utf8(bytes);
+    result.map_err(|_| Error::InvalidUtf8)
+}
+
+const UNKNOWN: u8 = 0;
+const VALID: u8 = 1;
+const INVALID: u8 = 2;
+
+/// A view on a serialized string whose UTF-8 content is validated
+/// according to a [`StrPolicy`].
+///
+/// This type can be used as the ε-copy deserialization type of a field
+/// whose type is a type parameter instantiated with [`String`] or
+/// `Box<str>` by using the field attribute
+/// `#[epserde(deser_type = "LazyStr<'epserde>", deser_with = "epserde::deser::lazy_str")]`.
+/// With [`StrPolicy::Lazy`], the cost of validation is paid only for the
+/// strings that are actually accessed, and only once.
+///
+/// ```rust
+/// use epserde::prelude::*;
+/// use epserde::deser::LazyStr;
+///
+/// #[derive(Epserde)]
+/// struct Data<S> {
+///     #[epserde(deser_type = "LazyStr<'epserde>", deser_with = "epserde::deser::lazy_str")]
+///     name: S,
+/// }
+///
+/// let data = Data { name: "data".to_string() };
+/// let mut cursor = epserde::new_aligned_cursor();
+/// data.serialize(&mut cursor).unwrap();
+/// let bytes = cursor.into_inner();
+/// let eps = <Data<String>>::deserialize_eps_with_policy(&bytes, StrPolicy::Lazy).unwrap();
+/// assert_eq!(eps.name.as_str().unwrap(), "data");
+/// ```
+#[derive(Debug)]
+pub struct LazyStr<'a> {
+    bytes: &'a [u8],
+    state: AtomicU8,
+}
+
+impl<'a> LazyStr<'a> {
+    /// Return the string, validating it if necessary.
+    ///
+    /// An error is returned if the string is not valid UTF-8.
+    pub fn as_str(&self) -> Result<&'a str> {
+        match self.state.load(Ordering::Relaxed) {
+            VALID => {}
+            INVALID => return Err(Error::InvalidUtf8),
+            _ => {
+                let valid = validate_utf8(self.bytes).is_ok();
+                self.state
+                    .store(if valid { VALID } else { INVALID }, Ordering::Relaxed);
+                if !valid {
+                    return Err(Error::InvalidUtf8);
+                }
+            }
+        }
+        // SAFETY: the bytes have been validated, or the policy is trusted.
+        Ok(unsafe { core::str::from_utf8_unchecked(self.bytes) })
+    }
+
+    /// Return the bytes of the string, which are not validated.
+    pub fn as_bytes(&self) -> &'a [u8] {
+        self.bytes
+    }
+}
+
+impl Clone for LazyStr<'_> {
+    fn clone(&self) -> Self {
+        Self {
+            bytes: self.bytes,
+            state: AtomicU8::new(self.state.load(Ordering::Relaxed)),
+        }
+    }
+}
+
+/// ε-copy deserialize a string as a [`LazyStr`], validating it according to
+/// the [policy](StrPolicy) of the backend.
+///
+/// This function is meant to be used with the `deser_with` field attribute.
+pub fn lazy_str<'a>(backend: &mut SliceWithPos<'a>) -> Result<LazyStr<'a>> {
+    let bytes = deserialize_eps_slice_zero::<u8>(backend)?;
+    let state = match backend.str_policy() {
+        StrPolicy::Trusted => VALID,
+        StrPolicy::Validated => {
+            validate_utf8(bytes)?;
+            VALID
+        }
+        StrPolicy::Lazy => UNKNOWN,
+    };
+    Ok(LazyStr {
+        bytes,
+        state: AtomicU8::new(state),
+    })
+}
+
+/// ε-copy deserialize a string as a `&str`, validating it according to
+/// the [policy](StrPolicy) of the backend.
+pub fn deserialize_eps_str<'a>(backend: &mut SliceWithPos<'a>) -> Result<&'a str> {
+    let bytes = deserialize_eps_slice_zero::<u8>(backend)?;
+    match backend.str_policy() {
+        StrPolicy::Trusted => Ok(unsafe {
+            #[allow(clippy::transmute_bytes_to_str)]
+            core::mem::transmute::<&'_ [u8], &'_ str>(bytes)
+        }),
+        StrPolicy::Validated | StrPolicy::Lazy => validate_utf8(bytes),
+    }
+}
@@ -0,0 +1,45 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ * SPDX-FileCopyrightText: 2023 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+/*!
+
+Validation of the bit patterns of zero-copy values during ε-copy
+deserialization.
+
+*/
+
+/// Whether the bit patterns of [zero-copy](crate::traits::ZeroCopy) values
+/// are [checked](crate::traits::CheckBytes) during ε-copy deserialization.
+///
+/// Not all bit patterns are valid values of a zero-copy type (e.g., a `bool`
+/// must be zero or one, and the tag of a zero-copy enum must correspond to a
+/// variant), and a view on an invalid bit pattern is undefined behavior.
+/// Checking the values requires reading all zero-copy data, so ε-copy
+/// deserialization does not check them by default; full-copy deserialization
+/// always checks them.
+///
+/// The validation is set on a [`SliceWithPos`](super::SliceWithPos) with
+/// [`SliceWithPos::set_validation`](super::SliceWithPos::set_validation),
+/// usually through
+/// [`Deserialize::deserialize_eps_untrusted`](super::Deserialize::deserialize_eps_untrusted)
+/// or [`Flags::UNTRUSTED`](super::Flags::UNTRUSTED), which also validate
+/// strings using [`StrPolicy::Validated`](super::StrPolicy::Validated).
+/// Together with the checks on lengths and on the tags of deep-copy enums,
+/// which are always performed, checking values and validating strings makes
+/// it safe to ε-copy deserialize data coming from untrusted sources.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum Validation {
+    /// Zero-copy values are not checked. This setting is appropriate only
+    /// for data coming from a trusted source (e.g., files serialized by the
+    /// same application).
+    #[default]
+    Trusted,
+    /// Zero-copy values are checked during deserialization, and an error
+    /// (e.g., [`Error::InvalidValue`](super::Error::InvalidValue)) is
+    /// returned if some value has an invalid bit pattern.
+    Checked,
+}
@@ -0,0 +1,196 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ * SPDX-FileCopyrightText: 2023 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+/*!
+
+Structures sharing a dictionary.
+
+[`WithDict`] serializes a shared *dictionary* (e.g., a string table)
+followed by a sequence of structures referring to the dictionary
+(e.g., by index). At ε-copy deserialization time the dictionary is
+deserialized once, and its view is passed as a context to the
+deserialization of each structure, which can thus resolve its references
+into the dictionary: for example, indices into a string table can be turned
+into `&str` pointing directly into the serialized data.
+
+Structures referring to a dictionary of type `D` must implement
+[`DeserializeWithDict<D>`](DeserializeWithDict), which specifies their
+ε-copy deserialization type in the presence of a dictionary. Full-copy
+deserialization does not use the dictionary, and returns the dictionary
+and the structures as they were serialized.
+
+```rust
+use epserde::prelude::*;
+use epserde::dict::{DeserializeWithDict, WithDict};
+
+#[derive(Epserde, Debug, Clone, Copy, PartialEq)]
+#[repr(C)]
+#[zero_copy]
+struct Edge {
+    src: u32,
+    dst: u32,
+}
+
+/// An edge whose endpoints have been resolved in the string table.
+#[derive(Clone, Copy)]
+struct EdgeView<'a> {
+    src: &'a str,
+    dst: &'a str,
+}
+
+impl DeserializeWithDict<Vec<String>> for Edge {
+    type DictDeserType<'a> = EdgeView<'a>;
+
+    fn _deserialize_eps_with_dict<'a>(
+        backend: &mut deser::SliceWithPos<'a>,
+        dict: &Vec<&'a str>,
+    ) -> deser::Result<EdgeView<'a>> {
+        let edge = Edge::_deserialize_eps_inner(backend)?;
+        let name = |index: u32| {
+            dict.get(index as usize)
+                .copied()
+                .ok_or(deser::Error::InvalidValue("Edge"))
+        };
+        Ok(EdgeView {
+            src: name(edge.src)?,
+            dst: name(edge.dst)?,
+        })
+    }
+}
+
+let graph = WithDict {
+    dict: vec!["a".to_string(), "b".to_string(), "c".to_string()],
+    items: vec![Edge { src: 0, dst: 1 }, Edge { src: 1, dst: 2 }],
+};
+let mut cursor = epserde::new_aligned_cursor();
+graph.serialize(&mut cursor)?;
+let buf = cursor.into_inner();
+
+let view = <WithDict<Vec<String>, Vec<Edge>>>::deserialize_eps(&buf)?;
+assert_eq!(view.items[1].src, "b");
+assert_eq!(view.items[1].dst, "c");
+# Ok::<(), Box<dyn std::error::Error>>(())
+```
+
+*/
+
+use crate::prelude::*;
+use core::hash::Hash;
+use deser::*;
+use ser::*;
+
+#[cfg(all(feature = "alloc", not(feature = "std")))]
+use alloc::vec::Vec;
+
+/// A dictionary followed by a sequence of structures referring to it.
+///
+/// The serializable form is `WithDict<D, Vec<T>>`; its ε-copy
+/// deserialization type is `WithDict<DeserType<'a, D>, Vec<T::DictDeserType<'a>>>`,
+/// where `T::DictDeserType` is the deserialization type specified by
+/// [`DeserializeWithDict`].
+///
+/// See the [module documentation](self) for more information.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct WithDict<D, V> {
+    /// The dictionary.
+    pub dict: D,
+    /// The structures referring to the dictionary.
+    pub items: V,
+}
+
+/// ε-copy deserialization of a structure referring to a dictionary
+/// of type `D`.
+///
+/// This trait is used by [`WithDict`] to pass the ε-copy deserialized
+/// dictionary to the deserialization of each structure.
+pub trait DeserializeWithDict<D: DeserializeInner>: DeserializeInner {
+    /// The ε-copy deserialization type of the structure in the presence
+    /// of a dictionary.
+    type DictDeserType<'a>;
+
+    /// ε-copy deserialize the structure using the ε-copy deserialized
+    /// dictionary.
+    ///
+    /// The implementation must consume exactly the bytes consumed by
+    /// [`DeserializeInner::_deserialize_eps_inner`], which is usually called
+    /// to deserialize the structure before resolving its references.
+    fn _deserialize_eps_with_dict<'a>(
+        backend: &mut SliceWithPos<'a>,
+        dict: &DeserType<'a, D>,
+    ) -> deser::Result<Self::DictDeserType<'a>>;
+}
+
+impl<D, T> CopyType for WithDict<D, Vec<T>> {
+    type Copy = Deep;
+}
+
+impl<D: TypeHash, T: TypeHash> TypeHash for WithDict<D, Vec<T>> {
+    fn type_hash(hasher: &mut impl core::hash::Hasher) {
+        "WithDict".hash(hasher);
+        D::type_hash(hasher);
+        T::type_hash(hasher);
+    }
+}
+
+impl<D: ReprHash, T: ReprHash> ReprHash for WithDict<D, Vec<T>> {
+    fn repr_hash(hasher: &mut impl core::hash::Hasher, offset_of: &mut usize) {
+        *offset_of = 0;
+        D::repr_hash(hasher, offset_of);
+        *offset_of = 0;
+        T::repr_hash(hasher, offset_of);
+    }
+}
+
+// The structures are serialized one by one after their number, so that
+// their deserialization can be performed one by one, too.
+impl<D: SerializeInner, T: SerializeInner> SerializeInner for WithDict<D, Vec<T>> {
+    const IS_ZERO_COPY: bool = false;
+    const ZERO_COPY_MISMATCH: bool = false;
+    const MAX_FIELD_ALIGN: usize =
+        crate::ser::helpers::max_align(&[D::MAX_FIELD_ALIGN, T::MAX_FIELD_ALIGN]);
+
+    fn _serialize_inner(&self, backend: &mut impl WriteWithNames) -> ser::Result<()> {
+        backend.write("dict", &self.dict)?;
+        backend.write("len", &self.items.len())?;
+        for item in &self.items {
+            backend.write("item", item)?;
+        }
+        Ok(())
+    }
+}
+
+impl<D: DeserializeInner, T: DeserializeWithDict<D>> DeserializeInner for WithDict<D, Vec<T>> {
+    type DeserType<'a> = WithDict<DeserType<'a, D>, Vec<T::DictDeserType<'a>>>;
+
+    fn _deserialize_full_inner(backend: &mut impl ReadWithPos) -> deser::Result<Self> {
+        let dict = D::_deserialize_full_inner(backend)?;
+        let len = usize::_deserialize_full_inner(backend)?;
+        let mut items = Vec::new();
+        items
+            .try_reserve_exact(len)
+            .map_err(|_| deser::Error::ReadError)?;
+        for _ in 0..len {
+            items.push(T::_deserialize_full_inner(backend)?);
+        }
+        Ok(WithDict { dict, items })
+    }
+
+    fn _deserialize_eps_inner<'a>(
+        backend: &mut SliceWithPos<'a>,
+    ) -> deser::Result<Self::DeserType<'a>> {
+        let dict = D::_deserialize_eps_inner(backend)?;
+        let len = usize::_deserialize_full_inner(backend)?;
+        let mut items = Vec::new();
+        items
+            .try_reserve_exact(len.min(backend.data.len()))
+            .map_err(|_| deser::Error::ReadError)?;
+        for _ in 0..len {
+            items.push(T::_deserialize_eps_with_dict(backend, &dict)?);
+        }
+        Ok(WithDict { dict, items })
+    }
+}
@@ -0,0 +1,234 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ * SPDX-FileCopyrightText: 2023 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+/*!
+
+Schema-driven access to serialized data.
+
+A [`Schema`], as returned by
+[`Serialize::serialize_with_schema`](crate::ser::Serialize::serialize_with_schema)
+or read from the JSON sidecar written by
+[`Serialize::store_with_schema`](crate::ser::Serialize::store_with_schema)
+using [`Schema::from_json`], describes the position, size, and type of every
+piece of data in a file. A [`DynamicValue`] uses this information to walk the
+serialized data by field name and to read primitive values and slices of
+primitive values, without having at hand the Rust type that was serialized.
+This makes it possible to inspect corrupted files, or to write external
+tools.
+
+Fields are named as in the schema: the fields of structures use their
+names, the elements of vectors and slices are named `item`, their length is
+named `len`, and the content of zero-copy data (e.g., the elements of a
+vector of primitive types, or the bytes of a string) is named `zero`.
+
+```rust
+use epserde::prelude::*;
+use epserde::dynamic::DynamicValue;
+
+#[derive(Epserde)]
+struct Data<A> {
+    values: A,
+    name: String,
+    count: usize,
+}
+
+let data = Data { values: vec![1_u32, 2, 3], name: "data".to_string(), count: 42 };
+let mut cursor = epserde::new_aligned_cursor();
+let schema = data.serialize_with_schema(&mut cursor).unwrap();
+let bytes = cursor.into_inner();
+
+let root = DynamicValue::root(&schema, &bytes).unwrap();
+assert_eq!(root.field("count").unwrap().read::<usize>().unwrap(), 42);
+assert_eq!(root.field("values").unwrap().read_slice::<u32>().unwrap(), [1, 2, 3]);
+assert_eq!(root.field("name").unwrap().read_str().unwrap(), "data");
+// Types are checked against the schema
+assert!(root.field("count").unwrap().read::<u64>().is_err());
+```
+
+*/
+
+use crate::deser;
+use crate::ser::{Schema, SchemaRow};
+
+#[cfg(all(feature = "alloc", not(feature = "std")))]
+use alloc::{format, string::ToString};
+
+/// A piece of data described by a row of a [`Schema`].
+///
+/// Values are obtained from [`DynamicValue::root`] or [`DynamicValue::new`],
+/// and then navigated using [`DynamicValue::field`] and
+/// [`DynamicValue::children`].
+#[derive(Debug, Clone, Copy)]
+pub struct DynamicValue<'a> {
+    data: &'a [u8],
+    /// The row describing the value.
+    row: &'a SchemaRow,
+    /// The rows following the row describing the value.
+    following: &'a [SchemaRow],
+}
+
+impl<'a> DynamicValue<'a> {
+    /// Return the value at the given path (e.g., `ROOT.values`, or
+    /// `TYPE_NAME` for a field of the header), or `None` if the
+    /// schema contains no such path.
+    ///
+    /// If the path appears multiple times (e.g., in the case of the
+    /// elements of a vector), the first occurrence is returned.
+    pub fn new(schema: &'a Schema, data: &'a [u8], path: &str) -> Option<Self> {
+        let index = schema.0.iter().position(|row| row.field == path)?;
+        let (row, following) = schema.0.get(index..)?.split_first()?;
+        Some(Self {
+            data,
+            row,
+            following,
+        })
+    }
+
+    /// Return the value serialized in `data`, that is, the value at
+    /// the path `ROOT`.
+    pub fn root(schema: &'a Schema, data: &'a [u8]) -> Option<Self> {
+        Self::new(schema, data, "ROOT")
+    }
+
+    fn row(&self) -> &'a SchemaRow {
+        self.row
+    }
+
+    /// Return the full path of the value.
+    pub fn path(&self) -> &'a str {
+        &self.row().field
+    }
+
+    /// Return the name of the value, that is, the last component of its path.
+    pub fn name(&self) -> &'a str {
+        let path = self.path();
+        path.rsplit('.').next().unwrap_or(path)
+    }
+
+    /// Return the name of the type of the value, as recorded in the schema.
+    pub fn ty(&self) -> &'a str {
+        &self.row().ty
+    }
+
+    /// Return the offset of the value from the start of the data.
+    pub fn offset(&self) -> usize {
+        self.row().offset
+    }
+
+    /// Return the size in bytes of the value.
+    pub fn size(&self) -> usize {
+        self.row().size
+    }
+
+    /// Return the bytes of the value.
+    ///
+    /// An error is returned if the schema points outside of the data.
+    pub fn bytes(&self) -> deser::Result<&'a [u8]> {
+        let row = self.row();
+        row.offset
+            .checked_add(row.size)
+            .and_then(|end| self.data.get(row.offset..end))
+            .ok_or(deser::Error::ReadError)
+    }
+
+    /// Return an iterator on the direct children of the value, in
+    /// serialization order.
+    ///
+    /// Padding is not returned.
+    pub fn children(&self) -> impl Iterator<Item = DynamicValue<'a>> + 'a {
+        let prefix = format!("{}.", self.path());
+        let data = self.data;
+        let mut rest = self.following;
+        // Each row is paired with the rows following it
+        core::iter::from_fn(move || {
+            let (row, following) = rest.split_first()?;
+            rest = following;
+            Some((row, following))
+        })
+        .filter(|(row, _)| row.field != "PADDING")
+        .map_while(move |(row, following)| {
+            let name = row.field.strip_prefix(&prefix)?;
+            Some((row, following, name))
+        })
+        .filter(|(_, _, name)| !name.contains('.'))
+        .map(move |(row, following, _)| DynamicValue {
+            data,
+            row,
+            following,
+        })
+    }
+
+    /// Return the first direct child of the value with the given name,
+    /// or `None` if there is no such child.
+    pub fn field(&self, name: &str) -> Option<DynamicValue<'a>> {
+        self.children().find(|child| child.name() == name)
+    }
+
+    /// Return the value at the given path relative to this value
+    /// (e.g., `inner.values`), or `None` if there is no such value.
+    pub fn get(&self, path: &str) -> Option<DynamicValue<'a>> {
+        path.split('.')
+            .try_fold(*self, |value, name| value.field(name))
+    }
+
+    fn wrong_type<T: ?Sized>(&self) -> deser::Error {
+        deser::Error::WrongSchemaType {
+            field: self.path().to_string(),
+            ty: self.ty().to_string(),
+            requested: core::any::type_name::<T>(),
+        }
+    }
+
+    /// Read the value as a primitive value of type `T`.
+    ///
+    /// The type of the value in the schema must be `T`. Types with invalid
+    /// bit patterns, such as `bool` and `char`, cannot be read, as the data
+    /// might be corrupted; they can be read as `u8` or `u32` using
+    /// [`DynamicValue::bytes`].
+    pub fn read<T: bytemuck::Pod>(&self) -> deser::Result<T> {
+        if self.ty() != core::any::type_name::<T>() || self.size() != core::mem::size_of::<T>() {
+            return Err(self.wrong_type::<T>());
+        }
+        Ok(bytemuck::pod_read_unaligned(self.bytes()?))
+    }
+
+    /// Read the value as a slice of primitive values of type `T`.
+    ///
+    /// The value must be zero-copy data of type `T` or `[T; N]`, or have a
+    /// direct child named `zero` satisfying this condition, as in the case
+    /// of vectors, boxed slices, and arrays. The data must be properly
+    /// aligned in memory.
+    pub fn read_slice<T: bytemuck::Pod>(&self) -> deser::Result<&'a [T]> {
+        let value = match self.field("zero") {
+            Some(zero) => zero,
+            None => *self,
+        };
+        let name = core::any::type_name::<T>();
+        let ty = value.ty();
+        let is_array = ty
+            .strip_prefix('[')
+            .and_then(|ty| ty.strip_prefix(name))
+            .is_some_and(|ty| ty.starts_with("; "));
+        if ty != name && !is_array {
+            return Err(value.wrong_type::<[T]>());
+        }
+        bytemuck::try_cast_slice(value.bytes()?).map_err(|_| deser::Error::AlignmentError)
+    }
+
+    /// Read the value as a string.
+    ///
+    /// The value must have a direct child named `zero` of type `u8`, as in
+    /// the case of strings, and its content must be valid UTF-8.
+    pub fn read_str(&self) -> deser::Result<&'a str> {
+        let bytes = self
+            .field("zero")
+            .filter(|zero| zero.ty() == "u8")
+            .ok_or_else(|| self.wrong_type::<str>())?
+            .bytes()?;
+        core::str::from_utf8(bytes).map_err(|_| deser::Error::ReadError)
+    }
+}
@@ -0,0 +1,365 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ * SPDX-FileCopyrightText: 2023 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+/*!
+
+Complete ε-serde serializations embedded in other structures.
+
+An [`EmbeddedArchive<T>`] stores the complete serialization of a value of
+type `T`, header included, as a sequence of bytes. When serialized as a field
+of another structure, the bytes are copied verbatim; in particular, the type
+hash of the enclosing structure does not depend on `T`, and the header of
+the embedded archive is checked only when the archive is opened.
+
+In this way, composite datasets can nest independently-versioned components:
+the component can change its type without invalidating the enclosing
+structure, and opening it with the wrong type will return an error
+rather than garbage. Moreover, components are opened lazily: ε-copy
+deserializing the enclosing structure just returns an [`EmbeddedArchiveView`]
+referring to the bytes of the component, which can be later
+[opened](EmbeddedArchiveView::open), obtaining a deserialized value borrowed
+from the same backend. As usual, this happens only if the type of the field is
+a type parameter; otherwise, the field is fully deserialized.
+
+```rust
+use epserde::prelude::*;
+use epserde::embedded::EmbeddedArchive;
+
+#[derive(Epserde)]
+struct Dataset<A> {
+    name: String,
+    ids: A,
+}
+
+let dataset = Dataset {
+    name: "ids".to_string(),
+    ids: EmbeddedArchive::new(&vec![1_u64, 2, 3]).unwrap(),
+};
+let mut cursor = epserde::new_aligned_cursor();
+dataset.serialize(&mut cursor).unwrap();
+let buf = cursor.into_inner();
+
+let view = <Dataset<EmbeddedArchive<Vec<u64>>>>::deserialize_eps(&buf).unwrap();
+let ids: &[u64] = view.ids.open().unwrap();
+assert_eq!(ids, &[1, 2, 3]);
+```
+
+The bytes of an embedded archive are aligned to 16 bytes, which is sufficient
+for ε-copy deserialization of all types without `repr(align)` attributes
+requiring larger alignment.
+
+## Packing existing files
+
+An [`ArchivePacker`] writes a `Vec<EmbeddedArchive<T>>` one archive at a
+time, so existing files can be consolidated in a single file without
+deserializing them: the bytes of each file are copied verbatim, after
+[checking](EmbeddedArchive::from_bytes_checked) their header and their
+checksum, if present, and only the header of the resulting file and the
+padding between archives are written anew.
+
+```rust
+use epserde::prelude::*;
+use epserde::embedded::{ArchivePacker, EmbeddedArchive};
+
+let dir = std::env::temp_dir();
+let paths = [dir.join("part0.eps"), dir.join("part1.eps")];
+vec![1_u64, 2].store(&paths[0])?;
+vec![3_u64].store(&paths[1])?;
+
+let mut cursor = epserde::new_aligned_cursor();
+let mut packer = ArchivePacker::<Vec<u64>, _>::new(&mut cursor, paths.len())?;
+for path in &paths {
+    // Only one file at a time is in memory
+    packer.push(&EmbeddedArchive::load(path)?)?;
+}
+packer.finish()?;
+
+let buf = cursor.into_inner();
+let parts = <Vec<EmbeddedArchive<Vec<u64>>>>::deserialize_eps(&buf)?;
+assert_eq!(parts[0].open()?, &[1, 2]);
+assert_eq!(parts[1].open()?, &[3]);
+# Ok::<(), Box<dyn std::error::Error>>(())
+```
+
+*/
+
+use crate::prelude::*;
+use core::hash::Hash;
+use core::marker::PhantomData;
+use deser::*;
+use ser::*;
+use std::path::Path;
+
+/// The complete serialization, header included, of a value of type `T`.
+///
+/// See the [module documentation](crate::embedded) for more information.
+pub struct EmbeddedArchive<T> {
+    bytes: Vec<u8>,
+    _marker: PhantomData<T>,
+}
+
+impl<T> core::fmt::Debug for EmbeddedArchive<T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("EmbeddedArchive")
+            .field("type", &core::any::type_name::<T>())
+            .field("len", &self.bytes.len())
+            .finish()
+    }
+}
+
+impl<T> Clone for EmbeddedArchive<T> {
+    fn clone(&self) -> Self {
+        Self {
+            bytes: self.bytes.clone(),
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<T> PartialEq for EmbeddedArchive<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.bytes == other.bytes
+    }
+}
+
+impl<T> Eq for EmbeddedArchive<T> {}
+
+impl<T: Serialize> EmbeddedArchive<T> {
+    /// Create an embedded archive containing the serialization of `value`.
+    pub fn new(value: &T) -> ser::Result<Self> {
+        let mut bytes = Vec::new();
+        value.serialize(&mut bytes)?;
+        Ok(Self::from_bytes(bytes))
+    }
+}
+
+impl<T> EmbeddedArchive<T> {
+    /// Create an embedded archive from the serialization of a value of type `T`
+    /// (e.g., the content of a file written by [`Serialize::store`]).
+    ///
+    /// No check is performed until the archive is deserialized.
+    pub fn from_bytes(bytes: Vec<u8>) -> Self {
+        Self {
+            bytes,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Return the serialized bytes.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    /// Return the serialized bytes, consuming the archive.
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.bytes
+    }
+}
+
+impl<T: Deserialize> EmbeddedArchive<T> {
+    /// Create an embedded archive from the serialization of a value of type `T`,
+    /// checking its header and verifying its
+    /// [checksum](crate::ser::Serialize::serialize_with_checksum), if present.
+    ///
+    /// The payload is not deserialized.
+    pub fn from_bytes_checked(bytes: Vec<u8>) -> deser::Result<Self> {
+        check_data::<T>(&bytes)?;
+        Ok(Self::from_bytes(bytes))
+    }
+
+    /// Create an embedded archive from the content of a file written by
+    /// [`Serialize::store`], checking it as
+    /// [`from_bytes_checked`](EmbeddedArchive::from_bytes_checked).
+    pub fn load(path: impl AsRef<Path>) -> deser::Result<Self> {
+        let bytes = std::fs::read(path).map_err(deser::Error::FileOpenError)?;
+        Self::from_bytes_checked(bytes)
+    }
+}
+
+impl<T: Deserialize> EmbeddedArchive<T> {
+    /// Fully deserialize the embedded value.
+    pub fn load_full(&self) -> deser::Result<T> {
+        T::deserialize_full(&mut self.bytes.as_slice())
+    }
+}
+
+/// The ε-copy deserialization type of an [`EmbeddedArchive`]: a reference
+/// to the serialized bytes, which can be [opened](EmbeddedArchiveView::open) lazily.
+pub struct EmbeddedArchiveView<'a, T> {
+    bytes: &'a [u8],
+    _marker: PhantomData<T>,
+}
+
+impl<T> core::fmt::Debug for EmbeddedArchiveView<'_, T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("EmbeddedArchiveView")
+            .field("type", &core::any::type_name::<T>())
+            .field("len", &self.bytes.len())
+            .finish()
+    }
+}
+
+impl<T> Clone for EmbeddedArchiveView<'_, T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for EmbeddedArchiveView<'_, T> {}
+
+impl<'a, T: Deserialize> EmbeddedArchiveView<'a, T> {
+    /// Return the serialized bytes.
+    pub fn as_bytes(&self) -> &'a [u8] {
+        self.bytes
+    }
+
+    /// ε-copy deserialize the embedded value, checking its header.
+    ///
+    /// The result borrows from the same backend of the view.
+    pub fn open(&self) -> deser::Result<DeserType<'a, T>> {
+        T::deserialize_eps(self.bytes)
+    }
+
+    /// Fully deserialize the embedded value, checking its header.
+    pub fn load_full(&self) -> deser::Result<T> {
+        T::deserialize_full(&mut &*self.bytes)
+    }
+}
+
+impl<T> CopyType for EmbeddedArchive<T> {
+    type Copy = Deep;
+}
+
+impl<T> TypeHash for EmbeddedArchive<T> {
+    fn type_hash(hasher: &mut impl core::hash::Hasher) {
+        // The type of the embedded value is checked when opening the archive
+        "EmbeddedArchive".hash(hasher);
+    }
+}
+
+impl<T> ReprHash for EmbeddedArchive<T> {
+    fn repr_hash(_hasher: &mut impl core::hash::Hasher, _offset_of: &mut usize) {}
+}
+
+impl<T> SerializeInner for EmbeddedArchive<T> {
+    const IS_ZERO_COPY: bool = false;
+    const ZERO_COPY_MISMATCH: bool = false;
+    const MAX_FIELD_ALIGN: usize = 16;
+
+    fn _serialize_inner(&self, backend: &mut impl WriteWithNames) -> ser::Result<()> {
+        backend.write("len", &self.bytes.len())?;
+        // Align the archive as if it were a standalone file
+        backend.align::<u128>()?;
+        backend.write_bytes::<u8>(&self.bytes)
+    }
+}
+
+/// The size of the chunks in which embedded archives are read by
+/// full-copy deserialization.
+const READ_CHUNK_SIZE: usize = 1 << 20;
+
+impl<T: Deserialize> DeserializeInner for EmbeddedArchive<T> {
+    type DeserType<'a> = EmbeddedArchiveView<'a, T>;
+
+    fn _deserialize_full_inner(backend: &mut impl ReadWithPos) -> deser::Result<Self> {
+        let len = usize::_deserialize_full_inner(backend)?;
+        backend.align::<u128>()?;
+        // The buffer grows as data is read, so a corrupted length cannot
+        // cause an allocation larger than the input
+        let mut bytes = Vec::new();
+        while bytes.len() < len {
+            let start = bytes.len();
+            let chunk = (len - start).min(READ_CHUNK_SIZE);
+            bytes
+                .try_reserve(chunk)
+                .map_err(|_| deser::Error::ReadError)?;
+            bytes.resize(start + chunk, 0);
+            backend.read_exact(bytes.split_at_mut(start).1)?;
+        }
+        Ok(Self::from_bytes(bytes))
+    }
+
+    fn _deserialize_eps_inner<'a>(
+        backend: &mut SliceWithPos<'a>,
+    ) -> deser::Result<Self::DeserType<'a>> {
+        let len = usize::_deserialize_full_inner(backend)?;
+        backend.align::<u128>()?;
+        let bytes = backend.take(len)?;
+        Ok(EmbeddedArchiveView {
+            bytes,
+            _marker: PhantomData,
+        })
+    }
+}
+
+/// A serializer writing a `Vec<EmbeddedArchive<T>>` one archive at a time.
+///
+/// The header and the length of the vector are written by
+/// [`ArchivePacker::new`]; then, all archives must be written using
+/// [`ArchivePacker::push`]; finally, [`ArchivePacker::finish`] checks that
+/// no archive is missing and flushes the backend. The resulting data is
+/// indistinguishable from that written by [`Serialize::serialize`] on a
+/// vector containing the archives, but the archives are never in memory at
+/// the same time.
+///
+/// See the [module documentation](crate::embedded) for an example.
+pub struct ArchivePacker<'a, T, W: WriteNoStd> {
+    backend: WriterWithPos<'a, W>,
+    /// The number of archives to write.
+    len: usize,
+    /// The number of archives written so far.
+    written: usize,
+    _marker: PhantomData<fn(&T)>,
+}
+
+impl<'a, T: 'static, W: WriteNoStd> ArchivePacker<'a, T, W> {
+    /// Create a new packer writing `len` archives on the given backend,
+    /// and write the header.
+    pub fn new(backend: &'a mut W, len: usize) -> ser::Result<Self> {
+        let mut backend = WriterWithPos::new(backend);
+        write_header::<Vec<EmbeddedArchive<T>>>(&mut backend)?;
+        backend.write("len", &len)?;
+        Ok(Self {
+            backend,
+            len,
+            written: 0,
+            _marker: PhantomData,
+        })
+    }
+
+    /// Write the next archive, copying its bytes verbatim.
+    ///
+    /// # Panics
+    ///
+    /// If all archives have already been written.
+    pub fn push(&mut self, archive: &EmbeddedArchive<T>) -> ser::Result<()> {
+        assert!(
+            self.written < self.len,
+            "Cannot push more than {} archives",
+            self.len
+        );
+        self.written += 1;
+        self.backend.write("item", archive)
+    }
+
+    /// Check that all archives have been written and flush the backend,
+    /// returning the number of bytes written.
+    ///
+    /// # Panics
+    ///
+    /// If some archive has not been written.
+    pub fn finish(mut self) -> ser::Result<usize> {
+        assert!(
+            self.written == self.len,
+            "Cannot finish packing: {} archives of {} have been written",
+            self.written,
+            self.len
+        );
+        self.backend.flush()?;
+        Ok(self.backend.pos())
+    }
+}
@@ -0,0 +1,211 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ * SPDX-FileCopyrightText: 2023 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+/*!
+
+How to add ε-serde support to your own containers.
+
+The procedural macro `Epserde` covers most structures and enums, but some
+types must be supported by hand: for example, containers of other crates
+whose fields are private, containers whose serialized form differs from
+their memory representation, or zero-copy types needing custom validation.
+This module describes the *extension API*, that is, the minimal set of
+traits, helpers, and backends such implementations need. All items listed
+here are stable: they will change in a way that breaks existing
+implementations only in a release that increases the (major, or minor while
+the major version is zero) version of this crate, and the data written by
+the helpers will change only with the major number of the
+[file format version](crate::VERSION).
+
+The workspace of ε-serde contains the crate `epserde-ext-example`, which
+implements a generic deep-copy container and a validated zero-copy type
+using only the extension API, with its integration tests.
+
+# Traits
+
+Every type must implement:
+
+- [`CopyType`](crate::traits::CopyType), declaring whether the type is
+  [`Zero`](crate::traits::Zero) or [`Deep`](crate::traits::Deep) copy;
+- [`TypeHash`](crate::traits::TypeHash), hashing a name identifying
+  the type and, recursively, the type hashes of its type parameters or
+  fields; the type hash must not depend on the architecture;
+- [`ReprHash`](crate::traits::ReprHash), hashing the memory layout of
+  zero-copy data: zero-copy types hash their size and representation
+  attributes and recurse on their fields; deep-copy types reset `offset_of`
+  to zero and recurse on the types that can contain zero-copy data;
+- [`SerializeInner`](crate::ser::SerializeInner) and
+  [`DeserializeInner`](crate::deser::DeserializeInner), performing the
+  actual serialization and deserialization; the associated type
+  [`DeserType`](crate::deser::DeserializeInner::DeserType) is the type
+  returned by ε-copy deserialization.
+
+Zero-copy types must additionally implement
+[`MaxSizeOf`](crate::traits::MaxSizeOf),
+[`ByteSwap`](crate::traits::ByteSwap), and
+[`CheckBytes`](crate::traits::CheckBytes), so that they satisfy
+[`ZeroCopy`](crate::traits::ZeroCopy); they must be `repr(C)` (or
+`repr(transparent)`), and they must have no padding whose content matters.
+
+Implementing these traits makes [`Serialize`](crate::ser::Serialize) and
+[`Deserialize`](crate::deser::Deserialize) available through blanket
+implementations.
+
+# Upgrading zero-copy types implemented by hand
+
+Since version 0.4, [`ZeroCopy`](crate::traits::ZeroCopy) has
+[`ByteSwap`](crate::traits::ByteSwap) and
+[`CheckBytes`](crate::traits::CheckBytes) as supertraits, and there are no
+default implementations, as neither the endianness of the fields nor the
+valid bit patterns of a type can be guessed. Zero-copy types implemented by
+hand with previous versions no longer satisfy `ZeroCopy`, and thus cannot
+be serialized, until they implement both traits; types using the derive
+macro need no change.
+
+A `repr(C)` structure swaps and checks each field at its offset; a field
+whose bit patterns are all valid (e.g., an integer) needs no check:
+
+```rust
+use epserde::prelude::*;
+
+#[repr(C)]
+pub struct Point {
+    x: u32,
+    y: u32,
+}
+
+impl ByteSwap for Point {
+    fn byte_swap(bytes: &mut [u8]) -> deser::Result<()> {
+        let (x, y) = bytes.split_at_mut(core::mem::size_of::<u32>());
+        u32::byte_swap(x)?;
+        u32::byte_swap(y)
+    }
+}
+
+impl CheckBytes for Point {
+    fn check_bytes(_bytes: &[u8]) -> deser::Result<()> {
+        Ok(())
+    }
+}
+```
+
+# Serialization
+
+[`SerializeInner::_serialize_inner`](crate::ser::SerializeInner::_serialize_inner)
+receives a [`WriteWithNames`](crate::ser::WriteWithNames) backend. Deep-copy
+types write each component with
+[`WriteWithNames::write`](crate::ser::WriteWithNames::write), passing a
+name that will appear in [schemas](crate::ser::Schema) and error messages.
+Zero-copy types write themselves with
+[`serialize_zero`](crate::ser::helpers::serialize_zero). Sequences can be
+written with [`serialize_slice_zero`](crate::ser::helpers::serialize_slice_zero)
+and [`serialize_slice_deep`](crate::ser::helpers::serialize_slice_deep), or,
+if the elements are not contiguous, with
+[`SerializeIterHelper`](crate::ser::helpers::SerializeIterHelper), which
+dispatches on the copy type of the elements; the data written is the
+same as that of a vector, so it can be deserialized as a vector.
+
+The associated constants of [`SerializeInner`](crate::ser::SerializeInner)
+must be set as follows: `IS_ZERO_COPY` is true only for zero-copy types,
+`ZERO_COPY_MISMATCH` is false, and `MAX_FIELD_ALIGN` is the
+maximum of the `MAX_FIELD_ALIGN` of the components (for zero-copy types,
+[`max_size_of`](crate::traits::MaxSizeOf::max_size_of)), which can be
+computed at compile time with [`max_align`](crate::ser::helpers::max_align).
+
+# Deserialization
+
+Full-copy deserialization reads from a
+[`ReadWithPos`](crate::deser::ReadWithPos) backend, and ε-copy
+deserialization from a [`SliceWithPos`](crate::deser::SliceWithPos), whose
+data can be borrowed by the deserialized structure. Components must be read
+in the order in which they have been written, using the methods of
+[`DeserializeInner`](crate::deser::DeserializeInner) of the component
+types; [`in_field`](crate::deser::helpers::in_field) annotates errors with
+the name of the component when the backend
+[reports field paths](crate::deser::ReadWithPos::error_paths). Zero-copy
+types are read by [`deserialize_full_zero`](crate::deser::helpers::deserialize_full_zero) and
+[`deserialize_eps_zero`](crate::deser::helpers::deserialize_eps_zero), which
+check alignment, swap bytes, and call
+[`CheckBytes::check_bytes`](crate::traits::CheckBytes::check_bytes) as needed
+(always during full-copy deserialization, and if the
+[validation](crate::deser::Validation) is checked during ε-copy deserialization).
+
+By convention, a type whose type parameters are replaced by their
+deserialization types is the deserialization type of a generic container:
+for example, a `Container<B>` has deserialization type
+`Container<DeserType<'a, B>>`, so that a `Container<Vec<u32>>` is ε-copy
+deserialized as a `Container<&[u32]>`, and methods written for
+`Container<B>` with `B: AsRef<[u32]>` work on both.
+
+Deserialization must never panic or cause undefined behavior on malformed
+data: invariants of the container must be checked, and violations must be
+reported as errors, usually [`Error::InvalidValue`](crate::deser::Error::InvalidValue).
+
+# Example
+
+A stack whose serialized form is a vector of elements, and whose backend
+is a type parameter:
+
+```rust
+use epserde::prelude::*;
+use core::hash::Hash;
+
+#[derive(Debug, PartialEq)]
+pub struct Stack<B>(B);
+
+impl<B> CopyType for Stack<B> {
+    type Copy = Deep;
+}
+
+impl<B: TypeHash> TypeHash for Stack<B> {
+    fn type_hash(hasher: &mut impl core::hash::Hasher) {
+        "Stack".hash(hasher);
+        B::type_hash(hasher);
+    }
+}
+
+impl<B: ReprHash> ReprHash for Stack<B> {
+    fn repr_hash(hasher: &mut impl core::hash::Hasher, offset_of: &mut usize) {
+        *offset_of = 0;
+        B::repr_hash(hasher, offset_of);
+    }
+}
+
+impl<B: SerializeInner> SerializeInner for Stack<B> {
+    const IS_ZERO_COPY: bool = false;
+    const ZERO_COPY_MISMATCH: bool = false;
+    const MAX_FIELD_ALIGN: usize = B::MAX_FIELD_ALIGN;
+
+    fn _serialize_inner(&self, backend: &mut impl ser::WriteWithNames) -> ser::Result<()> {
+        backend.write("items", &self.0)
+    }
+}
+
+impl<B: DeserializeInner> DeserializeInner for Stack<B> {
+    type DeserType<'a> = Stack<deser::DeserType<'a, B>>;
+
+    fn _deserialize_full_inner(backend: &mut impl ReadWithPos) -> deser::Result<Self> {
+        Ok(Stack(B::_deserialize_full_inner(backend)?))
+    }
+
+    fn _deserialize_eps_inner<'a>(
+        backend: &mut SliceWithPos<'a>,
+    ) -> deser::Result<Self::DeserType<'a>> {
+        Ok(Stack(B::_deserialize_eps_inner(backend)?))
+    }
+}
+
+let stack = Stack(vec![1_u32, 2, 3]);
+let mut cursor = epserde::new_aligned_cursor();
+stack.serialize(&mut cursor)?;
+let buf = cursor.into_inner();
+let view: Stack<&[u32]> = <Stack<Vec<u32>>>::deserialize_eps(&buf)?;
+assert_eq!(view.0, &[1, 2, 3]);
+# Ok::<(), Box<dyn std::error::Error>>(())
+```
+
+*/
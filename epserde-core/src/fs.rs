@@ -0,0 +1,359 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ * SPDX-FileCopyrightText: 2023 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+/*!
+
+Filesystem abstraction for loaders.
+
+The loading methods [`Deserialize::load_full`] and [`Deserialize::load_mem`]
+access files through [`StdFileSystem`]. The functions of this module,
+[`load_full`], [`load_mem`], and [`mmap`], implement the same logic on top of
+any [`FileSystem`].
+
+In particular, [`MemFileSystem`] is a deterministic in-memory filesystem in
+which errors can be injected (e.g., failing opens, short reads, or read
+errors), so loading code can be tested hermetically, without touching the
+real filesystem:
+
+```rust
+use epserde::prelude::*;
+use epserde::fs::*;
+use std::io::ErrorKind;
+
+let mut cursor = epserde::new_aligned_cursor();
+vec![0_u32, 1, 2].serialize(&mut cursor)?;
+
+let mut fs = MemFileSystem::new();
+fs.insert("data.eps", cursor.into_inner());
+fs.insert("secret.eps", vec![]).fail_open(ErrorKind::PermissionDenied);
+
+let data = load_mem::<Vec<u32>>(&fs, "data.eps", StrPolicy::Trusted)?;
+assert_eq!(*data, [0, 1, 2]);
+assert!(load_full::<Vec<u32>>(&fs, "secret.eps").is_err());
+assert!(load_full::<Vec<u32>>(&fs, "missing.eps").is_err());
+
+// A file truncated while being read
+fs.get_mut("data.eps").unwrap().truncate_reads(10);
+assert!(load_full::<Vec<u32>>(&fs, "data.eps").is_err());
+# Ok::<(), anyhow::Error>(())
+```
+
+*/
+
+use crate::deser::{
+    self, DeserType, Deserialize, Flags, MemBackend, MemCase, SliceWithPos, StrPolicy,
+};
+use std::collections::HashMap;
+use std::io::{self, BufReader, ErrorKind, Read};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// The operations on files needed by loaders.
+pub trait FileSystem {
+    /// The type of an open file.
+    type File: Read;
+
+    /// Open a file for reading.
+    fn open(&self, path: &Path) -> io::Result<Self::File>;
+
+    /// Return the length in bytes of a file.
+    fn file_len(&self, path: &Path) -> io::Result<u64>;
+
+    /// Map a file in memory, returning a backend containing its content.
+    ///
+    /// The backend must satisfy the alignment requirements of ε-copy
+    /// deserialization (see [`Deserialize::deserialize_eps_owned`]).
+    #[cfg(feature = "mmap-rs")]
+    fn mmap(&self, path: &Path, flags: Flags) -> deser::Result<MemBackend>;
+}
+
+/// The filesystem of the operating system.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct StdFileSystem;
+
+impl FileSystem for StdFileSystem {
+    type File = std::fs::File;
+
+    fn open(&self, path: &Path) -> io::Result<Self::File> {
+        std::fs::File::open(path)
+    }
+
+    fn file_len(&self, path: &Path) -> io::Result<u64> {
+        Ok(path.metadata()?.len())
+    }
+
+    #[cfg(feature = "mmap-rs")]
+    fn mmap(&self, path: &Path, flags: Flags) -> deser::Result<MemBackend> {
+        let file = flags.open(path)?;
+        let file_len = self.file_len(path)? as usize;
+        // Mapping an empty region fails with platform-dependent errors
+        if file_len == 0 {
+            return Err(deser::Error::EmptyMapping);
+        }
+        let mmap = unsafe {
+            mmap_rs::MmapOptions::new(file_len)?
+                .with_flags(flags.mmap_flags())
+                .with_file(&file, 0)
+                .map()?
+        };
+        // Best effort, as in the case of the flags applied by mmap-rs
+        crate::deser::MemStorage::advise(&mmap, flags & Flags::WILL_NEED);
+        Ok(MemBackend::Mmap(mmap))
+    }
+}
+
+/// A file of a [`MemFileSystem`], with the errors to inject when accessing it.
+#[derive(Debug, Clone, Default)]
+pub struct MemFile {
+    data: Arc<[u8]>,
+    /// The kind of the error returned when opening the file, if any.
+    open_error: Option<ErrorKind>,
+    /// The number of bytes after which reads return end of file, if any.
+    truncate_reads: Option<usize>,
+    /// The position and the kind of the error returned by reads, if any.
+    read_error: Option<(usize, ErrorKind)>,
+    /// The maximum number of bytes returned by a read, if any.
+    max_read: Option<usize>,
+}
+
+impl MemFile {
+    /// Return the content of the file.
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+
+    /// Make opening the file fail with an error of the given kind (e.g.,
+    /// [`ErrorKind::PermissionDenied`]).
+    pub fn fail_open(&mut self, kind: ErrorKind) -> &mut Self {
+        self.open_error = Some(kind);
+        self
+    }
+
+    /// Make reads return end of file after `len` bytes, whereas the length
+    /// of the file is unchanged, as it happens when a file is truncated
+    /// while being read.
+    pub fn truncate_reads(&mut self, len: usize) -> &mut Self {
+        self.truncate_reads = Some(len);
+        self
+    }
+
+    /// Make reads fail with an error of the given kind after `pos` bytes.
+    ///
+    /// Note that [`Read::read_exact`] retries reads failing with
+    /// [`ErrorKind::Interrupted`], so errors of such kind make loaders loop
+    /// forever.
+    pub fn fail_read_at(&mut self, pos: usize, kind: ErrorKind) -> &mut Self {
+        self.read_error = Some((pos, kind));
+        self
+    }
+
+    /// Make each read return at most `len` bytes, as [`Read::read`] is
+    /// allowed to do.
+    ///
+    /// # Panics
+    ///
+    /// If `len` is zero, as reads would return end of file.
+    pub fn max_read(&mut self, len: usize) -> &mut Self {
+        assert!(len != 0, "The maximum length of a read must be positive");
+        self.max_read = Some(len);
+        self
+    }
+}
+
+/// An open [`MemFile`].
+#[derive(Debug)]
+pub struct MemReader {
+    file: MemFile,
+    pos: usize,
+}
+
+impl Read for MemReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let mut end = self.file.data.len();
+        if let Some(len) = self.file.truncate_reads {
+            end = end.min(len);
+        }
+        if let Some((pos, kind)) = self.file.read_error {
+            if self.pos >= pos {
+                return Err(io::Error::new(kind, "Injected read error"));
+            }
+            end = end.min(pos);
+        }
+        let mut len = buf.len().min(end.saturating_sub(self.pos));
+        if let Some(max_read) = self.file.max_read {
+            len = len.min(max_read);
+        }
+        let mut data = self
+            .file
+            .data
+            .get(self.pos..self.pos + len)
+            .ok_or(io::ErrorKind::UnexpectedEof)?;
+        let len = data.read(buf)?;
+        self.pos += len;
+        Ok(len)
+    }
+}
+
+/// A deterministic in-memory [`FileSystem`] with error injection.
+///
+/// Files are added with [`MemFileSystem::insert`], which returns the new
+/// [`MemFile`] so that errors can be injected. Opening a file that does
+/// not exist returns an error of kind [`ErrorKind::NotFound`]. Mapping a
+/// file copies its content into aligned memory.
+///
+/// See the [module documentation](self) for an example.
+#[derive(Debug, Clone, Default)]
+pub struct MemFileSystem {
+    files: HashMap<PathBuf, MemFile>,
+}
+
+impl MemFileSystem {
+    /// Create an empty filesystem.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a file with the given content, replacing the existing one, if
+    /// any, and return it.
+    pub fn insert(&mut self, path: impl Into<PathBuf>, data: impl Into<Vec<u8>>) -> &mut MemFile {
+        let file = self.files.entry(path.into()).or_default();
+        *file = MemFile {
+            data: data.into().into(),
+            ..MemFile::default()
+        };
+        file
+    }
+
+    /// Return the file with the given path, if any.
+    pub fn get_mut(&mut self, path: impl AsRef<Path>) -> Option<&mut MemFile> {
+        self.files.get_mut(path.as_ref())
+    }
+
+    /// Remove the file with the given path, returning it.
+    pub fn remove(&mut self, path: impl AsRef<Path>) -> Option<MemFile> {
+        self.files.remove(path.as_ref())
+    }
+
+    fn file(&self, path: &Path) -> io::Result<&MemFile> {
+        self.files.get(path).ok_or_else(|| {
+            io::Error::new(
+                ErrorKind::NotFound,
+                format!("No such file: {}", path.display()),
+            )
+        })
+    }
+}
+
+impl FileSystem for MemFileSystem {
+    type File = MemReader;
+
+    fn open(&self, path: &Path) -> io::Result<Self::File> {
+        let file = self.file(path)?;
+        if let Some(kind) = file.open_error {
+            return Err(io::Error::new(kind, "Injected open error"));
+        }
+        Ok(MemReader {
+            file: file.clone(),
+            pos: 0,
+        })
+    }
+
+    fn file_len(&self, path: &Path) -> io::Result<u64> {
+        Ok(self.file(path)?.data.len() as u64)
+    }
+
+    #[cfg(feature = "mmap-rs")]
+    fn mmap(&self, path: &Path, _flags: Flags) -> deser::Result<MemBackend> {
+        Ok(MemBackend::Memory(read_aligned(self, path)?))
+    }
+}
+
+/// Read a file into memory aligned to 16 bytes, zero-extending it to a
+/// multiple of 16 bytes.
+fn read_aligned(fs: &impl FileSystem, path: &Path) -> deser::Result<Vec<u8>> {
+    let mut file = fs.open(path).map_err(deser::Error::FileOpenError)?;
+    let file_len = fs.file_len(path)? as usize;
+    // Round up to u128 size
+    let capacity = file_len + crate::pad_align_to(file_len, 16);
+
+    if capacity == 0 {
+        return Ok(Vec::new());
+    }
+
+    // SAFETY: the entire vector will be filled with data read from the file,
+    // or with zeroes if the file is shorter than the vector.
+    let mut bytes = unsafe {
+        Vec::from_raw_parts(
+            std::alloc::alloc(std::alloc::Layout::from_size_align(capacity, 16)?),
+            capacity,
+            capacity,
+        )
+    };
+
+    let (data, padding) = bytes.split_at_mut(file_len);
+    file.read_exact(data)?;
+    // Fixes the last few bytes to guarantee zero-extension semantics
+    // for bit vectors and full-vector initialization.
+    padding.fill(0);
+    Ok(bytes)
+}
+
+/// ε-copy deserialize a structure from a backend, returning a [`MemCase`]
+/// containing the structure and the backend.
+fn encase<'a, T: Deserialize>(
+    backend: MemBackend,
+    str_policy: StrPolicy,
+    flags: Flags,
+) -> deser::Result<MemCase<DeserType<'a, T>>> {
+    let backend = backend.created();
+    // SAFETY: the memory of the backend is on the heap or mapped, so it does
+    // not move when the backend is moved into the MemCase, which keeps it
+    // alive as long as the deserialized structure.
+    let mem: &'a [u8] = unsafe { &*(backend.bytes()? as *const [u8]) };
+    let mut slice = SliceWithPos::new(mem);
+    slice.set_str_policy(str_policy);
+    let s = deser::deserialize_eps_with_flags::<T>(&mut slice, flags)?;
+    Ok(MemCase(s, backend))
+}
+
+/// Fully deserialize a structure from a file of the given [`FileSystem`].
+///
+/// See [`Deserialize::load_full`].
+pub fn load_full<T: Deserialize>(fs: &impl FileSystem, path: impl AsRef<Path>) -> deser::Result<T> {
+    let file = fs
+        .open(path.as_ref())
+        .map_err(deser::Error::FileOpenError)?;
+    T::deserialize_full(&mut BufReader::new(file))
+}
+
+/// Load a file of the given [`FileSystem`] in memory and ε-copy deserialize
+/// a structure from it, validating strings using the given
+/// [policy](StrPolicy).
+///
+/// See [`Deserialize::load_mem_with_policy`].
+pub fn load_mem<'a, T: Deserialize>(
+    fs: &impl FileSystem,
+    path: impl AsRef<Path>,
+    str_policy: StrPolicy,
+) -> deser::Result<MemCase<DeserType<'a, T>>> {
+    let bytes = read_aligned(fs, path.as_ref())?;
+    encase::<T>(MemBackend::Memory(bytes), str_policy, Flags::empty())
+}
+
+/// Map a file of the given [`FileSystem`] in memory and ε-copy deserialize
+/// a structure from it.
+///
+/// See [`Deserialize::mmap`].
+#[cfg(feature = "mmap-rs")]
+pub fn mmap<'a, T: Deserialize>(
+    fs: &impl FileSystem,
+    path: impl AsRef<Path>,
+    flags: Flags,
+) -> deser::Result<MemCase<DeserType<'a, T>>> {
+    let backend = fs.mmap(path.as_ref(), flags)?;
+    encase::<T>(backend, StrPolicy::Trusted, flags)
+}
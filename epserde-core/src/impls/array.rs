@@ -35,17 +35,42 @@ impl<T: Sized, const N: usize> ReprHash for [T; N] {
 }
 
 impl<T: MaxSizeOf, const N: usize> MaxSizeOf for [T; N] {
+    const WIDTH_DEPENDENT: bool = T::WIDTH_DEPENDENT;
+
     fn max_size_of() -> usize {
         T::max_size_of()
     }
 }
 
+impl<T: ByteSwap, const N: usize> ByteSwap for [T; N] {
+    fn byte_swap(bytes: &mut [u8]) -> deser::Result<()> {
+        if core::mem::size_of::<T>() != 0 {
+            for item in bytes.chunks_exact_mut(core::mem::size_of::<T>()) {
+                T::byte_swap(item)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<T: CheckBytes, const N: usize> CheckBytes for [T; N] {
+    fn check_bytes(bytes: &[u8]) -> deser::Result<()> {
+        if core::mem::size_of::<T>() != 0 {
+            for item in bytes.chunks_exact(core::mem::size_of::<T>()) {
+                T::check_bytes(item)?;
+            }
+        }
+        Ok(())
+    }
+}
+
 impl<T: CopyType + SerializeInner + TypeHash, const N: usize> SerializeInner for [T; N]
 where
     [T; N]: SerializeHelper<<T as CopyType>::Copy>,
 {
     const IS_ZERO_COPY: bool = T::IS_ZERO_COPY;
     const ZERO_COPY_MISMATCH: bool = T::ZERO_COPY_MISMATCH;
+    const MAX_FIELD_ALIGN: usize = T::MAX_FIELD_ALIGN;
     fn _serialize_inner(&self, backend: &mut impl WriteWithNames) -> ser::Result<()> {
         SerializeHelper::_serialize_inner(self, backend)
     }
@@ -91,27 +116,13 @@ impl<T: ZeroCopy + DeserializeInner + 'static, const N: usize> DeserializeHelper
     type DeserType<'a> = &'a [T; N];
     #[inline(always)]
     fn _deserialize_full_inner_impl(backend: &mut impl ReadWithPos) -> deser::Result<Self> {
-        let mut res = MaybeUninit::<[T; N]>::uninit();
-        backend.align::<T>()?;
-        // SAFETY: read_exact guarantees that the array will be filled with data.
-        unsafe {
-            backend.read_exact(res.assume_init_mut().align_to_mut::<u8>().1)?;
-            Ok(res.assume_init())
-        }
+        deserialize_full_zero::<Self>(backend)
     }
     #[inline(always)]
-
     fn _deserialize_eps_inner_impl<'a>(
         backend: &mut SliceWithPos<'a>,
     ) -> deser::Result<<Self as DeserializeInner>::DeserType<'a>> {
-        backend.align::<T>()?;
-        let bytes = std::mem::size_of::<[T; N]>();
-        let (pre, data, after) = unsafe { backend.data[..bytes].align_to::<[T; N]>() };
-        debug_assert!(pre.is_empty());
-        debug_assert!(after.is_empty());
-        let res = &data[0];
-        backend.skip(bytes);
-        Ok(res)
+        deserialize_eps_zero::<Self>(backend)
     }
 }
 
@@ -122,8 +133,11 @@ impl<T: DeepCopy + DeserializeInner + 'static, const N: usize> DeserializeHelper
     fn _deserialize_full_inner_impl(backend: &mut impl ReadWithPos) -> deser::Result<Self> {
         let mut res = MaybeUninit::<[T; N]>::uninit();
         unsafe {
-            for item in &mut res.assume_init_mut().iter_mut() {
-                std::ptr::write(item, T::_deserialize_full_inner(backend)?);
+            for (index, item) in res.assume_init_mut().iter_mut().enumerate() {
+                let value = in_item(backend, index, |backend| {
+                    T::_deserialize_full_inner(backend)
+                })?;
+                core::ptr::write(item, value);
             }
             Ok(res.assume_init())
         }
@@ -134,8 +148,9 @@ impl<T: DeepCopy + DeserializeInner + 'static, const N: usize> DeserializeHelper
     ) -> deser::Result<<Self as DeserializeInner>::DeserType<'a>> {
         let mut res = MaybeUninit::<<Self as DeserializeInner>::DeserType<'_>>::uninit();
         unsafe {
-            for item in &mut res.assume_init_mut().iter_mut() {
-                std::ptr::write(item, T::_deserialize_eps_inner(backend)?);
+            for (index, item) in res.assume_init_mut().iter_mut().enumerate() {
+                let value = in_item(backend, index, |backend| T::_deserialize_eps_inner(backend))?;
+                core::ptr::write(item, value);
             }
             Ok(res.assume_init())
         }
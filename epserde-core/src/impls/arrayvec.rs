@@ -0,0 +1,93 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ * SPDX-FileCopyrightText: 2023 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+/*!
+
+Implementations for [`ArrayVec`] (requires the `arrayvec` feature).
+
+Array vectors are serialized exactly as vectors, independently of their
+capacity, so ε-copy deserialization returns a slice for [`ZeroCopy`]
+elements, and a vector of deserialization types otherwise. Full-copy
+deserialization reports [`Error::InvalidValue`] if the elements do not fit
+the capacity.
+
+```rust
+use epserde::prelude::*;
+use arrayvec::ArrayVec;
+
+let v = ArrayVec::<u32, 4>::from_iter([1, 2, 3]);
+let mut cursor = epserde::new_aligned_cursor();
+v.serialize(&mut cursor).unwrap();
+let buf = cursor.into_inner();
+let view: &[u32] = <ArrayVec<u32, 4>>::deserialize_eps(&buf).unwrap();
+assert_eq!(view, &[1, 2, 3]);
+```
+
+*/
+
+use crate::prelude::*;
+use ::arrayvec::ArrayVec;
+use core::hash::Hash;
+use deser::*;
+use ser::*;
+
+#[cfg(all(feature = "alloc", not(feature = "std")))]
+use alloc::vec::Vec;
+
+impl<T, const CAP: usize> CopyType for ArrayVec<T, CAP> {
+    type Copy = Deep;
+}
+
+// The capacity is not part of the type hash, as it does not change the
+// serialized data
+impl<T: TypeHash, const CAP: usize> TypeHash for ArrayVec<T, CAP> {
+    fn type_hash(hasher: &mut impl core::hash::Hasher) {
+        "ArrayVec".hash(hasher);
+        T::type_hash(hasher);
+    }
+}
+
+impl<T: ReprHash, const CAP: usize> ReprHash for ArrayVec<T, CAP> {
+    fn repr_hash(hasher: &mut impl core::hash::Hasher, offset_of: &mut usize) {
+        *offset_of = 0;
+        T::repr_hash(hasher, offset_of);
+    }
+}
+
+impl<T: CopyType + SerializeInner, const CAP: usize> SerializeInner for ArrayVec<T, CAP>
+where
+    T: SerializeIterHelper<<T as CopyType>::Copy>,
+{
+    const IS_ZERO_COPY: bool = false;
+    const ZERO_COPY_MISMATCH: bool = false;
+    const MAX_FIELD_ALIGN: usize = T::MAX_FIELD_ALIGN;
+
+    fn _serialize_inner(&self, backend: &mut impl WriteWithNames) -> ser::Result<()> {
+        T::_serialize_iter(backend, self.len(), self.iter())
+    }
+}
+
+impl<T, const CAP: usize> DeserializeInner for ArrayVec<T, CAP>
+where
+    Vec<T>: DeserializeInner,
+{
+    type DeserType<'a> = DeserType<'a, Vec<T>>;
+
+    fn _deserialize_full_inner(backend: &mut impl ReadWithPos) -> deser::Result<Self> {
+        let vec = Vec::<T>::_deserialize_full_inner(backend)?;
+        if vec.len() > CAP {
+            return Err(deser::Error::InvalidValue("ArrayVec"));
+        }
+        Ok(vec.into_iter().collect())
+    }
+
+    fn _deserialize_eps_inner<'a>(
+        backend: &mut SliceWithPos<'a>,
+    ) -> deser::Result<Self::DeserType<'a>> {
+        Vec::<T>::_deserialize_eps_inner(backend)
+    }
+}
@@ -0,0 +1,85 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ * SPDX-FileCopyrightText: 2023 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+/*!
+
+Implementations for atomic integers.
+
+Atomic integers are serialized as the corresponding integers using a
+[relaxed](Ordering::Relaxed) load, one value at a time. In this way,
+structures built concurrently (e.g., a `Vec<AtomicU64>`) can be serialized
+directly: values modified concurrently during serialization may or may not
+be reflected in the serialized data, but each value is read atomically.
+
+Atomic integers are deep-copy, as a view on the serialized data would make
+it possible to modify memory that has been borrowed immutably (or mapped
+read-only). Their ε-copy deserialization type is the corresponding integer:
+for example, a `Vec<AtomicU64>` is ε-copy deserialized as a `Vec<u64>`.
+
+*/
+
+use crate::prelude::*;
+use core::hash::Hash;
+use core::sync::atomic::*;
+use deser::*;
+use ser::*;
+
+macro_rules! impl_atomic {
+    ($($ty:ty => $prim:ty),*) => {$(
+        impl CopyType for $ty {
+            type Copy = Deep;
+        }
+
+        impl TypeHash for $ty {
+            fn type_hash(
+                hasher: &mut impl core::hash::Hasher,
+            ) {
+                stringify!($ty).hash(hasher);
+            }
+        }
+
+        impl ReprHash for $ty {
+            fn repr_hash(_hasher: &mut impl core::hash::Hasher, _offset_of: &mut usize) {}
+        }
+
+        impl SerializeInner for $ty {
+            const IS_ZERO_COPY: bool = false;
+            const ZERO_COPY_MISMATCH: bool = false;
+            const MAX_FIELD_ALIGN: usize = <$prim>::MAX_FIELD_ALIGN;
+
+            #[inline(always)]
+            fn _serialize_inner(&self, backend: &mut impl WriteWithNames) -> ser::Result<()> {
+                self.load(Ordering::Relaxed)._serialize_inner(backend)
+            }
+        }
+
+        impl DeserializeInner for $ty {
+            #[inline(always)]
+            fn _deserialize_full_inner(backend: &mut impl ReadWithPos) -> deser::Result<Self> {
+                Ok(<$ty>::new(<$prim>::_deserialize_full_inner(backend)?))
+            }
+            type DeserType<'a> = $prim;
+            #[inline(always)]
+            fn _deserialize_eps_inner<'a>(
+                backend: &mut SliceWithPos<'a>,
+            ) -> deser::Result<Self::DeserType<'a>> {
+                <$prim>::_deserialize_full_inner(backend)
+            }
+        }
+    )*};
+}
+
+#[cfg(target_has_atomic = "8")]
+impl_atomic!(AtomicI8 => i8, AtomicU8 => u8);
+#[cfg(target_has_atomic = "16")]
+impl_atomic!(AtomicI16 => i16, AtomicU16 => u16);
+#[cfg(target_has_atomic = "32")]
+impl_atomic!(AtomicI32 => i32, AtomicU32 => u32);
+#[cfg(target_has_atomic = "64")]
+impl_atomic!(AtomicI64 => i64, AtomicU64 => u64);
+#[cfg(target_has_atomic = "ptr")]
+impl_atomic!(AtomicIsize => isize, AtomicUsize => usize);
@@ -0,0 +1,114 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ * SPDX-FileCopyrightText: 2023 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+/*!
+
+Implementations for boxes.
+
+A [`Box`] is serialized by serializing its content. The deserialization
+type of `Box<T>` is a reference to `T` if `T` is zero-copy, and a `Box` of
+the deserialization type of `T` otherwise.
+
+Boxed slices and boxed strings are implemented in the
+[`boxed_slice`](crate::impls::boxed_slice) and [`string`](crate::impls::string)
+modules.
+
+*/
+
+use crate::deser::helpers::*;
+use crate::prelude::*;
+use core::hash::Hash;
+use deser::*;
+use ser::*;
+
+#[cfg(all(feature = "alloc", not(feature = "std")))]
+use alloc::boxed::Box;
+
+impl<T> CopyType for Box<T> {
+    type Copy = Deep;
+}
+
+impl<T: TypeHash> TypeHash for Box<T> {
+    fn type_hash(hasher: &mut impl core::hash::Hasher) {
+        "Box".hash(hasher);
+        T::type_hash(hasher);
+    }
+}
+
+impl<T: ReprHash> ReprHash for Box<T> {
+    fn repr_hash(hasher: &mut impl core::hash::Hasher, offset_of: &mut usize) {
+        *offset_of = 0;
+        T::repr_hash(hasher, offset_of);
+    }
+}
+
+impl<T: SerializeInner> SerializeInner for Box<T> {
+    const IS_ZERO_COPY: bool = false;
+    const ZERO_COPY_MISMATCH: bool = false;
+    const MAX_FIELD_ALIGN: usize = T::MAX_FIELD_ALIGN;
+    #[inline(always)]
+    fn _serialize_inner(&self, backend: &mut impl WriteWithNames) -> ser::Result<()> {
+        T::_serialize_inner(self, backend)
+    }
+}
+
+// This delegates to a private helper trait which we can specialize on in stable rust
+impl<T: DeserializeInner + CopyType + 'static> DeserializeInner for Box<T>
+where
+    Box<T>: DeserializeHelper<<T as CopyType>::Copy, FullType = Box<T>>,
+{
+    type DeserType<'a> = <Box<T> as DeserializeHelper<<T as CopyType>::Copy>>::DeserType<'a>;
+    #[inline(always)]
+    fn _deserialize_full_inner(backend: &mut impl ReadWithPos) -> deser::Result<Self> {
+        <Box<T> as DeserializeHelper<<T as CopyType>::Copy>>::_deserialize_full_inner_impl(backend)
+    }
+
+    #[inline(always)]
+    fn _deserialize_eps_inner<'a>(
+        backend: &mut SliceWithPos<'a>,
+    ) -> deser::Result<<Box<T> as DeserializeHelper<<T as CopyType>::Copy>>::DeserType<'a>> {
+        <Box<T> as DeserializeHelper<<T as CopyType>::Copy>>::_deserialize_eps_inner_impl(backend)
+    }
+
+    #[inline(always)]
+    fn _deserialize_full_into_inner(
+        &mut self,
+        backend: &mut impl ReadWithPos,
+    ) -> deser::Result<()> {
+        (**self)._deserialize_full_into_inner(backend)
+    }
+}
+
+impl<T: ZeroCopy + DeserializeInner + 'static> DeserializeHelper<Zero> for Box<T> {
+    type FullType = Self;
+    type DeserType<'a> = &'a T;
+    #[inline(always)]
+    fn _deserialize_full_inner_impl(backend: &mut impl ReadWithPos) -> deser::Result<Self> {
+        Ok(Box::new(deserialize_full_zero::<T>(backend)?))
+    }
+    #[inline(always)]
+    fn _deserialize_eps_inner_impl<'a>(
+        backend: &mut SliceWithPos<'a>,
+    ) -> deser::Result<<Self as DeserializeInner>::DeserType<'a>> {
+        deserialize_eps_zero(backend)
+    }
+}
+
+impl<T: DeepCopy + DeserializeInner + 'static> DeserializeHelper<Deep> for Box<T> {
+    type FullType = Self;
+    type DeserType<'a> = Box<<T as DeserializeInner>::DeserType<'a>>;
+    #[inline(always)]
+    fn _deserialize_full_inner_impl(backend: &mut impl ReadWithPos) -> deser::Result<Self> {
+        Ok(Box::new(T::_deserialize_full_inner(backend)?))
+    }
+    #[inline(always)]
+    fn _deserialize_eps_inner_impl<'a>(
+        backend: &mut SliceWithPos<'a>,
+    ) -> deser::Result<<Self as DeserializeInner>::DeserType<'a>> {
+        Ok(Box::new(T::_deserialize_eps_inner(backend)?))
+    }
+}
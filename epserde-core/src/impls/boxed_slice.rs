@@ -16,6 +16,9 @@ use core::hash::Hash;
 use deser::*;
 use ser::*;
 
+#[cfg(all(feature = "alloc", not(feature = "std")))]
+use alloc::boxed::Box;
+
 impl<T> CopyType for Box<[T]> {
     type Copy = Deep;
 }
@@ -40,6 +43,7 @@ where
 {
     const IS_ZERO_COPY: bool = false;
     const ZERO_COPY_MISMATCH: bool = false;
+    const MAX_FIELD_ALIGN: usize = T::MAX_FIELD_ALIGN;
     fn _serialize_inner(&self, backend: &mut impl WriteWithNames) -> ser::Result<()> {
         SerializeHelper::_serialize_inner(self, backend)
     }
@@ -0,0 +1,284 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ * SPDX-FileCopyrightText: 2023 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+/*!
+
+Implementations for [`BTreeMap`] and [`BTreeSet`].
+
+Maps are serialized as a vector of keys followed by a vector of values,
+and sets as a vector of keys, in both cases in sorted order.
+Full-copy deserialization rebuilds the original container, whereas
+ε-copy deserialization returns a [`SortedMap`] or a [`SortedSet`], which
+are views on the deserialized vectors supporting lookups by binary search.
+If keys and values are [`ZeroCopy`], no allocation happens at all.
+
+```rust
+use epserde::prelude::*;
+use std::collections::BTreeMap;
+
+let map = BTreeMap::from([(3_u32, 30_u64), (1, 10), (2, 20)]);
+let mut cursor = epserde::new_aligned_cursor();
+map.serialize(&mut cursor).unwrap();
+let buf = cursor.into_inner();
+let view = <BTreeMap<u32, u64>>::deserialize_eps(&buf).unwrap();
+assert_eq!(view.get(&2), Some(&20));
+assert_eq!(view.keys(), &[1, 2, 3]);
+```
+
+*/
+
+use crate::prelude::*;
+use core::borrow::Borrow;
+use core::hash::Hash;
+use core::ops::Deref;
+use deser::*;
+use ser::*;
+
+#[cfg(all(feature = "alloc", not(feature = "std")))]
+use alloc::{
+    collections::{BTreeMap, BTreeSet},
+    vec::Vec,
+};
+#[cfg(feature = "std")]
+use std::collections::{BTreeMap, BTreeSet};
+
+/// The ε-copy deserialization type of a [`BTreeMap`]: a sorted sequence
+/// of keys and a parallel sequence of values, supporting lookups
+/// by binary search.
+///
+/// The type parameters are the deserialization types of the vectors of
+/// keys and values, which will be slices for [`ZeroCopy`] types.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SortedMap<KS, VS> {
+    keys: KS,
+    values: VS,
+}
+
+impl<KS, VS> SortedMap<KS, VS> {
+    /// Create a map from parallel sequences of keys and values. The keys
+    /// must be sorted.
+    pub(crate) fn new(keys: KS, values: VS) -> Self {
+        Self { keys, values }
+    }
+}
+
+impl<K, V, KS: Deref<Target = [K]>, VS: Deref<Target = [V]>> SortedMap<KS, VS> {
+    /// Return the number of entries.
+    #[inline(always)]
+    pub fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    /// Return whether the map is empty.
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    /// Return the keys, in sorted order.
+    #[inline(always)]
+    pub fn keys(&self) -> &[K] {
+        &self.keys
+    }
+
+    /// Return the values, in the order of the respective keys.
+    #[inline(always)]
+    pub fn values(&self) -> &[V] {
+        &self.values
+    }
+
+    /// Return an iterator on the entries, in sorted order.
+    pub fn iter(&self) -> core::iter::Zip<core::slice::Iter<'_, K>, core::slice::Iter<'_, V>> {
+        self.keys.iter().zip(self.values.iter())
+    }
+
+    /// Return the index of the given key in [`keys`](SortedMap::keys), if present.
+    pub fn index_of<Q: Ord + ?Sized>(&self, key: &Q) -> Option<usize>
+    where
+        K: Borrow<Q>,
+    {
+        self.keys.binary_search_by(|k| k.borrow().cmp(key)).ok()
+    }
+
+    /// Return the value associated with the given key, if present.
+    pub fn get<Q: Ord + ?Sized>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+    {
+        self.index_of(key).and_then(|index| self.values.get(index))
+    }
+
+    /// Return whether the map contains the given key.
+    pub fn contains_key<Q: Ord + ?Sized>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+    {
+        self.index_of(key).is_some()
+    }
+}
+
+/// The ε-copy deserialization type of a [`BTreeSet`]: a sorted sequence
+/// of keys supporting lookups by binary search.
+///
+/// The type parameter is the deserialization type of the vector of keys,
+/// which will be a slice for [`ZeroCopy`] types.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SortedSet<KS> {
+    keys: KS,
+}
+
+impl<K, KS: Deref<Target = [K]>> SortedSet<KS> {
+    /// Return the number of keys.
+    #[inline(always)]
+    pub fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    /// Return whether the set is empty.
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    /// Return the keys, in sorted order.
+    #[inline(always)]
+    pub fn as_slice(&self) -> &[K] {
+        &self.keys
+    }
+
+    /// Return an iterator on the keys, in sorted order.
+    pub fn iter(&self) -> core::slice::Iter<'_, K> {
+        self.keys.iter()
+    }
+
+    /// Return the index of the given key in [`as_slice`](SortedSet::as_slice), if present.
+    pub fn index_of<Q: Ord + ?Sized>(&self, key: &Q) -> Option<usize>
+    where
+        K: Borrow<Q>,
+    {
+        self.keys.binary_search_by(|k| k.borrow().cmp(key)).ok()
+    }
+
+    /// Return whether the set contains the given key.
+    pub fn contains<Q: Ord + ?Sized>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+    {
+        self.index_of(key).is_some()
+    }
+}
+
+impl<K, V> CopyType for BTreeMap<K, V> {
+    type Copy = Deep;
+}
+
+impl<K: TypeHash, V: TypeHash> TypeHash for BTreeMap<K, V> {
+    fn type_hash(hasher: &mut impl core::hash::Hasher) {
+        "BTreeMap".hash(hasher);
+        K::type_hash(hasher);
+        V::type_hash(hasher);
+    }
+}
+
+impl<K: ReprHash, V: ReprHash> ReprHash for BTreeMap<K, V> {
+    fn repr_hash(hasher: &mut impl core::hash::Hasher, offset_of: &mut usize) {
+        *offset_of = 0;
+        K::repr_hash(hasher, offset_of);
+        *offset_of = 0;
+        V::repr_hash(hasher, offset_of);
+    }
+}
+
+impl<K: CopyType + SerializeInner, V: CopyType + SerializeInner> SerializeInner for BTreeMap<K, V>
+where
+    K: SerializeIterHelper<<K as CopyType>::Copy>,
+    V: SerializeIterHelper<<V as CopyType>::Copy>,
+{
+    const IS_ZERO_COPY: bool = false;
+    const ZERO_COPY_MISMATCH: bool = false;
+    const MAX_FIELD_ALIGN: usize =
+        crate::ser::helpers::max_align(&[K::MAX_FIELD_ALIGN, V::MAX_FIELD_ALIGN]);
+
+    fn _serialize_inner(&self, backend: &mut impl WriteWithNames) -> ser::Result<()> {
+        K::_serialize_iter(backend, self.len(), self.keys())?;
+        V::_serialize_iter(backend, self.len(), self.values())
+    }
+}
+
+impl<K: DeserializeInner + Ord, V: DeserializeInner> DeserializeInner for BTreeMap<K, V>
+where
+    Vec<K>: DeserializeInner,
+    Vec<V>: DeserializeInner,
+{
+    type DeserType<'a> = SortedMap<DeserType<'a, Vec<K>>, DeserType<'a, Vec<V>>>;
+
+    fn _deserialize_full_inner(backend: &mut impl ReadWithPos) -> deser::Result<Self> {
+        let keys = Vec::<K>::_deserialize_full_inner(backend)?;
+        let values = Vec::<V>::_deserialize_full_inner(backend)?;
+        Ok(keys.into_iter().zip(values).collect())
+    }
+
+    fn _deserialize_eps_inner<'a>(
+        backend: &mut SliceWithPos<'a>,
+    ) -> deser::Result<Self::DeserType<'a>> {
+        let keys = Vec::<K>::_deserialize_eps_inner(backend)?;
+        let values = Vec::<V>::_deserialize_eps_inner(backend)?;
+        Ok(SortedMap { keys, values })
+    }
+}
+
+impl<K> CopyType for BTreeSet<K> {
+    type Copy = Deep;
+}
+
+impl<K: TypeHash> TypeHash for BTreeSet<K> {
+    fn type_hash(hasher: &mut impl core::hash::Hasher) {
+        "BTreeSet".hash(hasher);
+        K::type_hash(hasher);
+    }
+}
+
+impl<K: ReprHash> ReprHash for BTreeSet<K> {
+    fn repr_hash(hasher: &mut impl core::hash::Hasher, offset_of: &mut usize) {
+        *offset_of = 0;
+        K::repr_hash(hasher, offset_of);
+    }
+}
+
+impl<K: CopyType + SerializeInner> SerializeInner for BTreeSet<K>
+where
+    K: SerializeIterHelper<<K as CopyType>::Copy>,
+{
+    const IS_ZERO_COPY: bool = false;
+    const ZERO_COPY_MISMATCH: bool = false;
+    const MAX_FIELD_ALIGN: usize = K::MAX_FIELD_ALIGN;
+
+    fn _serialize_inner(&self, backend: &mut impl WriteWithNames) -> ser::Result<()> {
+        K::_serialize_iter(backend, self.len(), self.iter())
+    }
+}
+
+impl<K: DeserializeInner + Ord> DeserializeInner for BTreeSet<K>
+where
+    Vec<K>: DeserializeInner,
+{
+    type DeserType<'a> = SortedSet<DeserType<'a, Vec<K>>>;
+
+    fn _deserialize_full_inner(backend: &mut impl ReadWithPos) -> deser::Result<Self> {
+        Ok(Vec::<K>::_deserialize_full_inner(backend)?
+            .into_iter()
+            .collect())
+    }
+
+    fn _deserialize_eps_inner<'a>(
+        backend: &mut SliceWithPos<'a>,
+    ) -> deser::Result<Self::DeserType<'a>> {
+        Ok(SortedSet {
+            keys: Vec::<K>::_deserialize_eps_inner(backend)?,
+        })
+    }
+}
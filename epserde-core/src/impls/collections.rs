@@ -0,0 +1,108 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ * SPDX-FileCopyrightText: 2023 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+/*!
+
+Implementations for [`VecDeque`], [`LinkedList`], and [`BinaryHeap`].
+
+All three containers are serialized contiguously, exactly as a vector
+containing their elements in iteration order, so that they can be ε-copy
+deserialized as the vector would be: a slice for [`ZeroCopy`] elements, a
+vector of deserialization types otherwise. Full-copy deserialization
+rebuilds the original container.
+
+Binary heaps are serialized in the order of their internal array, so the
+first element of the ε-copy deserialized slice is the greatest one. Note,
+however, that the heap property is restored only by full-copy
+deserialization.
+
+```rust
+use epserde::prelude::*;
+use std::collections::VecDeque;
+
+let mut queue = VecDeque::from([2_u32, 3]);
+queue.push_front(1);
+let mut cursor = epserde::new_aligned_cursor();
+queue.serialize(&mut cursor).unwrap();
+let buf = cursor.into_inner();
+let view: &[u32] = <VecDeque<u32>>::deserialize_eps(&buf).unwrap();
+assert_eq!(view, &[1, 2, 3]);
+```
+
+*/
+
+use crate::prelude::*;
+use core::hash::Hash;
+use deser::*;
+use ser::*;
+
+#[cfg(all(feature = "alloc", not(feature = "std")))]
+use alloc::{
+    collections::{BinaryHeap, LinkedList, VecDeque},
+    vec::Vec,
+};
+#[cfg(feature = "std")]
+use std::collections::{BinaryHeap, LinkedList, VecDeque};
+
+macro_rules! impl_seq {
+    ($name:literal, $ty:ident $(, $bound:ident)?) => {
+        impl<T> CopyType for $ty<T> {
+            type Copy = Deep;
+        }
+
+        impl<T: TypeHash> TypeHash for $ty<T> {
+            fn type_hash(hasher: &mut impl core::hash::Hasher) {
+                $name.hash(hasher);
+                T::type_hash(hasher);
+            }
+        }
+
+        impl<T: ReprHash> ReprHash for $ty<T> {
+            fn repr_hash(hasher: &mut impl core::hash::Hasher, offset_of: &mut usize) {
+                *offset_of = 0;
+                T::repr_hash(hasher, offset_of);
+            }
+        }
+
+        impl<T: CopyType + SerializeInner> SerializeInner for $ty<T>
+        where
+            T: SerializeIterHelper<<T as CopyType>::Copy>,
+        {
+            const IS_ZERO_COPY: bool = false;
+            const ZERO_COPY_MISMATCH: bool = false;
+            const MAX_FIELD_ALIGN: usize = T::MAX_FIELD_ALIGN;
+
+            fn _serialize_inner(&self, backend: &mut impl WriteWithNames) -> ser::Result<()> {
+                T::_serialize_iter(backend, self.len(), self.iter())
+            }
+        }
+
+        impl<T: DeserializeInner $(+ $bound)?> DeserializeInner for $ty<T>
+        where
+            Vec<T>: DeserializeInner,
+        {
+            type DeserType<'a> = DeserType<'a, Vec<T>>;
+
+            fn _deserialize_full_inner(backend: &mut impl ReadWithPos) -> deser::Result<Self> {
+                // Collecting reuses the allocation of the vector, if possible
+                Ok(Vec::<T>::_deserialize_full_inner(backend)?
+                    .into_iter()
+                    .collect())
+            }
+
+            fn _deserialize_eps_inner<'a>(
+                backend: &mut SliceWithPos<'a>,
+            ) -> deser::Result<Self::DeserType<'a>> {
+                Vec::<T>::_deserialize_eps_inner(backend)
+            }
+        }
+    };
+}
+
+impl_seq!("VecDeque", VecDeque);
+impl_seq!("LinkedList", LinkedList);
+impl_seq!("BinaryHeap", BinaryHeap, Ord);
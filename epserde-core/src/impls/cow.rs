@@ -0,0 +1,109 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ * SPDX-FileCopyrightText: 2023 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+/*!
+
+Implementations for [`Cow`] of slices of zero-copy types and of strings.
+
+The serialized form is the same of the corresponding borrowed type. Full-copy
+deserialization yields an owned value, whereas ε-copy deserialization
+yields a borrowed value, so fields of type `Cow<'static, [T]>` deserialize
+to `Cow<'a, [T]>` views that are cloned only when (and if) they are
+mutated using [`Cow::to_mut`].
+
+*/
+
+use crate::deser::helpers::*;
+use crate::prelude::*;
+use core::hash::Hash;
+use deser::*;
+use ser::*;
+
+#[cfg(all(feature = "alloc", not(feature = "std")))]
+use alloc::{borrow::Cow, string::String};
+#[cfg(feature = "std")]
+use std::borrow::Cow;
+
+impl<T: Clone> CopyType for Cow<'static, [T]> {
+    type Copy = Deep;
+}
+
+impl<T: Clone + TypeHash> TypeHash for Cow<'static, [T]> {
+    fn type_hash(hasher: &mut impl core::hash::Hasher) {
+        "Cow<[]>".hash(hasher);
+        T::type_hash(hasher);
+    }
+}
+
+impl<T: Clone + ReprHash> ReprHash for Cow<'static, [T]> {
+    fn repr_hash(hasher: &mut impl core::hash::Hasher, offset_of: &mut usize) {
+        *offset_of = 0;
+        T::repr_hash(hasher, offset_of);
+    }
+}
+
+impl<T: ZeroCopy + Clone + SerializeInner + TypeHash> SerializeInner for Cow<'static, [T]> {
+    const IS_ZERO_COPY: bool = false;
+    const ZERO_COPY_MISMATCH: bool = false;
+    const MAX_FIELD_ALIGN: usize = T::MAX_FIELD_ALIGN;
+    fn _serialize_inner(&self, backend: &mut impl WriteWithNames) -> ser::Result<()> {
+        serialize_slice_zero(backend, self)
+    }
+}
+
+impl<T: ZeroCopy + Clone + DeserializeInner + 'static> DeserializeInner for Cow<'static, [T]> {
+    type DeserType<'a> = Cow<'a, [T]>;
+    #[inline(always)]
+    fn _deserialize_full_inner(backend: &mut impl ReadWithPos) -> deser::Result<Self> {
+        Ok(Cow::Owned(deserialize_full_vec_zero(backend)?))
+    }
+
+    #[inline(always)]
+    fn _deserialize_eps_inner<'a>(
+        backend: &mut SliceWithPos<'a>,
+    ) -> deser::Result<Self::DeserType<'a>> {
+        Ok(Cow::Borrowed(deserialize_eps_slice_zero(backend)?))
+    }
+}
+
+impl CopyType for Cow<'static, str> {
+    type Copy = Deep;
+}
+
+impl TypeHash for Cow<'static, str> {
+    fn type_hash(hasher: &mut impl core::hash::Hasher) {
+        "Cow<str>".hash(hasher);
+    }
+}
+
+impl ReprHash for Cow<'static, str> {
+    fn repr_hash(_hasher: &mut impl core::hash::Hasher, _offset_of: &mut usize) {}
+}
+
+impl SerializeInner for Cow<'static, str> {
+    const IS_ZERO_COPY: bool = false;
+    const ZERO_COPY_MISMATCH: bool = false;
+    const MAX_FIELD_ALIGN: usize = 1;
+    fn _serialize_inner(&self, backend: &mut impl WriteWithNames) -> ser::Result<()> {
+        serialize_slice_zero(backend, self.as_bytes())
+    }
+}
+
+impl DeserializeInner for Cow<'static, str> {
+    type DeserType<'a> = Cow<'a, str>;
+    #[inline(always)]
+    fn _deserialize_full_inner(backend: &mut impl ReadWithPos) -> deser::Result<Self> {
+        Ok(Cow::Owned(String::_deserialize_full_inner(backend)?))
+    }
+
+    #[inline(always)]
+    fn _deserialize_eps_inner<'a>(
+        backend: &mut SliceWithPos<'a>,
+    ) -> deser::Result<Self::DeserType<'a>> {
+        Ok(Cow::Borrowed(deserialize_eps_str(backend)?))
+    }
+}
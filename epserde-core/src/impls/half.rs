@@ -0,0 +1,121 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ * SPDX-FileCopyrightText: 2023 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+/*!
+
+Implementations for the half-precision floating-point types [`f16`](struct@f16) and
+[`bf16`] of the [`half`](https://crates.io/crates/half) crate (requires the
+`half` feature).
+
+The types are treated exactly like the primitive floating-point types: they
+are [`ZeroCopy`], so vectors and boxed slices of half-precision values (e.g.,
+embedding tables) are ε-copy deserialized to slices, but in isolation they
+are deserialized as values. The type hashes are `f16` and `bf16`, and the
+representation hashes describe two-byte values with the alignment of `u16`.
+
+```rust
+use epserde::prelude::*;
+use half::f16;
+
+let table: Vec<f16> = [0.5_f32, -1.0, 2.0].into_iter().map(f16::from_f32).collect();
+let mut cursor = epserde::new_aligned_cursor();
+table.serialize(&mut cursor).unwrap();
+let buf = cursor.into_inner();
+let view: &[f16] = <Vec<f16>>::deserialize_eps(&buf).unwrap();
+assert_eq!(view[1].to_f32(), -1.0);
+```
+
+*/
+
+use crate::prelude::*;
+use ::half::{bf16, f16};
+use core::hash::Hash;
+use core::mem::size_of;
+use deser::*;
+use ser::*;
+
+macro_rules! impl_half {
+    ($($ty:ident),*) => {$(
+        impl CopyType for $ty {
+            type Copy = Zero;
+        }
+
+        impl TypeHash for $ty {
+            fn type_hash(hasher: &mut impl core::hash::Hasher) {
+                stringify!($ty).hash(hasher);
+            }
+        }
+
+        impl ReprHash for $ty {
+            fn repr_hash(hasher: &mut impl core::hash::Hasher, offset_of: &mut usize) {
+                crate::traits::std_repr_hash::<Self>(hasher, offset_of)
+            }
+        }
+
+        impl MaxSizeOf for $ty {
+            const WIDTH_DEPENDENT: bool = false;
+
+            fn max_size_of() -> usize {
+                size_of::<$ty>()
+            }
+        }
+
+        impl ByteSwap for $ty {
+            #[inline(always)]
+            fn byte_swap(bytes: &mut [u8]) -> deser::Result<()> {
+                bytes.reverse();
+                Ok(())
+            }
+        }
+
+        impl CheckBytes for $ty {
+            #[inline(always)]
+            fn check_bytes(_bytes: &[u8]) -> deser::Result<()> {
+                // Every bit pattern is valid
+                Ok(())
+            }
+        }
+
+        impl SerializeInner for $ty {
+            // As with primitive types, half-precision values are zero-copy
+            // but they are deserialized in isolation as values.
+            const IS_ZERO_COPY: bool = true;
+            const ZERO_COPY_MISMATCH: bool = false;
+            const MAX_FIELD_ALIGN: usize = size_of::<$ty>();
+
+            #[inline(always)]
+            fn _serialize_inner(&self, backend: &mut impl WriteWithNames) -> ser::Result<()> {
+                backend.write_all(&self.to_ne_bytes())
+            }
+        }
+
+        impl DeserializeInner for $ty {
+            #[inline(always)]
+            fn _deserialize_full_inner(backend: &mut impl ReadWithPos) -> deser::Result<$ty> {
+                let mut buf = [0; size_of::<$ty>()];
+                backend.read_exact(&mut buf)?;
+                if backend.swap_bytes() {
+                    buf.reverse();
+                }
+                Ok(<$ty>::from_ne_bytes(buf))
+            }
+
+            type DeserType<'a> = Self;
+
+            #[inline(always)]
+            fn _deserialize_eps_inner<'a>(
+                backend: &mut SliceWithPos<'a>,
+            ) -> deser::Result<Self::DeserType<'a>> {
+                let mut buf = [0; size_of::<$ty>()];
+                backend.read_exact(&mut buf)?;
+                Ok(<$ty>::from_ne_bytes(buf))
+            }
+        }
+    )*};
+}
+
+impl_half!(f16, bf16);
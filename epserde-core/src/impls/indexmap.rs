@@ -0,0 +1,371 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ * SPDX-FileCopyrightText: 2023 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+/*!
+
+Implementations for [`IndexMap`] and [`IndexSet`] (requires the `indexmap` feature).
+
+Maps are serialized as a vector of keys followed by a vector of values,
+and sets as a vector of keys, in both cases in insertion order.
+Full-copy deserialization rebuilds the original container, whereas
+ε-copy deserialization returns an [`IndexMapView`] or an [`IndexSetView`],
+which preserve the order and provide O(1) access by index. Lookups by key
+use a hash index that is built the first time it is needed.
+
+Since logically equal maps and sets might have different insertion orders,
+[`Serialize::serialize_canonical`]
+writes entries sorted by a fixed hash of their key instead, so that they
+always produce the same bytes.
+
+```rust
+use epserde::prelude::*;
+use indexmap::IndexMap;
+
+let map = IndexMap::<String, u32>::from_iter([("b".to_string(), 1), ("a".to_string(), 2)]);
+let mut cursor = epserde::new_aligned_cursor();
+map.serialize(&mut cursor).unwrap();
+let buf = cursor.into_inner();
+let view = <IndexMap<String, u32>>::deserialize_eps(&buf).unwrap();
+assert_eq!(view.get_index(0), Some((&"b", &1)));
+assert_eq!(view.get("a"), Some(&2));
+```
+
+*/
+
+use crate::prelude::*;
+use ::indexmap::{IndexMap, IndexSet};
+use core::borrow::Borrow;
+use core::hash::{BuildHasher, Hash};
+use core::ops::Deref;
+use deser::*;
+use ser::*;
+use std::collections::hash_map::RandomState;
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+/// A hash index on a slice of keys, mapping the hash of each key to the
+/// list of positions of keys with the same hash.
+#[derive(Debug, Clone)]
+struct HashIndex {
+    build_hasher: RandomState,
+    /// The first position for each hash.
+    heads: HashMap<u64, usize>,
+    /// For each position, the next position with the same hash, or `usize::MAX`.
+    next: Vec<usize>,
+}
+
+impl HashIndex {
+    fn new<K: Hash>(keys: &[K]) -> Self {
+        let build_hasher = RandomState::new();
+        let mut heads = HashMap::with_capacity(keys.len());
+        let mut next = vec![usize::MAX; keys.len()];
+        // We scan backwards so that chains are in increasing order
+        for (index, key) in keys.iter().enumerate().rev() {
+            let hash = build_hasher.hash_one(key);
+            if let (Some(head), Some(next)) = (heads.insert(hash, index), next.get_mut(index)) {
+                *next = head;
+            }
+        }
+        Self {
+            build_hasher,
+            heads,
+            next,
+        }
+    }
+
+    fn index_of<K: Borrow<Q>, Q: Hash + Eq + ?Sized>(&self, keys: &[K], key: &Q) -> Option<usize> {
+        let mut index = *self.heads.get(&self.build_hasher.hash_one(key))?;
+        while index != usize::MAX {
+            if keys.get(index)?.borrow() == key {
+                return Some(index);
+            }
+            index = *self.next.get(index)?;
+        }
+        None
+    }
+}
+
+/// Return the fixed hash used to sort keys in
+/// [canonical order](crate::ser::Serialize::serialize_canonical).
+fn canonical_hash<K: Hash>(key: &K) -> u64 {
+    xxhash_rust::xxh3::Xxh3DefaultBuilder::new().hash_one(key)
+}
+
+/// The ε-copy deserialization type of an [`IndexMap`]: a sequence of keys and
+/// a parallel sequence of values in insertion order.
+///
+/// The type parameters are the deserialization types of the vectors of
+/// keys and values, which will be slices for [`ZeroCopy`] types.
+/// The hash index used by [`get`](IndexMapView::get) is built lazily
+/// at the first lookup by key.
+#[derive(Debug, Clone)]
+pub struct IndexMapView<KS, VS> {
+    keys: KS,
+    values: VS,
+    index: OnceLock<HashIndex>,
+}
+
+impl<K: Hash + Eq, V, KS: Deref<Target = [K]>, VS: Deref<Target = [V]>> IndexMapView<KS, VS> {
+    /// Return the number of entries.
+    #[inline(always)]
+    pub fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    /// Return whether the map is empty.
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    /// Return the keys, in insertion order.
+    #[inline(always)]
+    pub fn keys(&self) -> &[K] {
+        &self.keys
+    }
+
+    /// Return the values, in insertion order.
+    #[inline(always)]
+    pub fn values(&self) -> &[V] {
+        &self.values
+    }
+
+    /// Return the entry at the given position, if any.
+    #[inline(always)]
+    pub fn get_index(&self, index: usize) -> Option<(&K, &V)> {
+        Some((self.keys.get(index)?, self.values.get(index)?))
+    }
+
+    /// Return an iterator on the entries, in insertion order.
+    pub fn iter(&self) -> core::iter::Zip<core::slice::Iter<'_, K>, core::slice::Iter<'_, V>> {
+        self.keys.iter().zip(self.values.iter())
+    }
+
+    /// Return the position of the given key, if present.
+    pub fn get_index_of<Q: Hash + Eq + ?Sized>(&self, key: &Q) -> Option<usize>
+    where
+        K: Borrow<Q>,
+    {
+        self.index
+            .get_or_init(|| HashIndex::new(&self.keys))
+            .index_of(&self.keys, key)
+    }
+
+    /// Return the value associated with the given key, if present.
+    pub fn get<Q: Hash + Eq + ?Sized>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+    {
+        self.get_index_of(key)
+            .and_then(|index| self.values.get(index))
+    }
+
+    /// Return whether the map contains the given key.
+    pub fn contains_key<Q: Hash + Eq + ?Sized>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+    {
+        self.get_index_of(key).is_some()
+    }
+}
+
+/// The ε-copy deserialization type of an [`IndexSet`]: a sequence of keys
+/// in insertion order.
+///
+/// The type parameter is the deserialization type of the vector of keys,
+/// which will be a slice for [`ZeroCopy`] types.
+/// The hash index used by [`contains`](IndexSetView::contains) is built lazily
+/// at the first lookup by key.
+#[derive(Debug, Clone)]
+pub struct IndexSetView<KS> {
+    keys: KS,
+    index: OnceLock<HashIndex>,
+}
+
+impl<K: Hash + Eq, KS: Deref<Target = [K]>> IndexSetView<KS> {
+    /// Return the number of keys.
+    #[inline(always)]
+    pub fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    /// Return whether the set is empty.
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    /// Return the keys, in insertion order.
+    #[inline(always)]
+    pub fn as_slice(&self) -> &[K] {
+        &self.keys
+    }
+
+    /// Return the key at the given position, if any.
+    #[inline(always)]
+    pub fn get_index(&self, index: usize) -> Option<&K> {
+        self.keys.get(index)
+    }
+
+    /// Return an iterator on the keys, in insertion order.
+    pub fn iter(&self) -> core::slice::Iter<'_, K> {
+        self.keys.iter()
+    }
+
+    /// Return the position of the given key, if present.
+    pub fn get_index_of<Q: Hash + Eq + ?Sized>(&self, key: &Q) -> Option<usize>
+    where
+        K: Borrow<Q>,
+    {
+        self.index
+            .get_or_init(|| HashIndex::new(&self.keys))
+            .index_of(&self.keys, key)
+    }
+
+    /// Return whether the set contains the given key.
+    pub fn contains<Q: Hash + Eq + ?Sized>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+    {
+        self.get_index_of(key).is_some()
+    }
+}
+
+impl<K, V, S> CopyType for IndexMap<K, V, S> {
+    type Copy = Deep;
+}
+
+impl<K: TypeHash, V: TypeHash, S> TypeHash for IndexMap<K, V, S> {
+    fn type_hash(hasher: &mut impl core::hash::Hasher) {
+        "IndexMap".hash(hasher);
+        K::type_hash(hasher);
+        V::type_hash(hasher);
+    }
+}
+
+impl<K: ReprHash, V: ReprHash, S> ReprHash for IndexMap<K, V, S> {
+    fn repr_hash(hasher: &mut impl core::hash::Hasher, offset_of: &mut usize) {
+        *offset_of = 0;
+        K::repr_hash(hasher, offset_of);
+        *offset_of = 0;
+        V::repr_hash(hasher, offset_of);
+    }
+}
+
+impl<K: CopyType + SerializeInner + Hash, V: CopyType + SerializeInner, S> SerializeInner
+    for IndexMap<K, V, S>
+where
+    K: SerializeIterHelper<<K as CopyType>::Copy>,
+    V: SerializeIterHelper<<V as CopyType>::Copy>,
+{
+    const IS_ZERO_COPY: bool = false;
+    const ZERO_COPY_MISMATCH: bool = false;
+    const MAX_FIELD_ALIGN: usize =
+        crate::ser::helpers::max_align(&[K::MAX_FIELD_ALIGN, V::MAX_FIELD_ALIGN]);
+
+    fn _serialize_inner(&self, backend: &mut impl WriteWithNames) -> ser::Result<()> {
+        if backend.is_canonical() {
+            let mut entries: Vec<_> = self.iter().collect();
+            // Stable, so keys with the same hash keep their relative order
+            entries.sort_by_cached_key(|(key, _)| canonical_hash(key));
+            K::_serialize_iter(backend, self.len(), entries.iter().map(|(key, _)| *key))?;
+            return V::_serialize_iter(
+                backend,
+                self.len(),
+                entries.iter().map(|(_, value)| *value),
+            );
+        }
+        K::_serialize_iter(backend, self.len(), self.keys())?;
+        V::_serialize_iter(backend, self.len(), self.values())
+    }
+}
+
+impl<K: DeserializeInner + Hash + Eq, V: DeserializeInner, S: BuildHasher + Default>
+    DeserializeInner for IndexMap<K, V, S>
+where
+    Vec<K>: DeserializeInner,
+    Vec<V>: DeserializeInner,
+{
+    type DeserType<'a> = IndexMapView<DeserType<'a, Vec<K>>, DeserType<'a, Vec<V>>>;
+
+    fn _deserialize_full_inner(backend: &mut impl ReadWithPos) -> deser::Result<Self> {
+        let keys = Vec::<K>::_deserialize_full_inner(backend)?;
+        let values = Vec::<V>::_deserialize_full_inner(backend)?;
+        Ok(keys.into_iter().zip(values).collect())
+    }
+
+    fn _deserialize_eps_inner<'a>(
+        backend: &mut SliceWithPos<'a>,
+    ) -> deser::Result<Self::DeserType<'a>> {
+        let keys = Vec::<K>::_deserialize_eps_inner(backend)?;
+        let values = Vec::<V>::_deserialize_eps_inner(backend)?;
+        Ok(IndexMapView {
+            keys,
+            values,
+            index: OnceLock::new(),
+        })
+    }
+}
+
+impl<K, S> CopyType for IndexSet<K, S> {
+    type Copy = Deep;
+}
+
+impl<K: TypeHash, S> TypeHash for IndexSet<K, S> {
+    fn type_hash(hasher: &mut impl core::hash::Hasher) {
+        "IndexSet".hash(hasher);
+        K::type_hash(hasher);
+    }
+}
+
+impl<K: ReprHash, S> ReprHash for IndexSet<K, S> {
+    fn repr_hash(hasher: &mut impl core::hash::Hasher, offset_of: &mut usize) {
+        *offset_of = 0;
+        K::repr_hash(hasher, offset_of);
+    }
+}
+
+impl<K: CopyType + SerializeInner + Hash, S> SerializeInner for IndexSet<K, S>
+where
+    K: SerializeIterHelper<<K as CopyType>::Copy>,
+{
+    const IS_ZERO_COPY: bool = false;
+    const ZERO_COPY_MISMATCH: bool = false;
+    const MAX_FIELD_ALIGN: usize = K::MAX_FIELD_ALIGN;
+
+    fn _serialize_inner(&self, backend: &mut impl WriteWithNames) -> ser::Result<()> {
+        if backend.is_canonical() {
+            let mut keys: Vec<_> = self.iter().collect();
+            // Stable, so keys with the same hash keep their relative order
+            keys.sort_by_cached_key(canonical_hash);
+            return K::_serialize_iter(backend, self.len(), keys.into_iter());
+        }
+        K::_serialize_iter(backend, self.len(), self.iter())
+    }
+}
+
+impl<K: DeserializeInner + Hash + Eq, S: BuildHasher + Default> DeserializeInner for IndexSet<K, S>
+where
+    Vec<K>: DeserializeInner,
+{
+    type DeserType<'a> = IndexSetView<DeserType<'a, Vec<K>>>;
+
+    fn _deserialize_full_inner(backend: &mut impl ReadWithPos) -> deser::Result<Self> {
+        Ok(Vec::<K>::_deserialize_full_inner(backend)?
+            .into_iter()
+            .collect())
+    }
+
+    fn _deserialize_eps_inner<'a>(
+        backend: &mut SliceWithPos<'a>,
+    ) -> deser::Result<Self::DeserType<'a>> {
+        Ok(IndexSetView {
+            keys: Vec::<K>::_deserialize_eps_inner(backend)?,
+            index: OnceLock::new(),
+        })
+    }
+}
@@ -0,0 +1,50 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ * SPDX-FileCopyrightText: 2023 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+/*!
+
+Implementations of [`SerializeInner`](crate::ser::SerializeInner)
+and [`DeserializeInner`](crate::deser::DeserializeInner) for standard Rust types.
+
+*/
+
+pub mod array;
+#[cfg(feature = "arrayvec")]
+pub mod arrayvec;
+pub mod atomic;
+pub mod boxed;
+pub mod boxed_slice;
+#[cfg(any(feature = "alloc", feature = "std"))]
+pub mod btree;
+#[cfg(any(feature = "alloc", feature = "std"))]
+pub mod collections;
+#[cfg(any(feature = "alloc", feature = "std"))]
+pub mod cow;
+#[cfg(feature = "half")]
+pub mod half;
+#[cfg(all(feature = "indexmap", feature = "std"))]
+pub mod indexmap;
+#[cfg(feature = "nalgebra")]
+pub mod nalgebra;
+#[cfg(feature = "ndarray")]
+pub mod ndarray;
+pub mod net;
+pub mod prim;
+#[cfg(any(feature = "alloc", feature = "std"))]
+pub mod rc;
+#[cfg(all(feature = "simd", any(target_arch = "x86", target_arch = "x86_64")))]
+pub mod simd;
+pub mod slice;
+#[cfg(feature = "smallvec")]
+pub mod smallvec;
+pub mod string;
+pub mod time;
+#[cfg(feature = "tinyvec")]
+pub mod tinyvec;
+pub mod tuple;
+#[cfg(any(feature = "alloc", feature = "std"))]
+pub mod vec;
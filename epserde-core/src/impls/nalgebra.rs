@@ -0,0 +1,105 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ * SPDX-FileCopyrightText: 2023 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+/*!
+
+Implementations for [`nalgebra`] statically sized matrices
+(requires the `nalgebra` feature).
+
+An [`SMatrix`] (and thus an [`SVector`](::nalgebra::SVector)) of
+[`ZeroCopy`] elements is zero-copy, as its storage is a column-major
+array of arrays; ε-copy deserialization returns a reference to the matrix,
+and slices of matrices can be ε-copy deserialized, too.
+
+```rust
+use epserde::prelude::*;
+use nalgebra::Matrix2;
+
+let v = vec![Matrix2::new(1.0_f64, 2.0, 3.0, 4.0); 3];
+let mut cursor = epserde::new_aligned_cursor();
+v.serialize(&mut cursor).unwrap();
+let buf = cursor.into_inner();
+let view: &[Matrix2<f64>] = <Vec<Matrix2<f64>>>::deserialize_eps(&buf).unwrap();
+assert_eq!(view, v.as_slice());
+```
+
+*/
+
+use crate::prelude::*;
+use ::nalgebra::SMatrix;
+use core::hash::Hash;
+use deser::*;
+use ser::*;
+
+impl<T: ZeroCopy, const R: usize, const C: usize> CopyType for SMatrix<T, R, C> {
+    type Copy = Zero;
+}
+
+impl<T: TypeHash, const R: usize, const C: usize> TypeHash for SMatrix<T, R, C> {
+    fn type_hash(hasher: &mut impl core::hash::Hasher) {
+        "nalgebra::SMatrix".hash(hasher);
+        hasher.write_usize(R);
+        hasher.write_usize(C);
+        T::type_hash(hasher);
+    }
+}
+
+// A matrix is repr(C) and has the same layout of its storage, which is a
+// repr(transparent) wrapper around [[T; R]; C]
+impl<T: Sized, const R: usize, const C: usize> ReprHash for SMatrix<T, R, C> {
+    fn repr_hash(hasher: &mut impl core::hash::Hasher, offset_of: &mut usize) {
+        <[[T; R]; C]>::repr_hash(hasher, offset_of)
+    }
+}
+
+impl<T: MaxSizeOf, const R: usize, const C: usize> MaxSizeOf for SMatrix<T, R, C> {
+    const WIDTH_DEPENDENT: bool = T::WIDTH_DEPENDENT;
+
+    fn max_size_of() -> usize {
+        T::max_size_of()
+    }
+}
+
+impl<T: ByteSwap, const R: usize, const C: usize> ByteSwap for SMatrix<T, R, C> {
+    fn byte_swap(bytes: &mut [u8]) -> deser::Result<()> {
+        <[[T; R]; C]>::byte_swap(bytes)
+    }
+}
+
+impl<T: CheckBytes, const R: usize, const C: usize> CheckBytes for SMatrix<T, R, C> {
+    fn check_bytes(bytes: &[u8]) -> deser::Result<()> {
+        <[[T; R]; C]>::check_bytes(bytes)
+    }
+}
+
+impl<T: ZeroCopy + SerializeInner + TypeHash, const R: usize, const C: usize> SerializeInner
+    for SMatrix<T, R, C>
+{
+    const IS_ZERO_COPY: bool = T::IS_ZERO_COPY;
+    const ZERO_COPY_MISMATCH: bool = false;
+    const MAX_FIELD_ALIGN: usize = T::MAX_FIELD_ALIGN;
+
+    fn _serialize_inner(&self, backend: &mut impl WriteWithNames) -> ser::Result<()> {
+        serialize_zero(backend, self)
+    }
+}
+
+impl<T: ZeroCopy + DeserializeInner + 'static, const R: usize, const C: usize> DeserializeInner
+    for SMatrix<T, R, C>
+{
+    fn _deserialize_full_inner(backend: &mut impl ReadWithPos) -> deser::Result<Self> {
+        deserialize_full_zero(backend)
+    }
+
+    type DeserType<'a> = &'a SMatrix<T, R, C>;
+
+    fn _deserialize_eps_inner<'a>(
+        backend: &mut SliceWithPos<'a>,
+    ) -> deser::Result<Self::DeserType<'a>> {
+        deserialize_eps_zero(backend)
+    }
+}
@@ -0,0 +1,101 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ * SPDX-FileCopyrightText: 2023 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+/*!
+
+Implementations for one- and two-dimensional [`ndarray`] arrays
+(requires the `ndarray` feature).
+
+An [`Array1`] or an [`Array2`] of [`ZeroCopy`] elements is serialized as its
+shape followed by its elements in logical (row-major) order, independently
+of its memory layout. ε-copy deserialization returns an [`ArrayView1`] or an
+[`ArrayView2`] backed by the serialized data, and reports
+[`Error::InvalidValue`] if the shape does not match the number of elements.
+
+```rust
+use epserde::prelude::*;
+use ndarray::{array, Array2, ArrayView2};
+
+let a = array![[1_u32, 2, 3], [4, 5, 6]];
+let mut cursor = epserde::new_aligned_cursor();
+a.serialize(&mut cursor).unwrap();
+let buf = cursor.into_inner();
+let view: ArrayView2<u32> = <Array2<u32>>::deserialize_eps(&buf).unwrap();
+assert_eq!(view, a);
+```
+
+*/
+
+use crate::prelude::*;
+use ::ndarray::{Array, ArrayView, Ix1, Ix2};
+use core::hash::Hash;
+use deser::*;
+use ser::*;
+
+#[cfg(doc)]
+use ::ndarray::{Array1, Array2, ArrayView1, ArrayView2};
+
+macro_rules! impl_array {
+    ($dim:ty, $n:literal, $name:literal) => {
+        impl<T> CopyType for Array<T, $dim> {
+            type Copy = Deep;
+        }
+
+        impl<T: TypeHash> TypeHash for Array<T, $dim> {
+            fn type_hash(hasher: &mut impl core::hash::Hasher) {
+                "ndarray::Array".hash(hasher);
+                hasher.write_usize($n);
+                T::type_hash(hasher);
+            }
+        }
+
+        impl<T: ReprHash> ReprHash for Array<T, $dim> {
+            fn repr_hash(hasher: &mut impl core::hash::Hasher, offset_of: &mut usize) {
+                *offset_of = 0;
+                T::repr_hash(hasher, offset_of);
+            }
+        }
+
+        impl<T: ZeroCopy + SerializeInner> SerializeInner for Array<T, $dim> {
+            const IS_ZERO_COPY: bool = false;
+            const ZERO_COPY_MISMATCH: bool = false;
+            const MAX_FIELD_ALIGN: usize = max_align(&[usize::MAX_FIELD_ALIGN, T::MAX_FIELD_ALIGN]);
+
+            fn _serialize_inner(&self, backend: &mut impl WriteWithNames) -> ser::Result<()> {
+                let mut shape = [0; $n];
+                shape.copy_from_slice(self.shape());
+                backend.write("shape", &shape)?;
+                match self.as_slice() {
+                    Some(data) => serialize_slice_zero(backend, data),
+                    // Not in standard layout (e.g., transposed)
+                    None => T::_serialize_iter(backend, self.len(), self.iter()),
+                }
+            }
+        }
+
+        impl<T: ZeroCopy + DeserializeInner + 'static> DeserializeInner for Array<T, $dim> {
+            fn _deserialize_full_inner(backend: &mut impl ReadWithPos) -> deser::Result<Self> {
+                let shape = in_field(backend, "shape", <[usize; $n]>::_deserialize_full_inner)?;
+                let data = deserialize_full_vec_zero(backend)?;
+                Array::from_shape_vec(shape, data).map_err(|_| deser::Error::InvalidValue($name))
+            }
+
+            type DeserType<'a> = ArrayView<'a, T, $dim>;
+
+            fn _deserialize_eps_inner<'a>(
+                backend: &mut SliceWithPos<'a>,
+            ) -> deser::Result<Self::DeserType<'a>> {
+                let shape = in_field(backend, "shape", <[usize; $n]>::_deserialize_full_inner)?;
+                let data = deserialize_eps_slice_zero(backend)?;
+                ArrayView::from_shape(shape, data).map_err(|_| deser::Error::InvalidValue($name))
+            }
+        }
+    };
+}
+
+impl_array!(Ix1, 1, "Array1");
+impl_array!(Ix2, 2, "Array2");
@@ -0,0 +1,248 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ * SPDX-FileCopyrightText: 2023 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+/*!
+
+Implementations for IP addresses and socket addresses.
+
+[`Ipv4Addr`] and [`Ipv6Addr`] are serialized as their octets, in network
+order. [`SocketAddrV4`] is serialized as its address followed by a `u16`
+port; [`SocketAddrV6`] is serialized as its address followed by a `u16`
+port, a `u32` flow information, and a `u32` scope identifier.
+[`IpAddr`] and [`SocketAddr`] are serialized as a `u8` tag (4 or 6)
+followed by the corresponding variant.
+
+All types are deep-copy, as their layout is not specified, and they
+deserialize to themselves.
+
+*/
+
+use crate::prelude::*;
+use core::hash::Hash;
+use core::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, SocketAddrV4, SocketAddrV6};
+use deser::*;
+use ser::*;
+
+macro_rules! impl_net {
+    ($($ty:ident),*) => {$(
+        impl CopyType for $ty {
+            type Copy = Deep;
+        }
+
+        impl TypeHash for $ty {
+            fn type_hash(hasher: &mut impl core::hash::Hasher) {
+                stringify!($ty).hash(hasher);
+            }
+        }
+
+        impl ReprHash for $ty {
+            fn repr_hash(_hasher: &mut impl core::hash::Hasher, _offset_of: &mut usize) {}
+        }
+    )*};
+}
+
+impl_net!(
+    Ipv4Addr,
+    Ipv6Addr,
+    IpAddr,
+    SocketAddrV4,
+    SocketAddrV6,
+    SocketAddr
+);
+
+impl SerializeInner for Ipv4Addr {
+    const IS_ZERO_COPY: bool = false;
+    const ZERO_COPY_MISMATCH: bool = false;
+    const MAX_FIELD_ALIGN: usize = 1;
+
+    fn _serialize_inner(&self, backend: &mut impl WriteWithNames) -> ser::Result<()> {
+        backend.write("octets", &self.octets())
+    }
+}
+
+impl DeserializeInner for Ipv4Addr {
+    type DeserType<'a> = Self;
+
+    fn _deserialize_full_inner(backend: &mut impl ReadWithPos) -> deser::Result<Self> {
+        Ok(<[u8; 4]>::_deserialize_full_inner(backend)?.into())
+    }
+
+    fn _deserialize_eps_inner<'a>(
+        backend: &mut SliceWithPos<'a>,
+    ) -> deser::Result<Self::DeserType<'a>> {
+        Self::_deserialize_full_inner(backend)
+    }
+}
+
+impl SerializeInner for Ipv6Addr {
+    const IS_ZERO_COPY: bool = false;
+    const ZERO_COPY_MISMATCH: bool = false;
+    const MAX_FIELD_ALIGN: usize = 1;
+
+    fn _serialize_inner(&self, backend: &mut impl WriteWithNames) -> ser::Result<()> {
+        backend.write("octets", &self.octets())
+    }
+}
+
+impl DeserializeInner for Ipv6Addr {
+    type DeserType<'a> = Self;
+
+    fn _deserialize_full_inner(backend: &mut impl ReadWithPos) -> deser::Result<Self> {
+        Ok(<[u8; 16]>::_deserialize_full_inner(backend)?.into())
+    }
+
+    fn _deserialize_eps_inner<'a>(
+        backend: &mut SliceWithPos<'a>,
+    ) -> deser::Result<Self::DeserType<'a>> {
+        Self::_deserialize_full_inner(backend)
+    }
+}
+
+impl SerializeInner for SocketAddrV4 {
+    const IS_ZERO_COPY: bool = false;
+    const ZERO_COPY_MISMATCH: bool = false;
+    const MAX_FIELD_ALIGN: usize =
+        crate::ser::helpers::max_align(&[Ipv4Addr::MAX_FIELD_ALIGN, u16::MAX_FIELD_ALIGN]);
+
+    fn _serialize_inner(&self, backend: &mut impl WriteWithNames) -> ser::Result<()> {
+        backend.write("ip", self.ip())?;
+        backend.write("port", &self.port())
+    }
+}
+
+impl DeserializeInner for SocketAddrV4 {
+    type DeserType<'a> = Self;
+
+    fn _deserialize_full_inner(backend: &mut impl ReadWithPos) -> deser::Result<Self> {
+        let ip = Ipv4Addr::_deserialize_full_inner(backend)?;
+        let port = u16::_deserialize_full_inner(backend)?;
+        Ok(SocketAddrV4::new(ip, port))
+    }
+
+    fn _deserialize_eps_inner<'a>(
+        backend: &mut SliceWithPos<'a>,
+    ) -> deser::Result<Self::DeserType<'a>> {
+        Self::_deserialize_full_inner(backend)
+    }
+}
+
+impl SerializeInner for SocketAddrV6 {
+    const IS_ZERO_COPY: bool = false;
+    const ZERO_COPY_MISMATCH: bool = false;
+    const MAX_FIELD_ALIGN: usize = crate::ser::helpers::max_align(&[
+        Ipv6Addr::MAX_FIELD_ALIGN,
+        u16::MAX_FIELD_ALIGN,
+        u32::MAX_FIELD_ALIGN,
+    ]);
+
+    fn _serialize_inner(&self, backend: &mut impl WriteWithNames) -> ser::Result<()> {
+        backend.write("ip", self.ip())?;
+        backend.write("port", &self.port())?;
+        backend.write("flowinfo", &self.flowinfo())?;
+        backend.write("scope_id", &self.scope_id())
+    }
+}
+
+impl DeserializeInner for SocketAddrV6 {
+    type DeserType<'a> = Self;
+
+    fn _deserialize_full_inner(backend: &mut impl ReadWithPos) -> deser::Result<Self> {
+        let ip = Ipv6Addr::_deserialize_full_inner(backend)?;
+        let port = u16::_deserialize_full_inner(backend)?;
+        let flowinfo = u32::_deserialize_full_inner(backend)?;
+        let scope_id = u32::_deserialize_full_inner(backend)?;
+        Ok(SocketAddrV6::new(ip, port, flowinfo, scope_id))
+    }
+
+    fn _deserialize_eps_inner<'a>(
+        backend: &mut SliceWithPos<'a>,
+    ) -> deser::Result<Self::DeserType<'a>> {
+        Self::_deserialize_full_inner(backend)
+    }
+}
+
+impl SerializeInner for IpAddr {
+    const IS_ZERO_COPY: bool = false;
+    const ZERO_COPY_MISMATCH: bool = false;
+    const MAX_FIELD_ALIGN: usize =
+        crate::ser::helpers::max_align(&[Ipv4Addr::MAX_FIELD_ALIGN, Ipv6Addr::MAX_FIELD_ALIGN]);
+
+    fn _serialize_inner(&self, backend: &mut impl WriteWithNames) -> ser::Result<()> {
+        match self {
+            IpAddr::V4(ip) => {
+                backend.write("Tag", &4_u8)?;
+                backend.write("V4", ip)
+            }
+            IpAddr::V6(ip) => {
+                backend.write("Tag", &6_u8)?;
+                backend.write("V6", ip)
+            }
+        }
+    }
+}
+
+impl DeserializeInner for IpAddr {
+    type DeserType<'a> = Self;
+
+    fn _deserialize_full_inner(backend: &mut impl ReadWithPos) -> deser::Result<Self> {
+        match u8::_deserialize_full_inner(backend)? {
+            4 => Ok(IpAddr::V4(Ipv4Addr::_deserialize_full_inner(backend)?)),
+            6 => Ok(IpAddr::V6(Ipv6Addr::_deserialize_full_inner(backend)?)),
+            tag => Err(deser::Error::InvalidTag(tag as usize)),
+        }
+    }
+
+    fn _deserialize_eps_inner<'a>(
+        backend: &mut SliceWithPos<'a>,
+    ) -> deser::Result<Self::DeserType<'a>> {
+        Self::_deserialize_full_inner(backend)
+    }
+}
+
+impl SerializeInner for SocketAddr {
+    const IS_ZERO_COPY: bool = false;
+    const ZERO_COPY_MISMATCH: bool = false;
+    const MAX_FIELD_ALIGN: usize = crate::ser::helpers::max_align(&[
+        SocketAddrV4::MAX_FIELD_ALIGN,
+        SocketAddrV6::MAX_FIELD_ALIGN,
+    ]);
+
+    fn _serialize_inner(&self, backend: &mut impl WriteWithNames) -> ser::Result<()> {
+        match self {
+            SocketAddr::V4(addr) => {
+                backend.write("Tag", &4_u8)?;
+                backend.write("V4", addr)
+            }
+            SocketAddr::V6(addr) => {
+                backend.write("Tag", &6_u8)?;
+                backend.write("V6", addr)
+            }
+        }
+    }
+}
+
+impl DeserializeInner for SocketAddr {
+    type DeserType<'a> = Self;
+
+    fn _deserialize_full_inner(backend: &mut impl ReadWithPos) -> deser::Result<Self> {
+        match u8::_deserialize_full_inner(backend)? {
+            4 => Ok(SocketAddr::V4(SocketAddrV4::_deserialize_full_inner(
+                backend,
+            )?)),
+            6 => Ok(SocketAddr::V6(SocketAddrV6::_deserialize_full_inner(
+                backend,
+            )?)),
+            tag => Err(deser::Error::InvalidTag(tag as usize)),
+        }
+    }
+
+    fn _deserialize_eps_inner<'a>(
+        backend: &mut SliceWithPos<'a>,
+    ) -> deser::Result<Self::DeserType<'a>> {
+        Self::_deserialize_full_inner(backend)
+    }
+}
@@ -0,0 +1,538 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ * SPDX-FileCopyrightText: 2023 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+/*!
+
+Implementations for primitive types, nonzero integers, `()`, [`RangeFull`],
+[`PhantomData`] and [`Option`].
+
+`()`, [`RangeFull`], and [`PhantomData`] are zero-sized zero-copy types, so
+they can be used as fields of zero-copy structures (e.g., as type-level
+configuration markers) without contributing any byte to the serialized data.
+
+*/
+
+use crate::prelude::*;
+use core::hash::Hash;
+use core::marker::PhantomData;
+use core::mem::size_of;
+use core::num::*;
+use core::ops::RangeFull;
+use deser::*;
+use ser::*;
+
+macro_rules! impl_prim_type_hash {
+    ($($ty:ty),*) => {
+        impl_prim_type_hash!(false; $($ty),*);
+    };
+    ($width_dependent:literal; $($ty:ty),*) => {$(
+        impl CopyType for $ty {
+            type Copy = Zero;
+        }
+
+        impl TypeHash for $ty {
+            fn type_hash(
+                hasher: &mut impl core::hash::Hasher,
+            ) {
+                stringify!($ty).hash(hasher);
+            }
+        }
+
+        impl ReprHash for $ty {
+            fn repr_hash(hasher: &mut impl core::hash::Hasher, offset_of: &mut usize) {
+                // Width-dependent types are hashed as 64-bit integers, so
+                // that their representation hash does not depend on the
+                // pointer width (see repr_hash_of)
+                if $width_dependent {
+                    crate::traits::std_repr_hash::<u64>(hasher, offset_of)
+                } else {
+                    crate::traits::std_repr_hash::<Self>(hasher, offset_of)
+                }
+            }
+        }
+
+        impl MaxSizeOf for $ty {
+            const WIDTH_DEPENDENT: bool = $width_dependent;
+
+            fn max_size_of() -> usize {
+                size_of::<$ty>()
+            }
+        }
+
+        impl ByteSwap for $ty {
+            #[inline(always)]
+            fn byte_swap(bytes: &mut [u8]) -> deser::Result<()> {
+                bytes.reverse();
+                Ok(())
+            }
+        }
+    )*};
+}
+
+macro_rules! impl_prim_check_bytes {
+    ($($ty:ty),*) => {$(
+        impl CheckBytes for $ty {
+            #[inline(always)]
+            fn check_bytes(_bytes: &[u8]) -> deser::Result<()> {
+                // Every bit pattern is valid
+                Ok(())
+            }
+        }
+    )*};
+}
+
+macro_rules! impl_prim_ser_des {
+    ($($ty:ty),*) => {$(
+		impl SerializeInner for $ty {
+            // Note that primitive types are declared zero-copy to be able to
+            // be part of zero-copy types, but we actually deserialize
+            // them in isolation as values.
+            const IS_ZERO_COPY: bool = true;
+            const ZERO_COPY_MISMATCH: bool = false;
+            const MAX_FIELD_ALIGN: usize = size_of::<$ty>();
+
+            #[inline(always)]
+            fn _serialize_inner(&self, backend: &mut impl WriteWithNames) -> ser::Result<()> {
+                backend.write_all(&self.to_ne_bytes())
+            }
+        }
+
+		impl DeserializeInner for $ty {
+            #[inline(always)]
+            fn _deserialize_full_inner(backend: &mut impl ReadWithPos) -> deser::Result<$ty> {
+                let mut buf = [0; size_of::<$ty>()];
+                backend.read_exact(&mut buf)?;
+                if backend.swap_bytes() {
+                    buf.reverse();
+                }
+                Ok(<$ty>::from_ne_bytes(buf))
+            }
+            type DeserType<'a> = Self;
+            #[inline(always)]
+            fn _deserialize_eps_inner<'a>(
+                backend: &mut SliceWithPos<'a>,
+            ) -> deser::Result<Self::DeserType<'a>> {
+                let mut buf = [0; size_of::<$ty>()];
+                backend.read_exact(&mut buf)?;
+                Ok(<$ty>::from_ne_bytes(buf))
+            }
+        }
+    )*};
+}
+
+// The size of usize and isize depends on the pointer width.
+impl_prim_type_hash!(true; isize, usize);
+impl_prim_type_hash!(
+    i8,
+    i16,
+    i32,
+    i64,
+    i128,
+    u8,
+    u16,
+    u32,
+    u64,
+    u128,
+    f32,
+    f64,
+    bool,
+    char,
+    (),
+    RangeFull
+);
+impl_prim_check_bytes!(
+    isize,
+    i8,
+    i16,
+    i32,
+    i64,
+    i128,
+    usize,
+    u8,
+    u16,
+    u32,
+    u64,
+    u128,
+    f32,
+    f64,
+    (),
+    RangeFull
+);
+impl_prim_ser_des!(i8, i16, i32, i64, i128, u8, u16, u32, u64, u128, f32, f64);
+
+// usize and isize are written using eight bytes in portable data, and
+// might need to be read using four or eight bytes on architectures with a
+// different pointer width.
+
+macro_rules! impl_size_ser_des {
+    ($($ty:ty => $portable:ty, $narrow:ty),*) => {$(
+        impl SerializeInner for $ty {
+            const IS_ZERO_COPY: bool = true;
+            const ZERO_COPY_MISMATCH: bool = false;
+            const MAX_FIELD_ALIGN: usize = size_of::<$ty>();
+
+            #[inline(always)]
+            fn _serialize_inner(&self, backend: &mut impl WriteWithNames) -> ser::Result<()> {
+                if backend.is_portable() {
+                    backend.write_all(&(*self as $portable).to_ne_bytes())
+                } else {
+                    backend.write_all(&self.to_ne_bytes())
+                }
+            }
+        }
+
+        impl DeserializeInner for $ty {
+            #[inline(always)]
+            fn _deserialize_full_inner(backend: &mut impl ReadWithPos) -> deser::Result<$ty> {
+                if backend.usize_size() == size_of::<$ty>() {
+                    let mut buf = [0; size_of::<$ty>()];
+                    backend.read_exact(&mut buf)?;
+                    if backend.swap_bytes() {
+                        buf.reverse();
+                    }
+                    Ok(<$ty>::from_ne_bytes(buf))
+                } else if backend.usize_size() == size_of::<$narrow>() {
+                    let value = <$narrow>::_deserialize_full_inner(backend)?;
+                    <$ty>::try_from(value).map_err(|_| deser::Error::UsizeOutOfRange(value as i128))
+                } else {
+                    let value = <$portable>::_deserialize_full_inner(backend)?;
+                    <$ty>::try_from(value).map_err(|_| deser::Error::UsizeOutOfRange(value as i128))
+                }
+            }
+            type DeserType<'a> = Self;
+            #[inline(always)]
+            fn _deserialize_eps_inner<'a>(
+                backend: &mut SliceWithPos<'a>,
+            ) -> deser::Result<Self::DeserType<'a>> {
+                Self::_deserialize_full_inner(backend)
+            }
+        }
+    )*};
+}
+
+impl_size_ser_des!(usize => u64, u32, isize => i64, i32);
+
+// Booleans are zero-copy serialized as u8.
+
+impl SerializeInner for bool {
+    const IS_ZERO_COPY: bool = true;
+    const ZERO_COPY_MISMATCH: bool = false;
+    const MAX_FIELD_ALIGN: usize = 1;
+
+    #[inline(always)]
+    fn _serialize_inner(&self, backend: &mut impl WriteWithNames) -> ser::Result<()> {
+        let val = if *self { 1 } else { 0 };
+        backend.write_all(&[val])
+    }
+}
+
+impl DeserializeInner for bool {
+    #[inline(always)]
+    fn _deserialize_full_inner(backend: &mut impl ReadWithPos) -> deser::Result<bool> {
+        match u8::_deserialize_full_inner(backend)? {
+            0 => Ok(false),
+            1 => Ok(true),
+            _ => Err(deser::Error::InvalidValue("bool")),
+        }
+    }
+    type DeserType<'a> = Self;
+    #[inline(always)]
+    fn _deserialize_eps_inner<'a>(
+        backend: &mut SliceWithPos<'a>,
+    ) -> deser::Result<Self::DeserType<'a>> {
+        Self::_deserialize_full_inner(backend)
+    }
+}
+
+impl CheckBytes for bool {
+    #[inline(always)]
+    fn check_bytes(bytes: &[u8]) -> deser::Result<()> {
+        match bytes.first() {
+            Some(0 | 1) => Ok(()),
+            _ => Err(deser::Error::InvalidValue("bool")),
+        }
+    }
+}
+
+// Chars are zero-copy serialized as u32.
+
+impl SerializeInner for char {
+    const IS_ZERO_COPY: bool = true;
+    const ZERO_COPY_MISMATCH: bool = false;
+    const MAX_FIELD_ALIGN: usize = 4;
+
+    #[inline(always)]
+    fn _serialize_inner(&self, backend: &mut impl WriteWithNames) -> ser::Result<()> {
+        (*self as u32)._serialize_inner(backend)
+    }
+}
+
+impl DeserializeInner for char {
+    #[inline(always)]
+    fn _deserialize_full_inner(backend: &mut impl ReadWithPos) -> deser::Result<Self> {
+        char::from_u32(u32::_deserialize_full_inner(backend)?)
+            .ok_or(deser::Error::InvalidValue("char"))
+    }
+    type DeserType<'a> = Self;
+    #[inline(always)]
+    fn _deserialize_eps_inner<'a>(
+        backend: &mut SliceWithPos<'a>,
+    ) -> deser::Result<Self::DeserType<'a>> {
+        char::from_u32(u32::_deserialize_eps_inner(backend)?)
+            .ok_or(deser::Error::InvalidValue("char"))
+    }
+}
+
+impl CheckBytes for char {
+    #[inline(always)]
+    fn check_bytes(bytes: &[u8]) -> deser::Result<()> {
+        let mut buf = [0; size_of::<u32>()];
+        buf.copy_from_slice(bytes);
+        char::from_u32(u32::from_ne_bytes(buf))
+            .map(|_| ())
+            .ok_or(deser::Error::InvalidValue("char"))
+    }
+}
+
+// Nonzero integers are zero-copy serialized as the corresponding integers.
+// Deserializing a zero yields an error.
+
+impl_prim_type_hash!(true; NonZeroIsize, NonZeroUsize);
+impl_prim_type_hash!(
+    NonZeroI8,
+    NonZeroI16,
+    NonZeroI32,
+    NonZeroI64,
+    NonZeroI128,
+    NonZeroU8,
+    NonZeroU16,
+    NonZeroU32,
+    NonZeroU64,
+    NonZeroU128
+);
+
+macro_rules! impl_nonzero_ser_des {
+    ($($ty:ty => $prim:ty),*) => {$(
+        impl SerializeInner for $ty {
+            const IS_ZERO_COPY: bool = true;
+            const ZERO_COPY_MISMATCH: bool = false;
+            const MAX_FIELD_ALIGN: usize = size_of::<$ty>();
+
+            #[inline(always)]
+            fn _serialize_inner(&self, backend: &mut impl WriteWithNames) -> ser::Result<()> {
+                self.get()._serialize_inner(backend)
+            }
+        }
+
+        impl DeserializeInner for $ty {
+            #[inline(always)]
+            fn _deserialize_full_inner(backend: &mut impl ReadWithPos) -> deser::Result<Self> {
+                <$ty>::new(<$prim>::_deserialize_full_inner(backend)?)
+                    .ok_or(deser::Error::InvalidValue(stringify!($ty)))
+            }
+            type DeserType<'a> = Self;
+            #[inline(always)]
+            fn _deserialize_eps_inner<'a>(
+                backend: &mut SliceWithPos<'a>,
+            ) -> deser::Result<Self::DeserType<'a>> {
+                <$ty>::new(<$prim>::_deserialize_eps_inner(backend)?)
+                    .ok_or(deser::Error::InvalidValue(stringify!($ty)))
+            }
+        }
+
+        impl CheckBytes for $ty {
+            #[inline(always)]
+            fn check_bytes(bytes: &[u8]) -> deser::Result<()> {
+                if bytes.iter().all(|&byte| byte == 0) {
+                    return Err(deser::Error::InvalidValue(stringify!($ty)));
+                }
+                Ok(())
+            }
+        }
+    )*};
+}
+
+impl_nonzero_ser_des!(
+    NonZeroIsize => isize,
+    NonZeroI8 => i8,
+    NonZeroI16 => i16,
+    NonZeroI32 => i32,
+    NonZeroI64 => i64,
+    NonZeroI128 => i128,
+    NonZeroUsize => usize,
+    NonZeroU8 => u8,
+    NonZeroU16 => u16,
+    NonZeroU32 => u32,
+    NonZeroU64 => u64,
+    NonZeroU128 => u128
+);
+
+// () and RangeFull are zero-copy. No reading or writing is performed when (de)serializing them.
+
+macro_rules! impl_unit_ser_des {
+    ($($ty:ty => $value:expr),*) => {$(
+        impl SerializeInner for $ty {
+            const IS_ZERO_COPY: bool = true;
+            const ZERO_COPY_MISMATCH: bool = false;
+            const MAX_FIELD_ALIGN: usize = 1;
+
+            #[inline(always)]
+            fn _serialize_inner(&self, _backend: &mut impl WriteWithNames) -> ser::Result<()> {
+                Ok(())
+            }
+        }
+
+        impl DeserializeInner for $ty {
+            #[inline(always)]
+            fn _deserialize_full_inner(_backend: &mut impl ReadWithPos) -> deser::Result<Self> {
+                Ok($value)
+            }
+            type DeserType<'a> = Self;
+            #[inline(always)]
+            fn _deserialize_eps_inner<'a>(
+                _backend: &mut SliceWithPos<'a>,
+            ) -> deser::Result<Self::DeserType<'a>> {
+                Ok($value)
+            }
+        }
+    )*};
+}
+
+impl_unit_ser_des!(() => (), RangeFull => ..);
+
+// PhantomData is zero-copy. No reading or writing is performed when (de)serializing it.
+
+impl<T: ?Sized> CopyType for PhantomData<T> {
+    type Copy = Zero;
+}
+
+impl<T: ?Sized + TypeHash> TypeHash for PhantomData<T> {
+    #[inline(always)]
+    fn type_hash(hasher: &mut impl core::hash::Hasher) {
+        "PhantomData".hash(hasher);
+        T::type_hash(hasher);
+    }
+}
+
+impl<T: ?Sized> ReprHash for PhantomData<T> {
+    #[inline(always)]
+    fn repr_hash(_hasher: &mut impl core::hash::Hasher, _offset_of: &mut usize) {}
+}
+
+impl<T: ?Sized> MaxSizeOf for PhantomData<T> {
+    const WIDTH_DEPENDENT: bool = false;
+
+    #[inline(always)]
+    fn max_size_of() -> usize {
+        0
+    }
+}
+
+impl<T: ?Sized> ByteSwap for PhantomData<T> {
+    #[inline(always)]
+    fn byte_swap(_bytes: &mut [u8]) -> deser::Result<()> {
+        Ok(())
+    }
+}
+
+impl<T: ?Sized> CheckBytes for PhantomData<T> {
+    #[inline(always)]
+    fn check_bytes(_bytes: &[u8]) -> deser::Result<()> {
+        Ok(())
+    }
+}
+
+impl<T: ?Sized + TypeHash> SerializeInner for PhantomData<T> {
+    const IS_ZERO_COPY: bool = true;
+    const ZERO_COPY_MISMATCH: bool = false;
+    const MAX_FIELD_ALIGN: usize = 1;
+
+    #[inline(always)]
+    fn _serialize_inner(&self, _backend: &mut impl WriteWithNames) -> ser::Result<()> {
+        Ok(())
+    }
+}
+
+impl<T: ?Sized + TypeHash> DeserializeInner for PhantomData<T> {
+    #[inline(always)]
+    fn _deserialize_full_inner(_backend: &mut impl ReadWithPos) -> deser::Result<Self> {
+        Ok(PhantomData::<T>)
+    }
+    type DeserType<'a> = Self;
+    #[inline(always)]
+    fn _deserialize_eps_inner<'a>(
+        _backend: &mut SliceWithPos<'a>,
+    ) -> deser::Result<Self::DeserType<'a>> {
+        Ok(PhantomData)
+    }
+}
+
+// Options are deep-copy types serialized as a one-byte tag (0 for None, 1 for Some) followed, in case, by the value.
+
+impl<T> CopyType for Option<T> {
+    type Copy = Deep;
+}
+
+impl<T: TypeHash> TypeHash for Option<T> {
+    #[inline(always)]
+    fn type_hash(hasher: &mut impl core::hash::Hasher) {
+        "Option".hash(hasher);
+        T::type_hash(hasher);
+    }
+}
+
+impl<T: ReprHash> ReprHash for Option<T> {
+    fn repr_hash(hasher: &mut impl core::hash::Hasher, offset_of: &mut usize) {
+        *offset_of = 0;
+        T::repr_hash(hasher, offset_of);
+    }
+}
+
+impl<T: SerializeInner> SerializeInner for Option<T> {
+    const IS_ZERO_COPY: bool = false;
+    const ZERO_COPY_MISMATCH: bool = false;
+    const MAX_FIELD_ALIGN: usize = T::MAX_FIELD_ALIGN;
+
+    #[inline(always)]
+    fn _serialize_inner(&self, backend: &mut impl WriteWithNames) -> ser::Result<()> {
+        match self {
+            None => backend.write("Tag", &0_u8),
+            Some(val) => {
+                backend.write("Tag", &1_u8)?;
+                backend.write("Some", val)
+            }
+        }
+    }
+}
+
+impl<T: DeserializeInner> DeserializeInner for Option<T> {
+    #[inline(always)]
+    fn _deserialize_full_inner(backend: &mut impl ReadWithPos) -> deser::Result<Self> {
+        let tag = u8::_deserialize_full_inner(backend)?;
+        match tag {
+            0 => Ok(None),
+            1 => Ok(Some(in_field(backend, "Some", |backend| {
+                T::_deserialize_full_inner(backend)
+            })?)),
+            _ => Err(deser::Error::InvalidTag(tag as usize)),
+        }
+    }
+    type DeserType<'a> = Option<<T as DeserializeInner>::DeserType<'a>>;
+    #[inline(always)]
+    fn _deserialize_eps_inner<'a>(
+        backend: &mut SliceWithPos<'a>,
+    ) -> deser::Result<Self::DeserType<'a>> {
+        let tag = u8::_deserialize_full_inner(backend)?;
+        match tag {
+            0 => Ok(None),
+            1 => Ok(Some(in_field(backend, "Some", |backend| {
+                T::_deserialize_eps_inner(backend)
+            })?)),
+            _ => Err(deser::Error::InvalidTag(tag as usize)),
+        }
+    }
+}
@@ -0,0 +1,180 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ * SPDX-FileCopyrightText: 2023 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+/*!
+
+Implementations for [`Rc`] and [`Arc`].
+
+Shared pointers are serialized by serializing their pointee, so sharing
+is not preserved: if the same pointee is shared by several pointers, it is
+serialized several times.
+
+The deserialization type of `Arc<T>` (`Rc<T>`) is an `Arc` (`Rc`) of the
+deserialization type of `T`. The deserialization type of `Arc<[T]>`
+(`Rc<[T]>`) is a reference to a slice if `T` is zero-copy, and an `Arc<[_]>`
+(`Rc<[_]>`) of the deserialization type of `T` otherwise.
+
+*/
+
+use crate::deser::helpers::*;
+use crate::prelude::*;
+use core::hash::Hash;
+use deser::*;
+use ser::*;
+
+#[cfg(all(feature = "alloc", not(feature = "std")))]
+use alloc::{rc::Rc, sync::Arc};
+#[cfg(feature = "std")]
+use std::{rc::Rc, sync::Arc};
+
+macro_rules! impl_rc {
+    ($rc:ident) => {
+        impl<T> CopyType for $rc<T> {
+            type Copy = Deep;
+        }
+
+        impl<T: TypeHash> TypeHash for $rc<T> {
+            fn type_hash(hasher: &mut impl core::hash::Hasher) {
+                stringify!($rc).hash(hasher);
+                T::type_hash(hasher);
+            }
+        }
+
+        impl<T: ReprHash> ReprHash for $rc<T> {
+            fn repr_hash(hasher: &mut impl core::hash::Hasher, offset_of: &mut usize) {
+                *offset_of = 0;
+                T::repr_hash(hasher, offset_of);
+            }
+        }
+
+        impl<T: SerializeInner> SerializeInner for $rc<T> {
+            const IS_ZERO_COPY: bool = false;
+            const ZERO_COPY_MISMATCH: bool = false;
+            const MAX_FIELD_ALIGN: usize = T::MAX_FIELD_ALIGN;
+            #[inline(always)]
+            fn _serialize_inner(&self, backend: &mut impl WriteWithNames) -> ser::Result<()> {
+                T::_serialize_inner(self, backend)
+            }
+        }
+
+        impl<T: DeserializeInner> DeserializeInner for $rc<T> {
+            type DeserType<'a> = $rc<<T as DeserializeInner>::DeserType<'a>>;
+            #[inline(always)]
+            fn _deserialize_full_inner(backend: &mut impl ReadWithPos) -> deser::Result<Self> {
+                Ok($rc::new(T::_deserialize_full_inner(backend)?))
+            }
+
+            #[inline(always)]
+            fn _deserialize_eps_inner<'a>(
+                backend: &mut SliceWithPos<'a>,
+            ) -> deser::Result<Self::DeserType<'a>> {
+                Ok($rc::new(T::_deserialize_eps_inner(backend)?))
+            }
+        }
+
+        impl<T> CopyType for $rc<[T]> {
+            type Copy = Deep;
+        }
+
+        impl<T: TypeHash> TypeHash for $rc<[T]> {
+            fn type_hash(hasher: &mut impl core::hash::Hasher) {
+                concat!(stringify!($rc), "<[]>").hash(hasher);
+                T::type_hash(hasher);
+            }
+        }
+
+        impl<T: ReprHash> ReprHash for $rc<[T]> {
+            fn repr_hash(hasher: &mut impl core::hash::Hasher, offset_of: &mut usize) {
+                *offset_of = 0;
+                T::repr_hash(hasher, offset_of);
+            }
+        }
+
+        impl<T: CopyType + SerializeInner + TypeHash> SerializeInner for $rc<[T]>
+        where
+            $rc<[T]>: SerializeHelper<<T as CopyType>::Copy>,
+        {
+            const IS_ZERO_COPY: bool = false;
+            const ZERO_COPY_MISMATCH: bool = false;
+            const MAX_FIELD_ALIGN: usize = T::MAX_FIELD_ALIGN;
+            fn _serialize_inner(&self, backend: &mut impl WriteWithNames) -> ser::Result<()> {
+                SerializeHelper::_serialize_inner(self, backend)
+            }
+        }
+
+        impl<T: ZeroCopy + SerializeInner> SerializeHelper<Zero> for $rc<[T]> {
+            #[inline(always)]
+            fn _serialize_inner(&self, backend: &mut impl WriteWithNames) -> ser::Result<()> {
+                serialize_slice_zero(backend, self)
+            }
+        }
+
+        impl<T: DeepCopy + SerializeInner> SerializeHelper<Deep> for $rc<[T]> {
+            #[inline(always)]
+            fn _serialize_inner(&self, backend: &mut impl WriteWithNames) -> ser::Result<()> {
+                serialize_slice_deep(backend, self)
+            }
+        }
+
+        // This delegates to a private helper trait which we can specialize on in stable rust
+        impl<T: DeserializeInner + CopyType + 'static> DeserializeInner for $rc<[T]>
+        where
+            $rc<[T]>: DeserializeHelper<<T as CopyType>::Copy, FullType = $rc<[T]>>,
+        {
+            type DeserType<'a> =
+                <$rc<[T]> as DeserializeHelper<<T as CopyType>::Copy>>::DeserType<'a>;
+            #[inline(always)]
+            fn _deserialize_full_inner(backend: &mut impl ReadWithPos) -> deser::Result<Self> {
+                <$rc<[T]> as DeserializeHelper<<T as CopyType>::Copy>>::_deserialize_full_inner_impl(
+                    backend,
+                )
+            }
+
+            #[inline(always)]
+            fn _deserialize_eps_inner<'a>(
+                backend: &mut SliceWithPos<'a>,
+            ) -> deser::Result<Self::DeserType<'a>> {
+                <$rc<[T]> as DeserializeHelper<<T as CopyType>::Copy>>::_deserialize_eps_inner_impl(
+                    backend,
+                )
+            }
+        }
+
+        impl<T: ZeroCopy + DeserializeInner + 'static> DeserializeHelper<Zero> for $rc<[T]> {
+            type FullType = Self;
+            type DeserType<'a> = &'a [T];
+            #[inline(always)]
+            fn _deserialize_full_inner_impl(backend: &mut impl ReadWithPos) -> deser::Result<Self> {
+                Ok(deserialize_full_vec_zero::<T>(backend)?.into())
+            }
+            #[inline(always)]
+            fn _deserialize_eps_inner_impl<'a>(
+                backend: &mut SliceWithPos<'a>,
+            ) -> deser::Result<<Self as DeserializeInner>::DeserType<'a>> {
+                deserialize_eps_slice_zero(backend)
+            }
+        }
+
+        impl<T: DeepCopy + DeserializeInner + 'static> DeserializeHelper<Deep> for $rc<[T]> {
+            type FullType = Self;
+            type DeserType<'a> = $rc<[<T as DeserializeInner>::DeserType<'a>]>;
+            #[inline(always)]
+            fn _deserialize_full_inner_impl(backend: &mut impl ReadWithPos) -> deser::Result<Self> {
+                Ok(deserialize_full_vec_deep(backend)?.into())
+            }
+            #[inline(always)]
+            fn _deserialize_eps_inner_impl<'a>(
+                backend: &mut SliceWithPos<'a>,
+            ) -> deser::Result<<Self as DeserializeInner>::DeserType<'a>> {
+                Ok(deserialize_eps_vec_deep::<T>(backend)?.into())
+            }
+        }
+    };
+}
+
+impl_rc!(Rc);
+impl_rc!(Arc);
@@ -0,0 +1,130 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ * SPDX-FileCopyrightText: 2023 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+/*!
+
+Implementations for the SIMD vector types of [`core::arch`] on `x86` and
+`x86_64` (requires the `simd` feature).
+
+The types `__m128`, `__m128d`, `__m128i`, `__m256`, `__m256d`, and `__m256i`
+are [`ZeroCopy`], so vectors and boxed slices of such types are ε-copy
+deserialized to slices that can be used directly in SIMD kernels. The
+type hash and the representation hash include the lane type and the number of
+lanes; the lanes of integer vectors are described as bytes, as their
+interpretation depends on the instructions used.
+
+Note that 256-bit vectors require an alignment of 32 bytes, which is larger
+than the alignment provided by [`Deserialize::load_mem`](crate::deser::Deserialize::load_mem):
+in that case, use [`Deserialize::mmap`](crate::deser::Deserialize::mmap) or
+[`Deserialize::load_mmap`](crate::deser::Deserialize::load_mmap).
+
+Byte swapping is supported only by floating-point vectors.
+
+The portable SIMD types of `core::simd` are not supported, as they are
+available only on nightly Rust.
+
+*/
+
+use crate::prelude::*;
+#[cfg(target_arch = "x86")]
+use core::arch::x86::*;
+#[cfg(target_arch = "x86_64")]
+use core::arch::x86_64::*;
+use core::hash::Hash;
+use core::mem::size_of;
+use deser::*;
+use ser::*;
+
+macro_rules! impl_simd {
+    ($($ty:ident: [$lane:ty; $lanes:literal] $swap:ident),*) => {$(
+        impl CopyType for $ty {
+            type Copy = Zero;
+        }
+
+        impl TypeHash for $ty {
+            fn type_hash(hasher: &mut impl core::hash::Hasher) {
+                stringify!($ty).hash(hasher);
+                stringify!($lane).hash(hasher);
+                hasher.write_usize($lanes);
+            }
+        }
+
+        impl ReprHash for $ty {
+            fn repr_hash(hasher: &mut impl core::hash::Hasher, offset_of: &mut usize) {
+                crate::traits::std_repr_hash::<Self>(hasher, offset_of);
+                hasher.write_usize($lanes);
+            }
+        }
+
+        impl MaxSizeOf for $ty {
+            const WIDTH_DEPENDENT: bool = false;
+
+            fn max_size_of() -> usize {
+                size_of::<$ty>()
+            }
+        }
+
+        impl ByteSwap for $ty {
+            fn byte_swap(bytes: &mut [u8]) -> deser::Result<()> {
+                impl_simd!(@$swap $ty, $lane, bytes)
+            }
+        }
+
+        impl CheckBytes for $ty {
+            fn check_bytes(_bytes: &[u8]) -> deser::Result<()> {
+                // Every bit pattern is valid
+                Ok(())
+            }
+        }
+
+        impl SerializeInner for $ty {
+            const IS_ZERO_COPY: bool = true;
+            const ZERO_COPY_MISMATCH: bool = false;
+            const MAX_FIELD_ALIGN: usize = size_of::<$ty>();
+
+            #[inline(always)]
+            fn _serialize_inner(&self, backend: &mut impl WriteWithNames) -> ser::Result<()> {
+                serialize_zero(backend, self)
+            }
+        }
+
+        impl DeserializeInner for $ty {
+            type DeserType<'a> = &'a $ty;
+
+            #[inline(always)]
+            fn _deserialize_full_inner(backend: &mut impl ReadWithPos) -> deser::Result<Self> {
+                deserialize_full_zero::<$ty>(backend)
+            }
+
+            #[inline(always)]
+            fn _deserialize_eps_inner<'a>(
+                backend: &mut SliceWithPos<'a>,
+            ) -> deser::Result<Self::DeserType<'a>> {
+                deserialize_eps_zero::<$ty>(backend)
+            }
+        }
+    )*};
+    (@swap $ty:ident, $lane:ty, $bytes:ident) => {{
+        $bytes
+            .chunks_exact_mut(size_of::<$lane>())
+            .for_each(|lane| lane.reverse());
+        Ok(())
+    }};
+    (@opaque $ty:ident, $lane:ty, $bytes:ident) => {{
+        let _ = $bytes;
+        Err(deser::Error::ByteSwapUnsupported(stringify!($ty)))
+    }};
+}
+
+impl_simd!(
+    __m128: [f32; 4] swap,
+    __m128d: [f64; 2] swap,
+    __m128i: [u8; 16] opaque,
+    __m256: [f32; 8] swap,
+    __m256d: [f64; 4] swap,
+    __m256i: [u8; 32] opaque
+);
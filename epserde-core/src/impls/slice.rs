@@ -37,8 +37,11 @@ assert_eq!(a, *b);
 */
 
 use crate::prelude::*;
+use core::hash::Hash;
 use ser::*;
-use std::hash::Hash;
+
+#[cfg(all(feature = "alloc", not(feature = "std")))]
+use alloc::vec::Vec;
 
 impl<T: TypeHash> TypeHash for [T] {
     #[inline(always)]
@@ -53,17 +56,38 @@ impl<T> ReprHash for [T] {
     fn repr_hash(_hasher: &mut impl core::hash::Hasher, _offset_of: &mut usize) {}
 }
 
-impl<T: SerializeInner + CopyType + TypeHash + ReprHash> Serialize for [T]
+impl<T: SerializeInner + CopyType + TypeHash + ReprHash + 'static> Serialize for [T]
 where
     Vec<T>: SerializeHelper<<T as CopyType>::Copy>,
 {
     fn serialize_on_field_write(&self, backend: &mut impl WriteWithNames) -> ser::Result<()> {
         write_header::<Vec<T>>(backend)?;
-        // SAFETY: the fake vector we create is never used, and we forget it immediately
-        // after writing it to the backend.
-        let fake = unsafe { Vec::from_raw_parts(self.as_ptr() as *mut T, self.len(), self.len()) };
-        backend.write("ROOT", &fake)?;
-        core::mem::forget(fake);
+        // SAFETY: the fake vector we create is only borrowed, and it is wrapped
+        // in a ManuallyDrop so that it is never dropped, even on errors.
+        let fake = core::mem::ManuallyDrop::new(unsafe {
+            Vec::from_raw_parts(self.as_ptr() as *mut T, self.len(), self.len())
+        });
+        backend.write("ROOT", &*fake)?;
         backend.flush()
     }
+
+    #[cfg(feature = "std")]
+    fn serialize_compressed<C: crate::compress::Codec>(
+        &self,
+        backend: &mut impl WriteNoStd,
+    ) -> ser::Result<usize> {
+        let mut writer_with_pos = WriterWithPos::new(backend);
+        write_header_with_codec::<Vec<T>>(&mut writer_with_pos, Some(C::NAME))?;
+        // SAFETY: the fake vector we create is only borrowed, and it is wrapped
+        // in a ManuallyDrop so that it is never dropped, even on errors.
+        let fake = core::mem::ManuallyDrop::new(unsafe {
+            Vec::from_raw_parts(self.as_ptr() as *mut T, self.len(), self.len())
+        });
+        writer_with_pos.write(
+            "ROOT",
+            &crate::compress::Compressed::<C, Vec<T>>::new(&fake),
+        )?;
+        writer_with_pos.flush()?;
+        Ok(writer_with_pos.pos())
+    }
 }
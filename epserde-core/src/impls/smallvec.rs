@@ -0,0 +1,99 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ * SPDX-FileCopyrightText: 2023 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+/*!
+
+Implementations for [`SmallVec`] (requires the `smallvec` feature).
+
+Small vectors are serialized exactly as vectors, independently of their
+inline capacity, so ε-copy deserialization returns a slice for
+[`ZeroCopy`] elements, and a vector of deserialization types otherwise.
+Full-copy deserialization stores the elements inline whenever they fit.
+
+```rust
+use epserde::prelude::*;
+use smallvec::{smallvec, SmallVec};
+
+let v: SmallVec<[u32; 4]> = smallvec![1, 2, 3];
+let mut cursor = epserde::new_aligned_cursor();
+v.serialize(&mut cursor).unwrap();
+let buf = cursor.into_inner();
+let view: &[u32] = <SmallVec<[u32; 4]>>::deserialize_eps(&buf).unwrap();
+assert_eq!(view, &[1, 2, 3]);
+```
+
+*/
+
+use crate::prelude::*;
+use ::smallvec::{Array, SmallVec};
+use core::hash::Hash;
+use deser::*;
+use ser::*;
+
+#[cfg(all(feature = "alloc", not(feature = "std")))]
+use alloc::vec::Vec;
+
+impl<A: Array> CopyType for SmallVec<A> {
+    type Copy = Deep;
+}
+
+// The inline capacity is not part of the type hash, as it does not
+// change the serialized data
+impl<A: Array> TypeHash for SmallVec<A>
+where
+    A::Item: TypeHash,
+{
+    fn type_hash(hasher: &mut impl core::hash::Hasher) {
+        "SmallVec".hash(hasher);
+        A::Item::type_hash(hasher);
+    }
+}
+
+impl<A: Array> ReprHash for SmallVec<A>
+where
+    A::Item: ReprHash,
+{
+    fn repr_hash(hasher: &mut impl core::hash::Hasher, offset_of: &mut usize) {
+        *offset_of = 0;
+        A::Item::repr_hash(hasher, offset_of);
+    }
+}
+
+impl<A: Array> SerializeInner for SmallVec<A>
+where
+    A::Item: CopyType + SerializeInner + SerializeIterHelper<<A::Item as CopyType>::Copy>,
+{
+    const IS_ZERO_COPY: bool = false;
+    const ZERO_COPY_MISMATCH: bool = false;
+    const MAX_FIELD_ALIGN: usize = A::Item::MAX_FIELD_ALIGN;
+
+    fn _serialize_inner(&self, backend: &mut impl WriteWithNames) -> ser::Result<()> {
+        A::Item::_serialize_iter(backend, self.len(), self.iter())
+    }
+}
+
+impl<A: Array> DeserializeInner for SmallVec<A>
+where
+    Vec<A::Item>: DeserializeInner,
+{
+    type DeserType<'a> = DeserType<'a, Vec<A::Item>>;
+
+    fn _deserialize_full_inner(backend: &mut impl ReadWithPos) -> deser::Result<Self> {
+        let vec = Vec::<A::Item>::_deserialize_full_inner(backend)?;
+        Ok(if vec.len() <= A::size() {
+            vec.into_iter().collect()
+        } else {
+            SmallVec::from_vec(vec)
+        })
+    }
+
+    fn _deserialize_eps_inner<'a>(
+        backend: &mut SliceWithPos<'a>,
+    ) -> deser::Result<Self::DeserType<'a>> {
+        Vec::<A::Item>::_deserialize_eps_inner(backend)
+    }
+}
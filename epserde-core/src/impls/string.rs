@@ -9,6 +9,23 @@
 
 Implementations for strings.
 
+We also provide a serialize-only implementation for string slices that
+deserializes to strings, analogous to the
+[implementation for slices](crate::impls::slice), so that borrowed string
+slices can be serialized without copying them into a `String`.
+```rust
+use epserde::prelude::*;
+let s = "Hello, world!";
+let mut cursor = epserde::new_aligned_cursor();
+s.serialize(&mut cursor).unwrap();
+cursor.set_position(0);
+let t: String = <String>::deserialize_full(&mut cursor).unwrap();
+assert_eq!(s, t);
+let buf = cursor.into_inner();
+let t: &str = <String>::deserialize_eps(&buf).unwrap();
+assert_eq!(s, t);
+```
+
 */
 
 use crate::prelude::*;
@@ -21,7 +38,7 @@ impl CopyType for String {
 }
 
 #[cfg(all(feature = "alloc", not(feature = "std")))]
-use alloc::string::String;
+use alloc::{boxed::Box, string::String};
 
 #[cfg(feature = "alloc")]
 impl TypeHash for String {
@@ -58,6 +75,7 @@ impl SerializeInner for String {
     // Vec<$ty> can, but Vec<Vec<$ty>> cannot!
     const IS_ZERO_COPY: bool = false;
     const ZERO_COPY_MISMATCH: bool = false;
+    const MAX_FIELD_ALIGN: usize = 1;
 
     fn _serialize_inner(&self, backend: &mut impl WriteWithNames) -> ser::Result<()> {
         serialize_slice_zero(backend, self.as_bytes())
@@ -67,18 +85,23 @@ impl SerializeInner for String {
 impl DeserializeInner for String {
     fn _deserialize_full_inner(backend: &mut impl ReadWithPos) -> deser::Result<Self> {
         let slice = deserialize_full_vec_zero(backend)?;
-        Ok(String::from_utf8(slice).unwrap())
+        String::from_utf8(slice).map_err(|_| deser::Error::InvalidUtf8)
     }
     type DeserType<'a> = &'a str;
     #[inline(always)]
     fn _deserialize_eps_inner<'a>(
         backend: &mut SliceWithPos<'a>,
     ) -> deser::Result<Self::DeserType<'a>> {
-        let slice = deserialize_eps_slice_zero(backend)?;
-        Ok(unsafe {
-            #[allow(clippy::transmute_bytes_to_str)]
-            core::mem::transmute::<&'_ [u8], &'_ str>(slice)
-        })
+        deserialize_eps_str(backend)
+    }
+    fn _deserialize_full_into_inner(
+        &mut self,
+        backend: &mut impl ReadWithPos,
+    ) -> deser::Result<()> {
+        let mut bytes = core::mem::take(self).into_bytes();
+        deserialize_full_vec_zero_into(&mut bytes, backend)?;
+        *self = String::from_utf8(bytes).map_err(|_| deser::Error::InvalidUtf8)?;
+        Ok(())
     }
 }
 
@@ -90,6 +113,7 @@ impl SerializeInner for Box<str> {
     // Box<[$ty]> can, but Vec<Box<[$ty]>> cannot!
     const IS_ZERO_COPY: bool = false;
     const ZERO_COPY_MISMATCH: bool = false;
+    const MAX_FIELD_ALIGN: usize = 1;
 
     fn _serialize_inner(&self, backend: &mut impl WriteWithNames) -> ser::Result<()> {
         serialize_slice_zero(backend, self.as_bytes())
@@ -109,3 +133,36 @@ impl DeserializeInner for Box<str> {
         String::_deserialize_eps_inner(backend)
     }
 }
+
+impl Serialize for str {
+    fn serialize_on_field_write(&self, backend: &mut impl WriteWithNames) -> ser::Result<()> {
+        write_header::<String>(backend)?;
+        // SAFETY: the fake string we create is only borrowed, and it is wrapped
+        // in a ManuallyDrop so that it is never dropped, even on errors.
+        let fake = core::mem::ManuallyDrop::new(unsafe {
+            String::from_raw_parts(self.as_ptr() as *mut u8, self.len(), self.len())
+        });
+        backend.write("ROOT", &*fake)?;
+        backend.flush()
+    }
+
+    #[cfg(feature = "std")]
+    fn serialize_compressed<C: crate::compress::Codec>(
+        &self,
+        backend: &mut impl WriteNoStd,
+    ) -> ser::Result<usize> {
+        let mut writer_with_pos = WriterWithPos::new(backend);
+        write_header_with_codec::<String>(&mut writer_with_pos, Some(C::NAME))?;
+        // SAFETY: the fake string we create is only borrowed, and it is wrapped
+        // in a ManuallyDrop so that it is never dropped, even on errors.
+        let fake = core::mem::ManuallyDrop::new(unsafe {
+            String::from_raw_parts(self.as_ptr() as *mut u8, self.len(), self.len())
+        });
+        writer_with_pos.write(
+            "ROOT",
+            &crate::compress::Compressed::<C, String>::new(&fake),
+        )?;
+        writer_with_pos.flush()?;
+        Ok(writer_with_pos.pos())
+    }
+}
@@ -0,0 +1,148 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ * SPDX-FileCopyrightText: 2023 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+/*!
+
+Implementations for [`Duration`] and [`SystemTime`](std::time::SystemTime).
+
+Durations are serialized as a `u64` number of seconds followed by a `u32`
+number of nanoseconds. System times are serialized as the signed distance
+from the [Unix epoch](std::time::UNIX_EPOCH), that is, as an `i64`
+number of seconds (rounded towards negative infinity) followed by a `u32`
+number of nanoseconds, so times before the epoch are supported, too.
+
+Both types are deep-copy, as the layout of [`Duration`] is not specified,
+and they deserialize to themselves.
+
+*/
+
+use crate::prelude::*;
+use core::hash::Hash;
+use core::time::Duration;
+use deser::*;
+use ser::*;
+
+const NANOS_PER_SEC: u32 = 1_000_000_000;
+
+impl CopyType for Duration {
+    type Copy = Deep;
+}
+
+impl TypeHash for Duration {
+    fn type_hash(hasher: &mut impl core::hash::Hasher) {
+        "Duration".hash(hasher);
+    }
+}
+
+impl ReprHash for Duration {
+    fn repr_hash(_hasher: &mut impl core::hash::Hasher, _offset_of: &mut usize) {}
+}
+
+impl SerializeInner for Duration {
+    const IS_ZERO_COPY: bool = false;
+    const ZERO_COPY_MISMATCH: bool = false;
+    const MAX_FIELD_ALIGN: usize =
+        crate::ser::helpers::max_align(&[u64::MAX_FIELD_ALIGN, u32::MAX_FIELD_ALIGN]);
+
+    fn _serialize_inner(&self, backend: &mut impl WriteWithNames) -> ser::Result<()> {
+        backend.write("secs", &self.as_secs())?;
+        backend.write("nanos", &self.subsec_nanos())
+    }
+}
+
+/// Read seconds and nanoseconds, checking that nanoseconds are less
+/// than a second.
+fn read_secs_nanos<S: DeserializeInner>(
+    backend: &mut impl ReadWithPos,
+    type_name: &'static str,
+) -> deser::Result<(S, u32)> {
+    let secs = S::_deserialize_full_inner(backend)?;
+    let nanos = u32::_deserialize_full_inner(backend)?;
+    if nanos >= NANOS_PER_SEC {
+        return Err(deser::Error::InvalidValue(type_name));
+    }
+    Ok((secs, nanos))
+}
+
+impl DeserializeInner for Duration {
+    type DeserType<'a> = Self;
+
+    fn _deserialize_full_inner(backend: &mut impl ReadWithPos) -> deser::Result<Self> {
+        let (secs, nanos) = read_secs_nanos::<u64>(backend, "Duration")?;
+        Ok(Duration::new(secs, nanos))
+    }
+
+    fn _deserialize_eps_inner<'a>(
+        backend: &mut SliceWithPos<'a>,
+    ) -> deser::Result<Self::DeserType<'a>> {
+        Self::_deserialize_full_inner(backend)
+    }
+}
+
+#[cfg(feature = "std")]
+mod system_time {
+    use super::*;
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    impl CopyType for SystemTime {
+        type Copy = Deep;
+    }
+
+    impl TypeHash for SystemTime {
+        fn type_hash(hasher: &mut impl core::hash::Hasher) {
+            "SystemTime".hash(hasher);
+        }
+    }
+
+    impl ReprHash for SystemTime {
+        fn repr_hash(_hasher: &mut impl core::hash::Hasher, _offset_of: &mut usize) {}
+    }
+
+    impl SerializeInner for SystemTime {
+        const IS_ZERO_COPY: bool = false;
+        const ZERO_COPY_MISMATCH: bool = false;
+        const MAX_FIELD_ALIGN: usize =
+            crate::ser::helpers::max_align(&[i64::MAX_FIELD_ALIGN, u32::MAX_FIELD_ALIGN]);
+
+        fn _serialize_inner(&self, backend: &mut impl WriteWithNames) -> ser::Result<()> {
+            let (secs, nanos) = match self.duration_since(UNIX_EPOCH) {
+                Ok(after) => (after.as_secs() as i64, after.subsec_nanos()),
+                Err(err) => {
+                    let before = err.duration();
+                    match before.subsec_nanos() {
+                        0 => (-(before.as_secs() as i64), 0),
+                        nanos => (-(before.as_secs() as i64) - 1, NANOS_PER_SEC - nanos),
+                    }
+                }
+            };
+            backend.write("secs", &secs)?;
+            backend.write("nanos", &nanos)
+        }
+    }
+
+    impl DeserializeInner for SystemTime {
+        type DeserType<'a> = Self;
+
+        fn _deserialize_full_inner(backend: &mut impl ReadWithPos) -> deser::Result<Self> {
+            let (secs, nanos) = read_secs_nanos::<i64>(backend, "SystemTime")?;
+            let time = if secs >= 0 {
+                UNIX_EPOCH.checked_add(Duration::new(secs as u64, nanos))
+            } else {
+                UNIX_EPOCH
+                    .checked_sub(Duration::from_secs(secs.unsigned_abs()))
+                    .and_then(|time| time.checked_add(Duration::from_nanos(nanos as u64)))
+            };
+            time.ok_or(deser::Error::InvalidValue("SystemTime"))
+        }
+
+        fn _deserialize_eps_inner<'a>(
+            backend: &mut SliceWithPos<'a>,
+        ) -> deser::Result<Self::DeserType<'a>> {
+            Self::_deserialize_full_inner(backend)
+        }
+    }
+}
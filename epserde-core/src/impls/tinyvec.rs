@@ -0,0 +1,132 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ * SPDX-FileCopyrightText: 2023 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+/*!
+
+Implementations for [`ArrayVec`] and [`TinyVec`] of the `tinyvec` crate
+(requires the `tinyvec` feature).
+
+Both types are serialized exactly as vectors, independently of their
+capacity, so ε-copy deserialization returns a slice for [`ZeroCopy`]
+elements, and a vector of deserialization types otherwise. Full-copy
+deserialization of an [`ArrayVec`] reports [`Error::InvalidValue`] if the
+elements do not fit the capacity, whereas full-copy deserialization of a
+[`TinyVec`] stores the elements inline whenever they fit.
+
+```rust
+use epserde::prelude::*;
+use tinyvec::{tiny_vec, TinyVec};
+
+let v: TinyVec<[u32; 4]> = tiny_vec![1, 2, 3];
+let mut cursor = epserde::new_aligned_cursor();
+v.serialize(&mut cursor).unwrap();
+let buf = cursor.into_inner();
+let view: &[u32] = <TinyVec<[u32; 4]>>::deserialize_eps(&buf).unwrap();
+assert_eq!(view, &[1, 2, 3]);
+```
+
+*/
+
+use crate::prelude::*;
+use ::tinyvec::{Array, ArrayVec, TinyVec};
+use core::hash::Hash;
+use deser::*;
+use ser::*;
+
+#[cfg(all(feature = "alloc", not(feature = "std")))]
+use alloc::vec::Vec;
+
+macro_rules! impl_tiny {
+    ($name:literal, $ty:ident) => {
+        impl<A: Array> CopyType for $ty<A> {
+            type Copy = Deep;
+        }
+
+        // The capacity is not part of the type hash, as it does not change
+        // the serialized data
+        impl<A: Array> TypeHash for $ty<A>
+        where
+            A::Item: TypeHash,
+        {
+            fn type_hash(hasher: &mut impl core::hash::Hasher) {
+                $name.hash(hasher);
+                A::Item::type_hash(hasher);
+            }
+        }
+
+        impl<A: Array> ReprHash for $ty<A>
+        where
+            A::Item: ReprHash,
+        {
+            fn repr_hash(hasher: &mut impl core::hash::Hasher, offset_of: &mut usize) {
+                *offset_of = 0;
+                A::Item::repr_hash(hasher, offset_of);
+            }
+        }
+
+        impl<A: Array> SerializeInner for $ty<A>
+        where
+            A::Item: CopyType + SerializeInner + SerializeIterHelper<<A::Item as CopyType>::Copy>,
+        {
+            const IS_ZERO_COPY: bool = false;
+            const ZERO_COPY_MISMATCH: bool = false;
+            const MAX_FIELD_ALIGN: usize = A::Item::MAX_FIELD_ALIGN;
+
+            fn _serialize_inner(&self, backend: &mut impl WriteWithNames) -> ser::Result<()> {
+                A::Item::_serialize_iter(backend, self.len(), self.iter())
+            }
+        }
+    };
+}
+
+// Same type hash as the ArrayVec of the arrayvec crate, as the serialized
+// data is the same
+impl_tiny!("ArrayVec", ArrayVec);
+impl_tiny!("TinyVec", TinyVec);
+
+impl<A: Array> DeserializeInner for ArrayVec<A>
+where
+    Vec<A::Item>: DeserializeInner,
+{
+    type DeserType<'a> = DeserType<'a, Vec<A::Item>>;
+
+    fn _deserialize_full_inner(backend: &mut impl ReadWithPos) -> deser::Result<Self> {
+        let vec = Vec::<A::Item>::_deserialize_full_inner(backend)?;
+        if vec.len() > A::CAPACITY {
+            return Err(deser::Error::InvalidValue("ArrayVec"));
+        }
+        Ok(vec.into_iter().collect())
+    }
+
+    fn _deserialize_eps_inner<'a>(
+        backend: &mut SliceWithPos<'a>,
+    ) -> deser::Result<Self::DeserType<'a>> {
+        Vec::<A::Item>::_deserialize_eps_inner(backend)
+    }
+}
+
+impl<A: Array> DeserializeInner for TinyVec<A>
+where
+    Vec<A::Item>: DeserializeInner,
+{
+    type DeserType<'a> = DeserType<'a, Vec<A::Item>>;
+
+    fn _deserialize_full_inner(backend: &mut impl ReadWithPos) -> deser::Result<Self> {
+        let vec = Vec::<A::Item>::_deserialize_full_inner(backend)?;
+        Ok(if vec.len() <= A::CAPACITY {
+            vec.into_iter().collect()
+        } else {
+            TinyVec::Heap(vec)
+        })
+    }
+
+    fn _deserialize_eps_inner<'a>(
+        backend: &mut SliceWithPos<'a>,
+    ) -> deser::Result<Self::DeserType<'a>> {
+        Vec::<A::Item>::_deserialize_eps_inner(backend)
+    }
+}
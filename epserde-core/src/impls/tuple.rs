@@ -0,0 +1,230 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ * SPDX-FileCopyrightText: 2023 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+/*!
+
+Implementations for tuples.
+
+We support tuples of up to 12 elements. A tuple is [`ZeroCopy`] if all its
+elements are [`ZeroCopy`], in which case it is serialized as a block of memory
+and ε-copy deserialized to a reference. Otherwise, the tuple is [`DeepCopy`]:
+the elements are serialized one after the other, and the ε-copy
+deserialization type is the tuple of the ε-copy deserialization types of
+the elements.
+
+```rust
+use epserde::prelude::*;
+
+let tuple = (1_u32, vec![1_u64, 2, 3], "abc".to_string());
+let mut cursor = epserde::new_aligned_cursor();
+tuple.serialize(&mut cursor).unwrap();
+let buf = cursor.into_inner();
+let (a, b, c) = <(u32, Vec<u64>, String)>::deserialize_eps(&buf).unwrap();
+assert_eq!((a, b, c), (1, [1, 2, 3].as_slice(), "abc"));
+```
+
+For tuples of more than 12 elements you must use [`Epserde`](https://docs.rs/epserde-derive/latest/epserde_derive/derive.Epserde.html) on a newtype.
+
+*/
+use crate::deser::DeserializeInner;
+use crate::prelude::*;
+use crate::traits::TypeHash;
+use core::hash::Hash;
+use deser::*;
+use ser::*;
+
+// The copy type of a tuple: Zero if all elements are Zero, Deep otherwise.
+macro_rules! copy_and {
+    ($t:ident) => {
+        <$t as CopyType>::Copy
+    };
+    ($t:ident, $($rest:ident),+) => {
+        <<$t as CopyType>::Copy as CopySelector>::And<copy_and!($($rest),+)>
+    };
+}
+
+macro_rules! impl_tuples {
+    ($($t:ident $i:tt),*) => {
+        impl<$($t: CopyType,)*> CopyType for ($($t,)*)  {
+            type Copy = copy_and!($($t),*);
+        }
+
+        impl<$($t: TypeHash,)*> TypeHash for ($($t,)*)
+        {
+            #[inline(always)]
+            fn type_hash(
+                hasher: &mut impl core::hash::Hasher,
+            ) {
+                "()".hash(hasher);
+                $(
+                    <$t>::type_hash(hasher);
+                )*
+            }
+        }
+
+        impl<$($t: CopyType + ReprHash,)*> ReprHash for ($($t,)*)
+        {
+            #[inline(always)]
+            fn repr_hash(
+                hasher: &mut impl core::hash::Hasher,
+                offset_of: &mut usize,
+            ) {
+                if <Self as CopyType>::Copy::IS_ZERO_COPY {
+                    $(
+                        let curr_offset_of = *offset_of;
+                        <$t>::repr_hash(hasher, offset_of);
+                        *offset_of = curr_offset_of + core::mem::size_of::<$t>();
+                    )*
+                } else {
+                    $(
+                        *offset_of = 0;
+                        <$t>::repr_hash(hasher, offset_of);
+                    )*
+                }
+            }
+        }
+
+        impl<$($t: MaxSizeOf,)*> MaxSizeOf for ($($t,)*)
+        {
+            const WIDTH_DEPENDENT: bool = false $(|| <$t>::WIDTH_DEPENDENT)*;
+
+            #[inline(always)]
+            fn max_size_of() -> usize {
+                let mut max_size_of = 0;
+                $(if max_size_of < core::cmp::max(max_size_of, <$t>::max_size_of()) {
+                    max_size_of = <$t>::max_size_of();
+                })*
+                max_size_of
+            }
+        }
+
+        impl<$($t: ByteSwap,)*> ByteSwap for ($($t,)*) {
+            fn byte_swap(bytes: &mut [u8]) -> deser::Result<()> {
+                $(
+                    let offset = core::mem::offset_of!(Self, $i);
+                    <$t>::byte_swap(
+                        bytes
+                            .get_mut(offset..offset + core::mem::size_of::<$t>())
+                            .ok_or(deser::Error::ReadError)?,
+                    )?;
+                )*
+                Ok(())
+            }
+        }
+
+        impl<$($t: CheckBytes,)*> CheckBytes for ($($t,)*) {
+            fn check_bytes(bytes: &[u8]) -> deser::Result<()> {
+                $(
+                    let offset = core::mem::offset_of!(Self, $i);
+                    <$t>::check_bytes(
+                        bytes
+                            .get(offset..offset + core::mem::size_of::<$t>())
+                            .ok_or(deser::Error::ReadError)?,
+                    )?;
+                )*
+                Ok(())
+            }
+        }
+
+        impl<$($t: CopyType + SerializeInner,)*> SerializeInner for ($($t,)*)
+        where
+            Self: SerializeHelper<<Self as CopyType>::Copy>,
+        {
+            const IS_ZERO_COPY: bool = <Self as CopyType>::Copy::IS_ZERO_COPY $(&& <$t>::IS_ZERO_COPY)*;
+            const ZERO_COPY_MISMATCH: bool = false;
+            const MAX_FIELD_ALIGN: usize = crate::ser::helpers::max_align(&[$(<$t>::MAX_FIELD_ALIGN),*]);
+
+            #[inline(always)]
+            fn _serialize_inner(&self, backend: &mut impl WriteWithNames) -> ser::Result<()> {
+                SerializeHelper::_serialize_inner(self, backend)
+            }
+        }
+
+        impl<$($t: ZeroCopy + SerializeInner,)*> SerializeHelper<Zero> for ($($t,)*) {
+            #[inline(always)]
+            fn _serialize_inner(&self, backend: &mut impl WriteWithNames) -> ser::Result<()> {
+                serialize_zero(backend, self)
+            }
+        }
+
+        impl<$($t: CopyType + SerializeInner,)*> SerializeHelper<Deep> for ($($t,)*) {
+            #[inline(always)]
+            fn _serialize_inner(&self, backend: &mut impl WriteWithNames) -> ser::Result<()> {
+                $(
+                    backend.write(stringify!($i), &self.$i)?;
+                )*
+                Ok(())
+            }
+        }
+
+        impl<$($t: CopyType + DeserializeInner,)*> DeserializeInner for ($($t,)*)
+        where
+            Self: DeserializeHelper<<Self as CopyType>::Copy, FullType = Self>,
+        {
+            type DeserType<'a> = <Self as DeserializeHelper<<Self as CopyType>::Copy>>::DeserType<'a>;
+
+            #[inline(always)]
+            fn _deserialize_full_inner(backend: &mut impl ReadWithPos) -> deser::Result<Self> {
+                <Self as DeserializeHelper<<Self as CopyType>::Copy>>::_deserialize_full_inner_impl(backend)
+            }
+
+            #[inline(always)]
+            fn _deserialize_eps_inner<'a>(
+                backend: &mut SliceWithPos<'a>,
+            ) -> deser::Result<Self::DeserType<'a>> {
+                <Self as DeserializeHelper<<Self as CopyType>::Copy>>::_deserialize_eps_inner_impl(backend)
+            }
+        }
+
+        impl<$($t: ZeroCopy + DeserializeInner + 'static,)*> DeserializeHelper<Zero> for ($($t,)*) {
+            type FullType = Self;
+            type DeserType<'a> = &'a Self;
+
+            #[inline(always)]
+            fn _deserialize_full_inner_impl(backend: &mut impl ReadWithPos) -> deser::Result<Self> {
+                deserialize_full_zero::<Self>(backend)
+            }
+
+            #[inline(always)]
+            fn _deserialize_eps_inner_impl<'a>(
+                backend: &mut SliceWithPos<'a>,
+            ) -> deser::Result<Self::DeserType<'a>> {
+                deserialize_eps_zero::<Self>(backend)
+            }
+        }
+
+        impl<$($t: CopyType + DeserializeInner,)*> DeserializeHelper<Deep> for ($($t,)*) {
+            type FullType = Self;
+            type DeserType<'a> = ($(DeserType<'a, $t>,)*);
+
+            #[inline(always)]
+            fn _deserialize_full_inner_impl(backend: &mut impl ReadWithPos) -> deser::Result<Self> {
+                Ok(($(<$t>::_deserialize_full_inner(backend)?,)*))
+            }
+
+            #[inline(always)]
+            fn _deserialize_eps_inner_impl<'a>(
+                backend: &mut SliceWithPos<'a>,
+            ) -> deser::Result<Self::DeserType<'a>> {
+                Ok(($(<$t>::_deserialize_eps_inner(backend)?,)*))
+            }
+        }
+    };
+}
+
+impl_tuples!(T0 0);
+impl_tuples!(T0 0, T1 1);
+impl_tuples!(T0 0, T1 1, T2 2);
+impl_tuples!(T0 0, T1 1, T2 2, T3 3);
+impl_tuples!(T0 0, T1 1, T2 2, T3 3, T4 4);
+impl_tuples!(T0 0, T1 1, T2 2, T3 3, T4 4, T5 5);
+impl_tuples!(T0 0, T1 1, T2 2, T3 3, T4 4, T5 5, T6 6);
+impl_tuples!(T0 0, T1 1, T2 2, T3 3, T4 4, T5 5, T6 6, T7 7);
+impl_tuples!(T0 0, T1 1, T2 2, T3 3, T4 4, T5 5, T6 6, T7 7, T8 8);
+impl_tuples!(T0 0, T1 1, T2 2, T3 3, T4 4, T5 5, T6 6, T7 7, T8 8, T9 9);
+impl_tuples!(T0 0, T1 1, T2 2, T3 3, T4 4, T5 5, T6 6, T7 7, T8 8, T9 9, T10 10);
+impl_tuples!(T0 0, T1 1, T2 2, T3 3, T4 4, T5 5, T6 6, T7 7, T8 8, T9 9, T10 10, T11 11);
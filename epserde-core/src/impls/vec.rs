@@ -46,6 +46,7 @@ where
 {
     const IS_ZERO_COPY: bool = false;
     const ZERO_COPY_MISMATCH: bool = false;
+    const MAX_FIELD_ALIGN: usize = T::MAX_FIELD_ALIGN;
     fn _serialize_inner(&self, backend: &mut impl WriteWithNames) -> ser::Result<()> {
         SerializeHelper::_serialize_inner(self, backend)
     }
@@ -82,6 +83,16 @@ where
     ) -> deser::Result<<Vec<T> as DeserializeHelper<<T as CopyType>::Copy>>::DeserType<'a>> {
         <Vec<T> as DeserializeHelper<<T as CopyType>::Copy>>::_deserialize_eps_inner_impl(backend)
     }
+
+    #[inline(always)]
+    fn _deserialize_full_into_inner(
+        &mut self,
+        backend: &mut impl ReadWithPos,
+    ) -> deser::Result<()> {
+        <Vec<T> as DeserializeHelper<<T as CopyType>::Copy>>::_deserialize_full_into_inner_impl(
+            self, backend,
+        )
+    }
 }
 
 impl<T: ZeroCopy + DeserializeInner + 'static> DeserializeHelper<Zero> for Vec<T> {
@@ -97,6 +108,13 @@ impl<T: ZeroCopy + DeserializeInner + 'static> DeserializeHelper<Zero> for Vec<T
     ) -> deser::Result<<Self as DeserializeInner>::DeserType<'a>> {
         deserialize_eps_slice_zero(backend)
     }
+    #[inline(always)]
+    fn _deserialize_full_into_inner_impl(
+        value: &mut Self,
+        backend: &mut impl ReadWithPos,
+    ) -> deser::Result<()> {
+        deserialize_full_vec_zero_into(value, backend)
+    }
 }
 
 impl<T: DeepCopy + DeserializeInner + 'static> DeserializeHelper<Deep> for Vec<T> {
@@ -112,4 +130,11 @@ impl<T: DeepCopy + DeserializeInner + 'static> DeserializeHelper<Deep> for Vec<T
     ) -> deser::Result<<Self as DeserializeInner>::DeserType<'a>> {
         deserialize_eps_vec_deep::<T>(backend)
     }
+    #[inline(always)]
+    fn _deserialize_full_into_inner_impl(
+        value: &mut Self,
+        backend: &mut impl ReadWithPos,
+    ) -> deser::Result<()> {
+        deserialize_full_vec_deep_into(value, backend)
+    }
 }
@@ -0,0 +1,333 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ * SPDX-FileCopyrightText: 2023 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+/*!
+
+Sequences of deep-copy values with an offset table, providing constant-time
+access to single items without deserializing the whole sequence.
+
+The ε-copy deserialization of a vector of [`DeepCopy`] values deserializes
+all its items, as their position in the serialized data is known only after
+deserializing the previous ones. If a structure has a large vector of this
+kind of which only a few items are accessed (e.g., the nodes of a tree, or
+the documents of an index), the field can be annotated with
+`#[epserde(indexed)]`: the serializer then writes, before the items, a table
+containing the offset of each item, and the field is ε-copy deserialized as
+an [`IndexedView`], which deserializes an item only when it is accessed.
+
+The table contains one `u64` per item, plus one, so it is selected per field.
+The type of the field must be a type parameter that is instantiated with a
+type implementing [`IndexedSeq`], such as `Vec<T>` or `Box<[T]>`, and whose
+deserialization type is replaced with an [`IndexedView`]; for the same
+reason, the type parameter cannot be used for the type of other fields.
+Full-copy deserialization is not affected.
+
+In the [schema](crate::ser::Schema) of the serialized data, the field is
+followed by the table, named `offsets`, and then by the items, as in the case
+of a standard vector; tools can locate the `i`-th item by adding to the
+position of the first byte after the table the `i`-th offset.
+
+```rust
+use epserde::prelude::*;
+
+#[derive(Epserde, Debug, PartialEq)]
+struct Node {
+    label: String,
+    children: Vec<usize>,
+}
+
+#[derive(Epserde, Debug, PartialEq)]
+struct Tree<N> {
+    #[epserde(indexed)]
+    nodes: N,
+}
+
+let tree = Tree {
+    nodes: (0..100)
+        .map(|i| Node { label: i.to_string(), children: vec![2 * i + 1, 2 * i + 2] })
+        .collect::<Vec<_>>(),
+};
+let mut cursor = epserde::new_aligned_cursor();
+tree.serialize(&mut cursor).unwrap();
+let buf = cursor.into_inner();
+
+let view = <Tree<Vec<Node>>>::deserialize_eps(&buf).unwrap();
+assert_eq!(view.nodes.len(), 100);
+// Only the item 42 is deserialized
+let node = view.nodes.get(42).unwrap().unwrap();
+assert_eq!(node.label, "42");
+assert_eq!(node.children, [85, 86]);
+
+let full = <Tree<Vec<Node>>>::deserialize_full(&mut buf.as_slice()).unwrap();
+assert_eq!(full, tree);
+```
+
+*/
+
+use crate::deser::{self, DeserType, DeserializeInner, ReadWithPos, SliceWithPos};
+use crate::ser::{self, SerializeInner, WriteNoStd, WriteWithNames, WriteWithPos};
+use crate::traits::*;
+use core::hash::{Hash, Hasher};
+use core::marker::PhantomData;
+
+#[cfg(all(feature = "alloc", not(feature = "std")))]
+use alloc::{boxed::Box, vec, vec::Vec};
+
+/// A sequence of [`DeepCopy`] values that can be the type of a field
+/// annotated with `#[epserde(indexed)]`.
+pub trait IndexedSeq: Sized {
+    /// The type of the items.
+    type Item: DeepCopy + SerializeInner + DeserializeInner + 'static;
+
+    /// Return the items as a slice.
+    fn items(&self) -> &[Self::Item];
+
+    /// Build the sequence from a vector of items.
+    fn from_items(items: Vec<Self::Item>) -> Self;
+}
+
+impl<T: DeepCopy + SerializeInner + DeserializeInner + 'static> IndexedSeq for Vec<T> {
+    type Item = T;
+
+    fn items(&self) -> &[T] {
+        self
+    }
+
+    fn from_items(items: Vec<T>) -> Self {
+        items
+    }
+}
+
+impl<T: DeepCopy + SerializeInner + DeserializeInner + 'static> IndexedSeq for Box<[T]> {
+    type Item = T;
+
+    fn items(&self) -> &[T] {
+        self
+    }
+
+    fn from_items(items: Vec<T>) -> Self {
+        items.into_boxed_slice()
+    }
+}
+
+/// A writer that only keeps track of the position, used to compute
+/// the offsets of the items before writing them.
+struct PosCounter {
+    pos: usize,
+    portable: bool,
+    canonical: bool,
+}
+
+impl WriteNoStd for PosCounter {
+    fn write_all(&mut self, buf: &[u8]) -> ser::Result<()> {
+        self.pos += buf.len();
+        Ok(())
+    }
+
+    fn flush(&mut self) -> ser::Result<()> {
+        Ok(())
+    }
+}
+
+impl WriteWithPos for PosCounter {
+    fn pos(&self) -> usize {
+        self.pos
+    }
+
+    fn is_portable(&self) -> bool {
+        self.portable
+    }
+
+    fn is_canonical(&self) -> bool {
+        self.canonical
+    }
+}
+
+impl WriteWithNames for PosCounter {}
+
+/// A sequence of type `V` that will be serialized with an offset table.
+struct Indexed<'a, V>(&'a V);
+
+impl<V: IndexedSeq> SerializeInner for Indexed<'_, V> {
+    const IS_ZERO_COPY: bool = false;
+    const ZERO_COPY_MISMATCH: bool = false;
+    const MAX_FIELD_ALIGN: usize = indexed_max_field_align::<V>();
+
+    fn _serialize_inner(&self, backend: &mut impl WriteWithNames) -> ser::Result<()> {
+        let items = self.0.items();
+        let mut offsets = vec![0_u64; items.len() + 1];
+        // Items are serialized a first time on a counter to compute their
+        // offsets, as padding depends on the position
+        let mut counter = PosCounter {
+            pos: backend.pos(),
+            portable: backend.is_portable(),
+            canonical: backend.is_canonical(),
+        };
+        counter.write("offsets", &offsets)?;
+        let start = counter.pos;
+        counter.write("len", &items.len())?;
+        for (offset, item) in offsets.iter_mut().zip(items) {
+            *offset = (counter.pos - start) as u64;
+            counter.write("item", item)?;
+        }
+        if let Some(end) = offsets.last_mut() {
+            *end = (counter.pos - start) as u64;
+        }
+
+        backend.write("offsets", &offsets)?;
+        crate::ser::helpers::serialize_slice_deep(backend, items)
+    }
+}
+
+/// Serialize with an offset table a field annotated with `#[epserde(indexed)]`.
+///
+/// The items are serialized twice, the first time only to compute their
+/// offsets, so serialization takes about twice the time needed by a
+/// standard vector.
+pub fn serialize_indexed<V: IndexedSeq>(
+    backend: &mut impl WriteWithNames,
+    field_name: &str,
+    value: &V,
+) -> ser::Result<()> {
+    backend.write(field_name, &Indexed(value))
+}
+
+/// Fully deserialize a field annotated with `#[epserde(indexed)]`.
+///
+/// The offset table is skipped.
+pub fn deserialize_full_indexed<V: IndexedSeq>(backend: &mut impl ReadWithPos) -> deser::Result<V> {
+    crate::deser::in_field(backend, "offsets", Vec::<u64>::_deserialize_full_inner)?;
+    Ok(V::from_items(
+        crate::deser::helpers::deserialize_full_vec_deep(backend)?,
+    ))
+}
+
+/// ε-copy deserialize a field annotated with `#[epserde(indexed)]`.
+///
+/// The offset table is checked, but the items are not deserialized. If the
+/// table is not consistent with the data, [`Error::InvalidValue`](deser::Error::InvalidValue)
+/// is returned.
+pub fn deserialize_eps_indexed<'a, V: IndexedSeq>(
+    backend: &mut SliceWithPos<'a>,
+) -> deser::Result<IndexedView<'a, V::Item>> {
+    let offsets = crate::deser::in_field(
+        backend,
+        "offsets",
+        crate::deser::helpers::deserialize_eps_slice_zero::<u64>,
+    )?;
+    let start = backend.pos;
+    let data = backend.data;
+    let len = usize::_deserialize_full_inner(backend)?;
+    let invalid = || deser::Error::InvalidValue("Indexed");
+    let Some((&end, item_offsets)) = offsets.split_last() else {
+        return Err(invalid());
+    };
+    if item_offsets.len() != len
+        || item_offsets
+            .first()
+            .is_some_and(|&first| first != (backend.pos - start) as u64)
+        || !offsets.is_sorted()
+    {
+        return Err(invalid());
+    }
+    let end = usize::try_from(end).map_err(|_| invalid())?;
+    let items = data.get(..end).ok_or_else(invalid)?;
+    // With no items, nothing else checks that the end follows the length
+    backend.skip(end.checked_sub(backend.pos - start).ok_or_else(invalid)?)?;
+    Ok(IndexedView {
+        offsets,
+        items: backend.fork(items, start),
+        _marker: PhantomData,
+    })
+}
+
+/// Return the maximum alignment of the zero-copy data of a field of type
+/// `V` annotated with `#[epserde(indexed)]`.
+pub const fn indexed_max_field_align<V: IndexedSeq>() -> usize {
+    crate::ser::helpers::max_align(&[
+        u64::MAX_FIELD_ALIGN,
+        usize::MAX_FIELD_ALIGN,
+        <V::Item as SerializeInner>::MAX_FIELD_ALIGN,
+    ])
+}
+
+/// Hash the type of a field of type `V` annotated with `#[epserde(indexed)]`.
+pub fn indexed_type_hash<V: TypeHash>(hasher: &mut impl Hasher) {
+    "Indexed".hash(hasher);
+    V::type_hash(hasher);
+}
+
+/// Return the [shape](TypeShape) of the type of a field of type `V`
+/// annotated with `#[epserde(indexed)]`.
+pub fn indexed_type_shape<V: TypeHash>() -> TypeShape {
+    let mut hasher = xxhash_rust::xxh3::Xxh3::new();
+    indexed_type_hash::<V>(&mut hasher);
+    TypeShape::Leaf(hasher.finish())
+}
+
+/// The ε-copy deserialization type of a field annotated with
+/// `#[epserde(indexed)]`: a sequence of items of type `T` that are
+/// ε-copy deserialized when accessed.
+///
+/// Each access deserializes again the item: if an item is accessed
+/// repeatedly, it is better to keep the result.
+#[derive(Debug)]
+pub struct IndexedView<'a, T> {
+    /// The offsets of the items, plus the end of the last item,
+    /// relative to the position of `items`.
+    offsets: &'a [u64],
+    /// The serialized items, preceded by their number.
+    items: SliceWithPos<'a>,
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<T> Clone for IndexedView<'_, T> {
+    fn clone(&self) -> Self {
+        Self {
+            offsets: self.offsets,
+            items: self.items.fork(self.items.data, self.items.pos),
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<'a, T: DeserializeInner> IndexedView<'a, T> {
+    /// Return the number of items.
+    #[inline(always)]
+    pub fn len(&self) -> usize {
+        self.offsets.len() - 1
+    }
+
+    /// Return whether there are no items.
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// ε-copy deserialize the item of given index, if any.
+    ///
+    /// An error is returned if the serialized item is corrupted, or if
+    /// its deserialization does not end exactly at the start of the next item.
+    pub fn get(&self, index: usize) -> Option<deser::Result<DeserType<'a, T>>> {
+        let start = *self.offsets.get(index)? as usize;
+        let end = *self.offsets.get(index + 1)? as usize;
+        let data = self.items.data.get(start..end)?;
+        let mut backend = self.items.fork(data, self.items.pos + start);
+        Some(crate::deser::in_item(&mut backend, index, |backend| {
+            let item = T::_deserialize_eps_inner(backend)?;
+            if !backend.data.is_empty() {
+                return Err(deser::Error::InvalidValue("Indexed"));
+            }
+            Ok(item)
+        }))
+    }
+
+    /// Return an iterator ε-copy deserializing the items.
+    pub fn iter(&self) -> impl Iterator<Item = deser::Result<DeserType<'a, T>>> + '_ {
+        (0..self.len()).filter_map(move |index| self.get(index))
+    }
+}
@@ -0,0 +1,154 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ * SPDX-FileCopyrightText: 2023 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+/*!
+
+Inspection of the header of serialized data.
+
+The functions of this module read the header of serialized data without
+knowing the type that has been serialized, returning a [`Header`]. They
+are mainly useful to diagnose [`WrongTypeHash`](crate::deser::Error::WrongTypeHash)
+errors in production.
+
+Besides the [type hash](crate::type_hash_of), the header contains the name of
+the type as returned by [`core::any::type_name`], which however does not
+describe the structure of the type. Data serialized by
+[`Serialize::serialize_with_signature`](crate::ser::Serialize::serialize_with_signature)
+contains in the header also the signature of the type, that is, the data fed
+to the hasher while computing the type hash (field names, type names, etc.;
+see [`SignatureHasher`](crate::semver::SignatureHasher)), and
+[`Header::signature_diff`] describes how the signature of a type
+differs from the one stored in the header:
+
+```rust
+use epserde::prelude::*;
+
+#[derive(Epserde)]
+struct Person {
+    name: String,
+    age: u8,
+}
+
+#[derive(Epserde)]
+struct PersonV2 {
+    name: String,
+    age: u16,
+}
+
+let mut cursor = epserde::new_aligned_cursor();
+Person { name: "Alice".to_string(), age: 30 }.serialize_with_signature(&mut cursor)?;
+let buf = cursor.into_inner();
+
+let info = epserde::inspect::read_header_from(&mut &buf[..])?;
+println!("{}", info);
+assert!(PersonV2::deserialize_eps(&buf).is_err());
+// Reports that the type of the field age changed from u8 to u16
+let changes = info.signature_diff::<PersonV2>().unwrap();
+assert!(changes.iter().any(|change| change.contains("u16")));
+# Ok::<(), Box<dyn std::error::Error>>(())
+```
+
+*/
+
+use crate::deser::{self, DeserializeInner, Header, ReadNoStd, ReaderWithPos};
+use crate::traits::*;
+use crate::{
+    CHECKSUM_FLAG, CHUNK_CHECKSUMS_FLAG, COMPRESSED_FLAG, MAGIC, MAGIC_REV, PORTABLE_FLAG,
+    SIGNATURE_FLAG,
+};
+use std::fs::File;
+use std::io::BufReader;
+use std::path::{Path, PathBuf};
+
+impl Header {
+    /// Return a line-by-line description of the differences between the
+    /// signature stored in the header and the signature of `T`, or `None` if
+    /// the header does not contain a signature.
+    ///
+    /// The description is empty if the signatures are equal.
+    pub fn signature_diff<T: TypeHash + ?Sized>(&self) -> Option<Vec<String>> {
+        let mut hasher = crate::semver::SignatureHasher::default();
+        T::type_hash(&mut hasher);
+        self.type_signature
+            .as_ref()
+            .map(|expected| crate::semver::diff_signatures(expected, &hasher.into_tokens()))
+    }
+}
+
+/// Read the header of serialized data from the given backend.
+///
+/// See [`deser::read_header`].
+pub fn read_header_from(backend: &mut impl ReadNoStd) -> deser::Result<Header> {
+    deser::read_header(&mut ReaderWithPos::new(backend))
+}
+
+/// Read the header of the serialized data in a file.
+///
+/// See [`read_header_from`].
+pub fn read_header(path: impl AsRef<Path>) -> deser::Result<Header> {
+    let mut reader = BufReader::new(File::open(path).map_err(deser::Error::FileOpenError)?);
+    read_header_from(&mut reader)
+}
+
+/// Return the path of the sidecar file containing the
+/// [JSON representation](crate::ser::Schema::to_json) of the schema of the data in `path`,
+/// as written by [`Serialize::store_with_schema`](crate::ser::Serialize::store_with_schema).
+///
+/// The path is obtained by appending `.schema.json` to `path`.
+pub fn schema_path(path: impl AsRef<Path>) -> PathBuf {
+    let mut schema_path = path.as_ref().as_os_str().to_owned();
+    schema_path.push(".schema.json");
+    schema_path.into()
+}
+
+/// Switch serialized data between the native format of 64-bit
+/// architectures and the [portable](crate::ser::Serialize::serialize_portable)
+/// format, in place.
+///
+/// When `usize` and `isize` values are written using eight bytes, the two
+/// formats differ only for a flag in the header, so the conversion does not
+/// need to know the serialized type. An error is returned if the data has
+/// been serialized on an architecture with a different pointer width.
+///
+/// Note that changing the header invalidates the first chunk of a
+/// [checksum tree](crate::merkle), if present.
+pub fn set_portable(data: &mut [u8], portable: bool) -> deser::Result<()> {
+    let mut header = data.get(..13).ok_or(deser::Error::ReadError)?;
+    let mut backend = ReaderWithPos::new(&mut header);
+    match u64::_deserialize_full_inner(&mut backend)? {
+        MAGIC => {}
+        MAGIC_REV => return Err(deser::Error::EndiannessError),
+        magic => return Err(deser::Error::MagicCookieError(magic)),
+    }
+    let major = u16::_deserialize_full_inner(&mut backend)?;
+    if major != crate::VERSION.0 {
+        return Err(deser::Error::MajorVersionMismatch(major));
+    }
+    let minor = u16::_deserialize_full_inner(&mut backend)?;
+    if minor > crate::VERSION.1 {
+        return Err(deser::Error::MinorVersionMismatch(minor));
+    }
+    let flags = u8::_deserialize_full_inner(&mut backend)?;
+    let usize_size = flags
+        & !(PORTABLE_FLAG
+            | CHECKSUM_FLAG
+            | COMPRESSED_FLAG
+            | SIGNATURE_FLAG
+            | CHUNK_CHECKSUMS_FLAG);
+    if usize_size != 8 {
+        return Err(deser::Error::UsizeSizeMismatch(usize_size as usize));
+    }
+    let flags = if portable {
+        flags | PORTABLE_FLAG
+    } else {
+        flags & !PORTABLE_FLAG
+    };
+    if let Some(byte) = data.get_mut(12) {
+        *byte = flags;
+    }
+    Ok(())
+}
@@ -0,0 +1,184 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ * SPDX-FileCopyrightText: 2023 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+/*!
+
+Core traits and implementations of [ε-serde](https://crates.io/crates/epserde).
+
+This crate contains the serialization and deserialization traits, their
+implementations for primitive and standard types, and the backends. With
+`default-features = false` and the feature `alloc`, it does not depend on
+`std` nor on procedural macros, so projects such as firmware or kernel modules
+can depend on just the core traits and implement their own backends.
+
+Most users should depend on the facade crate
+[`epserde`](https://crates.io/crates/epserde), which re-exports this crate,
+enables `std` and memory mapping by default, and re-exports the
+[derive macro](https://docs.rs/epserde-derive/latest/epserde_derive/derive.Epserde.html).
+The examples in the documentation of this crate use the facade crate.
+
+*/
+#![deny(unconditional_recursion)]
+// Deserialization must not panic on malformed data: with the feature
+// panic-free, Clippy denies panics in the whole crate, and modules that can
+// panic only on misuse of the API opt out explicitly.
+#![cfg_attr(
+    feature = "panic-free",
+    deny(
+        clippy::panic,
+        clippy::unwrap_used,
+        clippy::expect_used,
+        clippy::indexing_slicing
+    )
+)]
+#![cfg_attr(not(feature = "std"), no_std)]
+#[cfg(all(feature = "alloc", not(feature = "std")))]
+extern crate alloc;
+
+#[cfg(not(any(feature = "alloc", feature = "std")))]
+compile_error!("ε-serde requires at least one of the features `alloc` and `std`");
+
+#[cfg(feature = "std")]
+use std::io::Cursor;
+
+pub mod bit_vec;
+pub mod compat;
+#[cfg(feature = "std")]
+pub mod compress;
+pub mod deser;
+pub mod dict;
+pub mod dynamic;
+#[cfg(feature = "std")]
+pub mod embedded;
+pub mod extending;
+#[cfg(feature = "std")]
+pub mod fs;
+pub mod impls;
+pub mod indexed;
+#[cfg(feature = "std")]
+pub mod inspect;
+pub mod merkle;
+#[cfg(feature = "std")]
+pub mod persist;
+#[cfg(feature = "mmap-rs")]
+pub use persist::map;
+#[cfg(feature = "std")]
+pub use persist::{load, save};
+#[cfg(feature = "std")]
+pub mod self_check;
+#[cfg(feature = "std")]
+pub use self_check::self_check;
+#[cfg(feature = "std")]
+pub mod semver;
+pub mod ser;
+#[cfg(feature = "std")]
+pub mod snapshot;
+pub mod sorted_pairs;
+pub mod stable_id;
+#[cfg(feature = "testing")]
+pub mod testing;
+pub mod traits;
+pub use traits::type_hash_of;
+#[cfg(feature = "std")]
+pub mod transform;
+pub mod with;
+
+pub mod prelude {
+    pub use crate::deser;
+    pub use crate::deser::Deserialize;
+    pub use crate::deser::DeserializeEpsMut;
+    pub use crate::deser::DeserializeHelper;
+    pub use crate::deser::DeserializeInner;
+    pub use crate::deser::Flags;
+    pub use crate::deser::MemCase;
+    pub use crate::deser::MmapHints;
+    pub use crate::deser::ReadWithPos;
+    pub use crate::deser::SliceWithPos;
+    pub use crate::deser::StrPolicy;
+    pub use crate::deser::Validation;
+    #[cfg(feature = "std")]
+    pub use crate::load;
+    #[cfg(feature = "mmap-rs")]
+    pub use crate::map;
+    #[cfg(feature = "std")]
+    pub use crate::save;
+    pub use crate::ser;
+    pub use crate::ser::Serialize;
+    pub use crate::ser::SerializeHelper;
+    pub use crate::ser::SerializeInner;
+    pub use crate::traits::*;
+}
+
+/// (Major, Minor) version of the file format, this follows semantic versioning
+pub const VERSION: (u16, u16) = (1, 1);
+
+/// Flag set in the `USIZE_SIZE` field of the header of
+/// [portable](crate::ser::Serialize::serialize_portable) data.
+pub const PORTABLE_FLAG: u8 = 0x80;
+
+/// Flag set in the `USIZE_SIZE` field of the header of data
+/// [with a checksum](crate::ser::Serialize::serialize_with_checksum).
+pub const CHECKSUM_FLAG: u8 = 0x40;
+
+/// Flag set in the `USIZE_SIZE` field of the header of
+/// [compressed](crate::ser::Serialize::serialize_compressed) data.
+pub const COMPRESSED_FLAG: u8 = 0x20;
+
+/// Flag set in the `USIZE_SIZE` field of the header of data
+/// [with the signature of the type](crate::ser::Serialize::serialize_with_signature).
+pub const SIGNATURE_FLAG: u8 = 0x10;
+
+/// Flag set in the `USIZE_SIZE` field of the header of data
+/// [followed by a checksum tree](crate::ser::Serialize::serialize_with_chunk_checksums).
+///
+/// Since the size of `usize` is even, the lowest bit of the field is free.
+pub const CHUNK_CHECKSUMS_FLAG: u8 = 0x01;
+
+/// Magic cookie, also used as endianess marker.
+pub const MAGIC: u64 = u64::from_ne_bytes(*b"epserde ");
+/// What we will read if the endianness is mismatched.
+pub const MAGIC_REV: u64 = u64::from_le_bytes(MAGIC.to_be_bytes());
+
+/// Compute the padding needed for alignment, that is, the smallest
+/// number such that `((value + pad_align_to(value, align_to) & (align_to - 1) == 0`.
+///
+/// An alignment of zero (e.g., the [`max_size_of`](traits::MaxSizeOf::max_size_of)
+/// of zero-sized types) is treated as an alignment of one.
+pub fn pad_align_to(value: usize, align_to: usize) -> usize {
+    value.wrapping_neg() & align_to.saturating_sub(1)
+}
+
+/// Return a new cursor initialized with 1024 bytes of memory aligned to 128 bits.
+///
+/// Note that serialization to a memory-based cursor is mainly
+/// useful for debugging purposes and for examples.
+#[cfg(feature = "std")]
+pub fn new_aligned_cursor() -> Cursor<Vec<u8>> {
+    const INITIAL_SIZE: usize = 1024;
+    Cursor::new(unsafe {
+        Vec::from_raw_parts(
+            // SAFETY: the size is a multiple of the alignment, which is a
+            // power of two.
+            std::alloc::alloc_zeroed(std::alloc::Layout::from_size_align_unchecked(
+                INITIAL_SIZE,
+                128,
+            )),
+            0,
+            INITIAL_SIZE,
+        )
+    })
+}
+
+#[test]
+
+fn test_pad_align_to() {
+    assert_eq!(7 + pad_align_to(7, 8), 8);
+    assert_eq!(8 + pad_align_to(8, 8), 8);
+    assert_eq!(9 + pad_align_to(9, 8), 16);
+    assert_eq!(36 + pad_align_to(36, 16), 48);
+    assert_eq!(pad_align_to(7, 0), 0);
+}
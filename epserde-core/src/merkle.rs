@@ -0,0 +1,440 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ * SPDX-FileCopyrightText: 2023 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+/*!
+
+Checksum trees for the partial verification of large files.
+
+A [checksum in the header](crate::ser::Serialize::serialize_with_checksum)
+covers the whole payload, so it can be verified only by reading all the data,
+even if just a few fields will be accessed. Data serialized
+[with chunk checksums](crate::ser::Serialize::serialize_with_chunk_checksums)
+is instead divided into chunks (by default, of [`DEFAULT_CHUNK_SIZE`] bytes)
+and followed by a footer containing a [Merkle
+tree](https://en.wikipedia.org/wiki/Merkle_tree) of [xxh3](xxhash_rust::xxh3)
+checksums: the leaves are the checksums of the chunks, and each internal node
+is the checksum of its children.
+
+A [`ChunkVerifier`] verifies on demand only the chunks containing a given
+range of bytes, or the given fields of a [schema](crate::ser::Schema),
+checking also the path from each chunk to the root of the tree. In this way,
+the cost of verification is proportional to the amount of data accessed, and
+memory-mapped files remain lazy:
+
+```rust
+use epserde::prelude::*;
+use epserde::merkle::ChunkVerifier;
+
+let data = (vec![0_u64; 10_000], vec![1_u32; 10_000]);
+let mut cursor = epserde::new_aligned_cursor();
+data.serialize_with_chunk_checksums(&mut cursor, 4096)?;
+let buf = cursor.into_inner();
+
+// The schema describes the position of the fields
+let schema = data.serialize_with_schema(&mut std::io::sink())?;
+
+let mut verifier = ChunkVerifier::new(&buf)?.expect("No checksum tree");
+// Verify only the header and the second vector
+verifier.verify_range(0..1)?;
+verifier.verify_fields(&schema, &["ROOT.1"])?;
+assert!(verifier.is_verified(verifier.num_chunks() - 1));
+assert!(!verifier.is_verified(1));
+
+let (_, second) = <(Vec<u64>, Vec<u32>)>::deserialize_eps(&buf)?;
+assert_eq!(second[42], 1);
+# Ok::<(), Box<dyn std::error::Error>>(())
+```
+
+Chunks are contiguous ranges of the data preceding the footer, starting from
+the first byte of the header, and verified chunks are remembered, so each
+chunk is hashed at most once. Since the footer follows the data, the data
+can be deserialized by all the standard methods.
+
+The header records the presence of the footer: ε-copy deserialization methods
+(including [`load_mem`](crate::deser::Deserialize::load_mem) and
+[`mmap`](crate::deser::Deserialize::mmap)) verify the chunks containing the
+header, unless [`Flags::SKIP_CHECKSUM`](crate::deser::Flags::SKIP_CHECKSUM) is
+passed, and return
+[`Error::MissingChunkChecksums`](crate::deser::Error::MissingChunkChecksums)
+if the footer is missing. The chunks of the fields of a loaded or
+memory-mapped structure can then be verified on demand using the verifier
+returned by [`MemCase::chunk_verifier`](crate::deser::MemCase::chunk_verifier),
+and all chunks can be verified at once by
+[`check_data`](crate::deser::check_data).
+
+*/
+
+use crate::bit_vec::BitVec;
+use crate::deser::SliceWithPos;
+use crate::ser::{self, WriteNoStd};
+use crate::{deser, ser::Schema};
+use core::ops::Range;
+use xxhash_rust::xxh3::{xxh3_64, Xxh3};
+
+#[cfg(all(feature = "alloc", not(feature = "std")))]
+use alloc::{vec, vec::Vec};
+
+/// Magic cookie closing data with a checksum tree.
+pub const MERKLE_MAGIC: u64 = u64::from_ne_bytes(*b"epsmerkl");
+
+/// The default size in bytes of the chunks of a checksum tree (4 MiB).
+pub const DEFAULT_CHUNK_SIZE: usize = 4 << 20;
+
+/// The length in bytes of the trailer closing the footer: the chunk size,
+/// the length of the data, and [`MERKLE_MAGIC`].
+const TRAILER_LEN: usize = 24;
+
+/// Return the checksum of an internal node given the checksums of its
+/// children; the last node of a level with an odd number of nodes has no
+/// right child.
+fn parent_hash(left: u64, right: Option<u64>) -> u64 {
+    let mut hasher = Xxh3::new();
+    hasher.update(&left.to_ne_bytes());
+    if let Some(right) = right {
+        hasher.update(&right.to_ne_bytes());
+    }
+    hasher.digest()
+}
+
+/// Return the starting positions of the levels of a tree with the given
+/// number of leaves, from the leaves to the root, followed by the number of
+/// nodes.
+fn level_starts(leaves: usize) -> Vec<usize> {
+    let mut starts = vec![0];
+    let (mut end, mut len) = (0, leaves);
+    loop {
+        end += len;
+        starts.push(end);
+        if len <= 1 {
+            return starts;
+        }
+        len = len.div_ceil(2);
+    }
+}
+
+/// A [`WriteNoStd`] computing the checksums of the chunks of the data written
+/// to the underlying backend, and appending the checksum tree when
+/// [finished](ChunkHashWriter::finish).
+///
+/// Usually, this writer is used through
+/// [`Serialize::serialize_with_chunk_checksums`](crate::ser::Serialize::serialize_with_chunk_checksums),
+/// but it can wrap any serialization method (e.g.,
+/// [`serialize_with_embedded_schema`](crate::ser::Serialize::serialize_with_embedded_schema)).
+/// Data is written to the backend immediately, so no memory proportional to
+/// the size of the data is needed besides the checksums.
+pub struct ChunkHashWriter<'a, W: WriteNoStd> {
+    backend: &'a mut W,
+    chunk_size: usize,
+    /// The checksums of the chunks completed so far.
+    leaves: Vec<u64>,
+    /// The hasher of the current chunk.
+    hasher: Xxh3,
+    /// The number of bytes of the current chunk written so far.
+    in_chunk: usize,
+    /// The number of bytes written so far.
+    len: usize,
+}
+
+impl<'a, W: WriteNoStd> ChunkHashWriter<'a, W> {
+    /// Create a new writer on the given backend using chunks of the given
+    /// size.
+    ///
+    /// If `chunk_size` is zero, this method returns
+    /// [`Error::ZeroChunkSize`](ser::Error::ZeroChunkSize).
+    pub fn new(backend: &'a mut W, chunk_size: usize) -> ser::Result<Self> {
+        if chunk_size == 0 {
+            return Err(ser::Error::ZeroChunkSize);
+        }
+        Ok(Self {
+            backend,
+            chunk_size,
+            leaves: Vec::new(),
+            hasher: Xxh3::new(),
+            in_chunk: 0,
+            len: 0,
+        })
+    }
+
+    /// Write the checksum tree of the data written so far, returning the
+    /// total number of bytes written, including the tree.
+    pub fn finish(mut self) -> ser::Result<usize> {
+        // Empty data is a single empty chunk
+        if self.in_chunk > 0 || self.leaves.is_empty() {
+            self.leaves.push(self.hasher.digest());
+        }
+        let mut nodes = self.leaves;
+        let mut level = 0..nodes.len();
+        while level.len() > 1 {
+            let start = nodes.len();
+            let parents = nodes
+                .get(level)
+                .unwrap_or_default()
+                .chunks(2)
+                .map(|pair| match *pair {
+                    [left, right] => parent_hash(left, Some(right)),
+                    [left] => parent_hash(left, None),
+                    _ => 0,
+                })
+                .collect::<Vec<_>>();
+            nodes.extend(parents);
+            level = start..nodes.len();
+        }
+        for node in &nodes {
+            self.backend.write_all(&node.to_ne_bytes())?;
+        }
+        self.backend
+            .write_all(&(self.chunk_size as u64).to_ne_bytes())?;
+        self.backend.write_all(&(self.len as u64).to_ne_bytes())?;
+        self.backend.write_all(&MERKLE_MAGIC.to_ne_bytes())?;
+        self.backend.flush()?;
+        Ok(self.len + nodes.len() * 8 + TRAILER_LEN)
+    }
+}
+
+impl<W: WriteNoStd> WriteNoStd for ChunkHashWriter<'_, W> {
+    fn write_all(&mut self, mut buf: &[u8]) -> ser::Result<()> {
+        self.backend.write_all(buf)?;
+        self.len += buf.len();
+        while !buf.is_empty() {
+            let (head, tail) = buf.split_at((self.chunk_size - self.in_chunk).min(buf.len()));
+            self.hasher.update(head);
+            self.in_chunk += head.len();
+            buf = tail;
+            if self.in_chunk == self.chunk_size {
+                self.leaves.push(self.hasher.digest());
+                self.hasher.reset();
+                self.in_chunk = 0;
+            }
+        }
+        Ok(())
+    }
+
+    fn flush(&mut self) -> ser::Result<()> {
+        self.backend.flush()
+    }
+}
+
+/// Verifies on demand the chunks of data followed by a checksum tree.
+///
+/// See the [module documentation](self) for more information.
+#[derive(Debug, Clone)]
+pub struct ChunkVerifier<'a> {
+    /// The data covered by the checksum tree.
+    data: &'a [u8],
+    /// The nodes of the tree, level by level, from the leaves to the root.
+    nodes: &'a [u8],
+    chunk_size: usize,
+    /// The output of [`level_starts`].
+    levels: Vec<usize>,
+    /// The chunks verified so far.
+    verified: BitVec,
+}
+
+impl<'a> ChunkVerifier<'a> {
+    /// Create a verifier for the given bytes, which must contain exactly the
+    /// data written by
+    /// [`Serialize::serialize_with_chunk_checksums`](crate::ser::Serialize::serialize_with_chunk_checksums)
+    /// (e.g., a memory-mapped file).
+    ///
+    /// This method returns `None` if the bytes do not end with
+    /// [`MERKLE_MAGIC`], unless their header states that they are followed
+    /// by a checksum tree, in which case it returns
+    /// [`Error::MissingChunkChecksums`](deser::Error::MissingChunkChecksums).
+    /// If the footer is malformed, this method returns
+    /// [`Error::ReadError`](deser::Error::ReadError). No chunk is verified.
+    pub fn new(bytes: &'a [u8]) -> deser::Result<Option<Self>> {
+        Self::parse(bytes, false)
+    }
+
+    /// Create a verifier for the data contained in the given memory region,
+    /// which might be followed by zero padding (e.g., the memory backing a
+    /// [`MemCase`](crate::deser::MemCase)), and, if `prefix` is true,
+    /// preceded by other bytes.
+    pub(crate) fn in_region(bytes: &'a [u8], prefix: bool) -> deser::Result<Option<Self>> {
+        // The trailer ends with the magic cookie, whose last byte is not
+        // zero, so it cannot be confused with the padding
+        let len = bytes
+            .iter()
+            .rposition(|&byte| byte != 0)
+            .map_or(0, |pos| pos + 1);
+        Self::parse(bytes.get(..len).unwrap_or(bytes), prefix)
+    }
+
+    /// Implementation of [`ChunkVerifier::new`] and
+    /// [`ChunkVerifier::in_region`]; if `prefix` is true, the data might be
+    /// preceded by other bytes.
+    fn parse(bytes: &'a [u8], prefix: bool) -> deser::Result<Option<Self>> {
+        let read = |pos: usize| {
+            bytes
+                .get(pos..)
+                .and_then(<[u8]>::first_chunk::<8>)
+                .map(|word| u64::from_ne_bytes(*word))
+        };
+        let Some(trailer_start) = bytes
+            .len()
+            .checked_sub(TRAILER_LEN)
+            .filter(|&trailer_start| read(trailer_start + 16) == Some(MERKLE_MAGIC))
+        else {
+            return match deser::read_header(&mut SliceWithPos::new(bytes)) {
+                Ok(header) if header.chunk_checksums => Err(deser::Error::MissingChunkChecksums),
+                _ => Ok(None),
+            };
+        };
+        let to_usize = |value: Option<u64>| {
+            value
+                .and_then(|value| usize::try_from(value).ok())
+                .ok_or(deser::Error::ReadError)
+        };
+        let chunk_size = to_usize(read(trailer_start))?;
+        let data_len = to_usize(read(trailer_start + 8))?;
+        if chunk_size == 0 || data_len > trailer_start {
+            return Err(deser::Error::ReadError);
+        }
+        let num_chunks = data_len.div_ceil(chunk_size).max(1);
+        let levels = level_starts(num_chunks);
+        let data_start = levels
+            .last()
+            .and_then(|&nodes| trailer_start.checked_sub(data_len + nodes * 8))
+            .filter(|&data_start| prefix || data_start == 0)
+            .ok_or(deser::Error::ReadError)?;
+        let (data, nodes) = bytes
+            .get(data_start..trailer_start)
+            .and_then(|bytes| bytes.split_at_checked(data_len))
+            .ok_or(deser::Error::ReadError)?;
+        Ok(Some(Self {
+            data,
+            nodes,
+            chunk_size,
+            levels,
+            verified: BitVec::from_value(false, num_chunks),
+        }))
+    }
+
+    /// Return the size in bytes of the chunks.
+    pub fn chunk_size(&self) -> usize {
+        self.chunk_size
+    }
+
+    /// Return the number of chunks.
+    pub fn num_chunks(&self) -> usize {
+        self.verified.len()
+    }
+
+    /// Return the data covered by the checksum tree, that is, the bytes
+    /// preceding the footer.
+    pub fn data(&self) -> &'a [u8] {
+        self.data
+    }
+
+    /// Return the checksum at the root of the tree.
+    ///
+    /// The root checksum identifies the whole data, so it can be stored
+    /// elsewhere to check later that the tree has not been replaced.
+    pub fn root(&self) -> u64 {
+        self.node(self.nodes.len() / 8 - 1)
+    }
+
+    /// Return whether the chunk with the given index has been verified.
+    pub fn is_verified(&self, chunk: usize) -> bool {
+        self.verified.get(chunk).unwrap_or(false)
+    }
+
+    /// Return the node with the given index.
+    fn node(&self, index: usize) -> u64 {
+        // The length of the nodes has been checked by the constructor
+        self.nodes
+            .get(index * 8..)
+            .and_then(<[u8]>::first_chunk::<8>)
+            .map_or(0, |word| u64::from_ne_bytes(*word))
+    }
+
+    /// Verify the chunk with the given index, and the path from the chunk
+    /// to the root of the tree.
+    fn verify_chunk(&mut self, chunk: usize) -> deser::Result<()> {
+        if self.is_verified(chunk) {
+            return Ok(());
+        }
+        let start = chunk * self.chunk_size;
+        let end = (start + self.chunk_size).min(self.data.len());
+        let mut got = xxh3_64(self.data.get(start..end).ok_or(deser::Error::ReadError)?);
+        let mut index = chunk;
+        let mut expected = self.node(index);
+        let mismatch = |expected, got| {
+            Err(deser::Error::ChunkChecksumMismatch {
+                chunk,
+                expected,
+                got,
+            })
+        };
+        // Each window contains the start of a level, of its parent level,
+        // and of the following one; the root is checked after the loop
+        for starts in self.levels.windows(3) {
+            if got != expected {
+                return mismatch(expected, got);
+            }
+            let [level_start, parent_start, _] = *starts else {
+                break;
+            };
+            let sibling = index ^ 1;
+            got = if index.is_multiple_of(2) {
+                let right = (level_start + sibling < parent_start)
+                    .then(|| self.node(level_start + sibling));
+                parent_hash(got, right)
+            } else {
+                parent_hash(self.node(level_start + sibling), Some(got))
+            };
+            index /= 2;
+            expected = self.node(parent_start + index);
+        }
+        if got != expected {
+            return mismatch(expected, got);
+        }
+        self.verified.set(chunk, true);
+        Ok(())
+    }
+
+    /// Verify the chunks containing the given range of bytes of the data.
+    ///
+    /// If a chunk, or a node of the tree on the path from a chunk to the
+    /// root, has been corrupted, this method returns
+    /// [`Error::ChunkChecksumMismatch`](deser::Error::ChunkChecksumMismatch);
+    /// if the range exceeds the data, this method returns
+    /// [`Error::ReadError`](deser::Error::ReadError).
+    pub fn verify_range(&mut self, range: Range<usize>) -> deser::Result<()> {
+        if range.end > self.data.len() {
+            return Err(deser::Error::ReadError);
+        }
+        for chunk in range.start / self.chunk_size..range.end.div_ceil(self.chunk_size) {
+            self.verify_chunk(chunk)?;
+        }
+        Ok(())
+    }
+
+    /// Verify the chunks containing the given fields (e.g., `ROOT.labels`),
+    /// as described by the
+    /// [schema](crate::ser::Serialize::serialize_with_schema) of the data.
+    ///
+    /// Note that deserialization reads also the header, and the lengths of
+    /// vectors and strings containing the fields: they can be verified by
+    /// verifying the range `0..1`, and the corresponding fields of the schema.
+    pub fn verify_fields(&mut self, schema: &Schema, fields: &[&str]) -> deser::Result<()> {
+        for field in fields {
+            for range in schema.ranges(field) {
+                self.verify_range(range)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Verify all chunks.
+    pub fn verify_all(&mut self) -> deser::Result<()> {
+        for chunk in 0..self.num_chunks() {
+            self.verify_chunk(chunk)?;
+        }
+        Ok(())
+    }
+}
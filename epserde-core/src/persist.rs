@@ -0,0 +1,112 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ * SPDX-FileCopyrightText: 2023 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+/*!
+
+Persistence of values in two lines.
+
+The free functions [`save`], [`load`], and [`map`], which are re-exported at
+the crate root and in the prelude, cover the common case of storing a value
+in a file and reading it back, choosing safe defaults: files are written
+atomically with a [checksum](crate::ser::Serialize::serialize_with_checksum),
+and the header and the checksum are always verified when reading. The type
+to read is usually inferred:
+
+```rust
+use epserde::prelude::*;
+
+let path = std::env::temp_dir().join("persist_doc.eps");
+epserde::save(&vec![1_u64, 2, 3], &path)?;
+
+let values: Vec<u64> = epserde::load(&path)?;
+assert_eq!(values, [1, 2, 3]);
+
+let mapped = epserde::map::<Vec<u64>>(&path, Flags::empty())?;
+assert_eq!(*mapped, [1, 2, 3]);
+# std::fs::remove_file(&path)?;
+# Ok::<(), Box<dyn std::error::Error>>(())
+```
+
+For other trade-offs (e.g., lazy memory mapping of huge files, for which
+verifying the checksum upfront is too expensive), use the methods of
+[`Serialize`] and [`Deserialize`].
+
+*/
+
+use crate::deser::{self, Deserialize};
+#[cfg(feature = "mmap-rs")]
+use crate::deser::{DeserType, Flags, MemCase};
+use crate::ser::{self, Serialize};
+use std::io::Write;
+use std::path::Path;
+
+/// Save a value to a file atomically, with a
+/// [checksum](crate::ser::Serialize::serialize_with_checksum).
+///
+/// The value is written to a temporary file in the same directory, which is
+/// synced to disk and then renamed to `path`: a crash leaves either the old
+/// content of `path` or the new one. Since the checksum precedes the data,
+/// the value is serialized in memory before being written.
+///
+/// See the [module documentation](self) for an example.
+pub fn save<T: Serialize + ?Sized>(value: &T, path: impl AsRef<Path>) -> ser::Result<()> {
+    let path = path.as_ref();
+    let file_name = path.file_name().ok_or_else(|| {
+        ser::Error::FileOpenError(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            format!("{} is not a file path", path.display()),
+        ))
+    })?;
+    // Concurrent saves to the same path must not share the temporary file
+    let tmp_path = path.with_file_name(format!(
+        ".{}.{}.tmp",
+        file_name.to_string_lossy(),
+        std::process::id()
+    ));
+    let result = (|| {
+        let mut file = std::fs::File::create(&tmp_path).map_err(ser::Error::FileOpenError)?;
+        value.serialize_with_checksum(&mut file)?;
+        file.flush().map_err(ser::Error::IoError)?;
+        file.sync_all().map_err(ser::Error::IoError)?;
+        std::fs::rename(&tmp_path, path).map_err(ser::Error::IoError)
+    })();
+    if result.is_err() {
+        let _ = std::fs::remove_file(&tmp_path);
+    }
+    result?;
+    let dir = path.parent().filter(|dir| !dir.as_os_str().is_empty());
+    crate::snapshot::sync_dir(dir.unwrap_or(Path::new("."))).map_err(ser::Error::IoError)
+}
+
+/// Load a value from a file, fully deserializing it.
+///
+/// The header is checked, and the
+/// [checksum](crate::ser::Serialize::serialize_with_checksum), if present,
+/// is verified. This function is equivalent to [`Deserialize::load_full`].
+///
+/// See the [module documentation](self) for an example.
+pub fn load<T: Deserialize>(path: impl AsRef<Path>) -> deser::Result<T> {
+    T::load_full(path)
+}
+
+/// Memory map a file and ε-copy deserialize a value from it, returning a
+/// [`MemCase`] containing the value and the memory mapping.
+///
+/// The header is checked, and the
+/// [checksum](crate::ser::Serialize::serialize_with_checksum), if present,
+/// is verified unless `flags` contains
+/// [`Flags::SKIP_CHECKSUM`]. This function is equivalent to
+/// [`Deserialize::mmap`].
+///
+/// See the [module documentation](self) for an example.
+#[cfg(feature = "mmap-rs")]
+pub fn map<T: Deserialize>(
+    path: impl AsRef<Path>,
+    flags: Flags,
+) -> deser::Result<MemCase<DeserType<'static, T>>> {
+    T::mmap(path, flags)
+}
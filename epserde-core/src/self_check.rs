@@ -0,0 +1,224 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ * SPDX-FileCopyrightText: 2023 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+/*!
+
+Runtime self-test of serialization and deserialization.
+
+[`self_check`] serializes a sample value of a type in memory, deserializes
+it in all modes, and returns a [`SelfCheckReport`] describing the outcome.
+It is meant to be called at startup (e.g., of a service) or in tests to
+catch problems such as layout changes due to a compiler upgrade, or buggy
+manual implementations of the ε-serde traits, before reading production
+files. The [type hash and representation hash](crate::traits::type_hashes)
+in the report can be logged and compared with those of previous builds.
+
+```rust
+use epserde::prelude::*;
+
+#[derive(Epserde, Default, PartialEq)]
+struct Data {
+    values: Vec<u64>,
+    name: String,
+}
+
+let report = epserde::self_check::<Data>();
+assert!(report.is_ok(), "{}", report);
+```
+
+*/
+
+use crate::deser::Deserialize;
+use crate::ser::Serialize;
+use crate::traits::type_hashes;
+use crate::{deser, ser};
+use core::fmt::{Display, Formatter};
+
+/// A failure detected by [`self_check`].
+#[derive(Debug)]
+pub enum SelfCheckFailure {
+    /// Serialization failed.
+    Serialize(ser::Error),
+    /// Full-copy deserialization failed.
+    DeserializeFull(deser::Error),
+    /// The fully deserialized value is different from the original one.
+    FullMismatch,
+    /// ε-copy deserialization failed.
+    DeserializeEps(deser::Error),
+    /// Serialization in [portable](Serialize::serialize_portable) format failed.
+    SerializePortable(ser::Error),
+    /// Full-copy deserialization of portable data failed.
+    DeserializePortable(deser::Error),
+    /// The value fully deserialized from portable data is different from
+    /// the original one.
+    PortableMismatch,
+    /// Serializing again the fully deserialized value yielded a different
+    /// number of bytes.
+    LengthMismatch {
+        /// The length of the serialization of the original value.
+        expected: usize,
+        /// The length of the serialization of the deserialized value.
+        got: usize,
+    },
+}
+
+impl Display for SelfCheckFailure {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Serialize(err) => write!(f, "Serialization failed: {}", err),
+            Self::DeserializeFull(err) => write!(f, "Full-copy deserialization failed: {}", err),
+            Self::FullMismatch => write!(
+                f,
+                "The fully deserialized value is different from the original one"
+            ),
+            Self::DeserializeEps(err) => write!(f, "ε-copy deserialization failed: {}", err),
+            Self::SerializePortable(err) => write!(f, "Portable serialization failed: {}", err),
+            Self::DeserializePortable(err) => write!(
+                f,
+                "Full-copy deserialization of portable data failed: {}",
+                err
+            ),
+            Self::PortableMismatch => write!(
+                f,
+                "The value deserialized from portable data is different from the original one"
+            ),
+            Self::LengthMismatch { expected, got } => write!(
+                f,
+                "The deserialized value is serialized in {} bytes instead of {}",
+                got, expected
+            ),
+        }
+    }
+}
+
+/// The outcome of a [`self_check`].
+///
+/// The [`Display`] implementation prints a human-readable report.
+#[derive(Debug)]
+pub struct SelfCheckReport {
+    /// The name of the checked type.
+    pub type_name: &'static str,
+    /// The type hash of the checked type.
+    pub type_hash: u64,
+    /// The representation hash of the checked type.
+    pub repr_hash: u64,
+    /// The length in bytes of the serialized sample value, or zero
+    /// if serialization failed.
+    pub len: usize,
+    /// The failures detected, in the order in which checks were performed.
+    pub failures: Vec<SelfCheckFailure>,
+}
+
+impl SelfCheckReport {
+    /// Return whether all checks passed.
+    pub fn is_ok(&self) -> bool {
+        self.failures.is_empty()
+    }
+}
+
+impl Display for SelfCheckReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        writeln!(f, "Type:      {}", self.type_name)?;
+        writeln!(f, "Type hash: {:016x}", self.type_hash)?;
+        writeln!(f, "Repr hash: {:016x}", self.repr_hash)?;
+        writeln!(f, "Length:    {}", self.len)?;
+        if self.failures.is_empty() {
+            writeln!(f, "All checks passed")
+        } else {
+            for failure in &self.failures {
+                writeln!(f, "FAILED:    {}", failure)?;
+            }
+            Ok(())
+        }
+    }
+}
+
+/// Check the serialization and deserialization of the default value of `T`.
+///
+/// See [`self_check_value`].
+pub fn self_check<T: Serialize + Deserialize + Default + PartialEq>() -> SelfCheckReport {
+    self_check_value(&T::default())
+}
+
+/// Check the serialization and deserialization of a sample value.
+///
+/// The value is serialized in memory, and then:
+/// - it is fully deserialized and compared with the original value;
+/// - it is ε-copy deserialized;
+/// - the fully deserialized value is serialized again, checking that the
+///   length is the same;
+/// - it is serialized in [portable](Serialize::serialize_portable) format,
+///   fully deserialized, and compared with the original value.
+///
+/// All failures are recorded in the returned report. Samples
+/// containing nonempty vectors and strings check the code more
+/// thoroughly than default values.
+pub fn self_check_value<T: Serialize + Deserialize + PartialEq>(value: &T) -> SelfCheckReport {
+    let (type_hash, repr_hash) = type_hashes::<T>();
+    let mut report = SelfCheckReport {
+        type_name: core::any::type_name::<T>(),
+        type_hash,
+        repr_hash,
+        len: 0,
+        failures: vec![],
+    };
+
+    let mut cursor = crate::new_aligned_cursor();
+    match value.serialize(&mut cursor) {
+        Ok(len) => report.len = len,
+        Err(err) => {
+            report.failures.push(SelfCheckFailure::Serialize(err));
+            return report;
+        }
+    }
+    let bytes = cursor.into_inner();
+
+    match T::deserialize_full(&mut std::io::Cursor::new(&bytes)) {
+        Ok(full) => {
+            if full != *value {
+                report.failures.push(SelfCheckFailure::FullMismatch);
+            }
+            let mut cursor = crate::new_aligned_cursor();
+            match full.serialize(&mut cursor) {
+                Ok(len) if len != report.len => {
+                    report.failures.push(SelfCheckFailure::LengthMismatch {
+                        expected: report.len,
+                        got: len,
+                    })
+                }
+                Ok(_) => {}
+                Err(err) => report.failures.push(SelfCheckFailure::Serialize(err)),
+            }
+        }
+        Err(err) => report.failures.push(SelfCheckFailure::DeserializeFull(err)),
+    }
+
+    if let Err(err) = T::deserialize_eps(&bytes) {
+        report.failures.push(SelfCheckFailure::DeserializeEps(err));
+    }
+
+    let mut cursor = crate::new_aligned_cursor();
+    match value.serialize_portable(&mut cursor) {
+        Ok(_) => {
+            let bytes = cursor.into_inner();
+            match T::deserialize_full(&mut std::io::Cursor::new(&bytes)) {
+                Ok(full) if full != *value => {
+                    report.failures.push(SelfCheckFailure::PortableMismatch)
+                }
+                Ok(_) => {}
+                Err(err) => report
+                    .failures
+                    .push(SelfCheckFailure::DeserializePortable(err)),
+            }
+        }
+        Err(err) => report
+            .failures
+            .push(SelfCheckFailure::SerializePortable(err)),
+    }
+
+    report
+}
@@ -0,0 +1,500 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ * SPDX-FileCopyrightText: 2023 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+/*!
+
+Tools to check that the on-disk format of persisted types does not change
+unexpectedly.
+
+A [`TypeHashLock`] records, for a set of types, the type hash and the
+representation hash that ε-serde writes in the header of serialized data,
+together with a *signature* of the type, that is, the sequence of names and
+values that were fed to the hasher while computing the hashes. The signature
+makes it possible to report which part of the type changed (e.g., which field
+has been renamed or has changed type) rather than just a hash mismatch.
+
+The lock can be stored in a file (similarly to a `Cargo.lock`) and checked
+in a test using the [`check_type_hash_lock`](crate::check_type_hash_lock) macro:
+```rust,no_run
+use epserde::prelude::*;
+
+#[derive(Epserde)]
+struct Person {
+    name: String,
+    age: u8,
+}
+
+// In a test
+epserde::check_type_hash_lock!("tests/types.lock", Person, Vec<Person>);
+```
+If the lock file does not exist, or if the environment variable
+`EPSERDE_UPDATE_LOCK` is set, the lock file is (re)written; otherwise,
+the test fails with a description of the differences. In this way, every change
+to the on-disk format shows up as a change to the lock file in code review.
+
+For single types, the [`assert_type_hash_stable`](crate::assert_type_hash_stable)
+macro compares the [type hash](crate::type_hash_of) with a golden value
+written directly in the test:
+```rust
+epserde::assert_type_hash_stable!(Vec<u64>, 0xfe37109733990d08);
+```
+Type hashes do not depend on the architecture, so golden values can be
+checked on any platform.
+
+*/
+
+use crate::traits::*;
+use core::hash::Hasher;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// The name of the environment variable that, if set, makes
+/// [`TypeHashLock::check`] update the lock file instead of comparing it.
+pub const UPDATE_LOCK_ENV: &str = "EPSERDE_UPDATE_LOCK";
+
+/// A [`Hasher`] recording the data that it is fed as a sequence of tokens.
+///
+/// Strings become tokens (the [`Hash`](core::hash::Hash) implementation of [`str`] writes
+/// the bytes of the string followed by `0xff`), and so do integers,
+/// which are recorded in decimal notation prefixed by `#`.
+#[derive(Debug, Default, Clone)]
+pub struct SignatureHasher {
+    /// The tokens recorded so far.
+    pub tokens: Vec<String>,
+    /// Bytes written but not yet terminated.
+    pending: Vec<u8>,
+}
+
+impl SignatureHasher {
+    fn flush_pending(&mut self) {
+        if !self.pending.is_empty() {
+            let token = match core::str::from_utf8(&self.pending) {
+                Ok(s) => s.to_string(),
+                Err(_) => format!("{:02x?}", self.pending),
+            };
+            self.tokens.push(token);
+            self.pending.clear();
+        }
+    }
+
+    fn push_number(&mut self, n: impl core::fmt::Display) {
+        self.flush_pending();
+        self.tokens.push(format!("#{}", n));
+    }
+
+    /// Return the recorded tokens, including pending data.
+    pub fn into_tokens(mut self) -> Vec<String> {
+        self.flush_pending();
+        self.tokens
+    }
+}
+
+impl Hasher for SignatureHasher {
+    fn finish(&self) -> u64 {
+        0
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        self.pending.extend_from_slice(bytes);
+    }
+
+    fn write_u8(&mut self, i: u8) {
+        if i == 0xff {
+            // End of a string
+            let token = String::from_utf8_lossy(&self.pending).into_owned();
+            self.tokens.push(token);
+            self.pending.clear();
+        } else {
+            self.push_number(i);
+        }
+    }
+
+    fn write_u16(&mut self, i: u16) {
+        self.push_number(i);
+    }
+
+    fn write_u32(&mut self, i: u32) {
+        self.push_number(i);
+    }
+
+    fn write_u64(&mut self, i: u64) {
+        self.push_number(i);
+    }
+
+    fn write_u128(&mut self, i: u128) {
+        self.push_number(i);
+    }
+
+    fn write_usize(&mut self, i: usize) {
+        self.push_number(i);
+    }
+}
+
+/// The information recorded for a type in a [`TypeHashLock`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LockEntry {
+    /// The type hash, as written in the header.
+    pub type_hash: u64,
+    /// The representation hash, as written in the header.
+    pub repr_hash: u64,
+    /// The data fed to the hasher by [`TypeHash::type_hash`].
+    pub type_signature: Vec<String>,
+    /// The data fed to the hasher by [`ReprHash::repr_hash`].
+    pub repr_signature: Vec<String>,
+}
+
+impl LockEntry {
+    /// Compute the entry for a type.
+    pub fn of<T: TypeHash + ReprHash + ?Sized>() -> Self {
+        let (type_hash, repr_hash) = compute_type_hashes::<T>();
+
+        let mut hasher = SignatureHasher::default();
+        T::type_hash(&mut hasher);
+        let type_signature = hasher.into_tokens();
+
+        let mut hasher = SignatureHasher::default();
+        let mut offset_of = 0;
+        T::repr_hash(&mut hasher, &mut offset_of);
+        let repr_signature = hasher.into_tokens();
+
+        Self {
+            type_hash,
+            repr_hash,
+            type_signature,
+            repr_signature,
+        }
+    }
+}
+
+/// A difference between two [`TypeHashLock`]s.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LockDiff {
+    /// A type is present in the expected lock but it is not recorded anymore.
+    Missing(String),
+    /// A type is recorded but it is not present in the expected lock.
+    Added(String),
+    /// The type hash of a type has changed.
+    TypeHashChanged {
+        type_name: String,
+        expected: u64,
+        got: u64,
+        /// A line-by-line description of the changes of the signature.
+        changes: Vec<String>,
+    },
+    /// The representation hash of a type has changed.
+    ReprHashChanged {
+        type_name: String,
+        expected: u64,
+        got: u64,
+        /// A line-by-line description of the changes of the signature.
+        changes: Vec<String>,
+    },
+}
+
+impl core::fmt::Display for LockDiff {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Missing(type_name) => write!(f, "Type {} is not recorded anymore", type_name),
+            Self::Added(type_name) => write!(f, "Type {} is not in the lock file", type_name),
+            Self::TypeHashChanged {
+                type_name,
+                expected,
+                got,
+                changes,
+            } => {
+                writeln!(
+                    f,
+                    "Type hash of {} changed from 0x{:016x} to 0x{:016x}:",
+                    type_name, expected, got
+                )?;
+                for change in changes {
+                    writeln!(f, "    {}", change)?;
+                }
+                Ok(())
+            }
+            Self::ReprHashChanged {
+                type_name,
+                expected,
+                got,
+                changes,
+            } => {
+                writeln!(
+                    f,
+                    "Representation hash of {} changed from 0x{:016x} to 0x{:016x}:",
+                    type_name, expected, got
+                )?;
+                for change in changes {
+                    writeln!(f, "    {}", change)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Describe the differences between two signatures.
+///
+/// We compute a longest common subsequence and report removed and added tokens,
+/// each preceded by the last common token as context.
+pub(crate) fn diff_signatures(expected: &[String], got: &[String]) -> Vec<String> {
+    // The length of a longest common subsequence of expected[i..] and
+    // got[j..], which is zero outside of the table
+    fn lcs_len(lcs: &[Vec<usize>], i: usize, j: usize) -> usize {
+        lcs.get(i).and_then(|row| row.get(j)).copied().unwrap_or(0)
+    }
+    let (n, m) = (expected.len(), got.len());
+    let mut lcs = vec![vec![0_usize; m + 1]; n + 1];
+    for (i, expected_token) in expected.iter().enumerate().rev() {
+        for (j, got_token) in got.iter().enumerate().rev() {
+            let len = if expected_token == got_token {
+                lcs_len(&lcs, i + 1, j + 1) + 1
+            } else {
+                lcs_len(&lcs, i + 1, j).max(lcs_len(&lcs, i, j + 1))
+            };
+            if let Some(cell) = lcs.get_mut(i).and_then(|row| row.get_mut(j)) {
+                *cell = len;
+            }
+        }
+    }
+
+    let mut changes = vec![];
+    let (mut i, mut j) = (0, 0);
+    let mut context: Option<&str> = None;
+    let describe = |context: Option<&str>| match context {
+        Some(token) => format!(" (after {:?})", token),
+        None => " (at start)".to_string(),
+    };
+    loop {
+        let remove = j == m || lcs_len(&lcs, i + 1, j) >= lcs_len(&lcs, i, j + 1);
+        match (expected.get(i), got.get(j)) {
+            (Some(expected_token), Some(got_token)) if expected_token == got_token => {
+                context = Some(expected_token);
+                i += 1;
+                j += 1;
+            }
+            (Some(expected_token), _) if remove => {
+                changes.push(format!("- {:?}{}", expected_token, describe(context)));
+                i += 1;
+            }
+            (_, Some(got_token)) => {
+                changes.push(format!("+ {:?}{}", got_token, describe(context)));
+                j += 1;
+            }
+            _ => break,
+        }
+    }
+    changes
+}
+
+/// A set of types with their hashes and signatures, that can be stored in
+/// and loaded from a lock file.
+///
+/// Types are identified by their name as returned by [`core::any::type_name`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TypeHashLock {
+    pub entries: BTreeMap<String, LockEntry>,
+}
+
+impl TypeHashLock {
+    /// Create an empty lock.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record a type.
+    pub fn record<T: TypeHash + ReprHash + ?Sized>(&mut self) -> &mut Self {
+        self.entries.insert(
+            core::any::type_name::<T>().to_string(),
+            LockEntry::of::<T>(),
+        );
+        self
+    }
+
+    /// Return the differences between this lock and an `expected` one.
+    pub fn diff(&self, expected: &TypeHashLock) -> Vec<LockDiff> {
+        let mut diffs = vec![];
+        for (type_name, expected_entry) in &expected.entries {
+            let Some(entry) = self.entries.get(type_name) else {
+                diffs.push(LockDiff::Missing(type_name.clone()));
+                continue;
+            };
+            if entry.type_hash != expected_entry.type_hash {
+                diffs.push(LockDiff::TypeHashChanged {
+                    type_name: type_name.clone(),
+                    expected: expected_entry.type_hash,
+                    got: entry.type_hash,
+                    changes: diff_signatures(&expected_entry.type_signature, &entry.type_signature),
+                });
+            }
+            if entry.repr_hash != expected_entry.repr_hash {
+                diffs.push(LockDiff::ReprHashChanged {
+                    type_name: type_name.clone(),
+                    expected: expected_entry.repr_hash,
+                    got: entry.repr_hash,
+                    changes: diff_signatures(&expected_entry.repr_signature, &entry.repr_signature),
+                });
+            }
+        }
+        for type_name in self.entries.keys() {
+            if !expected.entries.contains_key(type_name) {
+                diffs.push(LockDiff::Added(type_name.clone()));
+            }
+        }
+        diffs
+    }
+
+    /// Parse a lock from the textual format produced by the [`Display`](core::fmt::Display)
+    /// implementation.
+    pub fn parse(s: &str) -> Result<Self, String> {
+        let mut lock = Self::new();
+        let mut current: Option<(String, LockEntry)> = None;
+        let parse_hash = |value: &str, line_number: usize| {
+            u64::from_str_radix(value.trim_start_matches("0x"), 16)
+                .map_err(|e| format!("Line {}: invalid hash {:?}: {}", line_number, value, e))
+        };
+        let parse_signature = |value: &str| {
+            if value.is_empty() {
+                vec![]
+            } else {
+                value.split('\t').map(|s| s.to_string()).collect()
+            }
+        };
+
+        for (line_number, line) in s.lines().enumerate() {
+            let line_number = line_number + 1;
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (key, value) = line.split_once(' ').unwrap_or((line, ""));
+            if key == "type" {
+                if let Some((type_name, entry)) = current.take() {
+                    lock.entries.insert(type_name, entry);
+                }
+                current = Some((
+                    value.to_string(),
+                    LockEntry {
+                        type_hash: 0,
+                        repr_hash: 0,
+                        type_signature: vec![],
+                        repr_signature: vec![],
+                    },
+                ));
+                continue;
+            }
+            let Some((_, entry)) = current.as_mut() else {
+                return Err(format!("Line {}: no type specified", line_number));
+            };
+            match key {
+                "type_hash" => entry.type_hash = parse_hash(value, line_number)?,
+                "repr_hash" => entry.repr_hash = parse_hash(value, line_number)?,
+                "type_signature" => entry.type_signature = parse_signature(value),
+                "repr_signature" => entry.repr_signature = parse_signature(value),
+                _ => return Err(format!("Line {}: unknown key {:?}", line_number, key)),
+            }
+        }
+        if let Some((type_name, entry)) = current.take() {
+            lock.entries.insert(type_name, entry);
+        }
+        Ok(lock)
+    }
+
+    /// Load a lock from a file.
+    pub fn load(path: impl AsRef<Path>) -> std::io::Result<Self> {
+        let s = std::fs::read_to_string(path)?;
+        Self::parse(&s).map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))
+    }
+
+    /// Store the lock into a file.
+    pub fn store(&self, path: impl AsRef<Path>) -> std::io::Result<()> {
+        std::fs::write(path, self.to_string())
+    }
+
+    /// Compare this lock with the one stored in the given file.
+    ///
+    /// If the file does not exist, or if the environment variable
+    /// [`UPDATE_LOCK_ENV`] is set, the lock is stored in the file and
+    /// the method returns successfully. Otherwise, an error containing a
+    /// description of all differences is returned if the locks differ.
+    pub fn check(&self, path: impl AsRef<Path>) -> Result<(), String> {
+        let path = path.as_ref();
+        if !path.exists() || std::env::var_os(UPDATE_LOCK_ENV).is_some() {
+            return self
+                .store(path)
+                .map_err(|e| format!("Could not write {}: {}", path.display(), e));
+        }
+        let expected =
+            Self::load(path).map_err(|e| format!("Could not read {}: {}", path.display(), e))?;
+        let diffs = self.diff(&expected);
+        if diffs.is_empty() {
+            return Ok(());
+        }
+        let mut message = format!(
+            "The on-disk format of persisted types differs from {}.\n",
+            path.display()
+        );
+        for diff in diffs {
+            message.push_str(&format!("{}\n", diff));
+        }
+        message.push_str(&format!(
+            "If the change is intended, set the environment variable {} to update the lock file.",
+            UPDATE_LOCK_ENV
+        ));
+        Err(message)
+    }
+}
+
+impl core::fmt::Display for TypeHashLock {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        writeln!(f, "# ε-serde type hash lock file")?;
+        for (type_name, entry) in &self.entries {
+            writeln!(f)?;
+            writeln!(f, "type {}", type_name)?;
+            writeln!(f, "type_hash 0x{:016x}", entry.type_hash)?;
+            writeln!(f, "repr_hash 0x{:016x}", entry.repr_hash)?;
+            writeln!(f, "type_signature {}", entry.type_signature.join("\t"))?;
+            writeln!(f, "repr_signature {}", entry.repr_signature.join("\t"))?;
+        }
+        Ok(())
+    }
+}
+
+/// Check that the hashes of the given types match those recorded in a
+/// lock file, panicking with a description of the differences otherwise.
+///
+/// See the [module documentation](crate::semver) and [`TypeHashLock::check`].
+#[macro_export]
+macro_rules! check_type_hash_lock {
+    ($path:expr, $($ty:ty),+ $(,)?) => {{
+        let mut lock = $crate::semver::TypeHashLock::new();
+        $(
+            lock.record::<$ty>();
+        )+
+        if let Err(message) = lock.check($path) {
+            panic!("{}", message);
+        }
+    }};
+}
+
+/// Assert that the [type hash](crate::type_hash_of) of a type is equal
+/// to a golden value, panicking with the actual value otherwise.
+///
+/// See the [module documentation](crate::semver).
+#[macro_export]
+macro_rules! assert_type_hash_stable {
+    ($ty:ty, $hash:expr $(,)?) => {{
+        let expected: u64 = $hash;
+        let got = $crate::type_hash_of::<$ty>();
+        if got != expected {
+            panic!(
+                "The type hash of {} is 0x{:016x} instead of 0x{:016x}, so data serialized with the previous format cannot be deserialized; use a TypeHashLock to find out which part of the type changed",
+                stringify!($ty),
+                got,
+                expected
+            );
+        }
+    }};
+}
@@ -0,0 +1,249 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ * SPDX-FileCopyrightText: 2023 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+/*!
+
+Helpers for serialization.
+
+*/
+
+use super::{SerializeInner, WriteWithNames};
+use crate::ser;
+use crate::traits::*;
+
+#[cfg(all(feature = "alloc", not(feature = "std")))]
+use alloc::vec::Vec;
+
+/// Return the maximum of the given alignments, or 1 if there are none.
+///
+/// This function is used by the derive macros to compute
+/// [`SerializeInner::MAX_FIELD_ALIGN`].
+pub const fn max_align(aligns: &[usize]) -> usize {
+    let mut max = 1;
+    let mut i = 0;
+    while i < aligns.len() {
+        if max < aligns[i] {
+            max = aligns[i];
+        }
+        i += 1;
+    }
+    max
+}
+
+pub fn check_zero_copy<V: SerializeInner>() {
+    if !V::IS_ZERO_COPY {
+        panic!(
+            "Cannot serialize type {} declared as zero-copy as it is not zero-copy",
+            core::any::type_name::<V>()
+        );
+    }
+}
+
+/// Serialize a zero-copy structure by writing its bytes properly [aligned](WriteWithNames::align).
+///
+/// Note that this method uses a single `write_all` call to write the entire structure.
+///
+/// Here we check [that the type is actually zero-copy](SerializeInner::IS_ZERO_COPY).
+pub fn serialize_zero<V: ZeroCopy + SerializeInner>(
+    backend: &mut impl WriteWithNames,
+    value: &V,
+) -> ser::Result<()> {
+    check_zero_copy::<V>();
+    let buffer = unsafe {
+        #[allow(clippy::manual_slice_size_calculation)]
+        core::slice::from_raw_parts(value as *const V as *const u8, core::mem::size_of::<V>())
+    };
+    backend.align::<V>()?;
+    backend.write_bytes::<V>(buffer)
+}
+
+/// Serialize a slice of zero-copy structures by encoding
+/// its length first, and then its bytes properly [aligned](WriteWithNames::align).
+///
+/// Note that this method uses a single `write_all`
+/// call to write the entire slice.
+///
+/// Here we check [that the type is actually zero-copy](SerializeInner::IS_ZERO_COPY).
+pub fn serialize_slice_zero<V: SerializeInner + ZeroCopy>(
+    backend: &mut impl WriteWithNames,
+    data: &[V],
+) -> ser::Result<()> {
+    check_zero_copy::<V>();
+
+    let len = data.len();
+    backend.write("len", &len)?;
+    let buffer = unsafe {
+        #[allow(clippy::manual_slice_size_calculation)]
+        core::slice::from_raw_parts(data.as_ptr() as *const u8, len * core::mem::size_of::<V>())
+    };
+    backend.align::<V>()?;
+    backend.write_bytes::<V>(buffer)
+}
+
+/// Serialize a sequence of `len` zero-copy structures returned by an iterator
+/// in the same format of [`serialize_slice_zero`], that is, as a slice.
+///
+/// Differently from [`serialize_slice_zero`], the sequence does not need
+/// to be available in memory: values are buffered and written in chunks of
+/// about 64 KiB. The data can be deserialized as a vector or as a boxed
+/// slice.
+///
+/// Here we check [that the type is actually zero-copy](SerializeInner::IS_ZERO_COPY).
+///
+/// # Panics
+///
+/// If the iterator does not return exactly `len` values.
+pub fn serialize_iter_zero<V: SerializeInner + ZeroCopy>(
+    backend: &mut impl WriteWithNames,
+    len: usize,
+    iter: impl IntoIterator<Item = V>,
+) -> ser::Result<()> {
+    check_zero_copy::<V>();
+    const CHUNK_SIZE: usize = 1 << 16;
+
+    let size = core::mem::size_of::<V>();
+    backend.write("len", &len)?;
+    backend.align::<V>()?;
+    backend.begin_bytes::<V>(len * size)?;
+
+    let mut buffer = Vec::with_capacity((CHUNK_SIZE / size.max(1)).clamp(1, len.max(1)));
+    let mut count = 0;
+    for value in iter {
+        count += 1;
+        if count > len {
+            panic!(
+                "Cannot serialize more than the declared {} values of type {}",
+                len,
+                core::any::type_name::<V>()
+            );
+        }
+        buffer.push(value);
+        if buffer.len() == buffer.capacity() {
+            write_values(backend, &buffer)?;
+            buffer.clear();
+        }
+    }
+    if count != len {
+        panic!(
+            "Cannot serialize {} values of type {}: the iterator returned only {} values",
+            len,
+            core::any::type_name::<V>(),
+            count
+        );
+    }
+    write_values(backend, &buffer)
+}
+
+/// Write the memory representation of a slice of zero-copy structures.
+fn write_values<V: ZeroCopy>(backend: &mut impl WriteWithNames, values: &[V]) -> ser::Result<()> {
+    let buffer = unsafe {
+        #[allow(clippy::manual_slice_size_calculation)]
+        core::slice::from_raw_parts(
+            values.as_ptr() as *const u8,
+            values.len() * core::mem::size_of::<V>(),
+        )
+    };
+    backend.write_all(buffer)
+}
+
+pub fn check_mismatch<V: SerializeInner>() {
+    #[cfg(feature = "std")]
+    if V::ZERO_COPY_MISMATCH {
+        eprintln!("Type {} is zero-copy, but it has not declared as such; use the #[deep_copy] attribute to silence this warning", core::any::type_name::<V>());
+    }
+}
+
+/// Serialize a slice of deep-copy structures by encoding
+/// its length first, and then the contents item by item.
+///
+/// Here we warn [that the type might actually be zero-copy](SerializeInner::ZERO_COPY_MISMATCH).
+pub fn serialize_slice_deep<V: SerializeInner>(
+    backend: &mut impl WriteWithNames,
+    data: &[V],
+) -> ser::Result<()> {
+    check_mismatch::<V>();
+    let len = data.len();
+    backend.write("len", &len)?;
+    for item in data.iter() {
+        backend.write("item", item)?;
+    }
+    Ok(())
+}
+
+/// A helper trait that makes it possible to serialize differently
+/// sequences of [`ZeroCopy`] and [`DeepCopy`] elements returned by an
+/// iterator, rather than stored in a slice.
+///
+/// The data written is exactly the same as that written by
+/// [`serialize_slice_zero`] or [`serialize_slice_deep`], so it
+/// can be deserialized as a vector. This is useful for containers,
+/// such as [`BTreeMap`](std::collections::BTreeMap), that do not store
+/// their elements contiguously.
+pub trait SerializeIterHelper<T: CopySelector>: SerializeInner {
+    fn _serialize_iter<'a>(
+        backend: &mut impl WriteWithNames,
+        len: usize,
+        iter: impl Iterator<Item = &'a Self>,
+    ) -> ser::Result<()>
+    where
+        Self: 'a;
+}
+
+impl<V: ZeroCopy + SerializeInner> SerializeIterHelper<Zero> for V {
+    fn _serialize_iter<'a>(
+        backend: &mut impl WriteWithNames,
+        len: usize,
+        iter: impl Iterator<Item = &'a Self>,
+    ) -> ser::Result<()>
+    where
+        Self: 'a,
+    {
+        check_zero_copy::<V>();
+        backend.write("len", &len)?;
+        backend.align::<V>()?;
+        let mut written = 0;
+        for item in iter {
+            let buffer = unsafe {
+                core::slice::from_raw_parts(
+                    item as *const V as *const u8,
+                    core::mem::size_of::<V>(),
+                )
+            };
+            backend.write_bytes::<V>(buffer)?;
+            written += 1;
+        }
+        assert_eq!(
+            written, len,
+            "The iterator returned a wrong number of items"
+        );
+        Ok(())
+    }
+}
+
+impl<V: DeepCopy + SerializeInner> SerializeIterHelper<Deep> for V {
+    fn _serialize_iter<'a>(
+        backend: &mut impl WriteWithNames,
+        len: usize,
+        iter: impl Iterator<Item = &'a Self>,
+    ) -> ser::Result<()>
+    where
+        Self: 'a,
+    {
+        check_mismatch::<V>();
+        backend.write("len", &len)?;
+        let mut written = 0;
+        for item in iter {
+            backend.write("item", item)?;
+            written += 1;
+        }
+        assert_eq!(
+            written, len,
+            "The iterator returned a wrong number of items"
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,228 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ * SPDX-FileCopyrightText: 2023 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+/*!
+
+Incremental serialization of deep-copy structures.
+
+An [`IncrementalSerializer`] writes the same bytes as
+[`Serialize::serialize`], but the fields of the structure are provided one
+at a time, in declaration order, rather than all together as a value of the
+structure type. In this way, each field can be dropped as soon as it has been
+written, and the structure never needs to exist in memory as a whole.
+
+```rust
+use epserde::prelude::*;
+use epserde::ser::IncrementalSerializer;
+
+#[derive(Epserde, Debug, PartialEq)]
+struct Index<A> {
+    offsets: A,
+    names: Vec<String>,
+    total: usize,
+}
+
+let mut cursor = epserde::new_aligned_cursor();
+let mut ser = IncrementalSerializer::<Index<Vec<u64>>, _>::new(&mut cursor).unwrap();
+// Each field might be produced by a different stage of a pipeline
+ser.write("offsets", &vec![0_u64, 10, 20]).unwrap();
+ser.write("names", &vec!["a".to_string(), "b".to_string()]).unwrap();
+ser.write("total", &30_usize).unwrap();
+ser.finish().unwrap();
+
+let buf = cursor.into_inner();
+let index = <Index<Vec<u64>>>::deserialize_eps(&buf).unwrap();
+assert_eq!(index.offsets, [0, 10, 20]);
+assert_eq!(index.total, 30);
+```
+
+Vectors of zero-copy types can be written with [`IncrementalSerializer::write_iter`]
+from an iterator returning a known number of values, and a vector can be
+serialized as the root using [`serialize_iter`], so that huge vectors produced
+lazily never need to be materialized in memory.
+
+*/
+
+use super::*;
+use core::marker::PhantomData;
+
+#[cfg(all(feature = "alloc", not(feature = "std")))]
+use alloc::{string::String, vec::Vec};
+
+/// A serializer writing the fields of a deep-copy structure of type `T`
+/// one at a time.
+///
+/// The header is written by [`IncrementalSerializer::new`]; then, all fields
+/// must be written, in declaration order, using [`IncrementalSerializer::write`];
+/// finally, [`IncrementalSerializer::finish`] checks that no field is missing
+/// and flushes the backend. The resulting data is indistinguishable from that
+/// written by [`Serialize::serialize`].
+///
+/// The name and the [shape](TypeShape) of each field are checked against
+/// those of the corresponding field of `T`; as in the case of
+/// [`serialize_zero`], violations are programming errors and cause a panic.
+pub struct IncrementalSerializer<'a, T, W: WriteNoStd> {
+    backend: WriterWithPos<'a, W>,
+    /// Name, type shape, and representation shape of the fields of `T`.
+    fields: Vec<(String, TypeShape, TypeShape)>,
+    /// The index of the next field to write.
+    next: usize,
+    _marker: PhantomData<fn(&T)>,
+}
+
+impl<'a, T: SerializeInner + TypeHash + ReprHash + 'static, W: WriteNoStd>
+    IncrementalSerializer<'a, T, W>
+{
+    /// Create a new incremental serializer writing on the given backend,
+    /// and write the header.
+    ///
+    /// # Panics
+    ///
+    /// If `T` is not a derived deep-copy structure.
+    pub fn new(backend: &'a mut W) -> Result<Self> {
+        let (
+            TypeShape::Struct { fields, .. },
+            TypeShape::Struct {
+                fields: repr_fields,
+                ..
+            },
+        ) = (T::type_shape(), T::repr_shape())
+        else {
+            panic!(
+                "Cannot serialize incrementally type {} as it is not a deep-copy structure",
+                core::any::type_name::<T>()
+            );
+        };
+        check_mismatch::<T>();
+
+        let mut backend = WriterWithPos::new(backend);
+        write_header::<T>(&mut backend)?;
+        Ok(Self {
+            backend,
+            fields: fields
+                .into_iter()
+                .zip(repr_fields)
+                .map(|((field, type_shape), (_, repr_shape))| (field, type_shape, repr_shape))
+                .collect(),
+            next: 0,
+            _marker: PhantomData,
+        })
+    }
+
+    /// Return the name of the next field to write, or `None` if all
+    /// fields have been written.
+    pub fn next_field(&self) -> Option<&str> {
+        self.fields
+            .get(self.next)
+            .map(|(field, _, _)| field.as_str())
+    }
+
+    /// Write the next field of the structure.
+    ///
+    /// # Panics
+    ///
+    /// If all fields have already been written, if `field` is not the name
+    /// of the next field, or if the shape of `V` is not that of the type
+    /// of the next field.
+    pub fn write<V: SerializeInner + TypeHash + ReprHash>(
+        &mut self,
+        field: &str,
+        value: &V,
+    ) -> Result<()> {
+        self.check_next::<V>(field);
+        self.next += 1;
+        self.backend.write(field, value)
+    }
+
+    /// Write the next field of the structure, which must be a vector of
+    /// `len` values of a zero-copy type, taking the values from an iterator.
+    ///
+    /// The data is written as by [`IncrementalSerializer::write`] on a
+    /// `Vec<V>`, but the values are never in memory at the same time
+    /// (see [`serialize_iter_zero`]).
+    ///
+    /// # Panics
+    ///
+    /// In the same cases of [`IncrementalSerializer::write`], with `Vec<V>`
+    /// in place of `V`, and if the iterator does not return exactly `len` values.
+    pub fn write_iter<V: SerializeInner + ZeroCopy + TypeHash + ReprHash>(
+        &mut self,
+        field: &str,
+        len: usize,
+        iter: impl IntoIterator<Item = V>,
+    ) -> Result<()> {
+        self.check_next::<Vec<V>>(field);
+        self.next += 1;
+        serialize_iter_zero(&mut self.backend, len, iter)
+    }
+
+    /// Check that `field` is the next field, and that its type has the
+    /// same shape of `V`.
+    fn check_next<V: TypeHash + ReprHash>(&self, field: &str) {
+        let type_name = core::any::type_name::<T>();
+        let Some((expected, type_shape, repr_shape)) = self.fields.get(self.next) else {
+            panic!(
+                "Cannot write field {} of type {}: all fields have been written",
+                field, type_name
+            );
+        };
+        if field != expected {
+            panic!(
+                "Cannot write field {} of type {}: the next field is {}",
+                field, type_name, expected
+            );
+        }
+        if V::type_shape() != *type_shape || V::repr_shape() != *repr_shape {
+            panic!(
+                "Cannot write field {} of type {}: type {} does not match the type of the field",
+                field,
+                type_name,
+                core::any::type_name::<V>()
+            );
+        }
+    }
+
+    /// Check that all fields have been written and flush the backend,
+    /// returning the number of bytes written.
+    ///
+    /// # Panics
+    ///
+    /// If some field has not been written.
+    pub fn finish(mut self) -> Result<usize> {
+        if let Some(field) = self.next_field() {
+            panic!(
+                "Cannot finish serialization of type {}: field {} has not been written",
+                core::any::type_name::<T>(),
+                field
+            );
+        }
+        self.backend.flush()?;
+        Ok(self.backend.pos())
+    }
+}
+
+/// Serialize a vector of `len` values of a zero-copy type, taking the
+/// values from an iterator, and return the number of bytes written.
+///
+/// The data is indistinguishable from that written by [`Serialize::serialize`]
+/// on a `Vec<V>`, but the values are never in memory at the same time
+/// (see [`serialize_iter_zero`]).
+///
+/// # Panics
+///
+/// If the iterator does not return exactly `len` values.
+pub fn serialize_iter<V: SerializeInner + ZeroCopy + TypeHash + ReprHash + 'static>(
+    backend: &mut impl WriteNoStd,
+    len: usize,
+    iter: impl IntoIterator<Item = V>,
+) -> Result<usize> {
+    let mut backend = WriterWithPos::new(backend);
+    write_header::<Vec<V>>(&mut backend)?;
+    serialize_iter_zero(&mut backend, len, iter)?;
+    backend.flush()?;
+    Ok(backend.pos())
+}
@@ -0,0 +1,159 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ * SPDX-FileCopyrightText: 2023 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+/*!
+
+Serialization directly into a memory-mapped file.
+
+*/
+
+use crate::prelude::*;
+use crate::ser::WriteNoStd;
+use std::fs::File;
+use std::path::Path;
+
+/// A [`WriteNoStd`] writing directly into a shared memory mapping of a file
+/// of given length.
+///
+/// Serializing with this writer does not involve system calls, except for
+/// page faults, which is advantageous for very large structures. Once
+/// serialization is complete, [`finish`](MmapWriter::finish) truncates the
+/// file to the number of bytes written, and returns a read-only mapping
+/// of the file, which can be immediately ε-deserialized (e.g., for
+/// verification):
+///
+/// ```rust
+/// use epserde::prelude::*;
+/// use epserde::ser::MmapWriter;
+///
+/// let data: Vec<u64> = (0..1000).collect();
+/// let path = std::env::temp_dir().join("mmap_writer_doc.eps");
+/// let mut writer = MmapWriter::create(&path, data.serialized_size())?;
+/// data.serialize(&mut writer)?;
+/// let mmap = writer.finish()?;
+/// assert_eq!(<Vec<u64>>::deserialize_eps(&mmap)?, data);
+/// # drop(mmap);
+/// # std::fs::remove_file(&path)?;
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+///
+/// As in the case of [`SliceWriter`](crate::ser::SliceWriter), bytes that do not fit into the mapping
+/// are discarded, but they are still counted, and
+/// [`finish`](MmapWriter::finish) will return
+/// [`Error::BufferTooSmall`](crate::ser::Error::BufferTooSmall) with the
+/// number of bytes that would have been necessary.
+///
+/// [`Serialize::store_mmap`] computes the length of the serialized data and
+/// serializes it using this writer.
+pub struct MmapWriter {
+    file: File,
+    mmap: mmap_rs::MmapMut,
+    /// The number of bytes written, including those that did not fit.
+    len: usize,
+}
+
+impl core::fmt::Debug for MmapWriter {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("MmapWriter")
+            .field("capacity", &self.capacity())
+            .field("len", &self.len)
+            .finish()
+    }
+}
+
+impl MmapWriter {
+    /// Create (or truncate) the file at the given path, extend it to
+    /// `capacity` bytes, and map it in shared mode.
+    ///
+    /// Mapping an empty region is not possible, so `capacity` must be
+    /// positive.
+    pub fn create(path: impl AsRef<Path>, capacity: usize) -> ser::Result<Self> {
+        let file = std::fs::OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(path)
+            .map_err(ser::Error::FileOpenError)?;
+        file.set_len(capacity as u64).map_err(ser::Error::IoError)?;
+        // SAFETY: the file has just been created with the right length, and
+        // it is not accessed through other means while mapped.
+        let mmap = unsafe {
+            mmap_rs::MmapOptions::new(capacity)
+                .and_then(|options| {
+                    options
+                        .with_flags(mmap_rs::MmapFlags::SHARED)
+                        .with_file(&file, 0)
+                        .map_mut()
+                })
+                .map_err(map_error)?
+        };
+        Ok(Self { file, mmap, len: 0 })
+    }
+
+    /// Return the length of the mapping.
+    pub fn capacity(&self) -> usize {
+        self.mmap.len()
+    }
+
+    /// Return the number of bytes written so far, including those that
+    /// did not fit into the mapping.
+    pub fn required_len(&self) -> usize {
+        self.len
+    }
+
+    /// Return whether all bytes written so far fit into the mapping.
+    pub fn fits(&self) -> bool {
+        self.len <= self.capacity()
+    }
+
+    /// Synchronize the mapping with the file, truncate the file to the number
+    /// of bytes written, and return a read-only mapping of the file.
+    ///
+    /// If the bytes written did not fit into the mapping, this method returns
+    /// [`Error::BufferTooSmall`](crate::ser::Error::BufferTooSmall).
+    pub fn finish(self) -> ser::Result<mmap_rs::Mmap> {
+        if !self.fits() {
+            return Err(ser::Error::BufferTooSmall { required: self.len });
+        }
+        self.mmap.flush(0..self.len).map_err(map_error)?;
+        let Self { file, mmap, len } = self;
+        // The mapping must be dropped before truncating the file
+        drop(mmap);
+        file.set_len(len as u64).map_err(ser::Error::IoError)?;
+        // SAFETY: the file is not accessed through other means while mapped.
+        unsafe {
+            mmap_rs::MmapOptions::new(len)
+                .and_then(|options| options.with_file(&file, 0).map())
+                .map_err(map_error)
+        }
+    }
+}
+
+/// Convert an `mmap_rs` error into an I/O error.
+fn map_error(err: mmap_rs::Error) -> ser::Error {
+    ser::Error::IoError(std::io::Error::other(err))
+}
+
+impl WriteNoStd for MmapWriter {
+    #[inline(always)]
+    fn write_all(&mut self, buf: &[u8]) -> ser::Result<()> {
+        if let Some(dst) = self.mmap.get_mut(self.len..self.len + buf.len()) {
+            dst.copy_from_slice(buf);
+        }
+        self.len += buf.len();
+        Ok(())
+    }
+
+    /// Does nothing, as the content of a shared mapping is immediately
+    /// visible to other processes; the mapping is synchronized with the
+    /// file by [`MmapWriter::finish`].
+    #[inline(always)]
+    fn flush(&mut self) -> ser::Result<()> {
+        Ok(())
+    }
+}
@@ -0,0 +1,715 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ * SPDX-FileCopyrightText: 2023 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+// Serialization panics only on misuse of the API, never on malformed data.
+#![cfg_attr(
+    feature = "panic-free",
+    allow(clippy::panic, clippy::expect_used, clippy::indexing_slicing)
+)]
+
+/*!
+
+Serialization traits and types.
+
+[`Serialize`] is the main serialization trait, providing a
+[`Serialize::serialize`] method that serializes the type into a
+generic [`WriteNoStd`] backend, and a [`Serialize::serialize_with_schema`] method
+that additionally returns a [`Schema`] describing the data that has been written.
+The implementation of this trait
+is based on [`SerializeInner`], which is automatically derived
+with `#[derive(Serialize)]`.
+
+An [`IncrementalSerializer`] makes it possible to serialize a deep-copy
+structure field by field, without ever holding the whole structure in memory.
+
+*/
+
+use crate::traits::*;
+use crate::*;
+
+#[cfg(all(feature = "alloc", not(feature = "std")))]
+use alloc::{
+    string::{String, ToString},
+    vec::Vec,
+};
+#[cfg(feature = "std")]
+use std::{io::BufWriter, path::Path};
+
+pub mod write_with_names;
+pub use write_with_names::*;
+pub mod helpers;
+pub use helpers::*;
+pub mod write;
+pub use write::*;
+pub mod incremental;
+pub use incremental::*;
+#[cfg(feature = "rayon")]
+pub mod parallel;
+#[cfg(feature = "rayon")]
+pub use parallel::*;
+#[cfg(feature = "mmap-rs")]
+pub mod mmap_writer;
+#[cfg(feature = "mmap-rs")]
+pub use mmap_writer::*;
+
+pub type Result<T> = core::result::Result<T, Error>;
+
+/// Main serialization trait. It is separated from [`SerializeInner`] to
+/// avoid that the user modify its behavior, and hide internal serialization
+/// methods.
+///
+/// It provides a convenience method [`Serialize::store`] that serializes
+/// the type to a file.
+pub trait Serialize: TypeHash + ReprHash + 'static {
+    /// Serialize the type using the given backend.
+    fn serialize(&self, backend: &mut impl WriteNoStd) -> Result<usize> {
+        let mut write_with_pos = WriterWithPos::new(backend);
+        self.serialize_on_field_write(&mut write_with_pos)?;
+        Ok(write_with_pos.pos())
+    }
+
+    /// Serialize the type into the given slice of bytes, returning the
+    /// number of bytes written.
+    ///
+    /// If the slice is too small, [`Error::BufferTooSmall`] is returned with
+    /// the number of bytes needed, and the content of the slice is
+    /// unspecified. This method makes it possible to serialize, for example,
+    /// into a region of shared memory without intermediate allocations.
+    ///
+    /// To ε-copy deserialize the data, the slice must be suitably aligned
+    /// in memory (e.g., to 16 bytes): otherwise,
+    /// [`Deserialize::deserialize_eps`](crate::deser::Deserialize::deserialize_eps)
+    /// will return [`Error::AlignmentError`](crate::deser::Error::AlignmentError).
+    ///
+    /// ```rust
+    /// use epserde::prelude::*;
+    ///
+    /// let data = vec![0_u64, 1, 2, 3];
+    /// // A buffer aligned to 16 bytes
+    /// let mut buf = [0_u128; 8];
+    /// let bytes: &mut [u8] = bytemuck::cast_slice_mut(&mut buf);
+    ///
+    /// let required = match data.serialize_into_slice(&mut bytes[..16]) {
+    ///     Err(ser::Error::BufferTooSmall { required }) => required,
+    ///     _ => unreachable!(),
+    /// };
+    /// assert_eq!(data.serialize_into_slice(bytes)?, required);
+    ///
+    /// let eps = <Vec<u64>>::deserialize_eps(&bytes[..required])?;
+    /// assert_eq!(eps, [0, 1, 2, 3]);
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    fn serialize_into_slice(&self, buf: &mut [u8]) -> Result<usize> {
+        let mut writer = SliceWriter::new(buf);
+        self.serialize(&mut writer)?;
+        if !writer.fits() {
+            return Err(Error::BufferTooSmall {
+                required: writer.required_len(),
+            });
+        }
+        Ok(writer.required_len())
+    }
+
+    /// Return the number of bytes written by [`Serialize::serialize`].
+    ///
+    /// The type is serialized on a [`CountingWriter`], which accounts for
+    /// alignment padding but performs no I/O and allocates no memory, so
+    /// callers can preallocate buffers, size memory-mapped files (see
+    /// `MmapWriter`), or compute HTTP
+    /// `Content-Length` headers. The time required is similar to that of
+    /// a serialization on memory, except for the copies.
+    ///
+    /// ```rust
+    /// use epserde::prelude::*;
+    ///
+    /// let data = vec![0_u64, 1, 2, 3];
+    /// let mut cursor = epserde::new_aligned_cursor();
+    /// data.serialize(&mut cursor)?;
+    /// assert_eq!(data.serialized_size(), cursor.into_inner().len());
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// If serialization fails, which is possible only if the serialization
+    /// code of some type returns an error by itself (e.g., when compression
+    /// fails).
+    fn serialized_size(&self) -> usize {
+        let mut writer = CountingWriter::new();
+        self.serialize(&mut writer)
+            .expect("Serialization failed while computing the serialized size");
+        writer.len()
+    }
+
+    /// Serialize the type using the given backend in portable format.
+    ///
+    /// In the portable format `usize` and `isize` values, including the lengths
+    /// of slices, vectors and strings, are always written using eight bytes, so
+    /// that data serialized on an architecture can be deserialized on another
+    /// architecture with a different pointer width (e.g., a 64-bit server
+    /// and a `wasm32` client). On 64-bit architectures, the only difference with
+    /// [`Serialize::serialize`] is a flag in the header.
+    ///
+    /// When reading data on an architecture with a different pointer width,
+    /// values are checked to fit the native `usize`/`isize`, and the
+    /// [representation hash](crate::traits::repr_hash_of), which does not depend
+    /// on the pointer width, is checked as usual: thus, zero-copy types whose
+    /// layout differs between the two architectures (e.g., a `repr(C)` structure
+    /// containing a `u32` and a `u64`) are rejected with
+    /// [`WrongTypeReprHash`](crate::deser::Error::WrongTypeReprHash), and
+    /// zero-copy types containing `usize` or `isize` fields are rejected with
+    /// [`UsizeSizeMismatch`](crate::deser::Error::UsizeSizeMismatch).
+    ///
+    /// Data in the native format written on a 32-bit or 64-bit architecture can
+    /// be read on the other one as well, with the same limitations, but portable
+    /// data makes it possible to share a single file among architectures.
+    fn serialize_portable(&self, backend: &mut impl WriteNoStd) -> Result<usize> {
+        let mut write_with_pos = WriterWithPos::new_portable(backend);
+        self.serialize_on_field_write(&mut write_with_pos)?;
+        Ok(write_with_pos.pos())
+    }
+
+    /// Serialize the type using the given backend, writing containers whose
+    /// iteration order is not determined by their content in canonical order.
+    ///
+    /// The entries of hash-based maps and sets, such as those of the
+    /// [`indexmap`](https://crates.io/crates/indexmap) crate, are usually
+    /// written in iteration order, so logically equal containers built in
+    /// different ways might be serialized differently. In canonical order,
+    /// entries are instead sorted by a fixed 64-bit hash of their key, so
+    /// logically equal values always serialize to the same bytes (barring
+    /// hash collisions between keys), making it possible to deduplicate data
+    /// by checksum or to build reproducible snapshots.
+    ///
+    /// Sorting requires additional time and memory proportional to the
+    /// number of entries. The data can be deserialized as usual.
+    ///
+    /// The hash of a key depends on its [`Hash`](core::hash::Hash)
+    /// implementation, which for `usize` and `isize` depends on the pointer
+    /// width, and in general is not guaranteed to be stable across versions
+    /// of the Rust standard library.
+    fn serialize_canonical(&self, backend: &mut impl WriteNoStd) -> Result<usize> {
+        let mut write_with_pos = WriterWithPos::new_canonical(backend);
+        self.serialize_on_field_write(&mut write_with_pos)?;
+        Ok(write_with_pos.pos())
+    }
+
+    /// Serialize the type using the given backend and return a [schema](Schema)
+    /// describing the data that has been written.
+    ///
+    /// This method is mainly useful for debugging and to check cross-language
+    /// interoperability.
+    fn serialize_with_schema(&self, backend: &mut impl WriteNoStd) -> Result<Schema> {
+        let mut writer_with_pos = WriterWithPos::new(backend);
+        let mut schema_writer = SchemaWriter::new(&mut writer_with_pos);
+        self.serialize_on_field_write(&mut schema_writer)?;
+        Ok(schema_writer.schema)
+    }
+
+    /// Serialize the type using the given backend and return a
+    /// [report](SizeReport) of the sizes of the fields of the root.
+    ///
+    /// This method is useful to log the composition of serialized data
+    /// without keeping the whole [schema](Serialize::serialize_with_schema).
+    fn serialize_with_size_report(&self, backend: &mut impl WriteNoStd) -> Result<SizeReport> {
+        Ok(self.serialize_with_schema(backend)?.size_report())
+    }
+
+    /// Serialize the type using the given backend, followed by an embedded
+    /// schema making [structural compatibility checks](crate::compat) possible.
+    ///
+    /// The data can be deserialized as usual, as the schema is ignored
+    /// by the standard deserialization methods.
+    fn serialize_with_embedded_schema(&self, backend: &mut impl WriteNoStd) -> Result<usize> {
+        let mut writer_with_pos = WriterWithPos::new(backend);
+        let mut schema_writer = SchemaWriter::new(&mut writer_with_pos);
+        self.serialize_on_field_write(&mut schema_writer)?;
+        let schema = schema_writer.schema;
+        crate::compat::write_embedded_schema::<Self>(&mut writer_with_pos, &schema)?;
+        Ok(writer_with_pos.pos())
+    }
+
+    /// Serialize the type using the given backend, storing in the header
+    /// an [xxh3](xxhash_rust::xxh3) checksum of the payload.
+    ///
+    /// The checksum is verified by all deserialization methods, which return
+    /// [`Error::ChecksumMismatch`](crate::deser::Error::ChecksumMismatch)
+    /// if the payload has been corrupted, unless
+    /// [`Flags::SKIP_CHECKSUM`](crate::deser::Flags::SKIP_CHECKSUM) is passed
+    /// to the memory-mapping methods. Note that verification requires reading
+    /// the whole payload, which makes memory mapping no longer lazy.
+    ///
+    /// Since the checksum precedes the payload, the data is
+    /// serialized in memory before being written to the backend.
+    #[cfg(feature = "std")]
+    fn serialize_with_checksum(&self, backend: &mut impl WriteNoStd) -> Result<usize> {
+        let mut bytes = Vec::new();
+        self.serialize_on_field_write(&mut WriterWithPos::new_with_checksum(&mut bytes))?;
+        // The checksum and the length of the payload close the header
+        let mut header = crate::deser::SliceWithPos::new(&bytes);
+        crate::deser::read_header(&mut header).expect("Invalid header");
+        let start = header.pos;
+        let checksum = xxhash_rust::xxh3::xxh3_64(&bytes[start..]);
+        let len = (bytes.len() - start) as u64;
+        bytes[start - 16..start - 8].copy_from_slice(&checksum.to_ne_bytes());
+        bytes[start - 8..start].copy_from_slice(&len.to_ne_bytes());
+        backend.write_all(&bytes)?;
+        backend.flush()?;
+        Ok(bytes.len())
+    }
+
+    /// Serialize the type using the given backend, followed by a
+    /// [checksum tree](crate::merkle) of chunks of the given size (e.g.,
+    /// [`DEFAULT_CHUNK_SIZE`](crate::merkle::DEFAULT_CHUNK_SIZE)).
+    ///
+    /// Contrarily to [`Serialize::serialize_with_checksum`], the data is
+    /// written directly to the backend, and it can be verified partially by a
+    /// [`ChunkVerifier`](crate::merkle::ChunkVerifier), so that, for
+    /// example, a memory-mapped file can be verified lazily.
+    ///
+    /// The header records the presence of the tree, so that ε-copy
+    /// deserialization methods can verify the chunks containing the header,
+    /// and return
+    /// [`Error::MissingChunkChecksums`](crate::deser::Error::MissingChunkChecksums)
+    /// if the tree is missing (e.g., because the data has been truncated);
+    /// the other chunks are verified on demand by a
+    /// [`ChunkVerifier`](crate::merkle::ChunkVerifier), or all together by
+    /// [`check_data`](crate::deser::check_data). Full-copy deserialization
+    /// methods ignore the tree.
+    ///
+    /// If `chunk_size` is zero, this method returns [`Error::ZeroChunkSize`].
+    fn serialize_with_chunk_checksums(
+        &self,
+        backend: &mut impl WriteNoStd,
+        chunk_size: usize,
+    ) -> Result<usize> {
+        let mut writer = crate::merkle::ChunkHashWriter::new(backend, chunk_size)?;
+        self.serialize_on_field_write(&mut WriterWithPos::new_with_chunk_checksums(&mut writer))?;
+        writer.finish()
+    }
+
+    /// Serialize the type using the given backend, storing in the header
+    /// the signature of the type, that is, the data fed to the hasher while
+    /// computing the [type hash](crate::type_hash_of) (see
+    /// [`SignatureHasher`](crate::semver::SignatureHasher)).
+    ///
+    /// The signature is a human-readable structural description of the type
+    /// (field names, type names, etc.) that can be read back by
+    /// [`inspect::read_header`] without knowing the type, and
+    /// [compared](crate::deser::Header::signature_diff) with the
+    /// signature of another type to diagnose a
+    /// [`WrongTypeHash`](crate::deser::Error::WrongTypeHash) error.
+    #[cfg(feature = "std")]
+    fn serialize_with_signature(&self, backend: &mut impl WriteNoStd) -> Result<usize> {
+        let mut write_with_pos = WriterWithPos::new_with_signature(backend);
+        self.serialize_on_field_write(&mut write_with_pos)?;
+        Ok(write_with_pos.pos())
+    }
+
+    /// Serialize the type using the given backend, compressing the
+    /// payload with the [codec](crate::compress::Codec) `C`.
+    ///
+    /// The header is not compressed, so the type of the data can be checked
+    /// before decompressing it. Compressed data can be deserialized only by
+    /// [`Deserialize::deserialize_full_compressed`](crate::deser::Deserialize::deserialize_full_compressed)
+    /// with the same codec; in particular, ε-copy deserialization is not possible.
+    ///
+    /// The payload is serialized in memory and compressed as a single block,
+    /// so this method needs memory proportional to the size of the
+    /// serialized data; see the [`crate::compress`] module.
+    #[cfg(feature = "std")]
+    fn serialize_compressed<C: crate::compress::Codec>(
+        &self,
+        backend: &mut impl WriteNoStd,
+    ) -> Result<usize>;
+
+    /// Serialize the type using the given [`WriteWithNames`].
+    fn serialize_on_field_write(&self, backend: &mut impl WriteWithNames) -> Result<()>;
+
+    /// Commodity method to serialize to a file.
+    ///
+    /// The file is written through a [`BufferedWriter`] with capacity
+    /// [`DEFAULT_BUFFER_SIZE`]; see [`Serialize::store_with_buffer_size`]
+    /// to choose a different capacity.
+    #[cfg(feature = "std")]
+    fn store(&self, path: impl AsRef<Path>) -> Result<()> {
+        self.store_with_buffer_size(path, DEFAULT_BUFFER_SIZE)
+    }
+
+    /// Commodity method to serialize to a file by writing directly into
+    /// a memory mapping of the file with an [`MmapWriter`].
+    ///
+    /// The type is serialized twice: first to compute the
+    /// [size](Serialize::serialized_size) of the file, and then to
+    /// write it. The file is identical to the one written by
+    /// [`Serialize::store`].
+    #[cfg(feature = "mmap-rs")]
+    fn store_mmap(&self, path: impl AsRef<Path>) -> Result<()> {
+        let mut writer = MmapWriter::create(path, self.serialized_size())?;
+        self.serialize(&mut writer)?;
+        writer.finish()?;
+        Ok(())
+    }
+
+    /// Commodity method to serialize to a file using a buffer of the given
+    /// size.
+    ///
+    /// Small buffers avoid large allocations when writing tiny files, whereas
+    /// large buffers reduce the number of system calls when writing huge
+    /// files. Writes larger than the buffer, such as the content of large
+    /// zero-copy vectors, bypass the buffer anyway.
+    #[cfg(feature = "std")]
+    fn store_with_buffer_size(&self, path: impl AsRef<Path>, buffer_size: usize) -> Result<()> {
+        let file = std::fs::File::create(path).map_err(Error::FileOpenError)?;
+        let mut buf_writer = BufferedWriter::with_capacity(buffer_size, file);
+        self.serialize(&mut buf_writer)?;
+        Ok(())
+    }
+
+    /// Commodity method to serialize to a file, writing also
+    /// a sidecar file containing the [JSON representation](Schema::to_json)
+    /// of the [schema](Serialize::serialize_with_schema).
+    ///
+    /// The name of the sidecar file is obtained by appending `.schema.json`
+    /// to `path` (see [`inspect::schema_path`]).
+    #[cfg(feature = "std")]
+    fn store_with_schema(&self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+        let file = std::fs::File::create(path).map_err(Error::FileOpenError)?;
+        let mut buf_writer = BufWriter::new(file);
+        let schema = self.serialize_with_schema(&mut buf_writer)?;
+
+        std::fs::write(crate::inspect::schema_path(path), schema.to_json())
+            .map_err(Error::FileOpenError)
+    }
+
+    /// Commodity method to serialize to a file, returning a
+    /// [report](Serialize::serialize_with_size_report) of the sizes
+    /// of the fields of the root.
+    #[cfg(feature = "std")]
+    fn store_with_size_report(&self, path: impl AsRef<Path>) -> Result<SizeReport> {
+        let file = std::fs::File::create(path).map_err(Error::FileOpenError)?;
+        let mut buf_writer = BufWriter::new(file);
+        self.serialize_with_size_report(&mut buf_writer)
+    }
+
+    /// Commodity method to serialize to a file with an
+    /// [embedded schema](Serialize::serialize_with_embedded_schema).
+    #[cfg(feature = "std")]
+    fn store_with_embedded_schema(&self, path: impl AsRef<Path>) -> Result<()> {
+        let file = std::fs::File::create(path).map_err(Error::FileOpenError)?;
+        let mut buf_writer = BufWriter::new(file);
+        self.serialize_with_embedded_schema(&mut buf_writer)?;
+        Ok(())
+    }
+
+    /// Commodity method to serialize to a file [with a checksum](Serialize::serialize_with_checksum).
+    #[cfg(feature = "std")]
+    fn store_with_checksum(&self, path: impl AsRef<Path>) -> Result<()> {
+        let file = std::fs::File::create(path).map_err(Error::FileOpenError)?;
+        let mut buf_writer = BufWriter::new(file);
+        self.serialize_with_checksum(&mut buf_writer)?;
+        Ok(())
+    }
+
+    /// Commodity method to serialize to a file [with chunk
+    /// checksums](Serialize::serialize_with_chunk_checksums).
+    #[cfg(feature = "std")]
+    fn store_with_chunk_checksums(&self, path: impl AsRef<Path>, chunk_size: usize) -> Result<()> {
+        let file = std::fs::File::create(path).map_err(Error::FileOpenError)?;
+        let mut buf_writer = BufWriter::new(file);
+        self.serialize_with_chunk_checksums(&mut buf_writer, chunk_size)?;
+        Ok(())
+    }
+
+    /// Commodity method to serialize to a file [with the signature of the
+    /// type](Serialize::serialize_with_signature).
+    #[cfg(feature = "std")]
+    fn store_with_signature(&self, path: impl AsRef<Path>) -> Result<()> {
+        let file = std::fs::File::create(path).map_err(Error::FileOpenError)?;
+        let mut buf_writer = BufWriter::new(file);
+        self.serialize_with_signature(&mut buf_writer)?;
+        Ok(())
+    }
+
+    /// Commodity method to serialize to a file writing large zero-copy
+    /// slices in parallel with a [`ParallelFileWriter`].
+    ///
+    /// The file is identical to the one written by [`Serialize::store`], but
+    /// the bytes of large vectors, boxed slices, and arrays of zero-copy
+    /// types are written concurrently by the rayon thread pool.
+    #[cfg(feature = "rayon")]
+    fn store_parallel(&self, path: impl AsRef<Path>) -> Result<()> {
+        let file = std::fs::File::create(path).map_err(Error::FileOpenError)?;
+        let mut buf_writer = BufWriter::new(ParallelFileWriter::new(file));
+        self.serialize(&mut buf_writer)?;
+        buf_writer.flush()?;
+        Ok(())
+    }
+
+    /// Commodity method to serialize to a file [compressing](Serialize::serialize_compressed)
+    /// the payload with the codec `C`.
+    #[cfg(feature = "std")]
+    fn store_compressed<C: crate::compress::Codec>(&self, path: impl AsRef<Path>) -> Result<()> {
+        let file = std::fs::File::create(path).map_err(Error::FileOpenError)?;
+        let mut buf_writer = BufWriter::new(file);
+        self.serialize_compressed::<C>(&mut buf_writer)?;
+        Ok(())
+    }
+
+    /// Commodity method to serialize to a file in [portable](Serialize::serialize_portable) format.
+    #[cfg(feature = "std")]
+    fn store_portable(&self, path: impl AsRef<Path>) -> Result<()> {
+        let file = std::fs::File::create(path).map_err(Error::FileOpenError)?;
+        let mut buf_writer = BufWriter::new(file);
+        self.serialize_portable(&mut buf_writer)?;
+        Ok(())
+    }
+
+    /// Commodity method to serialize to a file in [canonical order](Serialize::serialize_canonical).
+    #[cfg(feature = "std")]
+    fn store_canonical(&self, path: impl AsRef<Path>) -> Result<()> {
+        let file = std::fs::File::create(path).map_err(Error::FileOpenError)?;
+        let mut buf_writer = BufWriter::new(file);
+        self.serialize_canonical(&mut buf_writer)?;
+        Ok(())
+    }
+}
+
+/// Inner trait to implement serialization of a type. This trait exists
+/// to separate the user-facing [`Serialize`] trait from the low-level
+/// serialization mechanism of [`SerializeInner::_serialize_inner`]. Moreover,
+/// it makes it possible to behave slighly differently at the top
+/// of the recursion tree (e.g., to write the endianness marker), and to prevent
+/// the user from modifying the methods in [`Serialize`].
+///
+/// The user should not implement this trait directly, but rather derive it.
+/// Types that cannot be derived can be supported by hand using the stable
+/// extension API described in the [`crate::extending`] module.
+pub trait SerializeInner {
+    /// Inner constant used by the derive macros to keep
+    /// track recursively of whether the type
+    /// satisfies the conditions for being zero-copy. It is checked
+    /// at runtime against the trait implemented by the type, and
+    /// if a [`ZeroCopy`] type has this constant set to `false`
+    /// serialization will panic.
+    const IS_ZERO_COPY: bool;
+
+    /// Inner constant used by the derive macros to keep
+    /// track of whether all fields of a type are zero-copy
+    /// but neither the attribute `#[zero_copy]` nor the attribute `#[deep_copy]`
+    /// was specified. It is checked at runtime, and if it is true
+    /// a warning will be issued, as the type could be zero-copy,
+    /// which would be more efficient.
+    const ZERO_COPY_MISMATCH: bool;
+
+    /// The maximum alignment, in bytes, required by the zero-copy data
+    /// contained in the serialized form of this type, or 1 if there is no
+    /// such data.
+    ///
+    /// For zero-copy types, this is the value of [`MaxSizeOf::max_size_of`];
+    /// for deep-copy types, it is the maximum over the fields. A memory
+    /// region aligned to this value is sufficiently aligned for ε-copy
+    /// deserialization, so containers embedding serialized data (e.g.,
+    /// arenas or ring buffers) can use this constant to allocate memory
+    /// for a given type.
+    ///
+    /// The value is computed recursively by the derive macros.
+    const MAX_FIELD_ALIGN: usize;
+
+    /// Serialize this structure using the given backend.
+    fn _serialize_inner(&self, backend: &mut impl WriteWithNames) -> Result<()>;
+}
+
+/// Blanket implementation that prevents the user from overwriting the
+/// methods in [`Serialize`].
+///
+/// This implementation [writes a header](`write_header`) containing some hashes
+/// and debug information and then delegates to [WriteWithNames::write].
+impl<T: SerializeInner + TypeHash + ReprHash + 'static> Serialize for T {
+    /// Serialize the type using the given backend, followed by an embedded
+    /// schema making [structural compatibility checks](crate::compat) possible.
+    ///
+    /// The data can be deserialized as usual, as the schema is ignored
+    /// by the standard deserialization methods.
+    fn serialize_with_embedded_schema(&self, backend: &mut impl WriteNoStd) -> Result<usize> {
+        let mut writer_with_pos = WriterWithPos::new(backend);
+        let mut schema_writer = SchemaWriter::new(&mut writer_with_pos);
+        self.serialize_on_field_write(&mut schema_writer)?;
+        let schema = schema_writer.schema;
+        crate::compat::write_embedded_schema::<Self>(&mut writer_with_pos, &schema)?;
+        Ok(writer_with_pos.pos())
+    }
+
+    #[cfg(feature = "std")]
+    fn serialize_compressed<C: crate::compress::Codec>(
+        &self,
+        backend: &mut impl WriteNoStd,
+    ) -> Result<usize> {
+        let mut writer_with_pos = WriterWithPos::new(backend);
+        write_header_with_codec::<Self>(&mut writer_with_pos, Some(C::NAME))?;
+        writer_with_pos.write("ROOT", &crate::compress::Compressed::<C, Self>::new(self))?;
+        writer_with_pos.flush()?;
+        Ok(writer_with_pos.pos())
+    }
+
+    /// Serialize the type using the given [`WriteWithNames`].
+    fn serialize_on_field_write(&self, backend: &mut impl WriteWithNames) -> Result<()> {
+        write_header::<Self>(backend)?;
+        backend.write("ROOT", self)?;
+        backend.flush()
+    }
+}
+
+/// Write the header.
+///
+/// Must be kept in sync with [`crate::deser::check_header`].
+pub fn write_header<T: TypeHash + ReprHash + 'static>(
+    backend: &mut impl WriteWithNames,
+) -> Result<()> {
+    write_header_with_codec::<T>(backend, None)
+}
+
+/// Write the header, including the name of the codec compressing the
+/// payload, if any.
+pub(crate) fn write_header_with_codec<T: TypeHash + ReprHash + 'static>(
+    backend: &mut impl WriteWithNames,
+    codec: Option<&str>,
+) -> Result<()> {
+    let (type_hash, repr_hash) = type_hashes::<T>();
+    #[cfg(feature = "std")]
+    let type_signature = backend.has_signature().then(|| {
+        let mut hasher = crate::semver::SignatureHasher::default();
+        T::type_hash(&mut hasher);
+        hasher.into_tokens()
+    });
+    #[cfg(not(feature = "std"))]
+    let type_signature = None;
+    write_header_raw(
+        backend,
+        type_hash,
+        repr_hash,
+        core::any::type_name::<T>(),
+        type_signature,
+        codec,
+    )
+}
+
+/// Write the header given the type information of the serialized type.
+///
+/// This function does not depend on the serialized type, so it is
+/// instantiated once for each backend.
+fn write_header_raw(
+    backend: &mut impl WriteWithNames,
+    type_hash: u64,
+    repr_hash: u64,
+    type_name: &str,
+    type_signature: Option<Vec<String>>,
+    codec: Option<&str>,
+) -> Result<()> {
+    backend.write("MAGIC", &MAGIC)?;
+    backend.write("VERSION_MAJOR", &VERSION.0)?;
+    backend.write("VERSION_MINOR", &VERSION.1)?;
+    let mut flags = if backend.has_checksum() {
+        CHECKSUM_FLAG
+    } else {
+        0
+    };
+    if codec.is_some() {
+        flags |= COMPRESSED_FLAG;
+    }
+    if type_signature.is_some() {
+        flags |= SIGNATURE_FLAG;
+    }
+    if backend.has_chunk_checksums() {
+        flags |= CHUNK_CHECKSUMS_FLAG;
+    }
+    if backend.is_portable() {
+        backend.write("USIZE_SIZE", &(8 | PORTABLE_FLAG | flags))?;
+    } else {
+        backend.write("USIZE_SIZE", &(core::mem::size_of::<usize>() as u8 | flags))?;
+    }
+
+    backend.write("TYPE_HASH", &type_hash)?;
+    backend.write("REPR_HASH", &repr_hash)?;
+    backend.write("TYPE_NAME", &type_name.to_string())?;
+    if let Some(type_signature) = type_signature {
+        backend.write("TYPE_SIGNATURE", &type_signature)?;
+    }
+    if backend.has_checksum() {
+        // Patched by Serialize::serialize_with_checksum
+        backend.write("CHECKSUM", &0_u64)?;
+        backend.write("CHECKSUM_LEN", &0_u64)?;
+    }
+    if let Some(codec) = codec {
+        backend.write("CODEC", &codec.to_string())?;
+    }
+    Ok(())
+}
+
+/// A helper trait that makes it possible to implement differently
+/// serialization for [`crate::traits::ZeroCopy`] and [`crate::traits::DeepCopy`] types.
+/// See [`crate::traits::CopyType`] for more information.
+pub trait SerializeHelper<T: CopySelector> {
+    fn _serialize_inner(&self, backend: &mut impl WriteWithNames) -> Result<()>;
+}
+
+#[derive(Debug)]
+/// Errors that can happen during serialization.
+pub enum Error {
+    /// The underlying writer returned an error.
+    WriteError,
+    /// The slice passed to [`Serialize::serialize_into_slice`] is too
+    /// small; `required` is the number of bytes needed.
+    BufferTooSmall { required: usize },
+    /// The data of length `len` to compress is longer than
+    /// `max_len`, the maximum length accepted by the codec on decompression
+    /// (see `Codec::max_decompressed_len`).
+    CompressionRatio { len: usize, max_len: usize },
+    /// The chunk size of a [checksum tree](crate::merkle) is zero.
+    ZeroChunkSize,
+    /// [`Serialize::store`] could not open the provided file.
+    #[cfg(feature = "std")]
+    FileOpenError(std::io::Error),
+    /// A file operation other than writing (e.g., syncing or renaming a
+    /// file) failed.
+    #[cfg(feature = "std")]
+    IoError(std::io::Error),
+}
+
+impl core::error::Error for Error {}
+
+impl core::fmt::Display for Error {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        match self {
+            Self::WriteError => write!(f, "Write error during ε-serde serialization"),
+            Self::BufferTooSmall { required } => write!(
+                f,
+                "The buffer is too small for ε-serde serialization: {} bytes are required",
+                required
+            ),
+            Self::CompressionRatio { len, max_len } => write!(
+                f,
+                "The compressed data has length {} when decompressed, but the codec accepts at most {} bytes",
+                len, max_len
+            ),
+            Self::ZeroChunkSize => write!(f, "The chunk size of a checksum tree must be positive"),
+            #[cfg(feature = "std")]
+            Self::FileOpenError(error) => {
+                write!(
+                    f,
+                    "Error opening file during ε-serde serialization: {}",
+                    error
+                )
+            }
+            #[cfg(feature = "std")]
+            Self::IoError(error) => {
+                write!(f, "I/O error during ε-serde serialization: {}", error)
+            }
+        }
+    }
+}
@@ -0,0 +1,140 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ * SPDX-FileCopyrightText: 2023 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+/*!
+
+Parallel writing of large buffers to files.
+
+*/
+
+use rayon::prelude::*;
+use std::fs::File;
+use std::io::Write;
+
+/// A [`Write`] implementation on a [`File`] that writes large buffers in
+/// parallel using positioned writes.
+///
+/// The bytes of zero-copy slices are passed to the backend with a single
+/// call, so when serializing large vectors of zero-copy types most of the
+/// time is spent copying a single buffer to the file. Buffers of at least
+/// [`ParallelFileWriter::threshold`] bytes are split into chunks of
+/// [`ParallelFileWriter::chunk_size`] bytes that are written concurrently
+/// by the [rayon](https://crates.io/crates/rayon) thread pool; smaller
+/// buffers are written sequentially.
+///
+/// This writer does not buffer small writes, so it should be wrapped in a
+/// [`BufWriter`](std::io::BufWriter), which passes large buffers directly
+/// to the underlying writer (this is what
+/// [`Serialize::store_parallel`](crate::ser::Serialize::store_parallel)
+/// does).
+pub struct ParallelFileWriter {
+    file: File,
+    pos: u64,
+    threshold: usize,
+    chunk_size: usize,
+}
+
+impl ParallelFileWriter {
+    /// The default minimum size of a buffer written in parallel.
+    pub const DEFAULT_THRESHOLD: usize = 16 << 20;
+    /// The default size of the chunks written in parallel.
+    pub const DEFAULT_CHUNK_SIZE: usize = 4 << 20;
+
+    /// Create a new parallel writer starting at the beginning of the file.
+    pub fn new(file: File) -> Self {
+        Self {
+            file,
+            pos: 0,
+            threshold: Self::DEFAULT_THRESHOLD,
+            chunk_size: Self::DEFAULT_CHUNK_SIZE,
+        }
+    }
+
+    /// Set the minimum size of a buffer written in parallel.
+    pub fn with_threshold(mut self, threshold: usize) -> Self {
+        self.threshold = threshold;
+        self
+    }
+
+    /// Set the size of the chunks written in parallel.
+    ///
+    /// # Panics
+    ///
+    /// If `chunk_size` is zero.
+    pub fn with_chunk_size(mut self, chunk_size: usize) -> Self {
+        assert!(chunk_size > 0, "The chunk size must be positive");
+        self.chunk_size = chunk_size;
+        self
+    }
+
+    /// Return the minimum size of a buffer written in parallel.
+    pub fn threshold(&self) -> usize {
+        self.threshold
+    }
+
+    /// Return the size of the chunks written in parallel.
+    pub fn chunk_size(&self) -> usize {
+        self.chunk_size
+    }
+
+    /// Return the underlying file.
+    pub fn into_inner(self) -> File {
+        self.file
+    }
+}
+
+/// Write the whole buffer at the given position, without changing the
+/// position of the file.
+#[cfg(unix)]
+fn write_all_at(file: &File, buf: &[u8], pos: u64) -> std::io::Result<()> {
+    std::os::unix::fs::FileExt::write_all_at(file, buf, pos)
+}
+
+/// Write the whole buffer at the given position.
+#[cfg(windows)]
+fn write_all_at(file: &File, mut buf: &[u8], mut pos: u64) -> std::io::Result<()> {
+    while !buf.is_empty() {
+        match std::os::windows::fs::FileExt::seek_write(file, buf, pos) {
+            Ok(0) => return Err(std::io::ErrorKind::WriteZero.into()),
+            Ok(n) => {
+                buf = &buf[n..];
+                pos += n as u64;
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(())
+}
+
+impl Write for ParallelFileWriter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.write_all(buf)?;
+        Ok(buf.len())
+    }
+
+    fn write_all(&mut self, buf: &[u8]) -> std::io::Result<()> {
+        if buf.len() >= self.threshold {
+            let file = &self.file;
+            let pos = self.pos;
+            let chunk_size = self.chunk_size;
+            buf.par_chunks(chunk_size)
+                .enumerate()
+                .try_for_each(|(i, chunk)| {
+                    write_all_at(file, chunk, pos + (i * chunk_size) as u64)
+                })?;
+        } else {
+            write_all_at(&self.file, buf, self.pos)?;
+        }
+        self.pos += buf.len() as u64;
+        Ok(())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.file.flush()
+    }
+}
@@ -0,0 +1,469 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ * SPDX-FileCopyrightText: 2023 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+/*!
+
+No-std support for writing while keeping track of the current position.
+
+ */
+
+use crate::prelude::*;
+
+#[cfg(all(feature = "alloc", not(feature = "std")))]
+use alloc::vec::Vec;
+
+/// [`std::io::Write`]-like trait for serialization that does not
+/// depend on [`std`].
+///
+/// In an [`std`] context, the user does not need to use directly
+/// this trait as we provide a blanket
+/// implementation that implements [`WriteNoStd`] for all types that implement
+/// [`std::io::Write`]. In particular, in such a context you can use [`std::io::Cursor`]
+/// for in-memory serialization.
+pub trait WriteNoStd {
+    /// Write some bytes.
+    fn write_all(&mut self, buf: &[u8]) -> ser::Result<()>;
+
+    /// Flush all changes to the underlying storage if applicable.
+    fn flush(&mut self) -> ser::Result<()>;
+}
+
+#[cfg(feature = "std")]
+use std::io::Write;
+
+#[cfg(feature = "std")]
+impl<W: Write> WriteNoStd for W {
+    #[inline(always)]
+    fn write_all(&mut self, buf: &[u8]) -> ser::Result<()> {
+        Write::write_all(self, buf).map_err(|_| ser::Error::WriteError)
+    }
+    #[inline(always)]
+    fn flush(&mut self) -> ser::Result<()> {
+        Write::flush(self).map_err(|_| ser::Error::WriteError)
+    }
+}
+
+/// A [`WriteNoStd`] writing on a caller-provided slice of bytes.
+///
+/// Once the slice is full, further bytes are discarded, but they are still
+/// counted, so that at the end of serialization
+/// [`required_len`](SliceWriter::required_len) is the number of bytes
+/// that would have been necessary. Usually, this writer is used through
+/// [`Serialize::serialize_into_slice`].
+///
+/// In an [`std`] context, [`std::io::Cursor<&mut [u8]>`](std::io::Cursor)
+/// can be used, too, but it just returns an error when the slice is full.
+#[derive(Debug)]
+pub struct SliceWriter<'a> {
+    buf: &'a mut [u8],
+    /// The number of bytes written, including those that did not fit.
+    len: usize,
+}
+
+impl<'a> SliceWriter<'a> {
+    /// Create a new writer on the given slice.
+    pub fn new(buf: &'a mut [u8]) -> Self {
+        Self { buf, len: 0 }
+    }
+
+    /// Return the number of bytes written so far, including those that
+    /// did not fit into the slice.
+    pub fn required_len(&self) -> usize {
+        self.len
+    }
+
+    /// Return whether all bytes written so far fit into the slice.
+    pub fn fits(&self) -> bool {
+        self.len <= self.buf.len()
+    }
+}
+
+impl WriteNoStd for SliceWriter<'_> {
+    #[inline(always)]
+    fn write_all(&mut self, buf: &[u8]) -> ser::Result<()> {
+        if let Some(dst) = self.buf.get_mut(self.len..self.len + buf.len()) {
+            dst.copy_from_slice(buf);
+        }
+        self.len += buf.len();
+        Ok(())
+    }
+
+    #[inline(always)]
+    fn flush(&mut self) -> ser::Result<()> {
+        Ok(())
+    }
+}
+
+/// A [`WriteNoStd`] discarding bytes and counting them.
+///
+/// Usually, this writer is used through [`Serialize::serialized_size`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CountingWriter {
+    len: usize,
+}
+
+impl CountingWriter {
+    /// Create a new writer.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Return the number of bytes written so far.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Return whether no bytes have been written so far.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+}
+
+impl WriteNoStd for CountingWriter {
+    #[inline(always)]
+    fn write_all(&mut self, buf: &[u8]) -> ser::Result<()> {
+        self.len += buf.len();
+        Ok(())
+    }
+
+    #[inline(always)]
+    fn flush(&mut self) -> ser::Result<()> {
+        Ok(())
+    }
+}
+
+/// The default capacity of a [`BufferedWriter`], which is also the size of
+/// the buffer used by [`Serialize::store`].
+pub const DEFAULT_BUFFER_SIZE: usize = 8 * 1024;
+
+/// A buffering [`WriteNoStd`] with configurable capacity and reusable buffer.
+///
+/// Writes are accumulated in a buffer of fixed capacity, which is written to
+/// the underlying [`WriteNoStd`] when full; writes larger than the capacity
+/// (e.g., the content of large zero-copy vectors) bypass the buffer. A
+/// small capacity avoids allocating large buffers for tiny files, whereas a
+/// large capacity reduces the number of system calls for huge files.
+///
+/// Differently from [`std::io::BufWriter`], the buffer can be moved to a new
+/// underlying writer with [`reset`](BufferedWriter::reset), so that
+/// many small serializations can share the same allocation:
+///
+/// ```rust
+/// use epserde::prelude::*;
+/// use epserde::ser::BufferedWriter;
+///
+/// let mut writer = BufferedWriter::with_capacity(1024, Vec::new());
+/// vec![0_u64, 1, 2].serialize(&mut writer)?;
+/// let first = writer.reset(Vec::new())?;
+/// vec![3_u64, 4, 5].serialize(&mut writer)?;
+/// let second = writer.into_inner()?;
+/// assert_eq!(first.len(), second.len());
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+///
+/// The buffer is emptied by [`flush`](WriteNoStd::flush), which all
+/// serialization methods call at the end. Data still in the buffer when the
+/// writer is dropped is discarded.
+#[derive(Debug)]
+pub struct BufferedWriter<W: WriteNoStd> {
+    inner: W,
+    buf: Vec<u8>,
+}
+
+impl<W: WriteNoStd> BufferedWriter<W> {
+    /// Create a new writer with capacity [`DEFAULT_BUFFER_SIZE`].
+    pub fn new(inner: W) -> Self {
+        Self::with_capacity(DEFAULT_BUFFER_SIZE, inner)
+    }
+
+    /// Create a new writer with the given capacity.
+    ///
+    /// If the capacity is zero, all writes bypass the buffer.
+    pub fn with_capacity(capacity: usize, inner: W) -> Self {
+        Self {
+            inner,
+            buf: Vec::with_capacity(capacity),
+        }
+    }
+
+    /// Return the capacity of the buffer.
+    pub fn capacity(&self) -> usize {
+        self.buf.capacity()
+    }
+
+    /// Return a reference to the underlying writer.
+    pub fn get_ref(&self) -> &W {
+        &self.inner
+    }
+
+    /// Return a mutable reference to the underlying writer.
+    ///
+    /// Writing directly to the underlying writer is likely to
+    /// interleave data with the content of the buffer.
+    pub fn get_mut(&mut self) -> &mut W {
+        &mut self.inner
+    }
+
+    /// Flush the buffer and replace the underlying writer with `inner`,
+    /// returning the previous one.
+    ///
+    /// The buffer is kept, so no allocation takes place. If flushing fails,
+    /// the error is returned and the underlying writer is not replaced.
+    pub fn reset(&mut self, inner: W) -> ser::Result<W> {
+        WriteNoStd::flush(self)?;
+        Ok(core::mem::replace(&mut self.inner, inner))
+    }
+
+    /// Flush the buffer and return the underlying writer.
+    pub fn into_inner(mut self) -> ser::Result<W> {
+        WriteNoStd::flush(&mut self)?;
+        Ok(self.inner)
+    }
+
+    /// Write the content of the buffer to the underlying writer.
+    fn flush_buf(&mut self) -> ser::Result<()> {
+        if !self.buf.is_empty() {
+            self.inner.write_all(&self.buf)?;
+            self.buf.clear();
+        }
+        Ok(())
+    }
+}
+
+impl<W: WriteNoStd> WriteNoStd for BufferedWriter<W> {
+    #[inline(always)]
+    fn write_all(&mut self, buf: &[u8]) -> ser::Result<()> {
+        if self.buf.len() + buf.len() > self.buf.capacity() {
+            self.flush_buf()?;
+        }
+        if buf.len() >= self.buf.capacity() {
+            self.inner.write_all(buf)
+        } else {
+            self.buf.extend_from_slice(buf);
+            Ok(())
+        }
+    }
+
+    fn flush(&mut self) -> ser::Result<()> {
+        self.flush_buf()?;
+        self.inner.flush()
+    }
+}
+
+/// A trait for [`WriteNoStd`] that also keeps track of the current position.
+///
+/// This is needed because the [`Write`] trait doesn't have a `seek` method and
+/// [`std::io::Seek`] would be a requirement much stronger than needed.
+pub trait WriteWithPos: WriteNoStd {
+    fn pos(&self) -> usize;
+
+    /// Return whether we are writing [portable](Serialize::serialize_portable) data,
+    /// in which `usize` and `isize` values are always written using eight bytes.
+    ///
+    /// The default implementation returns false.
+    fn is_portable(&self) -> bool {
+        false
+    }
+
+    /// Return whether the header must contain space for a
+    /// [checksum](Serialize::serialize_with_checksum) of the payload.
+    ///
+    /// The default implementation returns false.
+    fn has_checksum(&self) -> bool {
+        false
+    }
+
+    /// Return whether the header must contain the
+    /// [signature](Serialize::serialize_with_signature) of the serialized type.
+    ///
+    /// The default implementation returns false.
+    fn has_signature(&self) -> bool {
+        false
+    }
+
+    /// Return whether the data will be followed by a
+    /// [checksum tree](Serialize::serialize_with_chunk_checksums), which
+    /// is recorded in the header.
+    ///
+    /// The default implementation returns false.
+    fn has_chunk_checksums(&self) -> bool {
+        false
+    }
+
+    /// Return whether containers whose iteration order is not determined by
+    /// their content, such as hash-based maps and sets, must be written in
+    /// [canonical order](Serialize::serialize_canonical).
+    ///
+    /// The default implementation returns false.
+    fn is_canonical(&self) -> bool {
+        false
+    }
+}
+
+/// A wrapper for a [`WriteNoStd`] that implements [`WriteWithPos`]
+/// by keeping track of the current position.
+pub struct WriterWithPos<'a, F: WriteNoStd> {
+    /// What we actually write on.
+    backend: &'a mut F,
+    /// How many bytes we have written from the start.
+    pos: usize,
+    /// Whether we are writing portable data.
+    portable: bool,
+    /// Whether the header contains a checksum.
+    checksum: bool,
+    /// Whether the header contains the signature of the serialized type.
+    signature: bool,
+    /// Whether the data is followed by a checksum tree.
+    chunk_checksums: bool,
+    /// Whether unordered containers are written in canonical order.
+    canonical: bool,
+}
+
+impl<'a, F: WriteNoStd> WriterWithPos<'a, F> {
+    #[inline(always)]
+    /// Create a new [`WriterWithPos`] on top of a generic [`WriteNoStd`] `F`.
+    pub fn new(backend: &'a mut F) -> Self {
+        Self {
+            backend,
+            pos: 0,
+            portable: false,
+            checksum: false,
+            signature: false,
+            chunk_checksums: false,
+            canonical: false,
+        }
+    }
+
+    #[inline(always)]
+    /// Create a new [`WriterWithPos`] on top of a generic [`WriteNoStd`] `F`
+    /// writing [portable](Serialize::serialize_portable) data.
+    pub fn new_portable(backend: &'a mut F) -> Self {
+        Self {
+            backend,
+            pos: 0,
+            portable: true,
+            checksum: false,
+            signature: false,
+            chunk_checksums: false,
+            canonical: false,
+        }
+    }
+
+    #[inline(always)]
+    /// Create a new [`WriterWithPos`] on top of a generic [`WriteNoStd`] `F`
+    /// writing a header with space for a [checksum](Serialize::serialize_with_checksum).
+    ///
+    /// The checksum is written as zero, and it must be patched
+    /// after serialization.
+    pub fn new_with_checksum(backend: &'a mut F) -> Self {
+        Self {
+            backend,
+            pos: 0,
+            portable: false,
+            checksum: true,
+            signature: false,
+            chunk_checksums: false,
+            canonical: false,
+        }
+    }
+
+    #[inline(always)]
+    /// Create a new [`WriterWithPos`] on top of a generic [`WriteNoStd`] `F`
+    /// writing a header containing the [signature](Serialize::serialize_with_signature)
+    /// of the serialized type.
+    pub fn new_with_signature(backend: &'a mut F) -> Self {
+        Self {
+            backend,
+            pos: 0,
+            portable: false,
+            checksum: false,
+            signature: true,
+            chunk_checksums: false,
+            canonical: false,
+        }
+    }
+
+    #[inline(always)]
+    /// Create a new [`WriterWithPos`] on top of a generic [`WriteNoStd`] `F`
+    /// writing a header stating that the data is followed by a
+    /// [checksum tree](Serialize::serialize_with_chunk_checksums).
+    ///
+    /// The tree must be written by a
+    /// [`ChunkHashWriter`](crate::merkle::ChunkHashWriter) wrapping `F`.
+    pub fn new_with_chunk_checksums(backend: &'a mut F) -> Self {
+        Self {
+            backend,
+            pos: 0,
+            portable: false,
+            checksum: false,
+            signature: false,
+            chunk_checksums: true,
+            canonical: false,
+        }
+    }
+
+    #[inline(always)]
+    /// Create a new [`WriterWithPos`] on top of a generic [`WriteNoStd`] `F`
+    /// writing unordered containers in [canonical order](Serialize::serialize_canonical).
+    pub fn new_canonical(backend: &'a mut F) -> Self {
+        Self {
+            backend,
+            pos: 0,
+            portable: false,
+            checksum: false,
+            signature: false,
+            chunk_checksums: false,
+            canonical: true,
+        }
+    }
+}
+
+impl<'a, F: WriteNoStd> WriteNoStd for WriterWithPos<'a, F> {
+    #[inline(always)]
+    fn write_all(&mut self, buf: &[u8]) -> ser::Result<()> {
+        self.backend.write_all(buf)?;
+        self.pos += buf.len();
+        Ok(())
+    }
+
+    #[inline(always)]
+    fn flush(&mut self) -> ser::Result<()> {
+        self.backend.flush()
+    }
+}
+
+impl<'a, F: WriteNoStd> WriteWithPos for WriterWithPos<'a, F> {
+    #[inline(always)]
+    fn pos(&self) -> usize {
+        self.pos
+    }
+
+    #[inline(always)]
+    fn is_portable(&self) -> bool {
+        self.portable
+    }
+
+    #[inline(always)]
+    fn has_checksum(&self) -> bool {
+        self.checksum
+    }
+
+    #[inline(always)]
+    fn has_signature(&self) -> bool {
+        self.signature
+    }
+
+    #[inline(always)]
+    fn has_chunk_checksums(&self) -> bool {
+        self.chunk_checksums
+    }
+
+    #[inline(always)]
+    fn is_canonical(&self) -> bool {
+        self.canonical
+    }
+}
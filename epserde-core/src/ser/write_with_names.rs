@@ -0,0 +1,736 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ * SPDX-FileCopyrightText: 2023 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+/*!
+
+Traits and implementations to write named field during serialization.
+
+[`SerializeInner::_serialize_inner`] writes on a [`WriteWithNames`], rather
+than on a [`WriteWithPos`], with the purpose of easily recording write
+events happening during a serialization.
+
+*/
+
+use super::*;
+#[cfg(all(feature = "alloc", not(feature = "std")))]
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
+
+/// Trait extending [`WriteWithPos`] with methods providing
+/// alignment, serialization of named data, and writing of byte slices
+/// of zero-copy types.
+///
+/// The purpose of this trait is that of interposing between [`SerializeInner`]
+/// and the underlying [`WriteWithPos`] a layer in which serialization operations
+/// can be easily intercepted and recorded. In particular, serialization methods
+/// must use the methods of this trait if they want to record the schema of the
+/// serialized data; this is true (maybe counterintuitively) even of ancillary
+/// data such as tags and slice lengths: see [`helpers`] or the
+/// [implementation of `Option`](impls::prim) for examples.
+/// All methods have a default
+/// implementation that must be replicated in other implementations.
+///
+/// There are two implementations of [`WriteWithNames`]: [`WriterWithPos`],
+/// which uses the default implementation, and [`SchemaWriter`],
+/// which additionally records a [`Schema`] of the serialized data.
+pub trait WriteWithNames: WriteWithPos + Sized {
+    /// Add some zero padding so that `self.pos() % V:max_size_of() == 0.`
+    ///
+    /// Other implementations must write the same number of zeros.
+    fn align<V: MaxSizeOf>(&mut self) -> Result<()> {
+        write_zeros(self, pad_align_to(self.pos(), V::max_size_of()))
+    }
+
+    /// Write a value with an associated name.
+    ///
+    /// The default implementation simply delegates to [`SerializeInner::_serialize_inner`].
+    /// Other implementations might use the name information (e.g., [`SchemaWriter`]),
+    /// but they must in the end delegate to [`SerializeInner::_serialize_inner`].
+    fn write<V: SerializeInner>(&mut self, _field_name: &str, value: &V) -> Result<()> {
+        value._serialize_inner(self)
+    }
+
+    /// Write the memory representation of a (slice of a) zero-copy type.
+    ///
+    /// The default implementation simply delegates to [`WriteNoStd::write_all`].
+    /// Other implementations might use the type information in `V` (e.g., [`SchemaWriter`]),
+    /// but they must in the end delegate to [`WriteNoStd::write_all`].
+    fn write_bytes<V: SerializeInner + ZeroCopy>(&mut self, value: &[u8]) -> Result<()> {
+        self.write_all(value)
+    }
+
+    /// Announce that the memory representation of `size` bytes of a slice
+    /// of a zero-copy type will be written next using [`WriteNoStd::write_all`],
+    /// possibly with multiple calls.
+    ///
+    /// This method makes it possible to write slices that are not available
+    /// in memory as a whole (see [`serialize_iter_zero`]). The default
+    /// implementation does nothing. Other implementations might use the type
+    /// information in `V` (e.g., [`SchemaWriter`]), and must record the same
+    /// information as [`WriteWithNames::write_bytes`].
+    fn begin_bytes<V: SerializeInner + ZeroCopy>(&mut self, _size: usize) -> Result<()> {
+        Ok(())
+    }
+}
+
+impl<F: WriteNoStd> WriteWithNames for WriterWithPos<'_, F> {}
+
+/// Write `len` zeros using as few calls to [`WriteNoStd::write_all`] as possible.
+fn write_zeros(backend: &mut impl WriteNoStd, mut len: usize) -> Result<()> {
+    const ZEROS: [u8; 64] = [0; 64];
+    while len > 0 {
+        let chunk = len.min(ZEROS.len());
+        backend.write_all(&ZEROS[..chunk])?;
+        len -= chunk;
+    }
+    Ok(())
+}
+
+/// Information about data written during serialization, either fields or
+/// ancillary data such as option tags and slice lengths.
+#[derive(Debug, Clone)]
+pub struct SchemaRow {
+    /// Name of the piece of data.
+    pub field: String,
+    /// Type of the piece of data.
+    pub ty: String,
+    /// Offset from the start of the file.
+    pub offset: usize,
+    /// Length in bytes of the piece of data.
+    pub size: usize,
+    /// The alignment needed by the piece of data, zero if not applicable
+    /// (e.g., primitive fields, ancillary data, or structures).
+    pub align: usize,
+}
+
+#[derive(Default, Debug, Clone)]
+/// A vector containing all the fields written during serialization, including
+/// ancillary data such as slice lengths and [`Option`] tags.
+pub struct Schema(pub Vec<SchemaRow>);
+
+impl Schema {
+    /// Return a CSV representation of the schema, including data.
+    ///
+    /// WARNING: the size of the CSV will be larger than the size of the
+    /// serialized file, so it is not a good idea to call this method
+    /// on big structures.
+    pub fn debug(&self, data: &[u8]) -> String {
+        let mut result = "field,offset,align,size,ty,bytes\n".to_string();
+        for i in 0..self.0.len().saturating_sub(1) {
+            let row = &self.0[i];
+            // if it's a composed type, don't print the bytes
+            if row.offset == self.0[i + 1].offset {
+                result.push_str(&format!(
+                    "{},{},{},{},{},\n",
+                    row.field, row.offset, row.align, row.size, row.ty,
+                ));
+            } else {
+                result.push_str(&format!(
+                    "{},{},{},{},{},{:02x?}\n",
+                    row.field,
+                    row.offset,
+                    row.align,
+                    row.size,
+                    row.ty,
+                    &data[row.offset..row.offset + row.size],
+                ));
+            }
+        }
+
+        // the last field can't be a composed type by definition
+        if let Some(row) = self.0.last() {
+            result.push_str(&format!(
+                "{},{},{},{},{},{:02x?}\n",
+                row.field,
+                row.offset,
+                row.align,
+                row.size,
+                row.ty,
+                &data[row.offset..row.offset + row.size],
+            ));
+        }
+
+        result
+    }
+
+    /// Return a CSV representation of the schema, excluding data.
+    pub fn to_csv(&self) -> String {
+        let mut result = "field,offset,align,size,ty\n".to_string();
+        for row in &self.0 {
+            result.push_str(&format!(
+                "{},{},{},{},{}\n",
+                row.field, row.offset, row.align, row.size, row.ty
+            ));
+        }
+        result
+    }
+
+    /// Return a JSON representation of the schema, excluding data.
+    ///
+    /// The representation is an object containing the
+    /// [version](crate::VERSION) of the file format as a pair of integers and
+    /// an array of rows, each represented by an object with keys `field`,
+    /// `offset`, `align`, `size`, and `ty`.
+    pub fn to_json(&self) -> String {
+        let mut result = format!(
+            "{{\n  \"version\": [{}, {}],\n  \"rows\": [",
+            crate::VERSION.0,
+            crate::VERSION.1
+        );
+        for (i, row) in self.0.iter().enumerate() {
+            result.push_str(&format!(
+                "{}\n    {{\"field\": {}, \"offset\": {}, \"align\": {}, \"size\": {}, \"ty\": {}}}",
+                if i == 0 { "" } else { "," },
+                json_string(&row.field),
+                row.offset,
+                row.align,
+                row.size,
+                json_string(&row.ty)
+            ));
+        }
+        if !self.0.is_empty() {
+            result.push_str("\n  ");
+        }
+        result.push_str("]\n}\n");
+        result
+    }
+
+    /// Parse the JSON representation of a schema returned by [`Schema::to_json`].
+    ///
+    /// Keys other than those written by [`Schema::to_json`] are ignored. An
+    /// error is returned if the JSON is malformed, if some key is missing, or
+    /// if the major version of the file format is not the current one.
+    pub fn from_json(json: &str) -> crate::deser::Result<Self> {
+        use crate::deser::Error;
+        let mut parser = JsonParser(json.as_bytes());
+        let value = parser.value()?;
+        parser.skip_whitespace();
+        if !parser.0.is_empty() {
+            return Err(Error::ReadError);
+        }
+
+        let major = value.get("version")?.index(0)?.as_usize()?;
+        if major != crate::VERSION.0 as usize {
+            return Err(Error::MajorVersionMismatch(
+                major.try_into().unwrap_or(u16::MAX),
+            ));
+        }
+        let Json::Array(rows) = value.get("rows")? else {
+            return Err(Error::ReadError);
+        };
+        rows.iter()
+            .map(|row| {
+                Ok(SchemaRow {
+                    field: row.get("field")?.as_str()?.to_string(),
+                    ty: row.get("ty")?.as_str()?.to_string(),
+                    offset: row.get("offset")?.as_usize()?,
+                    size: row.get("size")?.as_usize()?,
+                    align: row.get("align")?.as_usize()?,
+                })
+            })
+            .collect::<crate::deser::Result<Vec<_>>>()
+            .map(Schema)
+    }
+
+    /// Return an iterator on the byte ranges of the pieces of data with the
+    /// given name (e.g., `ROOT.nodes.item.label`).
+    ///
+    /// The ranges are relative to the start of the file, so they can be
+    /// used to locate fields in a memory-mapped file (see, e.g.,
+    /// [`MemCase::release_fields`](crate::deser::MemCase::release_fields)).
+    pub fn ranges<'a>(
+        &'a self,
+        field: &'a str,
+    ) -> impl Iterator<Item = core::ops::Range<usize>> + 'a {
+        self.0
+            .iter()
+            .filter(move |row| row.field == field)
+            .map(|row| row.offset..row.offset + row.size)
+    }
+
+    /// Return the total size in bytes of the padding inserted to align
+    /// zero-copy data.
+    pub fn padding(&self) -> usize {
+        self.padding_rows().map(|row| row.size).sum()
+    }
+
+    /// Return the size in bytes of the padding inserted to align zero-copy
+    /// data within the pieces of data with the given name (e.g.,
+    /// `ROOT.nodes.item.label`), summed over all of them.
+    ///
+    /// Comparing the padding of the fields of a structure with their size makes
+    /// it possible to see how much space alignment costs, and possibly to
+    /// reorder the fields accordingly.
+    pub fn padding_of(&self, field: &str) -> usize {
+        let padding = PaddingIndex::new(self);
+        self.0
+            .iter()
+            .filter(|row| row.field == field)
+            .map(|row| padding.within(row))
+            .sum()
+    }
+
+    /// Return an iterator on the rows describing padding.
+    fn padding_rows(&self) -> impl Iterator<Item = &SchemaRow> {
+        self.0.iter().filter(|row| row.field == "PADDING")
+    }
+
+    /// Return a [report](SizeReport) of the sizes of the direct
+    /// children of the root (e.g., the fields of a root structure).
+    pub fn size_report(&self) -> SizeReport {
+        let Some(root) = self.0.iter().find(|row| row.field == "ROOT") else {
+            return SizeReport::default();
+        };
+        let padding = PaddingIndex::new(self);
+        let (fields, fields_padding): (Vec<(String, usize)>, Vec<usize>) = self
+            .0
+            .iter()
+            .filter_map(|row| {
+                let name = row.field.strip_prefix("ROOT.")?;
+                (!name.contains('.')).then(|| ((name.to_string(), row.size), padding.within(row)))
+            })
+            .unzip();
+        let fields_size = fields.iter().map(|(_, size)| size).sum::<usize>();
+        SizeReport {
+            header: root.offset,
+            padding: root.size - fields_size,
+            total: root.offset + root.size,
+            total_padding: self.padding(),
+            fields,
+            fields_padding,
+        }
+    }
+}
+
+/// The padding rows of a [`Schema`] sorted by offset, with prefix sums of
+/// their sizes, making it possible to compute quickly the padding within
+/// a piece of data.
+struct PaddingIndex {
+    offsets: Vec<usize>,
+    /// `sums[i]` is the size of the first `i` padding rows.
+    sums: Vec<usize>,
+}
+
+impl PaddingIndex {
+    fn new(schema: &Schema) -> Self {
+        let mut rows = schema
+            .padding_rows()
+            .map(|row| (row.offset, row.size))
+            .collect::<Vec<_>>();
+        rows.sort_unstable();
+        let mut sums = Vec::with_capacity(rows.len() + 1);
+        sums.push(0);
+        for &(_, size) in &rows {
+            sums.push(sums[sums.len() - 1] + size);
+        }
+        Self {
+            offsets: rows.into_iter().map(|(offset, _)| offset).collect(),
+            sums,
+        }
+    }
+
+    /// Return the size of the padding within the bytes of `row`.
+    fn within(&self, row: &SchemaRow) -> usize {
+        let start = self.offsets.partition_point(|&offset| offset < row.offset);
+        let end = self
+            .offsets
+            .partition_point(|&offset| offset < row.offset + row.size);
+        self.sums[end] - self.sums[start]
+    }
+}
+
+/// A summary of the sizes of serialized data, aggregated by the
+/// direct children of the root (e.g., the fields of a root structure).
+///
+/// A report is returned by
+/// [`Serialize::serialize_with_size_report`] and
+/// [`Serialize::store_with_size_report`], or it can be computed from
+/// a [`Schema`] with [`Schema::size_report`]. Its [`Display`](core::fmt::Display)
+/// implementation prints a table of sizes and percentages, suitable for
+/// logging the composition of serialized data.
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
+pub struct SizeReport {
+    /// The size in bytes of the header, including padding before the root.
+    pub header: usize,
+    /// The names and sizes in bytes of the direct children of the root,
+    /// in serialization order. The size of a child includes padding
+    /// within the child.
+    pub fields: Vec<(String, usize)>,
+    /// The size in bytes of padding within each direct child of the root,
+    /// in the same order of [`fields`](SizeReport::fields).
+    pub fields_padding: Vec<usize>,
+    /// The size in bytes of padding between the children of the root.
+    pub padding: usize,
+    /// The total size in bytes of padding, both between and within
+    /// the children of the root.
+    pub total_padding: usize,
+    /// The total size in bytes.
+    pub total: usize,
+}
+
+impl core::fmt::Display for SizeReport {
+    /// Print a row for the header, for each child of the root, and for the
+    /// padding between children, followed by the total size. The rows of
+    /// children contain also the padding within the child, if any.
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        let rows = core::iter::once(("HEADER", self.header, 0))
+            .chain(
+                self.fields
+                    .iter()
+                    .zip(self.fields_padding.iter().chain(core::iter::repeat(&0)))
+                    .map(|((name, size), padding)| (name.as_str(), *size, *padding)),
+            )
+            .chain(core::iter::once(("PADDING", self.padding, 0)));
+        let width = self
+            .fields
+            .iter()
+            .map(|(name, _)| name.len())
+            .chain([7])
+            .max()
+            .unwrap_or(7);
+        for (name, size, padding) in rows {
+            write!(
+                f,
+                "{:<width$} {:>12} {:>6.2}%",
+                name,
+                size,
+                100.0 * size as f64 / self.total.max(1) as f64,
+            )?;
+            if padding != 0 {
+                write!(f, " (padding {})", padding)?;
+            }
+            writeln!(f)?;
+        }
+        write!(f, "{:<width$} {:>12}", "TOTAL", self.total)
+    }
+}
+
+/// The subset of JSON values needed by [`Schema::from_json`].
+enum Json {
+    Number(usize),
+    String(String),
+    Array(Vec<Json>),
+    Object(Vec<(String, Json)>),
+}
+
+impl Json {
+    fn get(&self, key: &str) -> crate::deser::Result<&Json> {
+        match self {
+            Json::Object(entries) => entries
+                .iter()
+                .find(|(k, _)| k == key)
+                .map(|(_, value)| value),
+            _ => None,
+        }
+        .ok_or(crate::deser::Error::ReadError)
+    }
+
+    fn index(&self, index: usize) -> crate::deser::Result<&Json> {
+        match self {
+            Json::Array(values) => values.get(index),
+            _ => None,
+        }
+        .ok_or(crate::deser::Error::ReadError)
+    }
+
+    fn as_usize(&self) -> crate::deser::Result<usize> {
+        match self {
+            Json::Number(n) => Ok(*n),
+            _ => Err(crate::deser::Error::ReadError),
+        }
+    }
+
+    fn as_str(&self) -> crate::deser::Result<&str> {
+        match self {
+            Json::String(s) => Ok(s),
+            _ => Err(crate::deser::Error::ReadError),
+        }
+    }
+}
+
+/// A recursive-descent parser for [`Json`] values on the remaining input.
+struct JsonParser<'a>(&'a [u8]);
+
+impl JsonParser<'_> {
+    fn skip_whitespace(&mut self) {
+        while let [b' ' | b'\t' | b'\n' | b'\r', rest @ ..] = self.0 {
+            self.0 = rest;
+        }
+    }
+
+    /// Skip whitespace and consume the byte `expected`, if present.
+    fn eat(&mut self, expected: u8) -> bool {
+        self.skip_whitespace();
+        match self.0 {
+            [b, rest @ ..] if *b == expected => {
+                self.0 = rest;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn expect(&mut self, expected: u8) -> crate::deser::Result<()> {
+        if self.eat(expected) {
+            Ok(())
+        } else {
+            Err(crate::deser::Error::ReadError)
+        }
+    }
+
+    /// Parse a comma-separated sequence of items up to `close`.
+    fn sequence<T>(
+        &mut self,
+        close: u8,
+        mut item: impl FnMut(&mut Self) -> crate::deser::Result<T>,
+    ) -> crate::deser::Result<Vec<T>> {
+        let mut items = Vec::new();
+        if self.eat(close) {
+            return Ok(items);
+        }
+        loop {
+            items.push(item(self)?);
+            if self.eat(close) {
+                return Ok(items);
+            }
+            self.expect(b',')?;
+        }
+    }
+
+    fn value(&mut self) -> crate::deser::Result<Json> {
+        self.skip_whitespace();
+        let data = self.0;
+        match data {
+            [b'{', rest @ ..] => {
+                self.0 = rest;
+                self.sequence(b'}', |parser| {
+                    parser.skip_whitespace();
+                    let key = parser.string()?;
+                    parser.expect(b':')?;
+                    Ok((key, parser.value()?))
+                })
+                .map(Json::Object)
+            }
+            [b'[', rest @ ..] => {
+                self.0 = rest;
+                self.sequence(b']', Self::value).map(Json::Array)
+            }
+            [b'"', ..] => self.string().map(Json::String),
+            [b'0'..=b'9', ..] => {
+                let len = self.0.iter().take_while(|b| b.is_ascii_digit()).count();
+                let (digits, rest) = self.0.split_at(len);
+                self.0 = rest;
+                core::str::from_utf8(digits)
+                    .ok()
+                    .and_then(|digits| digits.parse().ok())
+                    .map(Json::Number)
+                    .ok_or(crate::deser::Error::ReadError)
+            }
+            _ => Err(crate::deser::Error::ReadError),
+        }
+    }
+
+    fn string(&mut self) -> crate::deser::Result<String> {
+        let [b'"', rest @ ..] = self.0 else {
+            return Err(crate::deser::Error::ReadError);
+        };
+        // Find the closing quote, skipping escaped characters
+        let mut end = 0;
+        loop {
+            match rest.get(end) {
+                Some(b'"') => break,
+                Some(b'\\') => end += 2,
+                Some(_) => end += 1,
+                None => return Err(crate::deser::Error::ReadError),
+            }
+        }
+        let s = rest
+            .get(..end)
+            .and_then(|s| core::str::from_utf8(s).ok())
+            .ok_or(crate::deser::Error::ReadError)?;
+        self.0 = rest.get(end + 1..).ok_or(crate::deser::Error::ReadError)?;
+
+        let mut result = String::with_capacity(s.len());
+        let mut chars = s.chars();
+        while let Some(c) = chars.next() {
+            if c != '\\' {
+                result.push(c);
+                continue;
+            }
+            match chars.next() {
+                Some('"') => result.push('"'),
+                Some('\\') => result.push('\\'),
+                Some('/') => result.push('/'),
+                Some('n') => result.push('\n'),
+                Some('r') => result.push('\r'),
+                Some('t') => result.push('\t'),
+                Some('b') => result.push('\u{8}'),
+                Some('f') => result.push('\u{c}'),
+                Some('u') => {
+                    let hex = chars.by_ref().take(4).collect::<String>();
+                    let c = u32::from_str_radix(&hex, 16)
+                        .ok()
+                        .and_then(char::from_u32)
+                        .ok_or(crate::deser::Error::ReadError)?;
+                    result.push(c);
+                }
+                _ => return Err(crate::deser::Error::ReadError),
+            }
+        }
+        Ok(result)
+    }
+}
+
+/// Return a quoted and escaped JSON string.
+pub(crate) fn json_string(s: &str) -> String {
+    let mut result = String::with_capacity(s.len() + 2);
+    result.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => result.push_str("\\\""),
+            '\\' => result.push_str("\\\\"),
+            '\n' => result.push_str("\\n"),
+            '\r' => result.push_str("\\r"),
+            '\t' => result.push_str("\\t"),
+            c if (c as u32) < 0x20 => result.push_str(&format!("\\u{:04x}", c as u32)),
+            c => result.push(c),
+        }
+    }
+    result.push('"');
+    result
+}
+
+/// A [`WriteWithNames`] that keeps track of the data written on an underlying
+/// [`WriteWithPos`] in a [`Schema`].
+pub struct SchemaWriter<'a, W> {
+    /// The schema so far.
+    pub schema: Schema,
+    /// A recursively-built sequence of previous names.
+    path: Vec<String>,
+    /// What we actually write on.
+    writer: &'a mut W,
+}
+
+impl<'a, W: WriteWithPos> SchemaWriter<'a, W> {
+    #[inline(always)]
+    /// Create a new empty [`SchemaWriter`] on top of a generic writer `W`.
+    pub fn new(backend: &'a mut W) -> Self {
+        Self {
+            schema: Default::default(),
+            path: vec![],
+            writer: backend,
+        }
+    }
+}
+impl<W: WriteNoStd> WriteNoStd for SchemaWriter<'_, W> {
+    fn write_all(&mut self, buf: &[u8]) -> ser::Result<()> {
+        self.writer.write_all(buf)
+    }
+
+    fn flush(&mut self) -> ser::Result<()> {
+        self.writer.flush()
+    }
+}
+
+impl<W: WriteWithPos> WriteWithPos for SchemaWriter<'_, W> {
+    fn pos(&self) -> usize {
+        self.writer.pos()
+    }
+
+    fn is_portable(&self) -> bool {
+        self.writer.is_portable()
+    }
+
+    fn has_checksum(&self) -> bool {
+        self.writer.has_checksum()
+    }
+
+    fn has_signature(&self) -> bool {
+        self.writer.has_signature()
+    }
+
+    fn has_chunk_checksums(&self) -> bool {
+        self.writer.has_chunk_checksums()
+    }
+
+    fn is_canonical(&self) -> bool {
+        self.writer.is_canonical()
+    }
+}
+
+/// WARNING: these implementations must be kept in sync with the ones
+/// in the default implementation of [`WriteWithNames`].
+impl<W: WriteWithPos> WriteWithNames for SchemaWriter<'_, W> {
+    #[inline(always)]
+    fn align<T: MaxSizeOf>(&mut self) -> Result<()> {
+        let padding = pad_align_to(self.pos(), T::max_size_of());
+        if padding != 0 {
+            self.schema.0.push(SchemaRow {
+                field: "PADDING".into(),
+                ty: format!("[u8; {}]", padding),
+                offset: self.pos(),
+                size: padding,
+                align: 1,
+            });
+            write_zeros(self, padding)?;
+        }
+
+        Ok(())
+    }
+
+    #[inline(always)]
+    fn write<V: SerializeInner>(&mut self, field_name: &str, value: &V) -> Result<()> {
+        // prepare a row with the field name and the type
+        self.path.push(field_name.into());
+        let pos = self.pos();
+
+        let len = self.schema.0.len();
+        value._serialize_inner(self)?;
+
+        // This is slightly inefficient because we have to shift
+        // the whole vector, but it's not a big deal and it keeps
+        // the schema in the correct order.
+        self.schema.0.insert(
+            len,
+            SchemaRow {
+                field: self.path.join("."),
+                ty: core::any::type_name::<V>().to_string(),
+                offset: pos,
+                align: 0,
+                size: self.pos() - pos,
+            },
+        );
+        self.path.pop();
+        Ok(())
+    }
+
+    #[inline(always)]
+    fn write_bytes<V: SerializeInner + ZeroCopy>(&mut self, value: &[u8]) -> Result<()> {
+        self.begin_bytes::<V>(value.len())?;
+        self.write_all(value)
+    }
+
+    #[inline(always)]
+    fn begin_bytes<V: SerializeInner + ZeroCopy>(&mut self, size: usize) -> Result<()> {
+        self.path.push("zero".to_string());
+        // Note that we are writing the schema row of the field before
+        // having written its content.
+        self.schema.0.push(SchemaRow {
+            field: self.path.join("."),
+            ty: core::any::type_name::<V>().to_string(),
+            offset: self.pos(),
+            size,
+            align: V::max_size_of(),
+        });
+        self.path.pop();
+        Ok(())
+    }
+}
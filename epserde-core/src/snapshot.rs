@@ -0,0 +1,389 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ * SPDX-FileCopyrightText: 2023 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+/*!
+
+Atomic writing of sets of related files.
+
+Datasets are often made of several serialized structures that must be
+consistent with each other (e.g., a graph and its node labels), and a process
+crashing while replacing them might leave on disk a mix of old and new files.
+
+A [`SnapshotWriter`] writes the files of a snapshot in a directory, first
+under temporary names, and then [commits](SnapshotWriter::commit) them by
+removing the manifest of the previous snapshot, if any, renaming the files to
+their final names, and writing, last, a new manifest file containing their
+lengths and [xxh3](xxhash_rust::xxh3) hashes. A
+[`SnapshotReader`] reads the manifest and checks that the files match it,
+refusing sets of files whose commit has not been completed:
+
+```rust
+use epserde::prelude::*;
+use epserde::snapshot::*;
+
+let dir = std::env::temp_dir().join("snapshot_doc");
+let mut writer = SnapshotWriter::new(&dir)?;
+writer.add("values", &vec![1_u64, 2, 3])?;
+writer.add("names", &vec!["a".to_string(), "b".to_string()])?;
+writer.commit()?;
+
+let reader = SnapshotReader::open(&dir)?;
+let values = reader.load_mem::<Vec<u64>>("values")?;
+assert_eq!(*values, [1, 2, 3]);
+let names = reader.load_full::<Vec<String>>("names")?;
+assert_eq!(names, ["a", "b"]);
+# std::fs::remove_dir_all(&dir)?;
+# Ok::<(), Box<dyn std::error::Error>>(())
+```
+
+If a commit is interrupted, the directory contains no manifest, and it will
+be unreadable until a new snapshot is committed; to keep the previous
+snapshot available while writing a new one, use a different directory for
+each snapshot.
+
+The files are checked when the snapshot is opened: if they are modified
+afterwards, loading methods will return the new content.
+
+*/
+
+#[cfg(feature = "mmap-rs")]
+use crate::deser::Flags;
+use crate::deser::{self, DeserType, Deserialize, MemCase};
+use crate::ser::{self, Serialize};
+use std::io::{self, BufWriter, ErrorKind, Read, Write};
+use std::path::{Path, PathBuf};
+use xxhash_rust::xxh3::Xxh3;
+
+/// The name of the manifest file.
+pub const MANIFEST: &str = "MANIFEST";
+
+/// The first line of manifest files, containing the version of the format.
+const MANIFEST_HEADER: &str = "epserde-snapshot 1";
+
+/// The suffix of temporary files.
+const TMP_SUFFIX: &str = ".tmp";
+
+/// A file of a snapshot, as described in the manifest.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SnapshotFile {
+    /// The name of the file, relative to the directory of the snapshot.
+    pub name: String,
+    /// The length in bytes of the file.
+    pub len: u64,
+    /// The xxh3 hash of the content of the file.
+    pub hash: u64,
+}
+
+/// A [`Write`] adapter computing the length and the hash of the bytes written.
+struct HashWriter<W> {
+    inner: W,
+    hasher: Xxh3,
+    len: u64,
+}
+
+impl<W: Write> Write for HashWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let len = self.inner.write(buf)?;
+        self.hasher.update(buf.get(..len).unwrap_or(buf));
+        self.len += len as u64;
+        Ok(len)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// Writes the files of a snapshot, committing them atomically.
+///
+/// See the [module documentation](self) for the protocol and an example. If
+/// the writer is dropped without committing, the temporary files written so
+/// far are removed.
+#[derive(Debug)]
+pub struct SnapshotWriter {
+    dir: PathBuf,
+    files: Vec<SnapshotFile>,
+    committed: bool,
+}
+
+impl SnapshotWriter {
+    /// Create a writer for a snapshot in the given directory, creating the
+    /// directory if necessary.
+    pub fn new(dir: impl AsRef<Path>) -> ser::Result<Self> {
+        let dir = dir.as_ref().to_path_buf();
+        std::fs::create_dir_all(&dir).map_err(ser::Error::FileOpenError)?;
+        Ok(Self {
+            dir,
+            files: Vec::new(),
+            committed: false,
+        })
+    }
+
+    /// Serialize `value` into a temporary file that will be renamed to
+    /// `name` at commit time.
+    ///
+    /// # Panics
+    ///
+    /// If `name` is empty, contains path separators or newlines, ends with
+    /// `.tmp`, is the name of the manifest, or has already been added.
+    pub fn add<T: Serialize + ?Sized>(&mut self, name: &str, value: &T) -> ser::Result<()> {
+        self.add_with(name, |mut writer| value.serialize(&mut writer).map(|_| ()))
+    }
+
+    /// Like [`add`](SnapshotWriter::add), but the content of the file is
+    /// written by `write` (e.g., using
+    /// [`serialize_with_checksum`](Serialize::serialize_with_checksum)).
+    ///
+    /// # Panics
+    ///
+    /// See [`add`](SnapshotWriter::add).
+    pub fn add_with(
+        &mut self,
+        name: &str,
+        write: impl FnOnce(&mut dyn Write) -> ser::Result<()>,
+    ) -> ser::Result<()> {
+        assert!(
+            !name.is_empty()
+                && !name.contains(['/', '\\', '\n', '\r'])
+                && !name.ends_with(TMP_SUFFIX)
+                && name != MANIFEST,
+            "Invalid name {:?} for a file of a snapshot",
+            name
+        );
+        assert!(
+            self.files.iter().all(|file| file.name != name),
+            "The file {} has already been added to the snapshot",
+            name
+        );
+        let tmp_path = self.tmp_path(name);
+        let file = std::fs::File::create(&tmp_path).map_err(ser::Error::FileOpenError)?;
+        let result = (|| {
+            let mut writer = BufWriter::new(HashWriter {
+                inner: file,
+                hasher: Xxh3::new(),
+                len: 0,
+            });
+            write(&mut writer)?;
+            let writer = writer
+                .into_inner()
+                .map_err(|err| ser::Error::IoError(err.into_error()))?;
+            writer.inner.sync_all().map_err(ser::Error::IoError)?;
+            Ok(SnapshotFile {
+                name: name.to_string(),
+                len: writer.len,
+                hash: writer.hasher.digest(),
+            })
+        })();
+        match result {
+            Ok(file) => {
+                self.files.push(file);
+                Ok(())
+            }
+            Err(err) => {
+                let _ = std::fs::remove_file(&tmp_path);
+                Err(err)
+            }
+        }
+    }
+
+    /// Return the files added so far.
+    pub fn files(&self) -> &[SnapshotFile] {
+        &self.files
+    }
+
+    /// Commit the snapshot, removing the previous manifest, renaming the
+    /// temporary files to their final names, and writing the manifest last.
+    pub fn commit(mut self) -> ser::Result<()> {
+        // The previous manifest must not describe a mix of old and new files
+        match std::fs::remove_file(self.dir.join(MANIFEST)) {
+            Err(err) if err.kind() != ErrorKind::NotFound => return Err(ser::Error::IoError(err)),
+            _ => {}
+        }
+        sync_dir(&self.dir).map_err(ser::Error::IoError)?;
+        for file in &self.files {
+            std::fs::rename(self.tmp_path(&file.name), self.dir.join(&file.name))
+                .map_err(ser::Error::IoError)?;
+        }
+        // From now on, temporary files must not be removed
+        self.committed = true;
+        // The renames must be durable before the manifest is
+        sync_dir(&self.dir).map_err(ser::Error::IoError)?;
+
+        let mut manifest = format!("{}\n", MANIFEST_HEADER);
+        for file in &self.files {
+            manifest.push_str(&format!("{:016x} {} {}\n", file.hash, file.len, file.name));
+        }
+        let tmp_path = self.tmp_path(MANIFEST);
+        let mut file = std::fs::File::create(&tmp_path).map_err(ser::Error::FileOpenError)?;
+        file.write_all(manifest.as_bytes())
+            .map_err(|_| ser::Error::WriteError)?;
+        file.sync_all().map_err(ser::Error::IoError)?;
+        std::fs::rename(&tmp_path, self.dir.join(MANIFEST)).map_err(ser::Error::IoError)?;
+        sync_dir(&self.dir).map_err(ser::Error::IoError)
+    }
+
+    fn tmp_path(&self, name: &str) -> PathBuf {
+        self.dir.join(format!("{}{}", name, TMP_SUFFIX))
+    }
+}
+
+impl Drop for SnapshotWriter {
+    fn drop(&mut self) {
+        if !self.committed {
+            for file in &self.files {
+                let _ = std::fs::remove_file(self.tmp_path(&file.name));
+            }
+        }
+    }
+}
+
+/// Make renames in a directory durable.
+#[cfg(unix)]
+pub(crate) fn sync_dir(dir: &Path) -> io::Result<()> {
+    std::fs::File::open(dir)?.sync_all()
+}
+
+/// Directories cannot be opened on other systems, where renames are
+/// assumed to be durable.
+#[cfg(not(unix))]
+pub(crate) fn sync_dir(_dir: &Path) -> io::Result<()> {
+    Ok(())
+}
+
+/// Reads the files of a committed snapshot.
+///
+/// See the [module documentation](self) for the protocol and an example.
+#[derive(Debug, Clone)]
+pub struct SnapshotReader {
+    dir: PathBuf,
+    files: Vec<SnapshotFile>,
+}
+
+impl SnapshotReader {
+    /// Open the snapshot in the given directory, checking that its files
+    /// have the length and the hash recorded in the manifest.
+    ///
+    /// Checking the hashes requires reading all files: see
+    /// [`open_unverified`](SnapshotReader::open_unverified).
+    pub fn open(dir: impl AsRef<Path>) -> deser::Result<Self> {
+        let reader = Self::open_unverified(dir)?;
+        for file in &reader.files {
+            let mut hasher = Xxh3::new();
+            let mut buf = vec![0; 1 << 16];
+            let mut input = std::fs::File::open(reader.dir.join(&file.name))
+                .map_err(deser::Error::FileOpenError)?;
+            loop {
+                let len = match input.read(&mut buf) {
+                    Ok(0) => break,
+                    Ok(len) => len,
+                    Err(err) if err.kind() == ErrorKind::Interrupted => continue,
+                    Err(err) => return Err(err.into()),
+                };
+                hasher.update(buf.get(..len).unwrap_or(&buf));
+            }
+            if hasher.digest() != file.hash {
+                return Err(deser::Error::InconsistentSnapshot(format!(
+                    "The hash of the file {} does not match the manifest",
+                    file.name
+                )));
+            }
+        }
+        Ok(reader)
+    }
+
+    /// Open the snapshot in the given directory, checking only that its
+    /// files have the length recorded in the manifest.
+    pub fn open_unverified(dir: impl AsRef<Path>) -> deser::Result<Self> {
+        let dir = dir.as_ref().to_path_buf();
+        let manifest =
+            std::fs::read_to_string(dir.join(MANIFEST)).map_err(deser::Error::FileOpenError)?;
+        let files = parse_manifest(&manifest).ok_or_else(|| {
+            deser::Error::InconsistentSnapshot(format!("Malformed manifest in {}", dir.display()))
+        })?;
+        for file in &files {
+            let len = match dir.join(&file.name).metadata() {
+                Ok(metadata) => metadata.len(),
+                Err(err) if err.kind() == ErrorKind::NotFound => {
+                    return Err(deser::Error::InconsistentSnapshot(format!(
+                        "The file {} is missing",
+                        file.name
+                    )))
+                }
+                Err(err) => return Err(deser::Error::FileOpenError(err)),
+            };
+            if len != file.len {
+                return Err(deser::Error::InconsistentSnapshot(format!(
+                    "The file {} has length {}, but the manifest records length {}",
+                    file.name, len, file.len
+                )));
+            }
+        }
+        Ok(Self { dir, files })
+    }
+
+    /// Return the files of the snapshot, in the order in which they have
+    /// been added.
+    pub fn files(&self) -> &[SnapshotFile] {
+        &self.files
+    }
+
+    /// Return the path of the file with the given name.
+    ///
+    /// An error of kind [`ErrorKind::NotFound`] is returned if the snapshot
+    /// contains no such file.
+    pub fn path(&self, name: &str) -> deser::Result<PathBuf> {
+        if self.files.iter().any(|file| file.name == name) {
+            Ok(self.dir.join(name))
+        } else {
+            Err(deser::Error::FileOpenError(io::Error::new(
+                ErrorKind::NotFound,
+                format!("No file named {} in the snapshot", name),
+            )))
+        }
+    }
+
+    /// Fully deserialize the file with the given name (see [`Deserialize::load_full`]).
+    pub fn load_full<T: Deserialize>(&self, name: &str) -> deser::Result<T> {
+        T::load_full(self.path(name)?)
+    }
+
+    /// Load the file with the given name into memory (see [`Deserialize::load_mem`]).
+    pub fn load_mem<'a, T: Deserialize>(
+        &self,
+        name: &str,
+    ) -> deser::Result<MemCase<DeserType<'a, T>>> {
+        T::load_mem(self.path(name)?)
+    }
+
+    /// Memory map the file with the given name (see `Deserialize::mmap`).
+    #[cfg(feature = "mmap-rs")]
+    pub fn mmap<'a, T: Deserialize>(
+        &self,
+        name: &str,
+        flags: Flags,
+    ) -> deser::Result<MemCase<DeserType<'a, T>>> {
+        T::mmap(self.path(name)?, flags)
+    }
+}
+
+/// Parse the content of a manifest, returning `None` if it is malformed.
+fn parse_manifest(manifest: &str) -> Option<Vec<SnapshotFile>> {
+    let mut lines = manifest.lines();
+    if lines.next()? != MANIFEST_HEADER {
+        return None;
+    }
+    lines
+        .map(|line| {
+            let mut parts = line.splitn(3, ' ');
+            Some(SnapshotFile {
+                hash: u64::from_str_radix(parts.next()?, 16).ok()?,
+                len: parts.next()?.parse().ok()?,
+                name: parts.next()?.to_string(),
+            })
+        })
+        .collect()
+}
@@ -0,0 +1,213 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ * SPDX-FileCopyrightText: 2023 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+/*!
+
+A vector of key-value pairs that is serialized as a sorted map.
+
+[`SortedPairs`] wraps a `Vec<(K, V)>` whose pairs can be pushed in any
+order. At serialization time the pairs are written in key order as a
+vector of keys followed by a vector of values (if the pairs are not
+sorted, a sorted permutation is computed, leaving the pairs untouched).
+ε-copy deserialization returns a [`SortedMap`], which supports lookups by
+binary search; if keys and values are [`ZeroCopy`], the view refers
+directly to the serialized data, and no allocation happens at all.
+Full-copy deserialization returns a sorted `SortedPairs`.
+
+```rust
+use epserde::prelude::*;
+use epserde::sorted_pairs::SortedPairs;
+
+let mut pairs = SortedPairs::new();
+pairs.push(30_u32, 3.0_f64);
+pairs.push(10, 1.0);
+pairs.push(20, 2.0);
+
+let mut cursor = epserde::new_aligned_cursor();
+pairs.serialize(&mut cursor).unwrap();
+let buf = cursor.into_inner();
+
+let view = <SortedPairs<u32, f64>>::deserialize_eps(&buf).unwrap();
+assert_eq!(view.keys(), &[10, 20, 30]);
+assert_eq!(view.get(&20), Some(&2.0));
+assert_eq!(view.get(&40), None);
+```
+
+*/
+
+use crate::impls::btree::SortedMap;
+use crate::prelude::*;
+use core::borrow::Borrow;
+use core::hash::Hash;
+use deser::*;
+use ser::*;
+
+#[cfg(all(feature = "alloc", not(feature = "std")))]
+use alloc::vec::Vec;
+
+/// A vector of key-value pairs that is serialized as a sorted map.
+///
+/// See the [module documentation](self) for more information.
+///
+/// If several pairs have the same key, they are serialized in insertion
+/// order, and lookups will return any of the associated values.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SortedPairs<K, V>(Vec<(K, V)>);
+
+impl<K, V> Default for SortedPairs<K, V> {
+    fn default() -> Self {
+        Self(Vec::new())
+    }
+}
+
+impl<K, V> SortedPairs<K, V> {
+    /// Create an empty vector of pairs.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Return the number of pairs.
+    #[inline(always)]
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Return whether there are no pairs.
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Add a pair.
+    pub fn push(&mut self, key: K, value: V) {
+        self.0.push((key, value));
+    }
+
+    /// Return the pairs.
+    #[inline(always)]
+    pub fn as_slice(&self) -> &[(K, V)] {
+        &self.0
+    }
+
+    /// Return a mutable reference to the underlying vector.
+    pub fn as_mut_vec(&mut self) -> &mut Vec<(K, V)> {
+        &mut self.0
+    }
+
+    /// Return the underlying vector.
+    pub fn into_inner(self) -> Vec<(K, V)> {
+        self.0
+    }
+}
+
+impl<K: Ord, V> SortedPairs<K, V> {
+    /// Return whether the pairs are sorted by key.
+    pub fn is_sorted(&self) -> bool {
+        self.0.is_sorted_by(|a, b| a.0 <= b.0)
+    }
+
+    /// Sort the pairs by key, preserving the order of pairs with the same key.
+    pub fn sort(&mut self) {
+        self.0.sort_by(|a, b| a.0.cmp(&b.0));
+    }
+
+    /// Return the value associated with the given key, if present, using
+    /// binary search.
+    ///
+    /// The pairs must be [sorted](SortedPairs::sort) (e.g., because they have
+    /// been fully deserialized); otherwise, the result is unspecified.
+    pub fn get<Q: Ord + ?Sized>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+    {
+        self.0
+            .binary_search_by(|(k, _)| k.borrow().cmp(key))
+            .ok()
+            .and_then(|index| self.0.get(index))
+            .map(|(_, v)| v)
+    }
+}
+
+impl<K, V> From<Vec<(K, V)>> for SortedPairs<K, V> {
+    fn from(pairs: Vec<(K, V)>) -> Self {
+        Self(pairs)
+    }
+}
+
+impl<K, V> FromIterator<(K, V)> for SortedPairs<K, V> {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        Self(iter.into_iter().collect())
+    }
+}
+
+impl<K, V> CopyType for SortedPairs<K, V> {
+    type Copy = Deep;
+}
+
+impl<K: TypeHash, V: TypeHash> TypeHash for SortedPairs<K, V> {
+    fn type_hash(hasher: &mut impl core::hash::Hasher) {
+        "SortedPairs".hash(hasher);
+        K::type_hash(hasher);
+        V::type_hash(hasher);
+    }
+}
+
+impl<K: ReprHash, V: ReprHash> ReprHash for SortedPairs<K, V> {
+    fn repr_hash(hasher: &mut impl core::hash::Hasher, offset_of: &mut usize) {
+        *offset_of = 0;
+        K::repr_hash(hasher, offset_of);
+        *offset_of = 0;
+        V::repr_hash(hasher, offset_of);
+    }
+}
+
+impl<K: CopyType + SerializeInner + Ord, V: CopyType + SerializeInner> SerializeInner
+    for SortedPairs<K, V>
+where
+    K: SerializeIterHelper<<K as CopyType>::Copy>,
+    V: SerializeIterHelper<<V as CopyType>::Copy>,
+{
+    const IS_ZERO_COPY: bool = false;
+    const ZERO_COPY_MISMATCH: bool = false;
+    const MAX_FIELD_ALIGN: usize =
+        crate::ser::helpers::max_align(&[K::MAX_FIELD_ALIGN, V::MAX_FIELD_ALIGN]);
+
+    fn _serialize_inner(&self, backend: &mut impl WriteWithNames) -> ser::Result<()> {
+        let len = self.len();
+        if self.is_sorted() {
+            K::_serialize_iter(backend, len, self.0.iter().map(|(k, _)| k))?;
+            V::_serialize_iter(backend, len, self.0.iter().map(|(_, v)| v))
+        } else {
+            let mut perm = self.0.iter().collect::<Vec<_>>();
+            perm.sort_by(|a, b| a.0.cmp(&b.0));
+            K::_serialize_iter(backend, len, perm.iter().map(|(k, _)| k))?;
+            V::_serialize_iter(backend, len, perm.iter().map(|(_, v)| v))
+        }
+    }
+}
+
+impl<K: DeserializeInner, V: DeserializeInner> DeserializeInner for SortedPairs<K, V>
+where
+    Vec<K>: DeserializeInner,
+    Vec<V>: DeserializeInner,
+{
+    type DeserType<'a> = SortedMap<DeserType<'a, Vec<K>>, DeserType<'a, Vec<V>>>;
+
+    fn _deserialize_full_inner(backend: &mut impl ReadWithPos) -> deser::Result<Self> {
+        let keys = Vec::<K>::_deserialize_full_inner(backend)?;
+        let values = Vec::<V>::_deserialize_full_inner(backend)?;
+        Ok(keys.into_iter().zip(values).collect())
+    }
+
+    fn _deserialize_eps_inner<'a>(
+        backend: &mut SliceWithPos<'a>,
+    ) -> deser::Result<Self::DeserType<'a>> {
+        let keys = Vec::<K>::_deserialize_eps_inner(backend)?;
+        let values = Vec::<V>::_deserialize_eps_inner(backend)?;
+        Ok(SortedMap::new(keys, values))
+    }
+}
@@ -0,0 +1,361 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ * SPDX-FileCopyrightText: 2023 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+/*!
+
+Typed references to structures serialized in other files.
+
+A [`StableId<T>`] is a zero-copy pair made of a user-provided `u128`
+identifier (e.g., a UUID) and the [type hash](crate::type_hash_of) of `T`. It
+can be stored inside serialized structures to refer to a structure of type `T`
+serialized elsewhere, for example in another file of a
+[snapshot](crate::snapshot), making it possible to build object graphs spanning
+several files.
+
+At runtime, a [`Resolver`] maps identifiers to [`MemCase`]-backed ε-copy
+deserialized structures, checking that the type of the structure is the one
+recorded in the reference: a reference written when `T` had a different
+definition, or a reference to a structure of another type, will be reported
+as an error instead of being silently misinterpreted. Since the type of the
+referenced structure is not part of the type hash of a reference,
+structures can contain references to structures of the same type. [`MemCaseResolver`] is a resolver built by registering
+structures explicitly:
+
+```rust
+use epserde::prelude::*;
+use epserde::stable_id::*;
+
+const LABELS: u128 = 0x6c61_6265_6c73;
+
+let labels = vec!["zero".to_string(), "one".to_string()];
+let nodes = (StableId::<Vec<String>>::new(LABELS), vec![0_u32, 1, 1]);
+
+let mut cursor = epserde::new_aligned_cursor();
+labels.serialize(&mut cursor)?;
+let labels_buf = cursor.into_inner();
+let mut cursor = epserde::new_aligned_cursor();
+nodes.serialize(&mut cursor)?;
+let nodes_buf = cursor.into_inner();
+
+let mut resolver = MemCaseResolver::new();
+let labels = <Vec<String>>::deserialize_eps_owned(labels_buf)?;
+resolver.insert(StableId::<Vec<String>>::new(LABELS), labels);
+
+let nodes = <(StableId<Vec<String>>, Vec<u32>)>::deserialize_eps(&nodes_buf)?;
+let labels = resolver.resolve(nodes.0)?;
+assert_eq!(labels[nodes.1[2] as usize], "one");
+# Ok::<(), Box<dyn std::error::Error>>(())
+```
+
+*/
+
+use crate::prelude::*;
+use core::any::Any;
+use core::hash::Hash;
+use core::marker::PhantomData;
+use core::mem::{align_of, offset_of, size_of};
+use deser::*;
+use ser::*;
+
+#[cfg(all(feature = "alloc", not(feature = "std")))]
+use alloc::{boxed::Box, collections::BTreeMap};
+#[cfg(feature = "std")]
+use std::collections::BTreeMap;
+
+/// A typed reference to a structure of type `T` serialized elsewhere.
+///
+/// See the [module documentation](self) for more information.
+///
+/// This type is zero-copy, so vectors of references can be ε-copy
+/// deserialized as slices; a single reference is ε-copy deserialized by
+/// value.
+#[repr(C)]
+pub struct StableId<T: ?Sized> {
+    id: u128,
+    type_hash: u64,
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<T: ?Sized + TypeHash> StableId<T> {
+    /// Create a reference with the given identifier to a structure of type `T`.
+    pub fn new(id: u128) -> Self {
+        Self {
+            id,
+            type_hash: crate::type_hash_of::<T>(),
+            _marker: PhantomData,
+        }
+    }
+
+    /// Return whether the type hash recorded in the reference is the type
+    /// hash of `T`.
+    ///
+    /// The result might be false if the reference has been deserialized
+    /// from data written when `T` had a different definition.
+    pub fn is_current(&self) -> bool {
+        self.type_hash == crate::type_hash_of::<T>()
+    }
+}
+
+impl<T: ?Sized> StableId<T> {
+    /// Return the identifier of the referenced structure.
+    pub fn id(&self) -> u128 {
+        self.id
+    }
+
+    /// Return the type hash recorded in the reference.
+    pub fn type_hash(&self) -> u64 {
+        self.type_hash
+    }
+}
+
+impl<T: ?Sized> Clone for StableId<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T: ?Sized> Copy for StableId<T> {}
+
+impl<T: ?Sized> PartialEq for StableId<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id && self.type_hash == other.type_hash
+    }
+}
+
+impl<T: ?Sized> Eq for StableId<T> {}
+
+impl<T: ?Sized> Hash for StableId<T> {
+    fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
+        self.id.hash(state);
+        self.type_hash.hash(state);
+    }
+}
+
+impl<T: ?Sized> core::fmt::Debug for StableId<T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("StableId")
+            .field("id", &format_args!("0x{:032x}", self.id))
+            .field("type_hash", &format_args!("0x{:016x}", self.type_hash))
+            .finish()
+    }
+}
+
+impl<T: ?Sized> CopyType for StableId<T> {
+    type Copy = Zero;
+}
+
+/// The type hash of `T` is not part of the type hash of a reference, as
+/// otherwise structures containing references to themselves would have
+/// infinite type hashes; the type of the referenced structure is checked
+/// at resolution time using the type hash recorded in the reference.
+impl<T: ?Sized> TypeHash for StableId<T> {
+    fn type_hash(hasher: &mut impl core::hash::Hasher) {
+        "ZeroCopy".hash(hasher);
+        "StableId".hash(hasher);
+        "id".hash(hasher);
+        "type_hash".hash(hasher);
+        u128::type_hash(hasher);
+        u64::type_hash(hasher);
+    }
+}
+
+impl<T: ?Sized> ReprHash for StableId<T> {
+    fn repr_hash(hasher: &mut impl core::hash::Hasher, offset_of: &mut usize) {
+        size_of::<Self>().hash(hasher);
+        "C".hash(hasher);
+        u128::repr_hash(hasher, offset_of);
+        u64::repr_hash(hasher, offset_of);
+    }
+
+    fn layout() -> crate::traits::TypeLayout {
+        crate::traits::TypeLayout::new_struct::<Self, _>([
+            ("id", offset_of!(Self, id), u128::layout()),
+            ("type_hash", offset_of!(Self, type_hash), u64::layout()),
+        ])
+    }
+}
+
+impl<T: ?Sized> MaxSizeOf for StableId<T> {
+    const WIDTH_DEPENDENT: bool = false;
+
+    fn max_size_of() -> usize {
+        align_of::<Self>()
+            .max(u128::max_size_of())
+            .max(u64::max_size_of())
+    }
+}
+
+impl<T: ?Sized> ByteSwap for StableId<T> {
+    fn byte_swap(bytes: &mut [u8]) -> deser::Result<()> {
+        let offset = offset_of!(Self, id);
+        u128::byte_swap(
+            bytes
+                .get_mut(offset..offset + size_of::<u128>())
+                .ok_or(deser::Error::ReadError)?,
+        )?;
+        let offset = offset_of!(Self, type_hash);
+        u64::byte_swap(
+            bytes
+                .get_mut(offset..offset + size_of::<u64>())
+                .ok_or(deser::Error::ReadError)?,
+        )
+    }
+}
+
+impl<T: ?Sized> CheckBytes for StableId<T> {
+    fn check_bytes(_bytes: &[u8]) -> deser::Result<()> {
+        Ok(())
+    }
+}
+
+impl<T: ?Sized> SerializeInner for StableId<T> {
+    const IS_ZERO_COPY: bool = true;
+    const ZERO_COPY_MISMATCH: bool = false;
+    const MAX_FIELD_ALIGN: usize = crate::ser::helpers::max_align(&[
+        align_of::<Self>(),
+        u128::MAX_FIELD_ALIGN,
+        u64::MAX_FIELD_ALIGN,
+    ]);
+
+    #[inline(always)]
+    fn _serialize_inner(&self, backend: &mut impl WriteWithNames) -> ser::Result<()> {
+        crate::ser::helpers::serialize_zero(backend, self)
+    }
+}
+
+impl<T: ?Sized> DeserializeInner for StableId<T> {
+    #[inline(always)]
+    fn _deserialize_full_inner(backend: &mut impl ReadWithPos) -> deser::Result<Self> {
+        crate::deser::helpers::deserialize_full_zero::<Self>(backend)
+    }
+
+    type DeserType<'a> = Self;
+
+    #[inline(always)]
+    fn _deserialize_eps_inner<'a>(
+        backend: &mut SliceWithPos<'a>,
+    ) -> deser::Result<Self::DeserType<'a>> {
+        // The reference is copied, as the type parameter might not outlive 'a
+        crate::deser::helpers::deserialize_full_zero::<Self>(backend)
+    }
+}
+
+/// The ε-copy deserialized structure of type `T` returned by a [`Resolver`].
+pub type ResolvedCase<T> = MemCase<DeserType<'static, T>>;
+
+/// A map from identifiers to [`MemCase`]-backed ε-copy deserialized
+/// structures.
+///
+/// Implementations have just to provide [`Resolver::resolve_erased`];
+/// [`Resolver::resolve`] performs the type checks and the downcasting.
+pub trait Resolver {
+    /// Return the type hash and the structure with the given identifier, if
+    /// any.
+    ///
+    /// The structure must be a [`ResolvedCase<T>`] for some type `T` whose
+    /// type hash is the one returned.
+    fn resolve_erased(&self, id: u128) -> Option<(u64, &(dyn Any + Send + Sync))>;
+
+    /// Return the structure referenced by `id`.
+    ///
+    /// If there is no structure with the given identifier, this method
+    /// returns [`Error::UnknownStableId`]; if
+    /// the type of the structure is not
+    /// the one recorded in `id`, or if it is not `T`, this method returns
+    /// [`Error::WrongStableIdType`].
+    fn resolve<T: DeserializeInner + TypeHash + 'static>(
+        &self,
+        id: StableId<T>,
+    ) -> deser::Result<&ResolvedCase<T>> {
+        let (type_hash, case) = self
+            .resolve_erased(id.id)
+            .ok_or(deser::Error::UnknownStableId(id.id))?;
+        let wrong_type = |expected| deser::Error::WrongStableIdType {
+            id: id.id,
+            expected,
+            got: type_hash,
+        };
+        if type_hash != id.type_hash {
+            return Err(wrong_type(id.type_hash));
+        }
+        case.downcast_ref()
+            .ok_or_else(|| wrong_type(crate::type_hash_of::<T>()))
+    }
+}
+
+/// A [`Resolver`] containing explicitly registered structures.
+///
+/// See the [module documentation](self) for an example.
+#[derive(Default)]
+pub struct MemCaseResolver {
+    cases: BTreeMap<u128, (u64, Box<dyn Any + Send + Sync>)>,
+}
+
+impl core::fmt::Debug for MemCaseResolver {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("MemCaseResolver")
+            .field("len", &self.len())
+            .finish()
+    }
+}
+
+impl MemCaseResolver {
+    /// Create an empty resolver.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register a structure under the identifier of `id`, replacing the
+    /// structure previously registered with the same identifier, if any.
+    ///
+    /// The structure is registered with the type hash of `T`, not with
+    /// the type hash recorded in `id`.
+    ///
+    /// The structure is usually the result of an ε-copy loading method
+    /// such as [`load_mem`](Deserialize::load_mem) or `mmap`.
+    pub fn insert<T: DeserializeInner + TypeHash + 'static>(
+        &mut self,
+        id: StableId<T>,
+        case: ResolvedCase<T>,
+    ) -> &mut Self
+    where
+        DeserType<'static, T>: Send + Sync,
+    {
+        self.cases
+            .insert(id.id, (crate::type_hash_of::<T>(), Box::new(case)));
+        self
+    }
+
+    /// Remove the structure with the given identifier, returning whether
+    /// it was present.
+    pub fn remove(&mut self, id: u128) -> bool {
+        self.cases.remove(&id).is_some()
+    }
+
+    /// Return whether a structure with the given identifier is registered.
+    pub fn contains(&self, id: u128) -> bool {
+        self.cases.contains_key(&id)
+    }
+
+    /// Return the number of registered structures.
+    pub fn len(&self) -> usize {
+        self.cases.len()
+    }
+
+    /// Return whether no structure is registered.
+    pub fn is_empty(&self) -> bool {
+        self.cases.is_empty()
+    }
+}
+
+impl Resolver for MemCaseResolver {
+    fn resolve_erased(&self, id: u128) -> Option<(u64, &(dyn Any + Send + Sync))> {
+        self.cases
+            .get(&id)
+            .map(|(type_hash, case)| (*type_hash, case.as_ref()))
+    }
+}
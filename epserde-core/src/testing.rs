@@ -0,0 +1,246 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ * SPDX-FileCopyrightText: 2023 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+/*!
+
+Adapters injecting failures into writers and readers.
+
+[`FaultyWriter`] and [`FaultyReader`] wrap a [`Write`] or a [`Read`],
+respectively, and can be configured to fail after a given number of bytes,
+to return end of file early, or to perform short writes and reads, as
+[`Write::write`] and [`Read::read`] are allowed to do. They make it possible
+to test systematically how serialization and deserialization code (in
+ε-serde or in an application) behaves on partial writes and reads:
+
+```rust
+use epserde::prelude::*;
+use epserde::testing::*;
+use std::io::ErrorKind;
+
+let data = vec![0_u64, 1, 2];
+let mut cursor = epserde::new_aligned_cursor();
+data.serialize(&mut cursor)?;
+let bytes = cursor.into_inner();
+
+for pos in 0..bytes.len() {
+    // Serialization fails if the writer fails at any position
+    let mut writer = FaultyWriter::new(Vec::new());
+    writer.fail_write_at(pos, ErrorKind::Other);
+    assert!(data.serialize(&mut writer).is_err());
+    assert_eq!(writer.get_ref()[..], bytes[..pos]);
+
+    // Deserialization fails if the data is truncated at any position
+    let mut reader = FaultyReader::new(&bytes[..]);
+    reader.truncate_reads(pos);
+    assert!(<Vec<u64>>::deserialize_full(&mut reader).is_err());
+}
+
+// Short reads are not a problem
+let mut reader = FaultyReader::new(&bytes[..]);
+reader.max_read(1);
+assert_eq!(<Vec<u64>>::deserialize_full(&mut reader)?, data);
+# Ok::<(), anyhow::Error>(())
+```
+
+For failures when opening files, see [`MemFileSystem`](crate::fs::MemFileSystem).
+
+*/
+
+use std::io::{self, ErrorKind, Read, Write};
+
+/// A [`Write`] adapter injecting failures and short writes.
+///
+/// See the [module documentation](self) for an example.
+#[derive(Debug, Clone, Default)]
+pub struct FaultyWriter<W> {
+    inner: W,
+    /// The number of bytes written so far.
+    pos: usize,
+    /// The position and the kind of the error returned by writes, if any.
+    write_error: Option<(usize, ErrorKind)>,
+    /// The kind of the error returned by flushes, if any.
+    flush_error: Option<ErrorKind>,
+    /// The maximum number of bytes accepted by a write, if any.
+    max_write: Option<usize>,
+}
+
+impl<W> FaultyWriter<W> {
+    /// Wrap the given writer, injecting no failures.
+    pub fn new(inner: W) -> Self {
+        Self {
+            inner,
+            pos: 0,
+            write_error: None,
+            flush_error: None,
+            max_write: None,
+        }
+    }
+
+    /// Make writes fail with an error of the given kind after `pos` bytes
+    /// have been written.
+    ///
+    /// Note that [`Write::write_all`] retries writes failing with
+    /// [`ErrorKind::Interrupted`], so errors of such kind make writers loop
+    /// forever.
+    pub fn fail_write_at(&mut self, pos: usize, kind: ErrorKind) -> &mut Self {
+        self.write_error = Some((pos, kind));
+        self
+    }
+
+    /// Make flushes fail with an error of the given kind.
+    pub fn fail_flush(&mut self, kind: ErrorKind) -> &mut Self {
+        self.flush_error = Some(kind);
+        self
+    }
+
+    /// Make each write accept at most `len` bytes, as [`Write::write`] is
+    /// allowed to do.
+    ///
+    /// # Panics
+    ///
+    /// If `len` is zero, as [`Write::write_all`] would fail.
+    pub fn max_write(&mut self, len: usize) -> &mut Self {
+        assert!(len != 0, "The maximum length of a write must be positive");
+        self.max_write = Some(len);
+        self
+    }
+
+    /// Return the number of bytes written so far.
+    pub fn pos(&self) -> usize {
+        self.pos
+    }
+
+    /// Return a reference to the underlying writer.
+    pub fn get_ref(&self) -> &W {
+        &self.inner
+    }
+
+    /// Return the underlying writer.
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+}
+
+impl<W: Write> Write for FaultyWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let mut len = buf.len();
+        if let Some((pos, kind)) = self.write_error {
+            if self.pos >= pos && len != 0 {
+                return Err(io::Error::new(kind, "Injected write error"));
+            }
+            len = len.min(pos - self.pos);
+        }
+        if let Some(max_write) = self.max_write {
+            len = len.min(max_write);
+        }
+        let len = self.inner.write(buf.get(..len).unwrap_or(buf))?;
+        self.pos += len;
+        Ok(len)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        if let Some(kind) = self.flush_error {
+            return Err(io::Error::new(kind, "Injected flush error"));
+        }
+        self.inner.flush()
+    }
+}
+
+/// A [`Read`] adapter injecting failures, early ends of file, and short reads.
+///
+/// See the [module documentation](self) for an example.
+#[derive(Debug, Clone, Default)]
+pub struct FaultyReader<R> {
+    inner: R,
+    /// The number of bytes read so far.
+    pos: usize,
+    /// The number of bytes after which reads return end of file, if any.
+    truncate_reads: Option<usize>,
+    /// The position and the kind of the error returned by reads, if any.
+    read_error: Option<(usize, ErrorKind)>,
+    /// The maximum number of bytes returned by a read, if any.
+    max_read: Option<usize>,
+}
+
+impl<R> FaultyReader<R> {
+    /// Wrap the given reader, injecting no failures.
+    pub fn new(inner: R) -> Self {
+        Self {
+            inner,
+            pos: 0,
+            truncate_reads: None,
+            read_error: None,
+            max_read: None,
+        }
+    }
+
+    /// Make reads return end of file after `len` bytes, as it happens when
+    /// a file is truncated.
+    pub fn truncate_reads(&mut self, len: usize) -> &mut Self {
+        self.truncate_reads = Some(len);
+        self
+    }
+
+    /// Make reads fail with an error of the given kind after `pos` bytes
+    /// have been read.
+    ///
+    /// Note that [`Read::read_exact`] retries reads failing with
+    /// [`ErrorKind::Interrupted`], so errors of such kind make readers loop
+    /// forever.
+    pub fn fail_read_at(&mut self, pos: usize, kind: ErrorKind) -> &mut Self {
+        self.read_error = Some((pos, kind));
+        self
+    }
+
+    /// Make each read return at most `len` bytes, as [`Read::read`] is
+    /// allowed to do.
+    ///
+    /// # Panics
+    ///
+    /// If `len` is zero, as reads would return end of file.
+    pub fn max_read(&mut self, len: usize) -> &mut Self {
+        assert!(len != 0, "The maximum length of a read must be positive");
+        self.max_read = Some(len);
+        self
+    }
+
+    /// Return the number of bytes read so far.
+    pub fn pos(&self) -> usize {
+        self.pos
+    }
+
+    /// Return a reference to the underlying reader.
+    pub fn get_ref(&self) -> &R {
+        &self.inner
+    }
+
+    /// Return the underlying reader.
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+}
+
+impl<R: Read> Read for FaultyReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let mut len = buf.len();
+        if let Some(end) = self.truncate_reads {
+            len = len.min(end.saturating_sub(self.pos));
+        }
+        if let Some((pos, kind)) = self.read_error {
+            if self.pos >= pos && len != 0 {
+                return Err(io::Error::new(kind, "Injected read error"));
+            }
+            len = len.min(pos - self.pos);
+        }
+        if let Some(max_read) = self.max_read {
+            len = len.min(max_read);
+        }
+        let len = self.inner.by_ref().take(len as u64).read(buf)?;
+        self.pos += len;
+        Ok(len)
+    }
+}
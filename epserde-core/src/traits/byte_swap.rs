@@ -0,0 +1,39 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ * SPDX-FileCopyrightText: 2023 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+/*!
+
+Trait to reverse the endianness of the in-memory representation of a
+zero-copy type.
+
+*/
+
+use crate::deser;
+
+/// Reverse in place the endianness of the memory representation of a
+/// zero-copy type.
+///
+/// This trait is used by
+/// [`Deserialize::deserialize_full_swapped`](crate::deser::Deserialize::deserialize_full_swapped)
+/// to fully deserialize data that was serialized on an architecture with
+/// the opposite endianness. Since the bytes might not represent a valid value
+/// until they have been swapped, the method works on a slice of bytes
+/// of length [`core::mem::size_of`] the type rather than on a value.
+///
+/// The implementation for primitive types reverses the bytes;
+/// the implementation for structures swaps the bytes of each field, leaving
+/// padding untouched. Zero-copy enums and unions cannot be swapped, as the
+/// layout depends on the variant (or on the field) stored, and return
+/// [`Error::ByteSwapUnsupported`](crate::deser::Error::ByteSwapUnsupported).
+///
+/// You should not implement this trait manually, but rather use the provided
+/// [derive macro](https://docs.rs/epserde-derive/latest/epserde_derive/derive.Epserde.html).
+pub trait ByteSwap {
+    /// Reverse the endianness of the representation of a value of this
+    /// type stored in `bytes`.
+    fn byte_swap(bytes: &mut [u8]) -> deser::Result<()>;
+}
@@ -0,0 +1,48 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ * SPDX-FileCopyrightText: 2023 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+/*!
+
+Trait to check that the in-memory representation of a zero-copy type
+is a valid value.
+
+*/
+
+use crate::deser;
+
+/// Check that a sequence of bytes is the memory representation of a valid
+/// value of a zero-copy type.
+///
+/// Not all bit patterns are valid values of a zero-copy type: for example,
+/// a `bool` must be zero or one, a `char` must be a Unicode scalar value,
+/// a [nonzero integer](core::num::NonZeroU64) must not be zero, and the tag
+/// of a zero-copy enum must be the discriminant of one of its variants.
+/// Interpreting an invalid bit pattern as a value is undefined behavior, so
+/// this trait is used to check data coming from untrusted sources.
+///
+/// Values are always checked during full-copy deserialization; during
+/// ε-copy deserialization, they are checked only if the
+/// [validation](crate::deser::Validation) is [`Checked`](crate::deser::Validation::Checked).
+///
+/// The implementation for primitive types checks the value; the
+/// implementation for structures checks each field, ignoring padding;
+/// the implementation for enums checks the tag and the fields of the
+/// corresponding variant. Unions are always valid, as reading their
+/// fields is unsafe anyway.
+///
+/// You should not implement this trait manually, but rather use the provided
+/// [derive macro](https://docs.rs/epserde-derive/latest/epserde_derive/derive.Epserde.html),
+/// unless you are supporting a type by hand (see the [`crate::extending`]
+/// module).
+pub trait CheckBytes {
+    /// Check that `bytes`, whose length is [`core::mem::size_of`] the
+    /// type, contain a valid value of this type.
+    ///
+    /// Return [`Error::InvalidValue`](crate::deser::Error::InvalidValue) or
+    /// [`Error::InvalidTag`](crate::deser::Error::InvalidTag) otherwise.
+    fn check_bytes(bytes: &[u8]) -> deser::Result<()>;
+}
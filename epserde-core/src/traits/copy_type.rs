@@ -11,7 +11,7 @@ Traits to mark types as zero-copy or deep-copy.
 
 */
 
-use crate::prelude::MaxSizeOf;
+use crate::prelude::{ByteSwap, CheckBytes, MaxSizeOf};
 use sealed::sealed;
 
 /// Internal trait used to select whether a type is zero-copy
@@ -26,6 +26,10 @@ use sealed::sealed;
 #[sealed]
 pub trait CopySelector {
     const IS_ZERO_COPY: bool;
+    /// [`Zero`] if both `Self` and `C` are [`Zero`], [`Deep`] otherwise.
+    ///
+    /// This type is used to compute the copy type of tuples.
+    type And<C: CopySelector>: CopySelector;
 }
 /// An implementation of a [`CopySelector`] specifying that a type is zero-copy.
 pub struct Zero {}
@@ -33,6 +37,7 @@ pub struct Zero {}
 #[sealed]
 impl CopySelector for Zero {
     const IS_ZERO_COPY: bool = true;
+    type And<C: CopySelector> = C;
 }
 
 /// An implementation of a [`CopySelector`] specifying that a type is deep-copy.
@@ -41,6 +46,7 @@ pub struct Deep {}
 #[sealed]
 impl CopySelector for Deep {
     const IS_ZERO_COPY: bool = false;
+    type And<C: CopySelector> = Deep;
 }
 
 /**
@@ -49,7 +55,7 @@ Marker trait for data specifying whether it is zero-copy or deep-copy.
 
 The trait comes in two flavors: `CopySelector<Type=Zero>` and
 `CopySelector<Type=Deep>`. To each of these flavors corresponds two
-dependent traits, [`ZeroCopy`] (which requires implementing [`MaxSizeOf`])
+dependent traits, [`ZeroCopy`] (which requires implementing [`MaxSizeOf`], [`ByteSwap`], and [`CheckBytes`])
 and [`DeepCopy`], which are automatically
 implemented.
 ```rust
@@ -62,7 +68,7 @@ impl CopyType for MyType {
 }
 // Now MyType implements DeepCopy
 ```
-You should not implement this trait manually, but rather use the provided [derive macro](epserde_derive::Epserde).
+You should not implement this trait manually, but rather use the provided [derive macro](https://docs.rs/epserde-derive/latest/epserde_derive/derive.Epserde.html).
 
 We use this trait to implement a different behavior for [`ZeroCopy`] and [`DeepCopy`] types,
 in particular on arrays, vectors, and boxed slices,
@@ -72,7 +78,7 @@ exclusive](https://github.com/rust-lang/rfcs/pull/1672#issuecomment-1405377983).
 
 For an array of elements of type `T` to be zero-copy serializable and
 deserializable, `T` must implement `CopySelector<Type=Zero>`. The conditions for this marker trait are that
-`T` is a copy type, that it has a fixed memory layout, and that it does not contain any reference.
+`T` can be copied bitwise (e.g., it is a [`Copy`] type), that it has a fixed memory layout, and that it does not contain any reference.
 If this happen vectors of `T` or boxed slices of `T` can be ε-copy deserialized
 using a reference to a slice of `T`.
 
@@ -95,15 +101,18 @@ just have to add `#[zero_copy]` to your structures (if you want them to be zero-
 and ε-serde will do the rest.
 
 */
-
 pub trait CopyType: Sized {
     type Copy: CopySelector;
 }
 
 /// Marker trait for zero-copy types. You should never implement
 /// this trait directly, but rather implement [`CopyType`] with `Copy=Zero`.
-pub trait ZeroCopy: CopyType<Copy = Zero> + Copy + MaxSizeOf {}
-impl<T: CopyType<Copy = Zero> + Copy + MaxSizeOf> ZeroCopy for T {}
+///
+/// Types implemented by hand must also implement [`ByteSwap`] and
+/// [`CheckBytes`], which have been added as supertraits in version 0.4 (see
+/// the [`crate::extending`] module).
+pub trait ZeroCopy: CopyType<Copy = Zero> + MaxSizeOf + ByteSwap + CheckBytes {}
+impl<T: CopyType<Copy = Zero> + MaxSizeOf + ByteSwap + CheckBytes> ZeroCopy for T {}
 
 /// Marker trait for deep-copy types. You should never implement
 /// this trait directly, but rather implement [`CopyType`] with `Copy=Deep`.
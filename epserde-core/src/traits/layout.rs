@@ -0,0 +1,159 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ * SPDX-FileCopyrightText: 2023 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+/*!
+
+Descriptors of the memory layout of zero-copy types.
+
+The [representation hash](crate::traits::ReprHash) of a zero-copy type
+summarizes its layout, but when two hashes differ (e.g., between two
+architectures) it does not tell where the difference is. [`ReprHash::layout`]
+returns instead a [`TypeLayout`] describing the size, the alignment, and the
+offset of each field, computed by the derive macros using
+[`core::mem::offset_of`]. Layouts can be exported in JSON format using
+[`TypeLayout::to_json`], stored as golden files, and compared across
+architectures.
+
+```rust
+use epserde::prelude::*;
+
+#[derive(Epserde, Clone, Copy)]
+#[repr(C)]
+#[zero_copy]
+struct Point {
+    tag: u8,
+    x: u32,
+}
+
+let layout = Point::layout();
+assert_eq!(layout.size, 8);
+assert_eq!(layout.fields[1].name, "x");
+assert_eq!(layout.fields[1].offset, 4);
+assert_eq!(layout.padding(), 3);
+```
+
+*/
+
+use crate::traits::ReprHash;
+
+#[cfg(all(feature = "alloc", not(feature = "std")))]
+use alloc::{format, string::String, vec::Vec};
+
+/// The memory layout of a type, as returned by [`ReprHash::layout`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct TypeLayout {
+    /// The name of the type, as returned by [`core::any::type_name`].
+    pub ty: String,
+    /// The size of the type.
+    pub size: usize,
+    /// The alignment of the type.
+    pub align: usize,
+    /// The fields of the type, in declaration order; empty for types
+    /// that are not zero-copy structures.
+    pub fields: Vec<FieldLayout>,
+}
+
+/// The layout of a field of a zero-copy structure.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct FieldLayout {
+    /// The name of the field.
+    pub name: String,
+    /// The offset of the field in the structure.
+    pub offset: usize,
+    /// The layout of the type of the field.
+    pub layout: TypeLayout,
+}
+
+impl TypeLayout {
+    /// Return the layout of a type without fields.
+    pub fn of<T>() -> Self {
+        Self {
+            ty: core::any::type_name::<T>().into(),
+            size: core::mem::size_of::<T>(),
+            align: core::mem::align_of::<T>(),
+            fields: Vec::new(),
+        }
+    }
+
+    /// Return the layout of a zero-copy structure given the name, offset,
+    /// and layout of its fields.
+    ///
+    /// This method is used by the derive macros.
+    pub fn new_struct<T, const N: usize>(fields: [(&str, usize, TypeLayout); N]) -> Self {
+        Self {
+            fields: fields
+                .into_iter()
+                .map(|(name, offset, layout)| FieldLayout {
+                    name: name.into(),
+                    offset,
+                    layout,
+                })
+                .collect(),
+            ..Self::of::<T>()
+        }
+    }
+
+    /// Return the number of padding bytes of the type, that is, the number
+    /// of bytes that do not belong to any field, recursively.
+    ///
+    /// Types without fields have no padding.
+    pub fn padding(&self) -> usize {
+        if self.fields.is_empty() {
+            return 0;
+        }
+        self.size
+            - self
+                .fields
+                .iter()
+                .map(|field| field.layout.size - field.layout.padding())
+                .sum::<usize>()
+    }
+
+    /// Return a JSON representation of the layout.
+    ///
+    /// The representation is an object with keys `ty`, `size`, `align`,
+    /// and `fields`; the latter is an array of objects with keys `name`,
+    /// `offset`, and `layout`, which is in turn the representation of the
+    /// layout of the field.
+    pub fn to_json(&self) -> String {
+        let mut result = String::new();
+        self.write_json(&mut result, 0);
+        result.push('\n');
+        result
+    }
+
+    fn write_json(&self, result: &mut String, indent: usize) {
+        let pad = " ".repeat(indent);
+        result.push_str(&format!(
+            "{{\n{pad}  \"ty\": {},\n{pad}  \"size\": {},\n{pad}  \"align\": {},\n{pad}  \"fields\": [",
+            crate::ser::json_string(&self.ty),
+            self.size,
+            self.align,
+        ));
+        for (i, field) in self.fields.iter().enumerate() {
+            result.push_str(&format!(
+                "{}\n{pad}    {{\"name\": {}, \"offset\": {}, \"layout\": ",
+                if i == 0 { "" } else { "," },
+                crate::ser::json_string(&field.name),
+                field.offset,
+            ));
+            field.layout.write_json(result, indent + 4);
+            result.push('}');
+        }
+        if !self.fields.is_empty() {
+            result.push_str(&format!("\n{pad}  "));
+        }
+        result.push_str(&format!("]\n{pad}}}"));
+    }
+}
+
+/// Return the [layout](TypeLayout) of a type.
+///
+/// This is a convenience function calling [`ReprHash::layout`].
+pub fn type_layout<T: ReprHash>() -> TypeLayout {
+    T::layout()
+}
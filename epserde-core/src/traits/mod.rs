@@ -9,7 +9,7 @@
 
 Basic traits that must be implemented by all types using ε-serde.
 
-If you use the procedural macro [`Epserde`](epserde_derive::Epserde), you do not
+If you use the procedural macro [`Epserde`](https://docs.rs/epserde-derive/latest/epserde_derive/derive.Epserde.html), you do not
 need to worry about these traits—they will be implemented for you.
 
 */
@@ -19,3 +19,12 @@ pub use type_info::*;
 
 pub mod copy_type;
 pub use copy_type::*;
+
+pub mod byte_swap;
+pub use byte_swap::*;
+
+pub mod check_bytes;
+pub use check_bytes::*;
+
+pub mod layout;
+pub use layout::*;
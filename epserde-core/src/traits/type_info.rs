@@ -0,0 +1,279 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ * SPDX-FileCopyrightText: 2023 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+/*!
+
+Traits computing information about a type.
+
+*/
+
+use crate::pad_align_to;
+use crate::traits::TypeLayout;
+use core::hash::{Hash, Hasher};
+
+#[cfg(all(feature = "alloc", not(feature = "std")))]
+use alloc::{string::String, vec::Vec};
+
+/// Recursively compute a type hash for a type.
+///
+/// [`TypeHash::type_hash`] is a recursive function that computes information
+/// about a type. It is used to
+/// check that the type of the data being deserialized matches
+/// syntactically the type of the data that was written.
+///
+/// The type hasher should store information about the name and the type
+/// of the fields of a type, and the name of the type itself.
+pub trait TypeHash {
+    /// Accumulate type information in `hasher`.
+    fn type_hash(hasher: &mut impl core::hash::Hasher);
+
+    /// Call [`TypeHash::type_hash`] on a value.
+    fn type_hash_val(&self, hasher: &mut impl core::hash::Hasher) {
+        Self::type_hash(hasher);
+    }
+
+    /// Return the [shape](TypeShape) of the type, used by
+    /// [structural compatibility checks](crate::compat).
+    ///
+    /// The default implementation returns a leaf containing the type hash;
+    /// the derive macros return a structure for deep-copy structures.
+    fn type_shape() -> TypeShape {
+        let mut hasher = xxhash_rust::xxh3::Xxh3::new();
+        Self::type_hash(&mut hasher);
+        TypeShape::Leaf(hasher.finish())
+    }
+}
+
+/// Recursively compute a representational hash for a type.
+///
+/// [`ReprHash::repr_hash`] is a recursive function that computes
+/// representation information about a zero-copy type. It is used to
+/// check that the the alignment and the representation data
+/// of the data being deserialized.
+///
+/// More precisely, at each call a zero-copy type look at `offset_of`,
+/// assuming that the type is stored at that offset in the structure,
+/// hashes in the padding necessary to make `offset_of` a multiple of
+/// [`core::mem::align_of`] the type, hashes in the type size, and
+/// finally increases `offset_of` by [`core::mem::size_of`] the type.
+pub trait ReprHash {
+    /// Accumulate representional information in `hasher` assuming to
+    /// be positioned at `offset_of`.
+    fn repr_hash(_hasher: &mut impl core::hash::Hasher, _offset_of: &mut usize);
+
+    /// Call [`ReprHash::repr_hash`] on a value.
+    fn repr_hash_val(&self, hasher: &mut impl core::hash::Hasher, offset_of: &mut usize) {
+        Self::repr_hash(hasher, offset_of);
+    }
+
+    /// Return the representational [shape](TypeShape) of the type, used by
+    /// [structural compatibility checks](crate::compat).
+    ///
+    /// The default implementation returns a leaf containing the
+    /// representation hash computed at offset zero; the derive macros
+    /// return a structure for deep-copy structures.
+    fn repr_shape() -> TypeShape {
+        TypeShape::Leaf(repr_hash_of::<Self>())
+    }
+
+    /// Return the memory [layout](TypeLayout) of the type.
+    ///
+    /// The default implementation returns a layout without fields; the
+    /// derive macros return the offset and the layout of each field for
+    /// zero-copy structures.
+    fn layout() -> TypeLayout
+    where
+        Self: Sized,
+    {
+        TypeLayout::of::<Self>()
+    }
+}
+
+/// The structure of a type, as seen by
+/// [structural compatibility checks](crate::compat).
+///
+/// Shapes are returned by [`TypeHash::type_shape`] and
+/// [`ReprHash::repr_shape`]: deep-copy structures are described field by
+/// field, whereas all other types are opaque leaves described by a hash.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum TypeShape {
+    /// A type described by its type or representation hash.
+    Leaf(u64),
+    /// A deep-copy structure with the given name and named fields.
+    Struct {
+        name: String,
+        fields: Vec<(String, TypeShape)>,
+    },
+}
+
+impl TypeShape {
+    /// Return the shape of a deep-copy structure.
+    ///
+    /// This method is used by the derive macros.
+    pub fn new_struct<const N: usize>(name: &str, fields: [(&str, TypeShape); N]) -> Self {
+        TypeShape::Struct {
+            name: name.into(),
+            fields: fields
+                .into_iter()
+                .map(|(field, shape)| (field.into(), shape))
+                .collect(),
+        }
+    }
+}
+
+/// A function providing a reasonable default
+/// implementation of [`ReprHash::repr_hash`] for basic sized types.
+pub(crate) fn std_repr_hash<T>(hasher: &mut impl core::hash::Hasher, offset_of: &mut usize) {
+    let padding = pad_align_to(*offset_of, core::mem::align_of::<T>());
+    padding.hash(hasher);
+    core::mem::size_of::<T>().hash(hasher);
+    *offset_of += padding;
+    *offset_of += core::mem::size_of::<T>();
+}
+
+/// A trait providing the maximum size of a primitive field in a type
+/// maximized with [`core::mem::align_of`].
+///
+/// We use the value returned by [`MaxSizeOf::max_size_of`]
+/// to generate padding before storing a zero-copy type. Note that this
+/// is different from the padding used to align the same type inside
+/// a struct, which is not under our control and is
+/// given by [`core::mem::align_of`].
+///
+/// In this way we increase interoperability between architectures
+/// with different alignment requirements for the same types (e.g.,
+/// 4 or 8 bytes for `u64`).
+///
+/// By maximizing with [`core::mem::align_of`] we ensure that
+/// we provide sufficient alignment in case the attribute `repr(align(N))`
+/// was specified.
+pub trait MaxSizeOf: Sized {
+    /// Whether the layout of the type depends on the pointer width, that
+    /// is, whether the type contains `usize` or `isize` values.
+    ///
+    /// Data containing such a type as zero-copy data cannot be read on an
+    /// architecture with a pointer width different from that of the
+    /// architecture on which it was written.
+    const WIDTH_DEPENDENT: bool;
+
+    fn max_size_of() -> usize;
+}
+
+/// Compute the type hash and the representation hash of a type,
+/// as written in the header of serialized data.
+pub fn compute_type_hashes<T: TypeHash + ReprHash + ?Sized>() -> (u64, u64) {
+    (type_hash_of::<T>(), repr_hash_of::<T>())
+}
+
+/// A hasher that hashes `usize` and `isize` values as 64-bit values.
+///
+/// On 64-bit architectures the hash is the same as that of the
+/// wrapped hasher; on other architectures, sizes and offsets hashed by
+/// [`ReprHash::repr_hash`] yield the same hash as on 64-bit architectures.
+struct WidthIndependentHasher<H>(H);
+
+impl<H: core::hash::Hasher> core::hash::Hasher for WidthIndependentHasher<H> {
+    fn finish(&self) -> u64 {
+        self.0.finish()
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        self.0.write(bytes);
+    }
+
+    fn write_usize(&mut self, i: usize) {
+        self.0.write_u64(i as u64);
+    }
+
+    fn write_isize(&mut self, i: isize) {
+        self.0.write_i64(i as i64);
+    }
+}
+
+/// Return the representation hash of a type, as written in the header of
+/// serialized data.
+///
+/// The hash does not depend on the pointer width, but only on the
+/// layout of the type: thus, it is the same on all architectures on which
+/// the type has the same layout, and it can be checked also when reading
+/// data written on an architecture with a different pointer width.
+/// `usize` and `isize` values are hashed as if they were eight bytes wide,
+/// and zero-copy data containing them is rejected on such architectures
+/// (see [`MaxSizeOf::WIDTH_DEPENDENT`]).
+pub fn repr_hash_of<T: ReprHash + ?Sized>() -> u64 {
+    let mut hasher = WidthIndependentHasher(xxhash_rust::xxh3::Xxh3::new());
+    T::repr_hash(&mut hasher, &mut 0);
+    hasher.finish()
+}
+
+/// Return the type hash of a type, as written in the header of serialized
+/// data.
+///
+/// Differently from the representation hash, the type hash does not depend
+/// on the architecture, so it can be compared with a golden value in a
+/// regression test to guarantee that serialized data remains loadable
+/// (see [`assert_type_hash_stable`](crate::assert_type_hash_stable)).
+pub fn type_hash_of<T: TypeHash + ?Sized>() -> u64 {
+    let mut hasher = xxhash_rust::xxh3::Xxh3::new();
+    T::type_hash(&mut hasher);
+    hasher.finish()
+}
+
+#[cfg(feature = "std")]
+fn type_hashes_cache(
+) -> &'static std::sync::RwLock<std::collections::HashMap<core::any::TypeId, (u64, u64)>> {
+    static CACHE: std::sync::OnceLock<
+        std::sync::RwLock<std::collections::HashMap<core::any::TypeId, (u64, u64)>>,
+    > = std::sync::OnceLock::new();
+    CACHE.get_or_init(Default::default)
+}
+
+/// Return the type hash and the representation hash of a type,
+/// as written in the header of serialized data.
+///
+/// The hashes depend only on the type, so with the `std` feature they are
+/// computed only once per process and cached; in this way, we avoid walking
+/// recursively the type at each serialization or deserialization.
+/// The cache is keyed by the [`TypeId`](core::any::TypeId) of the type,
+/// which must thus be `'static`.
+/// You can use [`prewarm_type_hashes`] to populate the cache at startup.
+#[cfg(feature = "std")]
+pub fn type_hashes<T: TypeHash + ReprHash + ?Sized + 'static>() -> (u64, u64) {
+    let key = core::any::TypeId::of::<T>();
+    let cache = type_hashes_cache();
+    if let Some(hashes) = cache
+        .read()
+        .unwrap_or_else(std::sync::PoisonError::into_inner)
+        .get(&key)
+    {
+        return *hashes;
+    }
+    let hashes = compute_type_hashes::<T>();
+    cache
+        .write()
+        .unwrap_or_else(std::sync::PoisonError::into_inner)
+        .insert(key, hashes);
+    hashes
+}
+
+/// Return the type hash and the representation hash of a type,
+/// as written in the header of serialized data.
+///
+/// Without the `std` feature, this function just
+/// delegates to [`compute_type_hashes`].
+#[cfg(not(feature = "std"))]
+pub fn type_hashes<T: TypeHash + ReprHash + ?Sized + 'static>() -> (u64, u64) {
+    compute_type_hashes::<T>()
+}
+
+/// Compute and cache the hashes of a type, so that the first
+/// serialization or deserialization of the type does not have to.
+///
+/// See [`type_hashes`].
+pub fn prewarm_type_hashes<T: TypeHash + ReprHash + ?Sized + 'static>() {
+    type_hashes::<T>();
+}
@@ -0,0 +1,287 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ * SPDX-FileCopyrightText: 2023 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+/*!
+
+Streaming transformation of the elements of a field of serialized data.
+
+[`transform_field`] copies a file containing serialized data, applying a
+transformation to the elements of a named field containing a slice of
+zero-copy values (e.g., a `Vec<f32>` or a `Box<[f32]>`), without
+deserializing the data. The file is read and written sequentially, so only
+a small buffer is kept in memory, independently of the size of the file.
+
+Since elements are transformed in place, all offsets and lengths in the
+file are unchanged; if the file has a
+[checksum](crate::ser::Serialize::serialize_with_checksum), the checksum of
+the original payload is verified and the checksum of the new payload is
+written in the header.
+
+The position of the field in the file is obtained from the [`Schema`] of the
+file, which must describe the file exactly, such as the one returned by
+[`Serialize::serialize_with_schema`](crate::ser::Serialize::serialize_with_schema)
+when the file was written; for example, it can be stored next to the file
+using [`Schema::to_json`].
+
+```rust
+use epserde::prelude::*;
+
+#[derive(Epserde, Debug, PartialEq)]
+struct Embeddings {
+    ids: Vec<u64>,
+    values: Vec<f32>,
+}
+
+let data = Embeddings {
+    ids: vec![0, 1, 2],
+    values: vec![1.0, 2.0, 3.0],
+};
+let src = std::env::temp_dir().join("transform_doc_src.eps");
+let dst = std::env::temp_dir().join("transform_doc_dst.eps");
+let schema = data.serialize_with_schema(&mut std::fs::File::create(&src)?)?;
+
+let count = epserde::transform::transform_field(
+    &src,
+    &dst,
+    &schema,
+    "ROOT.values",
+    |x: &mut f32| *x *= 0.5,
+)?;
+assert_eq!(count, 3);
+
+let scaled = Embeddings::load_full(&dst)?;
+assert_eq!(scaled.ids, data.ids);
+assert_eq!(scaled.values, vec![0.5, 1.0, 1.5]);
+# std::fs::remove_file(&src)?;
+# std::fs::remove_file(&dst)?;
+# Ok::<(), anyhow::Error>(())
+```
+
+*/
+
+use crate::deser::{self, read_header, ReaderWithPos};
+use crate::ser::Schema;
+use crate::traits::ZeroCopy;
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::Path;
+use xxhash_rust::xxh3::Xxh3;
+
+/// The size in bytes of the buffers used to copy and transform data.
+const BUFFER_SIZE: usize = 1 << 20;
+
+/// Copy the file `src` to `dst`, applying `f` to all elements of the field
+/// `field`, and return the number of transformed elements.
+///
+/// `field` is the name of the field in `schema` (e.g., `ROOT.values` for
+/// the field `values` of the root structure); if the field is contained in
+/// a sequence (e.g., `ROOT.values.item` for a `Vec<Vec<f32>>`), the elements
+/// of all the instances of the field are transformed. The type of the elements
+/// of the field must be `V`.
+///
+/// An error is returned if the schema does not match the file or if the field
+/// is not a slice of elements of type `V`
+/// ([`Error::SchemaMismatch`](deser::Error::SchemaMismatch)), if the file is
+/// compressed ([`Error::WrongCodec`](deser::Error::WrongCodec)), or if the
+/// checksum of the original file is wrong
+/// ([`Error::ChecksumMismatch`](deser::Error::ChecksumMismatch)).
+pub fn transform_field<V: ZeroCopy + 'static>(
+    src: impl AsRef<Path>,
+    dst: impl AsRef<Path>,
+    schema: &Schema,
+    field: &str,
+    mut f: impl FnMut(&mut V),
+) -> deser::Result<usize> {
+    let mut src = BufReader::with_capacity(
+        BUFFER_SIZE,
+        File::open(src).map_err(deser::Error::FileOpenError)?,
+    );
+    let header = read_header(&mut ReaderWithPos::new(&mut src))?;
+    let start = src.stream_position()? as usize;
+    src.rewind()?;
+
+    if header.codec.is_some() {
+        return Err(deser::Error::WrongCodec {
+            expected: header.codec,
+            got: None,
+        });
+    }
+    let mismatch = |field: &str, reason: String| {
+        Err(deser::Error::SchemaMismatch {
+            field: field.to_string(),
+            reason,
+        })
+    };
+    match schema.0.iter().find(|row| row.field == "ROOT") {
+        Some(root) if root.ty == header.type_name => {}
+        _ => return mismatch("ROOT", format!("the data has type {}", header.type_name)),
+    }
+
+    let name = format!("{}.zero", field);
+    let mut rows = schema
+        .0
+        .iter()
+        .filter(|row| row.field == name)
+        .collect::<Vec<_>>();
+    if rows.is_empty() {
+        return mismatch(
+            field,
+            "no slice of zero-copy data has this name".to_string(),
+        );
+    }
+    rows.sort_by_key(|row| row.offset);
+
+    let size = core::mem::size_of::<V>();
+    for row in &rows {
+        if row.ty != core::any::type_name::<V>() {
+            return mismatch(
+                field,
+                format!(
+                    "the elements have type {} instead of {}",
+                    row.ty,
+                    core::any::type_name::<V>()
+                ),
+            );
+        }
+        if size == 0 || row.size % size != 0 {
+            return mismatch(
+                field,
+                format!("the size is not a multiple of the size of {}", row.ty),
+            );
+        }
+    }
+
+    let mut copier = Copier {
+        src,
+        dst: BufWriter::with_capacity(BUFFER_SIZE, File::create(dst)?),
+        pos: 0,
+        start,
+        end: header.checksum.map(|(_, len)| start + len),
+        old_hasher: Xxh3::new(),
+        new_hasher: Xxh3::new(),
+        buf: vec![0; BUFFER_SIZE],
+    };
+
+    copier.copy(start)?;
+    // Elements are read in a buffer of values of type V, so they are
+    // correctly aligned independently of their offset in the file.
+    let chunk_len = (BUFFER_SIZE / size).max(1);
+    let mut elements: Vec<V> = Vec::with_capacity(chunk_len);
+    // SAFETY: the capacity is at least chunk_len.
+    unsafe { core::ptr::write_bytes(elements.as_mut_ptr(), 0, chunk_len) };
+    let mut count = 0;
+    for row in rows {
+        if row.offset < copier.pos {
+            return mismatch(field, "the rows overlap".to_string());
+        }
+        copier.copy(row.offset)?;
+        let mut len = row.size / size;
+        while len > 0 {
+            let chunk = len.min(chunk_len);
+            // SAFETY: the memory is initialized, and zero-copy types can be
+            // read from their memory representation.
+            let bytes = unsafe {
+                core::slice::from_raw_parts_mut(elements.as_mut_ptr() as *mut u8, chunk * size)
+            };
+            copier.read(bytes)?;
+            let values = unsafe { core::slice::from_raw_parts_mut(elements.as_mut_ptr(), chunk) };
+            values.iter_mut().for_each(&mut f);
+            let bytes = unsafe {
+                core::slice::from_raw_parts(elements.as_ptr() as *const u8, chunk * size)
+            };
+            copier.write(bytes)?;
+            len -= chunk;
+            count += chunk;
+        }
+    }
+    copier.copy(usize::MAX)?;
+
+    let mut dst = copier.dst.into_inner().map_err(|err| err.into_error())?;
+    if let Some((expected, _)) = header.checksum {
+        let got = copier.old_hasher.digest();
+        if got != expected {
+            return Err(deser::Error::ChecksumMismatch { expected, got });
+        }
+        // The checksum precedes the length of the payload, which is unchanged
+        dst.seek(SeekFrom::Start(start as u64 - 16))?;
+        dst.write_all(&copier.new_hasher.digest().to_ne_bytes())?;
+    }
+    dst.flush()?;
+    Ok(count)
+}
+
+/// Sequential copy of a file, keeping track of the checksums of the original
+/// and of the new payload.
+struct Copier {
+    src: BufReader<File>,
+    dst: BufWriter<File>,
+    /// The current position in both files.
+    pos: usize,
+    /// The start of the payload.
+    start: usize,
+    /// The end of the payload, if the file has a checksum.
+    end: Option<usize>,
+    old_hasher: Xxh3,
+    new_hasher: Xxh3,
+    buf: Vec<u8>,
+}
+
+impl Copier {
+    /// Return the part of `buf`, starting at the current position, that is
+    /// covered by the checksum.
+    fn checked<'b>(&self, buf: &'b [u8]) -> &'b [u8] {
+        let Some(end) = self.end else {
+            return &[];
+        };
+        let from = self.start.max(self.pos) - self.pos;
+        let to = end.saturating_sub(self.pos).min(buf.len());
+        buf.get(from..to).unwrap_or_default()
+    }
+
+    /// Read from the original file.
+    fn read(&mut self, buf: &mut [u8]) -> deser::Result<()> {
+        self.src.read_exact(buf)?;
+        let checked = self.checked(buf);
+        self.old_hasher.update(checked);
+        Ok(())
+    }
+
+    /// Write to the new file, advancing the current position.
+    fn write(&mut self, buf: &[u8]) -> deser::Result<()> {
+        self.dst.write_all(buf)?;
+        let checked = self.checked(buf);
+        self.new_hasher.update(checked);
+        self.pos += buf.len();
+        Ok(())
+    }
+
+    /// Copy data unchanged up to the given position, or up to the end of the
+    /// original file.
+    fn copy(&mut self, to: usize) -> deser::Result<()> {
+        let mut buf = core::mem::take(&mut self.buf);
+        while self.pos < to {
+            let len = (to - self.pos).min(buf.len());
+            let (chunk, _) = buf.split_at_mut(len);
+            let read = self.src.read(chunk)?;
+            if read == 0 {
+                if to != usize::MAX {
+                    return Err(deser::Error::SchemaMismatch {
+                        field: "ROOT".to_string(),
+                        reason: "the data is shorter than the schema".to_string(),
+                    });
+                }
+                break;
+            }
+            let data = chunk.get(..read).ok_or(deser::Error::ReadError)?;
+            let checked = self.checked(data);
+            self.old_hasher.update(checked);
+            self.write(data)?;
+        }
+        self.buf = buf;
+        Ok(())
+    }
+}
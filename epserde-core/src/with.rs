@@ -0,0 +1,139 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ * SPDX-FileCopyrightText: 2023 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+/*!
+
+Custom serialization of individual fields of deep-copy structures.
+
+Fields whose type does not implement the ε-serde traits (e.g., because it
+comes from a third-party crate) can be serialized by annotating them with
+`#[epserde(with = "module")]`, where `module` is a path to a module
+providing the following functions, in which `T` is the type of the field:
+
+```ignore
+pub fn serialize(value: &T, backend: &mut impl WriteWithNames) -> ser::Result<()>;
+pub fn deserialize_full(backend: &mut impl ReadWithPos) -> deser::Result<T>;
+pub fn type_hash(hasher: &mut impl core::hash::Hasher);
+```
+
+The first two functions must write and read the same data, usually by
+delegating to the methods of the backend and to the implementations of
+[`SerializeInner`] and [`DeserializeInner`](crate::deser::DeserializeInner)
+of some other type; the third function is the contribution of the field
+to the [type hash](TypeHash) of the structure, and it must change whenever
+the serialized data changes.
+
+Fields annotated with `with` are always fully deserialized, even in an ε-copy
+deserialization, and their type is not replaced with its
+[deserialization type](crate::deser::DeserializeInner::DeserType).
+They do not contribute to the [representation hash](ReprHash) of the
+structure, so any data written by `serialize` must be independent of the
+architecture, or the type hash must take care of the differences.
+
+```rust
+use epserde::prelude::*;
+use epserde::ser::WriteWithNames;
+use epserde::deser::ReadWithPos;
+
+// A type we cannot implement the ε-serde traits for
+mod foreign {
+    pub struct Ratio {
+        pub num: i64,
+        pub den: i64,
+    }
+}
+
+mod ratio {
+    use super::foreign::Ratio;
+    use epserde::prelude::*;
+    use epserde::ser::WriteWithNames;
+    use epserde::deser::{DeserializeInner, ReadWithPos};
+
+    pub fn serialize(value: &Ratio, backend: &mut impl WriteWithNames) -> ser::Result<()> {
+        backend.write("ratio", &(value.num, value.den))
+    }
+
+    pub fn deserialize_full(backend: &mut impl ReadWithPos) -> deser::Result<Ratio> {
+        let (num, den) = <(i64, i64)>::_deserialize_full_inner(backend)?;
+        Ok(Ratio { num, den })
+    }
+
+    pub fn type_hash(hasher: &mut impl core::hash::Hasher) {
+        use core::hash::Hash;
+        "Ratio".hash(hasher);
+    }
+}
+
+#[derive(Epserde)]
+struct Data<A> {
+    values: A,
+    #[epserde(with = "ratio")]
+    scale: foreign::Ratio,
+}
+
+# fn main() {
+let data = Data { values: vec![1, 2, 3], scale: foreign::Ratio { num: 2, den: 3 } };
+let mut cursor = epserde::new_aligned_cursor();
+data.serialize(&mut cursor).unwrap();
+let bytes = cursor.into_inner();
+let eps = <Data<Vec<i32>>>::deserialize_eps(&bytes).unwrap();
+assert_eq!(eps.values, [1, 2, 3]);
+assert_eq!(eps.scale.den, 3);
+# }
+```
+
+*/
+
+use crate::ser::{self, SerializeInner, WriteWithNames};
+use crate::traits::*;
+use core::hash::Hasher;
+use core::marker::PhantomData;
+
+/// Serialization of the field of index `FIELD`, of type `T`, annotated
+/// with `#[epserde(with = "...")]`.
+///
+/// This trait is implemented by the derive macros on the structure
+/// containing the field, delegating to the functions of the module; it
+/// should not be implemented directly.
+pub trait SerializeWith<T, const FIELD: usize> {
+    fn serialize(value: &T, backend: &mut impl WriteWithNames) -> ser::Result<()>;
+}
+
+/// The field of index `FIELD`, of type `T`, of a structure `S`.
+///
+/// This type is used by the code generated for fields annotated with
+/// `#[epserde(with = "...")]`, so that serialization happens through
+/// [`WriteWithNames::write`] and the field is recorded in the
+/// [schema](crate::ser::Schema) under its name.
+pub struct With<'a, S, T, const FIELD: usize>(&'a T, PhantomData<fn() -> S>);
+
+impl<'a, S: SerializeWith<T, FIELD>, T, const FIELD: usize> With<'a, S, T, FIELD> {
+    pub fn new(value: &'a T) -> Self {
+        Self(value, PhantomData)
+    }
+}
+
+impl<S: SerializeWith<T, FIELD>, T, const FIELD: usize> SerializeInner for With<'_, S, T, FIELD> {
+    const IS_ZERO_COPY: bool = false;
+    const ZERO_COPY_MISMATCH: bool = false;
+    const MAX_FIELD_ALIGN: usize = 1;
+
+    fn _serialize_inner(&self, backend: &mut impl WriteWithNames) -> ser::Result<()> {
+        S::serialize(self.0, backend)
+    }
+}
+
+/// Return a leaf [shape](TypeShape) whose hash is computed by `hash`.
+///
+/// This function is used by the code generated for fields annotated with
+/// `#[epserde(with = "...")]` to compute their shapes from the type hash
+/// provided by the module, and from an empty representation hash.
+pub fn leaf_shape(hash: impl FnOnce(&mut dyn Hasher)) -> TypeShape {
+    let mut hasher = xxhash_rust::xxh3::Xxh3::new();
+    hash(&mut hasher);
+    TypeShape::Leaf(hasher.finish())
+}
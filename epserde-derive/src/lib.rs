@@ -12,10 +12,10 @@ Derive procedural macros for the [`epserde`](https://crates.io/crates/epserde) c
 */
 
 use proc_macro::TokenStream;
-use quote::{quote, ToTokens};
+use quote::{quote, quote_spanned, ToTokens};
 use syn::{
-    parse_macro_input, punctuated::Punctuated, token, BoundLifetimes, Data, DeriveInput,
-    GenericParam, LifetimeParam, PredicateType, WhereClause, WherePredicate,
+    parse_macro_input, punctuated::Punctuated, spanned::Spanned, token, BoundLifetimes, Data,
+    DeriveInput, GenericParam, LifetimeParam, PredicateType, WhereClause, WherePredicate,
 };
 
 /// Pre-parsed information for the derive macros.
@@ -110,11 +110,25 @@ impl CommonDeriveInput {
 
 /// Return whether the struct has attributes `repr(C)`, `zero_copy`, and `deep_copy`.
 ///
-/// Performs coherence checks (e.g., to be `zero_copy` the struct must be `repr(C)`).
+/// Performs coherence checks (e.g., to be `zero_copy` the struct must be `repr(C)`,
+/// possibly with other representation hints such as `packed`, and `repr(packed)`
+/// structs must be `zero_copy`).
 fn check_attrs(input: &DeriveInput) -> (bool, bool, bool) {
-    let is_repr_c = input.attrs.iter().any(|x| {
-        x.meta.path().is_ident("repr") && x.meta.require_list().unwrap().tokens.to_string() == "C"
-    });
+    // The representation hints, e.g., `C` and `packed` for `repr(C, packed(2))`
+    let reprs = input
+        .attrs
+        .iter()
+        .filter(|x| x.meta.path().is_ident("repr"))
+        .flat_map(|x| {
+            x.parse_args_with(Punctuated::<syn::Meta, syn::Token![,]>::parse_terminated)
+                .unwrap()
+        })
+        .collect::<Vec<_>>();
+    // Empty enums cannot be repr(C), but they have no values, and thus no
+    // layout, so they can be used, e.g., as type-level placeholders
+    let is_empty_enum = matches!(&input.data, Data::Enum(e) if e.variants.is_empty());
+    let is_repr_c = is_empty_enum || reprs.iter().any(|x| x.path().is_ident("C"));
+    let is_packed = reprs.iter().any(|x| x.path().is_ident("packed"));
     let is_zero_copy = input
         .attrs
         .iter()
@@ -135,31 +149,361 @@ fn check_attrs(input: &DeriveInput) -> (bool, bool, bool) {
             input.ident
         );
     }
+    // Deep-copy code takes references to fields, which might be misaligned
+    if is_packed && !is_zero_copy {
+        panic!(
+            "Type {} is repr(packed), but it is not declared as zero copy",
+            input.ident
+        );
+    }
 
     (is_repr_c, is_zero_copy, is_deep_copy)
 }
 
+/// Add to the deserialization where clause the bounds on the deserialization
+/// types of the generic types of the fields, derived from the bounds of
+/// the generic types themselves.
+///
+/// For example, if a type parameter `T` has the bound `AsRef<[u32]>` and it
+/// is the type of a field, the deserialization type of the type will
+/// have `<T as DeserializeInner>::DeserType<'_>` in place of `T`, so we need to add
+/// the bound `for<'a> <T as DeserializeInner>::DeserType<'a>: AsRef<[u32]>`.
+///
+/// TODO: we presently handle only inlined bounds, and not bounds in a where clause.
+fn add_deser_type_bounds(
+    where_clause_des: &mut WhereClause,
+    generics: &syn::Generics,
+    generic_types: &[String],
+) {
+    generics.params.iter().for_each(|param| {
+        if let GenericParam::Type(t) = param {
+            let ty = &t.ident;
+
+            // Skip generics not involved in deserialization type substitution.
+            if t.bounds.is_empty() || !generic_types.iter().any(|x| *ty == x) {
+                return;
+            }
+
+            // add a lifetime so we express bounds on DeserType
+            let mut lifetimes = Punctuated::new();
+            lifetimes.push(GenericParam::Lifetime(LifetimeParam {
+                attrs: vec![],
+                lifetime: syn::Lifetime::new("'epserde_desertype", proc_macro2::Span::call_site()),
+                colon_token: None,
+                bounds: Punctuated::new(),
+            }));
+            // add that the DeserType is a DeserializeInner
+            where_clause_des
+                .predicates
+                .push(WherePredicate::Type(PredicateType {
+                    lifetimes: Some(BoundLifetimes {
+                        for_token: token::For::default(),
+                        lt_token: token::Lt::default(),
+                        lifetimes,
+                        gt_token: token::Gt::default(),
+                    }),
+                    bounded_ty: syn::parse_quote!(
+                        <#ty as epserde::deser::DeserializeInner>::DeserType<'epserde_desertype>
+                    ),
+                    colon_token: token::Colon::default(),
+                    bounds: t.bounds.clone(),
+                }));
+        }
+    });
+}
+
+/// Add to the serialization and deserialization where clauses of a
+/// zero-copy type the bounds on its type parameters.
+///
+/// The type parameters of a zero-copy type must be zero-copy, and since the
+/// deserialization type is a reference to the type itself, they must also be
+/// `'static`. With these bounds, type parameters appearing only inside other
+/// types (e.g., as `[T; N]`) need no decoration.
+fn add_zero_copy_bounds(
+    where_clause_ser: &mut WhereClause,
+    where_clause_des: &mut WhereClause,
+    generics: &syn::Generics,
+) {
+    for param in generics.type_params() {
+        let ident = &param.ident;
+        where_clause_ser
+            .predicates
+            .push(syn::parse_quote!(#ident: epserde::traits::ZeroCopy));
+        where_clause_des
+            .predicates
+            .push(syn::parse_quote!(#ident: epserde::traits::ZeroCopy + 'static));
+    }
+}
+
+/// A field-level override of the ε-copy deserialization type, specified with
+/// `#[epserde(deser_type = "...", deser_with = "...")]`.
+struct DeserTypeOverride {
+    /// The ε-copy deserialization type of the field, in which the lifetime
+    /// `'epserde` has been replaced with the lifetime of the deserialization type.
+    deser_type: syn::Type,
+    /// A path to a function ε-copy deserializing `deser_type` from a
+    /// [`SliceWithPos`](epserde::deser::SliceWithPos), if specified.
+    deser_with: Option<syn::Path>,
+}
+
+/// Replace the lifetime `'epserde` with `'epserde_desertype`.
+fn rename_epserde_lifetime(tokens: proc_macro2::TokenStream) -> proc_macro2::TokenStream {
+    use proc_macro2::{Group, TokenTree};
+    let mut after_quote = false;
+    tokens
+        .into_iter()
+        .map(|token| {
+            let token = match token {
+                TokenTree::Group(group) => {
+                    let mut new_group =
+                        Group::new(group.delimiter(), rename_epserde_lifetime(group.stream()));
+                    new_group.set_span(group.span());
+                    TokenTree::Group(new_group)
+                }
+                TokenTree::Ident(ident) if after_quote && ident == "epserde" => {
+                    TokenTree::Ident(syn::Ident::new("epserde_desertype", ident.span()))
+                }
+                token => token,
+            };
+            after_quote = matches!(&token, TokenTree::Punct(punct) if punct.as_char() == '\'');
+            token
+        })
+        .collect()
+}
+
+/// The `epserde` attributes of a type.
+#[derive(Default)]
+struct TypeAttrs {
+    /// Whether the type has the attribute `#[epserde(deny_deep_copy)]`.
+    deny_deep_copy: bool,
+    /// The name of the constructor specified with `#[epserde(constructor)]`
+    /// or `#[epserde(constructor = "...")]`, if any.
+    constructor: Option<syn::Ident>,
+}
+
+/// Parse the `epserde` attributes of a type.
+fn parse_type_attrs(input: &DeriveInput) -> TypeAttrs {
+    let mut attrs = TypeAttrs::default();
+    for attr in input.attrs.iter().filter(|x| x.path().is_ident("epserde")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("deny_deep_copy") {
+                attrs.deny_deep_copy = true;
+                Ok(())
+            } else if meta.path.is_ident("constructor") {
+                attrs.constructor = Some(if meta.input.peek(token::Eq) {
+                    meta.value()?
+                        .parse::<syn::LitStr>()?
+                        .parse::<syn::Ident>()?
+                } else {
+                    syn::Ident::new("new", proc_macro2::Span::call_site())
+                });
+                Ok(())
+            } else {
+                Err(meta.error("unsupported epserde attribute"))
+            }
+        })
+        .unwrap_or_else(|err| panic!("{}", err));
+    }
+    attrs
+}
+
+/// The `epserde` attributes of a field.
+struct FieldAttrs {
+    /// The overridden ε-copy deserialization type, if any.
+    deser_type_override: Option<DeserTypeOverride>,
+    /// A path to the codec compressing the field, specified with
+    /// `#[epserde(compress = "...")]`, if any.
+    compress: Option<syn::Path>,
+    /// A path to the module serializing the field, specified with
+    /// `#[epserde(with = "...")]`, if any.
+    with: Option<syn::Path>,
+    /// Whether the field has the attribute `#[epserde(ignore_context)]`.
+    ignore_context: bool,
+    /// Whether the field has the attribute `#[epserde(indexed)]`.
+    indexed: bool,
+}
+
+impl FieldAttrs {
+    fn is_empty(&self) -> bool {
+        self.deser_type_override.is_none()
+            && self.compress.is_none()
+            && self.with.is_none()
+            && !self.ignore_context
+            && !self.indexed
+    }
+}
+
+/// Parse the `epserde` attributes of a field.
+fn parse_field_attrs(field: &syn::Field) -> FieldAttrs {
+    let mut deser_type = None;
+    let mut deser_with = None;
+    let mut compress = None;
+    let mut with = None;
+    let mut ignore_context = false;
+    let mut indexed = false;
+    for attr in field.attrs.iter().filter(|x| x.path().is_ident("epserde")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("ignore_context") {
+                ignore_context = true;
+                return Ok(());
+            }
+            if meta.path.is_ident("indexed") {
+                indexed = true;
+                return Ok(());
+            }
+            let value = meta.value()?.parse::<syn::LitStr>()?;
+            if meta.path.is_ident("deser_type") {
+                let tokens = rename_epserde_lifetime(value.value().parse()?);
+                deser_type = Some(syn::parse2::<syn::Type>(tokens)?);
+                Ok(())
+            } else if meta.path.is_ident("deser_with") {
+                deser_with = Some(value.parse::<syn::Path>()?);
+                Ok(())
+            } else if meta.path.is_ident("compress") {
+                compress = Some(value.parse::<syn::Path>()?);
+                Ok(())
+            } else if meta.path.is_ident("with") {
+                with = Some(value.parse::<syn::Path>()?);
+                Ok(())
+            } else {
+                Err(meta.error("unsupported epserde attribute"))
+            }
+        })
+        .unwrap_or_else(|err| panic!("{}", err));
+    }
+
+    let deser_type_override = match (deser_type, deser_with) {
+        (None, None) => None,
+        (None, Some(_)) => panic!("Attribute deser_with requires attribute deser_type"),
+        (Some(deser_type), deser_with) => Some(DeserTypeOverride {
+            deser_type,
+            deser_with,
+        }),
+    };
+    if deser_type_override.is_some() && compress.is_some() {
+        panic!("Attribute compress cannot be used together with attribute deser_type");
+    }
+    if with.is_some() && (deser_type_override.is_some() || compress.is_some()) {
+        panic!("Attribute with cannot be used together with attributes deser_type or compress");
+    }
+    if indexed && (deser_type_override.is_some() || compress.is_some() || with.is_some()) {
+        panic!(
+            "Attribute indexed cannot be used together with attributes deser_type, compress, or with"
+        );
+    }
+
+    FieldAttrs {
+        deser_type_override,
+        compress,
+        with,
+        ignore_context,
+        indexed,
+    }
+}
+
 /// Generate an ε-serde implementation for custom types.
 ///
 /// It generates implementations for the traits `CopyType`,
 /// `MaxSizeOf`, `TypeHash`, `ReprHash`, `SerializeInner`,
 /// and `DeserializeInner`.
 ///
-/// Presently we do not support unions.
+/// Structs and enums are supported; unions are supported only if they are
+/// declared `zero_copy`, in which case they are treated as opaque blocks of bytes.
+/// Deep-copy enums are serialized by writing the index of the variant as a `usize`
+/// tag, followed by the fields of the variant; as in the case of structs, their
+/// ε-copy deserialization type is the same enum in which type parameters
+/// are replaced by their deserialization types.
+///
+/// Since the ε-copy deserialization type of a deep-copy type is the type itself
+/// with different type parameters, deriving [`Clone`] and [`Copy`] on the type
+/// makes its ε-copy deserialization type [`Clone`] and [`Copy`], too, whenever
+/// the deserialization types of the parameters are (e.g., `&[T]` and `&str`),
+/// so views can be passed around by value.
 ///
 /// The attribute `zero_copy` can be used to generate an implementation for a zero-copy
 /// type, but the type must be `repr(C)` and all fields must be zero-copy.
 ///
+/// Zero-copy structs can also be `repr(C, packed)` (or `repr(C, packed(N))`),
+/// which is useful, for example, for telemetry records; the representation hints
+/// are part of the representation hash. Since the fields of a packed struct
+/// might be misaligned, references to them cannot be taken: the fields of the
+/// ε-copy deserialization type, which is a reference to the struct, must be
+/// read by value (e.g., `let x = view.x;` or `{ view.x }`). For the same
+/// reason, `repr(packed)` structs must be declared `zero_copy`.
+///
 /// If you do not specify `zero_copy`, the macro assumes your structure is deep-copy.
 /// However, if you have a structure that could be zero-copy, but has no attribute,
 /// a warning will be issued every time you serialize. The warning can be silenced adding
 /// the explicity attribute `deep_copy`.
-#[proc_macro_derive(Epserde, attributes(zero_copy, deep_copy))]
+///
+/// In a deep-copy struct, the ε-copy deserialization type of a field whose type is a
+/// type parameter can be replaced by a hand-written type using the field attribute
+/// `#[epserde(deser_type = "MyView<'epserde>", deser_with = "my_view")]`, where
+/// `'epserde` is the lifetime of the deserialization type. The function `my_view`
+/// must have signature `fn my_view<'a>(backend: &mut SliceWithPos<'a>) ->
+/// deser::Result<MyView<'a>>`, and must consume exactly the bytes written when
+/// serializing the field. If `deser_with` is omitted, the standard ε-copy
+/// deserialization of the field is converted into the hand-written type using [`Into`].
+/// The serialized data and the full-copy deserialization are not affected.
+///
+/// In a deep-copy struct, the field attribute `#[epserde(compress = "C")]`, where `C`
+/// implements `epserde::compress::Codec`, compresses the field with the codec `C`.
+/// Compressed fields are always fully deserialized, even in an ε-copy
+/// deserialization; see the `epserde::compress` module for more information.
+///
+/// In a deep-copy struct, the field attribute `#[epserde(with = "module")]` serializes
+/// the field using the functions `serialize`, `deserialize_full`, and `type_hash` of
+/// `module`, making it possible to use types that do not implement the ε-serde traits.
+/// Such fields are always fully deserialized; see the `epserde::with` module for more
+/// information.
+///
+/// The deserialization context (see `epserde::deser::Context`), if any, is forwarded
+/// to the deserialization of all fields. In a deep-copy struct, the field attribute
+/// `#[epserde(ignore_context)]` deserializes the field without context, which is
+/// useful when the field contains types whose deserialization would otherwise use
+/// a context meant for another part of the structure.
+///
+/// In a deep-copy struct, the field attribute `#[epserde(indexed)]` on a field whose
+/// type is a type parameter instantiated with a sequence of deep-copy values (e.g.,
+/// a `Vec`) serializes the field with a table containing the offset of each item.
+/// The deserialization type of the type parameter is replaced by an
+/// `epserde::indexed::IndexedView`, which deserializes an item only when it is
+/// accessed; see the `epserde::indexed` module for more information.
+///
+/// The struct attribute `#[epserde(deny_deep_copy)]` makes compilation fail if the
+/// type of a field is deep-copy. Fields whose type is a type parameter are bound
+/// to be zero-copy, so the check is deferred to the instantiation of the type.
+/// This is useful to avoid silent performance regressions in structures that
+/// should contain only zero-copy data.
+///
+/// The struct attribute `#[epserde(constructor)]` generates a constructor `new`
+/// taking the fields of the struct in order (`#[epserde(constructor = "name")]`
+/// uses a different name). The constructor is generic in the type parameters,
+/// so it can be used to build directly the ε-copy deserialization type of the
+/// struct from borrowed parts (e.g., in unit tests of functions accepting
+/// a deserialization type), without serializing and deserializing data.
+#[proc_macro_derive(Epserde, attributes(zero_copy, deep_copy, epserde))]
 pub fn epserde_derive(input: TokenStream) -> TokenStream {
     // Cloning input for type hash
     let input_for_typehash = input.clone();
     let derive_input = parse_macro_input!(input as DeriveInput);
     let (is_repr_c, is_zero_copy, is_deep_copy) = check_attrs(&derive_input);
+    let TypeAttrs {
+        deny_deep_copy,
+        constructor,
+    } = parse_type_attrs(&derive_input);
+    if deny_deep_copy && !matches!(derive_input.data, Data::Struct(_)) {
+        panic!(
+            "Attribute deny_deep_copy of {} is supported only on structs",
+            derive_input.ident
+        );
+    }
+    if constructor.is_some() && !matches!(derive_input.data, Data::Struct(_)) {
+        panic!(
+            "Attribute constructor of {} is supported only on structs",
+            derive_input.ident
+        );
+    }
 
     // Common values between serialize and deserialize
     let CommonDeriveInput {
@@ -191,8 +535,27 @@ pub fn epserde_derive(input: TokenStream) -> TokenStream {
             let mut non_generic_types = vec![];
             let mut generic_fields = vec![];
             let mut generic_types = vec![];
+            // For each field, whether it could be zero-copy.
+            let mut fields_zero_copy = vec![];
+            // For each field, the maximum alignment of its zero-copy data.
+            let mut fields_align = vec![];
+            let mut ser_exprs = vec![];
+            let mut full_exprs = vec![];
+            // Statements fully deserializing fields in place.
+            let mut into_exprs = vec![];
+            // Types of the fields that must implement the ε-serde traits.
+            let mut bounded_types = vec![];
+            // Implementations of SerializeWith for fields annotated with `with`.
+            let mut with_impls = vec![];
+            // Types of the fields annotated with `indexed`.
+            let mut indexed_types = vec![];
+            let where_clause_input = &derive_input.generics.where_clause;
 
             // Scan the struct to find which fields are generics, and which are not.
+            // Compressed fields and fields serialized with a module are always
+            // fully deserialized, so their type is never replaced by its
+            // deserialization type. Indexed fields are serialized with an
+            // offset table, and their type is replaced by an IndexedView.
             s.fields.iter().enumerate().for_each(|(field_idx, field)| {
                 let ty = &field.ty;
                 let field_name = field
@@ -201,28 +564,219 @@ pub fn epserde_derive(input: TokenStream) -> TokenStream {
                     .map(|x| x.to_token_stream())
                     .unwrap_or_else(|| syn::Index::from(field_idx).to_token_stream());
 
-                if generics_names_raw.contains(&ty.to_token_stream().to_string()) {
-                    generic_fields.push(field_name.clone());
-                    generic_types.push(ty);
-                } else {
-                    non_generic_fields.push(field_name.clone());
-                    non_generic_types.push(ty);
+                let attrs = parse_field_attrs(field);
+                let indexed = attrs.indexed;
+                match (attrs.compress, attrs.with) {
+                    _ if indexed => {
+                        if is_zero_copy {
+                            panic!(
+                                "The fields of zero-copy type {} cannot be indexed",
+                                name
+                            );
+                        }
+                        if deny_deep_copy {
+                            panic!(
+                                "The fields of type {} cannot be indexed, as deep-copy fields are denied",
+                                name
+                            );
+                        }
+                        if !generics_names_raw.contains(&ty.to_token_stream().to_string()) {
+                            panic!(
+                                "Field {} of {} can be indexed only if its type is a type parameter",
+                                field_name, name
+                            );
+                        }
+                        non_generic_fields.push(field_name.clone());
+                        non_generic_types.push(ty);
+                        indexed_types.push(ty);
+                        fields_zero_copy.push(quote!(false));
+                        fields_align.push(quote!(epserde::indexed::indexed_max_field_align::<#ty>()));
+                        ser_exprs.push(quote!(epserde::indexed::serialize_indexed::<#ty>(backend, stringify!(#field_name), &self.#field_name)?));
+                        full_exprs.push(quote!(epserde::indexed::deserialize_full_indexed::<#ty>(backend)?));
+                        into_exprs.push(quote!(self.#field_name = epserde::indexed::deserialize_full_indexed::<#ty>(backend)?));
+                    }
+                    (Some(codec), _) => {
+                        if is_zero_copy {
+                            panic!(
+                                "The fields of zero-copy type {} cannot be compressed",
+                                name
+                            );
+                        }
+                        non_generic_fields.push(field_name.clone());
+                        non_generic_types.push(ty);
+                        bounded_types.push(ty);
+                        fields_zero_copy.push(quote!(false));
+                        // Compressed fields are always fully deserialized
+                        fields_align.push(quote!(1));
+                        ser_exprs.push(quote!(epserde::compress::serialize_compressed::<#codec, #ty>(backend, stringify!(#field_name), &self.#field_name)?));
+                        full_exprs.push(quote!(epserde::compress::deserialize_compressed::<#codec, #ty>(backend)?));
+                        into_exprs.push(quote!(self.#field_name = epserde::compress::deserialize_compressed::<#codec, #ty>(backend)?));
+                    }
+                    (None, Some(module)) => {
+                        if is_zero_copy {
+                            panic!(
+                                "The fields of zero-copy type {} cannot be serialized with a module",
+                                name
+                            );
+                        }
+                        if deny_deep_copy {
+                            panic!(
+                                "The fields of type {} cannot be serialized with a module, as deep-copy fields are denied",
+                                name
+                            );
+                        }
+                        let field_idx = proc_macro2::Literal::usize_unsuffixed(field_idx);
+                        non_generic_fields.push(field_name.clone());
+                        non_generic_types.push(ty);
+                        fields_zero_copy.push(quote!(false));
+                        // Fields serialized with a module are always fully deserialized
+                        fields_align.push(quote!(1));
+                        ser_exprs.push(quote!(backend.write(stringify!(#field_name), &epserde::with::With::<Self, #ty, #field_idx>::new(&self.#field_name))?));
+                        full_exprs.push(quote!(#module::deserialize_full(backend)?));
+                        into_exprs.push(quote!(self.#field_name = #module::deserialize_full(backend)?));
+                        with_impls.push(quote! {
+                            #[automatically_derived]
+                            #[doc(hidden)]
+                            impl<#generics> epserde::with::SerializeWith<#ty, #field_idx> for #name<#generics_names> #where_clause_input {
+                                #[inline(always)]
+                                fn serialize(value: &#ty, backend: &mut impl epserde::ser::WriteWithNames) -> epserde::ser::Result<()> {
+                                    #module::serialize(value, backend)
+                                }
+                            }
+                        });
+                    }
+                    (None, None) => {
+                        bounded_types.push(ty);
+                        if generics_names_raw.contains(&ty.to_token_stream().to_string()) {
+                            generic_fields.push(field_name.clone());
+                            generic_types.push(ty);
+                        } else {
+                            non_generic_fields.push(field_name.clone());
+                            non_generic_types.push(ty);
+                        }
+                        fields_zero_copy.push(quote!(<#ty>::IS_ZERO_COPY));
+                        fields_align.push(quote!(<#ty as epserde::ser::SerializeInner>::MAX_FIELD_ALIGN));
+                        ser_exprs.push(quote!(backend.write(stringify!(#field_name), &self.#field_name)?));
+                        full_exprs.push(quote!(<#ty>::_deserialize_full_inner(backend)?));
+                        into_exprs.push(quote!(<#ty as epserde::deser::DeserializeInner>::_deserialize_full_into_inner(&mut self.#field_name, backend)?));
+                    }
                 }
                 fields_types.push(ty);
                 fields_names.push(field_name);
             });
 
             // Assign  ε-copy deserialization or full deserialization to
-            // fields depending whether they are generic or not.
-            let mut methods: Vec<proc_macro2::TokenStream> = vec![];
-
-            s.fields.iter().for_each(|field| {
+            // fields depending whether they are generic or not. Generic
+            // fields with an overridden deserialization type are converted
+            // using the provided function.
+            let mut eps_exprs: Vec<proc_macro2::TokenStream> = vec![];
+            // Type parameters whose deserialization type has been overridden.
+            let mut overrides: Vec<(String, syn::Type)> = vec![];
+            // Bounds making it possible to convert the deserialization type of
+            // type parameters to the overridden deserialization type.
+            let mut from_bounds: Vec<WherePredicate> = vec![];
+
+            s.fields.iter().zip(&full_exprs).for_each(|(field, full_expr)| {
                 let ty = &field.ty;
-                if generics_names_raw.contains(&ty.to_token_stream().to_string()) {
-                    methods.push(syn::parse_quote!(_deserialize_eps_inner));
+                let ty_name = ty.to_token_stream().to_string();
+                let FieldAttrs {
+                    deser_type_override,
+                    compress,
+                    with,
+                    indexed,
+                    ..
+                } = parse_field_attrs(field);
+                if compress.is_some() || with.is_some() {
+                    eps_exprs.push(full_expr.clone());
+                } else if indexed {
+                    if overrides.iter().any(|(name, _)| *name == ty_name) {
+                        panic!(
+                            "The deserialization type of type parameter {} of {} is overridden more than once",
+                            ty_name, name
+                        );
+                    }
+                    eps_exprs.push(quote!(epserde::indexed::deserialize_eps_indexed::<#ty>(backend)?));
+                    overrides.push((
+                        ty_name,
+                        syn::parse_quote!(epserde::indexed::IndexedView<'epserde_desertype, <#ty as epserde::indexed::IndexedSeq>::Item>),
+                    ));
+                } else if generics_names_raw.contains(&ty_name) {
+                    match deser_type_override {
+                        Some(DeserTypeOverride {
+                            deser_type,
+                            deser_with,
+                        }) => {
+                            if overrides.iter().any(|(name, _)| *name == ty_name) {
+                                panic!(
+                                    "The deserialization type of type parameter {} of {} is overridden more than once",
+                                    ty_name, name
+                                );
+                            }
+                            match deser_with {
+                                Some(deser_with) => eps_exprs.push(quote!(#deser_with(backend)?)),
+                                None => {
+                                    eps_exprs.push(quote!(core::convert::Into::into(<#ty>::_deserialize_eps_inner(backend)?)));
+                                    // The conversion must be possible for every lifetime.
+                                    from_bounds.push(syn::parse_quote!(
+                                        for<'epserde_desertype> #deser_type: core::convert::From<<#ty as epserde::deser::DeserializeInner>::DeserType<'epserde_desertype>>
+                                    ));
+                                }
+                            }
+                            overrides.push((ty_name, deser_type));
+                        }
+                        None => eps_exprs.push(quote!(<#ty>::_deserialize_eps_inner(backend)?)),
+                    }
                 } else {
-                    methods.push(syn::parse_quote!(_deserialize_full_inner));
+                    if deser_type_override.is_some() {
+                        panic!(
+                            "The deserialization type of field {} of {} can be overridden only if its type is a type parameter",
+                            field.ident.to_token_stream(),
+                            name
+                        );
+                    }
+                    eps_exprs.push(full_expr.clone());
+                }
+            });
+
+            // Fields with the attribute ignore_context are deserialized
+            // after removing the deserialization context from the backend.
+            s.fields.iter().enumerate().for_each(|(field_idx, field)| {
+                if !parse_field_attrs(field).ignore_context {
+                    return;
+                }
+                if is_zero_copy {
+                    panic!(
+                        "The fields of zero-copy type {} cannot ignore the deserialization context",
+                        name
+                    );
                 }
+                for expr in [&mut full_exprs[field_idx], &mut eps_exprs[field_idx]] {
+                    *expr = quote!(epserde::deser::without_context(backend, |backend| {
+                        let value = #expr;
+                        Ok(value)
+                    })?);
+                }
+                let into_expr = &into_exprs[field_idx];
+                into_exprs[field_idx] = quote!(epserde::deser::without_context(backend, |backend| {
+                    #into_expr;
+                    Ok(())
+                })?);
+            });
+
+            // Errors are annotated with the name of the field, as in the
+            // schema, and its offset.
+            fields_names.iter().enumerate().for_each(|(field_idx, field_name)| {
+                for expr in [&mut full_exprs[field_idx], &mut eps_exprs[field_idx]] {
+                    *expr = quote!(epserde::deser::in_field(backend, stringify!(#field_name), |backend| {
+                        let value = #expr;
+                        Ok(value)
+                    })?);
+                }
+                let into_expr = &into_exprs[field_idx];
+                into_exprs[field_idx] = quote!(epserde::deser::in_field(backend, stringify!(#field_name), |backend| {
+                    #into_expr;
+                    Ok(())
+                })?);
             });
 
             // Gather deserialization types of fields,
@@ -230,7 +784,11 @@ pub fn epserde_derive(input: TokenStream) -> TokenStream {
             let deser_type_generics = generics_name_vec
                 .iter()
                 .map(|ty| {
-                    if generic_types
+                    if let Some((_, deser_type)) =
+                        overrides.iter().find(|(name, _)| *name == ty.to_string())
+                    {
+                        deser_type.to_token_stream()
+                    } else if generic_types
                         .iter()
                         .any(|x| x.to_token_stream().to_string() == ty.to_string())
                     {
@@ -253,7 +811,7 @@ pub fn epserde_derive(input: TokenStream) -> TokenStream {
             let mut where_clause_des = where_clause.clone();
             let mut where_clause_ser = where_clause.clone();
 
-            fields_types.iter().for_each(|ty| {
+            bounded_types.iter().for_each(|ty| {
                 // add that every struct field has to implement SerializeInner
                 let mut bounds_ser = Punctuated::new();
                 bounds_ser.push(syn::parse_quote!(epserde::ser::SerializeInner));
@@ -278,49 +836,72 @@ pub fn epserde_derive(input: TokenStream) -> TokenStream {
                     }));
             });
 
+            indexed_types.iter().for_each(|ty| {
+                let bound: WherePredicate = syn::parse_quote!(#ty: epserde::indexed::IndexedSeq);
+                where_clause_ser.predicates.push(bound.clone());
+                where_clause_des.predicates.push(bound);
+            });
+
             // We add to the deserialization where clause the bounds on the deserialization
             // types of the fields derived from the bounds of the original types of the fields.
-            // TODO: we presently handle only inlined bounds, and not bounds in a where clause.
-            derive_input.generics.params.iter().for_each(|param| {
-                if let GenericParam::Type(t) = param {
-                    let ty = &t.ident;
-
-                    // Skip generics not involved in deserialization type substitution.
-                    if t.bounds.is_empty() || ! generic_types
-                        .iter()
-                        .any(|x| *ty == x.to_token_stream().to_string())
-                    {
-                        return;
+            // Overridden deserialization types are the responsibility of the user.
+            add_deser_type_bounds(
+                &mut where_clause_des,
+                &derive_input.generics,
+                &generic_types
+                    .iter()
+                    .map(|ty| ty.to_token_stream().to_string())
+                    .filter(|ty| !overrides.iter().any(|(name, _)| name == ty))
+                    .collect::<Vec<_>>(),
+            );
+
+            where_clause_des.predicates.extend(from_bounds);
+
+            // If deep-copy fields are denied, fields whose type is a type
+            // parameter are bound to be zero-copy, whereas the remaining
+            // fields are checked at compile time by a dummy function.
+            let mut deny_deep_copy_check = quote! {};
+            if deny_deep_copy {
+                let mut checks = vec![];
+                for field in s.fields.iter() {
+                    let ty = &field.ty;
+                    let bound: WherePredicate = syn::parse_quote_spanned!(ty.span()=>
+                        #ty: epserde::traits::CopyType<Copy = epserde::traits::Zero>
+                    );
+                    if generics_names_raw.contains(&ty.to_token_stream().to_string()) {
+                        where_clause_ser.predicates.push(bound.clone());
+                        where_clause_des.predicates.push(bound);
+                    } else {
+                        checks.push(quote_spanned!(ty.span()=> deny_deep_copy::<#ty>();));
                     }
-
-                    // add a lifetime so we express bounds on DeserType
-                    let mut lifetimes = Punctuated::new();
-                    lifetimes.push(GenericParam::Lifetime(LifetimeParam {
-                        attrs: vec![],
-                        lifetime: syn::Lifetime::new("'epserde_desertype", proc_macro2::Span::call_site()),
-                        colon_token: None,
-                        bounds: Punctuated::new(),
-                    }));
-                    // add that the DeserType is a DeserializeInner
-                    where_clause_des
-                        .predicates
-                        .push(WherePredicate::Type(PredicateType {
-                            lifetimes: Some(BoundLifetimes {
-                                for_token: token::For::default(),
-                                lt_token: token::Lt::default(),
-                                lifetimes,
-                                gt_token: token::Gt::default(),
-                            }),
-                            bounded_ty: syn::parse_quote!(
-                                <#ty as epserde::deser::DeserializeInner>::DeserType<'epserde_desertype>
-                            ),
-                            colon_token: token::Colon::default(),
-                            bounds: t.bounds.clone(),
-                        }));
                 }
-            });
+                deny_deep_copy_check = quote! {
+                    #[allow(dead_code)]
+                    const _: () = {
+                        fn deny_deep_copy<T: epserde::traits::CopyType<Copy = epserde::traits::Zero>>() {}
+                        fn check<#generics>() #where_clause {
+                            #(#checks)*
+                        }
+                    };
+                };
+            }
+
+            if is_zero_copy && !overrides.is_empty() {
+                panic!(
+                    "The deserialization type of the fields of zero-copy type {} cannot be overridden",
+                    name
+                );
+            }
 
             if is_zero_copy {
+                add_zero_copy_bounds(
+                    &mut where_clause_ser,
+                    &mut where_clause_des,
+                    &derive_input.generics,
+                );
+            }
+
+            let impls = if is_zero_copy {
                 quote! {
                     #[automatically_derived]
                     impl<#generics> epserde::traits::CopyType for  #name<#generics_names> #where_clause {
@@ -337,6 +918,12 @@ pub fn epserde_derive(input: TokenStream) -> TokenStream {
                         // The type is declared as zero copy, so a fortiori there is no mismatch.
                         const ZERO_COPY_MISMATCH: bool = false;
 
+                        // As in the implementation of MaxSizeOf
+                        const MAX_FIELD_ALIGN: usize = epserde::ser::helpers::max_align(&[
+                            core::mem::align_of::<Self>(),
+                            #(<#fields_types as epserde::ser::SerializeInner>::MAX_FIELD_ALIGN,)*
+                        ]);
+
                         #[inline(always)]
                         fn _serialize_inner(&self, backend: &mut impl epserde::ser::WriteWithNames) -> epserde::ser::Result<()> {
                             // No-op code that however checks that all fields are zero-copy.
@@ -379,18 +966,21 @@ pub fn epserde_derive(input: TokenStream) -> TokenStream {
                     impl<#generics_serialize> epserde::ser::SerializeInner for #name<#generics_names> #where_clause_ser {
                         // Compute whether the type could be zero copy
                         const IS_ZERO_COPY: bool = #is_repr_c #(
-                            && <#fields_types>::IS_ZERO_COPY
+                            && #fields_zero_copy
                         )*;
 
                         // Compute whether the type could be zero copy but it is not declared as such,
                         // and the attribute `deep_copy` is missing.
-                        const ZERO_COPY_MISMATCH: bool = ! #is_deep_copy #(&& <#fields_types>::IS_ZERO_COPY)*;
+                        const ZERO_COPY_MISMATCH: bool = ! #is_deep_copy #(&& #fields_zero_copy)*;
+
+                        // The maximum alignment of the zero-copy data of the fields
+                        const MAX_FIELD_ALIGN: usize = epserde::ser::helpers::max_align(&[#(#fields_align,)*]);
 
                         #[inline(always)]
                         fn _serialize_inner(&self, backend: &mut impl epserde::ser::WriteWithNames) -> epserde::ser::Result<()> {
                             epserde::ser::helpers::check_mismatch::<Self>();
                             #(
-                                backend.write(stringify!(#fields_names), &self.#fields_names)?;
+                                #ser_exprs;
                             )*
                             Ok(())
                         }
@@ -404,7 +994,7 @@ pub fn epserde_derive(input: TokenStream) -> TokenStream {
                             use epserde::deser::DeserializeInner;
                             Ok(#name{
                                 #(
-                                    #fields_names: <#fields_types>::_deserialize_full_inner(backend)?,
+                                    #fields_names: #full_exprs,
                                 )*
                             })
                         }
@@ -418,15 +1008,73 @@ pub fn epserde_derive(input: TokenStream) -> TokenStream {
                             use epserde::deser::DeserializeInner;
                             Ok(#name{
                                 #(
-                                    #fields_names: <#fields_types>::#methods(backend)?,
+                                    #fields_names: #eps_exprs,
                                 )*
                             })
                         }
+
+                        fn _deserialize_full_into_inner(
+                            &mut self,
+                            backend: &mut impl epserde::deser::ReadWithPos,
+                        ) -> core::result::Result<(), epserde::deser::Error> {
+                            #(
+                                #into_exprs;
+                            )*
+                            Ok(())
+                        }
                     }
                 }
+            };
+
+            let constructor_impl = constructor.map(|constructor| {
+                let vis = &derive_input.vis;
+                let args = fields_names
+                    .iter()
+                    .enumerate()
+                    .map(|(field_idx, field_name)| match &s.fields {
+                        syn::Fields::Named(_) => quote::format_ident!("{}", field_name.to_string()),
+                        _ => quote::format_ident!("field_{}", field_idx),
+                    })
+                    .collect::<Vec<_>>();
+                let body = match &s.fields {
+                    syn::Fields::Named(_) => quote!(Self { #(#args,)* }),
+                    syn::Fields::Unnamed(_) => quote!(Self(#(#args,)*)),
+                    syn::Fields::Unit => quote!(Self),
+                };
+                quote! {
+                    #[automatically_derived]
+                    impl<#generics> #name<#generics_names> #where_clause_input {
+                        /// Create a new instance from its fields.
+                        ///
+                        /// Since the ε-copy deserialization type is the same
+                        /// structure with type parameters replaced by their
+                        /// deserialization types, this constructor can be used
+                        /// to build deserialization types from borrowed parts.
+                        #[allow(clippy::too_many_arguments)]
+                        #vis fn #constructor(#(#args: #fields_types,)*) -> Self {
+                            #body
+                        }
+                    }
+                }
+            });
+
+            quote! {
+                #impls
+                #deny_deep_copy_check
+                #(#with_impls)*
+                #constructor_impl
             }
         }
         Data::Enum(e) => {
+            if e.variants.iter().any(|variant| {
+                variant
+                    .fields
+                    .iter()
+                    .any(|field| !parse_field_attrs(field).is_empty())
+            }) {
+                panic!("The fields of enum {} cannot have epserde attributes", name);
+            }
+
             let where_clause = derive_input
                 .generics
                 .where_clause
@@ -522,12 +1170,12 @@ pub fn epserde_derive(input: TokenStream) -> TokenStream {
                     });
                     variant_full_des.push(quote! {
                         #(
-                            #var_fields_names: <#var_fields_types>::_deserialize_full_inner(backend)?,
+                            #var_fields_names: epserde::deser::in_field(backend, stringify!(#var_fields_names), |backend| <#var_fields_types>::_deserialize_full_inner(backend))?,
                         )*
                     });
                     variant_eps_des.push(quote! {
                         #(
-                            #var_fields_names: <#var_fields_types>::#methods(backend)?,
+                            #var_fields_names: epserde::deser::in_field(backend, stringify!(#var_fields_names), |backend| <#var_fields_types>::#methods(backend))?,
                         )*
                     });
                 }
@@ -606,12 +1254,12 @@ pub fn epserde_derive(input: TokenStream) -> TokenStream {
                     });
                     variant_full_des.push(quote! {
                         #(
-                            #var_fields_vars    : <#var_fields_types>::_deserialize_full_inner(backend)?,
+                            #var_fields_vars    : epserde::deser::in_field(backend, stringify!(#var_fields_names), |backend| <#var_fields_types>::_deserialize_full_inner(backend))?,
                         )*
                     });
                     variant_eps_des.push(quote! {
                         #(
-                            #var_fields_vars    : <#var_fields_types>::#methods(backend)?,
+                            #var_fields_vars    : epserde::deser::in_field(backend, stringify!(#var_fields_names), |backend| <#var_fields_types>::#methods(backend))?,
                         )*
                     });
                 }
@@ -633,8 +1281,42 @@ pub fn epserde_derive(input: TokenStream) -> TokenStream {
                 })
                 .collect::<Vec<_>>();
 
+            // We add to the deserialization where clause the bounds on the deserialization
+            // types of the fields derived from the bounds of the original types of the fields.
+            add_deser_type_bounds(
+                &mut where_clause_des,
+                &derive_input.generics,
+                &generic_types
+                    .iter()
+                    .map(|ty| ty.to_string())
+                    .collect::<Vec<_>>(),
+            );
+
             let tag = (0..variants.len()).collect::<Vec<_>>();
 
+            if is_zero_copy {
+                add_zero_copy_bounds(
+                    &mut where_clause_ser,
+                    &mut where_clause_des,
+                    &derive_input.generics,
+                );
+            }
+
+            // An empty enum has no values, so there is nothing to serialize,
+            // and matching on a reference would not be exhaustive.
+            let ser_body = if variants.is_empty() {
+                quote! { match *self {} }
+            } else {
+                quote! {
+                    match self {
+                        #(
+                           Self::#variants => { #variant_ser }
+                        )*
+                    }
+                    Ok(())
+                }
+            };
+
             if is_zero_copy {
                 quote! {
                     #[automatically_derived]
@@ -652,6 +1334,12 @@ pub fn epserde_derive(input: TokenStream) -> TokenStream {
                         // The type is declared as zero copy, so a fortiori there is no mismatch.
                         const ZERO_COPY_MISMATCH: bool = false;
 
+                        // As in the implementation of MaxSizeOf
+                        const MAX_FIELD_ALIGN: usize = epserde::ser::helpers::max_align(&[
+                            core::mem::align_of::<Self>(),
+                            #(<#fields_types as epserde::ser::SerializeInner>::MAX_FIELD_ALIGN,)*
+                        ]);
+
                         #[inline(always)]
                         fn _serialize_inner(&self, backend: &mut impl epserde::ser::WriteWithNames) -> epserde::ser::Result<()> {
                             // No-op code that however checks that all fields are zero-copy.
@@ -699,15 +1387,15 @@ pub fn epserde_derive(input: TokenStream) -> TokenStream {
                         // and the attribute `deep_copy` is missing.
                         const ZERO_COPY_MISMATCH: bool = ! #is_deep_copy #(&& <#fields_types>::IS_ZERO_COPY)*;
 
+                        // The maximum alignment of the zero-copy data of the fields
+                        const MAX_FIELD_ALIGN: usize = epserde::ser::helpers::max_align(&[
+                            #(<#fields_types as epserde::ser::SerializeInner>::MAX_FIELD_ALIGN,)*
+                        ]);
+
                         #[inline(always)]
                         fn _serialize_inner(&self, backend: &mut impl epserde::ser::WriteWithNames) -> epserde::ser::Result<()> {
                             epserde::ser::helpers::check_mismatch::<Self>();
-                            match self {
-                                #(
-                                   Self::#variants => { #variant_ser }
-                                )*
-                            }
-                            Ok(())
+                            #ser_body
                         }
                     }
 
@@ -743,7 +1431,113 @@ pub fn epserde_derive(input: TokenStream) -> TokenStream {
                 }
             }
         }
-        _ => todo!("Union types are not currently supported"),
+        Data::Union(u) => {
+            if !is_zero_copy {
+                panic!(
+                    "Union {} must be declared zero copy, as ε-serde cannot know which field is valid",
+                    name
+                );
+            }
+
+            // A union is treated as an opaque block of bytes, so we just need the
+            // types of the fields to check that they are all zero-copy.
+            let fields_types = u
+                .fields
+                .named
+                .iter()
+                .map(|field| &field.ty)
+                .collect::<Vec<_>>();
+
+            let where_clause = derive_input
+                .generics
+                .where_clause
+                .clone()
+                .unwrap_or_else(|| WhereClause {
+                    where_token: token::Where::default(),
+                    predicates: Punctuated::new(),
+                });
+
+            let mut where_clause_des = where_clause.clone();
+            let mut where_clause_ser = where_clause.clone();
+
+            fields_types.iter().for_each(|ty| {
+                // add that every union field has to implement SerializeInner
+                let mut bounds_ser = Punctuated::new();
+                bounds_ser.push(syn::parse_quote!(epserde::ser::SerializeInner));
+                where_clause_ser
+                    .predicates
+                    .push(WherePredicate::Type(PredicateType {
+                        lifetimes: None,
+                        bounded_ty: (*ty).clone(),
+                        colon_token: token::Colon::default(),
+                        bounds: bounds_ser,
+                    }));
+                // add that every union field has to implement DeserializeInner
+                let mut bounds_des = Punctuated::new();
+                bounds_des.push(syn::parse_quote!(epserde::deser::DeserializeInner));
+                where_clause_des
+                    .predicates
+                    .push(WherePredicate::Type(PredicateType {
+                        lifetimes: None,
+                        bounded_ty: (*ty).clone(),
+                        colon_token: token::Colon::default(),
+                        bounds: bounds_des,
+                    }));
+            });
+
+            quote! {
+                #[automatically_derived]
+                impl<#generics> epserde::traits::CopyType for  #name<#generics_names> #where_clause {
+                    type Copy = epserde::traits::Zero;
+                }
+
+                #[automatically_derived]
+                impl<#generics_serialize> epserde::ser::SerializeInner for #name<#generics_names> #where_clause_ser {
+                    // Compute whether the type could be zero copy
+                    const IS_ZERO_COPY: bool = #is_repr_c #(
+                        && <#fields_types>::IS_ZERO_COPY
+                    )*;
+
+                    // The type is declared as zero copy, so a fortiori there is no mismatch.
+                    const ZERO_COPY_MISMATCH: bool = false;
+
+                    // As in the implementation of MaxSizeOf
+                    const MAX_FIELD_ALIGN: usize = epserde::ser::helpers::max_align(&[
+                        core::mem::align_of::<Self>(),
+                        #(<#fields_types as epserde::ser::SerializeInner>::MAX_FIELD_ALIGN,)*
+                    ]);
+
+                    #[inline(always)]
+                    fn _serialize_inner(&self, backend: &mut impl epserde::ser::WriteWithNames) -> epserde::ser::Result<()> {
+                        // No-op code that however checks that all fields are zero-copy.
+                        fn test<T: epserde::traits::ZeroCopy>() {}
+                        #(
+                            test::<#fields_types>();
+                        )*
+                        epserde::ser::helpers::serialize_zero(backend, self)
+                    }
+                }
+
+                #[automatically_derived]
+                impl<#generics_deserialize> epserde::deser::DeserializeInner for #name<#generics_names> #where_clause_des
+                {
+                    fn _deserialize_full_inner(
+                        backend: &mut impl epserde::deser::ReadWithPos,
+                    ) -> core::result::Result<Self, epserde::deser::Error> {
+                        epserde::deser::helpers::deserialize_full_zero::<Self>(backend)
+                    }
+
+                    type DeserType<'epserde_desertype> = &'epserde_desertype #name<#generics_names>;
+
+                    fn _deserialize_eps_inner<'a>(
+                        backend: &mut epserde::deser::SliceWithPos<'a>,
+                    ) -> core::result::Result<Self::DeserType<'a>, epserde::deser::Error>
+                    {
+                        epserde::deser::helpers::deserialize_eps_zero::<Self>(backend)
+                    }
+                }
+            }
+        }
     };
 
     let mut out: TokenStream = out.into();
@@ -757,7 +1551,7 @@ pub fn epserde_derive(input: TokenStream) -> TokenStream {
 /// It generates implementations just for the traits
 /// `MaxSizeOf`, `TypeHash`, and `ReprHash`. See the documentation
 /// of [`epserde_derive`] for more information.
-#[proc_macro_derive(TypeInfo, attributes(zero_copy, deep_copy))]
+#[proc_macro_derive(TypeInfo, attributes(zero_copy, deep_copy, epserde))]
 pub fn epserde_type_hash(input: TokenStream) -> TokenStream {
     let input = parse_macro_input!(input as DeriveInput);
     let (_, is_zero_copy, _) = check_attrs(&input);
@@ -791,6 +1585,22 @@ pub fn epserde_type_hash(input: TokenStream) -> TokenStream {
         vec![syn::parse_quote!(epserde::traits::MaxSizeOf)],
     );
 
+    let CommonDeriveInput {
+        generics: generics_byteswap,
+        ..
+    } = CommonDeriveInput::new(
+        input.clone(),
+        vec![syn::parse_quote!(epserde::traits::ByteSwap)],
+    );
+
+    let CommonDeriveInput {
+        generics: generics_checkbytes,
+        ..
+    } = CommonDeriveInput::new(
+        input.clone(),
+        vec![syn::parse_quote!(epserde::traits::CheckBytes)],
+    );
+
     let out = match input.data {
         Data::Struct(s) => {
             let fields_names = s
@@ -812,6 +1622,67 @@ pub fn epserde_type_hash(input: TokenStream) -> TokenStream {
                 .map(|field| field.ty.to_owned())
                 .collect::<Vec<_>>();
 
+            let fields_members = s.fields.members().collect::<Vec<_>>();
+
+            // Compressed fields hash in the codec and are leaves of the shape;
+            // fields serialized with a module use the type hash of the module,
+            // and have no representation hash.
+            let mut fields_type_hashes = vec![];
+            let mut fields_type_shapes = vec![];
+            let mut fields_repr_hashes = vec![];
+            let mut fields_repr_shapes = vec![];
+            s.fields.iter().for_each(|field| {
+                let ty = &field.ty;
+                let attrs = parse_field_attrs(field);
+                let indexed = attrs.indexed;
+                match (attrs.compress, attrs.with) {
+                    _ if indexed => {
+                        fields_type_hashes
+                            .push(quote!(epserde::indexed::indexed_type_hash::<#ty>(hasher)));
+                        fields_type_shapes
+                            .push(quote!(epserde::indexed::indexed_type_shape::<#ty>()));
+                        fields_repr_hashes.push(quote!(
+                            *offset_of = 0;
+                            <#ty as epserde::traits::ReprHash>::repr_hash(hasher, offset_of);
+                        ));
+                        fields_repr_shapes
+                            .push(quote!(<#ty as epserde::traits::ReprHash>::repr_shape()));
+                    }
+                    (_, Some(module)) => {
+                        fields_type_hashes.push(quote!(#module::type_hash(hasher)));
+                        fields_type_shapes.push(quote!(epserde::with::leaf_shape(|mut hasher| #module::type_hash(&mut hasher))));
+                        fields_repr_hashes.push(quote!());
+                        fields_repr_shapes.push(quote!(epserde::with::leaf_shape(|_| {})));
+                    }
+                    (Some(codec), None) => {
+                        fields_type_hashes.push(
+                            quote!(epserde::compress::compressed_type_hash::<#codec, #ty>(hasher)),
+                        );
+                        fields_type_shapes.push(
+                            quote!(epserde::compress::compressed_type_shape::<#codec, #ty>()),
+                        );
+                        fields_repr_hashes.push(quote!(
+                            *offset_of = 0;
+                            <#ty as epserde::traits::ReprHash>::repr_hash(hasher, offset_of);
+                        ));
+                        fields_repr_shapes
+                            .push(quote!(epserde::compress::compressed_repr_shape::<#ty>()));
+                    }
+                    (None, None) => {
+                        fields_repr_hashes.push(quote!(
+                            *offset_of = 0;
+                            <#ty as epserde::traits::ReprHash>::repr_hash(hasher, offset_of);
+                        ));
+                        fields_type_hashes
+                            .push(quote!(<#ty as epserde::traits::TypeHash>::type_hash(hasher)));
+                        fields_type_shapes
+                            .push(quote!(<#ty as epserde::traits::TypeHash>::type_shape()));
+                        fields_repr_shapes
+                            .push(quote!(<#ty as epserde::traits::ReprHash>::repr_shape()));
+                    }
+                }
+            });
+
             // Build type name
             let name_literal = name.to_string();
 
@@ -869,9 +1740,23 @@ pub fn epserde_type_hash(input: TokenStream) -> TokenStream {
                                 );
                             )*
                         }
+
+                        fn layout() -> epserde::traits::TypeLayout {
+                            epserde::traits::TypeLayout::new_struct::<Self, _>([#(
+                                (
+                                    #fields_names,
+                                    core::mem::offset_of!(Self, #fields_members),
+                                    <#fields_types as epserde::traits::ReprHash>::layout(),
+                                ),
+                            )*])
+                        }
                     }
 
                     impl<#generics_maxsizeof> epserde::traits::MaxSizeOf for #name<#generics_names> #where_clause{
+                        const WIDTH_DEPENDENT: bool = false #(
+                            || <#fields_types as epserde::traits::MaxSizeOf>::WIDTH_DEPENDENT
+                        )*;
+
                         #[inline(always)]
                         fn max_size_of() -> usize {
                             let mut max_size_of = std::mem::align_of::<Self>();
@@ -884,6 +1769,38 @@ pub fn epserde_type_hash(input: TokenStream) -> TokenStream {
                             max_size_of
                         }
                     }
+
+                    impl<#generics_byteswap> epserde::traits::ByteSwap for #name<#generics_names> #where_clause{
+                        #[inline(always)]
+                        fn byte_swap(bytes: &mut [u8]) -> epserde::deser::Result<()> {
+                            // Swap each field in place, leaving padding untouched.
+                            #(
+                                let offset = core::mem::offset_of!(Self, #fields_members);
+                                <#fields_types as epserde::traits::ByteSwap>::byte_swap(
+                                    bytes
+                                        .get_mut(offset..offset + core::mem::size_of::<#fields_types>())
+                                        .ok_or(epserde::deser::Error::ReadError)?,
+                                )?;
+                            )*
+                            Ok(())
+                        }
+                    }
+
+                    impl<#generics_checkbytes> epserde::traits::CheckBytes for #name<#generics_names> #where_clause{
+                        #[inline(always)]
+                        fn check_bytes(bytes: &[u8]) -> epserde::deser::Result<()> {
+                            // Check each field, ignoring padding.
+                            #(
+                                let offset = core::mem::offset_of!(Self, #fields_members);
+                                <#fields_types as epserde::traits::CheckBytes>::check_bytes(
+                                    bytes
+                                        .get(offset..offset + core::mem::size_of::<#fields_types>())
+                                        .ok_or(epserde::deser::Error::ReadError)?,
+                                )?;
+                            )*
+                            Ok(())
+                        }
+                    }
                 }
             } else {
                 quote! {
@@ -905,9 +1822,18 @@ pub fn epserde_type_hash(input: TokenStream) -> TokenStream {
                             )*
                             // Recurse on all fields.
                             #(
-                                <#fields_types as epserde::traits::TypeHash>::type_hash(hasher);
+                                #fields_type_hashes;
                             )*
                         }
+
+                        fn type_shape() -> epserde::traits::TypeShape {
+                            epserde::traits::TypeShape::new_struct(
+                                #name_literal,
+                                [#(
+                                    (#fields_names, #fields_type_shapes),
+                                )*],
+                            )
+                        }
                     }
 
                     impl<#generics_reprhash> epserde::traits::ReprHash for #name<#generics_names> #where_clause{
@@ -920,10 +1846,18 @@ pub fn epserde_type_hash(input: TokenStream) -> TokenStream {
                             // zero-copy types, but we must add their representation in isolation
                             // as they will be aligned.
                             #(
-                                *offset_of = 0;
-                                <#fields_types as epserde::traits::ReprHash>::repr_hash(hasher, offset_of);
+                                #fields_repr_hashes
                             )*
                         }
+
+                        fn repr_shape() -> epserde::traits::TypeShape {
+                            epserde::traits::TypeShape::new_struct(
+                                #name_literal,
+                                [#(
+                                    (#fields_names, #fields_repr_shapes),
+                                )*],
+                            )
+                        }
                     }
                 }
             }
@@ -941,12 +1875,50 @@ pub fn epserde_type_hash(input: TokenStream) -> TokenStream {
             let mut var_type_hashes = Vec::new();
             let mut var_repr_hashes = Vec::new();
             let mut var_max_size_ofs = Vec::new();
+            let mut var_check_bytes = Vec::new();
+            let mut var_payload_aligns = Vec::new();
+            // The types of the fields of all variants.
+            let mut all_fields_types = Vec::new();
 
             e.variants.iter().for_each(|variant| {
                 let ident = variant.ident.to_owned();
                 let mut var_type_hash = quote! { stringify!(#ident).hash(hasher); };
                 let mut var_repr_hash = quote! { };
                 let mut var_max_size_of = quote! {  };
+                let var_fields_types = variant
+                    .fields
+                    .iter()
+                    .map(|field| &field.ty)
+                    .collect::<Vec<_>>();
+                all_fields_types.extend(var_fields_types.iter().cloned());
+                // The discriminant is either explicit or the previous one plus one.
+                let discriminant = match &variant.discriminant {
+                    Some((_, expr)) => quote! { discriminant = (#expr) as i128; },
+                    None => quote! { discriminant += 1; },
+                };
+                // The fields of the variant are laid out as in a repr(C)
+                // structure starting at the offset of the payload.
+                var_check_bytes.push(quote! {
+                    #discriminant
+                    if tag == discriminant {
+                        let mut offset = payload;
+                        #(
+                            offset += epserde::pad_align_to(offset, core::mem::align_of::<#var_fields_types>());
+                            <#var_fields_types as epserde::traits::CheckBytes>::check_bytes(
+                                bytes
+                                    .get(offset..offset + core::mem::size_of::<#var_fields_types>())
+                                    .ok_or(epserde::deser::Error::ReadError)?,
+                            )?;
+                            offset += core::mem::size_of::<#var_fields_types>();
+                        )*
+                        return Ok(());
+                    }
+                });
+                var_payload_aligns.push(quote! {
+                    #(
+                        payload_align = payload_align.max(core::mem::align_of::<#var_fields_types>());
+                    )*
+                });
                 match &variant.fields {
                     syn::Fields::Unit => {}
                     syn::Fields::Named(fields) => {
@@ -1003,6 +1975,27 @@ pub fn epserde_type_hash(input: TokenStream) -> TokenStream {
                 var_max_size_ofs.push(var_max_size_of);
             });
 
+            // The type of the tag: the integer type of the representation,
+            // if present, or the type of C enums.
+            let tag_type = input
+                .attrs
+                .iter()
+                .filter(|x| x.meta.path().is_ident("repr"))
+                .flat_map(|x| {
+                    x.parse_args_with(Punctuated::<syn::Meta, syn::Token![,]>::parse_terminated)
+                        .unwrap()
+                })
+                .filter_map(|x| x.path().get_ident().cloned())
+                .find(|x| {
+                    [
+                        "u8", "u16", "u32", "u64", "u128", "usize", "i8", "i16", "i32", "i64",
+                        "i128", "isize",
+                    ]
+                    .contains(&x.to_string().as_str())
+                })
+                .map(|x| x.to_token_stream())
+                .unwrap_or_else(|| quote!(core::ffi::c_int));
+
             // Build type name
             let name_literal = name.to_string();
 
@@ -1058,6 +2051,10 @@ pub fn epserde_type_hash(input: TokenStream) -> TokenStream {
                     }
 
                     impl<#generics_maxsizeof> epserde::traits::MaxSizeOf for #name<#generics_names> #where_clause{
+                        const WIDTH_DEPENDENT: bool = false #(
+                            || <#all_fields_types as epserde::traits::MaxSizeOf>::WIDTH_DEPENDENT
+                        )*;
+
                         #[inline(always)]
                         fn max_size_of() -> usize {
                             let mut max_size_of = std::mem::align_of::<Self>();
@@ -1067,6 +2064,38 @@ pub fn epserde_type_hash(input: TokenStream) -> TokenStream {
                             max_size_of
                         }
                     }
+
+                    impl<#generics_byteswap> epserde::traits::ByteSwap for #name<#generics_names> #where_clause{
+                        #[inline(always)]
+                        fn byte_swap(_bytes: &mut [u8]) -> epserde::deser::Result<()> {
+                            // The layout depends on the variant, which we cannot
+                            // know before swapping.
+                            Err(epserde::deser::Error::ByteSwapUnsupported(core::any::type_name::<Self>()))
+                        }
+                    }
+
+                    impl<#generics_checkbytes> epserde::traits::CheckBytes for #name<#generics_names> #where_clause{
+                        #[inline(always)]
+                        #[allow(unused_assignments)]
+                        fn check_bytes(bytes: &[u8]) -> epserde::deser::Result<()> {
+                            // A repr(C) enum is laid out as a repr(C) structure
+                            // containing the tag followed by a union of the variants.
+                            let tag = <#tag_type>::from_ne_bytes(
+                                *bytes.first_chunk().ok_or(epserde::deser::Error::ReadError)?,
+                            ) as i128;
+                            let mut payload_align = 1;
+                            #(
+                                #var_payload_aligns
+                            )*
+                            let payload = core::mem::size_of::<#tag_type>()
+                                + epserde::pad_align_to(core::mem::size_of::<#tag_type>(), payload_align);
+                            let mut discriminant: i128 = -1;
+                            #(
+                                #var_check_bytes
+                            )*
+                            Err(epserde::deser::Error::InvalidTag(tag as usize))
+                        }
+                    }
                 }
             } else {
                 quote! {
@@ -1107,7 +2136,125 @@ pub fn epserde_type_hash(input: TokenStream) -> TokenStream {
                 }
             }
         }
-        _ => todo!("Union types are not currently supported"),
+        Data::Union(u) => {
+            if !is_zero_copy {
+                panic!(
+                    "Union {} must be declared zero copy, as ε-serde cannot know which field is valid",
+                    name
+                );
+            }
+
+            let fields_names = u
+                .fields
+                .named
+                .iter()
+                .map(|field| field.ident.as_ref().unwrap().to_string())
+                .collect::<Vec<_>>();
+
+            let fields_types = u
+                .fields
+                .named
+                .iter()
+                .map(|field| field.ty.to_owned())
+                .collect::<Vec<_>>();
+
+            // Build type name
+            let name_literal = name.to_string();
+
+            // Add reprs
+            let repr = input
+                .attrs
+                .iter()
+                .filter(|x| x.meta.path().is_ident("repr"))
+                .map(|x| x.meta.require_list().unwrap().tokens.to_string())
+                .collect::<Vec<_>>();
+
+            quote! {
+                #[automatically_derived]
+                impl<#generics_typehash> epserde::traits::TypeHash for #name<#generics_names> #where_clause{
+
+                    #[inline(always)]
+                    fn type_hash(
+                        hasher: &mut impl core::hash::Hasher,
+                    ) {
+                        use core::hash::Hash;
+                        // Hash in ZeroCopy
+                        "ZeroCopy".hash(hasher);
+                        // Hash in union and field names.
+                        "union".hash(hasher);
+                        #name_literal.hash(hasher);
+                        #(
+                            #fields_names.hash(hasher);
+                        )*
+                        // Recurse on all fields.
+                        #(
+                            <#fields_types as epserde::traits::TypeHash>::type_hash(hasher);
+                        )*
+                    }
+                }
+
+                impl<#generics_reprhash> epserde::traits::ReprHash for #name<#generics_names> #where_clause{
+                    #[inline(always)]
+                    fn repr_hash(
+                        hasher: &mut impl core::hash::Hasher,
+                        offset_of: &mut usize,
+                    ) {
+                        use core::hash::Hash;
+                        // Hash in size, as padding is given by MaxSizeOf.
+                        // and it is independent of the architecture.
+                        core::mem::size_of::<Self>().hash(hasher);
+                        // Hash in representation data.
+                        #(
+                            #repr.hash(hasher);
+                        )*
+                        // Recurse on all fields, which all start at the same offset.
+                        let old_offset_of = *offset_of;
+                        #(
+                            *offset_of = old_offset_of;
+                            <#fields_types as epserde::traits::ReprHash>::repr_hash(
+                                hasher,
+                                offset_of,
+                            );
+                        )*
+                        *offset_of = old_offset_of + core::mem::size_of::<Self>();
+                    }
+                }
+
+                impl<#generics_maxsizeof> epserde::traits::MaxSizeOf for #name<#generics_names> #where_clause{
+                    const WIDTH_DEPENDENT: bool = false #(
+                        || <#fields_types as epserde::traits::MaxSizeOf>::WIDTH_DEPENDENT
+                    )*;
+
+                    #[inline(always)]
+                    fn max_size_of() -> usize {
+                        let mut max_size_of = std::mem::align_of::<Self>();
+                        // Recurse on all fields.
+                        #(
+                            if max_size_of < <#fields_types as epserde::traits::MaxSizeOf>::max_size_of() {
+                                max_size_of = <#fields_types as epserde::traits::MaxSizeOf>::max_size_of();
+                            }
+                        )*
+                        max_size_of
+                    }
+                }
+
+                impl<#generics_byteswap> epserde::traits::ByteSwap for #name<#generics_names> #where_clause{
+                    #[inline(always)]
+                    fn byte_swap(_bytes: &mut [u8]) -> epserde::deser::Result<()> {
+                        // We cannot know which field is valid.
+                        Err(epserde::deser::Error::ByteSwapUnsupported(core::any::type_name::<Self>()))
+                    }
+                }
+
+                impl<#generics_checkbytes> epserde::traits::CheckBytes for #name<#generics_names> #where_clause{
+                    #[inline(always)]
+                    fn check_bytes(_bytes: &[u8]) -> epserde::deser::Result<()> {
+                        // Reading a field of a union is unsafe anyway.
+                        Ok(())
+                    }
+                }
+            }
+        }
     };
     out.into()
 }
@@ -0,0 +1,218 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ * SPDX-FileCopyrightText: 2023 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+//! Example of ε-serde support implemented by hand in an external crate.
+//!
+//! All implementations use only the stable extension API described in the
+//! `extending` module of ε-serde.
+
+use core::hash::Hash;
+use core::mem::{align_of, size_of};
+use core::ops::Deref;
+use epserde::deser::helpers::*;
+use epserde::prelude::*;
+use epserde::ser::helpers::*;
+use epserde::ser::WriteWithNames;
+
+/// A percentage, that is, an integer between 0 and 100.
+///
+/// This type is zero-copy, and its values are checked at deserialization
+/// time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(C)]
+pub struct Percent(u8);
+
+impl Percent {
+    /// Create a percentage, returning `None` if `value` is larger than 100.
+    pub fn new(value: u8) -> Option<Self> {
+        (value <= 100).then_some(Self(value))
+    }
+
+    /// Return the value of the percentage.
+    pub fn get(&self) -> u8 {
+        self.0
+    }
+}
+
+impl CopyType for Percent {
+    type Copy = Zero;
+}
+
+impl TypeHash for Percent {
+    fn type_hash(hasher: &mut impl core::hash::Hasher) {
+        "ZeroCopy".hash(hasher);
+        "Percent".hash(hasher);
+        "0".hash(hasher);
+        u8::type_hash(hasher);
+    }
+}
+
+impl ReprHash for Percent {
+    fn repr_hash(hasher: &mut impl core::hash::Hasher, offset_of: &mut usize) {
+        size_of::<Self>().hash(hasher);
+        "C".hash(hasher);
+        u8::repr_hash(hasher, offset_of);
+    }
+}
+
+impl MaxSizeOf for Percent {
+    const WIDTH_DEPENDENT: bool = false;
+
+    fn max_size_of() -> usize {
+        align_of::<Self>().max(u8::max_size_of())
+    }
+}
+
+impl ByteSwap for Percent {
+    fn byte_swap(_bytes: &mut [u8]) -> deser::Result<()> {
+        // A single byte has no endianness
+        Ok(())
+    }
+}
+
+impl CheckBytes for Percent {
+    fn check_bytes(bytes: &[u8]) -> deser::Result<()> {
+        match bytes {
+            [value] if *value <= 100 => Ok(()),
+            _ => Err(deser::Error::InvalidValue("Percent")),
+        }
+    }
+}
+
+impl SerializeInner for Percent {
+    const IS_ZERO_COPY: bool = true;
+    const ZERO_COPY_MISMATCH: bool = false;
+    const MAX_FIELD_ALIGN: usize = max_align(&[align_of::<Self>(), u8::MAX_FIELD_ALIGN]);
+
+    fn _serialize_inner(&self, backend: &mut impl WriteWithNames) -> ser::Result<()> {
+        serialize_zero(backend, self)
+    }
+}
+
+impl DeserializeInner for Percent {
+    fn _deserialize_full_inner(backend: &mut impl ReadWithPos) -> deser::Result<Self> {
+        deserialize_full_zero(backend)
+    }
+
+    type DeserType<'a> = &'a Percent;
+
+    fn _deserialize_eps_inner<'a>(
+        backend: &mut SliceWithPos<'a>,
+    ) -> deser::Result<Self::DeserType<'a>> {
+        deserialize_eps_zero(backend)
+    }
+}
+
+/// A ring buffer of fixed capacity keeping the last values pushed.
+///
+/// The backend `B` is a `Vec<T>` for owned buffers, and a `&[T]` for
+/// ε-copy deserialized buffers. Elements must be zero-copy.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Ring<B> {
+    buf: B,
+    /// The index of the oldest value.
+    head: usize,
+}
+
+impl<T> Ring<Vec<T>> {
+    /// Create a ring buffer containing the given values, the first one being
+    /// the oldest.
+    pub fn new(buf: Vec<T>) -> Self {
+        Self { buf, head: 0 }
+    }
+
+    /// Push a value, returning the oldest one, which is replaced.
+    ///
+    /// # Panics
+    ///
+    /// This method panics if the capacity of the ring buffer is zero.
+    pub fn push(&mut self, value: T) -> T {
+        assert!(
+            !self.buf.is_empty(),
+            "Cannot push into an empty ring buffer"
+        );
+        let oldest = core::mem::replace(&mut self.buf[self.head], value);
+        self.head = (self.head + 1) % self.buf.len();
+        oldest
+    }
+}
+
+impl<T, B: Deref<Target = [T]>> Ring<B> {
+    /// Return the number of values.
+    pub fn len(&self) -> usize {
+        self.buf.len()
+    }
+
+    /// Return whether the ring buffer has capacity zero.
+    pub fn is_empty(&self) -> bool {
+        self.buf.is_empty()
+    }
+
+    /// Return an iterator on the values, from the oldest to the newest.
+    pub fn iter<'a>(&'a self) -> impl Iterator<Item = &'a T>
+    where
+        T: 'a,
+    {
+        let (newest, oldest) = self.buf.split_at(self.head);
+        oldest.iter().chain(newest)
+    }
+
+    /// Check that the index of the oldest value is within the buffer.
+    fn check(self) -> deser::Result<Self> {
+        if self.head != 0 && self.head >= self.buf.len() {
+            return Err(deser::Error::InvalidValue("Ring"));
+        }
+        Ok(self)
+    }
+}
+
+impl<B> CopyType for Ring<B> {
+    type Copy = Deep;
+}
+
+impl<B: TypeHash> TypeHash for Ring<B> {
+    fn type_hash(hasher: &mut impl core::hash::Hasher) {
+        "Ring".hash(hasher);
+        B::type_hash(hasher);
+    }
+}
+
+impl<B: ReprHash> ReprHash for Ring<B> {
+    fn repr_hash(hasher: &mut impl core::hash::Hasher, offset_of: &mut usize) {
+        *offset_of = 0;
+        B::repr_hash(hasher, offset_of);
+    }
+}
+
+impl<T: ZeroCopy + SerializeInner> SerializeInner for Ring<Vec<T>> {
+    const IS_ZERO_COPY: bool = false;
+    const ZERO_COPY_MISMATCH: bool = false;
+    const MAX_FIELD_ALIGN: usize = max_align(&[usize::MAX_FIELD_ALIGN, T::MAX_FIELD_ALIGN]);
+
+    fn _serialize_inner(&self, backend: &mut impl WriteWithNames) -> ser::Result<()> {
+        backend.write("head", &self.head)?;
+        serialize_slice_zero(backend, &self.buf)
+    }
+}
+
+impl<T: ZeroCopy + DeserializeInner + 'static> DeserializeInner for Ring<Vec<T>> {
+    fn _deserialize_full_inner(backend: &mut impl ReadWithPos) -> deser::Result<Self> {
+        let head = in_field(backend, "head", usize::_deserialize_full_inner)?;
+        let buf = deserialize_full_vec_zero(backend)?;
+        Ring { buf, head }.check()
+    }
+
+    type DeserType<'a> = Ring<&'a [T]>;
+
+    fn _deserialize_eps_inner<'a>(
+        backend: &mut SliceWithPos<'a>,
+    ) -> deser::Result<Self::DeserType<'a>> {
+        let head = in_field(backend, "head", usize::_deserialize_full_inner)?;
+        let buf = deserialize_eps_slice_zero(backend)?;
+        Ring { buf, head }.check()
+    }
+}
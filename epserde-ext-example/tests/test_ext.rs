@@ -0,0 +1,108 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ * SPDX-FileCopyrightText: 2023 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+#![cfg(test)]
+
+use epserde::prelude::*;
+use epserde_ext_example::{Percent, Ring};
+use std::io::Cursor;
+
+fn serialize<T: Serialize>(value: &T) -> Vec<u8> {
+    let mut cursor = epserde::new_aligned_cursor();
+    value.serialize(&mut cursor).unwrap();
+    cursor.into_inner()
+}
+
+/// A derived structure using the types of this crate.
+#[derive(Epserde, Debug, PartialEq)]
+struct Sensor<R> {
+    name: String,
+    samples: R,
+    threshold: Percent,
+}
+
+fn ring() -> Ring<Vec<u64>> {
+    let mut ring = Ring::new(vec![0; 5]);
+    for value in 1..8 {
+        ring.push(value);
+    }
+    ring
+}
+
+#[test]
+fn test_ring() {
+    let ring = ring();
+    assert_eq!(ring.iter().copied().collect::<Vec<_>>(), [3, 4, 5, 6, 7]);
+    let buf = serialize(&ring);
+
+    let view: Ring<&[u64]> = <Ring<Vec<u64>>>::deserialize_eps(&buf).unwrap();
+    assert_eq!(view.len(), 5);
+    assert_eq!(view.iter().copied().collect::<Vec<_>>(), [3, 4, 5, 6, 7]);
+
+    let full = <Ring<Vec<u64>>>::deserialize_full(&mut Cursor::new(&buf)).unwrap();
+    assert_eq!(full, ring);
+
+    let empty = Ring::<Vec<u64>>::new(vec![]);
+    let buf = serialize(&empty);
+    assert!(<Ring<Vec<u64>>>::deserialize_eps(&buf).unwrap().is_empty());
+}
+
+#[test]
+fn test_percent() {
+    let percents = [0, 50, 100].map(|value| Percent::new(value).unwrap());
+    assert_eq!(Percent::new(101), None);
+    let ring = Ring::new(percents.to_vec());
+    let buf = serialize(&ring);
+    let view = <Ring<Vec<Percent>>>::deserialize_eps(&buf).unwrap();
+    assert_eq!(
+        view.iter().map(Percent::get).collect::<Vec<_>>(),
+        [0, 50, 100]
+    );
+
+    let mut buf = serialize(&Percent::new(42).unwrap());
+    assert_eq!(<Percent>::deserialize_eps(&buf).unwrap().get(), 42);
+    *buf.last_mut().unwrap() = 200;
+    // Zero-copy values are checked only when data is untrusted
+    assert_eq!(<Percent>::deserialize_eps(&buf).unwrap().get(), 200);
+    assert!(matches!(
+        <Percent>::deserialize_eps_untrusted(&buf).unwrap_err(),
+        deser::Error::InvalidValue("Percent")
+    ));
+    assert!(<Percent>::deserialize_full(&mut Cursor::new(&buf)).is_err());
+}
+
+#[test]
+fn test_invalid_ring() {
+    let mut cursor = epserde::new_aligned_cursor();
+    let schema = ring().serialize_with_schema(&mut cursor).unwrap();
+    let mut buf = cursor.into_inner();
+    let head = schema.ranges("ROOT.head").next().unwrap();
+    buf[head].copy_from_slice(&5_usize.to_ne_bytes());
+    assert!(matches!(
+        <Ring<Vec<u64>>>::deserialize_eps(&buf).unwrap_err(),
+        deser::Error::InvalidValue("Ring")
+    ));
+    assert!(<Ring<Vec<u64>>>::deserialize_full(&mut Cursor::new(&buf)).is_err());
+}
+
+#[test]
+fn test_derived() {
+    let sensor = Sensor {
+        name: "temperature".to_string(),
+        samples: ring(),
+        threshold: Percent::new(90).unwrap(),
+    };
+    let buf = serialize(&sensor);
+
+    let view = <Sensor<Ring<Vec<u64>>>>::deserialize_eps(&buf).unwrap();
+    let samples: &Ring<&[u64]> = &view.samples;
+    assert_eq!(samples.iter().max(), Some(&7));
+    assert_eq!(view.threshold.get(), 90);
+
+    let full = <Sensor<Ring<Vec<u64>>>>::deserialize_full(&mut Cursor::new(&buf)).unwrap();
+    assert_eq!(full, sensor);
+}
@@ -0,0 +1,81 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+//! Throughput of the validation of strings during ε-copy deserialization.
+//!
+//! Compare the results of `cargo bench --bench str_validation` and
+//! `cargo bench --bench str_validation --features simdutf8`; the
+//! [`StrPolicy::Trusted`] benchmarks measure the cost of deserialization
+//! without validation.
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use epserde::prelude::*;
+
+const LEN: usize = 1 << 26;
+
+/// Repeat `text` until the result is at least [`LEN`] bytes long.
+fn repeat(text: &str) -> String {
+    text.repeat(LEN.div_ceil(text.len()))
+}
+
+fn serialize(value: &impl Serialize) -> Vec<u8> {
+    let mut cursor = epserde::new_aligned_cursor();
+    value.serialize(&mut cursor).unwrap();
+    cursor.into_inner()
+}
+
+fn bench_str_validation(c: &mut Criterion) {
+    let texts = [
+        (
+            "ascii",
+            repeat("The quick brown fox jumps over the lazy dog. "),
+        ),
+        (
+            "mixed",
+            repeat("Perché ε-serde è veloce? Ελληνικά, 日本語, and emoji 🦀. "),
+        ),
+    ];
+
+    let mut group = c.benchmark_group("String");
+    group.sample_size(10);
+    for (name, text) in &texts {
+        group.throughput(Throughput::Bytes(text.len() as u64));
+        let bytes = serialize(text);
+        for policy in [StrPolicy::Trusted, StrPolicy::Validated] {
+            group.bench_with_input(
+                BenchmarkId::new(format!("{:?}", policy), name),
+                &bytes,
+                |b, bytes| b.iter(|| String::deserialize_eps_with_policy(bytes, policy).unwrap()),
+            );
+        }
+    }
+    group.finish();
+
+    // The same text split in short strings
+    let mut group = c.benchmark_group("Vec<String>");
+    group.sample_size(10);
+    for (name, text) in &texts {
+        let strings = text
+            .split_inclusive(' ')
+            .map(str::to_string)
+            .collect::<Vec<_>>();
+        group.throughput(Throughput::Bytes(text.len() as u64));
+        let bytes = serialize(&strings);
+        for policy in [StrPolicy::Trusted, StrPolicy::Validated] {
+            group.bench_with_input(
+                BenchmarkId::new(format!("{:?}", policy), name),
+                &bytes,
+                |b, bytes| {
+                    b.iter(|| <Vec<String>>::deserialize_eps_with_policy(bytes, policy).unwrap())
+                },
+            );
+        }
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_str_validation);
+criterion_main!(benches);
@@ -0,0 +1,77 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+//! Throughput of serialization and deserialization of byte payloads.
+//!
+//! [`Vec<u8>`], [`Box<[u8]>`] and [`String`] are written with a single
+//! `write_all`, fully deserialized with a single `read_exact`, and ε-copy
+//! deserialized in constant time, so throughput should be close to that of
+//! `memcpy` for serialization and full-copy deserialization.
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use epserde::prelude::*;
+use std::io::Cursor;
+
+const SIZES: [usize; 3] = [1 << 10, 1 << 20, 1 << 28];
+
+/// Serialize `value` into a buffer of sufficient capacity.
+fn serialize<T: Serialize>(value: &T, len: usize) -> Vec<u8> {
+    let mut cursor = Cursor::new(Vec::with_capacity(len + 1024));
+    value.serialize(&mut cursor).unwrap();
+    cursor.into_inner()
+}
+
+/// Copy `bytes` into 16-byte-aligned memory, as required by ε-copy deserialization.
+fn aligned(bytes: &[u8]) -> Vec<u128> {
+    let mut aligned = vec![0_u128; bytes.len().div_ceil(16)];
+    bytemuck::cast_slice_mut::<u128, u8>(&mut aligned)[..bytes.len()].copy_from_slice(bytes);
+    aligned
+}
+
+fn bench_payload<T: Serialize + Deserialize>(
+    c: &mut Criterion,
+    name: &str,
+    make: impl Fn(usize) -> T,
+) {
+    let mut group = c.benchmark_group(name);
+    group.sample_size(10);
+    for len in SIZES {
+        let value = make(len);
+        group.throughput(Throughput::Bytes(len as u64));
+
+        let mut buf = Vec::with_capacity(len + 1024);
+        group.bench_with_input(BenchmarkId::new("ser", len), &value, |b, value| {
+            b.iter(|| {
+                buf.clear();
+                let mut cursor = Cursor::new(&mut buf);
+                value.serialize(&mut cursor).unwrap();
+            })
+        });
+
+        let bytes = serialize(&value, len);
+        group.bench_with_input(BenchmarkId::new("full", len), &bytes, |b, bytes| {
+            b.iter(|| T::deserialize_full(&mut Cursor::new(bytes)).unwrap())
+        });
+
+        let aligned = aligned(&bytes);
+        let aligned = &bytemuck::cast_slice::<u128, u8>(&aligned)[..bytes.len()];
+        group.bench_with_input(BenchmarkId::new("eps", len), aligned, |b, aligned| {
+            b.iter(|| {
+                black_box(T::deserialize_eps(aligned).unwrap());
+            })
+        });
+    }
+    group.finish();
+}
+
+fn bench_u8_payloads(c: &mut Criterion) {
+    bench_payload(c, "Vec<u8>", |len| vec![0x55_u8; len]);
+    bench_payload(c, "Box<[u8]>", |len| vec![0x55_u8; len].into_boxed_slice());
+    bench_payload(c, "String", |len| "U".repeat(len));
+}
+
+criterion_group!(benches, bench_u8_payloads);
+criterion_main!(benches);
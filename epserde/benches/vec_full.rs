@@ -0,0 +1,74 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+//! Throughput of full-copy deserialization of vectors of structures.
+//!
+//! A vector of zero-copy structures is read with a single `read_exact` into
+//! the spare capacity of the vector, whereas a vector of deep-copy
+//! structures with the same fields is deserialized element by element.
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use epserde::prelude::*;
+use std::io::Cursor;
+
+const SIZES: [usize; 3] = [1 << 10, 1 << 16, 1 << 22];
+
+#[derive(Epserde, Clone, Copy)]
+#[repr(C)]
+#[zero_copy]
+struct Zero {
+    a: u64,
+    b: u32,
+    c: u16,
+    d: u16,
+}
+
+#[derive(Epserde, Clone, Copy)]
+#[deep_copy]
+struct Deep {
+    a: u64,
+    b: u32,
+    c: u16,
+    d: u16,
+}
+
+fn bench_vec<T: Serialize + Deserialize>(c: &mut Criterion, name: &str, make: impl Fn(u64) -> T)
+where
+    Vec<T>: Serialize + Deserialize,
+{
+    let mut group = c.benchmark_group(name);
+    group.sample_size(10);
+    for len in SIZES {
+        let value = (0..len as u64).map(&make).collect::<Vec<_>>();
+        group.throughput(Throughput::Bytes((len * core::mem::size_of::<T>()) as u64));
+
+        let mut cursor = Cursor::new(Vec::new());
+        value.serialize(&mut cursor).unwrap();
+        let bytes = cursor.into_inner();
+        group.bench_with_input(BenchmarkId::new("full", len), &bytes, |b, bytes| {
+            b.iter(|| <Vec<T>>::deserialize_full(&mut Cursor::new(bytes)).unwrap())
+        });
+    }
+    group.finish();
+}
+
+fn bench_vec_full(c: &mut Criterion) {
+    bench_vec(c, "Vec<Zero>", |i| Zero {
+        a: i,
+        b: i as u32,
+        c: i as u16,
+        d: 0,
+    });
+    bench_vec(c, "Vec<Deep>", |i| Deep {
+        a: i,
+        b: i as u32,
+        c: i as u16,
+        d: 0,
+    });
+}
+
+criterion_group!(benches, bench_vec_full);
+criterion_main!(benches);
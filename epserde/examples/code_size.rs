@@ -0,0 +1,57 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ * SPDX-FileCopyrightText: 2023 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+/// Example persisting many types, useful to measure the size of the code
+/// generated by ε-serde.
+///
+/// Compare the size of the binaries generated by
+/// `cargo build --release --example code_size` and
+/// `cargo build --release --example code_size --features small-code`.
+use epserde::prelude::*;
+
+macro_rules! persist {
+    ($($name:ident),*) => {$(
+        #[derive(Epserde, Debug, Default, Clone, Copy, PartialEq)]
+        #[repr(C)]
+        #[zero_copy]
+        struct $name {
+            a: u64,
+            b: u32,
+            c: [u16; 4],
+        }
+
+        {
+            let data = Data {
+                value: $name::default(),
+                values: vec![$name::default(); 10],
+            };
+            let mut cursor = epserde::new_aligned_cursor();
+            data.serialize(&mut cursor).unwrap();
+            cursor.set_position(0);
+            let full = Data::<$name, Vec<$name>>::deserialize_full(&mut cursor).unwrap();
+            assert_eq!(full, data);
+            let buf = cursor.into_inner();
+            let eps = Data::<$name, Vec<$name>>::deserialize_eps(&buf).unwrap();
+            assert_eq!(*eps.value, data.value);
+            assert_eq!(eps.values, &data.values[..]);
+        }
+    )*};
+}
+
+#[derive(Epserde, Debug, PartialEq)]
+struct Data<A, B> {
+    value: A,
+    values: B,
+}
+
+fn main() {
+    persist!(
+        T00, T01, T02, T03, T04, T05, T06, T07, T08, T09, T10, T11, T12, T13, T14, T15, T16, T17,
+        T18, T19, T20, T21, T22, T23, T24, T25, T26, T27, T28, T29, T30, T31
+    );
+    println!("All types persisted");
+}
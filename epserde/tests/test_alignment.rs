@@ -0,0 +1,105 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ * SPDX-FileCopyrightText: 2023 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+#![cfg(test)]
+
+use epserde::prelude::*;
+
+#[derive(Epserde, Debug, Clone, Copy, PartialEq)]
+#[repr(C)]
+#[zero_copy]
+struct Point {
+    x: u64,
+    y: u32,
+}
+
+#[derive(Epserde, Debug, PartialEq)]
+struct Data<A, B, C> {
+    point: A,
+    points: B,
+    array: C,
+}
+
+fn data() -> Data<Point, Vec<Point>, [u32; 4]> {
+    Data {
+        point: Point { x: 1, y: 2 },
+        points: vec![Point { x: 3, y: 4 }; 10],
+        array: [5, 6, 7, 8],
+    }
+}
+
+fn serialize(value: &impl Serialize) -> Vec<u8> {
+    let mut cursor = epserde::new_aligned_cursor();
+    value.serialize(&mut cursor).unwrap();
+    cursor.into_inner()
+}
+
+/// Run `f` on a copy of `bytes` starting at the given offset from a
+/// 16-byte boundary.
+fn with_offset(bytes: &[u8], offset: usize, f: impl FnOnce(&[u8])) {
+    let mut storage = vec![0_u128; bytes.len().div_ceil(16) + 1];
+    let storage = bytemuck::cast_slice_mut::<u128, u8>(&mut storage);
+    storage[offset..offset + bytes.len()].copy_from_slice(bytes);
+    f(&storage[offset..offset + bytes.len()])
+}
+
+macro_rules! check_misaligned {
+    ($ty:ty, $value:expr) => {{
+        let buf = serialize(&$value);
+        with_offset(&buf, 0, |bytes| {
+            assert!(<$ty>::deserialize_eps(bytes).is_ok());
+        });
+        for offset in 1..8 {
+            with_offset(&buf, offset, |bytes| {
+                assert!(matches!(
+                    <$ty>::deserialize_eps(bytes),
+                    Err(deser::Error::AlignmentError)
+                ));
+                // Full-copy deserialization does not depend on the alignment of the buffer
+                assert_eq!(<$ty>::deserialize_full(&mut &bytes[..]).unwrap(), $value);
+            });
+        }
+    }};
+}
+
+#[test]
+fn test_misaligned() {
+    check_misaligned!(Point, Point { x: 1, y: 2 });
+    check_misaligned!(Vec<u64>, vec![1_u64, 2, 3]);
+    check_misaligned!(Vec<Point>, vec![Point { x: 1, y: 2 }]);
+    check_misaligned!([u64; 4], [1_u64, 2, 3, 4]);
+    check_misaligned!([Point; 2], [Point { x: 1, y: 2 }; 2]);
+    check_misaligned!(Data<Point, Vec<Point>, [u32; 4]>, data());
+}
+
+#[test]
+fn test_truncated() {
+    let buf = serialize(&data());
+    for len in 0..buf.len() {
+        with_offset(&buf[..len], 0, |bytes| {
+            assert!(Data::<Point, Vec<Point>, [u32; 4]>::deserialize_eps(bytes).is_err());
+            assert!(
+                Data::<Point, Vec<Point>, [u32; 4]>::deserialize_full(&mut &bytes[..]).is_err()
+            );
+        });
+    }
+
+    // A length that does not fit in the buffer
+    let mut cursor = epserde::new_aligned_cursor();
+    let schema = vec![1_u64, 2, 3]
+        .serialize_with_schema(&mut cursor)
+        .unwrap();
+    let mut buf = cursor.into_inner();
+    let row = schema.0.iter().find(|row| row.field == "ROOT.len").unwrap();
+    buf[row.offset..row.offset + 8].copy_from_slice(&u64::MAX.to_ne_bytes());
+    with_offset(&buf, 0, |bytes| {
+        assert!(matches!(
+            Vec::<u64>::deserialize_eps(bytes),
+            Err(deser::Error::ReadError)
+        ));
+    });
+}
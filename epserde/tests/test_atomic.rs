@@ -0,0 +1,106 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+#![cfg(test)]
+
+use epserde::prelude::*;
+use std::sync::atomic::*;
+
+#[test]
+fn test_atomic() {
+    let data = AtomicU32::new(42);
+    let mut v = vec![];
+    data.serialize(&mut std::io::Cursor::new(&mut v)).unwrap();
+    let full = AtomicU32::deserialize_full(&mut std::io::Cursor::new(&v)).unwrap();
+    assert_eq!(full.load(Ordering::Relaxed), 42);
+    let eps: u32 = AtomicU32::deserialize_eps(&v).unwrap();
+    assert_eq!(eps, 42);
+
+    // Atomics are not hash-compatible with the underlying integers
+    assert!(u32::deserialize_eps(&v).is_err());
+}
+
+#[test]
+fn test_atomic_vec() {
+    // Concurrent construction
+    let data: Vec<AtomicU64> = (0..100).map(|_| AtomicU64::new(0)).collect();
+    std::thread::scope(|s| {
+        for t in 0..4 {
+            let data = &data;
+            s.spawn(move || {
+                for i in (t..100).step_by(4) {
+                    data[i].fetch_add(i as u64, Ordering::Relaxed);
+                }
+            });
+        }
+    });
+
+    let mut cursor = epserde::new_aligned_cursor();
+    data.serialize(&mut cursor).unwrap();
+    let v = cursor.into_inner();
+
+    let full = <Vec<AtomicU64>>::deserialize_full(&mut std::io::Cursor::new(&v)).unwrap();
+    // ε-copy deserialization does not return atomics borrowed from the data
+    let eps: Vec<u64> = <Vec<AtomicU64>>::deserialize_eps(&v).unwrap();
+    for i in 0..100 {
+        assert_eq!(full[i].load(Ordering::Relaxed), i as u64);
+        assert_eq!(eps[i], i as u64);
+    }
+
+    // Arrays, too
+    let array: [AtomicU32; 3] = [AtomicU32::new(1), AtomicU32::new(2), AtomicU32::new(3)];
+    let mut cursor = epserde::new_aligned_cursor();
+    array.serialize(&mut cursor).unwrap();
+    let v = cursor.into_inner();
+    assert_eq!(<[AtomicU32; 3]>::deserialize_eps(&v).unwrap(), [1, 2, 3]);
+}
+
+#[derive(Epserde, Debug)]
+struct Counters {
+    hits: AtomicUsize,
+    misses: AtomicI32,
+}
+
+#[derive(Epserde, Debug)]
+struct Index {
+    counters: Vec<Counters>,
+    total: AtomicU16,
+}
+
+#[test]
+fn test_atomic_struct() {
+    let index = Index {
+        counters: vec![
+            Counters {
+                hits: AtomicUsize::new(3),
+                misses: AtomicI32::new(-1),
+            },
+            Counters {
+                hits: AtomicUsize::new(5),
+                misses: AtomicI32::new(2),
+            },
+        ],
+        total: AtomicU16::new(8),
+    };
+    let mut cursor = epserde::new_aligned_cursor();
+    index.serialize(&mut cursor).unwrap();
+    let v = cursor.into_inner();
+
+    let full = Index::deserialize_full(&mut std::io::Cursor::new(&v)).unwrap();
+    assert_eq!(full.counters[1].hits.load(Ordering::Relaxed), 5);
+    assert_eq!(full.total.load(Ordering::Relaxed), 8);
+
+    let eps = Index::deserialize_eps(&v).unwrap();
+    assert_eq!(eps.counters[0].misses.load(Ordering::Relaxed), -1);
+    assert_eq!(eps.total.load(Ordering::Relaxed), 8);
+
+    // Portable data
+    let mut cursor = epserde::new_aligned_cursor();
+    index.serialize_portable(&mut cursor).unwrap();
+    let v = cursor.into_inner();
+    let full = Index::deserialize_full(&mut std::io::Cursor::new(&v)).unwrap();
+    assert_eq!(full.counters[0].hits.load(Ordering::Relaxed), 3);
+}
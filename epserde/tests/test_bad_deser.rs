@@ -23,7 +23,7 @@ fn test_wrong_endianess() {
             len,
         )
     };
-    assert!(v.as_ptr() as usize % 16 == 0, "{:p}", v.as_ptr());
+    assert!((v.as_ptr() as usize).is_multiple_of(16), "{:p}", v.as_ptr());
     let mut buf = std::io::Cursor::new(&mut v);
 
     let schema = data.serialize_with_schema(&mut buf).unwrap();
@@ -138,3 +138,76 @@ fn test_wrong_endianess() {
         panic!("wrong error type: {:?}", err);
     }
 }
+
+#[test]
+fn test_bad_vec_len() {
+    let data = vec![1_u64, 2, 3];
+    let mut cursor = new_aligned_cursor();
+    let schema = data.serialize_with_schema(&mut cursor).unwrap();
+    let mut v = cursor.into_inner();
+    let len_offset = schema
+        .0
+        .iter()
+        .find(|row| row.field == "ROOT.len")
+        .unwrap()
+        .offset;
+
+    // A length that cannot be allocated
+    v[len_offset..len_offset + 8].copy_from_slice(&(usize::MAX / 8).to_ne_bytes());
+    assert!(matches!(
+        <Vec<u64>>::deserialize_full(&mut std::io::Cursor::new(&v)),
+        Err(deser::Error::ReadError)
+    ));
+
+    // A length larger than the data
+    v[len_offset..len_offset + 8].copy_from_slice(&4_usize.to_ne_bytes());
+    assert!(matches!(
+        <Vec<u64>>::deserialize_full(&mut std::io::Cursor::new(&v)),
+        Err(deser::Error::ReadError)
+    ));
+
+    // A shorter length reads a prefix
+    v[len_offset..len_offset + 8].copy_from_slice(&2_usize.to_ne_bytes());
+    assert_eq!(
+        <Vec<u64>>::deserialize_full(&mut std::io::Cursor::new(&v)).unwrap(),
+        [1, 2]
+    );
+}
+
+#[derive(Epserde, Debug, PartialEq)]
+struct Corruptible {
+    values: Vec<u64>,
+    name: String,
+    rows: Vec<Vec<u16>>,
+    tag: Option<char>,
+    fixed: [u32; 4],
+}
+
+#[test]
+fn test_truncated_and_corrupted() {
+    let data = Corruptible {
+        values: vec![1, 2, 3],
+        name: "name".into(),
+        rows: vec![vec![1], vec![2, 3]],
+        tag: Some('x'),
+        fixed: [4, 5, 6, 7],
+    };
+    let mut cursor = new_aligned_cursor();
+    data.serialize(&mut cursor).unwrap();
+    let mut v = cursor.into_inner();
+
+    // Truncated data must yield an error, not a panic
+    for len in 0..v.len() {
+        assert!(Corruptible::deserialize_eps(&v[..len]).is_err());
+        assert!(Corruptible::deserialize_full(&mut std::io::Cursor::new(&v[..len])).is_err());
+    }
+
+    // Corrupted data might be deserialized, but must not cause a panic
+    for i in 0..v.len() {
+        let old = v[i];
+        v[i] = 0xFF;
+        let _ = Corruptible::deserialize_eps(&v);
+        let _ = Corruptible::deserialize_full(&mut std::io::Cursor::new(&v));
+        v[i] = old;
+    }
+}
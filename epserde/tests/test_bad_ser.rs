@@ -19,6 +19,8 @@ fn test_fake_zero() {
     }
 
     impl MaxSizeOf for NewType {
+        const WIDTH_DEPENDENT: bool = true;
+
         fn max_size_of() -> usize {
             0
         }
@@ -0,0 +1,91 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ * SPDX-FileCopyrightText: 2023 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+#![cfg(test)]
+
+use epserde::bit_vec::BitVec;
+use epserde::prelude::*;
+
+fn serialize<T: Serialize>(value: &T) -> Vec<u8> {
+    let mut cursor = epserde::new_aligned_cursor();
+    value.serialize(&mut cursor).unwrap();
+    cursor.into_inner()
+}
+
+#[test]
+fn test_bit_vec() {
+    let bits = (0..1000).map(|i| i % 3 == 0).collect::<Vec<_>>();
+    let bit_vec = BitVec::from(bits.clone());
+    assert_eq!(bit_vec.len(), 1000);
+    assert_eq!(bit_vec.as_words().len(), 16);
+    assert_eq!(bit_vec.count_ones(), 334);
+    assert_eq!(bit_vec.iter().collect::<Vec<_>>(), bits);
+    // Much smaller than one byte per boolean
+    let buf = serialize(&bit_vec);
+    assert!(buf.len() < serialize(&bits).len() / 4);
+
+    let view = <BitVec>::deserialize_eps(&buf).unwrap();
+    let words: &[u64] = view.as_words();
+    assert_eq!(words, bit_vec.as_words());
+    assert_eq!(view.len(), 1000);
+    assert_eq!(view.get(999), Some(true));
+    assert_eq!(view.get(1000), None);
+    assert_eq!(view.count_ones(), 334);
+    assert_eq!(view, bit_vec);
+    assert_eq!(view.to_bit_vec(), bit_vec);
+
+    let full = <BitVec>::deserialize_full(&mut std::io::Cursor::new(&buf)).unwrap();
+    assert_eq!(full, bit_vec);
+
+    for len in [0, 1, 63, 64, 65] {
+        let bit_vec = BitVec::from_value(true, len);
+        assert_eq!(bit_vec.count_ones(), len);
+        let buf = serialize(&bit_vec);
+        assert_eq!(<BitVec>::deserialize_eps(&buf).unwrap(), bit_vec);
+    }
+}
+
+#[test]
+fn test_edit() {
+    let mut bit_vec = BitVec::with_capacity(100);
+    assert!(bit_vec.is_empty());
+    assert_eq!(bit_vec.pop(), None);
+    bit_vec.extend([true; 65]);
+    bit_vec.set(1, false);
+    assert_eq!(bit_vec.get(1), Some(false));
+    assert_eq!(bit_vec.pop(), Some(true));
+    assert_eq!(bit_vec.as_words(), &[!2]);
+    bit_vec.push(false);
+    assert_eq!(bit_vec.pop(), Some(false));
+    let mut expected = BitVec::from([true; 64].as_slice());
+    expected.set(1, false);
+    assert_eq!(bit_vec, expected);
+    bit_vec.clear();
+    assert_eq!(bit_vec, BitVec::new());
+}
+
+#[test]
+#[should_panic]
+fn test_set_out_of_bounds() {
+    BitVec::from_value(false, 10).set(10, true);
+}
+
+#[test]
+fn test_invalid() {
+    let mut buf = serialize(&BitVec::from(vec![true, false, true]));
+    // The last word of the serialized data contains the bits
+    let len = buf.len();
+    let mut word = u64::from_ne_bytes(buf[len - 8..].try_into().unwrap());
+    assert_eq!(word, 0b101);
+    word |= 1 << 10;
+    buf[len - 8..].copy_from_slice(&word.to_ne_bytes());
+    assert!(matches!(
+        <BitVec>::deserialize_eps(&buf).unwrap_err(),
+        deser::Error::InvalidValue("BitVec")
+    ));
+    assert!(<BitVec>::deserialize_full(&mut std::io::Cursor::new(&buf)).is_err());
+}
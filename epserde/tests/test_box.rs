@@ -0,0 +1,82 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+#![cfg(test)]
+
+use epserde::prelude::*;
+
+#[derive(Epserde, Debug, PartialEq, Eq, Clone, Copy)]
+#[repr(C)]
+#[zero_copy]
+struct Point {
+    x: u32,
+    y: u32,
+}
+
+#[derive(Epserde, Debug, PartialEq, Eq)]
+struct Inner<A> {
+    values: A,
+    name: String,
+}
+
+#[derive(Epserde, Debug, PartialEq, Eq)]
+struct Data<A, B> {
+    zero: A,
+    deep: B,
+    boxed: Box<Inner<Vec<u8>>>,
+}
+
+type Type = Data<Box<Point>, Box<Inner<Vec<u32>>>>;
+
+fn data() -> Type {
+    Data {
+        zero: Box::new(Point { x: 1, y: 2 }),
+        deep: Box::new(Inner {
+            values: vec![3, 4, 5],
+            name: "deep".into(),
+        }),
+        boxed: Box::new(Inner {
+            values: vec![6],
+            name: "boxed".into(),
+        }),
+    }
+}
+
+#[test]
+fn test_box() {
+    let data = data();
+    let mut cursor = epserde::new_aligned_cursor();
+    data.serialize(&mut cursor).unwrap();
+    let bytes = cursor.into_inner();
+
+    let full = Type::deserialize_full(&mut std::io::Cursor::new(&bytes)).unwrap();
+    assert_eq!(full, data);
+
+    let eps = Type::deserialize_eps(&bytes).unwrap();
+    let zero: &Point = eps.zero;
+    assert_eq!(zero, &Point { x: 1, y: 2 });
+    let deep: Box<Inner<&[u32]>> = eps.deep;
+    assert_eq!(deep.values, [3, 4, 5]);
+    assert_eq!(deep.name, "deep");
+    assert_eq!(eps.boxed, data.boxed);
+}
+
+#[test]
+fn test_box_full_into() {
+    let mut value = data();
+    let ptr = &*value.deep as *const Inner<Vec<u32>>;
+    let mut other = data();
+    other.deep.values.push(6);
+    let mut cursor = epserde::new_aligned_cursor();
+    other.serialize(&mut cursor).unwrap();
+    let bytes = cursor.into_inner();
+    value
+        .deserialize_full_into(&mut std::io::Cursor::new(&bytes))
+        .unwrap();
+    assert_eq!(value, other);
+    // The box has been reused
+    assert_eq!(&*value.deep as *const Inner<Vec<u32>>, ptr);
+}
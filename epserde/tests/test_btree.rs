@@ -0,0 +1,82 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+#![cfg(test)]
+
+use epserde::prelude::*;
+use std::collections::{BTreeMap, BTreeSet};
+
+#[test]
+fn test_btree_map_zero() {
+    let map: BTreeMap<u32, u64> = (0..100).map(|i| (99 - i, i as u64 * 10)).collect();
+
+    let mut cursor = epserde::new_aligned_cursor();
+    let mut schema = map.serialize_with_schema(&mut cursor).unwrap();
+    schema.0.sort_by_key(|row| row.offset);
+    println!("{}", schema.to_csv());
+
+    cursor.set_position(0);
+    let full = <BTreeMap<u32, u64>>::deserialize_full(&mut cursor).unwrap();
+    assert_eq!(map, full);
+
+    let buf = cursor.into_inner();
+    let eps = <BTreeMap<u32, u64>>::deserialize_eps(&buf).unwrap();
+    assert_eq!(eps.len(), map.len());
+    for (k, v) in &map {
+        assert_eq!(eps.get(k), Some(v));
+    }
+    assert_eq!(eps.get(&100), None);
+    assert!(eps.keys().windows(2).all(|w| w[0] < w[1]));
+    assert!(eps.iter().eq(map.iter()));
+}
+
+#[test]
+fn test_btree_map_deep() {
+    let mut map = BTreeMap::new();
+    map.insert("zeta".to_string(), vec![1_u8, 2]);
+    map.insert("alpha".to_string(), vec![]);
+    map.insert("mu".to_string(), vec![3]);
+
+    let mut cursor = epserde::new_aligned_cursor();
+    map.serialize(&mut cursor).unwrap();
+
+    cursor.set_position(0);
+    let full = <BTreeMap<String, Vec<u8>>>::deserialize_full(&mut cursor).unwrap();
+    assert_eq!(map, full);
+
+    let buf = cursor.into_inner();
+    let eps = <BTreeMap<String, Vec<u8>>>::deserialize_eps(&buf).unwrap();
+    assert_eq!(eps.keys(), &["alpha", "mu", "zeta"]);
+    assert_eq!(eps.get("zeta"), Some(&[1_u8, 2].as_slice()));
+    assert_eq!(eps.get("alpha"), Some(&[].as_slice()));
+    assert!(!eps.contains_key("beta"));
+}
+
+#[test]
+fn test_btree_set() {
+    let set: BTreeSet<i64> = [5, -3, 12, 0].into_iter().collect();
+
+    let mut cursor = epserde::new_aligned_cursor();
+    set.serialize(&mut cursor).unwrap();
+
+    cursor.set_position(0);
+    let full = <BTreeSet<i64>>::deserialize_full(&mut cursor).unwrap();
+    assert_eq!(set, full);
+
+    let buf = cursor.into_inner();
+    let eps = <BTreeSet<i64>>::deserialize_eps(&buf).unwrap();
+    assert_eq!(eps.as_slice(), &[-3, 0, 5, 12]);
+    assert!(eps.contains(&12));
+    assert!(!eps.contains(&1));
+
+    // A set is serialized exactly as the vector of its keys
+    let buf_vec = {
+        let mut cursor = epserde::new_aligned_cursor();
+        vec![-3_i64, 0, 5, 12].serialize(&mut cursor).unwrap();
+        cursor.into_inner()
+    };
+    assert_eq!(&buf[buf.len() - 32..], &buf_vec[buf_vec.len() - 32..]);
+}
@@ -0,0 +1,100 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ * SPDX-FileCopyrightText: 2023 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+#![cfg(test)]
+
+use epserde::prelude::*;
+use epserde::ser::{BufferedWriter, WriteNoStd, DEFAULT_BUFFER_SIZE};
+use std::io::Write;
+
+#[derive(Epserde, Debug, PartialEq)]
+struct Data {
+    name: String,
+    values: Vec<u64>,
+    labels: Vec<String>,
+}
+
+fn data(len: u64) -> Data {
+    Data {
+        name: "data".to_string(),
+        values: (0..len).collect(),
+        labels: (0..len).map(|i| i.to_string()).collect(),
+    }
+}
+
+/// A writer counting the calls to `write`.
+#[derive(Default)]
+struct CountingWriter {
+    bytes: Vec<u8>,
+    writes: usize,
+}
+
+impl Write for CountingWriter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.writes += 1;
+        self.bytes.write(buf)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+#[test]
+fn test_capacity() -> anyhow::Result<()> {
+    let data = data(1000);
+    let mut cursor = epserde::new_aligned_cursor();
+    data.serialize(&mut cursor)?;
+    let expected = cursor.into_inner();
+
+    let mut writes = vec![];
+    for capacity in [0, 1, 16, DEFAULT_BUFFER_SIZE, 1 << 20] {
+        let mut writer = BufferedWriter::with_capacity(capacity, CountingWriter::default());
+        assert!(writer.capacity() >= capacity);
+        data.serialize(&mut writer)?;
+        let inner = writer.into_inner()?;
+        assert_eq!(inner.bytes, expected);
+        writes.push(inner.writes);
+    }
+    // Larger buffers cause fewer writes
+    assert!(writes.windows(2).all(|w| w[0] >= w[1]));
+    assert_eq!(writes.last(), Some(&1));
+    Ok(())
+}
+
+#[test]
+fn test_reset() -> anyhow::Result<()> {
+    let mut writer = BufferedWriter::with_capacity(1024, Vec::new());
+    let capacity = writer.capacity();
+    let mut outputs = vec![];
+    for len in 0..10 {
+        data(len).serialize(&mut writer)?;
+        outputs.push(writer.reset(Vec::new())?);
+        assert_eq!(writer.capacity(), capacity);
+    }
+    assert!(writer.get_ref().is_empty());
+    for (len, bytes) in outputs.iter().enumerate() {
+        assert_eq!(Data::deserialize_full(&mut &bytes[..])?, data(len as u64));
+    }
+
+    // Data written directly is kept in the buffer until flushed
+    WriteNoStd::write_all(&mut writer, &[1, 2, 3])?;
+    assert!(writer.get_ref().is_empty());
+    assert_eq!(writer.reset(Vec::new())?, [1, 2, 3]);
+    Ok(())
+}
+
+#[test]
+fn test_store_with_buffer_size() -> anyhow::Result<()> {
+    let path = std::env::temp_dir().join("test_buffered.eps");
+    for buffer_size in [0, 1, 1 << 20] {
+        data(1000).store_with_buffer_size(&path, buffer_size)?;
+        assert_eq!(Data::load_full(&path)?, data(1000));
+    }
+    std::fs::remove_file(&path)?;
+    Ok(())
+}
@@ -0,0 +1,89 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+#![cfg(test)]
+
+use epserde::deser::ByteView;
+use epserde::prelude::*;
+use std::collections::{BTreeSet, HashMap};
+
+#[derive(Epserde, Debug, PartialEq, Clone, Copy)]
+#[repr(C)]
+#[zero_copy]
+struct Pair {
+    a: u32,
+    b: u32,
+}
+
+#[test]
+fn test_byte_view_floats() {
+    let mut cursor = epserde::new_aligned_cursor();
+    vec![
+        vec![0.5_f64, f64::NAN],
+        vec![0.0],
+        vec![-0.0],
+        vec![0.5, f64::NAN],
+    ]
+    .serialize(&mut cursor)
+    .unwrap();
+    let bytes = cursor.into_inner();
+    let keys = <Vec<Vec<f64>>>::deserialize_eps(&bytes).unwrap();
+
+    // NaNs with the same bit pattern are equal
+    assert_eq!(ByteView::new(keys[0]), ByteView::new(keys[3]));
+    // Zeroes with different signs are different
+    assert_ne!(ByteView::new(keys[1]), ByteView::new(keys[2]));
+
+    let mut map = HashMap::new();
+    for (i, &key) in keys.iter().enumerate() {
+        map.entry(ByteView::new(key)).or_insert(i);
+    }
+    assert_eq!(map.len(), 3);
+    let nan = [0.5, f64::NAN];
+    assert_eq!(map[&ByteView::new(&nan[..])], 0);
+    assert_eq!(map.get(&ByteView::new(&[1.0][..])), None);
+}
+
+#[test]
+fn test_byte_view_zero_copy() {
+    let pairs = vec![
+        Pair { a: 1, b: 2 },
+        Pair { a: 3, b: 4 },
+        Pair { a: 1, b: 2 },
+    ];
+    let mut cursor = epserde::new_aligned_cursor();
+    pairs.serialize(&mut cursor).unwrap();
+    let bytes = cursor.into_inner();
+    let eps = <Vec<Pair>>::deserialize_eps(&bytes).unwrap();
+
+    // SAFETY: the slices come from ε-copy deserialization
+    let views = eps
+        .chunks(1)
+        .map(|chunk| unsafe { ByteView::new_unchecked(chunk) })
+        .collect::<Vec<_>>();
+    assert_eq!(views[0], views[2]);
+    assert_ne!(views[0], views[1]);
+    assert_eq!(views[1][0], Pair { a: 3, b: 4 });
+    assert_eq!(views[0].as_bytes().len(), core::mem::size_of::<Pair>());
+
+    let set = views.iter().copied().collect::<BTreeSet<_>>();
+    assert_eq!(set.len(), 2);
+}
+
+#[test]
+fn test_byte_view_ord() {
+    let a = [1_u8, 2, 3];
+    let b = [1_u8, 3];
+    let c = [1_u8, 2];
+    let mut views = [
+        ByteView::new(&a[..]),
+        ByteView::new(&b[..]),
+        ByteView::new(&c[..]),
+    ];
+    views.sort();
+    assert_eq!(views.map(|view| view.as_slice().len()), [2, 3, 2]);
+    assert_eq!(views[2].as_slice(), b);
+}
@@ -0,0 +1,88 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+#![cfg(test)]
+
+use epserde::prelude::*;
+use epserde::{deser::ReadNoStd, ser::WriteNoStd};
+
+/// A backend recording the length of each read or write.
+#[derive(Default)]
+struct Recorder {
+    data: Vec<u8>,
+    pos: usize,
+    calls: Vec<usize>,
+}
+
+impl WriteNoStd for Recorder {
+    fn write_all(&mut self, buf: &[u8]) -> ser::Result<()> {
+        self.data.extend_from_slice(buf);
+        self.calls.push(buf.len());
+        Ok(())
+    }
+
+    fn flush(&mut self) -> ser::Result<()> {
+        Ok(())
+    }
+}
+
+impl ReadNoStd for Recorder {
+    fn read_exact(&mut self, buf: &mut [u8]) -> deser::Result<()> {
+        buf.copy_from_slice(&self.data[self.pos..self.pos + buf.len()]);
+        self.pos += buf.len();
+        self.calls.push(buf.len());
+        Ok(())
+    }
+}
+
+/// Check that a payload of `len` bytes is written and read in a single call.
+fn check_single_call<T: Serialize + Deserialize + PartialEq + core::fmt::Debug>(
+    value: T,
+    len: usize,
+) {
+    let mut recorder = Recorder::default();
+    value.serialize(&mut recorder).unwrap();
+    assert_eq!(recorder.calls.iter().filter(|&&l| l == len).count(), 1);
+    assert!(recorder.calls.iter().all(|&l| l <= len));
+
+    recorder.calls.clear();
+    assert_eq!(T::deserialize_full(&mut recorder).unwrap(), value);
+    assert_eq!(recorder.calls.iter().filter(|&&l| l == len).count(), 1);
+    assert!(recorder.calls.iter().all(|&l| l <= len));
+}
+
+#[test]
+fn test_single_call() {
+    let len = 100_000;
+    check_single_call(vec![1_u8; len], len);
+    check_single_call(vec![1_u8; len].into_boxed_slice(), len);
+    check_single_call("a".repeat(len), len);
+    check_single_call("a".repeat(len).into_boxed_str(), len);
+    check_single_call(Box::new([1_u8; 100_000]), len);
+}
+
+#[test]
+fn test_eps_borrows() {
+    let data = vec![7_u8; 1000];
+    let mut cursor = epserde::new_aligned_cursor();
+    data.serialize(&mut cursor).unwrap();
+    let buf = cursor.into_inner();
+    let eps = <Vec<u8>>::deserialize_eps(&buf).unwrap();
+    // The ε-copy deserialized slice points inside the buffer
+    assert_eq!(eps, data.as_slice());
+    assert!(buf.as_ptr_range().contains(&eps.as_ptr()));
+}
+
+#[test]
+fn test_eps_array_borrows() {
+    let data = [7_u8; 1000];
+    let mut cursor = epserde::new_aligned_cursor();
+    data.serialize(&mut cursor).unwrap();
+    let buf = cursor.into_inner();
+    let eps = <[u8; 1000]>::deserialize_eps(&buf).unwrap();
+    assert_eq!(eps, &data);
+    assert!(buf.as_ptr_range().contains(&eps.as_ptr()));
+}
@@ -0,0 +1,222 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ * SPDX-FileCopyrightText: 2023 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+#![cfg(test)]
+
+use core::num::NonZeroU32;
+use epserde::prelude::*;
+
+#[derive(Epserde, Debug, Clone, Copy, PartialEq)]
+#[repr(C)]
+#[zero_copy]
+struct Flags {
+    value: u64,
+    flag: bool,
+    letter: char,
+}
+
+#[derive(Epserde, Debug, Clone, Copy, PartialEq)]
+#[repr(C)]
+#[zero_copy]
+enum Shape {
+    Empty,
+    Small(u8, u64),
+    Flagged { flag: bool, value: u16 },
+}
+
+#[derive(Epserde, Debug, Clone, Copy, PartialEq)]
+#[repr(C, u16)]
+#[zero_copy]
+enum Sparse {
+    A = 3,
+    B(bool),
+    C = 10,
+}
+
+/// Serialize `value` and return the bytes and the offset of the given field.
+fn serialize(value: &impl Serialize, field: &str) -> (Vec<u8>, usize) {
+    let mut cursor = epserde::new_aligned_cursor();
+    let schema = value.serialize_with_schema(&mut cursor).unwrap();
+    let offset = schema
+        .0
+        .iter()
+        .find(|row| row.field == field)
+        .unwrap()
+        .offset;
+    (cursor.into_inner(), offset)
+}
+
+fn to_aligned(bytes: &[u8]) -> Vec<u128> {
+    let mut aligned = vec![0_u128; bytes.len().div_ceil(16)];
+    bytemuck::cast_slice_mut::<u128, u8>(&mut aligned)[..bytes.len()].copy_from_slice(bytes);
+    aligned
+}
+
+/// Check that `value` can be deserialized and that after setting the byte
+/// at the given offset from `field` to `byte` deserialization fails.
+fn check<T: Serialize + Deserialize + PartialEq + core::fmt::Debug>(
+    value: T,
+    field: &str,
+    delta: usize,
+    byte: u8,
+) -> deser::Error {
+    let (mut buf, offset) = serialize(&value, field);
+    assert_eq!(T::deserialize_full(&mut &buf[..]).unwrap(), value);
+    let aligned = to_aligned(&buf);
+    let bytes = &bytemuck::cast_slice::<u128, u8>(&aligned)[..buf.len()];
+    assert!(T::deserialize_eps_untrusted(bytes).is_ok());
+
+    buf[offset + delta] = byte;
+    let err_full = T::deserialize_full(&mut &buf[..]).unwrap_err();
+    let aligned = to_aligned(&buf);
+    let bytes = &bytemuck::cast_slice::<u128, u8>(&aligned)[..buf.len()];
+    let err_eps = T::deserialize_eps_untrusted(bytes).map(|_| ()).unwrap_err();
+    assert_eq!(err_eps.to_string(), err_full.to_string());
+    err_full
+}
+
+#[test]
+fn test_prim() {
+    assert!(matches!(
+        check(true, "ROOT", 0, 2),
+        deser::Error::InvalidValue("bool")
+    ));
+    assert!(matches!(
+        check(vec![true, false], "ROOT.zero", 1, 0xFF),
+        deser::Error::InvalidValue("bool")
+    ));
+    assert!(matches!(
+        check(vec!['a', 'b'], "ROOT.zero", 5, 0xD8),
+        deser::Error::InvalidValue("char")
+    ));
+    let nonzero = NonZeroU32::new(1).unwrap();
+    assert!(matches!(
+        check([nonzero, nonzero], "ROOT.zero", 4, 0),
+        deser::Error::InvalidValue("NonZeroU32")
+    ));
+}
+
+#[test]
+fn test_struct() {
+    let flags = Flags {
+        value: 1,
+        flag: true,
+        letter: 'x',
+    };
+    let offset = core::mem::offset_of!(Flags, flag);
+    assert!(matches!(
+        check(flags, "ROOT.zero", offset, 2),
+        deser::Error::InvalidValue("bool")
+    ));
+    let offset = core::mem::offset_of!(Flags, letter);
+    assert!(matches!(
+        check(
+            vec![flags; 3],
+            "ROOT.zero",
+            2 * size_of::<Flags>() + offset + 2,
+            0x11
+        ),
+        deser::Error::InvalidValue("char")
+    ));
+    // Padding is not checked
+    let (mut buf, root) = serialize(&flags, "ROOT.zero");
+    buf[root + offset - 1] = 0xFF;
+    assert_eq!(Flags::deserialize_full(&mut &buf[..]).unwrap(), flags);
+}
+
+#[test]
+fn test_enum() {
+    // Valid values check the layout
+    for shape in [
+        Shape::Empty,
+        Shape::Small(0xFF, u64::MAX),
+        Shape::Flagged {
+            flag: true,
+            value: 0xFFFF,
+        },
+    ] {
+        assert!(matches!(
+            check(shape, "ROOT.zero", 0, 3),
+            deser::Error::InvalidTag(3)
+        ));
+    }
+    let (buf, root) = serialize(
+        &Shape::Flagged {
+            flag: true,
+            value: 0,
+        },
+        "ROOT.zero",
+    );
+    // The tag is a C int, followed by the payload
+    let flag = buf[root + 4..].iter().position(|&byte| byte == 1).unwrap() + 4;
+    assert_eq!(flag, 8);
+    assert!(matches!(
+        check(
+            Shape::Flagged {
+                flag: true,
+                value: 0
+            },
+            "ROOT.zero",
+            flag,
+            2
+        ),
+        deser::Error::InvalidValue("bool")
+    ));
+
+    for sparse in [Sparse::A, Sparse::B(true), Sparse::C] {
+        assert!(matches!(
+            check(sparse, "ROOT.zero", 0, 5),
+            deser::Error::InvalidTag(5)
+        ));
+    }
+    assert!(matches!(
+        check(Sparse::B(false), "ROOT.zero", 2, 2),
+        deser::Error::InvalidValue("bool")
+    ));
+}
+
+#[test]
+fn test_validation() {
+    let (mut buf, offset) = serialize(&vec![true, false], "ROOT.zero");
+    buf[offset] = 2;
+    let aligned = to_aligned(&buf);
+    let bytes = &bytemuck::cast_slice::<u128, u8>(&aligned)[..buf.len()];
+    // The policy for strings does not check zero-copy values, which are
+    // checked only by the validation
+    let mut backend = SliceWithPos::new(bytes);
+    backend.set_str_policy(StrPolicy::Validated);
+    assert_eq!(backend.validation(), Validation::Trusted);
+    backend.set_validation(Validation::Checked);
+    deser::check_header::<Vec<bool>>(&mut backend).unwrap();
+    assert!(matches!(
+        <Vec<bool>>::_deserialize_eps_inner(&mut backend).map(|_| ()),
+        Err(deser::Error::InvalidValue("bool"))
+    ));
+    assert!(matches!(
+        <Vec<bool>>::deserialize_eps_untrusted(bytes).map(|_| ()),
+        Err(deser::Error::InvalidValue("bool"))
+    ));
+}
+
+#[cfg(feature = "mmap-rs")]
+#[test]
+fn test_untrusted_flag() {
+    let (mut buf, offset) = serialize(&vec![true, false], "ROOT.zero");
+    buf[offset] = 2;
+    let path = std::env::temp_dir().join("test_check_bytes_untrusted.eps");
+    std::fs::write(&path, &buf).unwrap();
+    let flags = deser::Flags::UNTRUSTED;
+    assert!(matches!(
+        <Vec<bool>>::load_mmap(&path, flags),
+        Err(deser::Error::InvalidValue("bool"))
+    ));
+    assert!(matches!(
+        <Vec<bool>>::mmap(&path, flags),
+        Err(deser::Error::InvalidValue("bool"))
+    ));
+    std::fs::remove_file(&path).unwrap();
+}
@@ -0,0 +1,112 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+#![cfg(test)]
+
+use epserde::prelude::*;
+
+#[derive(Epserde, Debug, PartialEq)]
+struct Data<A> {
+    values: A,
+    name: String,
+}
+
+fn data() -> Data<Vec<u64>> {
+    Data {
+        values: (0..1000).collect(),
+        name: "data".into(),
+    }
+}
+
+fn serialized() -> Vec<u8> {
+    let mut cursor = epserde::new_aligned_cursor();
+    let len = data().serialize_with_checksum(&mut cursor).unwrap();
+    let bytes = cursor.into_inner();
+    assert_eq!(len, bytes.len());
+    bytes
+}
+
+/// Flip a bit of the last value of the vector.
+fn corrupt(bytes: &mut [u8]) {
+    let pos = bytes
+        .windows(8)
+        .rposition(|w| w == 999_u64.to_ne_bytes())
+        .unwrap();
+    bytes[pos] ^= 1;
+}
+
+#[test]
+fn test_checksum() {
+    let bytes = serialized();
+    let full = <Data<Vec<u64>>>::deserialize_full(&mut std::io::Cursor::new(&bytes)).unwrap();
+    assert_eq!(full, data());
+    let eps = <Data<Vec<u64>>>::deserialize_eps(&bytes).unwrap();
+    assert_eq!(eps.values, data().values);
+    assert_eq!(eps.name, "data");
+
+    // The header is longer, but the payload is the same
+    let mut cursor = epserde::new_aligned_cursor();
+    let plain_len = data().serialize(&mut cursor).unwrap();
+    assert!(plain_len < bytes.len());
+}
+
+#[test]
+fn test_checksum_mismatch() {
+    let mut bytes = serialized();
+    corrupt(&mut bytes);
+    assert!(matches!(
+        <Data<Vec<u64>>>::deserialize_full(&mut std::io::Cursor::new(&bytes)),
+        Err(deser::Error::ChecksumMismatch { .. })
+    ));
+    assert!(matches!(
+        <Data<Vec<u64>>>::deserialize_eps(&bytes),
+        Err(deser::Error::ChecksumMismatch { .. })
+    ));
+    // Without verification, the corrupted data is read
+    let eps = <Data<Vec<u64>>>::deserialize_eps_unverified(&bytes).unwrap();
+    assert_eq!(eps.values[999], 998);
+}
+
+#[test]
+fn test_checksum_truncated() {
+    let bytes = serialized();
+    let mut cursor = epserde::new_aligned_cursor();
+    std::io::Write::write_all(&mut cursor, &bytes[..bytes.len() - 8]).unwrap();
+    let bytes = cursor.into_inner();
+    assert!(matches!(
+        <Data<Vec<u64>>>::deserialize_eps(&bytes),
+        Err(deser::Error::ReadError)
+    ));
+}
+
+#[cfg(feature = "mmap-rs")]
+#[test]
+fn test_checksum_mmap() -> anyhow::Result<()> {
+    let tmp_file = std::env::temp_dir().join("test_checksum.eps");
+    data().store_with_checksum(&tmp_file)?;
+    let eps = <Data<Vec<u64>>>::mmap(&tmp_file, Flags::empty())?;
+    assert_eq!(eps.values, data().values);
+    let eps = <Data<Vec<u64>>>::load_mem(&tmp_file)?;
+    assert_eq!(eps.name, "data");
+
+    let mut bytes = std::fs::read(&tmp_file)?;
+    corrupt(&mut bytes);
+    std::fs::write(&tmp_file, &bytes)?;
+    for result in [
+        <Data<Vec<u64>>>::mmap(&tmp_file, Flags::empty()).map(|_| ()),
+        <Data<Vec<u64>>>::load_mmap(&tmp_file, Flags::empty()).map(|_| ()),
+        <Data<Vec<u64>>>::load_full(&tmp_file).map(|_| ()),
+    ] {
+        assert!(matches!(
+            result.unwrap_err(),
+            deser::Error::ChecksumMismatch { .. }
+        ));
+    }
+    let eps = <Data<Vec<u64>>>::mmap(&tmp_file, Flags::SKIP_CHECKSUM)?;
+    assert_eq!(eps.values[999], 998);
+    std::fs::remove_file(&tmp_file)?;
+    Ok(())
+}
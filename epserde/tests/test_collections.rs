@@ -0,0 +1,88 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ * SPDX-FileCopyrightText: 2023 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+#![cfg(test)]
+
+use epserde::prelude::*;
+use std::collections::{BinaryHeap, LinkedList, VecDeque};
+
+fn serialize<T: Serialize>(value: &T) -> Vec<u8> {
+    let mut cursor = epserde::new_aligned_cursor();
+    value.serialize(&mut cursor).unwrap();
+    cursor.into_inner()
+}
+
+#[test]
+fn test_vec_deque() {
+    // Wrap around the end of the internal buffer
+    let mut queue = VecDeque::with_capacity(8);
+    queue.extend(4..10_u64);
+    for i in (0..4).rev() {
+        queue.push_front(i);
+    }
+    assert!(!queue.as_slices().1.is_empty());
+    let buf = serialize(&queue);
+
+    let view: &[u64] = <VecDeque<u64>>::deserialize_eps(&buf).unwrap();
+    assert_eq!(view, (0..10).collect::<Vec<_>>());
+    let full = <VecDeque<u64>>::deserialize_full(&mut buf.as_slice()).unwrap();
+    assert_eq!(full, queue);
+
+    let queue: VecDeque<String> = ["a", "b"].into_iter().map(String::from).collect();
+    let buf = serialize(&queue);
+    let view: Vec<&str> = <VecDeque<String>>::deserialize_eps(&buf).unwrap();
+    assert_eq!(view, ["a", "b"]);
+    let full = <VecDeque<String>>::deserialize_full(&mut buf.as_slice()).unwrap();
+    assert_eq!(full, queue);
+}
+
+#[test]
+fn test_linked_list() {
+    let list: LinkedList<(u32, u16)> = (0..5).map(|i| (i, i as u16 * 2)).collect();
+    let buf = serialize(&list);
+    let view: &[(u32, u16)] = <LinkedList<(u32, u16)>>::deserialize_eps(&buf).unwrap();
+    assert!(view.iter().eq(list.iter()));
+    let full = <LinkedList<(u32, u16)>>::deserialize_full(&mut buf.as_slice()).unwrap();
+    assert_eq!(full, list);
+
+    let list: LinkedList<Vec<u8>> = [vec![1, 2], vec![], vec![3]].into_iter().collect();
+    let buf = serialize(&list);
+    let view: Vec<&[u8]> = <LinkedList<Vec<u8>>>::deserialize_eps(&buf).unwrap();
+    assert_eq!(view, [&[1, 2][..], &[], &[3]]);
+    let full = <LinkedList<Vec<u8>>>::deserialize_full(&mut buf.as_slice()).unwrap();
+    assert_eq!(full, list);
+}
+
+#[test]
+fn test_binary_heap() {
+    let heap: BinaryHeap<i32> = [3, -1, 7, 0, 5, 7].into_iter().collect();
+    let buf = serialize(&heap);
+    let view: &[i32] = <BinaryHeap<i32>>::deserialize_eps(&buf).unwrap();
+    // Internal order, with the greatest element first
+    assert_eq!(view, heap.as_slice());
+    assert_eq!(view[0], 7);
+    let mut full = <BinaryHeap<i32>>::deserialize_full(&mut buf.as_slice()).unwrap();
+    assert_eq!(full.as_slice(), heap.as_slice());
+    assert_eq!(full.pop(), Some(7));
+    assert_eq!(full.pop(), Some(7));
+    assert_eq!(full.pop(), Some(5));
+}
+
+#[test]
+fn test_collections_hashes() {
+    let hashes = [
+        epserde::type_hash_of::<Vec<u32>>(),
+        epserde::type_hash_of::<VecDeque<u32>>(),
+        epserde::type_hash_of::<LinkedList<u32>>(),
+        epserde::type_hash_of::<BinaryHeap<u32>>(),
+    ];
+    for i in 0..hashes.len() {
+        for j in i + 1..hashes.len() {
+            assert_ne!(hashes[i], hashes[j]);
+        }
+    }
+}
@@ -0,0 +1,365 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ * SPDX-FileCopyrightText: 2023 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+#![cfg(test)]
+
+use epserde::prelude::*;
+
+mod v1 {
+    use epserde::prelude::*;
+
+    #[derive(Epserde, Debug, PartialEq, Clone)]
+    pub struct Inner {
+        pub a: u32,
+        pub b: String,
+    }
+
+    #[derive(Epserde, Debug, PartialEq, Clone)]
+    pub struct Outer<I> {
+        pub inner: I,
+        pub data: Vec<u128>,
+        pub name: String,
+    }
+
+    #[derive(Epserde, Debug, PartialEq, Clone)]
+    pub struct Concrete {
+        pub inner: Inner,
+        pub data: Vec<u64>,
+    }
+
+    #[derive(Epserde, Debug, PartialEq, Clone)]
+    pub struct Items {
+        pub items: Vec<Inner>,
+    }
+}
+
+mod v2 {
+    use epserde::prelude::*;
+
+    #[derive(Epserde, Debug, PartialEq, Clone)]
+    pub struct Inner {
+        pub a: u32,
+        pub b: String,
+        pub c: Vec<u8>,
+        pub d: u16,
+    }
+
+    #[derive(Epserde, Debug, PartialEq, Clone)]
+    pub struct Outer<I> {
+        pub inner: I,
+        pub data: Vec<u128>,
+        pub name: String,
+        pub extra: Vec<u64>,
+    }
+
+    #[derive(Epserde, Debug, PartialEq, Clone)]
+    pub struct Concrete {
+        pub inner: Inner,
+        pub data: Vec<u64>,
+    }
+
+    #[derive(Epserde, Debug, PartialEq, Clone)]
+    pub struct Items {
+        pub items: Vec<Inner>,
+    }
+}
+
+mod renamed {
+    use epserde::prelude::*;
+
+    #[derive(Epserde, Debug, PartialEq, Clone)]
+    pub struct Inner {
+        pub a: u32,
+        pub z: String,
+    }
+
+    #[derive(Epserde, Debug, PartialEq, Clone)]
+    pub struct Other {
+        pub a: u32,
+        pub b: String,
+    }
+}
+
+mod retyped {
+    use epserde::prelude::*;
+
+    #[derive(Epserde, Debug, PartialEq, Clone)]
+    pub struct Inner {
+        pub a: u64,
+        pub b: String,
+    }
+}
+
+fn serialize_with_embedded_schema(value: &impl Serialize) -> Vec<u8> {
+    let mut cursor = epserde::new_aligned_cursor();
+    value.serialize_with_embedded_schema(&mut cursor).unwrap();
+    cursor.into_inner()
+}
+
+fn serialize(value: &impl Serialize) -> Vec<u8> {
+    let mut cursor = epserde::new_aligned_cursor();
+    value.serialize(&mut cursor).unwrap();
+    cursor.into_inner()
+}
+
+fn v2_inner() -> v2::Inner {
+    v2::Inner {
+        a: 42,
+        b: "inner".into(),
+        c: vec![1, 2, 3],
+        d: 0xbeef,
+    }
+}
+
+fn v2_outer() -> v2::Outer<v2::Inner> {
+    v2::Outer {
+        inner: v2_inner(),
+        data: vec![1, 2, 3, u128::MAX],
+        name: "outer".into(),
+        extra: vec![4, 5],
+    }
+}
+
+#[test]
+fn test_same_type() {
+    let value = v2_outer();
+    let bytes = serialize_with_embedded_schema(&value);
+
+    // The embedded schema is ignored by the standard methods
+    let full = <v2::Outer<v2::Inner>>::deserialize_full(&mut std::io::Cursor::new(&bytes)).unwrap();
+    assert_eq!(full, value);
+    let eps = <v2::Outer<v2::Inner>>::deserialize_eps(&bytes).unwrap();
+    assert_eq!(eps.inner, value.inner);
+    assert_eq!(eps.data, value.data);
+
+    let full = <v2::Outer<v2::Inner>>::deserialize_full_compatible(&bytes).unwrap();
+    assert_eq!(full, value);
+    let eps = <v2::Outer<v2::Inner>>::deserialize_eps_compatible(&bytes).unwrap();
+    assert_eq!(eps.inner, value.inner);
+    assert_eq!(eps.data, value.data);
+    assert_eq!(eps.name, value.name);
+    assert_eq!(eps.extra, value.extra);
+}
+
+#[test]
+fn test_no_schema() {
+    let value = v2_outer();
+    let bytes = serialize(&value);
+
+    // Without schema, the hashes must match
+    let full = <v2::Outer<v2::Inner>>::deserialize_full_compatible(&bytes).unwrap();
+    assert_eq!(full, value);
+    assert!(matches!(
+        <v1::Outer<v1::Inner>>::deserialize_full_compatible(&bytes),
+        Err(deser::Error::WrongTypeHash { .. })
+    ));
+}
+
+#[test]
+fn test_trailing_fields() {
+    let value = v2_outer();
+    let bytes = serialize_with_embedded_schema(&value);
+
+    assert!(matches!(
+        <v1::Outer<v2::Inner>>::deserialize_eps(&bytes),
+        Err(deser::Error::WrongTypeHash { .. })
+    ));
+
+    let full = <v1::Outer<v2::Inner>>::deserialize_full_compatible(&bytes).unwrap();
+    assert_eq!(full.inner, value.inner);
+    assert_eq!(full.data, value.data);
+    assert_eq!(full.name, value.name);
+
+    let eps = <v1::Outer<v2::Inner>>::deserialize_eps_compatible(&bytes).unwrap();
+    assert_eq!(eps.inner, value.inner);
+    assert_eq!(eps.data, value.data);
+    assert_eq!(eps.name, value.name);
+}
+
+#[test]
+fn test_nested_fields() {
+    let value = v2_outer();
+    let bytes = serialize_with_embedded_schema(&value);
+
+    // Both the inner and the outer structure have evolved; the zero-copy
+    // vector following the inner structure must still be properly aligned
+    let full = <v1::Outer<v1::Inner>>::deserialize_full_compatible(&bytes).unwrap();
+    assert_eq!(
+        full,
+        v1::Outer {
+            inner: v1::Inner {
+                a: 42,
+                b: "inner".into(),
+            },
+            data: value.data.clone(),
+            name: value.name.clone(),
+        }
+    );
+
+    let eps = <v1::Outer<v1::Inner>>::deserialize_eps_compatible(&bytes).unwrap();
+    assert_eq!(eps.inner.a, 42);
+    assert_eq!(eps.inner.b, "inner");
+    assert_eq!(eps.data, value.data);
+    assert_eq!(eps.name, value.name);
+
+    // Only the outer structure has evolved
+    let eps = <v2::Outer<v1::Inner>>::deserialize_eps_compatible(&bytes).unwrap();
+    assert_eq!(eps.inner.b, "inner");
+    assert_eq!(eps.data, value.data);
+    assert_eq!(eps.extra, value.extra);
+
+    // Concrete field types are evolved as well
+    let value = v2::Concrete {
+        inner: v2_inner(),
+        data: vec![7, 8, 9],
+    };
+    let bytes = serialize_with_embedded_schema(&value);
+    let full = v1::Concrete::deserialize_full_compatible(&bytes).unwrap();
+    assert_eq!(full.inner.a, 42);
+    assert_eq!(full.inner.b, "inner");
+    assert_eq!(full.data, value.data);
+    let eps = v1::Concrete::deserialize_eps_compatible(&bytes).unwrap();
+    assert_eq!(eps.inner.b, "inner");
+    assert_eq!(eps.data, value.data);
+}
+
+#[test]
+fn test_root() {
+    // The root itself can be an evolved structure
+    let bytes = serialize_with_embedded_schema(&v2_inner());
+    let full = v1::Inner::deserialize_full_compatible(&bytes).unwrap();
+    assert_eq!(
+        full,
+        v1::Inner {
+            a: 42,
+            b: "inner".into()
+        }
+    );
+
+    // Leaves must match exactly
+    let bytes = serialize_with_embedded_schema(&vec![1_u32, 2, 3]);
+    assert_eq!(
+        <Vec<u32>>::deserialize_eps_compatible(&bytes).unwrap(),
+        [1, 2, 3]
+    );
+    assert!(matches!(
+        <Vec<u64>>::deserialize_eps_compatible(&bytes),
+        Err(deser::Error::IncompatibleSchema { .. })
+    ));
+}
+
+fn field<T>(result: deser::Result<T>) -> String {
+    match result {
+        Err(deser::Error::IncompatibleSchema { field, .. }) => field,
+        _ => panic!("Expected an incompatible schema"),
+    }
+}
+
+#[test]
+fn test_incompatible() {
+    let bytes = serialize_with_embedded_schema(&v1::Outer {
+        inner: v1::Inner {
+            a: 42,
+            b: "inner".into(),
+        },
+        data: vec![],
+        name: String::new(),
+    });
+
+    // The deserialized type has more fields
+    assert_eq!(
+        field(<v2::Outer<v1::Inner>>::deserialize_full_compatible(&bytes)),
+        "ROOT"
+    );
+    assert_eq!(
+        field(<v1::Outer<v2::Inner>>::deserialize_full_compatible(&bytes)),
+        "ROOT.inner"
+    );
+    // A field has been renamed
+    assert_eq!(
+        field(<v1::Outer<renamed::Inner>>::deserialize_full_compatible(
+            &bytes
+        )),
+        "ROOT.inner"
+    );
+    // A structure has been renamed
+    assert_eq!(
+        field(<v1::Outer<renamed::Other>>::deserialize_full_compatible(
+            &bytes
+        )),
+        "ROOT.inner"
+    );
+    // The type of a field has changed
+    assert_eq!(
+        field(<v1::Outer<retyped::Inner>>::deserialize_full_compatible(
+            &bytes
+        )),
+        "ROOT.inner.a"
+    );
+    // A structure has been replaced by another type
+    assert_eq!(
+        field(<v1::Outer<u32>>::deserialize_full_compatible(&bytes)),
+        "ROOT.inner"
+    );
+
+    // Structures inside other types cannot evolve
+    let bytes = serialize_with_embedded_schema(&v2::Items {
+        items: vec![v2_inner()],
+    });
+    assert_eq!(
+        field(v1::Items::deserialize_eps_compatible(&bytes)),
+        "ROOT.items"
+    );
+}
+
+#[test]
+fn test_corrupted_trailer() {
+    let mut bytes = serialize_with_embedded_schema(&v2_outer());
+    let trailer_start = bytes.len() - 16;
+    bytes[trailer_start..trailer_start + 8].copy_from_slice(&u64::MAX.to_ne_bytes());
+    assert!(matches!(
+        <v1::Outer<v1::Inner>>::deserialize_full_compatible(&bytes),
+        Err(deser::Error::ReadError)
+    ));
+}
+
+#[test]
+fn test_deep_schema() {
+    let mut bytes = serialize_with_embedded_schema(&v2_outer());
+    let trailer_start = bytes.len() - 16;
+    let start = u64::from_ne_bytes(bytes[trailer_start..trailer_start + 8].try_into().unwrap());
+    bytes.truncate(start as usize);
+    bytes.resize(bytes.len().next_multiple_of(16), 0);
+    let start = bytes.len() as u64;
+
+    // A schema describing structures nested too deeply
+    let shape = vec![("f".to_string(), "S".to_string(), 0_u64, 1_usize); 100_000];
+    let schema = (shape.clone(), shape, Vec::<(String, usize, usize)>::new());
+    bytes.extend(serialize(&schema));
+    bytes.extend(start.to_ne_bytes());
+    bytes.extend(epserde::compat::SCHEMA_MAGIC.to_ne_bytes());
+    assert!(matches!(
+        <v1::Outer<v1::Inner>>::deserialize_full_compatible(&bytes),
+        Err(deser::Error::SchemaTooDeep { max_depth }) if max_depth == epserde::compat::MAX_SCHEMA_DEPTH
+    ));
+}
+
+#[test]
+fn test_store() {
+    let value = v2_outer();
+    value.store_with_embedded_schema("test_compat.bin").unwrap();
+
+    let res = <v2::Outer<v2::Inner>>::load_full("test_compat.bin").unwrap();
+    assert_eq!(res, value);
+
+    let bytes = std::fs::read("test_compat.bin").unwrap();
+    let res = <v1::Outer<v1::Inner>>::deserialize_full_compatible(&bytes).unwrap();
+    assert_eq!(res.inner.b, "inner");
+    assert_eq!(res.data, value.data);
+
+    std::fs::remove_file("test_compat.bin").unwrap();
+}
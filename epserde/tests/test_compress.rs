@@ -0,0 +1,298 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+#![cfg(test)]
+
+use core::hash::Hasher;
+use epserde::compress::Codec;
+use epserde::prelude::*;
+
+/// A run-length encoding codec, writing pairs (run length, byte).
+struct Rle;
+
+impl Codec for Rle {
+    const NAME: &'static str = "rle";
+
+    fn compress(data: &[u8]) -> ser::Result<Vec<u8>> {
+        let mut result = vec![];
+        for chunk in data.chunk_by(|a, b| a == b) {
+            for run in chunk.chunks(u8::MAX as usize) {
+                result.push(run.len() as u8);
+                result.push(run[0]);
+            }
+        }
+        Ok(result)
+    }
+
+    fn decompress(data: &[u8], buf: &mut [u8]) -> deser::Result<()> {
+        let mut pos = 0;
+        for pair in data.chunks(2) {
+            let [len, byte] = pair else {
+                return Err(deser::Error::ReadError);
+            };
+            let end = pos + *len as usize;
+            buf.get_mut(pos..end)
+                .ok_or(deser::Error::ReadError)?
+                .fill(*byte);
+            pos = end;
+        }
+        if pos != buf.len() {
+            return Err(deser::Error::ReadError);
+        }
+        Ok(())
+    }
+}
+
+/// Another codec, identical to [`Rle`] but for the name.
+struct OtherRle;
+
+impl Codec for OtherRle {
+    const NAME: &'static str = "other";
+
+    fn compress(data: &[u8]) -> ser::Result<Vec<u8>> {
+        Rle::compress(data)
+    }
+
+    fn decompress(data: &[u8], buf: &mut [u8]) -> deser::Result<()> {
+        Rle::decompress(data, buf)
+    }
+}
+
+#[derive(Epserde, Debug, PartialEq, Clone)]
+struct Inner {
+    id: usize,
+    tags: Vec<String>,
+}
+
+#[derive(Epserde, Debug, PartialEq, Clone)]
+struct Data<A, C> {
+    hot: A,
+    #[epserde(compress = "Rle")]
+    cold: C,
+    #[epserde(compress = "Rle")]
+    inner: Inner,
+    count: usize,
+}
+
+#[derive(Epserde, Debug, PartialEq, Clone)]
+struct Plain<A, C> {
+    hot: A,
+    cold: C,
+    inner: Inner,
+    count: usize,
+}
+
+#[derive(Epserde, Debug, PartialEq, Clone)]
+struct OtherCodec<A, C> {
+    hot: A,
+    #[epserde(compress = "OtherRle")]
+    cold: C,
+    #[epserde(compress = "OtherRle")]
+    inner: Inner,
+    count: usize,
+}
+
+fn data() -> Data<Vec<u64>, Vec<u32>> {
+    Data {
+        hot: vec![1, 2, 3],
+        cold: vec![0; 1000],
+        inner: Inner {
+            id: 7,
+            tags: vec!["a".repeat(100), "b".into()],
+        },
+        count: 42,
+    }
+}
+
+#[test]
+fn test_compress() {
+    let data = data();
+    let mut cursor = epserde::new_aligned_cursor();
+    let len = data.serialize(&mut cursor).unwrap();
+    let bytes = cursor.into_inner();
+
+    let full =
+        <Data<Vec<u64>, Vec<u32>>>::deserialize_full(&mut std::io::Cursor::new(&bytes)).unwrap();
+    assert_eq!(full, data);
+
+    let eps = <Data<Vec<u64>, Vec<u32>>>::deserialize_eps(&bytes).unwrap();
+    // Uncompressed fields are ε-copy deserialized...
+    let hot: &[u64] = eps.hot;
+    assert_eq!(hot, data.hot);
+    // ...but compressed fields are owned
+    let cold: Vec<u32> = eps.cold;
+    assert_eq!(cold, data.cold);
+    assert_eq!(eps.inner, data.inner);
+    assert_eq!(eps.count, 42);
+
+    // The compressed data is much shorter
+    let plain = Plain {
+        hot: data.hot.clone(),
+        cold: data.cold.clone(),
+        inner: data.inner.clone(),
+        count: data.count,
+    };
+    let mut cursor = epserde::new_aligned_cursor();
+    let plain_len = plain.serialize(&mut cursor).unwrap();
+    assert!(len + 3000 < plain_len);
+}
+
+#[test]
+fn test_compress_portable() {
+    let data = data();
+    let mut cursor = epserde::new_aligned_cursor();
+    data.serialize_portable(&mut cursor).unwrap();
+    let bytes = cursor.into_inner();
+    let full =
+        <Data<Vec<u64>, Vec<u32>>>::deserialize_full(&mut std::io::Cursor::new(&bytes)).unwrap();
+    assert_eq!(full, data);
+}
+
+#[test]
+fn test_compress_type_hash() {
+    fn type_hash<T: TypeHash>() -> u64 {
+        let mut hasher = xxhash_rust::xxh3::Xxh3::new();
+        T::type_hash(&mut hasher);
+        hasher.finish()
+    }
+    let compressed = type_hash::<Data<Vec<u64>, Vec<u32>>>();
+    assert_ne!(compressed, type_hash::<Plain<Vec<u64>, Vec<u32>>>());
+    assert_ne!(compressed, type_hash::<OtherCodec<Vec<u64>, Vec<u32>>>());
+
+    let mut cursor = epserde::new_aligned_cursor();
+    data().serialize(&mut cursor).unwrap();
+    let bytes = cursor.into_inner();
+    assert!(matches!(
+        <OtherCodec<Vec<u64>, Vec<u32>>>::deserialize_eps(&bytes),
+        Err(deser::Error::WrongTypeHash { .. })
+    ));
+}
+
+#[test]
+fn test_compress_corrupted() {
+    let mut cursor = epserde::new_aligned_cursor();
+    let data = Data {
+        hot: vec![1_u64],
+        cold: vec![5_u8; 10],
+        inner: Inner {
+            id: 0,
+            tags: vec![],
+        },
+        count: 0,
+    };
+    data.serialize(&mut cursor).unwrap();
+    let mut bytes = cursor.into_inner();
+    // Corrupt the only run in the compressed data of the cold field
+    let pos = bytes.windows(2).position(|w| w == [10, 5]).unwrap();
+    bytes[pos] = 11;
+    assert!(matches!(
+        <Data<Vec<u64>, Vec<u8>>>::deserialize_eps(&bytes),
+        Err(deser::Error::ReadError)
+    ));
+}
+
+#[test]
+fn test_compress_bounded() {
+    /// A codec accepting only small compression ratios.
+    struct Bounded;
+
+    impl Codec for Bounded {
+        const NAME: &'static str = "bounded";
+
+        fn compress(data: &[u8]) -> ser::Result<Vec<u8>> {
+            Rle::compress(data)
+        }
+
+        fn decompress(data: &[u8], buf: &mut [u8]) -> deser::Result<()> {
+            Rle::decompress(data, buf)
+        }
+
+        fn max_decompressed_len(data: &[u8]) -> usize {
+            data.len() * 4
+        }
+    }
+
+    let mut cursor = epserde::new_aligned_cursor();
+    assert!(matches!(
+        vec![0_u32; 1000].serialize_compressed::<Bounded>(&mut cursor),
+        Err(ser::Error::CompressionRatio { len, .. }) if len == 8 + 4000
+    ));
+
+    // A corrupted length does not cause a huge allocation
+    let data = data();
+    let mut cursor = epserde::new_aligned_cursor();
+    let schema = data.serialize_with_schema(&mut cursor).unwrap();
+    let mut bytes = cursor.into_inner();
+    let len = schema
+        .0
+        .iter()
+        .find(|row| row.field == "ROOT.cold.len")
+        .unwrap();
+    bytes[len.offset..len.offset + len.size].copy_from_slice(&usize::MAX.to_ne_bytes());
+    assert!(matches!(
+        <Data<Vec<u64>, Vec<u32>>>::deserialize_full(&mut std::io::Cursor::new(&bytes)),
+        Err(deser::Error::InvalidValue("Compressed"))
+    ));
+}
+
+#[test]
+fn test_compress_payload() {
+    let plain = Plain {
+        hot: vec![1_u64, 2, 3],
+        cold: vec![0_u32; 1000],
+        inner: Inner {
+            id: 7,
+            tags: vec!["a".repeat(100)],
+        },
+        count: 42,
+    };
+    let mut cursor = epserde::new_aligned_cursor();
+    let len = plain.serialize_compressed::<Rle>(&mut cursor).unwrap();
+    let bytes = cursor.into_inner();
+    let mut cursor = epserde::new_aligned_cursor();
+    assert!(len + 3000 < plain.serialize(&mut cursor).unwrap());
+
+    let full = <Plain<Vec<u64>, Vec<u32>>>::deserialize_full_compressed::<Rle>(
+        &mut std::io::Cursor::new(&bytes),
+    )
+    .unwrap();
+    assert_eq!(full, plain);
+
+    // Slices are serialized as vectors
+    let mut cursor = epserde::new_aligned_cursor();
+    plain.cold[..]
+        .serialize_compressed::<Rle>(&mut cursor)
+        .unwrap();
+    let cold = <Vec<u32>>::deserialize_full_compressed::<Rle>(&mut std::io::Cursor::new(
+        cursor.into_inner(),
+    ))
+    .unwrap();
+    assert_eq!(cold, plain.cold);
+
+    // The codec must match...
+    assert!(matches!(
+        <Plain<Vec<u64>, Vec<u32>>>::deserialize_full_compressed::<OtherRle>(
+            &mut std::io::Cursor::new(&bytes)
+        ),
+        Err(deser::Error::WrongCodec { .. })
+    ));
+    // ...the payload cannot be read as uncompressed data...
+    assert!(matches!(
+        <Plain<Vec<u64>, Vec<u32>>>::deserialize_full(&mut std::io::Cursor::new(&bytes)),
+        Err(deser::Error::WrongCodec { .. })
+    ));
+    assert!(matches!(
+        <Plain<Vec<u64>, Vec<u32>>>::deserialize_eps(&bytes),
+        Err(deser::Error::WrongCodec { .. })
+    ));
+    // ...and the type is checked before decompressing
+    assert!(matches!(
+        <Plain<Vec<u32>, Vec<u32>>>::deserialize_full_compressed::<Rle>(&mut std::io::Cursor::new(
+            &bytes
+        )),
+        Err(deser::Error::WrongTypeHash { .. })
+    ));
+}
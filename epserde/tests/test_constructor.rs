@@ -0,0 +1,47 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+#![cfg(test)]
+
+use epserde::deser::DeserType;
+use epserde::prelude::*;
+
+#[derive(Epserde, Debug, PartialEq)]
+#[epserde(constructor)]
+struct Index<A, S> {
+    offsets: A,
+    name: S,
+    version: u32,
+}
+
+#[derive(Epserde, Debug, PartialEq)]
+#[epserde(constructor = "from_parts")]
+struct Pair<A>(A, u8);
+
+/// A function accepting the ε-copy deserialization type of `Index`.
+fn total(index: &DeserType<'_, Index<Vec<u64>, String>>) -> u64 {
+    index.offsets.iter().sum::<u64>() + index.name.len() as u64 + index.version as u64
+}
+
+#[test]
+fn test_constructor() {
+    let owned = Index::new(vec![1_u64, 2, 3], "abc".to_string(), 7);
+    assert_eq!(owned.offsets, [1, 2, 3]);
+
+    // The deserialization type can be built without serializing
+    let view: DeserType<'_, Index<Vec<u64>, String>> = Index::new(&[1, 2, 3][..], "abc", 7);
+    assert_eq!(total(&view), 16);
+
+    // It is the same as the one obtained by ε-copy deserialization
+    let mut cursor = epserde::new_aligned_cursor();
+    owned.serialize(&mut cursor).unwrap();
+    let buf = cursor.into_inner();
+    let eps = <Index<Vec<u64>, String>>::deserialize_eps(&buf).unwrap();
+    assert_eq!(eps, view);
+
+    let pair = Pair::from_parts(&[1_u32][..], 2);
+    assert_eq!(pair, Pair(&[1][..], 2));
+}
@@ -0,0 +1,133 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ * SPDX-FileCopyrightText: 2023 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+#![cfg(test)]
+
+use core::hash::Hash;
+use epserde::prelude::*;
+use std::sync::Arc;
+
+/// A value to which the deserialization context, if it is a `u64`, is added.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Offset(u64);
+
+impl CopyType for Offset {
+    type Copy = Deep;
+}
+
+impl TypeHash for Offset {
+    fn type_hash(hasher: &mut impl core::hash::Hasher) {
+        "Offset".hash(hasher);
+    }
+}
+
+impl ReprHash for Offset {
+    fn repr_hash(_hasher: &mut impl core::hash::Hasher, _offset_of: &mut usize) {}
+}
+
+impl SerializeInner for Offset {
+    const IS_ZERO_COPY: bool = false;
+    const ZERO_COPY_MISMATCH: bool = false;
+    const MAX_FIELD_ALIGN: usize = u64::MAX_FIELD_ALIGN;
+    fn _serialize_inner(&self, backend: &mut impl ser::WriteWithNames) -> ser::Result<()> {
+        backend.write("value", &self.0)
+    }
+}
+
+impl DeserializeInner for Offset {
+    type DeserType<'a> = Offset;
+
+    fn _deserialize_full_inner(backend: &mut impl deser::ReadWithPos) -> deser::Result<Self> {
+        let value = u64::_deserialize_full_inner(backend)?;
+        Ok(Offset(
+            value + backend.context_as::<u64>().copied().unwrap_or(0),
+        ))
+    }
+
+    fn _deserialize_eps_inner<'a>(
+        backend: &mut deser::SliceWithPos<'a>,
+    ) -> deser::Result<Self::DeserType<'a>> {
+        Self::_deserialize_full_inner(backend)
+    }
+}
+
+#[derive(Epserde, Debug, PartialEq)]
+struct Data<A> {
+    forwarded: Offset,
+    #[epserde(ignore_context)]
+    ignored: Offset,
+    generic: A,
+    #[epserde(ignore_context)]
+    ignored_generic: A,
+}
+
+fn serialize(data: &impl Serialize) -> Vec<u8> {
+    let mut cursor = epserde::new_aligned_cursor();
+    data.serialize(&mut cursor).unwrap();
+    cursor.into_inner()
+}
+
+#[test]
+fn test_context() {
+    let data = Data {
+        forwarded: Offset(1),
+        ignored: Offset(2),
+        generic: vec![Offset(3)],
+        ignored_generic: vec![Offset(4)],
+    };
+    let buf = serialize(&data);
+
+    let expected = Data {
+        forwarded: Offset(101),
+        ignored: Offset(2),
+        generic: vec![Offset(103)],
+        ignored_generic: vec![Offset(4)],
+    };
+
+    let full = Data::<Vec<Offset>>::deserialize_full_with_context(&mut &buf[..], Arc::new(100_u64))
+        .unwrap();
+    assert_eq!(full, expected);
+
+    let eps = Data::<Vec<Offset>>::deserialize_eps_with_context(&buf, Arc::new(100_u64)).unwrap();
+    assert_eq!(eps.forwarded, expected.forwarded);
+    assert_eq!(eps.ignored, expected.ignored);
+    assert_eq!(eps.generic, expected.generic);
+    assert_eq!(eps.ignored_generic, expected.ignored_generic);
+
+    // A context of a different type is not seen
+    let full = Data::<Vec<Offset>>::deserialize_full_with_context(&mut &buf[..], Arc::new(100_u32))
+        .unwrap();
+    assert_eq!(full, data);
+
+    // No context
+    let full = Data::<Vec<Offset>>::deserialize_full(&mut &buf[..]).unwrap();
+    assert_eq!(full, data);
+}
+
+#[test]
+fn test_without_context() {
+    let buf = serialize(&0_u64);
+    let mut bytes = &buf[..];
+    let mut backend = deser::ReaderWithPos::new(&mut bytes);
+    assert!(backend.context().is_none());
+    backend.replace_context(Some(Arc::new(1_u64)));
+    assert_eq!(backend.context_as::<u64>(), Some(&1));
+    assert_eq!(backend.context_as::<u32>(), None);
+
+    deser::without_context(&mut backend, |backend| {
+        assert!(backend.context().is_none());
+        Ok(())
+    })
+    .unwrap();
+    assert_eq!(backend.context_as::<u64>(), Some(&1));
+
+    // The context is restored after an error, too
+    let result: deser::Result<()> =
+        deser::without_context(&mut backend, |_| Err(deser::Error::ReadError));
+    assert!(result.is_err());
+    assert_eq!(backend.context_as::<u64>(), Some(&1));
+}
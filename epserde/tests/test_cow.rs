@@ -0,0 +1,121 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+#![cfg(test)]
+
+use epserde::deser::CowCase;
+use epserde::prelude::*;
+use std::borrow::Cow;
+
+#[derive(Epserde, Debug, PartialEq, Clone)]
+struct Data<A, S> {
+    values: A,
+    name: S,
+    fixed: Cow<'static, [u16]>,
+}
+
+fn data() -> Data<Cow<'static, [u32]>, Cow<'static, str>> {
+    Data {
+        values: Cow::Owned(vec![1, 2, 3]),
+        name: Cow::Borrowed("data"),
+        fixed: Cow::Borrowed(&[4, 5]),
+    }
+}
+
+#[test]
+fn test_cow() {
+    let data = data();
+    let mut cursor = epserde::new_aligned_cursor();
+    data.serialize(&mut cursor).unwrap();
+    let bytes = cursor.into_inner();
+
+    type Type = Data<Cow<'static, [u32]>, Cow<'static, str>>;
+    let full = Type::deserialize_full(&mut std::io::Cursor::new(&bytes)).unwrap();
+    assert_eq!(full, data);
+    assert!(matches!(full.values, Cow::Owned(_)));
+    assert!(matches!(full.name, Cow::Owned(_)));
+
+    let mut eps = Type::deserialize_eps(&bytes).unwrap();
+    assert!(matches!(eps.values, Cow::Borrowed(_)));
+    assert!(matches!(eps.name, Cow::Borrowed(_)));
+    assert_eq!(eps.values, data.values);
+    assert_eq!(eps.name, "data");
+    // Non-generic fields are fully deserialized
+    assert!(matches!(eps.fixed, Cow::Owned(_)));
+
+    // Mutation clones the data
+    eps.values.to_mut().push(4);
+    assert_eq!(eps.values[..], [1, 2, 3, 4]);
+    assert_eq!(Type::deserialize_eps(&bytes).unwrap().values, data.values);
+}
+
+#[test]
+fn test_cow_type_hash() {
+    let mut cursor = epserde::new_aligned_cursor();
+    vec![1_u64, 2, 3].serialize(&mut cursor).unwrap();
+    let vec_bytes = cursor.into_inner();
+    let mut cursor = epserde::new_aligned_cursor();
+    Cow::<'static, [u64]>::Owned(vec![1, 2, 3])
+        .serialize(&mut cursor)
+        .unwrap();
+    let cow_bytes = cursor.into_inner();
+    // The payloads are the same, but the types are different
+    assert!(<Cow<'static, [u64]>>::deserialize_eps(&vec_bytes).is_err());
+    assert!(<Vec<u64>>::deserialize_eps(&cow_bytes).is_err());
+}
+
+#[test]
+fn test_cow_case() -> anyhow::Result<()> {
+    let tmp_file = std::env::temp_dir().join("test_cow_case.eps");
+    let data = Data {
+        values: vec![1_u32, 2, 3],
+        name: "data".to_string(),
+        fixed: Cow::Borrowed(&[4, 5]),
+    };
+    data.store(&tmp_file)?;
+
+    let mut case = CowCase::<Data<Vec<u32>, String>>::load_mem(&tmp_file)?;
+    assert!(!case.is_owned());
+    assert!(case.as_owned().is_none());
+    let borrowed = case.as_borrowed().unwrap();
+    assert_eq!(borrowed.values, &[1, 2, 3]);
+    assert_eq!(borrowed.name, "data");
+
+    // The first write materializes the structure
+    case.to_mut()?.values.push(4);
+    assert!(case.is_owned());
+    assert!(case.as_borrowed().is_none());
+    case.to_mut()?.name.push('!');
+    let owned = case.into_owned()?;
+    assert_eq!(owned.values, vec![1, 2, 3, 4]);
+    assert_eq!(owned.name, "data!");
+
+    let case = CowCase::from_owned(data.clone());
+    assert_eq!(case.as_owned(), Some(&data));
+
+    std::fs::remove_file(&tmp_file)?;
+    Ok(())
+}
+
+#[cfg(feature = "mmap-rs")]
+#[test]
+fn test_cow_case_mmap() -> anyhow::Result<()> {
+    let tmp_file = std::env::temp_dir().join("test_cow_case_mmap.eps");
+    let data = Data {
+        values: vec![1_u32, 2, 3],
+        name: "data".to_string(),
+        fixed: Cow::Borrowed(&[4, 5]),
+    };
+    data.store(&tmp_file)?;
+    let case = CowCase::<Data<Vec<u32>, String>>::mmap(&tmp_file, Flags::empty())?;
+    assert_eq!(case.as_borrowed().unwrap().values, &[1, 2, 3]);
+    assert_eq!(case.into_owned()?, data);
+    let mut case = CowCase::<Data<Vec<u32>, String>>::load_mmap(&tmp_file, Flags::empty())?;
+    case.to_mut()?.values.clear();
+    assert!(case.as_owned().unwrap().values.is_empty());
+    std::fs::remove_file(&tmp_file)?;
+    Ok(())
+}
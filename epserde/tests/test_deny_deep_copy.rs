@@ -0,0 +1,48 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+#![cfg(test)]
+
+use epserde::prelude::*;
+
+#[derive(Epserde, Debug, PartialEq, Eq, Clone, Copy)]
+#[repr(C)]
+#[zero_copy]
+struct Point {
+    x: u64,
+    y: u64,
+}
+
+#[derive(Epserde, Debug, PartialEq, Eq)]
+#[epserde(deny_deep_copy)]
+#[deep_copy]
+struct Hot<P> {
+    count: usize,
+    origin: Point,
+    pair: (u32, u16),
+    corners: [Point; 2],
+    generic: P,
+}
+
+#[test]
+fn test_deny_deep_copy() {
+    let hot = Hot {
+        count: 2,
+        origin: Point { x: 0, y: 0 },
+        pair: (1, 2),
+        corners: [Point { x: 1, y: 2 }, Point { x: 3, y: 4 }],
+        generic: Point { x: 5, y: 6 },
+    };
+    let mut cursor = epserde::new_aligned_cursor();
+    hot.serialize(&mut cursor).unwrap();
+    cursor.set_position(0);
+    let full = <Hot<Point>>::deserialize_full(&mut cursor).unwrap();
+    assert_eq!(full, hot);
+    let buf = cursor.into_inner();
+    let eps = <Hot<Point>>::deserialize_eps(&buf).unwrap();
+    assert_eq!(eps.generic, &Point { x: 5, y: 6 });
+    assert_eq!(eps.corners, hot.corners);
+}
@@ -0,0 +1,96 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ * SPDX-FileCopyrightText: 2023 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+#![cfg(test)]
+
+use epserde::deser::DeserType;
+use epserde::prelude::*;
+use std::collections::{BTreeMap, BTreeSet};
+
+#[derive(Epserde, Debug, Clone, Copy, PartialEq)]
+#[repr(C)]
+#[zero_copy]
+struct Point {
+    x: f64,
+    y: f64,
+}
+
+#[derive(Epserde, Debug, Clone, Copy, PartialEq)]
+struct Data<A, B> {
+    id: usize,
+    values: A,
+    name: B,
+}
+
+#[derive(Epserde, Debug, Clone, Copy, PartialEq)]
+enum Shape<A> {
+    Empty,
+    Points(A),
+}
+
+fn assert_clone<T: Clone>() {}
+fn assert_copy<T: Copy>() {}
+
+#[test]
+fn test_copy() {
+    assert_copy::<DeserType<'_, usize>>();
+    assert_copy::<DeserType<'_, Point>>();
+    assert_copy::<DeserType<'_, [Point; 4]>>();
+    assert_copy::<DeserType<'_, Vec<u32>>>();
+    assert_copy::<DeserType<'_, Vec<Point>>>();
+    assert_copy::<DeserType<'_, Box<[u32]>>>();
+    assert_copy::<DeserType<'_, String>>();
+    assert_copy::<DeserType<'_, Box<str>>>();
+    assert_copy::<DeserType<'_, Option<Vec<u32>>>>();
+    assert_copy::<DeserType<'_, (Vec<u32>, String)>>();
+    assert_copy::<DeserType<'_, [String; 2]>>();
+    assert_copy::<DeserType<'_, BTreeMap<u32, Point>>>();
+    assert_copy::<DeserType<'_, BTreeSet<u32>>>();
+    assert_copy::<DeserType<'_, Data<Vec<Point>, String>>>();
+    assert_copy::<DeserType<'_, Shape<Vec<Point>>>>();
+    assert_copy::<DeserType<'_, Data<Data<Vec<u32>, String>, Option<String>>>>();
+}
+
+#[test]
+fn test_clone() {
+    assert_clone::<DeserType<'_, Vec<String>>>();
+    assert_clone::<DeserType<'_, Vec<Vec<u32>>>>();
+    assert_clone::<DeserType<'_, Box<Vec<u32>>>>();
+    assert_clone::<DeserType<'_, Data<Vec<String>, String>>>();
+}
+
+/// A function taking a view by value.
+fn total(data: Data<&[u32], &str>) -> u32 {
+    data.values.iter().sum::<u32>() + data.name.len() as u32
+}
+
+#[test]
+fn test_by_value() -> anyhow::Result<()> {
+    let data = Data {
+        id: 0,
+        values: vec![1_u32, 2, 3],
+        name: "name".to_string(),
+    };
+    let mut cursor = epserde::new_aligned_cursor();
+    data.serialize(&mut cursor)?;
+    let buf = cursor.into_inner();
+
+    let eps = <Data<Vec<u32>, String>>::deserialize_eps(&buf)?;
+    // The view is copied, so it can be used after the calls
+    assert_eq!(total(eps), 10);
+    assert_eq!(total(eps), 10);
+    let views = [eps; 2];
+    assert_eq!(views[1], eps);
+
+    let path = std::env::temp_dir().join("test_deser_copy.eps");
+    data.store(&path)?;
+    let mem = <Data<Vec<u32>, String>>::load_mem(&path)?;
+    // The view can be copied out of the memory case
+    assert_eq!(total(*mem), 10);
+    std::fs::remove_file(&path)?;
+    Ok(())
+}
@@ -0,0 +1,76 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+#![cfg(test)]
+
+use epserde::prelude::*;
+
+/// A hand-written view on a bit vector.
+#[derive(Debug)]
+struct BitsView<'a> {
+    words: &'a [u64],
+}
+
+impl<'a> BitsView<'a> {
+    /// Read the view from the serialization of a `Vec<u64>`.
+    fn read(backend: &mut SliceWithPos<'a>) -> deser::Result<Self> {
+        Ok(Self {
+            words: <Vec<u64>>::_deserialize_eps_inner(backend)?,
+        })
+    }
+
+    fn get(&self, index: usize) -> bool {
+        self.words[index / 64] >> (index % 64) & 1 != 0
+    }
+}
+
+/// A hand-written view on a string, converted using [`From`].
+#[derive(Debug)]
+struct Shouting(String);
+
+impl From<&str> for Shouting {
+    fn from(s: &str) -> Self {
+        Self(s.to_uppercase())
+    }
+}
+
+#[derive(Epserde, Debug, PartialEq, Eq)]
+struct Data<B, S, V> {
+    #[epserde(deser_type = "BitsView<'epserde>", deser_with = "BitsView::read")]
+    bits: B,
+    #[epserde(deser_type = "Shouting")]
+    name: S,
+    values: V,
+    count: usize,
+}
+
+#[test]
+fn test_deser_type_override() {
+    let data = Data {
+        bits: vec![0b1010_u64, 1],
+        name: "quiet".to_string(),
+        values: vec![1_u32, 2, 3],
+        count: 42,
+    };
+
+    let mut cursor = epserde::new_aligned_cursor();
+    data.serialize(&mut cursor).unwrap();
+
+    // Full-copy deserialization is not affected
+    cursor.set_position(0);
+    let full = <Data<Vec<u64>, String, Vec<u32>>>::deserialize_full(&mut cursor).unwrap();
+    assert_eq!(full, data);
+
+    let buf = cursor.into_inner();
+    let eps = <Data<Vec<u64>, String, Vec<u32>>>::deserialize_eps(&buf).unwrap();
+    assert!(!eps.bits.get(0));
+    assert!(eps.bits.get(1));
+    assert!(eps.bits.get(3));
+    assert!(eps.bits.get(64));
+    assert_eq!(eps.name.0, "QUIET");
+    assert_eq!(eps.values, &[1, 2, 3]);
+    assert_eq!(eps.count, 42);
+}
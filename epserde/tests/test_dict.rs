@@ -0,0 +1,170 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+#![cfg(test)]
+
+use epserde::deser::DeserType;
+use epserde::dict::{DeserializeWithDict, WithDict};
+use epserde::prelude::*;
+
+/// A deep-copy record referring to a string table.
+#[derive(Epserde, Debug, Clone, PartialEq)]
+struct Record {
+    name: u32,
+    tags: Vec<u32>,
+    values: Vec<f64>,
+}
+
+#[derive(Debug, PartialEq)]
+struct RecordView<'a> {
+    name: &'a str,
+    tags: Vec<&'a str>,
+    values: Vec<f64>,
+}
+
+fn resolve<'a>(dict: &[&'a str], index: u32) -> deser::Result<&'a str> {
+    dict.get(index as usize)
+        .copied()
+        .ok_or(deser::Error::InvalidValue("Record"))
+}
+
+impl DeserializeWithDict<Vec<String>> for Record {
+    type DictDeserType<'a> = RecordView<'a>;
+
+    fn _deserialize_eps_with_dict<'a>(
+        backend: &mut deser::SliceWithPos<'a>,
+        dict: &DeserType<'a, Vec<String>>,
+    ) -> deser::Result<RecordView<'a>> {
+        let record = Record::_deserialize_eps_inner(backend)?;
+        Ok(RecordView {
+            name: resolve(dict, record.name)?,
+            tags: record
+                .tags
+                .iter()
+                .map(|&tag| resolve(dict, tag))
+                .collect::<deser::Result<_>>()?,
+            values: record.values,
+        })
+    }
+}
+
+/// A zero-copy pair of indices.
+#[derive(Epserde, Debug, Clone, Copy, PartialEq)]
+#[repr(C)]
+#[zero_copy]
+struct Link(u16, u16);
+
+impl DeserializeWithDict<Vec<String>> for Link {
+    type DictDeserType<'a> = (&'a str, &'a str);
+
+    fn _deserialize_eps_with_dict<'a>(
+        backend: &mut deser::SliceWithPos<'a>,
+        dict: &DeserType<'a, Vec<String>>,
+    ) -> deser::Result<(&'a str, &'a str)> {
+        let link = Link::_deserialize_eps_inner(backend)?;
+        Ok((resolve(dict, link.0 as u32)?, resolve(dict, link.1 as u32)?))
+    }
+}
+
+fn dict() -> Vec<String> {
+    ["alpha", "beta", "gamma"].map(String::from).to_vec()
+}
+
+#[test]
+fn test_with_dict() -> anyhow::Result<()> {
+    let data = WithDict {
+        dict: dict(),
+        items: vec![
+            Record {
+                name: 2,
+                tags: vec![0, 1],
+                values: vec![1.0, 2.0],
+            },
+            Record {
+                name: 0,
+                tags: vec![],
+                values: vec![],
+            },
+        ],
+    };
+    let tmp_file = std::env::temp_dir().join("test_with_dict.eps");
+    data.store(&tmp_file)?;
+
+    let full = <WithDict<Vec<String>, Vec<Record>>>::load_full(&tmp_file)?;
+    assert_eq!(full, data);
+
+    let case = <WithDict<Vec<String>, Vec<Record>>>::load_mem(&tmp_file)?;
+    assert_eq!(case.dict, ["alpha", "beta", "gamma"]);
+    assert_eq!(
+        case.items,
+        [
+            RecordView {
+                name: "gamma",
+                tags: vec!["alpha", "beta"],
+                values: vec![1.0, 2.0],
+            },
+            RecordView {
+                name: "alpha",
+                tags: vec![],
+                values: vec![],
+            },
+        ]
+    );
+
+    std::fs::remove_file(&tmp_file)?;
+    Ok(())
+}
+
+/// A structure containing several sections sharing the same dictionary.
+#[derive(Epserde, Debug, PartialEq)]
+struct Sections<A, B> {
+    records: A,
+    links: B,
+    checksum: u64,
+}
+
+#[test]
+fn test_zero_copy_items() -> anyhow::Result<()> {
+    let data = Sections {
+        records: WithDict {
+            dict: dict(),
+            items: Vec::<Record>::new(),
+        },
+        links: WithDict {
+            dict: dict(),
+            items: vec![Link(0, 2), Link(1, 1)],
+        },
+        checksum: 42,
+    };
+    type S = Sections<WithDict<Vec<String>, Vec<Record>>, WithDict<Vec<String>, Vec<Link>>>;
+    let mut cursor = epserde::new_aligned_cursor();
+    data.serialize(&mut cursor)?;
+    let buf = cursor.into_inner();
+
+    let full = S::deserialize_full(&mut std::io::Cursor::new(&buf))?;
+    assert_eq!(full, data);
+    let eps = S::deserialize_eps(&buf)?;
+    assert!(eps.records.items.is_empty());
+    assert_eq!(eps.links.items, [("alpha", "gamma"), ("beta", "beta")]);
+    assert_eq!(eps.checksum, 42);
+    Ok(())
+}
+
+#[test]
+fn test_invalid_index() -> anyhow::Result<()> {
+    let data = WithDict {
+        dict: dict(),
+        items: vec![Link(0, 3)],
+    };
+    let mut cursor = epserde::new_aligned_cursor();
+    data.serialize(&mut cursor)?;
+    let buf = cursor.into_inner();
+    assert!(matches!(
+        <WithDict<Vec<String>, Vec<Link>>>::deserialize_eps(&buf),
+        Err(deser::Error::InvalidValue("Record"))
+    ));
+    Ok(())
+}
@@ -0,0 +1,155 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ * SPDX-FileCopyrightText: 2023 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+#![cfg(test)]
+
+use epserde::dynamic::DynamicValue;
+use epserde::prelude::*;
+use epserde::ser::Schema;
+
+#[derive(Epserde, Debug, PartialEq, Clone)]
+struct Inner {
+    x: u16,
+    tag: Option<u8>,
+}
+
+#[derive(Epserde, Debug, PartialEq, Clone)]
+struct Data<A> {
+    values: A,
+    name: String,
+    count: usize,
+    inner: Inner,
+    items: Vec<Inner>,
+    pair: (u8, f64),
+    array: [u32; 3],
+}
+
+fn data() -> Data<Vec<u64>> {
+    Data {
+        values: vec![1, 2, 3, 4],
+        name: "dynamic".to_string(),
+        count: 42,
+        inner: Inner { x: 7, tag: Some(3) },
+        items: vec![Inner { x: 1, tag: None }, Inner { x: 2, tag: Some(5) }],
+        pair: (1, 2.5),
+        array: [10, 20, 30],
+    }
+}
+
+#[test]
+fn test_dynamic() {
+    let mut cursor = epserde::new_aligned_cursor();
+    let schema = data().serialize_with_schema(&mut cursor).unwrap();
+    let bytes = cursor.into_inner();
+
+    let root = DynamicValue::root(&schema, &bytes).unwrap();
+    assert_eq!(root.path(), "ROOT");
+    assert_eq!(root.offset() + root.size(), bytes.len());
+    assert_eq!(
+        root.children()
+            .map(|child| child.name())
+            .collect::<Vec<_>>(),
+        ["values", "name", "count", "inner", "items", "pair", "array"]
+    );
+
+    assert_eq!(root.field("count").unwrap().read::<usize>().unwrap(), 42);
+    assert_eq!(
+        root.field("values").unwrap().read_slice::<u64>().unwrap(),
+        [1, 2, 3, 4]
+    );
+    assert_eq!(root.get("values.len").unwrap().read::<usize>().unwrap(), 4);
+    assert_eq!(root.field("name").unwrap().read_str().unwrap(), "dynamic");
+    assert_eq!(root.get("inner.x").unwrap().read::<u16>().unwrap(), 7);
+    assert_eq!(root.get("inner.tag.Some").unwrap().read::<u8>().unwrap(), 3);
+    assert_eq!(
+        root.field("array").unwrap().read_slice::<u32>().unwrap(),
+        [10, 20, 30]
+    );
+    assert_eq!(
+        root.get("pair.zero").unwrap().ty(),
+        core::any::type_name::<(u8, f64)>()
+    );
+
+    // Elements of vectors of structures are all named item
+    let items = root.field("items").unwrap();
+    assert_eq!(items.field("len").unwrap().read::<usize>().unwrap(), 2);
+    let xs = items
+        .children()
+        .filter(|child| child.name() == "item")
+        .map(|item| item.field("x").unwrap().read::<u16>().unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(xs, [1, 2]);
+
+    // Header fields are accessible, too
+    let type_name = DynamicValue::new(&schema, &bytes, "TYPE_NAME").unwrap();
+    assert_eq!(
+        type_name.read_str().unwrap(),
+        core::any::type_name::<Data<Vec<u64>>>()
+    );
+
+    assert!(root.field("missing").is_none());
+    assert!(root.get("inner.missing").is_none());
+}
+
+#[test]
+fn test_dynamic_errors() {
+    let mut cursor = epserde::new_aligned_cursor();
+    let schema = data().serialize_with_schema(&mut cursor).unwrap();
+    let bytes = cursor.into_inner();
+    let root = DynamicValue::root(&schema, &bytes).unwrap();
+
+    assert!(matches!(
+        root.field("count").unwrap().read::<u64>(),
+        Err(deser::Error::WrongSchemaType { .. })
+    ));
+    assert!(matches!(
+        root.field("values").unwrap().read_slice::<u32>(),
+        Err(deser::Error::WrongSchemaType { .. })
+    ));
+    assert!(matches!(
+        root.field("count").unwrap().read_str(),
+        Err(deser::Error::WrongSchemaType { .. })
+    ));
+
+    // The schema points outside truncated data
+    let truncated = &bytes[..bytes.len() - 1];
+    let root = DynamicValue::root(&schema, truncated).unwrap();
+    assert!(matches!(
+        root.field("array").unwrap().read_slice::<u32>(),
+        Err(deser::Error::ReadError)
+    ));
+
+    // Zero-copy data must be aligned in memory
+    let mut unaligned = vec![0_u8; bytes.len() + 1];
+    let offset = 1 - unaligned.as_ptr() as usize % 2;
+    unaligned[offset..offset + bytes.len()].copy_from_slice(&bytes);
+    let root = DynamicValue::root(&schema, &unaligned[offset..]).unwrap();
+    assert!(matches!(
+        root.field("values").unwrap().read_slice::<u64>(),
+        Err(deser::Error::AlignmentError)
+    ));
+    // Primitive values are read without alignment
+    assert_eq!(root.field("count").unwrap().read::<usize>().unwrap(), 42);
+}
+
+#[test]
+fn test_dynamic_sidecar() {
+    let data = data();
+    data.store_with_schema("test_dynamic.bin").unwrap();
+
+    // An external tool can read the data without knowing its type
+    let schema =
+        Schema::from_json(&std::fs::read_to_string("test_dynamic.bin.schema.json").unwrap())
+            .unwrap();
+    let bytes = std::fs::read("test_dynamic.bin").unwrap();
+    let root = DynamicValue::root(&schema, &bytes).unwrap();
+    assert_eq!(root.field("name").unwrap().read_str().unwrap(), "dynamic");
+    assert_eq!(root.get("items.len").unwrap().read::<usize>().unwrap(), 2);
+
+    std::fs::remove_file("test_dynamic.bin").unwrap();
+    std::fs::remove_file("test_dynamic.bin.schema.json").unwrap();
+}
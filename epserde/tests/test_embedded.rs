@@ -0,0 +1,191 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+#![cfg(test)]
+
+use epserde::embedded::{ArchivePacker, EmbeddedArchive};
+use epserde::prelude::*;
+
+#[derive(Epserde, Debug, PartialEq, Eq, Clone, Copy)]
+#[repr(C)]
+#[zero_copy]
+struct Point {
+    x: u64,
+    y: u64,
+}
+
+#[derive(Epserde, Debug, PartialEq, Eq)]
+struct Component {
+    points: Vec<Point>,
+    label: String,
+}
+
+#[derive(Epserde, Debug, PartialEq, Eq)]
+struct Dataset<C, I> {
+    tag: u8,
+    component: C,
+    ids: I,
+}
+
+type Archived = Dataset<EmbeddedArchive<Component>, EmbeddedArchive<Vec<u32>>>;
+
+fn dataset() -> Archived {
+    Dataset {
+        tag: 3,
+        component: EmbeddedArchive::new(&Component {
+            points: vec![Point { x: 1, y: 2 }, Point { x: 3, y: 4 }],
+            label: "points".to_string(),
+        })
+        .unwrap(),
+        ids: EmbeddedArchive::new(&vec![5, 6, 7]).unwrap(),
+    }
+}
+
+#[test]
+fn test_embedded_eps() {
+    let data = dataset();
+    let mut cursor = epserde::new_aligned_cursor();
+    data.serialize(&mut cursor).unwrap();
+    let buf = cursor.into_inner();
+
+    let view = Archived::deserialize_eps(&buf).unwrap();
+    assert_eq!(view.tag, 3);
+    assert_eq!(view.component.as_bytes(), data.component.as_bytes());
+
+    // The view borrows from the outer buffer
+    let range = buf.as_ptr_range();
+    assert!(range.contains(&view.ids.as_bytes().as_ptr()));
+
+    let component = view.component.open().unwrap();
+    assert_eq!(
+        component.points,
+        &[Point { x: 1, y: 2 }, Point { x: 3, y: 4 }]
+    );
+    assert_eq!(component.label, "points");
+
+    let ids = view.ids.open().unwrap();
+    assert_eq!(ids, &[5, 6, 7]);
+    assert!(range.contains(&ids.as_ptr().cast()));
+
+    assert_eq!(view.ids.load_full().unwrap(), vec![5, 6, 7]);
+}
+
+#[test]
+fn test_embedded_full() {
+    let data = dataset();
+    let mut cursor = epserde::new_aligned_cursor();
+    data.serialize(&mut cursor).unwrap();
+    cursor.set_position(0);
+
+    let full = Archived::deserialize_full(&mut cursor).unwrap();
+    assert_eq!(full, data);
+    assert_eq!(full.ids.load_full().unwrap(), vec![5, 6, 7]);
+    assert_eq!(full.component.load_full().unwrap().label, "points");
+}
+
+#[test]
+fn test_embedded_corrupted_len() {
+    let data = dataset();
+    let mut cursor = epserde::new_aligned_cursor();
+    let schema = data.serialize_with_schema(&mut cursor).unwrap();
+    let mut buf = cursor.into_inner();
+    let len = schema
+        .0
+        .iter()
+        .find(|row| row.field == "ROOT.ids.len")
+        .unwrap();
+    // A huge length makes reading fail, rather than allocating memory
+    buf[len.offset..len.offset + len.size].copy_from_slice(&(usize::MAX >> 1).to_ne_bytes());
+    assert!(Archived::deserialize_full(&mut std::io::Cursor::new(&buf)).is_err());
+    assert!(Archived::deserialize_eps(&buf).is_err());
+}
+
+#[test]
+fn test_embedded_wrong_type() {
+    #[derive(Epserde, Debug, PartialEq, Eq)]
+    struct Outer<I> {
+        inner: I,
+    }
+
+    // The embedded bytes contain a Vec<u64>
+    let mut bytes = Vec::new();
+    vec![1_u64, 2, 3].serialize(&mut bytes).unwrap();
+    let outer = Outer {
+        inner: EmbeddedArchive::<Vec<u32>>::from_bytes(bytes),
+    };
+    let mut cursor = epserde::new_aligned_cursor();
+    outer.serialize(&mut cursor).unwrap();
+    let buf = cursor.into_inner();
+
+    // The outer structure is valid; the error is detected only when opening
+    let view = <Outer<EmbeddedArchive<Vec<u32>>>>::deserialize_eps(&buf).unwrap();
+    assert!(matches!(
+        view.inner.open(),
+        Err(deser::Error::WrongTypeHash { .. })
+    ));
+}
+
+fn component(label: &str) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    Component {
+        points: vec![Point { x: 1, y: 2 }],
+        label: label.to_string(),
+    }
+    .serialize_with_checksum(&mut bytes)
+    .unwrap();
+    bytes
+}
+
+#[test]
+fn test_pack() {
+    let parts = ["a", "bc", "def"]
+        .map(|label| EmbeddedArchive::<Component>::from_bytes_checked(component(label)).unwrap());
+
+    let mut cursor = epserde::new_aligned_cursor();
+    let mut packer = ArchivePacker::new(&mut cursor, parts.len()).unwrap();
+    for part in &parts {
+        packer.push(part).unwrap();
+    }
+    let len = packer.finish().unwrap();
+    let buf = cursor.into_inner();
+    assert_eq!(len, buf.len());
+
+    // The result is the same as serializing the vector
+    let mut expected = epserde::new_aligned_cursor();
+    parts.to_vec().serialize(&mut expected).unwrap();
+    assert_eq!(buf, expected.into_inner());
+
+    let views = <Vec<EmbeddedArchive<Component>>>::deserialize_eps(&buf).unwrap();
+    assert_eq!(views.len(), 3);
+    assert_eq!(views[1].open().unwrap().label, "bc");
+    assert_eq!(views[2].load_full().unwrap().label, "def");
+}
+
+#[test]
+fn test_pack_checked() {
+    assert!(matches!(
+        EmbeddedArchive::<Vec<u32>>::from_bytes_checked(component("a")),
+        Err(deser::Error::WrongTypeHash { .. })
+    ));
+    let mut bytes = component("a");
+    *bytes.last_mut().unwrap() ^= 1;
+    assert!(matches!(
+        EmbeddedArchive::<Component>::from_bytes_checked(bytes),
+        Err(deser::Error::ChecksumMismatch { .. })
+    ));
+    assert!(matches!(
+        EmbeddedArchive::<Component>::load(std::env::temp_dir().join("test_pack_missing.eps")),
+        Err(deser::Error::FileOpenError(_))
+    ));
+}
+
+#[test]
+#[should_panic]
+fn test_pack_missing() {
+    let mut cursor = epserde::new_aligned_cursor();
+    let packer = ArchivePacker::<Component, _>::new(&mut cursor, 1).unwrap();
+    packer.finish().unwrap();
+}
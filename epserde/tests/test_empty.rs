@@ -0,0 +1,176 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ * SPDX-FileCopyrightText: 2023 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+#![cfg(test)]
+
+use epserde::deser::DeserType;
+use epserde::prelude::*;
+use std::fmt::Debug;
+
+#[derive(Epserde, Debug, Clone, Copy, PartialEq)]
+#[repr(C)]
+#[zero_copy]
+struct Point {
+    x: f64,
+    y: f64,
+}
+
+/// A zero-sized zero-copy type.
+#[derive(Epserde, Debug, Clone, Copy, PartialEq)]
+#[repr(C)]
+#[zero_copy]
+struct Marker {}
+
+#[derive(Epserde, Debug, PartialEq)]
+struct Data {
+    values: Vec<u64>,
+    points: Vec<Point>,
+    name: String,
+    tag: u8,
+    names: Vec<String>,
+    boxed: Box<[u32]>,
+    last: Vec<u128>,
+}
+
+fn empty() -> Data {
+    Data {
+        values: vec![],
+        points: vec![],
+        name: String::new(),
+        tag: 1,
+        names: vec![],
+        boxed: Box::new([]),
+        last: vec![],
+    }
+}
+
+/// Round-trip `value` through all serialization and deserialization paths,
+/// checking the ε-copy views with `check`.
+fn round_trip<T>(name: &str, value: &T, check: impl Fn(&DeserType<'_, T>))
+where
+    T: Serialize + Deserialize + PartialEq + Debug,
+{
+    let mut cursor = epserde::new_aligned_cursor();
+    let len = value.serialize(&mut cursor).unwrap();
+    let bytes = cursor.into_inner();
+    assert_eq!(len, bytes.len());
+
+    assert_eq!(&T::deserialize_full(&mut &bytes[..]).unwrap(), value);
+    check(&T::deserialize_eps(&bytes).unwrap());
+    check(&T::deserialize_eps_untrusted(&bytes).unwrap());
+
+    // The data is at the end of the file, so the views point past the end
+    // of the memory regions
+    let path = std::env::temp_dir().join(format!("test_empty_{}.eps", name));
+    value.store(&path).unwrap();
+    assert_eq!(&T::load_full(&path).unwrap(), value);
+    check(&T::load_mem(&path).unwrap());
+    #[cfg(feature = "mmap-rs")]
+    {
+        check(&T::load_mmap(&path, Flags::empty()).unwrap());
+        check(&T::mmap(&path, Flags::empty()).unwrap());
+    }
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn test_empty_vec() {
+    round_trip("vec_u8", &Vec::<u8>::new(), |view| assert!(view.is_empty()));
+    round_trip("vec_u64", &Vec::<u64>::new(), |view| {
+        assert!(view.is_empty())
+    });
+    round_trip("vec_u128", &Vec::<u128>::new(), |view| {
+        assert!(view.is_empty())
+    });
+    round_trip("vec_point", &Vec::<Point>::new(), |view| {
+        assert!(view.is_empty())
+    });
+    round_trip("vec_unit", &Vec::<()>::new(), |view| {
+        assert!(view.is_empty())
+    });
+    round_trip("vec_marker", &Vec::<Marker>::new(), |view| {
+        assert!(view.is_empty())
+    });
+    // Vectors of zero-sized types are empty in memory even if not empty
+    round_trip("vec_units", &vec![(); 3], |view| assert_eq!(view.len(), 3));
+    round_trip("vec_markers", &vec![Marker {}; 3], |view| {
+        assert_eq!(view.len(), 3)
+    });
+    round_trip("vec_string", &Vec::<String>::new(), |view| {
+        assert!(view.is_empty())
+    });
+    round_trip("vec_vec", &Vec::<Vec<u32>>::new(), |view| {
+        assert!(view.is_empty())
+    });
+    round_trip("vec_vec_empty", &vec![Vec::<u32>::new(); 3], |view| {
+        assert!(view.iter().all(|inner| inner.is_empty()))
+    });
+    round_trip("boxed", &Vec::<u32>::new().into_boxed_slice(), |view| {
+        assert!(view.is_empty())
+    });
+}
+
+#[test]
+fn test_empty_str() {
+    round_trip("string", &String::new(), |view| assert_eq!(*view, ""));
+    round_trip("boxed_str", &Box::<str>::from(""), |view| {
+        assert_eq!(*view, "")
+    });
+    round_trip("strings", &vec![String::new(); 3], |view| {
+        assert_eq!(*view, ["", "", ""])
+    });
+}
+
+#[test]
+fn test_empty_fields() {
+    round_trip("struct", &empty(), |view| {
+        assert!(view.values.is_empty());
+        assert!(view.points.is_empty());
+        assert_eq!(view.name, "");
+        assert_eq!(view.tag, 1);
+        assert!(view.names.is_empty());
+        assert!(view.boxed.is_empty());
+        assert!(view.last.is_empty());
+    });
+}
+
+#[test]
+fn test_empty_root() {
+    round_trip("unit", &(), |_| {});
+    round_trip("array", &[0_u64; 0], |view| assert!(view.is_empty()));
+    round_trip("tuple", &(Vec::<u64>::new(), String::new()), |view| {
+        assert!(view.0.is_empty());
+        assert_eq!(view.1, "");
+    });
+}
+
+#[test]
+fn test_empty_slice() {
+    // Serializing an empty slice is the same as serializing an empty vector
+    let mut cursor = epserde::new_aligned_cursor();
+    let empty: &[u64] = &[];
+    empty.serialize(&mut cursor).unwrap();
+    let bytes = cursor.into_inner();
+    assert!(<Vec<u64>>::deserialize_eps(&bytes).unwrap().is_empty());
+}
+
+#[test]
+fn test_empty_file() {
+    let path = std::env::temp_dir().join("test_empty_file.eps");
+    std::fs::write(&path, []).unwrap();
+    assert!(<Vec<u64>>::load_full(&path).is_err());
+    assert!(<Vec<u64>>::load_mem(&path).is_err());
+    #[cfg(feature = "mmap-rs")]
+    {
+        assert!(<Vec<u64>>::load_mmap(&path, Flags::empty()).is_err());
+        assert!(<Vec<u64>>::mmap(&path, Flags::empty()).is_err());
+    }
+    std::fs::remove_file(&path).unwrap();
+
+    assert!(<Vec<u64>>::deserialize_full(&mut &[][..]).is_err());
+    assert!(<Vec<u64>>::deserialize_eps(&[]).is_err());
+}
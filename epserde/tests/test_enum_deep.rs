@@ -0,0 +1,108 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+#![cfg(test)]
+
+use epserde::prelude::*;
+
+#[derive(Epserde, Clone, Debug, PartialEq)]
+enum Deep<A: AsRef<[u32]> = Vec<u32>, B = String> {
+    Empty,
+    Name(String),
+    Values { values: Vec<u64>, name: B },
+    Generic(A, B),
+    Nested(Option<Vec<String>>),
+}
+
+#[derive(Epserde, Clone, Debug, PartialEq)]
+struct Container<E> {
+    tag: u8,
+    items: E,
+}
+
+fn roundtrip(value: Deep) {
+    let mut cursor = epserde::new_aligned_cursor();
+    value.serialize(&mut cursor).unwrap();
+    cursor.set_position(0);
+    let full = <Deep>::deserialize_full(&mut cursor).unwrap();
+    assert_eq!(value, full);
+
+    let buf = cursor.into_inner();
+    let eps = <Deep>::deserialize_eps(&buf).unwrap();
+    match (&value, eps) {
+        (Deep::Empty, Deep::Empty) => {}
+        (Deep::Name(a), Deep::Name(b)) => assert_eq!(a, &b),
+        (
+            Deep::Values { values, name },
+            Deep::Values {
+                values: eps_values,
+                name: eps_name,
+            },
+        ) => {
+            assert_eq!(values, &eps_values);
+            assert_eq!(name.as_str(), eps_name);
+        }
+        (Deep::Generic(a, b), Deep::Generic(eps_a, eps_b)) => {
+            assert_eq!(a.as_slice(), eps_a);
+            assert_eq!(b.as_str(), eps_b);
+        }
+        (Deep::Nested(a), Deep::Nested(b)) => assert_eq!(a, &b),
+        _ => panic!("Wrong variant"),
+    }
+}
+
+#[test]
+fn test_deep_enum_variants() {
+    roundtrip(Deep::Empty);
+    roundtrip(Deep::Name("epserde".to_string()));
+    roundtrip(Deep::Values {
+        values: vec![1, 2, 3],
+        name: "values".to_string(),
+    });
+    roundtrip(Deep::Generic(vec![4, 5, 6], "generic".to_string()));
+    roundtrip(Deep::Nested(Some(vec!["a".to_string(), "b".to_string()])));
+    roundtrip(Deep::Nested(None));
+}
+
+#[test]
+fn test_deep_enum_in_vec() {
+    let data = Container {
+        tag: 7,
+        items: vec![
+            Deep::Generic(vec![1], "x".to_string()),
+            Deep::Empty,
+            Deep::Name("y".to_string()),
+        ],
+    };
+    let mut cursor = epserde::new_aligned_cursor();
+    data.serialize(&mut cursor).unwrap();
+    cursor.set_position(0);
+    let full = <Container<Vec<Deep>>>::deserialize_full(&mut cursor).unwrap();
+    assert_eq!(data, full);
+
+    let buf = cursor.into_inner();
+    let eps = <Container<Vec<Deep>>>::deserialize_eps(&buf).unwrap();
+    assert_eq!(eps.tag, 7);
+    assert_eq!(eps.items.len(), 3);
+    assert!(matches!(eps.items[0], Deep::Generic([1], "x")));
+    assert!(matches!(eps.items[1], Deep::Empty));
+    assert!(matches!(eps.items[2], Deep::Name(ref name) if name == "y"));
+}
+
+#[test]
+fn test_deep_enum_invalid_tag() {
+    let mut cursor = epserde::new_aligned_cursor();
+    let value: Deep = Deep::Empty;
+    value.serialize(&mut cursor).unwrap();
+    let mut buf = cursor.into_inner();
+    let len = buf.len();
+    // The tag is the last usize written
+    buf[len - core::mem::size_of::<usize>()..len].copy_from_slice(&42_usize.to_ne_bytes());
+    assert!(matches!(
+        <Deep>::deserialize_eps(&buf),
+        Err(deser::Error::InvalidTag(42))
+    ));
+}
@@ -0,0 +1,83 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ * SPDX-FileCopyrightText: 2023 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+#![cfg(test)]
+
+use epserde::prelude::*;
+
+#[test]
+fn test_deserialize_eps_mut() -> anyhow::Result<()> {
+    let mut cursor = epserde::new_aligned_cursor();
+    vec![0_u64; 10].serialize(&mut cursor)?;
+    let mut bytes = cursor.into_inner();
+
+    let bits = <Vec<u64>>::deserialize_eps_mut(&mut bytes)?;
+    assert_eq!(bits.len(), 10);
+    bits[3] |= 1 << 5;
+    assert_eq!(<Vec<u64>>::deserialize_eps(&bytes)?[3], 1 << 5);
+
+    let mut cursor = epserde::new_aligned_cursor();
+    [1_u32, 2, 3].serialize(&mut cursor)?;
+    let mut bytes = cursor.into_inner();
+    let array = <[u32; 3]>::deserialize_eps_mut(&mut bytes)?;
+    array.reverse();
+    assert_eq!(<[u32; 3]>::deserialize_eps(&bytes)?, &[3, 2, 1]);
+    Ok(())
+}
+
+#[test]
+fn test_checksum() -> anyhow::Result<()> {
+    let mut cursor = epserde::new_aligned_cursor();
+    vec![0_u8; 10]
+        .into_boxed_slice()
+        .serialize_with_checksum(&mut cursor)?;
+    let mut bytes = cursor.into_inner();
+
+    <Box<[u8]>>::deserialize_eps_mut(&mut bytes)?[0] = 1;
+    assert!(<Box<[u8]>>::deserialize_eps_mut(&mut bytes).is_err());
+    assert_eq!(
+        <Box<[u8]>>::deserialize_eps_mut_unverified(&mut bytes)?[0],
+        1
+    );
+    Ok(())
+}
+
+#[cfg(feature = "mmap-rs")]
+#[test]
+fn test_mmap_mut() -> anyhow::Result<()> {
+    let path = std::env::temp_dir().join("test_mmap_mut.eps");
+    vec![0_u64; 1000].store(&path)?;
+
+    let mut bits = <Vec<u64>>::mmap_mut(&path, Flags::empty())?;
+    let slice: &mut [u64] = &mut bits;
+    slice[10] = u64::MAX;
+    bits.flush()?;
+    drop(bits);
+    let bits = <Vec<u64>>::load_full(&path)?;
+    assert_eq!(bits[10], u64::MAX);
+    assert_eq!(bits.iter().filter(|&&word| word != 0).count(), 1);
+
+    // Private modifications are not written back
+    let mut bits = <Vec<u64>>::mmap_mut(&path, Flags::COPY_ON_WRITE)?;
+    bits[10] = 0;
+    bits[20] = 1;
+    assert_eq!(bits[20], 1);
+    drop(bits);
+    let bits = <Vec<u64>>::load_full(&path)?;
+    assert_eq!(bits[10], u64::MAX);
+    assert_eq!(bits[20], 0);
+
+    [1_u32, 2, 3].store(&path)?;
+    let mut array = <[u32; 3]>::mmap_mut(&path, Flags::empty())?;
+    let inner: &mut [u32; 3] = &mut array;
+    inner[1] = 4;
+    drop(array);
+    assert_eq!(<[u32; 3]>::load_full(&path)?, [1, 4, 3]);
+
+    std::fs::remove_file(&path)?;
+    Ok(())
+}
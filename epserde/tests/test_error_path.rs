@@ -0,0 +1,139 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ * SPDX-FileCopyrightText: 2023 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+#![cfg(test)]
+
+use epserde::prelude::*;
+
+#[derive(Epserde, Debug, PartialEq)]
+struct Node {
+    id: u32,
+    label: String,
+}
+
+#[derive(Epserde, Debug, PartialEq)]
+enum Edge {
+    Plain(u32, u32),
+    Labeled { src: u32, label: String },
+}
+
+#[derive(Epserde, Debug, PartialEq)]
+struct Graph {
+    nodes: Vec<Node>,
+    edges: [Edge; 2],
+    root: Option<Node>,
+}
+
+fn graph() -> Graph {
+    Graph {
+        nodes: (0..5)
+            .map(|id| Node {
+                id,
+                label: format!("node{}", id),
+            })
+            .collect(),
+        edges: [
+            Edge::Plain(0, 1),
+            Edge::Labeled {
+                src: 1,
+                label: "edge".to_string(),
+            },
+        ],
+        root: Some(Node {
+            id: 0,
+            label: "root".to_string(),
+        }),
+    }
+}
+
+/// Serialize the graph, returning the data and the offset of the `index`-th
+/// row of the schema with the given field name.
+fn serialize(field: &str, index: usize) -> (Vec<u8>, usize) {
+    let mut cursor = epserde::new_aligned_cursor();
+    let schema = graph().serialize_with_schema(&mut cursor).unwrap();
+    let offset = schema
+        .0
+        .iter()
+        .filter(|row| row.field == field)
+        .nth(index)
+        .unwrap()
+        .offset;
+    (cursor.into_inner(), offset)
+}
+
+/// Check that deserializing `bytes` fails with the given path and offset,
+/// and that the path is not reported by default.
+fn check(bytes: &[u8], path: &str, offset: usize) -> deser::Error {
+    let err_full = Graph::deserialize_full_with_paths(&mut &bytes[..]).unwrap_err();
+    let err_eps = Graph::deserialize_eps_with_paths(bytes)
+        .map(|_| ())
+        .unwrap_err();
+    for err in [&err_full, &err_eps] {
+        assert_eq!(err.path(), Some(path));
+        assert_eq!(err.offset(), Some(offset));
+    }
+    let err = Graph::deserialize_full(&mut &bytes[..]).unwrap_err();
+    assert_eq!(err.path(), None);
+    assert_eq!(err.to_string(), err_full.root_cause().to_string());
+    let err = Graph::deserialize_eps_untrusted(bytes)
+        .map(|_| ())
+        .unwrap_err();
+    assert_eq!(err.path(), None);
+    assert_eq!(err.to_string(), err_eps.root_cause().to_string());
+    err_full
+}
+
+#[test]
+fn test_path() {
+    let (mut bytes, offset) = serialize("ROOT.nodes.item.label", 3);
+    let (_, zero) = serialize("ROOT.nodes.item.label.zero", 3);
+    bytes[zero] = 0xFF;
+    let err = check(&bytes, "ROOT.nodes[3].label", offset);
+    assert!(matches!(err.root_cause(), deser::Error::InvalidUtf8));
+    assert!(err.to_string().contains("ROOT.nodes[3].label"));
+
+    let (mut bytes, offset) = serialize("ROOT.edges.item.label", 0);
+    let (_, zero) = serialize("ROOT.edges.item.label.zero", 0);
+    bytes[zero] = 0xFF;
+    let err = check(&bytes, "ROOT.edges[1].label", offset);
+    assert!(matches!(err.into_root_cause(), deser::Error::InvalidUtf8));
+
+    let (mut bytes, offset) = serialize("ROOT.edges.item", 0);
+    bytes[offset..offset + 8].copy_from_slice(&7_usize.to_ne_bytes());
+    let err = check(&bytes, "ROOT.edges[0]", offset);
+    assert!(matches!(err.root_cause(), deser::Error::InvalidTag(7)));
+}
+
+#[test]
+fn test_truncated() {
+    let (bytes, offset) = serialize("ROOT.root.Some.label", 0);
+    let err = check(&bytes[..bytes.len() - 1], "ROOT.root.Some.label", offset);
+    assert!(matches!(err.root_cause(), deser::Error::ReadError));
+
+    // Errors in the header have no path
+    let err = Graph::deserialize_full_with_paths(&mut &bytes[..4]).unwrap_err();
+    assert_eq!(err.path(), None);
+    assert_eq!(err.offset(), None);
+}
+
+#[cfg(feature = "mmap-rs")]
+#[test]
+fn test_flags() {
+    let (mut bytes, offset) = serialize("ROOT.nodes.item.label", 3);
+    let (_, zero) = serialize("ROOT.nodes.item.label.zero", 3);
+    bytes[zero] = 0xFF;
+    let path = std::env::temp_dir().join("test_error_path_flags.eps");
+    std::fs::write(&path, &bytes).unwrap();
+    let err = Graph::load_mmap(&path, Flags::UNTRUSTED | Flags::ERROR_PATHS).unwrap_err();
+    assert_eq!(err.path(), Some("ROOT.nodes[3].label"));
+    assert_eq!(err.offset(), Some(offset));
+    assert!(matches!(
+        Graph::load_mmap(&path, Flags::UNTRUSTED),
+        Err(deser::Error::InvalidUtf8)
+    ));
+    std::fs::remove_file(&path).unwrap();
+}
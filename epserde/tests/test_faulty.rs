@@ -0,0 +1,124 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ * SPDX-FileCopyrightText: 2023 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+#![cfg(all(test, feature = "testing"))]
+
+use epserde::prelude::*;
+use epserde::testing::*;
+use std::io::ErrorKind;
+
+#[derive(Epserde, Debug, PartialEq)]
+struct Data<A, B> {
+    name: A,
+    values: B,
+    nested: Vec<Vec<u32>>,
+}
+
+type Owned = Data<String, Vec<u64>>;
+
+fn data() -> Owned {
+    Data {
+        name: "data".to_string(),
+        values: (0..100).collect(),
+        nested: vec![vec![1, 2], vec![], vec![3]],
+    }
+}
+
+fn serialize(data: &impl Serialize) -> Vec<u8> {
+    let mut cursor = epserde::new_aligned_cursor();
+    data.serialize(&mut cursor).unwrap();
+    cursor.into_inner()
+}
+
+#[test]
+fn test_write_errors() {
+    let bytes = serialize(&data());
+    for pos in 0..bytes.len() {
+        let mut writer = FaultyWriter::new(Vec::new());
+        writer.fail_write_at(pos, ErrorKind::Other);
+        assert!(matches!(
+            data().serialize(&mut writer),
+            Err(ser::Error::WriteError)
+        ));
+        // Everything before the failure has been written
+        assert_eq!(writer.pos(), pos);
+        assert_eq!(writer.into_inner(), bytes[..pos]);
+    }
+
+    let mut writer = FaultyWriter::new(Vec::new());
+    writer.fail_write_at(bytes.len(), ErrorKind::Other);
+    data().serialize(&mut writer).unwrap();
+    assert_eq!(writer.into_inner(), bytes);
+
+    let mut writer = FaultyWriter::new(Vec::new());
+    writer.fail_flush(ErrorKind::Other);
+    assert!(data().serialize(&mut writer).is_err());
+    assert_eq!(writer.into_inner(), bytes);
+}
+
+#[test]
+fn test_unsized_write_errors() {
+    // Slices and string slices are serialized through a borrowed vector or
+    // string, which must not be dropped when the write fails
+    let text = "a string slice".to_string();
+    let bytes = serialize(&text);
+    for pos in 0..bytes.len() {
+        let mut writer = FaultyWriter::new(Vec::new());
+        writer.fail_write_at(pos, ErrorKind::Other);
+        assert!(text.as_str().serialize(&mut writer).is_err());
+    }
+    assert_eq!(text, "a string slice");
+
+    let values = vec![1_u64, 2, 3];
+    let bytes = serialize(&values);
+    for pos in 0..bytes.len() {
+        let mut writer = FaultyWriter::new(Vec::new());
+        writer.fail_write_at(pos, ErrorKind::Other);
+        assert!(values.as_slice().serialize(&mut writer).is_err());
+    }
+    assert_eq!(values, [1, 2, 3]);
+}
+
+#[test]
+fn test_short_writes() {
+    for max_write in [1, 3, 7] {
+        let mut writer = FaultyWriter::new(Vec::new());
+        writer.max_write(max_write);
+        data().serialize(&mut writer).unwrap();
+        assert_eq!(writer.into_inner(), serialize(&data()));
+    }
+}
+
+#[test]
+fn test_read_errors() {
+    let bytes = serialize(&data());
+    for pos in 0..bytes.len() {
+        let mut reader = FaultyReader::new(&bytes[..]);
+        reader.truncate_reads(pos);
+        assert!(Owned::deserialize_full(&mut reader).is_err());
+
+        let mut reader = FaultyReader::new(&bytes[..]);
+        reader.fail_read_at(pos, ErrorKind::Other);
+        assert!(Owned::deserialize_full(&mut reader).is_err());
+        assert_eq!(reader.pos(), pos);
+    }
+
+    let mut reader = FaultyReader::new(&bytes[..]);
+    reader.truncate_reads(bytes.len());
+    assert_eq!(Owned::deserialize_full(&mut reader).unwrap(), data());
+}
+
+#[test]
+fn test_short_reads() {
+    let bytes = serialize(&data());
+    for max_read in [1, 3, 7] {
+        let mut reader = FaultyReader::new(&bytes[..]);
+        reader.max_read(max_read);
+        assert_eq!(Owned::deserialize_full(&mut reader).unwrap(), data());
+        assert_eq!(reader.pos(), bytes.len());
+    }
+}
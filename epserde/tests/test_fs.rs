@@ -0,0 +1,148 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ * SPDX-FileCopyrightText: 2023 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+#![cfg(test)]
+
+use epserde::fs::*;
+use epserde::prelude::*;
+use std::io::ErrorKind;
+
+#[derive(Epserde, Debug, PartialEq)]
+struct Data {
+    name: String,
+    values: Vec<u64>,
+}
+
+fn data() -> Data {
+    Data {
+        name: "data".to_string(),
+        values: (0..1000).collect(),
+    }
+}
+
+fn fs() -> MemFileSystem {
+    let mut cursor = epserde::new_aligned_cursor();
+    data().serialize_with_checksum(&mut cursor).unwrap();
+    let mut fs = MemFileSystem::new();
+    fs.insert("data.eps", cursor.into_inner());
+    fs
+}
+
+#[test]
+fn test_load() -> anyhow::Result<()> {
+    let fs = fs();
+    assert_eq!(load_full::<Data>(&fs, "data.eps")?, data());
+    let mem = load_mem::<Data>(&fs, "data.eps", StrPolicy::Validated)?;
+    assert_eq!(mem.name, "data");
+    assert_eq!(mem.values, data().values);
+    #[cfg(feature = "mmap-rs")]
+    {
+        let mmap = mmap::<Data>(&fs, "data.eps", Flags::empty())?;
+        assert_eq!(mmap.values, data().values);
+    }
+    Ok(())
+}
+
+#[test]
+fn test_short_reads() -> anyhow::Result<()> {
+    let mut fs = fs();
+    fs.get_mut("data.eps").unwrap().max_read(3);
+    assert_eq!(load_full::<Data>(&fs, "data.eps")?, data());
+    let mem = load_mem::<Data>(&fs, "data.eps", StrPolicy::Trusted)?;
+    assert_eq!(mem.values, data().values);
+    Ok(())
+}
+
+#[test]
+fn test_open_errors() {
+    let mut fs = fs();
+    fs.get_mut("data.eps")
+        .unwrap()
+        .fail_open(ErrorKind::PermissionDenied);
+    match load_full::<Data>(&fs, "data.eps") {
+        Err(deser::Error::FileOpenError(err)) => {
+            assert_eq!(err.kind(), ErrorKind::PermissionDenied)
+        }
+        other => panic!("Unexpected result {:?}", other.map(|_| ())),
+    }
+    match load_mem::<Data>(&fs, "data.eps", StrPolicy::Trusted) {
+        Err(deser::Error::FileOpenError(err)) => {
+            assert_eq!(err.kind(), ErrorKind::PermissionDenied)
+        }
+        other => panic!("Unexpected result {:?}", other.map(|_| ())),
+    }
+
+    match load_full::<Data>(&fs, "missing.eps") {
+        Err(deser::Error::FileOpenError(err)) => assert_eq!(err.kind(), ErrorKind::NotFound),
+        other => panic!("Unexpected result {:?}", other.map(|_| ())),
+    }
+    match load_mem::<Data>(&fs, "missing.eps", StrPolicy::Trusted) {
+        Err(deser::Error::FileOpenError(err)) => assert_eq!(err.kind(), ErrorKind::NotFound),
+        other => panic!("Unexpected result {:?}", other.map(|_| ())),
+    }
+}
+
+#[test]
+fn test_read_errors() {
+    let mut fs = fs();
+    let len = fs.get_mut("data.eps").unwrap().data().len();
+
+    // A file truncated while being read
+    fs.get_mut("data.eps").unwrap().truncate_reads(len - 1);
+    assert!(load_full::<Data>(&fs, "data.eps").is_err());
+    match load_mem::<Data>(&fs, "data.eps", StrPolicy::Trusted) {
+        Err(deser::Error::IoError(err)) => assert_eq!(err.kind(), ErrorKind::UnexpectedEof),
+        other => panic!("Unexpected result {:?}", other.map(|_| ())),
+    }
+
+    let data = fs.get_mut("data.eps").unwrap().data().to_vec();
+    fs.insert("data.eps", data)
+        .fail_read_at(100, ErrorKind::Other);
+    assert!(load_full::<Data>(&fs, "data.eps").is_err());
+    #[cfg(feature = "mmap-rs")]
+    assert!(mmap::<Data>(&fs, "data.eps", Flags::empty()).is_err());
+}
+
+#[test]
+fn test_corrupted() {
+    let mut fs = fs();
+    let mut data = fs.get_mut("data.eps").unwrap().data().to_vec();
+    *data.last_mut().unwrap() ^= 1;
+    fs.insert("data.eps", data);
+    assert!(matches!(
+        load_full::<Data>(&fs, "data.eps"),
+        Err(deser::Error::ChecksumMismatch { .. })
+    ));
+    assert!(load_mem::<Data>(&fs, "data.eps", StrPolicy::Trusted).is_err());
+    #[cfg(feature = "mmap-rs")]
+    {
+        assert!(mmap::<Data>(&fs, "data.eps", Flags::empty()).is_err());
+        // The last value is corrupted, but the checksum is not verified
+        let mmap = mmap::<Data>(&fs, "data.eps", Flags::SKIP_CHECKSUM).unwrap();
+        assert_eq!(mmap.values.len(), 1000);
+    }
+
+    fs.insert("empty.eps", vec![]);
+    assert!(load_full::<Data>(&fs, "empty.eps").is_err());
+    assert!(load_mem::<Data>(&fs, "empty.eps", StrPolicy::Trusted).is_err());
+}
+
+#[test]
+fn test_std_file_system() -> anyhow::Result<()> {
+    let path = std::env::temp_dir().join("test_fs_std.eps");
+    data().store(&path)?;
+    assert_eq!(load_full::<Data>(&StdFileSystem, &path)?, data());
+    let mem = load_mem::<Data>(&StdFileSystem, &path, StrPolicy::Trusted)?;
+    assert_eq!(mem.values, data().values);
+    #[cfg(feature = "mmap-rs")]
+    {
+        let mmap = mmap::<Data>(&StdFileSystem, &path, Flags::empty())?;
+        assert_eq!(mmap.values, data().values);
+    }
+    std::fs::remove_file(&path)?;
+    Ok(())
+}
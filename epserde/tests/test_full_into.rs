@@ -0,0 +1,96 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+#![cfg(test)]
+
+use epserde::prelude::*;
+
+#[derive(Epserde, Debug, PartialEq, Default)]
+struct Data<A> {
+    values: A,
+    rows: Vec<Vec<u32>>,
+    name: String,
+    offset: usize,
+}
+
+fn data(n: u64) -> Data<Vec<u64>> {
+    Data {
+        values: (0..n).collect(),
+        rows: (0..n as u32 % 7).map(|i| vec![i; i as usize]).collect(),
+        name: "x".repeat(n as usize % 13),
+        offset: n as usize,
+    }
+}
+
+fn serialized(data: &Data<Vec<u64>>) -> Vec<u8> {
+    let mut cursor = epserde::new_aligned_cursor();
+    data.serialize(&mut cursor).unwrap();
+    cursor.into_inner()
+}
+
+#[test]
+fn test_full_into() {
+    let mut value = <Data<Vec<u64>>>::default();
+    for n in [1000, 10, 500, 0, 999] {
+        let bytes = serialized(&data(n));
+        value
+            .deserialize_full_into(&mut std::io::Cursor::new(&bytes))
+            .unwrap();
+        assert_eq!(value, data(n));
+    }
+}
+
+#[test]
+fn test_full_into_reuse() {
+    let mut value = data(1000);
+    let values_ptr = value.values.as_ptr();
+    let row_ptr = value.rows[4].as_ptr();
+    let name_ptr = value.name.as_ptr();
+
+    // Smaller data fits in the existing buffers
+    let bytes = serialized(&data(999));
+    value
+        .deserialize_full_into(&mut std::io::Cursor::new(&bytes))
+        .unwrap();
+    assert_eq!(value, data(999));
+    assert_eq!(value.values.as_ptr(), values_ptr);
+    assert_eq!(value.rows[4].as_ptr(), row_ptr);
+    assert_eq!(value.name.as_ptr(), name_ptr);
+}
+
+#[test]
+fn test_full_into_errors() {
+    let mut value = data(10);
+    let mut cursor = epserde::new_aligned_cursor();
+    data(10).serialize_with_checksum(&mut cursor).unwrap();
+    let mut bytes = cursor.into_inner();
+    let last = bytes.len() - 1;
+    bytes[last] ^= 1;
+    assert!(matches!(
+        value.deserialize_full_into(&mut std::io::Cursor::new(&bytes)),
+        Err(deser::Error::ChecksumMismatch { .. })
+    ));
+
+    let bytes = serialized(&data(10));
+    let mut other = <Data<Vec<u32>>>::default();
+    assert!(matches!(
+        other.deserialize_full_into(&mut std::io::Cursor::new(&bytes)),
+        Err(deser::Error::WrongTypeHash { .. })
+    ));
+}
+
+#[test]
+fn test_load_full_into() -> anyhow::Result<()> {
+    let tmp_file = std::env::temp_dir().join("test_load_full_into.eps");
+    let mut value = <Data<Vec<u64>>>::default();
+    for n in [100, 50] {
+        data(n).store(&tmp_file)?;
+        value.load_full_into(&tmp_file)?;
+        assert_eq!(value, data(n));
+    }
+    std::fs::remove_file(&tmp_file)?;
+    Ok(())
+}
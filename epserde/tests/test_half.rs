@@ -0,0 +1,88 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ * SPDX-FileCopyrightText: 2023 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+#![cfg(all(test, feature = "half"))]
+
+use epserde::prelude::*;
+use half::{bf16, f16};
+
+fn serialize<T: Serialize>(value: &T) -> Vec<u8> {
+    let mut cursor = epserde::new_aligned_cursor();
+    value.serialize(&mut cursor).unwrap();
+    cursor.into_inner()
+}
+
+#[test]
+fn test_values() {
+    let x = f16::from_f32(1.5);
+    let buf = serialize(&x);
+    assert_eq!(f16::deserialize_full(&mut buf.as_slice()).unwrap(), x);
+    assert_eq!(f16::deserialize_eps(&buf).unwrap(), x);
+
+    let y = bf16::from_f32(-3.25);
+    let buf = serialize(&y);
+    assert_eq!(bf16::deserialize_full(&mut buf.as_slice()).unwrap(), y);
+    assert_eq!(bf16::deserialize_eps(&buf).unwrap(), y);
+}
+
+#[test]
+fn test_embedding_table() {
+    let table: Vec<f16> = (0..1000).map(|i| f16::from_f32(i as f32 / 8.0)).collect();
+    let buf = serialize(&table);
+    let view: &[f16] = <Vec<f16>>::deserialize_eps(&buf).unwrap();
+    assert_eq!(view, table.as_slice());
+    assert!(buf.as_ptr_range().contains(&(view.as_ptr() as *const u8)));
+    assert_eq!(
+        <Vec<f16>>::deserialize_full(&mut buf.as_slice()).unwrap(),
+        table
+    );
+
+    // Same size, different type
+    assert!(matches!(
+        <Vec<bf16>>::deserialize_eps(&buf),
+        Err(deser::Error::WrongTypeHash { .. })
+    ));
+    assert!(matches!(
+        <Vec<u16>>::deserialize_eps(&buf),
+        Err(deser::Error::WrongTypeHash { .. })
+    ));
+}
+
+#[derive(Epserde, Debug, PartialEq, Clone, Copy)]
+#[repr(C)]
+#[zero_copy]
+struct Quantized {
+    scale: bf16,
+    offset: f16,
+    id: u32,
+}
+
+#[test]
+fn test_zero_copy_struct() {
+    let items: Vec<Quantized> = (0..10)
+        .map(|i| Quantized {
+            scale: bf16::from_f32(i as f32),
+            offset: f16::from_f32(-(i as f32)),
+            id: i,
+        })
+        .collect();
+    let buf = serialize(&items);
+    let view: &[Quantized] = <Vec<Quantized>>::deserialize_eps(&buf).unwrap();
+    assert_eq!(view, items.as_slice());
+}
+
+#[cfg(feature = "mmap-rs")]
+#[test]
+fn test_mmap() {
+    let table: Box<[bf16]> = (0..4096).map(|i| bf16::from_f32(i as f32)).collect();
+    let path = std::env::temp_dir().join("test_half.eps");
+    epserde::save(&table, &path).unwrap();
+    let mapped = epserde::map::<Box<[bf16]>>(&path, Flags::empty()).unwrap();
+    assert_eq!(*mapped, &*table);
+    drop(mapped);
+    std::fs::remove_file(&path).unwrap();
+}
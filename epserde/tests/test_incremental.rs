@@ -0,0 +1,220 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ * SPDX-FileCopyrightText: 2023 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+#![cfg(test)]
+
+use epserde::prelude::*;
+use epserde::ser::{
+    serialize_iter, IncrementalSerializer, SchemaWriter, WriteNoStd, WriterWithPos,
+};
+
+#[derive(Epserde, Debug, PartialEq, Clone)]
+struct Inner {
+    a: u32,
+    b: String,
+}
+
+#[derive(Epserde, Debug, PartialEq, Clone)]
+struct Wide<A, B> {
+    first: A,
+    inner: Inner,
+    second: B,
+    count: usize,
+}
+
+#[derive(Epserde, Debug, PartialEq, Clone, Copy)]
+#[repr(C)]
+#[zero_copy]
+struct Point {
+    x: u64,
+    y: u64,
+}
+
+type Data = Wide<Vec<u64>, Vec<Point>>;
+
+fn data() -> Data {
+    Wide {
+        first: (0..100).collect(),
+        inner: Inner {
+            a: 42,
+            b: "inner".into(),
+        },
+        second: vec![Point { x: 1, y: 2 }, Point { x: 3, y: 4 }],
+        count: 7,
+    }
+}
+
+fn write_all(ser: &mut IncrementalSerializer<Data, impl WriteNoStd>, data: &Data) {
+    assert_eq!(ser.next_field(), Some("first"));
+    ser.write("first", &data.first).unwrap();
+    assert_eq!(ser.next_field(), Some("inner"));
+    ser.write("inner", &data.inner).unwrap();
+    ser.write("second", &data.second).unwrap();
+    ser.write("count", &data.count).unwrap();
+    assert_eq!(ser.next_field(), None);
+}
+
+#[test]
+fn test_incremental() {
+    let data = data();
+    let mut cursor = epserde::new_aligned_cursor();
+    let len = data.serialize(&mut cursor).unwrap();
+    let expected = cursor.into_inner();
+
+    let mut cursor = epserde::new_aligned_cursor();
+    let mut ser = IncrementalSerializer::<Data, _>::new(&mut cursor).unwrap();
+    write_all(&mut ser, &data);
+    assert_eq!(ser.finish().unwrap(), len);
+    let bytes = cursor.into_inner();
+
+    // Exactly the same bytes of a standard serialization
+    assert_eq!(bytes, expected);
+
+    let full = Data::deserialize_full(&mut std::io::Cursor::new(&bytes)).unwrap();
+    assert_eq!(full, data);
+    let eps = Data::deserialize_eps(&bytes).unwrap();
+    assert_eq!(eps.first, data.first);
+    assert_eq!(eps.inner, data.inner);
+    assert_eq!(eps.second, data.second);
+    assert_eq!(eps.count, data.count);
+}
+
+#[test]
+fn test_incremental_file() {
+    let data = data();
+    {
+        let file = std::fs::File::create("test_incremental.bin").unwrap();
+        let mut buf_writer = std::io::BufWriter::new(file);
+        let mut ser = IncrementalSerializer::<Data, _>::new(&mut buf_writer).unwrap();
+        write_all(&mut ser, &data);
+        ser.finish().unwrap();
+    }
+    let res = Data::load_mem("test_incremental.bin").unwrap();
+    assert_eq!(res.first, data.first);
+    assert_eq!(res.second, data.second);
+
+    std::fs::remove_file("test_incremental.bin").unwrap();
+}
+
+#[test]
+#[should_panic(expected = "the next field is first")]
+fn test_wrong_order() {
+    let data = data();
+    let mut cursor = epserde::new_aligned_cursor();
+    let mut ser = IncrementalSerializer::<Data, _>::new(&mut cursor).unwrap();
+    ser.write("inner", &data.inner).unwrap();
+}
+
+#[test]
+#[should_panic(expected = "does not match the type of the field")]
+fn test_wrong_type() {
+    let mut cursor = epserde::new_aligned_cursor();
+    let mut ser = IncrementalSerializer::<Data, _>::new(&mut cursor).unwrap();
+    ser.write("first", &vec![0_u32]).unwrap();
+}
+
+#[test]
+#[should_panic(expected = "field count has not been written")]
+fn test_missing_field() {
+    let data = data();
+    let mut cursor = epserde::new_aligned_cursor();
+    let mut ser = IncrementalSerializer::<Data, _>::new(&mut cursor).unwrap();
+    ser.write("first", &data.first).unwrap();
+    ser.write("inner", &data.inner).unwrap();
+    ser.write("second", &data.second).unwrap();
+    ser.finish().unwrap();
+}
+
+#[test]
+#[should_panic(expected = "all fields have been written")]
+fn test_extra_field() {
+    let data = data();
+    let mut cursor = epserde::new_aligned_cursor();
+    let mut ser = IncrementalSerializer::<Data, _>::new(&mut cursor).unwrap();
+    write_all(&mut ser, &data);
+    ser.write("count", &data.count).unwrap();
+}
+
+#[test]
+#[should_panic(expected = "it is not a deep-copy structure")]
+fn test_zero_copy() {
+    let mut cursor = epserde::new_aligned_cursor();
+    let _ = IncrementalSerializer::<Point, _>::new(&mut cursor);
+}
+
+#[test]
+fn test_write_iter() {
+    let data = data();
+    let mut cursor = epserde::new_aligned_cursor();
+    data.serialize(&mut cursor).unwrap();
+    let expected = cursor.into_inner();
+
+    let mut cursor = epserde::new_aligned_cursor();
+    let mut ser = IncrementalSerializer::<Data, _>::new(&mut cursor).unwrap();
+    ser.write_iter("first", 100, 0..100_u64).unwrap();
+    ser.write("inner", &data.inner).unwrap();
+    ser.write_iter("second", 2, data.second.iter().copied())
+        .unwrap();
+    ser.write("count", &data.count).unwrap();
+    ser.finish().unwrap();
+    assert_eq!(cursor.into_inner(), expected);
+}
+
+#[test]
+fn test_serialize_iter() {
+    // More than a chunk
+    let len = 100_000;
+    let values = (0..len as u64).map(|x| x * x).collect::<Vec<_>>();
+    let mut cursor = epserde::new_aligned_cursor();
+    let expected_len = values.serialize(&mut cursor).unwrap();
+    let expected = cursor.into_inner();
+
+    let mut cursor = epserde::new_aligned_cursor();
+    let written = serialize_iter(&mut cursor, len, (0..len as u64).map(|x| x * x)).unwrap();
+    assert_eq!(written, expected_len);
+    let bytes = cursor.into_inner();
+    assert_eq!(bytes, expected);
+    assert_eq!(<Vec<u64>>::deserialize_eps(&bytes).unwrap(), values);
+
+    // Empty vectors
+    let mut cursor = epserde::new_aligned_cursor();
+    serialize_iter(&mut cursor, 0, core::iter::empty::<u32>()).unwrap();
+    assert!(<Vec<u32>>::deserialize_eps(&cursor.into_inner())
+        .unwrap()
+        .is_empty());
+}
+
+#[test]
+fn test_serialize_iter_schema() {
+    let mut cursor = epserde::new_aligned_cursor();
+    let mut writer = WriterWithPos::new(&mut cursor);
+    let mut schema_writer = SchemaWriter::new(&mut writer);
+    epserde::ser::serialize_iter_zero(&mut schema_writer, 1000, 0..1000_u32).unwrap();
+    let rows = schema_writer
+        .schema
+        .0
+        .iter()
+        .filter(|row| row.field == "zero")
+        .collect::<Vec<_>>();
+    // A single row for the whole content
+    assert_eq!(rows.len(), 1);
+    assert_eq!(rows[0].size, 4000);
+}
+
+#[test]
+#[should_panic(expected = "the iterator returned only 2 values")]
+fn test_serialize_iter_short() {
+    let mut cursor = epserde::new_aligned_cursor();
+    let _ = serialize_iter(&mut cursor, 3, [1_u8, 2]);
+}
+
+#[test]
+#[should_panic(expected = "more than the declared 1 values")]
+fn test_serialize_iter_long() {
+    let mut cursor = epserde::new_aligned_cursor();
+    let _ = serialize_iter(&mut cursor, 1, [1_u8, 2]);
+}
@@ -0,0 +1,195 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ * SPDX-FileCopyrightText: 2023 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+#![cfg(test)]
+
+use epserde::prelude::*;
+
+#[derive(Epserde, Debug, PartialEq, Clone)]
+struct Node {
+    label: String,
+    children: Vec<u32>,
+}
+
+#[derive(Epserde, Debug, PartialEq)]
+struct Tree<N, W> {
+    root: usize,
+    #[epserde(indexed)]
+    nodes: N,
+    weights: W,
+}
+
+fn tree(n: usize) -> Tree<Vec<Node>, Vec<u64>> {
+    Tree {
+        root: 0,
+        nodes: (0..n)
+            .map(|i| Node {
+                label: "x".repeat(i % 7),
+                children: (0..i as u32 % 5).collect(),
+            })
+            .collect(),
+        weights: (0..n as u64).collect(),
+    }
+}
+
+fn serialize<T: Serialize>(value: &T) -> Vec<u8> {
+    let mut cursor = epserde::new_aligned_cursor();
+    value.serialize(&mut cursor).unwrap();
+    cursor.into_inner()
+}
+
+#[test]
+fn test_indexed() {
+    let tree = tree(100);
+    let buf = serialize(&tree);
+
+    let view = <Tree<Vec<Node>, Vec<u64>>>::deserialize_eps(&buf).unwrap();
+    assert_eq!(view.nodes.len(), 100);
+    assert!(!view.nodes.is_empty());
+    assert_eq!(view.weights, tree.weights.as_slice());
+    for i in [99, 0, 42, 42] {
+        let node = view.nodes.get(i).unwrap().unwrap();
+        assert_eq!(node.label, tree.nodes[i].label);
+        assert_eq!(node.children, tree.nodes[i].children);
+    }
+    assert!(view.nodes.get(100).is_none());
+    let labels = view
+        .nodes
+        .clone()
+        .iter()
+        .map(|node| node.unwrap().label)
+        .collect::<Vec<_>>();
+    assert_eq!(
+        labels,
+        tree.nodes
+            .iter()
+            .map(|node| node.label.clone())
+            .collect::<Vec<_>>()
+    );
+
+    let full = <Tree<Vec<Node>, Vec<u64>>>::deserialize_full(&mut buf.as_slice()).unwrap();
+    assert_eq!(full, tree);
+}
+
+#[test]
+fn test_boxed_and_empty() {
+    let tree = tree(10);
+    let boxed = Tree {
+        root: tree.root,
+        nodes: tree.nodes.clone().into_boxed_slice(),
+        weights: tree.weights.clone(),
+    };
+    let buf = serialize(&boxed);
+    let full = <Tree<Box<[Node]>, Vec<u64>>>::deserialize_full(&mut buf.as_slice()).unwrap();
+    assert_eq!(full.nodes.as_ref(), tree.nodes.as_slice());
+    let view = <Tree<Box<[Node]>, Vec<u64>>>::deserialize_eps(&buf).unwrap();
+    assert_eq!(view.nodes.get(9).unwrap().unwrap().label, "xx");
+
+    let empty = Tree {
+        root: 0,
+        nodes: Vec::<Node>::new(),
+        weights: vec![1_u64],
+    };
+    let buf = serialize(&empty);
+    let view = <Tree<Vec<Node>, Vec<u64>>>::deserialize_eps(&buf).unwrap();
+    assert!(view.nodes.is_empty());
+    assert!(view.nodes.get(0).is_none());
+    assert_eq!(view.weights, [1]);
+    let full = <Tree<Vec<Node>, Vec<u64>>>::deserialize_full(&mut buf.as_slice()).unwrap();
+    assert_eq!(full, empty);
+}
+
+#[test]
+fn test_schema_and_corruption() {
+    let tree = tree(3);
+    let mut cursor = epserde::new_aligned_cursor();
+    let schema = tree.serialize_with_schema(&mut cursor).unwrap();
+    let mut buf = cursor.into_inner();
+    let table = schema
+        .0
+        .iter()
+        .find(|row| row.field == "ROOT.nodes.offsets.zero")
+        .unwrap();
+    assert_eq!(table.size, 4 * core::mem::size_of::<u64>());
+    // Items follow the table
+    let items = schema
+        .0
+        .iter()
+        .position(|row| row.field == "ROOT.nodes.item")
+        .unwrap();
+    assert!(schema.0[items].offset >= table.offset + table.size);
+
+    // Offsets must be non-decreasing
+    let second = table.offset + core::mem::size_of::<u64>();
+    buf[second..second + 8].copy_from_slice(&u64::MAX.to_ne_bytes());
+    assert!(matches!(
+        <Tree<Vec<Node>, Vec<u64>>>::deserialize_eps(&buf)
+            .map(|_| ())
+            .unwrap_err(),
+        deser::Error::InvalidValue("Indexed")
+    ));
+}
+
+#[test]
+fn test_malformed_table() {
+    let empty = Tree {
+        root: 0,
+        nodes: Vec::<Node>::new(),
+        weights: Vec::<u64>::new(),
+    };
+    let mut cursor = epserde::new_aligned_cursor();
+    let schema = empty.serialize_with_schema(&mut cursor).unwrap();
+    let buf = cursor.into_inner();
+    let table = schema
+        .0
+        .iter()
+        .find(|row| row.field == "ROOT.nodes.offsets.zero")
+        .unwrap();
+    assert_eq!(table.size, core::mem::size_of::<u64>());
+
+    // The end of the items precedes the end of the length, or it is past the
+    // end of the data
+    for end in [0, 1, u64::MAX] {
+        let mut buf = buf.clone();
+        buf[table.offset..table.offset + 8].copy_from_slice(&end.to_ne_bytes());
+        assert!(matches!(
+            <Tree<Vec<Node>, Vec<u64>>>::deserialize_eps(&buf)
+                .map(|_| ())
+                .unwrap_err(),
+            deser::Error::InvalidValue("Indexed")
+        ));
+    }
+}
+
+#[test]
+fn test_portable() {
+    let tree = tree(20);
+    let mut cursor = epserde::new_aligned_cursor();
+    tree.serialize_portable(&mut cursor).unwrap();
+    let buf = cursor.into_inner();
+    let full =
+        <Tree<Vec<Node>, Vec<u64>>>::deserialize_full(&mut std::io::Cursor::new(&buf)).unwrap();
+    assert_eq!(full, tree);
+}
+
+#[test]
+fn test_type_hash() {
+    #[derive(Epserde)]
+    struct Plain<N> {
+        nodes: N,
+    }
+    #[derive(Epserde)]
+    struct Indexed<N> {
+        #[epserde(indexed)]
+        nodes: N,
+    }
+    // The two layouts are not compatible
+    assert_ne!(
+        epserde::type_hash_of::<Plain<Vec<Node>>>(),
+        epserde::type_hash_of::<Indexed<Vec<Node>>>()
+    );
+}
@@ -0,0 +1,118 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+#![cfg(all(test, feature = "indexmap"))]
+
+use epserde::prelude::*;
+use indexmap::{IndexMap, IndexSet};
+
+#[test]
+fn test_index_map_zero() {
+    let map: IndexMap<u32, u64> = (0..100).map(|i| ((i * 37) % 101, i as u64 * 10)).collect();
+
+    let mut cursor = epserde::new_aligned_cursor();
+    map.serialize(&mut cursor).unwrap();
+
+    cursor.set_position(0);
+    let full = <IndexMap<u32, u64>>::deserialize_full(&mut cursor).unwrap();
+    assert_eq!(map, full);
+    assert!(full.iter().eq(map.iter()));
+
+    let buf = cursor.into_inner();
+    let eps = <IndexMap<u32, u64>>::deserialize_eps(&buf).unwrap();
+    assert_eq!(eps.len(), map.len());
+    assert!(eps.iter().eq(map.iter()));
+    for (i, (k, v)) in map.iter().enumerate() {
+        assert_eq!(eps.get_index(i), Some((k, v)));
+        assert_eq!(eps.get_index_of(k), Some(i));
+        assert_eq!(eps.get(k), Some(v));
+    }
+    assert_eq!(eps.get(&1000), None);
+    assert_eq!(eps.get_index(100), None);
+}
+
+#[test]
+fn test_index_map_deep() {
+    let mut map = IndexMap::new();
+    map.insert("zeta".to_string(), vec![1_u8, 2]);
+    map.insert("alpha".to_string(), vec![]);
+    map.insert("mu".to_string(), vec![3]);
+
+    let mut cursor = epserde::new_aligned_cursor();
+    map.serialize(&mut cursor).unwrap();
+
+    cursor.set_position(0);
+    let full = <IndexMap<String, Vec<u8>>>::deserialize_full(&mut cursor).unwrap();
+    assert!(full.iter().eq(map.iter()));
+
+    let buf = cursor.into_inner();
+    let eps = <IndexMap<String, Vec<u8>>>::deserialize_eps(&buf).unwrap();
+    assert_eq!(eps.keys(), &["zeta", "alpha", "mu"]);
+    assert_eq!(eps.get("zeta"), Some(&[1_u8, 2].as_slice()));
+    assert_eq!(eps.get("alpha"), Some(&[].as_slice()));
+    assert!(!eps.contains_key("beta"));
+}
+
+#[test]
+fn test_index_set() {
+    let set: IndexSet<i64> = [5, -3, 12, 0].into_iter().collect();
+
+    let mut cursor = epserde::new_aligned_cursor();
+    set.serialize(&mut cursor).unwrap();
+
+    cursor.set_position(0);
+    let full = <IndexSet<i64>>::deserialize_full(&mut cursor).unwrap();
+    assert!(full.iter().eq(set.iter()));
+
+    let buf = cursor.into_inner();
+    let eps = <IndexSet<i64>>::deserialize_eps(&buf).unwrap();
+    assert_eq!(eps.as_slice(), &[5, -3, 12, 0]);
+    assert_eq!(eps.get_index(2), Some(&12));
+    assert_eq!(eps.get_index_of(&0), Some(3));
+    assert!(eps.contains(&-3));
+    assert!(!eps.contains(&1));
+}
+
+#[test]
+fn test_canonical() {
+    let map: IndexMap<String, u64> = (0..100).map(|i| (i.to_string(), i)).collect();
+    let mut reversed = map.clone();
+    reversed.reverse();
+    assert_eq!(map, reversed);
+
+    let serialize = |map: &IndexMap<String, u64>, canonical: bool| {
+        let mut cursor = epserde::new_aligned_cursor();
+        if canonical {
+            map.serialize_canonical(&mut cursor).unwrap();
+        } else {
+            map.serialize(&mut cursor).unwrap();
+        }
+        cursor.into_inner()
+    };
+    assert_ne!(serialize(&map, false), serialize(&reversed, false));
+    let buf = serialize(&map, true);
+    assert_eq!(buf, serialize(&reversed, true));
+
+    // Canonical data is deserialized as usual, in canonical order
+    let full = <IndexMap<String, u64>>::deserialize_full(&mut buf.as_slice()).unwrap();
+    assert_eq!(full, map);
+    let eps = <IndexMap<String, u64>>::deserialize_eps(&buf).unwrap();
+    assert!(eps.keys().iter().eq(full.keys()));
+    for (k, v) in &map {
+        assert_eq!(eps.get(k.as_str()), Some(v));
+    }
+
+    // Sets, and maps nested in structures
+    let set: IndexSet<u32> = (0..100).collect();
+    let reversed_set: IndexSet<u32> = (0..100).rev().collect();
+    let mut a = epserde::new_aligned_cursor();
+    let mut b = epserde::new_aligned_cursor();
+    (set, vec![map]).serialize_canonical(&mut a).unwrap();
+    (reversed_set, vec![reversed])
+        .serialize_canonical(&mut b)
+        .unwrap();
+    assert_eq!(a.into_inner(), b.into_inner());
+}
@@ -0,0 +1,172 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ * SPDX-FileCopyrightText: 2023 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+#![cfg(test)]
+
+use epserde::inspect::{self, *};
+use epserde::prelude::*;
+
+#[derive(Epserde, Debug, PartialEq)]
+struct Data {
+    name: String,
+    values: Vec<u64>,
+}
+
+#[derive(Epserde, Debug, PartialEq)]
+struct DataV2 {
+    name: String,
+    values: Vec<u32>,
+}
+
+fn data() -> Data {
+    Data {
+        name: "data".to_string(),
+        values: (0..100).collect(),
+    }
+}
+
+#[test]
+fn test_signature() -> anyhow::Result<()> {
+    let mut cursor = epserde::new_aligned_cursor();
+    data().serialize_with_signature(&mut cursor)?;
+    let buf = cursor.into_inner();
+
+    let info = read_header_from(&mut &buf[..])?;
+    assert_eq!(info.version, epserde::VERSION);
+    assert_eq!(info.usize_size, core::mem::size_of::<usize>());
+    assert!(!info.portable);
+    assert_eq!(info.type_hash, epserde::type_hash_of::<Data>());
+    assert_eq!(info.type_name, core::any::type_name::<Data>());
+    assert!(info.checksum.is_none());
+    assert!(info.codec.is_none());
+    let type_signature = info.type_signature.as_ref().unwrap();
+    assert!(type_signature.iter().any(|token| token == "name"));
+    assert!(type_signature.iter().any(|token| token == "values"));
+    assert!(info.to_string().contains("Type signature"));
+
+    assert_eq!(info.signature_diff::<Data>(), Some(vec![]));
+    let changes = info.signature_diff::<DataV2>().unwrap();
+    assert!(!changes.is_empty());
+    assert!(changes.iter().any(|change| change.contains("u64")));
+    assert!(changes.iter().any(|change| change.contains("u32")));
+
+    // The signature does not change deserialization
+    assert_eq!(Data::deserialize_full(&mut &buf[..])?, data());
+    let eps = Data::deserialize_eps(&buf)?;
+    assert_eq!(eps.values, data().values);
+    assert!(matches!(
+        DataV2::deserialize_eps(&buf),
+        Err(deser::Error::WrongTypeHash { .. })
+    ));
+    Ok(())
+}
+
+#[test]
+fn test_no_signature() -> anyhow::Result<()> {
+    let mut cursor = epserde::new_aligned_cursor();
+    data().serialize_with_checksum(&mut cursor)?;
+    let buf = cursor.into_inner();
+
+    let info = read_header_from(&mut &buf[..])?;
+    assert!(info.type_signature.is_none());
+    assert!(info.signature_diff::<Data>().is_none());
+    assert!(info.checksum.is_some());
+    assert!(!info.to_string().contains("Type signature"));
+
+    assert!(read_header_from(&mut &b"not epserde data"[..]).is_err());
+    Ok(())
+}
+
+#[test]
+fn test_read_header() -> anyhow::Result<()> {
+    let path = std::env::temp_dir().join("test_inspect.eps");
+    data().store_with_signature(&path)?;
+    let info = read_header(&path)?;
+    assert_eq!(info.type_name, core::any::type_name::<Data>());
+    assert!(info.type_signature.is_some());
+    assert_eq!(Data::load_full(&path)?, data());
+    std::fs::remove_file(&path)?;
+    Ok(())
+}
+
+#[test]
+fn test_deser_read_header() -> anyhow::Result<()> {
+    let mut cursor = epserde::new_aligned_cursor();
+    data().serialize_portable(&mut cursor)?;
+    let buf = cursor.into_inner();
+
+    let mut backend = deser::SliceWithPos::new(&buf);
+    let header = deser::read_header(&mut backend)?;
+    assert_eq!(header.version, epserde::VERSION);
+    assert_eq!(header.usize_size, 8);
+    assert!(header.portable);
+    assert_eq!(header.type_hash, epserde::type_hash_of::<Data>());
+    assert_eq!(header.type_name, core::any::type_name::<Data>());
+    assert!(header.type_signature.is_none());
+    assert_eq!(header, read_header_from(&mut &buf[..])?);
+
+    // The backend is positioned at the start of the payload
+    let name = String::_deserialize_full_inner(&mut backend)?;
+    assert_eq!(name, "data");
+
+    let mut bad = buf.clone();
+    bad[..8].reverse();
+    assert!(matches!(
+        deser::read_header(&mut deser::SliceWithPos::new(&bad)),
+        Err(deser::Error::EndiannessError)
+    ));
+    Ok(())
+}
+
+#[test]
+fn test_set_portable() -> anyhow::Result<()> {
+    let mut cursor = epserde::new_aligned_cursor();
+    data().serialize_with_checksum(&mut cursor)?;
+    let mut buf = cursor.into_inner();
+    let native_usize_size = core::mem::size_of::<usize>();
+
+    if native_usize_size == 8 {
+        inspect::set_portable(&mut buf, true)?;
+        let info = read_header_from(&mut &buf[..])?;
+        assert!(info.portable);
+        assert!(info.checksum.is_some());
+        assert_eq!(Data::deserialize_full(&mut &buf[..])?, data());
+
+        inspect::set_portable(&mut buf, false)?;
+        assert!(!read_header_from(&mut &buf[..])?.portable);
+        assert_eq!(Data::deserialize_full(&mut &buf[..])?, data());
+    } else {
+        assert!(matches!(
+            inspect::set_portable(&mut buf, true),
+            Err(deser::Error::UsizeSizeMismatch(_))
+        ));
+    }
+
+    assert!(inspect::set_portable(&mut buf[..10], true).is_err());
+    buf[..8].reverse();
+    assert!(matches!(
+        inspect::set_portable(&mut buf, true),
+        Err(deser::Error::EndiannessError)
+    ));
+    Ok(())
+}
+
+#[test]
+fn test_schema_path() -> anyhow::Result<()> {
+    let path = std::env::temp_dir().join("test_inspect_schema.eps");
+    let schema_path = inspect::schema_path(&path);
+    assert_eq!(
+        schema_path,
+        std::env::temp_dir().join("test_inspect_schema.eps.schema.json")
+    );
+    data().store_with_schema(&path)?;
+    let schema = ser::Schema::from_json(&std::fs::read_to_string(&schema_path)?)?;
+    assert!(schema.0.iter().any(|row| row.field == "ROOT.values"));
+    std::fs::remove_file(&path)?;
+    std::fs::remove_file(&schema_path)?;
+    Ok(())
+}
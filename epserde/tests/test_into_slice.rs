@@ -0,0 +1,99 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ * SPDX-FileCopyrightText: 2023 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+#![cfg(test)]
+
+use epserde::prelude::*;
+use std::io::Cursor;
+
+#[derive(Epserde, Debug, PartialEq)]
+struct Data<A, B> {
+    name: A,
+    values: B,
+}
+
+fn data() -> Data<String, Vec<u32>> {
+    Data {
+        name: "data".to_string(),
+        values: (0..100).collect(),
+    }
+}
+
+#[test]
+fn test_into_slice() {
+    let mut buf = [0_u128; 64];
+    let bytes: &mut [u8] = bytemuck::cast_slice_mut(&mut buf);
+    let len = data().serialize_into_slice(bytes).unwrap();
+
+    let mut cursor = epserde::new_aligned_cursor();
+    data().serialize(&mut cursor).unwrap();
+    assert_eq!(&bytes[..len], &cursor.into_inner()[..]);
+
+    let full = <Data<String, Vec<u32>>>::deserialize_full(&mut &bytes[..len]).unwrap();
+    assert_eq!(full, data());
+    let eps = <Data<String, Vec<u32>>>::deserialize_eps(&bytes[..len]).unwrap();
+    assert_eq!(eps.name, "data");
+    assert_eq!(eps.values, &data().values[..]);
+
+    // Every shorter slice reports the required size
+    for short in [0, 1, 10, len - 1] {
+        assert!(matches!(
+            data().serialize_into_slice(&mut bytes[..short]),
+            Err(ser::Error::BufferTooSmall { required }) if required == len
+        ));
+    }
+    assert_eq!(data().serialize_into_slice(&mut bytes[..len]).unwrap(), len);
+}
+
+#[test]
+fn test_into_slice_alignment() {
+    let mut buf = [0_u128; 64];
+    let bytes: &mut [u8] = bytemuck::cast_slice_mut(&mut buf);
+    // Two messages in the same buffer, the second one at an aligned offset
+    let first = vec![1_u64, 2, 3].serialize_into_slice(bytes).unwrap();
+    let second_start = first.next_multiple_of(16);
+    let second = data()
+        .serialize_into_slice(&mut bytes[second_start..])
+        .unwrap();
+    assert_eq!(
+        <Vec<u64>>::deserialize_eps(&bytes[..first]).unwrap(),
+        [1, 2, 3]
+    );
+    let eps =
+        <Data<String, Vec<u32>>>::deserialize_eps(&bytes[second_start..second_start + second])
+            .unwrap();
+    assert_eq!(eps.values, &data().values[..]);
+
+    // A misaligned message can be fully deserialized, but not ε-copy deserialized
+    let len = data().serialize_into_slice(&mut bytes[1..]).unwrap();
+    assert!(matches!(
+        <Data<String, Vec<u32>>>::deserialize_eps(&bytes[1..1 + len]),
+        Err(deser::Error::AlignmentError)
+    ));
+    assert_eq!(
+        <Data<String, Vec<u32>>>::deserialize_full(&mut &bytes[1..1 + len]).unwrap(),
+        data()
+    );
+}
+
+#[test]
+fn test_cursor() {
+    let mut buf = [0_u128; 64];
+    let bytes: &mut [u8] = bytemuck::cast_slice_mut(&mut buf);
+    let mut cursor = Cursor::new(&mut bytes[..]);
+    let len = data().serialize(&mut cursor).unwrap();
+    assert_eq!(cursor.position() as usize, len);
+    let eps = <Data<String, Vec<u32>>>::deserialize_eps(&bytes[..len]).unwrap();
+    assert_eq!(eps.name, "data");
+
+    // A cursor just fails when the slice is full
+    let mut cursor = Cursor::new(&mut bytes[..len - 1]);
+    assert!(matches!(
+        data().serialize(&mut cursor),
+        Err(ser::Error::WriteError)
+    ));
+}
@@ -0,0 +1,139 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+#![cfg(test)]
+
+use epserde::prelude::*;
+use epserde::traits::type_layout;
+
+#[derive(Epserde, Debug, Clone, Copy)]
+#[repr(C)]
+#[zero_copy]
+struct Pos {
+    x: u32,
+    y: u16,
+}
+
+#[derive(Epserde, Debug, Clone, Copy)]
+#[repr(C)]
+#[zero_copy]
+struct Record {
+    tag: u8,
+    value: u64,
+    flags: u16,
+    pos: Pos,
+}
+
+#[derive(Epserde, Debug, Clone, Copy)]
+#[repr(C)]
+#[zero_copy]
+struct Pair<T> {
+    first: u8,
+    second: T,
+}
+
+#[derive(Epserde, Debug, PartialEq)]
+struct Deep {
+    values: Vec<u64>,
+}
+
+#[test]
+fn test_offsets() {
+    let layout = Record::layout();
+    assert_eq!(layout.size, core::mem::size_of::<Record>());
+    assert_eq!(layout.align, core::mem::align_of::<Record>());
+    let names = layout
+        .fields
+        .iter()
+        .map(|field| field.name.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(names, ["tag", "value", "flags", "pos"]);
+    assert_eq!(layout.fields[0].offset, 0);
+    assert_eq!(layout.fields[3].layout, Pos::layout());
+    assert_eq!(layout.fields[3].layout.fields[1].offset, 4);
+    assert_eq!(type_layout::<Record>(), layout);
+}
+
+#[test]
+fn test_leaves() {
+    let layout = u64::layout();
+    assert_eq!(layout.ty, "u64");
+    assert_eq!(layout.size, 8);
+    assert!(layout.fields.is_empty());
+    assert_eq!(layout.padding(), 0);
+    assert!(<[u32; 3]>::layout().fields.is_empty());
+    assert!(Deep::layout().fields.is_empty());
+}
+
+#[test]
+fn test_generic() {
+    let layout = Pair::<u32>::layout();
+    assert_eq!(layout.size, 8);
+    assert_eq!(layout.fields[1].offset, 4);
+    assert_eq!(layout.padding(), 3);
+
+    let layout = Pair::<Pos>::layout();
+    assert_eq!(layout.fields[1].layout, Pos::layout());
+    // Three bytes after first, and two at the end of Pos.
+    assert_eq!(layout.padding(), 5);
+}
+
+/// The layout of [`Record`] on 64-bit architectures such as x86_64 and
+/// aarch64, where `u64` is aligned to 8 bytes. CI runs this test on both.
+#[cfg(any(target_arch = "x86_64", target_arch = "aarch64"))]
+const RECORD_LAYOUT_64: &str = r#"{
+  "ty": "test_layout::Record",
+  "size": 32,
+  "align": 8,
+  "fields": [
+    {"name": "tag", "offset": 0, "layout": {
+      "ty": "u8",
+      "size": 1,
+      "align": 1,
+      "fields": []
+    }},
+    {"name": "value", "offset": 8, "layout": {
+      "ty": "u64",
+      "size": 8,
+      "align": 8,
+      "fields": []
+    }},
+    {"name": "flags", "offset": 16, "layout": {
+      "ty": "u16",
+      "size": 2,
+      "align": 2,
+      "fields": []
+    }},
+    {"name": "pos", "offset": 20, "layout": {
+      "ty": "test_layout::Pos",
+      "size": 8,
+      "align": 4,
+      "fields": [
+        {"name": "x", "offset": 0, "layout": {
+          "ty": "u32",
+          "size": 4,
+          "align": 4,
+          "fields": []
+        }},
+        {"name": "y", "offset": 4, "layout": {
+          "ty": "u16",
+          "size": 2,
+          "align": 2,
+          "fields": []
+        }}
+      ]
+    }}
+  ]
+}
+"#;
+
+#[cfg(any(target_arch = "x86_64", target_arch = "aarch64"))]
+#[test]
+fn test_golden() {
+    let layout = Record::layout();
+    assert_eq!(layout.to_json(), RECORD_LAYOUT_64);
+    assert_eq!(layout.padding(), 15);
+}
@@ -76,3 +76,55 @@ fn test_max_size_of_align() {
     let eps = <MyStruct64>::deserialize_eps(&buf).unwrap();
     assert_eq!(x, *eps);
 }
+
+#[derive(Epserde, Debug, PartialEq)]
+struct Deep {
+    id: u8,
+    name: String,
+    values: Vec<MyStruct64>,
+}
+
+#[test]
+fn test_max_field_align() {
+    // Zero-copy types agree with MaxSizeOf
+    assert_eq!(MyStruct64::MAX_FIELD_ALIGN, MyStruct64::max_size_of());
+    assert_eq!(MyStruct2::MAX_FIELD_ALIGN, MyStruct2::max_size_of());
+    assert_eq!(MyStruct::MAX_FIELD_ALIGN, MyStruct::max_size_of());
+    assert_eq!(u128::MAX_FIELD_ALIGN, u128::max_size_of());
+    assert_eq!(<[u16; 3]>::MAX_FIELD_ALIGN, 2);
+    assert_eq!(<(u8, u32)>::MAX_FIELD_ALIGN, 4);
+
+    // Deep-copy types use the maximum over their fields
+    assert_eq!(String::MAX_FIELD_ALIGN, 1);
+    assert_eq!(<Vec<u8>>::MAX_FIELD_ALIGN, 1);
+    assert_eq!(<Vec<u64>>::MAX_FIELD_ALIGN, 8);
+    assert_eq!(<Option<Vec<u32>>>::MAX_FIELD_ALIGN, 4);
+    assert_eq!(<Box<[MyStruct2]>>::MAX_FIELD_ALIGN, 4);
+    assert_eq!(<Vec<Vec<MyStruct64>>>::MAX_FIELD_ALIGN, 64);
+    assert_eq!(Deep::MAX_FIELD_ALIGN, 64);
+
+    // The constant can be used in constant expressions
+    const ALIGN: usize = Deep::MAX_FIELD_ALIGN;
+    assert_eq!(ALIGN, 64);
+}
+
+#[test]
+fn test_max_field_align_alloc() {
+    let deep = Deep {
+        id: 0,
+        name: "deep".to_string(),
+        values: vec![MyStruct64 { u: 0x89 }; 3],
+    };
+    let mut cursor = std::io::Cursor::new(Vec::new());
+    deep.serialize(&mut cursor).unwrap();
+    let bytes = cursor.into_inner();
+
+    // Allocate memory with the alignment required by the type
+    let layout = std::alloc::Layout::from_size_align(bytes.len(), Deep::MAX_FIELD_ALIGN).unwrap();
+    let ptr = unsafe { std::alloc::alloc(layout) };
+    let buf = unsafe { std::slice::from_raw_parts_mut(ptr, bytes.len()) };
+    buf.copy_from_slice(&bytes);
+    let eps = Deep::deserialize_eps(buf).unwrap();
+    assert_eq!(eps.values, deep.values);
+    unsafe { std::alloc::dealloc(ptr, layout) };
+}
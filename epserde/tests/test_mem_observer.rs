@@ -0,0 +1,102 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+#![cfg(test)]
+
+use epserde::prelude::*;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+#[derive(Default)]
+struct Accounting {
+    created: AtomicUsize,
+    dropped: AtomicUsize,
+    live_bytes: AtomicUsize,
+}
+
+impl deser::MemObserver for Accounting {
+    fn on_create(&self, backend: &deser::MemBackend) {
+        self.created.fetch_add(1, Ordering::SeqCst);
+        self.live_bytes.fetch_add(backend.size(), Ordering::SeqCst);
+    }
+
+    fn on_drop(&self, backend: &deser::MemBackend) {
+        self.dropped.fetch_add(1, Ordering::SeqCst);
+        self.live_bytes.fetch_sub(backend.size(), Ordering::SeqCst);
+    }
+}
+
+impl Accounting {
+    fn counts(&self) -> (usize, usize, usize) {
+        (
+            self.created.load(Ordering::SeqCst),
+            self.dropped.load(Ordering::SeqCst),
+            self.live_bytes.load(Ordering::SeqCst),
+        )
+    }
+}
+
+// A single test, as the observer is process-wide
+#[test]
+fn test_mem_observer() {
+    let accounting = Arc::new(Accounting::default());
+    assert!(deser::set_mem_observer(Some(accounting.clone())).is_none());
+
+    let data: Vec<u64> = (0..1000).collect();
+    data.store("test_observer.bin").unwrap();
+    let len = std::fs::metadata("test_observer.bin").unwrap().len() as usize;
+
+    let encased = MemCase::encase(data.clone());
+    drop(encased);
+    assert_eq!(accounting.counts(), (0, 0, 0));
+
+    let mem = <Vec<u64>>::load_mem("test_observer.bin").unwrap();
+    let (created, dropped, live_bytes) = accounting.counts();
+    assert_eq!((created, dropped), (1, 0));
+    assert!(live_bytes >= len);
+
+    let mmap = <Vec<u64>>::mmap("test_observer.bin", Flags::empty()).unwrap();
+    let load_mmap = <Vec<u64>>::load_mmap("test_observer.bin", Flags::empty()).unwrap();
+    let mut cursor = epserde::new_aligned_cursor();
+    data.serialize(&mut cursor).unwrap();
+    let owned = <Vec<u64>>::deserialize_eps_owned(cursor.into_inner()).unwrap();
+    assert_eq!(accounting.counts().0, 4);
+    assert_eq!(*mem, data);
+    assert_eq!(*mmap, data);
+    assert_eq!(*load_mmap, data);
+    assert_eq!(*owned, data);
+
+    drop(mem);
+    drop(mmap);
+    drop(load_mmap);
+    drop(owned);
+    assert_eq!(accounting.counts(), (4, 4, 0));
+
+    // Backends are freed also when deserialization fails
+    assert!(<Vec<u32>>::load_mem("test_observer.bin").is_err());
+    assert!(<Vec<u32>>::mmap("test_observer.bin", Flags::empty()).is_err());
+    assert!(<Vec<u32>>::load_mmap("test_observer.bin", Flags::empty()).is_err());
+    assert!(<Vec<u32>>::deserialize_eps_owned(vec![0; 64]).is_err());
+    assert_eq!(accounting.counts(), (8, 8, 0));
+
+    // A backend taken out of its MemCase is no longer observed
+    let backend = <Vec<u64>>::load_mem("test_observer.bin")
+        .unwrap()
+        .into_backend();
+    assert_eq!(accounting.counts(), (9, 9, 0));
+    let deser::MemBackend::Memory(bytes) = backend else {
+        panic!("unexpected backend {:?}", backend);
+    };
+    drop(bytes);
+    assert_eq!(accounting.counts(), (9, 9, 0));
+
+    // After removing the observer, nothing is reported
+    assert!(deser::set_mem_observer(None).is_some());
+    drop(<Vec<u64>>::load_mem("test_observer.bin").unwrap());
+    assert_eq!(accounting.counts(), (9, 9, 0));
+
+    std::fs::remove_file("test_observer.bin").unwrap();
+}
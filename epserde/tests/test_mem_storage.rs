@@ -0,0 +1,162 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+#![cfg(test)]
+
+use epserde::deser::MemStorage;
+use epserde::prelude::*;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+/// A region of 16-byte aligned memory counting how many times it is dropped.
+struct Region {
+    mem: Vec<u128>,
+    drops: Arc<AtomicUsize>,
+}
+
+impl Region {
+    fn new(len: usize, drops: Arc<AtomicUsize>) -> Self {
+        Self {
+            mem: vec![0; len.div_ceil(16)],
+            drops,
+        }
+    }
+}
+
+unsafe impl MemStorage for Region {
+    fn bytes(&self) -> &[u8] {
+        bytemuck::cast_slice(&self.mem)
+    }
+}
+
+impl AsMut<[u8]> for Region {
+    fn as_mut(&mut self) -> &mut [u8] {
+        bytemuck::cast_slice_mut(&mut self.mem)
+    }
+}
+
+impl Drop for Region {
+    fn drop(&mut self) {
+        self.drops.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+#[test]
+fn test_deserialize_eps_in() {
+    let data: Vec<u64> = (0..100).collect();
+    let mut cursor = epserde::new_aligned_cursor();
+    let len = data.serialize(&mut cursor).unwrap();
+    let drops = Arc::new(AtomicUsize::new(0));
+
+    let mut region = Region::new(len, drops.clone());
+    region.as_mut()[..len].copy_from_slice(&cursor.into_inner()[..len]);
+    let case = <Vec<u64>>::deserialize_eps_in(region).unwrap();
+    assert_eq!(*case, data.as_slice());
+    assert!(!case.advise(deser::Flags::SEQUENTIAL));
+    assert_eq!(drops.load(Ordering::SeqCst), 0);
+    drop(case);
+    assert_eq!(drops.load(Ordering::SeqCst), 1);
+
+    // The region is released also on errors
+    let region = Region::new(64, drops.clone());
+    assert!(<Vec<u64>>::deserialize_eps_in(region).is_err());
+    assert_eq!(drops.load(Ordering::SeqCst), 2);
+}
+
+#[test]
+fn test_load_in() {
+    let data: Vec<String> = vec!["a".into(), "bb".into(), "ccc".into()];
+    let tmp_file = std::env::temp_dir().join("test_load_in.eps");
+    data.store(&tmp_file).unwrap();
+    let drops = Arc::new(AtomicUsize::new(0));
+
+    // A larger region is fine
+    let case = <Vec<String>>::load_in(&tmp_file, |len| Ok(Region::new(len + 4096, drops.clone())))
+        .unwrap();
+    assert_eq!(*case, ["a", "bb", "ccc"]);
+    drop(case);
+    assert_eq!(drops.load(Ordering::SeqCst), 1);
+
+    // A shorter region is an error
+    assert!(
+        <Vec<String>>::load_in(&tmp_file, |len| Ok(Region::new(len - 16, drops.clone()))).is_err()
+    );
+    assert_eq!(drops.load(Ordering::SeqCst), 2);
+
+    std::fs::remove_file(&tmp_file).unwrap();
+}
+
+#[cfg(feature = "mmap-rs")]
+#[test]
+fn test_load_in_mmap() {
+    let data: Vec<String> = vec!["a".into(), "bb".into(), "ccc".into()];
+    let tmp_file = std::env::temp_dir().join("test_load_in_mmap.eps");
+    data.store(&tmp_file).unwrap();
+
+    let case = <Vec<String>>::load_in(&tmp_file, |len| {
+        Ok(mmap_rs::MmapOptions::new(len)?.map_mut()?)
+    })
+    .unwrap();
+    assert_eq!(*case, ["a", "bb", "ccc"]);
+    #[cfg(target_os = "linux")]
+    assert!(case.advise(deser::Flags::RANDOM_ACCESS));
+
+    std::fs::remove_file(&tmp_file).unwrap();
+}
+
+#[test]
+fn test_shared_buffers() {
+    let data: Vec<u64> = (0..100).collect();
+    let mut cursor = epserde::new_aligned_cursor();
+    data.serialize(&mut cursor).unwrap();
+    let bytes = cursor.into_inner();
+
+    let buffer: Arc<[u8]> = bytes.clone().into();
+    let case = <Vec<u64>>::deserialize_eps_in(buffer.clone()).unwrap();
+    assert_eq!(*case, data.as_slice());
+    // The buffer is shared with the case
+    assert_eq!(Arc::strong_count(&buffer), 2);
+    drop(case);
+    assert_eq!(Arc::strong_count(&buffer), 1);
+
+    let case = <Vec<u64>>::deserialize_eps_in(bytes.clone().into_boxed_slice()).unwrap();
+    assert_eq!(*case, data.as_slice());
+
+    let leaked: &'static [u8] = Box::leak(bytes.clone().into_boxed_slice());
+    let case = <Vec<u64>>::deserialize_eps_in(leaked).unwrap();
+    assert_eq!(*case, data.as_slice());
+
+    #[cfg(feature = "bytes")]
+    {
+        let buffer = bytes::Bytes::from(bytes);
+        let case = <Vec<u64>>::deserialize_eps_in(buffer.clone()).unwrap();
+        assert_eq!(*case, data.as_slice());
+        drop(case);
+        assert!(buffer.is_unique());
+    }
+}
+
+#[cfg(feature = "memmap2")]
+#[test]
+fn test_memmap2() {
+    let data: Vec<String> = vec!["a".into(), "bb".into(), "ccc".into()];
+    let tmp_file = std::env::temp_dir().join("test_memmap2.eps");
+    data.store(&tmp_file).unwrap();
+
+    let file = std::fs::File::open(&tmp_file).unwrap();
+    // SAFETY: the file is not modified while it is mapped.
+    let mmap = unsafe { memmap2::Mmap::map(&file) }.unwrap();
+    let case = <Vec<String>>::deserialize_eps_in(mmap).unwrap();
+    assert_eq!(*case, ["a", "bb", "ccc"]);
+    #[cfg(unix)]
+    assert!(case.advise(deser::Flags::RANDOM_ACCESS));
+
+    let case =
+        <Vec<String>>::load_in(&tmp_file, |len| Ok(memmap2::MmapMut::map_anon(len)?)).unwrap();
+    assert_eq!(*case, ["a", "bb", "ccc"]);
+
+    std::fs::remove_file(&tmp_file).unwrap();
+}
@@ -82,3 +82,330 @@ fn test_mem_case() {
     // cleanup the file
     std::fs::remove_file("test.bin").unwrap();
 }
+
+#[test]
+fn test_mmap_hints() {
+    let person = Person {
+        a: vec![0x89; 6],
+        b: Data {
+            a: vec![0x42; 7],
+            b: vec![0xbadf00d; 2],
+        },
+        test: -0xbadf00d,
+    };
+    person.store("test_hints.bin").unwrap();
+
+    // Hints are best-effort: a nonexistent node or an unusable address
+    // must not make loading fail
+    for hints in [
+        MmapHints::new(),
+        MmapHints::new().with_numa_node(0),
+        MmapHints::new().with_numa_node(1 << 20),
+        MmapHints::new().with_address(0x6000_0000_0000),
+        MmapHints::new().with_address(1).with_numa_node(0),
+    ] {
+        let res = Person::load_mmap_with_hints("test_hints.bin", Flags::empty(), hints).unwrap();
+        assert_eq!(person.test, res.test);
+        assert_eq!(person.a, res.a);
+        assert_eq!(person.b.a, res.b.a);
+        assert_eq!(person.b.b, res.b.b);
+
+        let res = Person::mmap_with_hints("test_hints.bin", Flags::empty(), hints).unwrap();
+        assert_eq!(person.test, res.test);
+        assert_eq!(person.a, res.a);
+        assert_eq!(person.b.a, res.b.a);
+        assert_eq!(person.b.b, res.b.b);
+    }
+
+    std::fs::remove_file("test_hints.bin").unwrap();
+}
+
+#[test]
+fn test_deserialize_eps_owned() {
+    let person = Person {
+        a: vec![0x89; 6],
+        b: Data {
+            a: vec![0x42; 7],
+            b: vec![0xbadf00d; 2],
+        },
+        test: -0xbadf00d,
+    };
+    let mut cursor = epserde::new_aligned_cursor();
+    person.serialize(&mut cursor).unwrap();
+
+    let res = Person::deserialize_eps_owned(cursor.into_inner()).unwrap();
+    assert_eq!(person.test, res.test);
+    assert_eq!(person.a, res.a);
+    assert_eq!(person.b.a, res.b.a);
+    assert_eq!(person.b.b, res.b.b);
+
+    // The MemCase is self-contained
+    let res = std::thread::spawn(move || res.a.to_vec()).join().unwrap();
+    assert_eq!(person.a, res);
+
+    assert!(matches!(
+        Person::deserialize_eps_owned(vec![0; 8]),
+        Err(deser::Error::MagicCookieError(0))
+    ));
+}
+
+#[test]
+fn test_mmap_range() {
+    let person = Person {
+        a: vec![0x89; 6],
+        b: Data {
+            a: vec![0x42; 7],
+            b: vec![0xbadf00d; 2],
+        },
+        test: -0xbadf00d,
+    };
+    let ints: Vec<u64> = (0..1000).collect();
+
+    // Pack several structures in the same file, at offsets that are
+    // multiple of 16, plus an unaligned one
+    let mut file = Vec::<u8>::new();
+    let mut ranges = vec![];
+    for _ in 0..2 {
+        let offset = file.len();
+        let len = person.serialize(&mut file).unwrap();
+        ranges.push((offset as u64, len));
+        file.resize(file.len() + epserde::pad_align_to(len, 16), 0);
+        let offset = file.len();
+        let len = ints.serialize(&mut file).unwrap();
+        ranges.push((offset as u64, len));
+        file.resize(file.len() + epserde::pad_align_to(len, 16), 0);
+    }
+    file.push(0);
+    let unaligned_offset = file.len();
+    let unaligned_len = ints.serialize(&mut file).unwrap();
+    std::fs::write("test_range.bin", file).unwrap();
+
+    for (i, &(offset, len)) in ranges.iter().enumerate() {
+        if i % 2 == 0 {
+            let res =
+                Person::load_mmap_range("test_range.bin", offset, len, Flags::empty()).unwrap();
+            assert_eq!(person.a, res.a);
+            assert_eq!(person.b.b, res.b.b);
+            let res = Person::mmap_range("test_range.bin", offset, len, Flags::empty()).unwrap();
+            assert_eq!(person.a, res.a);
+            assert_eq!(person.b.b, res.b.b);
+        } else {
+            let res =
+                <Vec<u64>>::load_mmap_range("test_range.bin", offset, len, Flags::empty()).unwrap();
+            assert_eq!(ints, *res);
+            let res =
+                <Vec<u64>>::mmap_range("test_range.bin", offset, len, Flags::empty()).unwrap();
+            assert_eq!(ints, *res);
+        }
+    }
+
+    // Loading copies the data, so any offset works...
+    let res = <Vec<u64>>::load_mmap_range(
+        "test_range.bin",
+        unaligned_offset as u64,
+        unaligned_len,
+        Flags::empty(),
+    )
+    .unwrap();
+    assert_eq!(ints, *res);
+    // ...but mapping requires aligned offsets
+    let err = <Vec<u64>>::mmap_range(
+        "test_range.bin",
+        unaligned_offset as u64,
+        unaligned_len,
+        Flags::empty(),
+    )
+    .err()
+    .unwrap();
+    assert!(matches!(err, deser::Error::AlignmentError));
+
+    // Ranges must lie within the file
+    assert!(matches!(
+        <Vec<u64>>::mmap_range(
+            "test_range.bin",
+            unaligned_offset as u64,
+            unaligned_len + 1,
+            Flags::empty()
+        ),
+        Err(deser::Error::RangeOutOfBounds { .. })
+    ));
+    assert!(matches!(
+        <Vec<u64>>::load_mmap_range("test_range.bin", u64::MAX, 1, Flags::empty()),
+        Err(deser::Error::RangeOutOfBounds { .. })
+    ));
+
+    std::fs::remove_file("test_range.bin").unwrap();
+}
+
+#[test]
+fn test_mmap_share_flags() {
+    let ints: Vec<u64> = (0..1000).collect();
+    ints.store("test_share.bin").unwrap();
+
+    for flags in [
+        Flags::SHARED,
+        Flags::COPY_ON_WRITE,
+        Flags::DENY_WRITE,
+        Flags::SHARED | Flags::DENY_WRITE,
+    ] {
+        let res = <Vec<u64>>::mmap("test_share.bin", flags).unwrap();
+        assert_eq!(ints, *res);
+        // Loading ignores the flags
+        let res = <Vec<u64>>::load_mmap("test_share.bin", flags).unwrap();
+        assert_eq!(ints, *res);
+    }
+    assert!(matches!(
+        <Vec<u64>>::mmap("test_share.bin", Flags::SHARED | Flags::COPY_ON_WRITE),
+        Err(deser::Error::FileOpenError(_))
+    ));
+
+    // On Windows, other writers are locked out while the file is mapped
+    #[cfg(windows)]
+    {
+        let res = <Vec<u64>>::mmap("test_share.bin", Flags::DENY_WRITE).unwrap();
+        assert!(std::fs::OpenOptions::new()
+            .write(true)
+            .open("test_share.bin")
+            .is_err());
+        drop(res);
+        assert!(std::fs::OpenOptions::new()
+            .write(true)
+            .open("test_share.bin")
+            .is_ok());
+    }
+
+    std::fs::remove_file("test_share.bin").unwrap();
+
+    // Empty files cannot be mapped
+    std::fs::write("test_empty.bin", []).unwrap();
+    assert!(<Vec<u64>>::mmap("test_empty.bin", Flags::empty()).is_err());
+    assert!(<Vec<u64>>::load_mmap("test_empty.bin", Flags::empty()).is_err());
+    std::fs::remove_file("test_empty.bin").unwrap();
+}
+
+#[test]
+fn test_mmap_advice_flags() {
+    let ints: Vec<u64> = (0..1000).collect();
+    ints.store("test_advice.bin").unwrap();
+
+    for flags in [
+        Flags::WILL_NEED,
+        Flags::NO_CORE_DUMP,
+        Flags::WILL_NEED | Flags::NO_CORE_DUMP | Flags::TRANSPARENT_HUGE_PAGES,
+    ] {
+        let res = <Vec<u64>>::mmap("test_advice.bin", flags).unwrap();
+        assert_eq!(ints, *res);
+        let res = <Vec<u64>>::load_mmap("test_advice.bin", flags).unwrap();
+        assert_eq!(ints, *res);
+    }
+
+    // Advice can be given also after mapping
+    let res = <Vec<u64>>::mmap("test_advice.bin", Flags::empty()).unwrap();
+    #[cfg(target_os = "linux")]
+    assert!(res.advise(Flags::WILL_NEED | Flags::NO_CORE_DUMP));
+    #[cfg(not(target_os = "linux"))]
+    assert!(!res.advise(Flags::WILL_NEED | Flags::NO_CORE_DUMP));
+    drop(res);
+
+    std::fs::remove_file("test_advice.bin").unwrap();
+}
+
+#[test]
+fn test_prefault() {
+    let ints: Vec<u64> = (0..100_000).collect();
+    ints.store("test_prefault.bin").unwrap();
+
+    let res = <Vec<u64>>::mmap("test_prefault.bin", Flags::POPULATE).unwrap();
+    assert_eq!(ints, *res);
+    let res = <Vec<u64>>::load_mmap("test_prefault.bin", Flags::POPULATE).unwrap();
+    assert_eq!(ints, *res);
+
+    // Warm-up after mapping
+    let res = <Vec<u64>>::mmap("test_prefault.bin", Flags::empty()).unwrap();
+    res.prefault();
+    assert_eq!(ints, *res);
+    let res = <Vec<u64>>::load_mem("test_prefault.bin").unwrap();
+    res.prefault();
+    assert_eq!(ints, *res);
+    // No backend, no-op
+    MemCase::encase(ints).prefault();
+
+    std::fs::remove_file("test_prefault.bin").unwrap();
+}
+
+#[test]
+fn test_release_fields() {
+    let data = Data {
+        a: (0..1_000_000_u64).collect::<Vec<_>>(),
+        b: (0..1_000_000).collect(),
+    };
+    let mut file = std::fs::File::create("test_release.bin").unwrap();
+    let schema = data.serialize_with_schema(&mut file).unwrap();
+    drop(file);
+
+    let ranges = schema.ranges("ROOT.a").collect::<Vec<_>>();
+    assert_eq!(ranges.len(), 1);
+    assert!(ranges[0].len() >= 8_000_000);
+    assert_eq!(schema.ranges("ROOT.c").count(), 0);
+
+    let res = <Data<Vec<u64>>>::mmap("test_release.bin", Flags::POPULATE).unwrap();
+    #[cfg(target_os = "linux")]
+    assert!(res.release_fields(&schema, &["ROOT.a"]));
+    #[cfg(not(target_os = "linux"))]
+    assert!(!res.release_fields(&schema, &["ROOT.a"]));
+    // Nothing to release
+    assert!(!res.release_fields(&schema, &["ROOT.c"]));
+    // The content is preserved
+    assert_eq!(res.a, data.a);
+    assert_eq!(res.b, data.b);
+
+    let res = <Data<Vec<u64>>>::load_mmap("test_release.bin", Flags::empty()).unwrap();
+    res.release_fields(&schema, &["ROOT.a", "ROOT.b"]);
+    assert_eq!(res.a, data.a);
+    assert_eq!(res.b, data.b);
+
+    // Heap memory is not released
+    let res = <Data<Vec<u64>>>::load_mem("test_release.bin").unwrap();
+    assert!(!res.release_fields(&schema, &["ROOT.a"]));
+
+    std::fs::remove_file("test_release.bin").unwrap();
+}
+
+#[test]
+fn test_mem_case_map() {
+    let data = Data {
+        a: vec![1_u64, 2, 3],
+        b: vec![4, 5],
+    };
+    let mut cursor = epserde::new_aligned_cursor();
+    data.serialize(&mut cursor).unwrap();
+    let bytes = cursor.into_inner();
+    let len = bytes.len();
+
+    let case = <Data<Vec<u64>>>::deserialize_eps_owned(bytes.clone()).unwrap();
+    assert!(matches!(case.backend(), deser::MemBackend::Memory(_)));
+    assert_eq!(case.backend().size(), len);
+    assert!(format!("{:?}", case).starts_with("MemCase(Data { a: [1, 2, 3]"));
+
+    // The mapped structure still refers to the memory of the backend
+    // SAFETY: the closures store the structure only in their results, and
+    // the errors do not refer to the memory of the backend
+    let sums = unsafe { case.map(|data| (data.a, data.b.iter().sum::<i32>())) };
+    assert_eq!(sums.0, [1, 2, 3]);
+    assert_eq!(sums.1, 9);
+    let first = unsafe { sums.try_map(|(a, _)| a.first().copied().ok_or("empty")) };
+    assert_eq!(*first.unwrap(), 1);
+
+    let case = MemCase::encase(data);
+    let err = unsafe { case.try_map(|data| data.a.get(3).copied().ok_or("short")) };
+    assert_eq!(err.err(), Some("short"));
+
+    let case = <Data<Vec<u64>>>::deserialize_eps_owned(bytes).unwrap();
+    let backend = case.into_backend();
+    assert_eq!(backend.size(), len);
+    assert_eq!(
+        format!("{:?}", backend),
+        format!("Memory {{ size: {} }}", len)
+    );
+    assert_eq!(format!("{:?}", deser::MemBackend::None), "None");
+}
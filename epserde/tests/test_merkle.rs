@@ -0,0 +1,242 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ * SPDX-FileCopyrightText: 2023 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+#![cfg(test)]
+
+use epserde::merkle::*;
+use epserde::prelude::*;
+use epserde::ser::WriteNoStd;
+
+type Data = (Vec<u64>, Vec<String>, Vec<u32>);
+
+fn data() -> Data {
+    (
+        (0..1000).collect(),
+        (0..100).map(|i| i.to_string()).collect(),
+        (0..500).collect(),
+    )
+}
+
+fn serialize(chunk_size: usize) -> Vec<u8> {
+    let mut cursor = epserde::new_aligned_cursor();
+    let len = data()
+        .serialize_with_chunk_checksums(&mut cursor, chunk_size)
+        .unwrap();
+    let buf = cursor.into_inner();
+    assert_eq!(len, buf.len());
+    buf
+}
+
+#[test]
+fn test_merkle() -> anyhow::Result<()> {
+    let mut plain = epserde::new_aligned_cursor();
+    let plain_len = data().serialize(&mut plain)?;
+
+    for chunk_size in [1, 7, 64, 1000, plain_len, 1 << 20] {
+        let buf = serialize(chunk_size);
+        // The data differs from plain data only for a flag in the header
+        assert_eq!(buf[12], plain.get_ref()[12] | epserde::CHUNK_CHECKSUMS_FLAG);
+        assert_eq!(&buf[13..plain_len], &plain.get_ref()[13..plain_len]);
+        let eps = <Data>::deserialize_eps(&buf)?;
+        assert_eq!(eps.2, data().2);
+        assert_eq!(<Data>::deserialize_full(&mut buf.as_slice())?, data());
+
+        let mut verifier = ChunkVerifier::new(&buf)?.unwrap();
+        assert_eq!(verifier.chunk_size(), chunk_size);
+        assert_eq!(verifier.num_chunks(), plain_len.div_ceil(chunk_size));
+        assert_eq!(verifier.data().len(), plain_len);
+        assert!(!verifier.is_verified(0));
+        verifier.verify_all()?;
+        assert!((0..verifier.num_chunks()).all(|chunk| verifier.is_verified(chunk)));
+    }
+
+    // The root depends on the chunk size
+    let root = ChunkVerifier::new(&serialize(64))?.unwrap().root();
+    assert_eq!(root, ChunkVerifier::new(&serialize(64))?.unwrap().root());
+    assert_ne!(root, ChunkVerifier::new(&serialize(65))?.unwrap().root());
+    Ok(())
+}
+
+#[test]
+fn test_merkle_fields() -> anyhow::Result<()> {
+    let buf = serialize(64);
+    let schema = data().serialize_with_schema(&mut std::io::sink())?;
+    let mut verifier = ChunkVerifier::new(&buf)?.unwrap();
+
+    verifier.verify_fields(&schema, &["ROOT.2"])?;
+    let range = schema.ranges("ROOT.2").next().unwrap();
+    for chunk in 0..verifier.num_chunks() {
+        let chunk_range = chunk * 64..(chunk + 1) * 64;
+        let overlaps = chunk_range.start < range.end && range.start < chunk_range.end;
+        assert_eq!(verifier.is_verified(chunk), overlaps);
+    }
+
+    assert!(verifier.verify_range(0..buf.len()).is_err());
+    verifier.verify_range(0..0)?;
+    assert!(!verifier.is_verified(0));
+    Ok(())
+}
+
+#[test]
+fn test_merkle_corrupted() -> anyhow::Result<()> {
+    let buf = serialize(64);
+    let verifier = ChunkVerifier::new(&buf)?.unwrap();
+    let num_chunks = verifier.num_chunks();
+    let data_len = verifier.data().len();
+
+    // A corrupted chunk is detected only when verified
+    let mut corrupted = buf.clone();
+    corrupted[64 * 5 + 3] ^= 1;
+    let mut verifier = ChunkVerifier::new(&corrupted)?.unwrap();
+    verifier.verify_range(0..64 * 5)?;
+    verifier.verify_range(64 * 6..data_len)?;
+    assert!(matches!(
+        verifier.verify_range(64 * 5..64 * 5 + 1),
+        Err(deser::Error::ChunkChecksumMismatch { chunk: 5, .. })
+    ));
+    assert!(!verifier.is_verified(5));
+    assert!(verifier.verify_all().is_err());
+
+    // So is a corrupted leaf
+    let mut corrupted = buf.clone();
+    corrupted[data_len + 8 * 2] ^= 1;
+    let mut verifier = ChunkVerifier::new(&corrupted)?.unwrap();
+    verifier.verify_range(0..64)?;
+    assert!(matches!(
+        verifier.verify_range(64 * 2..64 * 3),
+        Err(deser::Error::ChunkChecksumMismatch { chunk: 2, .. })
+    ));
+
+    // A corrupted internal node (the parent of chunks 6 and 7) is detected
+    // by all chunks below it and below its sibling
+    let mut corrupted = buf.clone();
+    corrupted[data_len + 8 * (num_chunks + 3)] ^= 1;
+    let mut verifier = ChunkVerifier::new(&corrupted)?.unwrap();
+    verifier.verify_range(0..64 * 4)?;
+    verifier.verify_range(64 * 8..data_len)?;
+    for chunk in 4..8 {
+        assert!(matches!(
+            verifier.verify_range(64 * chunk..64 * chunk + 1),
+            Err(deser::Error::ChunkChecksumMismatch { chunk: c, .. }) if c == chunk
+        ));
+    }
+
+    // So is a corrupted root
+    let mut corrupted = buf.clone();
+    corrupted[buf.len() - 25] ^= 1;
+    let mut verifier = ChunkVerifier::new(&corrupted)?.unwrap();
+    assert!(verifier.verify_range(0..1).is_err());
+    Ok(())
+}
+
+#[test]
+fn test_merkle_footer() -> anyhow::Result<()> {
+    // No footer
+    let mut cursor = epserde::new_aligned_cursor();
+    data().serialize(&mut cursor)?;
+    assert!(ChunkVerifier::new(&cursor.into_inner())?.is_none());
+    assert!(ChunkVerifier::new(&[])?.is_none());
+
+    // Malformed trailers
+    let buf = serialize(64);
+    let trailer = buf.len() - 24;
+    let mut malformed = buf.clone();
+    malformed[trailer..trailer + 8].copy_from_slice(&0_u64.to_ne_bytes());
+    assert!(matches!(
+        ChunkVerifier::new(&malformed),
+        Err(deser::Error::ReadError)
+    ));
+    let mut malformed = buf.clone();
+    malformed[trailer + 8..trailer + 16].copy_from_slice(&u64::MAX.to_ne_bytes());
+    assert!(ChunkVerifier::new(&malformed).is_err());
+    assert!(ChunkVerifier::new(&buf[1..]).is_err());
+
+    // Empty data
+    let mut buf = Vec::new();
+    let mut writer = ChunkHashWriter::new(&mut buf, 16)?;
+    writer.flush()?;
+    assert_eq!(writer.finish()?, 8 + 24);
+    let mut verifier = ChunkVerifier::new(&buf)?.unwrap();
+    assert_eq!(verifier.num_chunks(), 1);
+    verifier.verify_all()?;
+    assert!(verifier.is_verified(0));
+    Ok(())
+}
+
+#[test]
+fn test_merkle_header() -> anyhow::Result<()> {
+    let buf = serialize(64);
+    let data_len = ChunkVerifier::new(&buf)?.unwrap().data().len();
+    assert!(epserde::inspect::read_header_from(&mut buf.as_slice())?.chunk_checksums);
+    assert!(matches!(
+        data().serialize_with_chunk_checksums(&mut Vec::new(), 0),
+        Err(ser::Error::ZeroChunkSize)
+    ));
+    assert!(matches!(
+        ChunkHashWriter::new(&mut Vec::new(), 0),
+        Err(ser::Error::ZeroChunkSize)
+    ));
+
+    // A missing footer is detected thanks to the flag in the header
+    let truncated = &buf[..data_len];
+    assert!(matches!(
+        ChunkVerifier::new(truncated),
+        Err(deser::Error::MissingChunkChecksums)
+    ));
+    assert!(matches!(
+        <Data>::deserialize_eps(truncated),
+        Err(deser::Error::MissingChunkChecksums)
+    ));
+    assert!(<Data>::deserialize_eps_unverified(truncated).is_ok());
+    assert_eq!(<Data>::deserialize_full(&mut &*truncated)?, data());
+
+    // ε-copy deserialization verifies the chunk containing the header...
+    let mut corrupted = buf.clone();
+    // A byte of the type name, which is not checked
+    corrupted[40] ^= 1;
+    assert!(matches!(
+        <Data>::deserialize_eps(&corrupted),
+        Err(deser::Error::ChunkChecksumMismatch { chunk: 0, .. })
+    ));
+    // ...while check_data verifies all chunks
+    let mut corrupted = buf.clone();
+    corrupted[data_len - 1] ^= 1;
+    assert!(<Data>::deserialize_eps(&corrupted).is_ok());
+    assert!(matches!(
+        deser::check_data::<Data>(&corrupted),
+        Err(deser::Error::ChunkChecksumMismatch { .. })
+    ));
+    deser::check_data::<Data>(&buf)?;
+    Ok(())
+}
+
+#[test]
+fn test_merkle_file() -> anyhow::Result<()> {
+    let tmp_file = std::env::temp_dir().join("test_merkle.eps");
+    data().store_with_chunk_checksums(&tmp_file, DEFAULT_CHUNK_SIZE)?;
+    let case = <Data>::mmap(&tmp_file, Flags::empty())?;
+    assert_eq!(case.1[42], "42");
+    let bytes = std::fs::read(&tmp_file)?;
+    let mut verifier = ChunkVerifier::new(&bytes)?.unwrap();
+    assert_eq!(verifier.num_chunks(), 1);
+    verifier.verify_all()?;
+
+    // Verification of the fields of a loaded structure
+    let schema = data().serialize_with_schema(&mut std::io::sink())?;
+    let case = <Data>::load_mem(&tmp_file)?;
+    let mut verifier = case.chunk_verifier()?.unwrap();
+    verifier.verify_fields(&schema, &["ROOT.1"])?;
+    assert!(verifier.is_verified(0));
+    let case = <Data>::load_mmap(&tmp_file, Flags::empty())?;
+    case.chunk_verifier()?.unwrap().verify_all()?;
+    assert!(matches!(
+        MemCase::encase(data()).chunk_verifier(),
+        Err(deser::Error::NoBackend)
+    ));
+    std::fs::remove_file(&tmp_file)?;
+    Ok(())
+}
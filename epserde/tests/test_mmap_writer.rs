@@ -0,0 +1,79 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ * SPDX-FileCopyrightText: 2023 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+#![cfg(all(test, feature = "mmap-rs"))]
+
+use epserde::prelude::*;
+use epserde::ser::MmapWriter;
+
+#[derive(Epserde, Debug, PartialEq)]
+struct Data {
+    name: String,
+    values: Vec<u64>,
+    labels: Vec<String>,
+}
+
+fn data() -> Data {
+    Data {
+        name: "data".to_string(),
+        values: (0..10_000).collect(),
+        labels: (0..100).map(|i| i.to_string()).collect(),
+    }
+}
+
+#[test]
+fn test_mmap_writer() -> anyhow::Result<()> {
+    let path = std::env::temp_dir().join("test_mmap_writer.eps");
+    let data = data();
+    let mut cursor = epserde::new_aligned_cursor();
+    data.serialize(&mut cursor)?;
+    let expected = cursor.into_inner();
+    assert_eq!(data.serialized_size(), expected.len());
+
+    // A larger capacity is truncated
+    let mut writer = MmapWriter::create(&path, expected.len() + 1000)?;
+    assert_eq!(writer.capacity(), expected.len() + 1000);
+    data.serialize(&mut writer)?;
+    assert!(writer.fits());
+    assert_eq!(writer.required_len(), expected.len());
+    let mmap = writer.finish()?;
+    // The mapping can be ε-deserialized immediately
+    let eps = Data::deserialize_eps(&mmap)?;
+    assert_eq!(eps.values, data.values);
+    assert_eq!(eps.name, data.name);
+    drop(mmap);
+    assert_eq!(std::fs::read(&path)?, expected);
+
+    data.store_mmap(&path)?;
+    assert_eq!(std::fs::read(&path)?, expected);
+    assert_eq!(Data::load_full(&path)?, data);
+    let mmap = Data::mmap(&path, Flags::empty())?;
+    assert_eq!(mmap.values, data.values);
+    drop(mmap);
+
+    std::fs::remove_file(&path)?;
+    Ok(())
+}
+
+#[test]
+fn test_too_small() -> anyhow::Result<()> {
+    let path = std::env::temp_dir().join("test_mmap_writer_small.eps");
+    let data = data();
+    let mut writer = MmapWriter::create(&path, 100)?;
+    data.serialize(&mut writer)?;
+    assert!(!writer.fits());
+    match writer.finish() {
+        Err(ser::Error::BufferTooSmall { required }) => {
+            assert_eq!(required, data.serialized_size())
+        }
+        other => panic!("Unexpected result {:?}", other.map(|_| ())),
+    }
+
+    assert!(MmapWriter::create(&path, 0).is_err());
+    std::fs::remove_file(&path)?;
+    Ok(())
+}
@@ -0,0 +1,130 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ * SPDX-FileCopyrightText: 2023 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+#![cfg(all(test, feature = "ndarray", feature = "nalgebra"))]
+
+use epserde::prelude::*;
+use nalgebra::{Matrix2x3, Vector3};
+use ndarray::{array, Array1, Array2, ArrayView1, ArrayView2};
+
+fn serialize<T: Serialize>(value: &T) -> Vec<u8> {
+    let mut cursor = epserde::new_aligned_cursor();
+    value.serialize(&mut cursor).unwrap();
+    cursor.into_inner()
+}
+
+#[test]
+fn test_array1() {
+    let a: Array1<u64> = (0..100).collect();
+    let buf = serialize(&a);
+
+    let full = Array1::<u64>::deserialize_full(&mut buf.as_slice()).unwrap();
+    assert_eq!(full, a);
+
+    let view: ArrayView1<u64> = Array1::<u64>::deserialize_eps(&buf).unwrap();
+    assert_eq!(view, a);
+    // The view is backed by the serialized data
+    assert!(buf.as_ptr_range().contains(&(view.as_ptr() as *const u8)));
+}
+
+#[test]
+fn test_array2() {
+    let a = array![[1.0_f64, 2.0, 3.0], [4.0, 5.0, 6.0]];
+    let buf = serialize(&a);
+
+    let full = Array2::<f64>::deserialize_full(&mut buf.as_slice()).unwrap();
+    assert_eq!(full, a);
+
+    let view: ArrayView2<f64> = Array2::<f64>::deserialize_eps(&buf).unwrap();
+    assert_eq!(view.dim(), (2, 3));
+    assert_eq!(view, a);
+
+    let empty = Array2::<f64>::zeros((0, 5));
+    let buf = serialize(&empty);
+    let view = Array2::<f64>::deserialize_eps(&buf).unwrap();
+    assert_eq!(view.dim(), (0, 5));
+}
+
+#[test]
+fn test_non_standard_layout() {
+    let a = array![[1_u32, 2, 3], [4, 5, 6]];
+    let t = a.t().as_standard_layout().into_owned();
+    // Reversed axes give a column-major array
+    let f = a.clone().reversed_axes();
+    assert!(f.as_slice().is_none());
+
+    let buf = serialize(&f);
+    let view = Array2::<u32>::deserialize_eps(&buf).unwrap();
+    assert_eq!(view, t);
+    assert_eq!(
+        Array2::<u32>::deserialize_full(&mut buf.as_slice()).unwrap(),
+        t
+    );
+}
+
+#[test]
+fn test_shape_mismatch() {
+    let mut bad = serialize(&Array2::<u32>::zeros((1, 5)));
+    // Overwrite the number of rows
+    let shape_pos = bad
+        .windows(16)
+        .position(|w| w == [1_usize.to_ne_bytes(), 5_usize.to_ne_bytes()].concat())
+        .unwrap();
+    bad[shape_pos..shape_pos + 8].copy_from_slice(&2_usize.to_ne_bytes());
+
+    assert!(matches!(
+        Array2::<u32>::deserialize_eps(&bad).unwrap_err(),
+        deser::Error::InvalidValue("Array2")
+    ));
+    assert!(matches!(
+        Array2::<u32>::deserialize_full(&mut bad.as_slice()),
+        Err(deser::Error::InvalidValue("Array2"))
+    ));
+}
+
+#[derive(Epserde, Debug, PartialEq, Clone, Copy)]
+#[repr(C)]
+#[zero_copy]
+struct Pose {
+    rotation: Matrix2x3<f32>,
+    translation: Vector3<f32>,
+}
+
+#[test]
+fn test_nalgebra() {
+    let m = Matrix2x3::new(1.0_f32, 2.0, 3.0, 4.0, 5.0, 6.0);
+    let buf = serialize(&m);
+    assert_eq!(
+        Matrix2x3::<f32>::deserialize_full(&mut buf.as_slice()).unwrap(),
+        m
+    );
+    assert_eq!(*Matrix2x3::<f32>::deserialize_eps(&buf).unwrap(), m);
+
+    let poses = vec![
+        Pose {
+            rotation: m,
+            translation: Vector3::new(7.0, 8.0, 9.0),
+        };
+        10
+    ];
+    let buf = serialize(&poses);
+    let view: &[Pose] = <Vec<Pose>>::deserialize_eps(&buf).unwrap();
+    assert_eq!(view, poses.as_slice());
+}
+
+#[cfg(feature = "mmap-rs")]
+#[test]
+fn test_mmap() {
+    let a = Array2::from_shape_fn((100, 30), |(i, j)| (i * 30 + j) as u64);
+    let path = std::env::temp_dir().join("test_ndarray.eps");
+    epserde::save(&a, &path).unwrap();
+    let mapped = epserde::map::<Array2<u64>>(&path, Flags::empty()).unwrap();
+    assert_eq!(*mapped, a);
+    assert_eq!(mapped[[99, 29]], 2999);
+    drop(mapped);
+    std::fs::remove_file(&path).unwrap();
+}
@@ -0,0 +1,91 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+#![cfg(test)]
+
+use epserde::prelude::*;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, SocketAddrV4, SocketAddrV6};
+
+macro_rules! round_trip {
+    ($value:expr, $ty:ty) => {{
+        let value: $ty = $value;
+        let mut cursor = epserde::new_aligned_cursor();
+        value.serialize(&mut cursor).unwrap();
+        let buf = cursor.into_inner();
+        let full = <$ty>::deserialize_full(&mut std::io::Cursor::new(&buf)).unwrap();
+        assert_eq!(full, value);
+        let eps = <$ty>::deserialize_eps(&buf).unwrap();
+        assert_eq!(eps, value);
+    }};
+}
+
+#[test]
+fn test_ip() {
+    round_trip!(Ipv4Addr::LOCALHOST, Ipv4Addr);
+    round_trip!(Ipv4Addr::new(192, 168, 1, 254), Ipv4Addr);
+    round_trip!(Ipv6Addr::UNSPECIFIED, Ipv6Addr);
+    round_trip!(Ipv6Addr::new(0x2001, 0xdb8, 0, 0, 0, 0, 0, 1), Ipv6Addr);
+    round_trip!(IpAddr::V4(Ipv4Addr::BROADCAST), IpAddr);
+    round_trip!(IpAddr::V6(Ipv6Addr::LOCALHOST), IpAddr);
+}
+
+#[test]
+fn test_socket_addr() {
+    round_trip!(SocketAddrV4::new(Ipv4Addr::LOCALHOST, 8080), SocketAddrV4);
+    round_trip!(
+        SocketAddrV6::new(Ipv6Addr::LOCALHOST, 443, 0x12345, 7),
+        SocketAddrV6
+    );
+    round_trip!("10.0.0.1:53".parse().unwrap(), SocketAddr);
+    round_trip!("[fe80::1]:22".parse().unwrap(), SocketAddr);
+}
+
+#[derive(Epserde, Debug, PartialEq)]
+struct Flow {
+    src: SocketAddr,
+    dst: SocketAddr,
+    hops: Vec<IpAddr>,
+    bytes: u64,
+}
+
+#[test]
+fn test_struct() {
+    let flow = Flow {
+        src: "192.168.0.2:50000".parse().unwrap(),
+        dst: "[2001:db8::1]:443".parse().unwrap(),
+        hops: vec![
+            IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1)),
+            IpAddr::V6(Ipv6Addr::LOCALHOST),
+        ],
+        bytes: 1 << 20,
+    };
+    let mut cursor = epserde::new_aligned_cursor();
+    flow.serialize(&mut cursor).unwrap();
+    let buf = cursor.into_inner();
+    let full = Flow::deserialize_full(&mut std::io::Cursor::new(&buf)).unwrap();
+    assert_eq!(full, flow);
+    let eps = Flow::deserialize_eps(&buf).unwrap();
+    assert_eq!(eps.src, flow.src);
+    assert_eq!(eps.dst, flow.dst);
+    assert_eq!(eps.hops, flow.hops);
+    assert_eq!(eps.bytes, flow.bytes);
+}
+
+#[test]
+fn test_invalid_tag() {
+    let mut cursor = epserde::new_aligned_cursor();
+    IpAddr::V4(Ipv4Addr::LOCALHOST)
+        .serialize(&mut cursor)
+        .unwrap();
+    let mut buf = cursor.into_inner();
+    // The tag precedes the four octets
+    let len = buf.len();
+    buf[len - 5] = 5;
+    assert!(matches!(
+        IpAddr::deserialize_full(&mut std::io::Cursor::new(&buf)),
+        Err(deser::Error::InvalidTag(5))
+    ));
+}
@@ -0,0 +1,63 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+#![cfg(test)]
+
+use epserde::deser::*;
+use epserde::prelude::*;
+
+#[derive(Epserde, Debug, PartialEq, Eq, Clone)]
+struct Data<A> {
+    a: A,
+    b: Vec<u64>,
+    c: String,
+}
+
+#[test]
+fn test_deserialize_full_at() {
+    let data = Data {
+        a: vec![0x89_u16; 7],
+        b: vec![0xbadf00d; 3],
+        c: "epserde".to_string(),
+    };
+
+    let mut cursor = epserde::new_aligned_cursor();
+    data.serialize(&mut cursor).unwrap();
+    let payload = cursor.into_inner();
+
+    // Embed the payload after a custom header of odd length
+    for header_len in [0, 1, 3, 13, 4097] {
+        let mut container = vec![0xff_u8; header_len];
+        container.extend_from_slice(&payload);
+
+        let res = Data::<Vec<u16>>::deserialize_full_at(
+            &mut std::io::Cursor::new(&container),
+            header_len,
+        )
+        .unwrap();
+        assert_eq!(data, res);
+    }
+}
+
+#[test]
+fn test_reader_with_initial_offset() {
+    let mut cursor = epserde::new_aligned_cursor();
+    vec![1_u64, 2, 3].serialize(&mut cursor).unwrap();
+    let payload = cursor.into_inner();
+
+    let mut container = vec![0_u8; 5];
+    container.extend_from_slice(&payload);
+    let mut reader = std::io::Cursor::new(&container);
+    reader.set_position(5);
+
+    let mut backend = ReaderWithPos::with_initial_offset(&mut reader, 5);
+    assert_eq!(backend.pos(), 5);
+    assert_eq!(backend.initial_offset(), 5);
+    check_header::<Vec<u64>>(&mut backend).unwrap();
+    let res = Vec::<u64>::_deserialize_full_inner(&mut backend).unwrap();
+    assert_eq!(res, vec![1, 2, 3]);
+    assert_eq!(backend.pos(), container.len());
+}
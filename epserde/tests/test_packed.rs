@@ -0,0 +1,156 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ * SPDX-FileCopyrightText: 2023 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+#![cfg(test)]
+
+use epserde::prelude::*;
+
+/// A telemetry record, packed in 13 bytes.
+#[derive(Epserde, Debug, Clone, Copy, PartialEq)]
+#[repr(C, packed)]
+#[zero_copy]
+struct Record {
+    tag: u8,
+    timestamp: u64,
+    value: f32,
+}
+
+#[derive(Epserde, Debug, Clone, Copy, PartialEq)]
+#[repr(C, packed(2))]
+#[zero_copy]
+struct Pair(u8, u32);
+
+#[derive(Epserde, Debug, Clone, Copy, PartialEq)]
+#[repr(C)]
+#[zero_copy]
+struct Point {
+    x: u32,
+    y: u16,
+}
+
+/// A named struct containing a tuple struct.
+#[derive(Epserde, Debug, Clone, Copy, PartialEq)]
+#[repr(C)]
+#[zero_copy]
+struct Named {
+    id: u16,
+    pair: Pair,
+    point: Point,
+}
+
+/// A tuple struct containing named structs.
+#[derive(Epserde, Debug, Clone, Copy, PartialEq)]
+#[repr(C)]
+#[zero_copy]
+struct Tuple(Point, u8, Named);
+
+mod unpacked {
+    use epserde::prelude::*;
+
+    #[derive(Epserde, Debug, Clone, Copy, PartialEq)]
+    #[repr(C)]
+    #[zero_copy]
+    pub struct Record {
+        pub tag: u8,
+        pub timestamp: u64,
+        pub value: f32,
+    }
+}
+
+fn serialize(value: &impl Serialize) -> Vec<u8> {
+    let mut cursor = epserde::new_aligned_cursor();
+    value.serialize(&mut cursor).unwrap();
+    cursor.into_inner()
+}
+
+fn record(i: u64) -> Record {
+    Record {
+        tag: i as u8,
+        timestamp: 1_000_000 + i,
+        value: i as f32 / 2.0,
+    }
+}
+
+#[test]
+fn test_packed() {
+    assert_eq!(core::mem::size_of::<Record>(), 13);
+    assert_eq!(core::mem::size_of::<Pair>(), 6);
+
+    let data = record(7);
+    let buf = serialize(&data);
+    assert_eq!(Record::deserialize_full(&mut &buf[..]).unwrap(), data);
+    let eps = Record::deserialize_eps(&buf).unwrap();
+    assert_eq!(*eps, data);
+    // Fields of packed structs are read by value
+    let timestamp = eps.timestamp;
+    assert_eq!(timestamp, 1_000_007);
+    assert_eq!({ eps.value }, 3.5);
+
+    let data = Pair(1, 2);
+    let buf = serialize(&data);
+    assert_eq!(Pair::deserialize_full(&mut &buf[..]).unwrap(), data);
+    assert_eq!(*Pair::deserialize_eps(&buf).unwrap(), data);
+}
+
+#[test]
+fn test_packed_slice() {
+    let data = (0..100).map(record).collect::<Vec<_>>();
+    let buf = serialize(&data);
+    assert_eq!(
+        Vec::<Record>::deserialize_full(&mut &buf[..]).unwrap(),
+        data
+    );
+    let eps = Vec::<Record>::deserialize_eps(&buf).unwrap();
+    assert_eq!(eps, &data[..]);
+    let timestamps = eps.iter().map(|r| r.timestamp).collect::<Vec<_>>();
+    assert_eq!(
+        timestamps,
+        (0..100).map(|i| 1_000_000 + i).collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn test_mixed() {
+    let named = Named {
+        id: 1,
+        pair: Pair(2, 3),
+        point: Point { x: 4, y: 5 },
+    };
+    let data = Tuple(Point { x: 6, y: 7 }, 8, named);
+
+    let buf = serialize(&named);
+    assert_eq!(Named::deserialize_full(&mut &buf[..]).unwrap(), named);
+    assert_eq!(*Named::deserialize_eps(&buf).unwrap(), named);
+
+    let buf = serialize(&data);
+    assert_eq!(Tuple::deserialize_full(&mut &buf[..]).unwrap(), data);
+    let eps = Tuple::deserialize_eps(&buf).unwrap();
+    assert_eq!(*eps, data);
+    assert_eq!(eps.2.point, Point { x: 4, y: 5 });
+
+    let data = vec![data; 10];
+    let buf = serialize(&data);
+    assert_eq!(Vec::<Tuple>::deserialize_eps(&buf).unwrap(), &data[..]);
+}
+
+#[test]
+fn test_packed_repr_hash() {
+    // Same names and types, but different layout
+    assert_eq!(
+        epserde::type_hash_of::<Record>(),
+        epserde::type_hash_of::<unpacked::Record>()
+    );
+    let buf = serialize(&record(0));
+    assert!(matches!(
+        unpacked::Record::deserialize_eps(&buf),
+        Err(deser::Error::WrongTypeReprHash { .. })
+    ));
+    assert!(matches!(
+        unpacked::Record::deserialize_full(&mut &buf[..]),
+        Err(deser::Error::WrongTypeReprHash { .. })
+    ));
+}
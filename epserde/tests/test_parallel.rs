@@ -0,0 +1,64 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+#![cfg(test)]
+#![cfg(feature = "rayon")]
+
+use epserde::prelude::*;
+use epserde::ser::ParallelFileWriter;
+use std::io::BufWriter;
+
+#[derive(Epserde, Debug, PartialEq)]
+struct Data<A> {
+    values: A,
+    name: String,
+    tail: Vec<u8>,
+}
+
+fn data() -> Data<Vec<u64>> {
+    Data {
+        values: (0..100_000).collect(),
+        name: "data".into(),
+        tail: vec![1, 2, 3],
+    }
+}
+
+#[test]
+fn test_parallel_writer() -> anyhow::Result<()> {
+    let data = data();
+    let mut cursor = epserde::new_aligned_cursor();
+    data.serialize(&mut cursor)?;
+    let expected = cursor.into_inner();
+
+    let tmp_file = std::env::temp_dir().join("test_parallel_writer.eps");
+    let file = std::fs::File::create(&tmp_file)?;
+    // Small thresholds, so that the vector is written in many chunks
+    let writer = ParallelFileWriter::new(file)
+        .with_threshold(1 << 10)
+        .with_chunk_size(1000);
+    let mut buf_writer = BufWriter::with_capacity(64, writer);
+    let len = data.serialize(&mut buf_writer)?;
+    drop(buf_writer);
+    assert_eq!(len, expected.len());
+    assert_eq!(std::fs::read(&tmp_file)?, &expected[..]);
+
+    let full = <Data<Vec<u64>>>::load_full(&tmp_file)?;
+    assert_eq!(full, data);
+    std::fs::remove_file(&tmp_file)?;
+    Ok(())
+}
+
+#[test]
+fn test_store_parallel() -> anyhow::Result<()> {
+    let data = data();
+    let tmp_file = std::env::temp_dir().join("test_store_parallel.eps");
+    data.store_parallel(&tmp_file)?;
+    let eps = <Data<Vec<u64>>>::load_mem(&tmp_file)?;
+    assert_eq!(eps.values, data.values);
+    assert_eq!(eps.name, data.name);
+    std::fs::remove_file(&tmp_file)?;
+    Ok(())
+}
@@ -0,0 +1,81 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ * SPDX-FileCopyrightText: 2023 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+#![cfg(test)]
+
+use epserde::prelude::*;
+
+#[derive(Epserde, Debug, PartialEq)]
+struct Index {
+    names: Vec<String>,
+    offsets: Vec<u64>,
+}
+
+fn index() -> Index {
+    Index {
+        names: vec!["a".to_string(), "b".to_string()],
+        offsets: vec![0, 10, 20],
+    }
+}
+
+#[test]
+fn test_save_load() -> anyhow::Result<()> {
+    let dir = std::env::temp_dir().join("test_persist_save_load");
+    std::fs::create_dir_all(&dir)?;
+    let path = dir.join("index.eps");
+
+    save(&index(), &path)?;
+    let loaded: Index = load(&path)?;
+    assert_eq!(loaded, index());
+    let mapped = map::<Index>(&path, Flags::empty())?;
+    assert_eq!(mapped.names, index().names);
+
+    // Files have a checksum, and no temporary file is left behind
+    let header = epserde::inspect::read_header(&path)?;
+    assert!(header.checksum.is_some());
+    assert_eq!(std::fs::read_dir(&dir)?.count(), 1);
+
+    // Saving replaces the previous content
+    save(&vec![1_u32, 2], &path)?;
+    assert_eq!(load::<Vec<u32>>(&path)?, [1, 2]);
+    assert!(load::<Index>(&path).is_err());
+
+    std::fs::remove_dir_all(&dir)?;
+    Ok(())
+}
+
+#[test]
+fn test_load_corrupted() -> anyhow::Result<()> {
+    let path = std::env::temp_dir().join("test_persist_corrupted.eps");
+    save(&index(), &path)?;
+    let mut bytes = std::fs::read(&path)?;
+    let last = bytes.len() - 1;
+    bytes[last] ^= 1;
+    std::fs::write(&path, bytes)?;
+
+    assert!(matches!(
+        load::<Index>(&path),
+        Err(deser::Error::ChecksumMismatch { .. })
+    ));
+    assert!(matches!(
+        map::<Index>(&path, Flags::empty()),
+        Err(deser::Error::ChecksumMismatch { .. })
+    ));
+    assert!(map::<Index>(&path, Flags::SKIP_CHECKSUM).is_ok());
+    std::fs::remove_file(&path)?;
+    Ok(())
+}
+
+#[test]
+fn test_save_errors() {
+    let dir = std::env::temp_dir().join("test_persist_missing_dir");
+    let _ = std::fs::remove_dir_all(&dir);
+    assert!(save(&index(), dir.join("index.eps")).is_err());
+    assert!(!dir.exists());
+    assert!(save(&index(), "/").is_err());
+    assert!(load::<Index>(dir.join("index.eps")).is_err());
+}
@@ -10,7 +10,7 @@ use epserde::TypeInfo;
 /// This should be a NOOP
 fn test_phantom() {
     // Create a new value to serialize
-    let obj = <PhantomData<usize>>::default();
+    let obj = PhantomData::<usize>;
     let mut buf = new_aligned_cursor();
 
     // Serialize
@@ -0,0 +1,272 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+#![cfg(test)]
+
+use epserde::prelude::*;
+
+#[derive(Epserde, Debug, PartialEq, Eq)]
+struct Data {
+    values: Vec<u32>,
+    name: String,
+    count: usize,
+    delta: isize,
+}
+
+#[derive(Epserde, Debug, PartialEq, Eq, Clone, Copy)]
+#[repr(C)]
+#[zero_copy]
+struct Pair {
+    a: u32,
+    b: u64,
+}
+
+/// Offset of the `USIZE_SIZE` field in the header.
+const USIZE_SIZE_OFFSET: usize = 8 + 2 + 2;
+/// Offset of the representation hash in the header.
+const REPR_HASH_OFFSET: usize = USIZE_SIZE_OFFSET + 1 + 8;
+
+#[test]
+fn test_portable() {
+    let data = Data {
+        values: vec![1, 2, 3],
+        name: "portable".to_string(),
+        count: 1 << 20,
+        delta: -5,
+    };
+
+    let mut cursor = epserde::new_aligned_cursor();
+    let len = data.serialize_portable(&mut cursor).unwrap();
+    let portable = cursor.into_inner();
+    assert_eq!(len, portable.len());
+    assert_eq!(portable[USIZE_SIZE_OFFSET], 8 | epserde::PORTABLE_FLAG);
+
+    let mut cursor = std::io::Cursor::new(&portable);
+    assert_eq!(Data::deserialize_full(&mut cursor).unwrap(), data);
+
+    let eps = Data::deserialize_eps(&portable).unwrap();
+    assert_eq!(eps.values, &[1, 2, 3]);
+    assert_eq!(eps.name, "portable");
+    assert_eq!(eps.count, 1 << 20);
+    assert_eq!(eps.delta, -5);
+
+    let mut cursor = epserde::new_aligned_cursor();
+    data.serialize(&mut cursor).unwrap();
+    let native = cursor.into_inner();
+    assert_eq!(
+        native[USIZE_SIZE_OFFSET] as usize,
+        core::mem::size_of::<usize>()
+    );
+    if core::mem::size_of::<usize>() == 8 {
+        // On 64-bit architectures only the header flag differs
+        assert_eq!(native[..USIZE_SIZE_OFFSET], portable[..USIZE_SIZE_OFFSET]);
+        assert_eq!(
+            native[USIZE_SIZE_OFFSET + 1..],
+            portable[USIZE_SIZE_OFFSET + 1..]
+        );
+    }
+}
+
+#[test]
+fn test_usize_size_mismatch() {
+    let mut cursor = epserde::new_aligned_cursor();
+    vec![1_usize, 2, 3].serialize(&mut cursor).unwrap();
+    let mut buf = cursor.into_inner();
+    // A non-portable header with a different pointer width
+    buf[USIZE_SIZE_OFFSET] = if core::mem::size_of::<usize>() == 8 {
+        4
+    } else {
+        8
+    };
+
+    let mut cursor = std::io::Cursor::new(&buf);
+    let err = <Vec<usize>>::deserialize_full(&mut cursor).unwrap_err();
+    assert!(matches!(err, deser::Error::UsizeSizeMismatch(4 | 8)));
+}
+
+#[test]
+fn test_usize_size_migration() {
+    let data = Data {
+        values: vec![1, 2, 3],
+        name: "native".to_string(),
+        count: 1 << 20,
+        delta: -5,
+    };
+    let mut cursor = epserde::new_aligned_cursor();
+    data.serialize(&mut cursor).unwrap();
+    let native = cursor.into_inner();
+
+    // Rebuild the data as written on an architecture with a different
+    // pointer width: the header is the same up to the hashes, but the length
+    // of the type name is a usize, too
+    let mut reader = &native[..];
+    let mut backend = deser::ReaderWithPos::new(&mut reader);
+    let header = deser::read_header(&mut backend).unwrap();
+    let hashes_end = USIZE_SIZE_OFFSET + 1 + 8 + 8;
+    let mut buf = native[..hashes_end].to_vec();
+    let usize_size = if core::mem::size_of::<usize>() == 8 {
+        4
+    } else {
+        8
+    };
+    buf[USIZE_SIZE_OFFSET] = usize_size as u8;
+    let write_usize = |buf: &mut Vec<u8>, value: i64| {
+        buf.extend_from_slice(&value.to_ne_bytes()[..usize_size]);
+    };
+    write_usize(&mut buf, header.type_name.len() as i64);
+    buf.extend_from_slice(header.type_name.as_bytes());
+    write_usize(&mut buf, 3);
+    for value in &data.values {
+        buf.extend_from_slice(&value.to_ne_bytes());
+    }
+    write_usize(&mut buf, data.name.len() as i64);
+    buf.extend_from_slice(data.name.as_bytes());
+    write_usize(&mut buf, data.count as i64);
+    write_usize(&mut buf, data.delta as i64);
+
+    assert_eq!(Data::deserialize_full(&mut &buf[..]).unwrap(), data);
+    let mut cursor = epserde::new_aligned_cursor();
+    std::io::Write::write_all(&mut cursor, &buf).unwrap();
+    let buf = cursor.into_inner();
+    let eps = Data::deserialize_eps(&buf).unwrap();
+    assert_eq!(eps.values, &[1, 2, 3]);
+    assert_eq!(eps.name, "native");
+    assert_eq!(eps.count, 1 << 20);
+    assert_eq!(eps.delta, -5);
+}
+
+#[test]
+fn test_usize_size_mismatch_eps() {
+    let mut cursor = epserde::new_aligned_cursor();
+    vec![1_usize, 2, 3].serialize(&mut cursor).unwrap();
+    let native = cursor.into_inner();
+
+    // Rebuild the data as written on an architecture with a different
+    // pointer width, as in test_usize_size_migration
+    let mut reader = &native[..];
+    let header = deser::read_header(&mut deser::ReaderWithPos::new(&mut reader)).unwrap();
+    let usize_size = if core::mem::size_of::<usize>() == 8 {
+        4
+    } else {
+        8
+    };
+    let mut buf = native[..USIZE_SIZE_OFFSET + 1 + 8 + 8].to_vec();
+    buf[USIZE_SIZE_OFFSET] = usize_size as u8;
+    buf.extend_from_slice(&header.type_name.len().to_ne_bytes()[..usize_size]);
+    buf.extend_from_slice(header.type_name.as_bytes());
+    buf.extend_from_slice(&3_usize.to_ne_bytes()[..usize_size]);
+    buf.resize(buf.len().next_multiple_of(usize_size), 0);
+    buf.resize(buf.len() + 3 * usize_size, 0);
+    let mut cursor = epserde::new_aligned_cursor();
+    std::io::Write::write_all(&mut cursor, &buf).unwrap();
+    let buf = cursor.into_inner();
+
+    // The representation of usize values depends on the pointer width
+    let err = <Vec<usize>>::deserialize_eps(&buf).unwrap_err();
+    assert!(matches!(err, deser::Error::UsizeSizeMismatch(4 | 8)));
+}
+
+#[test]
+fn test_cross_width_repr_hash() {
+    // The representation hash does not depend on the pointer width
+    assert_eq!(
+        epserde::traits::repr_hash_of::<usize>(),
+        epserde::traits::repr_hash_of::<u64>()
+    );
+
+    let pair = Pair { a: 1, b: 2 };
+    let mut cursor = epserde::new_aligned_cursor();
+    pair.serialize(&mut cursor).unwrap();
+    let native = cursor.into_inner();
+
+    // Rebuild the data as written on an architecture with a different
+    // pointer width, as in test_usize_size_migration
+    let mut reader = &native[..];
+    let header = deser::read_header(&mut deser::ReaderWithPos::new(&mut reader)).unwrap();
+    let usize_size = if core::mem::size_of::<usize>() == 8 {
+        4
+    } else {
+        8
+    };
+    let mut buf = native[..USIZE_SIZE_OFFSET + 1 + 8 + 8].to_vec();
+    buf[USIZE_SIZE_OFFSET] = usize_size as u8;
+    buf.extend_from_slice(&header.type_name.len().to_ne_bytes()[..usize_size]);
+    buf.extend_from_slice(header.type_name.as_bytes());
+    buf.resize(buf.len().next_multiple_of(Pair::max_size_of()), 0);
+    buf.extend_from_slice(&native[native.len() - core::mem::size_of::<Pair>()..]);
+    let mut cursor = epserde::new_aligned_cursor();
+    std::io::Write::write_all(&mut cursor, &buf).unwrap();
+    let mut buf = cursor.into_inner();
+
+    // The layout of Pair is the same on both architectures
+    assert_eq!(*Pair::deserialize_eps(&buf).unwrap(), pair);
+
+    // The layout of Pair is different on the other architecture (e.g., on
+    // i686, where u64 is aligned to four bytes, and Pair is 12 bytes long)
+    buf[REPR_HASH_OFFSET..REPR_HASH_OFFSET + 8]
+        .copy_from_slice(&epserde::traits::repr_hash_of::<[u32; 3]>().to_ne_bytes());
+    let err = Pair::deserialize_eps(&buf).unwrap_err();
+    assert!(matches!(err, deser::Error::WrongTypeReprHash { .. }));
+    let err = Pair::deserialize_full(&mut &buf[..]).unwrap_err();
+    assert!(matches!(err, deser::Error::WrongTypeReprHash { .. }));
+}
+
+/// A backend using the default implementations of
+/// [`ReadWithPos::usize_size`] and [`ReadWithPos::set_usize_size`].
+struct NativeOnly<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl deser::ReadNoStd for NativeOnly<'_> {
+    fn read_exact(&mut self, buf: &mut [u8]) -> deser::Result<()> {
+        let (head, tail) = self
+            .data
+            .split_at_checked(buf.len())
+            .ok_or(deser::Error::ReadError)?;
+        buf.copy_from_slice(head);
+        self.data = tail;
+        self.pos += buf.len();
+        Ok(())
+    }
+}
+
+impl ReadWithPos for NativeOnly<'_> {
+    fn pos(&self) -> usize {
+        self.pos
+    }
+
+    fn align<T: MaxSizeOf>(&mut self) -> deser::Result<()> {
+        let padding = epserde::pad_align_to(self.pos, T::max_size_of());
+        deser::ReadNoStd::read_exact(self, &mut vec![0; padding])
+    }
+}
+
+#[test]
+fn test_default_usize_size() {
+    let mut cursor = epserde::new_aligned_cursor();
+    vec![1_usize, 2, 3].serialize(&mut cursor).unwrap();
+    let mut buf = cursor.into_inner();
+
+    let mut backend = NativeOnly { data: &buf, pos: 0 };
+    deser::check_header::<Vec<usize>>(&mut backend).unwrap();
+    assert_eq!(
+        <Vec<usize>>::_deserialize_full_inner(&mut backend).unwrap(),
+        [1, 2, 3]
+    );
+
+    // Data with a different pointer width cannot be read
+    buf[USIZE_SIZE_OFFSET] = if core::mem::size_of::<usize>() == 8 {
+        4
+    } else {
+        8
+    };
+    let mut backend = NativeOnly { data: &buf, pos: 0 };
+    assert!(matches!(
+        deser::read_header(&mut backend),
+        Err(deser::Error::UsizeSizeMismatch(4 | 8))
+    ));
+}
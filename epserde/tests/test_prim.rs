@@ -6,7 +6,9 @@
 
 #![cfg(test)]
 
+use epserde::deser::DeserializeInner;
 use epserde::prelude::*;
+use std::num::*;
 
 macro_rules! impl_test {
     ($data:expr, $ty:ty) => {{
@@ -59,6 +61,47 @@ test_primitive!(i64, test_i64);
 test_primitive!(i128, test_i128);
 test_primitive!(isize, test_isize);
 
+macro_rules! test_nonzero {
+    ($ty:ty, $test_name:ident) => {
+        #[test]
+        fn $test_name() {
+            impl_test!(<$ty>::MAX, $ty);
+            impl_test!(<$ty>::MIN, $ty);
+            impl_test!(<$ty>::new(7).unwrap(), $ty);
+        }
+    };
+}
+
+test_nonzero!(NonZeroU8, test_nonzero_u8);
+test_nonzero!(NonZeroU32, test_nonzero_u32);
+test_nonzero!(NonZeroU128, test_nonzero_u128);
+test_nonzero!(NonZeroUsize, test_nonzero_usize);
+test_nonzero!(NonZeroI16, test_nonzero_i16);
+test_nonzero!(NonZeroI64, test_nonzero_i64);
+test_nonzero!(NonZeroIsize, test_nonzero_isize);
+
+#[test]
+fn test_nonzero_zero() {
+    let mut v = vec![];
+    0_u32.serialize(&mut std::io::Cursor::new(&mut v)).unwrap();
+    // The type hashes differ, so we check the payload directly
+    let mut cursor = std::io::Cursor::new(&v[v.len() - 4..]);
+    let mut backend = deser::ReaderWithPos::new(&mut cursor);
+    assert!(matches!(
+        NonZeroU32::_deserialize_full_inner(&mut backend),
+        Err(deser::Error::InvalidValue("NonZeroU32"))
+    ));
+
+    let data = vec![NonZeroU64::new(1).unwrap(), NonZeroU64::MAX];
+    let mut v = vec![];
+    data.serialize(&mut std::io::Cursor::new(&mut v)).unwrap();
+    assert_eq!(
+        <Vec<NonZeroU64>>::deserialize_full(&mut std::io::Cursor::new(&v)).unwrap(),
+        data
+    );
+    assert_eq!(<Vec<NonZeroU64>>::deserialize_eps(&v).unwrap(), data);
+}
+
 #[test]
 fn test_unit() {
     impl_test!((), ());
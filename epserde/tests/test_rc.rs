@@ -0,0 +1,72 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+#![cfg(test)]
+
+use epserde::prelude::*;
+use std::rc::Rc;
+use std::sync::Arc;
+
+#[derive(Epserde, Debug, PartialEq, Eq, Clone, Copy)]
+#[repr(C)]
+#[zero_copy]
+struct Point {
+    x: u32,
+    y: u32,
+}
+
+#[derive(Epserde, Debug, PartialEq)]
+struct Data<A, B, C, D> {
+    shared: A,
+    slice: B,
+    point: C,
+    names: D,
+    rc: Rc<Vec<u8>>,
+}
+
+type Type = Data<Arc<Vec<u64>>, Arc<[u32]>, Arc<Point>, Rc<[String]>>;
+
+#[test]
+fn test_rc() {
+    let values = Arc::new((0..100).collect::<Vec<u64>>());
+    let data: Type = Data {
+        shared: values.clone(),
+        slice: Arc::from(vec![1, 2, 3]),
+        point: Arc::new(Point { x: 1, y: 2 }),
+        names: Rc::from(vec!["a".to_string(), "bc".to_string()]),
+        rc: Rc::new(vec![4, 5]),
+    };
+    let mut cursor = epserde::new_aligned_cursor();
+    data.serialize(&mut cursor).unwrap();
+    let bytes = cursor.into_inner();
+
+    let full = Type::deserialize_full(&mut std::io::Cursor::new(&bytes)).unwrap();
+    assert_eq!(full, data);
+
+    let eps = Type::deserialize_eps(&bytes).unwrap();
+    let shared: Arc<&[u64]> = eps.shared;
+    assert_eq!(*shared, values.as_slice());
+    let slice: &[u32] = eps.slice;
+    assert_eq!(slice, [1, 2, 3]);
+    let point: Arc<&Point> = eps.point;
+    assert_eq!(**point, Point { x: 1, y: 2 });
+    let names: Rc<[&str]> = eps.names;
+    assert_eq!(*names, ["a", "bc"]);
+    assert_eq!(*eps.rc, vec![4, 5]);
+}
+
+#[test]
+fn test_rc_type_hash() {
+    let mut cursor = epserde::new_aligned_cursor();
+    Arc::new(vec![1_u32, 2, 3]).serialize(&mut cursor).unwrap();
+    let bytes = cursor.into_inner();
+    assert!(<Vec<u32>>::deserialize_eps(&bytes).is_err());
+    assert!(<Rc<Vec<u32>>>::deserialize_eps(&bytes).is_err());
+    assert_eq!(
+        **<Arc<Vec<u32>>>::deserialize_eps(&bytes).unwrap(),
+        [1, 2, 3]
+    );
+}
@@ -0,0 +1,236 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ * SPDX-FileCopyrightText: 2023 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+#![cfg(test)]
+
+use epserde::prelude::*;
+use epserde::ser::{Schema, SchemaRow};
+
+#[derive(Epserde, Debug, PartialEq)]
+struct Data<A> {
+    a: A,
+    b: String,
+}
+
+#[test]
+fn test_to_json() {
+    let data = Data {
+        a: vec![1_u32, 2, 3],
+        b: "hello".to_string(),
+    };
+    let mut cursor = epserde::new_aligned_cursor();
+    let schema = data.serialize_with_schema(&mut cursor).unwrap();
+    let json = schema.to_json();
+
+    assert!(json.starts_with(&format!(
+        "{{\n  \"version\": [{}, {}],\n  \"rows\": [\n",
+        epserde::VERSION.0,
+        epserde::VERSION.1
+    )));
+    assert!(json.ends_with("\n  ]\n}\n"));
+    // One line per row
+    assert_eq!(json.lines().count(), schema.0.len() + 5);
+    for row in &schema.0 {
+        assert!(json.contains(&format!(
+            "{{\"field\": \"{}\", \"offset\": {}, \"align\": {}, \"size\": {}, \"ty\": \"{}\"}}",
+            row.field, row.offset, row.align, row.size, row.ty
+        )));
+    }
+    assert!(json.contains("\"field\": \"ROOT.a.zero\""));
+
+    assert_eq!(
+        Schema::default().to_json(),
+        format!(
+            "{{\n  \"version\": [{}, {}],\n  \"rows\": []\n}}\n",
+            epserde::VERSION.0,
+            epserde::VERSION.1
+        )
+    );
+}
+
+#[test]
+fn test_to_json_escape() {
+    let schema = Schema(vec![SchemaRow {
+        field: "a\"b\\c\nd\u{1}".to_string(),
+        ty: "&'a str".to_string(),
+        offset: 1,
+        size: 2,
+        align: 3,
+    }]);
+    assert!(schema.to_json().contains(
+        "{\"field\": \"a\\\"b\\\\c\\nd\\u0001\", \"offset\": 1, \"align\": 3, \"size\": 2, \"ty\": \"&'a str\"}"
+    ));
+}
+
+#[test]
+fn test_store_with_schema() {
+    let data = Data {
+        a: vec![1_u32, 2, 3],
+        b: "hello".to_string(),
+    };
+    data.store_with_schema("test_schema.bin").unwrap();
+
+    let res = <Data<Vec<u32>>>::load_full("test_schema.bin").unwrap();
+    assert_eq!(res, data);
+
+    let mut cursor = epserde::new_aligned_cursor();
+    let schema = data.serialize_with_schema(&mut cursor).unwrap();
+    assert_eq!(
+        std::fs::read_to_string("test_schema.bin.schema.json").unwrap(),
+        schema.to_json()
+    );
+
+    std::fs::remove_file("test_schema.bin").unwrap();
+    std::fs::remove_file("test_schema.bin.schema.json").unwrap();
+}
+
+fn assert_same_schema(a: &Schema, b: &Schema) {
+    assert_eq!(a.0.len(), b.0.len());
+    for (a, b) in a.0.iter().zip(&b.0) {
+        assert_eq!(
+            (&a.field, &a.ty, a.offset, a.size, a.align),
+            (&b.field, &b.ty, b.offset, b.size, b.align)
+        );
+    }
+}
+
+#[test]
+fn test_from_json() {
+    let data = Data {
+        a: vec![1_u32, 2, 3],
+        b: "hello".to_string(),
+    };
+    let mut cursor = epserde::new_aligned_cursor();
+    let schema = data.serialize_with_schema(&mut cursor).unwrap();
+    assert_same_schema(&Schema::from_json(&schema.to_json()).unwrap(), &schema);
+
+    let schema = Schema(vec![SchemaRow {
+        field: "a\"b\\c\nd\u{1}é".to_string(),
+        ty: "&'a str".to_string(),
+        offset: 1,
+        size: 2,
+        align: 3,
+    }]);
+    assert_same_schema(&Schema::from_json(&schema.to_json()).unwrap(), &schema);
+
+    // Formatting and unknown keys do not matter
+    let schema = Schema::from_json(&format!(
+        r#"{{"rows":[{{"ty":"u8","extra":[{{}}],"field":"x","offset":0,"size":1,"align":0}}],"version":[{},99]}}"#,
+        epserde::VERSION.0
+    ))
+    .unwrap();
+    assert_eq!(schema.0[0].field, "x");
+
+    assert!(matches!(
+        Schema::from_json(r#"{"version": [999, 0], "rows": []}"#),
+        Err(deser::Error::MajorVersionMismatch(999))
+    ));
+    for bad in [
+        "",
+        "{",
+        r#"{"rows": []}"#,
+        r#"{"version": [1, 1], "rows": [{"field": "x"}]}"#,
+        r#"{"version": [1, 1], "rows": []} x"#,
+        r#"{"version": [1, 1], "rows": [}"#,
+        r#"{"version": [1, 1], "rows": ["\q"]}"#,
+    ] {
+        assert!(Schema::from_json(bad).is_err(), "{}", bad);
+    }
+}
+
+#[test]
+fn test_size_report() {
+    let data = Data {
+        a: vec![1_u64, 2, 3],
+        b: "hello".to_string(),
+    };
+    let mut cursor = epserde::new_aligned_cursor();
+    let report = data.serialize_with_size_report(&mut cursor).unwrap();
+    let len = cursor.position() as usize;
+
+    let names = report
+        .fields
+        .iter()
+        .map(|(name, _)| name.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(names, ["a", "b"]);
+    let usize_size = core::mem::size_of::<usize>();
+    // The vector is aligned, so the padding is within the field
+    assert!(report.fields[0].1 >= usize_size + 24);
+    assert_eq!(report.fields[1].1, usize_size + 5);
+    assert_eq!(report.total, len);
+    assert_eq!(
+        report.header + report.fields[0].1 + report.fields[1].1 + report.padding,
+        report.total
+    );
+    assert_eq!(report.fields_padding[1], 0);
+    assert_eq!(
+        report.fields_padding[0] + report.padding,
+        report.total_padding
+    );
+
+    let display = report.to_string();
+    assert_eq!(display.lines().count(), 5);
+    assert!(display.starts_with("HEADER"));
+    assert!(display
+        .lines()
+        .last()
+        .unwrap()
+        .ends_with(&format!(" {}", len)));
+
+    let tmp_file = std::env::temp_dir().join("test_size_report.bin");
+    assert_eq!(data.store_with_size_report(&tmp_file).unwrap(), report);
+    std::fs::remove_file(&tmp_file).unwrap();
+}
+
+#[derive(Epserde, Debug, PartialEq)]
+struct Padded {
+    flag: u8,
+    values: Vec<u64>,
+    tag: u8,
+    more: Vec<u32>,
+}
+
+#[test]
+fn test_padding() {
+    let data = Padded {
+        flag: 1,
+        values: vec![1, 2, 3],
+        tag: 2,
+        more: vec![4, 5],
+    };
+    let mut cursor = epserde::new_aligned_cursor();
+    let schema = data.serialize_with_schema(&mut cursor).unwrap();
+    let offset = |field: &str| {
+        schema
+            .0
+            .iter()
+            .find(|row| row.field == field)
+            .unwrap()
+            .offset
+    };
+    let usize_size = core::mem::size_of::<usize>();
+    // The padding between the length and the content of the vectors
+    let values_padding = offset("ROOT.values.zero") - offset("ROOT.values") - usize_size;
+    let more_padding = offset("ROOT.more.zero") - offset("ROOT.more") - usize_size;
+    assert_eq!(schema.padding_of("ROOT.values"), values_padding);
+    assert_eq!(schema.padding_of("ROOT.more"), more_padding);
+    assert_eq!(schema.padding_of("ROOT.flag"), 0);
+    assert_eq!(schema.padding_of("ROOT"), values_padding + more_padding);
+    assert_eq!(schema.padding(), values_padding + more_padding);
+    assert_eq!(schema.padding_of("ROOT.missing"), 0);
+
+    let report = schema.size_report();
+    assert_eq!(report.fields_padding, [0, values_padding, 0, more_padding]);
+    assert_eq!(report.padding, 0);
+    assert_eq!(report.total_padding, values_padding + more_padding);
+    if values_padding != 0 {
+        assert!(report
+            .to_string()
+            .contains(&format!("(padding {})", values_padding)));
+    }
+}
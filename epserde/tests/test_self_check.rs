@@ -0,0 +1,93 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+#![cfg(test)]
+
+use epserde::deser::{DeserializeInner, ReadWithPos};
+use epserde::prelude::*;
+use epserde::self_check::{self_check_value, SelfCheckFailure};
+use epserde::ser::WriteWithNames;
+
+#[derive(Epserde, Debug, PartialEq, Eq, Clone, Copy, Default)]
+#[repr(C)]
+#[zero_copy]
+struct Point {
+    x: u32,
+    y: u32,
+}
+
+#[derive(Epserde, Debug, PartialEq, Default)]
+struct Data<A> {
+    values: A,
+    points: Vec<Point>,
+    names: Vec<String>,
+    len: usize,
+}
+
+/// A module deserializing the coordinates of a point in the wrong order.
+mod swapped {
+    use super::*;
+
+    pub fn serialize(value: &Point, backend: &mut impl WriteWithNames) -> ser::Result<()> {
+        backend.write("x", &value.x)?;
+        backend.write("y", &value.y)
+    }
+
+    pub fn deserialize_full(backend: &mut impl ReadWithPos) -> deser::Result<Point> {
+        let y = u32::_deserialize_full_inner(backend)?;
+        let x = u32::_deserialize_full_inner(backend)?;
+        Ok(Point { x, y })
+    }
+
+    pub fn type_hash(hasher: &mut impl core::hash::Hasher) {
+        core::hash::Hash::hash("Swapped", hasher);
+    }
+}
+
+#[derive(Epserde, Debug, PartialEq, Default)]
+struct Buggy {
+    #[epserde(with = "swapped")]
+    point: Point,
+}
+
+#[test]
+fn test_self_check() {
+    let report = epserde::self_check::<Data<Vec<u64>>>();
+    assert!(report.is_ok(), "{}", report);
+    assert!(report.len > 0);
+    assert_eq!(
+        (report.type_hash, report.repr_hash),
+        epserde::traits::type_hashes::<Data<Vec<u64>>>()
+    );
+
+    let report = self_check_value(&Data {
+        values: vec![1_u8, 2, 3],
+        points: vec![Point { x: 1, y: 2 }; 10],
+        names: vec!["a".into(), "bc".into()],
+        len: usize::MAX,
+    });
+    assert!(report.is_ok(), "{}", report);
+    assert!(report.to_string().contains("All checks passed"));
+}
+
+#[test]
+fn test_self_check_failure() {
+    // The default value is symmetric, so the bug is not detected
+    assert!(epserde::self_check::<Buggy>().is_ok());
+
+    let report = self_check_value(&Buggy {
+        point: Point { x: 1, y: 2 },
+    });
+    assert!(!report.is_ok());
+    assert!(matches!(
+        report.failures[..],
+        [
+            SelfCheckFailure::FullMismatch,
+            SelfCheckFailure::PortableMismatch
+        ]
+    ));
+    assert!(report.to_string().contains("FAILED"));
+}
@@ -0,0 +1,114 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+#![cfg(test)]
+
+use epserde::semver::*;
+
+mod v1 {
+    use epserde::prelude::*;
+    #[derive(Epserde)]
+    pub struct Person {
+        pub name: String,
+        pub age: u8,
+    }
+}
+
+mod v2 {
+    use epserde::prelude::*;
+    #[derive(Epserde)]
+    pub struct Person {
+        pub name: String,
+        pub age: u16,
+    }
+}
+
+#[test]
+fn test_lock_roundtrip() {
+    let mut lock = TypeHashLock::new();
+    lock.record::<v1::Person>()
+        .record::<Vec<u64>>()
+        .record::<(u8, u32)>();
+    let parsed = TypeHashLock::parse(&lock.to_string()).unwrap();
+    assert_eq!(lock, parsed);
+    assert!(lock.diff(&parsed).is_empty());
+}
+
+#[test]
+fn test_lock_diff() {
+    let mut old = TypeHashLock::new();
+    old.record::<v1::Person>();
+    let mut new = TypeHashLock::new();
+    new.record::<v2::Person>();
+
+    // Types are recorded by name, so we rename the new entry
+    let entry = new
+        .entries
+        .remove(core::any::type_name::<v2::Person>())
+        .unwrap();
+    new.entries
+        .insert(core::any::type_name::<v1::Person>().to_string(), entry);
+
+    let diffs = new.diff(&old);
+    assert_eq!(diffs.len(), 2);
+    match &diffs[0] {
+        LockDiff::TypeHashChanged { changes, .. } => {
+            assert_eq!(
+                changes,
+                &[
+                    "- \"u8\" (after \"String\")",
+                    "+ \"u16\" (after \"String\")"
+                ]
+            );
+        }
+        diff => panic!("Unexpected diff {:?}", diff),
+    }
+    assert!(matches!(diffs[1], LockDiff::ReprHashChanged { .. }));
+    println!("{}", diffs[0]);
+
+    let mut other = TypeHashLock::new();
+    other.record::<Vec<u8>>();
+    let diffs = other.diff(&old);
+    assert!(matches!(&diffs[0], LockDiff::Missing(_)));
+    assert!(matches!(&diffs[1], LockDiff::Added(_)));
+}
+
+#[test]
+fn test_check_lock_file() {
+    let path = std::env::temp_dir().join("epserde_test_semver.lock");
+    let _ = std::fs::remove_file(&path);
+    // The first check writes the file
+    epserde::check_type_hash_lock!(&path, v1::Person, Vec<v1::Person>);
+    assert!(path.exists());
+    // The second one compares it
+    epserde::check_type_hash_lock!(&path, v1::Person, Vec<v1::Person>);
+
+    let mut lock = TypeHashLock::new();
+    lock.record::<v1::Person>();
+    let message = lock.check(&path).unwrap_err();
+    assert!(message.contains("is not recorded anymore"));
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn test_type_hash_stable() {
+    assert_eq!(
+        epserde::type_hash_of::<Vec<u64>>(),
+        epserde::traits::type_hashes::<Vec<u64>>().0
+    );
+    epserde::assert_type_hash_stable!(Vec<u64>, 0xfe37109733990d08);
+    epserde::assert_type_hash_stable!((String, u8), 0x956dc04e83f51b8e);
+    assert_ne!(
+        epserde::type_hash_of::<v1::Person>(),
+        epserde::type_hash_of::<v2::Person>()
+    );
+}
+
+#[test]
+#[should_panic(expected = "instead of 0x0000000000000000")]
+fn test_type_hash_unstable() {
+    epserde::assert_type_hash_stable!(v1::Person, 0);
+}
@@ -0,0 +1,83 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ * SPDX-FileCopyrightText: 2023 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+#![cfg(test)]
+
+use epserde::prelude::*;
+use epserde::ser::CountingWriter;
+
+#[derive(Epserde, Debug, Clone, Copy, PartialEq)]
+#[repr(C)]
+#[zero_copy]
+struct Point {
+    x: u8,
+    y: u128,
+}
+
+#[derive(Epserde, Debug, PartialEq)]
+enum Shape {
+    Empty,
+    Points(Vec<Point>),
+    Named { name: String, points: [Point; 2] },
+}
+
+#[derive(Epserde, Debug, PartialEq)]
+struct Data<A> {
+    flag: bool,
+    values: A,
+    shapes: Vec<Shape>,
+}
+
+fn check<T: Serialize>(value: &T) {
+    let mut cursor = epserde::new_aligned_cursor();
+    let len = value.serialize(&mut cursor).unwrap();
+    assert_eq!(cursor.into_inner().len(), len);
+    assert_eq!(value.serialized_size(), len);
+}
+
+#[test]
+fn test_serialized_size() {
+    check(&0_u8);
+    check(&String::new());
+    check(&vec![1_u8, 2, 3]);
+    check(&vec![Point { x: 0, y: 1 }; 3]);
+    check(&(1_u8, vec![1_u64], 2_u16, vec![Point { x: 0, y: 1 }]));
+    check(&Data {
+        flag: true,
+        values: vec![1_u32, 2, 3],
+        shapes: vec![
+            Shape::Empty,
+            Shape::Points(vec![Point { x: 1, y: 2 }]),
+            Shape::Named {
+                name: "shape".to_string(),
+                points: [Point { x: 3, y: 4 }; 2],
+            },
+        ],
+    });
+    check(&Data {
+        flag: false,
+        values: Data {
+            flag: true,
+            values: "nested".to_string(),
+            shapes: vec![],
+        },
+        shapes: vec![],
+    });
+}
+
+#[test]
+fn test_counting_writer() {
+    let data = vec![0_u64; 100];
+    let mut writer = CountingWriter::new();
+    assert!(writer.is_empty());
+    let len = data.serialize(&mut writer).unwrap();
+    assert_eq!(writer.len(), len);
+    // The size can be used to preallocate exactly the space needed
+    let mut buf = vec![0_u128; len.div_ceil(16)];
+    let bytes: &mut [u8] = &mut bytemuck::cast_slice_mut(&mut buf)[..len];
+    assert_eq!(data.serialize_into_slice(bytes).unwrap(), len);
+}
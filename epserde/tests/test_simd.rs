@@ -0,0 +1,80 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+#![cfg(all(test, feature = "simd", target_arch = "x86_64"))]
+
+use core::arch::x86_64::*;
+use core::mem::transmute;
+use epserde::prelude::*;
+
+#[derive(Epserde, Debug, Clone, Copy)]
+#[repr(C)]
+#[zero_copy]
+struct Block {
+    keys: __m256i,
+    weights: __m256,
+}
+
+#[test]
+fn test_simd_vec() {
+    let data: Vec<__m256> = (0..10)
+        .map(|i| unsafe { transmute::<[f32; 8], __m256>([i as f32; 8]) })
+        .collect();
+    let mut cursor = epserde::new_aligned_cursor();
+    data.serialize(&mut cursor).unwrap();
+    let buf = cursor.into_inner();
+
+    let eps = <Vec<__m256>>::deserialize_eps(&buf).unwrap();
+    assert_eq!(eps.len(), 10);
+    assert!(buf.as_ptr_range().contains(&eps.as_ptr().cast()));
+    assert_eq!(eps.as_ptr() as usize % 32, 0);
+    for (i, v) in eps.iter().enumerate() {
+        assert_eq!(unsafe { transmute::<__m256, [f32; 8]>(*v) }, [i as f32; 8]);
+    }
+
+    let full = <Vec<__m128i>>::deserialize_full(&mut std::io::Cursor::new(&buf));
+    assert!(matches!(full, Err(deser::Error::WrongTypeHash { .. })));
+}
+
+#[test]
+fn test_simd_struct() {
+    let block = Block {
+        keys: unsafe { transmute::<[u32; 8], __m256i>([7; 8]) },
+        weights: unsafe { transmute::<[f32; 8], __m256>([0.5; 8]) },
+    };
+    let mut cursor = epserde::new_aligned_cursor();
+    block.serialize(&mut cursor).unwrap();
+    cursor.set_position(0);
+    let full = Block::deserialize_full(&mut cursor).unwrap();
+    assert_eq!(unsafe { transmute::<__m256i, [u32; 8]>(full.keys) }, [7; 8]);
+
+    let buf = cursor.into_inner();
+    let eps = Block::deserialize_eps(&buf).unwrap();
+    assert_eq!(
+        unsafe { transmute::<__m256, [f32; 8]>(eps.weights) },
+        [0.5; 8]
+    );
+}
+
+#[test]
+fn test_simd_byte_swap() {
+    let mut bytes = unsafe { transmute::<[f32; 4], [u8; 16]>([1.0, 2.0, 3.0, 4.0]) };
+    <__m128>::byte_swap(&mut bytes).unwrap();
+    <__m128>::byte_swap(&mut bytes).unwrap();
+    assert_eq!(
+        unsafe { transmute::<[u8; 16], [f32; 4]>(bytes) },
+        [1.0, 2.0, 3.0, 4.0]
+    );
+    <__m128>::byte_swap(&mut bytes).unwrap();
+    assert_eq!(
+        unsafe { transmute::<[u8; 16], [u32; 4]>(bytes) }[0],
+        1.0_f32.to_bits().swap_bytes()
+    );
+    assert!(matches!(
+        <__m128i>::byte_swap(&mut bytes),
+        Err(deser::Error::ByteSwapUnsupported(_))
+    ));
+}
@@ -10,6 +10,7 @@ use anyhow::Result;
 use epserde::prelude::*;
 
 #[derive(Epserde, Debug, PartialEq, Eq, Clone)]
+#[allow(dead_code)]
 struct Data<A: PartialEq = usize, const Q: usize = 3> {
     a: A,
     b: [i32; Q],
@@ -29,3 +30,52 @@ fn test_cheaty_serialize() -> Result<()> {
     assert_eq!(a, b);
     Ok(())
 }
+
+/// Serialize a value that might be unsized, as a generic function would do.
+fn serialize_ref<S: Serialize + ?Sized>(value: &S) -> Result<Vec<u8>> {
+    let mut cursor = epserde::new_aligned_cursor();
+    value.serialize(&mut cursor)?;
+    Ok(cursor.into_inner())
+}
+
+#[test]
+fn test_cheaty_serialize_str() -> Result<()> {
+    let owned = String::from("Hello, world!");
+    let s = &owned[7..];
+    let backend = serialize_ref(s)?;
+    assert_eq!(
+        String::deserialize_full(&mut std::io::Cursor::new(&backend))?,
+        "world!"
+    );
+    assert_eq!(String::deserialize_eps(&backend)?, "world!");
+
+    // The same bytes as the owned string
+    assert_eq!(backend, serialize_ref(&String::from("world!"))?);
+
+    let backend = serialize_ref("")?;
+    assert_eq!(String::deserialize_eps(&backend)?, "");
+    Ok(())
+}
+
+#[test]
+fn test_cheaty_serialize_ref() -> Result<()> {
+    // A borrowed slice assembled at runtime
+    let data = (0..10).collect::<Vec<u64>>();
+    let parts = [&data[2..5], &data[7..]];
+    for part in parts {
+        let backend = serialize_ref(part)?;
+        assert_eq!(<Vec<u64>>::deserialize_eps(&backend)?, part);
+    }
+
+    // References to sized types
+    let value = Data {
+        a: 7_usize,
+        b: [1, 2, 3],
+    };
+    let backend = serialize_ref(&value)?;
+    assert_eq!(
+        <Data>::deserialize_full(&mut std::io::Cursor::new(&backend))?,
+        value
+    );
+    Ok(())
+}
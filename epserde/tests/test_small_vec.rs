@@ -0,0 +1,113 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ * SPDX-FileCopyrightText: 2023 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+#![cfg(all(test, feature = "smallvec", feature = "arrayvec", feature = "tinyvec"))]
+
+use epserde::prelude::*;
+
+fn serialize<T: Serialize>(value: &T) -> Vec<u8> {
+    let mut cursor = epserde::new_aligned_cursor();
+    value.serialize(&mut cursor).unwrap();
+    cursor.into_inner()
+}
+
+#[derive(Epserde, Debug, PartialEq)]
+struct Node {
+    keys: smallvec::SmallVec<[u64; 4]>,
+    labels: arrayvec::ArrayVec<String, 3>,
+    children: tinyvec::TinyVec<[u32; 2]>,
+    weights: tinyvec::ArrayVec<[f32; 4]>,
+}
+
+#[test]
+fn test_derive() {
+    let node = Node {
+        keys: smallvec::smallvec![1, 2, 3, 4, 5],
+        labels: ["a", "b"].into_iter().map(String::from).collect(),
+        children: tinyvec::tiny_vec![7],
+        weights: tinyvec::array_vec!([f32; 4] => 0.5, 0.25),
+    };
+    let buf = serialize(&node);
+
+    let full = Node::deserialize_full(&mut buf.as_slice()).unwrap();
+    assert_eq!(full, node);
+    assert!(full.keys.spilled());
+    assert!(full.children.is_inline());
+
+    // Fields of non-generic structures are fully deserialized
+    assert_eq!(Node::deserialize_eps(&buf).unwrap(), node);
+}
+
+#[test]
+fn test_small_vec() {
+    use smallvec::SmallVec;
+
+    // The inline capacity does not change the data
+    let inline: SmallVec<[u32; 8]> = (0..5).collect();
+    let buf = serialize(&inline);
+    let spilled = <SmallVec<[u32; 2]>>::deserialize_full(&mut buf.as_slice()).unwrap();
+    assert!(spilled.spilled());
+    assert_eq!(spilled.as_slice(), inline.as_slice());
+
+    let buf = serialize(&spilled);
+    let full = <SmallVec<[u32; 8]>>::deserialize_full(&mut buf.as_slice()).unwrap();
+    assert!(!full.spilled());
+    assert_eq!(full, inline);
+    assert_ne!(
+        epserde::type_hash_of::<SmallVec<[u32; 8]>>(),
+        epserde::type_hash_of::<Vec<u32>>()
+    );
+}
+
+#[test]
+fn test_array_vec() {
+    let v: arrayvec::ArrayVec<u16, 8> = (0..8).collect();
+    let buf = serialize(&v);
+    let view: &[u16] = <arrayvec::ArrayVec<u16, 8>>::deserialize_eps(&buf).unwrap();
+    assert_eq!(view, v.as_slice());
+    assert_eq!(
+        <arrayvec::ArrayVec<u16, 16>>::deserialize_full(&mut buf.as_slice())
+            .unwrap()
+            .as_slice(),
+        v.as_slice()
+    );
+    // Too many elements for the capacity
+    assert!(matches!(
+        <arrayvec::ArrayVec<u16, 4>>::deserialize_full(&mut buf.as_slice()),
+        Err(deser::Error::InvalidValue("ArrayVec"))
+    ));
+    assert!(matches!(
+        <tinyvec::ArrayVec<[u16; 4]>>::deserialize_full(&mut buf.as_slice()),
+        Err(deser::Error::InvalidValue("ArrayVec"))
+    ));
+
+    // The array vectors of the two crates are interchangeable
+    let full = <tinyvec::ArrayVec<[u16; 8]>>::deserialize_full(&mut buf.as_slice()).unwrap();
+    assert_eq!(full.as_slice(), v.as_slice());
+}
+
+#[test]
+fn test_tiny_vec() {
+    use tinyvec::TinyVec;
+
+    let v: TinyVec<[u64; 2]> = (0..10).collect();
+    assert!(v.is_heap());
+    let buf = serialize(&v);
+    let view: &[u64] = <TinyVec<[u64; 2]>>::deserialize_eps(&buf).unwrap();
+    assert_eq!(view, v.as_slice());
+    let full = <TinyVec<[u64; 2]>>::deserialize_full(&mut buf.as_slice()).unwrap();
+    assert!(full.is_heap());
+    assert_eq!(full, v);
+
+    let v: TinyVec<[String; 2]> = tinyvec::tiny_vec!["x".to_string()];
+    let buf = serialize(&v);
+    let view: Vec<&str> = <TinyVec<[String; 2]>>::deserialize_eps(&buf).unwrap();
+    assert_eq!(view, ["x"]);
+    let full = <TinyVec<[String; 2]>>::deserialize_full(&mut buf.as_slice()).unwrap();
+    assert!(full.is_inline());
+    assert_eq!(full, v);
+}
@@ -0,0 +1,186 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ * SPDX-FileCopyrightText: 2023 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+#![cfg(test)]
+
+use epserde::prelude::*;
+use epserde::snapshot::*;
+use std::path::PathBuf;
+
+#[derive(Epserde, Debug, PartialEq)]
+struct Graph {
+    successors: Vec<Vec<u32>>,
+}
+
+fn dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(name);
+    let _ = std::fs::remove_dir_all(&dir);
+    dir
+}
+
+fn write(dir: &PathBuf, labels: &[&str]) {
+    let mut writer = SnapshotWriter::new(dir).unwrap();
+    writer
+        .add(
+            "graph",
+            &Graph {
+                successors: vec![vec![1, 2], vec![0]],
+            },
+        )
+        .unwrap();
+    let labels: Vec<String> = labels.iter().map(|s| s.to_string()).collect();
+    writer
+        .add_with("labels", |mut writer| {
+            labels.serialize_with_checksum(&mut writer).map(|_| ())
+        })
+        .unwrap();
+    assert_eq!(writer.files().len(), 2);
+    writer.commit().unwrap();
+}
+
+#[test]
+fn test_round_trip() {
+    let dir = dir("test_snapshot_round_trip");
+    write(&dir, &["a", "b", "c"]);
+
+    let reader = SnapshotReader::open(&dir).unwrap();
+    let names = reader
+        .files()
+        .iter()
+        .map(|file| file.name.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(names, ["graph", "labels"]);
+    let graph = reader.load_full::<Graph>("graph").unwrap();
+    assert_eq!(graph.successors, [vec![1, 2], vec![0]]);
+    let labels = reader.load_mem::<Vec<String>>("labels").unwrap();
+    assert_eq!(*labels, ["a", "b", "c"]);
+    #[cfg(feature = "mmap-rs")]
+    {
+        let graph = reader.mmap::<Graph>("graph", Flags::empty()).unwrap();
+        assert_eq!(graph.successors.len(), 2);
+    }
+    assert!(matches!(
+        reader.load_full::<Graph>("missing"),
+        Err(deser::Error::FileOpenError(_))
+    ));
+
+    // A new snapshot replaces the previous one
+    write(&dir, &["d"]);
+    let reader = SnapshotReader::open(&dir).unwrap();
+    assert_eq!(reader.load_full::<Vec<String>>("labels").unwrap(), ["d"]);
+    // No temporary files are left
+    assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 3);
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_interrupted() {
+    let dir = dir("test_snapshot_interrupted");
+    assert!(matches!(
+        SnapshotReader::open(&dir),
+        Err(deser::Error::FileOpenError(_))
+    ));
+
+    write(&dir, &["a", "b", "c"]);
+    // A commit interrupted after renaming one file with the same length
+    let mut cursor = epserde::new_aligned_cursor();
+    vec!["x".to_string(), "y".to_string(), "z".to_string()]
+        .serialize_with_checksum(&mut cursor)
+        .unwrap();
+    std::fs::write(dir.join("labels"), cursor.into_inner()).unwrap();
+    assert!(SnapshotReader::open_unverified(&dir).is_ok());
+    assert!(matches!(
+        SnapshotReader::open(&dir),
+        Err(deser::Error::InconsistentSnapshot(_))
+    ));
+
+    // A commit interrupted after renaming one file with a different length
+    write(&dir, &["a", "b", "c"]);
+    Vec::<String>::new().store(dir.join("labels")).unwrap();
+    assert!(matches!(
+        SnapshotReader::open_unverified(&dir),
+        Err(deser::Error::InconsistentSnapshot(_))
+    ));
+
+    std::fs::remove_file(dir.join("labels")).unwrap();
+    assert!(matches!(
+        SnapshotReader::open(&dir),
+        Err(deser::Error::InconsistentSnapshot(_))
+    ));
+
+    std::fs::write(dir.join(MANIFEST), "garbage").unwrap();
+    assert!(matches!(
+        SnapshotReader::open(&dir),
+        Err(deser::Error::InconsistentSnapshot(_))
+    ));
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_failed_commit() {
+    let dir = dir("test_snapshot_failed_commit");
+    write(&dir, &["a", "b", "c"]);
+    assert!(SnapshotReader::open(&dir).is_ok());
+
+    let mut writer = SnapshotWriter::new(&dir).unwrap();
+    writer
+        .add(
+            "graph",
+            &Graph {
+                successors: vec![vec![0], vec![1, 2]],
+            },
+        )
+        .unwrap();
+    writer.add("labels", &vec!["x".to_string()]).unwrap();
+    // Renaming the second file fails after the first one has been renamed
+    std::fs::remove_file(dir.join("labels")).unwrap();
+    std::fs::create_dir_all(dir.join("labels").join("busy")).unwrap();
+    assert!(writer.commit().is_err());
+    // The previous manifest has been removed, so the mix of old and new
+    // files is refused even if lengths match
+    assert!(matches!(
+        SnapshotReader::open_unverified(&dir),
+        Err(deser::Error::FileOpenError(_))
+    ));
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_uncommitted() {
+    let dir = dir("test_snapshot_uncommitted");
+    let mut writer = SnapshotWriter::new(&dir).unwrap();
+    writer.add("values", &vec![1_u64, 2, 3]).unwrap();
+    // Failed writes leave no temporary files
+    assert!(writer
+        .add_with("failed", |_| Err(ser::Error::WriteError))
+        .is_err());
+    assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 1);
+    drop(writer);
+    // Temporary files are removed, and there is no manifest
+    assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 0);
+    assert!(SnapshotReader::open(&dir).is_err());
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+#[should_panic]
+fn test_duplicate() {
+    let mut writer = SnapshotWriter::new(dir("test_snapshot_duplicate")).unwrap();
+    writer.add("values", &vec![1_u64]).unwrap();
+    let _ = writer.add("values", &vec![2_u64]);
+}
+
+#[test]
+#[should_panic]
+fn test_invalid_name() {
+    let mut writer = SnapshotWriter::new(dir("test_snapshot_invalid_name")).unwrap();
+    let _ = writer.add("a/b", &vec![1_u64]);
+}
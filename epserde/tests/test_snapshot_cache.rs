@@ -0,0 +1,116 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+#![cfg(test)]
+
+use epserde::deser::SnapshotCache;
+use epserde::prelude::*;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+
+#[test]
+fn test_snapshot_cache() -> anyhow::Result<()> {
+    let tmp_file = std::env::temp_dir().join("test_snapshot_cache.eps");
+    vec![0_u64, 1, 2].store(&tmp_file)?;
+
+    let cache = SnapshotCache::new();
+    let a = cache.load_mem::<Vec<u64>>(&tmp_file)?;
+    let b = cache.load_mem::<Vec<u64>>(&tmp_file)?;
+    assert!(Arc::ptr_eq(&a, &b));
+    assert_eq!(cache.len(), 1);
+
+    // Different files are cached separately
+    let other_file = std::env::temp_dir().join("test_snapshot_cache_other.eps");
+    vec![5_u64].store(&other_file)?;
+    let c = cache.load_mem::<Vec<u64>>(&other_file)?;
+    assert_eq!(**c, [5]);
+    assert_eq!(cache.len(), 2);
+    drop(c);
+    assert_eq!(cache.len(), 1);
+    std::fs::remove_file(&other_file)?;
+
+    // Rewriting the file invalidates the cache
+    vec![3_u64, 4].store(&tmp_file)?;
+    std::fs::File::options()
+        .write(true)
+        .open(&tmp_file)?
+        .set_modified(SystemTime::now() + Duration::from_secs(10))?;
+    let d = cache.load_mem::<Vec<u64>>(&tmp_file)?;
+    assert!(!Arc::ptr_eq(&a, &d));
+    assert_eq!(**a, [0, 1, 2]);
+    assert_eq!(**d, [3, 4]);
+
+    // Structures are released when no longer in use
+    drop(a);
+    drop(b);
+    drop(d);
+    assert!(cache.is_empty());
+
+    let e = SnapshotCache::global().load_mem::<Vec<u64>>(&tmp_file)?;
+    let f = SnapshotCache::global().load_mem::<Vec<u64>>(&tmp_file)?;
+    assert!(Arc::ptr_eq(&e, &f));
+
+    std::fs::remove_file(&tmp_file)?;
+    Ok(())
+}
+
+#[test]
+fn test_snapshot_cache_same_mtime() -> anyhow::Result<()> {
+    let tmp_file = std::env::temp_dir().join("test_snapshot_cache_same_mtime.eps");
+    vec![0_u64, 1, 2].store(&tmp_file)?;
+    let modified = tmp_file.metadata()?.modified()?;
+    let set_modified = |path: &std::path::Path| {
+        std::fs::File::options()
+            .write(true)
+            .open(path)?
+            .set_modified(modified)
+    };
+
+    let cache = SnapshotCache::new();
+    let a = cache.load_mem::<Vec<u64>>(&tmp_file)?;
+
+    // A file rewritten in place with a different length
+    vec![3_u64, 4].store(&tmp_file)?;
+    set_modified(&tmp_file)?;
+    let b = cache.load_mem::<Vec<u64>>(&tmp_file)?;
+    assert_eq!(**a, [0, 1, 2]);
+    assert_eq!(**b, [3, 4]);
+
+    // A file atomically replaced by one with the same length
+    #[cfg(unix)]
+    {
+        let new_file = std::env::temp_dir().join("test_snapshot_cache_same_mtime.new");
+        vec![5_u64, 6].store(&new_file)?;
+        set_modified(&new_file)?;
+        std::fs::rename(&new_file, &tmp_file)?;
+        let c = cache.load_mem::<Vec<u64>>(&tmp_file)?;
+        assert_eq!(**b, [3, 4]);
+        assert_eq!(**c, [5, 6]);
+    }
+
+    std::fs::remove_file(&tmp_file)?;
+    Ok(())
+}
+
+#[cfg(feature = "mmap-rs")]
+#[test]
+fn test_snapshot_cache_mmap() -> anyhow::Result<()> {
+    let tmp_file = std::env::temp_dir().join("test_snapshot_cache_mmap.eps");
+    vec![0_u64, 1, 2].store(&tmp_file)?;
+
+    let cache = SnapshotCache::new();
+    let a = cache.mmap::<Vec<u64>>(&tmp_file, Flags::empty())?;
+    let b = cache.mmap::<Vec<u64>>(&tmp_file, Flags::empty())?;
+    let c = cache.load_mem::<Vec<u64>>(&tmp_file)?;
+    assert!(Arc::ptr_eq(&a, &b));
+    assert_eq!(**a, **c);
+    assert_eq!(cache.len(), 2);
+    cache.clear();
+    assert!(cache.is_empty());
+
+    std::fs::remove_file(&tmp_file)?;
+    Ok(())
+}
@@ -0,0 +1,78 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+#![cfg(test)]
+
+use epserde::prelude::*;
+use epserde::sorted_pairs::SortedPairs;
+
+fn serialize<T: Serialize>(value: &T) -> Vec<u8> {
+    let mut cursor = epserde::new_aligned_cursor();
+    value.serialize(&mut cursor).unwrap();
+    cursor.into_inner()
+}
+
+#[test]
+fn test_zero_copy() {
+    let pairs: SortedPairs<u64, u32> = (0..1000_u64).rev().map(|i| (i * 3, i as u32)).collect();
+    assert!(!pairs.is_sorted());
+    let original = pairs.clone();
+    let buf = serialize(&pairs);
+    // Serialization does not sort the pairs
+    assert_eq!(pairs, original);
+
+    let view = <SortedPairs<u64, u32>>::deserialize_eps(&buf).unwrap();
+    let keys: &[u64] = view.keys();
+    let values: &[u32] = view.values();
+    assert_eq!(keys.len(), 1000);
+    assert!(keys.windows(2).all(|w| w[0] < w[1]));
+    assert_eq!(values[10], 10);
+    for i in 0..1000 {
+        assert_eq!(view.get(&(i * 3)), Some(&(i as u32)));
+        assert_eq!(view.get(&(i * 3 + 1)), None);
+    }
+
+    let full = <SortedPairs<u64, u32>>::deserialize_full(&mut std::io::Cursor::new(&buf)).unwrap();
+    assert!(full.is_sorted());
+    assert_eq!(full.get(&300), Some(&100));
+    let mut sorted = original;
+    sorted.sort();
+    assert_eq!(full, sorted);
+}
+
+#[test]
+fn test_deep_copy() {
+    let mut pairs = SortedPairs::new();
+    pairs.push("b".to_string(), vec![2_u16, 2]);
+    pairs.push("c".to_string(), vec![3]);
+    pairs.push("a".to_string(), vec![]);
+    let buf = serialize(&pairs);
+
+    let view = <SortedPairs<String, Vec<u16>>>::deserialize_eps(&buf).unwrap();
+    assert_eq!(view.keys(), &["a", "b", "c"]);
+    assert_eq!(view.get("b"), Some(&[2_u16, 2].as_slice()));
+    assert_eq!(view.get("d"), None);
+
+    let full =
+        <SortedPairs<String, Vec<u16>>>::deserialize_full(&mut std::io::Cursor::new(&buf)).unwrap();
+    assert_eq!(full.get("c"), Some(&vec![3]));
+    assert_eq!(full.len(), 3);
+}
+
+#[test]
+fn test_duplicates_and_empty() {
+    let pairs = SortedPairs::from(vec![(2_u8, 'x'), (1, 'y'), (2, 'z')]);
+    let buf = serialize(&pairs);
+    let full = <SortedPairs<u8, char>>::deserialize_full(&mut std::io::Cursor::new(&buf)).unwrap();
+    // Pairs with the same key keep their insertion order
+    assert_eq!(full.into_inner(), [(1, 'y'), (2, 'x'), (2, 'z')]);
+
+    let pairs = SortedPairs::<u32, u32>::new();
+    let buf = serialize(&pairs);
+    let view = <SortedPairs<u32, u32>>::deserialize_eps(&buf).unwrap();
+    assert!(view.is_empty());
+    assert_eq!(view.get(&0), None);
+}
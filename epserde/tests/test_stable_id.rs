@@ -0,0 +1,154 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ * SPDX-FileCopyrightText: 2023 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+#![cfg(test)]
+
+use epserde::prelude::*;
+use epserde::snapshot::*;
+use epserde::stable_id::*;
+
+#[derive(Epserde, Debug, PartialEq)]
+struct Labels {
+    names: Vec<String>,
+}
+
+#[derive(Epserde, Debug, PartialEq)]
+struct Graph {
+    labels: StableId<Labels>,
+    weights: StableId<Vec<f64>>,
+    successors: Vec<Vec<u32>>,
+    // References to other graphs
+    links: Vec<StableId<Graph>>,
+}
+
+const LABELS: u128 = 1;
+const WEIGHTS: u128 = 2;
+const GRAPH: u128 = 3;
+
+#[test]
+fn test_round_trip() -> anyhow::Result<()> {
+    let graph = Graph {
+        labels: StableId::new(LABELS),
+        weights: StableId::new(WEIGHTS),
+        successors: vec![vec![1], vec![0]],
+        links: vec![StableId::new(GRAPH), StableId::new(GRAPH + 1)],
+    };
+    assert!(graph.labels.is_current());
+    assert_eq!(graph.labels.id(), LABELS);
+    assert_eq!(graph.labels.type_hash(), epserde::type_hash_of::<Labels>());
+    assert_ne!(graph.labels.type_hash(), graph.weights.type_hash());
+
+    let mut cursor = epserde::new_aligned_cursor();
+    graph.serialize(&mut cursor)?;
+    let bytes = cursor.into_inner();
+    assert_eq!(Graph::deserialize_full(&mut &bytes[..])?, graph);
+    assert_eq!(Graph::deserialize_eps(&bytes)?, graph);
+
+    // Vectors of references are ε-copy deserialized as slices
+    let mut cursor = epserde::new_aligned_cursor();
+    graph.links.serialize(&mut cursor)?;
+    let bytes = cursor.into_inner();
+    let links: &[StableId<Graph>] = <Vec<StableId<Graph>>>::deserialize_eps(&bytes)?;
+    assert_eq!(links, graph.links);
+    // Single references are ε-copy deserialized by value
+    let mut cursor = epserde::new_aligned_cursor();
+    graph.labels.serialize(&mut cursor)?;
+    let bytes = cursor.into_inner();
+    let labels: StableId<Labels> = <StableId<Labels>>::deserialize_eps(&bytes)?;
+    assert_eq!(labels, graph.labels);
+    Ok(())
+}
+
+#[test]
+fn test_snapshot() -> anyhow::Result<()> {
+    let dir = std::env::temp_dir().join("test_stable_id_snapshot");
+    let _ = std::fs::remove_dir_all(&dir);
+    let mut writer = SnapshotWriter::new(&dir)?;
+    writer.add(
+        "labels",
+        &Labels {
+            names: vec!["a".to_string(), "b".to_string()],
+        },
+    )?;
+    writer.add("weights", &vec![0.5_f64, 1.5])?;
+    writer.add(
+        "graph",
+        &Graph {
+            labels: StableId::new(LABELS),
+            weights: StableId::new(WEIGHTS),
+            successors: vec![vec![1], vec![0]],
+            links: vec![],
+        },
+    )?;
+    writer.commit()?;
+
+    let reader = SnapshotReader::open(&dir)?;
+    let mut resolver = MemCaseResolver::new();
+    resolver
+        .insert(
+            StableId::<Labels>::new(LABELS),
+            reader.load_mem::<Labels>("labels")?,
+        )
+        .insert(
+            StableId::<Vec<f64>>::new(WEIGHTS),
+            reader.load_mem::<Vec<f64>>("weights")?,
+        );
+    assert_eq!(resolver.len(), 2);
+
+    let graph = reader.load_mem::<Graph>("graph")?;
+    let labels = resolver.resolve(graph.labels)?;
+    let weights = resolver.resolve(graph.weights)?;
+    for (node, successors) in graph.successors.iter().enumerate() {
+        assert_eq!(labels.names[successors[0] as usize], ["b", "a"][node]);
+        assert_eq!(weights[node], [0.5, 1.5][node]);
+    }
+
+    drop(reader);
+    std::fs::remove_dir_all(&dir)?;
+    Ok(())
+}
+
+#[test]
+fn test_errors() {
+    let mut resolver = MemCaseResolver::new();
+    assert!(resolver.is_empty());
+    assert!(matches!(
+        resolver.resolve(StableId::<Vec<u32>>::new(0)),
+        Err(deser::Error::UnknownStableId(0))
+    ));
+
+    // A structure of a different type registered under the same id
+    resolver.insert(StableId::<Vec<u64>>::new(0), MemCase::encase(&[][..]));
+    assert!(resolver.contains(0));
+    match resolver.resolve(StableId::<Vec<u32>>::new(0)) {
+        Err(deser::Error::WrongStableIdType { id, expected, got }) => {
+            assert_eq!(id, 0);
+            assert_eq!(expected, epserde::type_hash_of::<Vec<u32>>());
+            assert_eq!(got, epserde::type_hash_of::<Vec<u64>>());
+        }
+        other => panic!("Unexpected result {:?}", other.map(|_| ())),
+    }
+    assert_eq!(
+        **resolver.resolve(StableId::<Vec<u64>>::new(0)).unwrap(),
+        [0_u64; 0]
+    );
+
+    // A reference written when the referenced type was different
+    let mut cursor = epserde::new_aligned_cursor();
+    StableId::<Vec<u32>>::new(0).serialize(&mut cursor).unwrap();
+    let bytes = cursor.into_inner();
+    let stale = <StableId<Vec<u64>>>::deserialize_full(&mut &bytes[..]).unwrap();
+    assert!(!stale.is_current());
+    assert!(matches!(
+        resolver.resolve(stale),
+        Err(deser::Error::WrongStableIdType { .. })
+    ));
+
+    assert!(resolver.remove(0));
+    assert!(!resolver.remove(0));
+    assert!(resolver.is_empty());
+}
@@ -0,0 +1,103 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+#![cfg(test)]
+
+use epserde::deser::LazyStr;
+use epserde::prelude::*;
+
+#[derive(Epserde, Debug, PartialEq)]
+struct Data<S> {
+    #[epserde(
+        deser_type = "LazyStr<'epserde>",
+        deser_with = "epserde::deser::lazy_str"
+    )]
+    lazy: S,
+    name: String,
+}
+
+/// Serialize some data and corrupt the first byte of the given string.
+fn corrupted(target: &str) -> Vec<u8> {
+    let data = Data {
+        lazy: "lazy".to_string(),
+        name: "name".to_string(),
+    };
+    let mut cursor = epserde::new_aligned_cursor();
+    data.serialize(&mut cursor).unwrap();
+    let mut bytes = cursor.into_inner();
+    let pos = bytes
+        .windows(target.len())
+        .position(|w| w == target.as_bytes())
+        .unwrap();
+    bytes[pos] = 0xFF;
+    bytes
+}
+
+#[test]
+fn test_valid() {
+    let data = Data {
+        lazy: "lazy".to_string(),
+        name: "name".to_string(),
+    };
+    let mut cursor = epserde::new_aligned_cursor();
+    data.serialize(&mut cursor).unwrap();
+    let bytes = cursor.into_inner();
+    for policy in [StrPolicy::Trusted, StrPolicy::Validated, StrPolicy::Lazy] {
+        let eps = <Data<String>>::deserialize_eps_with_policy(&bytes, policy).unwrap();
+        assert_eq!(eps.lazy.as_str().unwrap(), "lazy");
+        assert_eq!(eps.lazy.as_bytes(), b"lazy");
+        assert_eq!(eps.name, "name");
+    }
+}
+
+#[test]
+fn test_invalid_str() {
+    let bytes = corrupted("name");
+    assert!(matches!(
+        <Data<String>>::deserialize_eps_with_policy(&bytes, StrPolicy::Validated),
+        Err(deser::Error::InvalidUtf8)
+    ));
+    // &str views are always validated, unless trusted
+    assert!(matches!(
+        <Data<String>>::deserialize_eps_with_policy(&bytes, StrPolicy::Lazy),
+        Err(deser::Error::InvalidUtf8)
+    ));
+    assert!(matches!(
+        <Data<String>>::deserialize_full(&mut std::io::Cursor::new(&bytes)),
+        Err(deser::Error::InvalidUtf8)
+    ));
+}
+
+#[test]
+fn test_invalid_lazy_str() {
+    let bytes = corrupted("lazy");
+    assert!(matches!(
+        <Data<String>>::deserialize_eps_with_policy(&bytes, StrPolicy::Validated),
+        Err(deser::Error::InvalidUtf8)
+    ));
+    // Validation is postponed to the first access, and cached
+    let eps = <Data<String>>::deserialize_eps_with_policy(&bytes, StrPolicy::Lazy).unwrap();
+    assert_eq!(eps.name, "name");
+    assert!(matches!(eps.lazy.as_str(), Err(deser::Error::InvalidUtf8)));
+    assert!(matches!(
+        eps.lazy.clone().as_str(),
+        Err(deser::Error::InvalidUtf8)
+    ));
+    assert_eq!(eps.lazy.as_bytes(), b"\xFFazy");
+}
+
+#[test]
+fn test_load_mem_with_policy() -> anyhow::Result<()> {
+    let bytes = corrupted("name");
+    let tmp_file = std::env::temp_dir().join("test_str_policy.eps");
+    std::fs::write(&tmp_file, &bytes)?;
+    let Err(err) = <Data<String>>::load_mem_with_policy(&tmp_file, StrPolicy::Validated) else {
+        panic!("Invalid UTF-8 was not detected");
+    };
+    assert!(matches!(err, deser::Error::InvalidUtf8));
+    std::fs::remove_file(&tmp_file)?;
+    Ok(())
+}
@@ -0,0 +1,160 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+#![cfg(test)]
+
+use epserde::prelude::*;
+
+/// Writes data with the opposite endianness of the current architecture.
+struct SwappedWriter(Vec<u8>);
+
+impl SwappedWriter {
+    fn new<T: TypeHash + ReprHash + 'static>() -> Self {
+        let mut writer = Self(Vec::new());
+        let (type_hash, repr_hash) = type_hashes::<T>();
+        writer.bytes(&epserde::MAGIC.swap_bytes().to_ne_bytes());
+        writer.bytes(&epserde::VERSION.0.swap_bytes().to_ne_bytes());
+        writer.bytes(&epserde::VERSION.1.swap_bytes().to_ne_bytes());
+        writer.bytes(&[core::mem::size_of::<usize>() as u8]);
+        writer.bytes(&type_hash.swap_bytes().to_ne_bytes());
+        writer.bytes(&repr_hash.swap_bytes().to_ne_bytes());
+        writer.str(core::any::type_name::<T>());
+        writer
+    }
+
+    fn bytes(&mut self, bytes: &[u8]) {
+        self.0.extend_from_slice(bytes);
+    }
+
+    fn align(&mut self, align: usize) {
+        let padding = epserde::pad_align_to(self.0.len(), align);
+        self.0.extend(std::iter::repeat_n(0, padding));
+    }
+
+    fn usize(&mut self, value: usize) {
+        self.bytes(&value.swap_bytes().to_ne_bytes());
+    }
+
+    fn str(&mut self, s: &str) {
+        self.usize(s.len());
+        self.bytes(s.as_bytes());
+    }
+}
+
+#[derive(Epserde, Debug, PartialEq, Eq, Clone, Copy)]
+#[repr(C)]
+#[zero_copy]
+struct Point {
+    x: u16,
+    y: u64,
+    z: (u8, u32),
+}
+
+#[derive(Epserde, Debug, PartialEq, Eq)]
+struct Data {
+    values: Vec<u32>,
+    point: Point,
+    name: String,
+    tag: Option<i64>,
+}
+
+#[test]
+fn test_swapped_prim() {
+    let mut writer = SwappedWriter::new::<u64>();
+    writer.bytes(&0x0102030405060708_u64.swap_bytes().to_ne_bytes());
+    let buf = writer.0;
+
+    let mut cursor = std::io::Cursor::new(&buf);
+    assert!(matches!(
+        u64::deserialize_full(&mut cursor),
+        Err(deser::Error::EndiannessError)
+    ));
+    let mut cursor = std::io::Cursor::new(&buf);
+    assert_eq!(
+        u64::deserialize_full_swapped(&mut cursor).unwrap(),
+        0x0102030405060708
+    );
+}
+
+#[test]
+fn test_swapped_struct() {
+    let data = Data {
+        values: vec![1, 0x01020304, u32::MAX - 1],
+        point: Point {
+            x: 0x0102,
+            y: 0x0102030405060708,
+            z: (7, 0x0a0b0c0d),
+        },
+        name: "swapped".to_string(),
+        tag: Some(-2),
+    };
+
+    let mut writer = SwappedWriter::new::<Data>();
+    writer.usize(data.values.len());
+    writer.align(4);
+    for value in &data.values {
+        writer.bytes(&value.swap_bytes().to_ne_bytes());
+    }
+    writer.align(8);
+    let start = writer.0.len();
+    writer.bytes(&data.point.x.swap_bytes().to_ne_bytes());
+    writer.align(8);
+    writer.bytes(&data.point.y.swap_bytes().to_ne_bytes());
+    // The layout of tuples is unspecified, so we ask the compiler
+    let offset = core::mem::offset_of!(Point, z);
+    writer.bytes(&vec![0; start + offset - writer.0.len()]);
+    let mut z = [0; core::mem::size_of::<(u8, u32)>()];
+    z[core::mem::offset_of!((u8, u32), 0)] = data.point.z.0;
+    z[core::mem::offset_of!((u8, u32), 1)..][..4]
+        .copy_from_slice(&data.point.z.1.swap_bytes().to_ne_bytes());
+    writer.bytes(&z);
+    writer.bytes(&vec![
+        0;
+        start + core::mem::size_of::<Point>() - writer.0.len()
+    ]);
+    writer.str(&data.name);
+    writer.bytes(&[1]);
+    writer.bytes(&data.tag.unwrap().swap_bytes().to_ne_bytes());
+    let buf = writer.0;
+
+    let mut cursor = std::io::Cursor::new(&buf);
+    let full = Data::deserialize_full_swapped(&mut cursor).unwrap();
+    assert_eq!(full, data);
+    assert_eq!(cursor.position() as usize, buf.len());
+
+    // Native data cannot be deserialized as swapped data
+    let mut cursor = epserde::new_aligned_cursor();
+    data.serialize(&mut cursor).unwrap();
+    cursor.set_position(0);
+    assert!(matches!(
+        Data::deserialize_full_swapped(&mut cursor),
+        Err(deser::Error::EndiannessError)
+    ));
+}
+
+#[derive(Epserde, Debug, PartialEq, Eq, Clone, Copy)]
+#[repr(C)]
+#[zero_copy]
+#[allow(dead_code)]
+enum Kind {
+    A,
+    B,
+}
+
+#[test]
+fn test_swapped_enum() {
+    let mut writer = SwappedWriter::new::<Vec<Kind>>();
+    writer.usize(1);
+    writer.align(core::mem::align_of::<Kind>());
+    writer.bytes(&vec![0; core::mem::size_of::<Kind>()]);
+    let buf = writer.0;
+
+    let mut cursor = std::io::Cursor::new(&buf);
+    assert!(matches!(
+        <Vec<Kind>>::deserialize_full_swapped(&mut cursor),
+        Err(deser::Error::ByteSwapUnsupported(_))
+    ));
+}
@@ -0,0 +1,78 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+#![cfg(test)]
+
+use epserde::prelude::*;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+macro_rules! round_trip {
+    ($value:expr, $ty:ty) => {{
+        let value: $ty = $value;
+        let mut cursor = epserde::new_aligned_cursor();
+        value.serialize(&mut cursor).unwrap();
+        let buf = cursor.into_inner();
+        let full = <$ty>::deserialize_full(&mut std::io::Cursor::new(&buf)).unwrap();
+        assert_eq!(full, value);
+        let eps = <$ty>::deserialize_eps(&buf).unwrap();
+        assert_eq!(eps, value);
+    }};
+}
+
+#[test]
+fn test_duration() {
+    round_trip!(Duration::ZERO, Duration);
+    round_trip!(Duration::new(3, 141_592_653), Duration);
+    round_trip!(Duration::MAX, Duration);
+}
+
+#[test]
+fn test_system_time() {
+    round_trip!(UNIX_EPOCH, SystemTime);
+    round_trip!(SystemTime::now(), SystemTime);
+    round_trip!(UNIX_EPOCH + Duration::new(1_700_000_000, 5), SystemTime);
+    round_trip!(UNIX_EPOCH - Duration::new(10, 0), SystemTime);
+    round_trip!(UNIX_EPOCH - Duration::new(10, 250_000_000), SystemTime);
+}
+
+#[derive(Epserde, Debug, PartialEq)]
+struct Metadata {
+    created: SystemTime,
+    build_time: Duration,
+    values: Vec<u32>,
+}
+
+#[test]
+fn test_struct() {
+    let metadata = Metadata {
+        created: SystemTime::now(),
+        build_time: Duration::from_millis(1234),
+        values: vec![1, 2, 3],
+    };
+    let mut cursor = epserde::new_aligned_cursor();
+    metadata.serialize(&mut cursor).unwrap();
+    let buf = cursor.into_inner();
+    let full = Metadata::deserialize_full(&mut std::io::Cursor::new(&buf)).unwrap();
+    assert_eq!(full, metadata);
+    let eps = Metadata::deserialize_eps(&buf).unwrap();
+    assert_eq!(eps.created, metadata.created);
+    assert_eq!(eps.build_time, metadata.build_time);
+    assert_eq!(eps.values, [1, 2, 3]);
+}
+
+#[test]
+fn test_invalid_nanos() {
+    let mut cursor = epserde::new_aligned_cursor();
+    Duration::new(5, 0).serialize(&mut cursor).unwrap();
+    let mut buf = cursor.into_inner();
+    // Nanoseconds are the last four bytes
+    let len = buf.len();
+    buf[len - 4..].copy_from_slice(&1_000_000_000_u32.to_ne_bytes());
+    assert!(matches!(
+        Duration::deserialize_full(&mut std::io::Cursor::new(&buf)),
+        Err(deser::Error::InvalidValue("Duration"))
+    ));
+}
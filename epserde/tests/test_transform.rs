@@ -0,0 +1,111 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+#![cfg(test)]
+
+use epserde::prelude::*;
+use epserde::ser::{SchemaWriter, WriterWithPos};
+use epserde::transform::transform_field;
+
+#[derive(Epserde, Debug, PartialEq, Clone)]
+struct Data {
+    name: String,
+    values: Vec<f32>,
+    nested: Vec<Vec<u16>>,
+    tail: u8,
+}
+
+fn sample() -> Data {
+    Data {
+        name: "data".into(),
+        values: (0..100_000).map(|i| i as f32).collect(),
+        nested: vec![vec![1, 2], vec![], vec![3]],
+        tail: 42,
+    }
+}
+
+#[test]
+fn test_transform_field() -> anyhow::Result<()> {
+    let data = sample();
+    let src = std::env::temp_dir().join("test_transform_src.eps");
+    let dst = std::env::temp_dir().join("test_transform_dst.eps");
+    let schema = data.serialize_with_schema(&mut std::fs::File::create(&src)?)?;
+
+    let count = transform_field(&src, &dst, &schema, "ROOT.values", |x: &mut f32| *x *= 2.0)?;
+    assert_eq!(count, data.values.len());
+    let mut expected = data.clone();
+    expected.values.iter_mut().for_each(|x| *x *= 2.0);
+    assert_eq!(Data::load_full(&dst)?, expected);
+    assert_eq!(
+        std::fs::metadata(&src)?.len(),
+        std::fs::metadata(&dst)?.len()
+    );
+
+    // All instances of a nested field are transformed
+    let count = transform_field(&src, &dst, &schema, "ROOT.nested.item", |x: &mut u16| {
+        *x += 10
+    })?;
+    assert_eq!(count, 3);
+    let mut expected = data.clone();
+    expected.nested = vec![vec![11, 12], vec![], vec![13]];
+    assert_eq!(Data::load_full(&dst)?, expected);
+
+    // Wrong type
+    assert!(matches!(
+        transform_field(&src, &dst, &schema, "ROOT.values", |_: &mut u32| {}),
+        Err(deser::Error::SchemaMismatch { field, .. }) if field == "ROOT.values"
+    ));
+    // Missing field
+    assert!(matches!(
+        transform_field(&src, &dst, &schema, "ROOT.missing", |_: &mut f32| {}),
+        Err(deser::Error::SchemaMismatch { .. })
+    ));
+    // Schema of a different file
+    let other_schema = vec![0_u8].serialize_with_schema(&mut std::io::sink())?;
+    assert!(matches!(
+        transform_field(&src, &dst, &other_schema, "ROOT", |_: &mut u8| {}),
+        Err(deser::Error::SchemaMismatch { .. })
+    ));
+
+    std::fs::remove_file(&src)?;
+    std::fs::remove_file(&dst)?;
+    Ok(())
+}
+
+#[test]
+fn test_transform_field_checksum() -> anyhow::Result<()> {
+    let data = sample();
+    let src = std::env::temp_dir().join("test_transform_checksum_src.eps");
+    let dst = std::env::temp_dir().join("test_transform_checksum_dst.eps");
+    data.store_with_checksum(&src)?;
+    // The schema of a file with a checksum
+    let mut sink = std::io::sink();
+    let mut writer = WriterWithPos::new_with_checksum(&mut sink);
+    let mut schema_writer = SchemaWriter::new(&mut writer);
+    data.serialize_on_field_write(&mut schema_writer)?;
+    let schema = schema_writer.schema;
+
+    transform_field(&src, &dst, &schema, "ROOT.values", |x: &mut f32| *x = -*x)?;
+    let mut expected = data.clone();
+    expected.values.iter_mut().for_each(|x| *x = -*x);
+    // Loading verifies the checksum
+    assert_eq!(Data::load_full(&dst)?, expected);
+    assert_eq!(Data::load_mem(&dst)?.values, &expected.values[..]);
+
+    // Corrupted files are rejected
+    let mut bytes = std::fs::read(&src)?;
+    let last = bytes.len() - 1;
+    bytes[last] ^= 1;
+    std::fs::write(&src, bytes)?;
+    assert!(matches!(
+        transform_field(&src, &dst, &schema, "ROOT.values", |_: &mut f32| {}),
+        Err(deser::Error::ChecksumMismatch { .. })
+    ));
+
+    std::fs::remove_file(&src)?;
+    std::fs::remove_file(&dst)?;
+    Ok(())
+}
@@ -0,0 +1,129 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+#![cfg(test)]
+
+use epserde::prelude::*;
+
+#[derive(Epserde, Debug, PartialEq, Eq, Clone, Copy)]
+#[repr(C)]
+#[zero_copy]
+struct Point {
+    x: u64,
+    y: u64,
+}
+
+#[test]
+fn test_tuple_copy_type() {
+    fn is_zero_copy<T: CopyType>() -> bool {
+        <T::Copy as CopySelector>::IS_ZERO_COPY
+    }
+    assert!(is_zero_copy::<(u8, Point, (u32, f64))>());
+    assert!(!is_zero_copy::<(u8, String)>());
+    assert!(!is_zero_copy::<(Vec<u8>, u32)>());
+    assert!(!is_zero_copy::<(u8, (u32, String))>());
+}
+
+#[test]
+fn test_tuple_mixed() {
+    let tuple = (
+        7_u8,
+        vec![Point { x: 1, y: 2 }],
+        "mixed".to_string(),
+        Point { x: 3, y: 4 },
+        (1_u16, vec![5_u32, 6]),
+    );
+    type Tuple = (u8, Vec<Point>, String, Point, (u16, Vec<u32>));
+
+    let mut cursor = epserde::new_aligned_cursor();
+    tuple.serialize(&mut cursor).unwrap();
+
+    cursor.set_position(0);
+    let full = Tuple::deserialize_full(&mut cursor).unwrap();
+    assert_eq!(full, tuple);
+
+    let buf = cursor.into_inner();
+    let eps = Tuple::deserialize_eps(&buf).unwrap();
+    assert_eq!(eps.0, 7);
+    assert_eq!(eps.1, &[Point { x: 1, y: 2 }]);
+    assert_eq!(eps.2, "mixed");
+    assert_eq!(eps.3, &Point { x: 3, y: 4 });
+    assert_eq!(eps.4 .0, 1);
+    assert_eq!(eps.4 .1, &[5, 6]);
+}
+
+#[test]
+fn test_tuple_12() {
+    let tuple = (
+        0_u8, 1_u16, 2_u32, 3_u64, 4_i8, 5_i16, 6_i32, 7_i64, 8_usize, 9_isize, 10_u128, 11_i128,
+    );
+    let mut cursor = epserde::new_aligned_cursor();
+    tuple.serialize(&mut cursor).unwrap();
+    let buf = cursor.into_inner();
+    let eps = <(
+        u8,
+        u16,
+        u32,
+        u64,
+        i8,
+        i16,
+        i32,
+        i64,
+        usize,
+        isize,
+        u128,
+        i128,
+    )>::deserialize_eps(&buf)
+    .unwrap();
+    assert_eq!(eps, &tuple);
+
+    let tuple = (
+        0_u8,
+        1_u16,
+        2_u32,
+        3_u64,
+        4_i8,
+        5_i16,
+        6_i32,
+        7_i64,
+        8_usize,
+        9_isize,
+        10_u128,
+        "eleven".to_string(),
+    );
+    let mut cursor = epserde::new_aligned_cursor();
+    tuple.serialize(&mut cursor).unwrap();
+    cursor.set_position(0);
+    let full = <(
+        u8,
+        u16,
+        u32,
+        u64,
+        i8,
+        i16,
+        i32,
+        i64,
+        usize,
+        isize,
+        u128,
+        String,
+    )>::deserialize_full(&mut cursor)
+    .unwrap();
+    assert_eq!(full, tuple);
+}
+
+#[test]
+fn test_tuple_vec() {
+    let data = vec![(1_u32, "a".to_string()), (2, "bb".to_string())];
+    let mut cursor = epserde::new_aligned_cursor();
+    data.serialize(&mut cursor).unwrap();
+    cursor.set_position(0);
+    let full = <Vec<(u32, String)>>::deserialize_full(&mut cursor).unwrap();
+    assert_eq!(full, data);
+    let buf = cursor.into_inner();
+    let eps = <Vec<(u32, String)>>::deserialize_eps(&buf).unwrap();
+    assert_eq!(eps, vec![(1, "a"), (2, "bb")]);
+}
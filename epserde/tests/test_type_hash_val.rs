@@ -58,3 +58,15 @@ fn test_type_hash_collision() {
 
     dbg!(hashes);
 }
+
+#[test]
+/// Check that cached hashes are the same as freshly computed ones
+fn test_type_hashes_cache() {
+    use epserde::traits::{compute_type_hashes, prewarm_type_hashes, type_hashes};
+
+    prewarm_type_hashes::<Vec<u32>>();
+    assert_eq!(type_hashes::<Vec<u32>>(), compute_type_hashes::<Vec<u32>>());
+    assert_eq!(type_hashes::<Vec<u32>>(), type_hashes::<Vec<u32>>());
+    assert_eq!(type_hashes::<String>(), compute_type_hashes::<String>());
+    assert_ne!(type_hashes::<Vec<u32>>(), type_hashes::<Vec<i32>>());
+}
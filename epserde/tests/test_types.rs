@@ -305,7 +305,7 @@ fn test_enum_deep() {
     assert!(matches!(eps, Data::E));
 
     let mut buf = epserde::new_aligned_cursor();
-    let a = Vec::from_iter(iter::repeat(Data::A).take(10));
+    let a = Vec::from_iter(iter::repeat_n(Data::A, 10));
     a.serialize(&mut buf).unwrap();
     buf.set_position(0);
     let full = <Vec<Data>>::deserialize_full(&mut buf).unwrap();
@@ -375,7 +375,7 @@ fn test_enum_zero() {
     assert_eq!(a, *eps);
 
     let mut buf = epserde::new_aligned_cursor();
-    let a = Vec::from_iter(iter::repeat(Data::A).take(10));
+    let a = Vec::from_iter(iter::repeat_n(Data::A, 10));
     a.serialize(&mut buf).unwrap();
     buf.set_position(0);
     let full = <Vec<Data>>::deserialize_full(&mut buf).unwrap();
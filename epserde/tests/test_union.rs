@@ -0,0 +1,101 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+#![cfg(test)]
+
+use epserde::prelude::*;
+
+#[derive(Epserde, Clone, Copy)]
+#[repr(C)]
+#[zero_copy]
+union Bits {
+    float: f64,
+    int: u64,
+    bytes: [u8; 8],
+}
+
+#[derive(Epserde, Clone, Copy)]
+#[repr(C)]
+#[zero_copy]
+struct Tagged {
+    tag: u8,
+    bits: Bits,
+}
+
+#[test]
+fn test_union() {
+    let bits = Bits { float: 1.5 };
+
+    let mut cursor = epserde::new_aligned_cursor();
+    bits.serialize(&mut cursor).unwrap();
+    cursor.set_position(0);
+    let full = Bits::deserialize_full(&mut cursor).unwrap();
+    assert_eq!(unsafe { full.int }, 1.5_f64.to_bits());
+
+    let buf = cursor.into_inner();
+    let eps = Bits::deserialize_eps(&buf).unwrap();
+    assert_eq!(unsafe { eps.float }, 1.5);
+    assert_eq!(unsafe { eps.bytes }, 1.5_f64.to_ne_bytes());
+}
+
+#[test]
+fn test_union_in_struct_and_vec() {
+    let data = vec![
+        Tagged {
+            tag: 0,
+            bits: Bits { int: 42 },
+        },
+        Tagged {
+            tag: 1,
+            bits: Bits { float: -2.0 },
+        },
+    ];
+
+    let mut cursor = epserde::new_aligned_cursor();
+    data.serialize(&mut cursor).unwrap();
+    cursor.set_position(0);
+    let full = <Vec<Tagged>>::deserialize_full(&mut cursor).unwrap();
+    assert_eq!(unsafe { full[0].bits.int }, 42);
+
+    let buf = cursor.into_inner();
+    let eps = <Vec<Tagged>>::deserialize_eps(&buf).unwrap();
+    assert_eq!(eps.len(), 2);
+    assert_eq!(eps[1].tag, 1);
+    assert_eq!(unsafe { eps[1].bits.float }, -2.0);
+}
+
+mod as_struct {
+    use epserde::prelude::*;
+    #[derive(Epserde, Clone, Copy)]
+    #[repr(C)]
+    #[zero_copy]
+    pub struct Single {
+        pub float: f64,
+    }
+}
+
+mod as_union {
+    use epserde::prelude::*;
+    #[derive(Epserde, Clone, Copy)]
+    #[repr(C)]
+    #[zero_copy]
+    pub union Single {
+        pub float: f64,
+    }
+}
+
+#[test]
+fn test_union_type_hash() {
+    // A union and a struct with the same name and fields have different hashes
+    assert_ne!(
+        compute_type_hashes::<as_struct::Single>().0,
+        compute_type_hashes::<as_union::Single>().0
+    );
+    assert_eq!(
+        compute_type_hashes::<as_struct::Single>().1,
+        compute_type_hashes::<as_union::Single>().1
+    );
+}
@@ -0,0 +1,137 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ * SPDX-FileCopyrightText: 2023 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+#![cfg(test)]
+
+use epserde::prelude::*;
+
+#[derive(Epserde, Debug, Clone, Copy, PartialEq)]
+#[repr(C)]
+#[zero_copy]
+struct Point {
+    x: u32,
+    flag: bool,
+    letter: char,
+}
+
+#[derive(Epserde, Debug, Clone, Copy, PartialEq)]
+#[repr(C)]
+#[zero_copy]
+enum Kind {
+    A,
+    B(u16),
+    C { flag: bool },
+}
+
+#[derive(Epserde, Debug, Clone, PartialEq)]
+enum Tree {
+    Leaf(String),
+    Node(Vec<u8>, Option<Vec<String>>),
+}
+
+#[derive(Epserde, Debug, Clone, PartialEq)]
+struct Data<A, B, C, D> {
+    name: A,
+    points: B,
+    kinds: C,
+    trees: D,
+    flags: Vec<bool>,
+    letters: Vec<char>,
+    tuple: (u8, u64),
+    array: [Point; 2],
+    option: Option<String>,
+}
+
+type Owned = Data<String, Vec<Point>, Box<[Kind]>, Vec<Tree>>;
+
+fn data() -> Owned {
+    let point = Point {
+        x: 1,
+        flag: true,
+        letter: 'ε',
+    };
+    Data {
+        name: "data".to_string(),
+        points: vec![point; 3],
+        kinds: vec![Kind::A, Kind::B(2), Kind::C { flag: true }].into_boxed_slice(),
+        trees: vec![
+            Tree::Leaf("leaf".to_string()),
+            Tree::Node(vec![1, 2, 3], Some(vec!["a".to_string()])),
+        ],
+        flags: vec![true, false],
+        letters: vec!['a', 'β'],
+        tuple: (1, 2),
+        array: [point; 2],
+        option: Some("option".to_string()),
+    }
+}
+
+/// Deserialize `bytes` in all possible ways; the result is irrelevant,
+/// but there must be no panic.
+fn deserialize(bytes: &[u8]) {
+    let mut buf = vec![0_u128; bytes.len().div_ceil(16)];
+    let aligned = &mut bytemuck::cast_slice_mut::<u128, u8>(&mut buf)[..bytes.len()];
+    aligned.copy_from_slice(bytes);
+    let _ = Owned::deserialize_eps_untrusted(aligned);
+    let mut backend = SliceWithPos::new(aligned);
+    backend.set_str_policy(StrPolicy::Lazy);
+    backend.set_validation(Validation::Checked);
+    let _ = deser::check_header::<Owned>(&mut backend)
+        .and_then(|()| Owned::_deserialize_eps_inner(&mut backend).map(|_| ()));
+    let _ = Owned::deserialize_full(&mut &bytes[..]);
+}
+
+#[test]
+fn test_untrusted_roundtrip() {
+    let mut cursor = epserde::new_aligned_cursor();
+    data().serialize(&mut cursor).unwrap();
+    let bytes = cursor.into_inner();
+    let eps = Owned::deserialize_eps_untrusted(&bytes).unwrap();
+    assert_eq!(eps.name, "data");
+    assert_eq!(eps.points, &data().points[..]);
+    assert_eq!(eps.kinds, &data().kinds[..]);
+    assert_eq!(eps.trees, data().trees);
+    assert_eq!(eps.letters, &data().letters[..]);
+    assert_eq!(eps.array, data().array);
+}
+
+#[test]
+fn test_untrusted_mutations() {
+    let mut cursor = epserde::new_aligned_cursor();
+    data().serialize(&mut cursor).unwrap();
+    let bytes = cursor.into_inner();
+
+    for len in 0..bytes.len() {
+        deserialize(&bytes[..len]);
+    }
+
+    let mut mutated = bytes.clone();
+    for pos in 0..bytes.len() {
+        for byte in [0, 1, 2, 0x7F, 0x80, 0xFF, bytes[pos] ^ 1, bytes[pos] ^ 0x80] {
+            mutated[pos] = byte;
+            deserialize(&mutated);
+        }
+        mutated[pos] = bytes[pos];
+    }
+
+    // Random mutations of several bytes
+    let mut state = 0x9E37_79B9_7F4A_7C15_u64;
+    let mut next = || {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        state
+    };
+    for _ in 0..10_000 {
+        let mut mutated = bytes.clone();
+        for _ in 0..1 + next() % 4 {
+            let pos = next() as usize % bytes.len();
+            mutated[pos] = next() as u8;
+        }
+        deserialize(&mutated);
+    }
+}
@@ -0,0 +1,126 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+#![cfg(test)]
+
+use core::hash::{Hash, Hasher};
+use epserde::deser::{DeserializeInner, ReadWithPos};
+use epserde::prelude::*;
+use epserde::ser::WriteWithNames;
+
+/// A type not implementing the ε-serde traits.
+#[derive(Debug, PartialEq, Clone)]
+struct Point {
+    x: f64,
+    y: f64,
+}
+
+mod point {
+    use super::*;
+
+    pub fn serialize(value: &Point, backend: &mut impl WriteWithNames) -> ser::Result<()> {
+        backend.write("x", &value.x)?;
+        backend.write("y", &value.y)
+    }
+
+    pub fn deserialize_full(backend: &mut impl ReadWithPos) -> deser::Result<Point> {
+        let x = f64::_deserialize_full_inner(backend)?;
+        let y = f64::_deserialize_full_inner(backend)?;
+        Ok(Point { x, y })
+    }
+
+    pub fn type_hash(hasher: &mut impl Hasher) {
+        "Point".hash(hasher);
+    }
+}
+
+/// Same serialization as [`point`], but with a different type hash.
+mod other_point {
+    pub use super::point::{deserialize_full, serialize};
+
+    pub fn type_hash(hasher: &mut impl core::hash::Hasher) {
+        use core::hash::Hash;
+        "OtherPoint".hash(hasher);
+    }
+}
+
+#[derive(Epserde, Debug, PartialEq, Clone)]
+struct Data<A> {
+    values: A,
+    #[epserde(with = "point")]
+    origin: Point,
+    #[epserde(with = "point")]
+    corner: Point,
+    name: String,
+}
+
+#[derive(Epserde, Debug, PartialEq, Clone)]
+struct Other<A> {
+    values: A,
+    #[epserde(with = "other_point")]
+    origin: Point,
+    #[epserde(with = "point")]
+    corner: Point,
+    name: String,
+}
+
+fn data() -> Data<Vec<u32>> {
+    Data {
+        values: vec![1, 2, 3],
+        origin: Point { x: 0.5, y: -1.0 },
+        corner: Point { x: 2.0, y: 3.0 },
+        name: "data".into(),
+    }
+}
+
+#[test]
+fn test_with() {
+    let data = data();
+    let mut cursor = epserde::new_aligned_cursor();
+    data.serialize(&mut cursor).unwrap();
+    let bytes = cursor.into_inner();
+
+    let full = <Data<Vec<u32>>>::deserialize_full(&mut std::io::Cursor::new(&bytes)).unwrap();
+    assert_eq!(full, data);
+
+    let eps = <Data<Vec<u32>>>::deserialize_eps(&bytes).unwrap();
+    let values: &[u32] = eps.values;
+    assert_eq!(values, data.values);
+    let origin: Point = eps.origin;
+    assert_eq!(origin, data.origin);
+    assert_eq!(eps.corner, data.corner);
+    assert_eq!(eps.name, "data");
+}
+
+#[test]
+fn test_with_schema() {
+    let mut cursor = epserde::new_aligned_cursor();
+    let schema = data().serialize_with_schema(&mut cursor).unwrap();
+    for path in ["ROOT.origin", "ROOT.origin.x", "ROOT.corner.y"] {
+        assert!(schema.0.iter().any(|row| row.field == path), "{}", path);
+    }
+}
+
+#[test]
+fn test_with_type_hash() {
+    fn type_hash<T: TypeHash>() -> u64 {
+        let mut hasher = xxhash_rust::xxh3::Xxh3::new();
+        T::type_hash(&mut hasher);
+        hasher.finish()
+    }
+    assert_ne!(
+        type_hash::<Data<Vec<u32>>>(),
+        type_hash::<Other<Vec<u32>>>()
+    );
+
+    let mut cursor = epserde::new_aligned_cursor();
+    data().serialize(&mut cursor).unwrap();
+    let bytes = cursor.into_inner();
+    assert!(matches!(
+        <Other<Vec<u32>>>::deserialize_eps(&bytes),
+        Err(deser::Error::WrongTypeHash { .. })
+    ));
+}
@@ -0,0 +1,138 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+#![cfg(test)]
+
+use epserde::prelude::*;
+
+#[derive(Epserde, Debug, PartialEq, Eq, Clone, Copy, Default)]
+#[repr(C)]
+#[zero_copy]
+struct MyPoint {
+    x: u32,
+    y: u32,
+}
+
+fn points<const N: usize>() -> [MyPoint; N] {
+    core::array::from_fn(|i| MyPoint {
+        x: i as u32,
+        y: 2 * i as u32,
+    })
+}
+
+/// A zero-copy structure containing arrays of a derived zero-copy structure.
+#[derive(Epserde, Debug, PartialEq, Eq, Clone, Copy)]
+#[repr(C)]
+#[zero_copy]
+struct Grid {
+    points: [MyPoint; 4],
+    origin: MyPoint,
+}
+
+/// A zero-copy structure whose type parameters appear only inside arrays,
+/// and thus need no bounds.
+#[derive(Epserde, Debug, PartialEq, Eq, Clone, Copy)]
+#[repr(C)]
+#[zero_copy]
+struct Block<T, const N: usize> {
+    items: [T; N],
+    rows: [[T; 2]; 2],
+}
+
+#[derive(Epserde, Debug, PartialEq, Eq)]
+struct Data<A, B, C> {
+    points: A,
+    grids: B,
+    rows: C,
+    fixed: [MyPoint; 2],
+}
+
+#[derive(Epserde, Debug, PartialEq, Eq)]
+struct Outer<D, E> {
+    inner: D,
+    blocks: E,
+}
+
+type Inner = Data<[MyPoint; 1024], Vec<Grid>, Vec<[MyPoint; 3]>>;
+
+fn data() -> Inner {
+    Data {
+        points: points(),
+        grids: vec![
+            Grid {
+                points: points(),
+                origin: MyPoint { x: 1, y: 1 },
+            };
+            3
+        ],
+        rows: vec![points(), [MyPoint::default(); 3]],
+        fixed: points(),
+    }
+}
+
+#[test]
+fn test_array_of_zero_copy() {
+    let data = data();
+    let mut cursor = epserde::new_aligned_cursor();
+    data.serialize(&mut cursor).unwrap();
+    let bytes = cursor.into_inner();
+
+    let full = Inner::deserialize_full(&mut std::io::Cursor::new(&bytes)).unwrap();
+    assert_eq!(full, data);
+
+    let eps = Inner::deserialize_eps(&bytes).unwrap();
+    let points: &[MyPoint; 1024] = eps.points;
+    assert_eq!(points, &data.points);
+    let grids: &[Grid] = eps.grids;
+    assert_eq!(grids, data.grids);
+    let rows: &[[MyPoint; 3]] = eps.rows;
+    assert_eq!(rows, data.rows);
+    let fixed: [MyPoint; 2] = eps.fixed;
+    assert_eq!(fixed, data.fixed);
+}
+
+#[test]
+fn test_nested() {
+    let outer = Outer {
+        inner: data(),
+        blocks: vec![
+            Block {
+                items: points::<5>(),
+                rows: [points(), points()],
+            };
+            2
+        ],
+    };
+    let mut cursor = epserde::new_aligned_cursor();
+    outer.serialize(&mut cursor).unwrap();
+    let bytes = cursor.into_inner();
+
+    type Type = Outer<Inner, Vec<Block<MyPoint, 5>>>;
+    let full = Type::deserialize_full(&mut std::io::Cursor::new(&bytes)).unwrap();
+    assert_eq!(full, outer);
+
+    let eps = Type::deserialize_eps(&bytes).unwrap();
+    let points: &[MyPoint; 1024] = eps.inner.points;
+    assert_eq!(points, &outer.inner.points);
+    assert_eq!(eps.inner.grids, outer.inner.grids);
+    let blocks: &[Block<MyPoint, 5>] = eps.blocks;
+    assert_eq!(blocks, outer.blocks);
+}
+
+#[test]
+fn test_array_of_generic_zero_copy() {
+    let blocks = [Block {
+        items: [1_u16, 2],
+        rows: [[3, 4], [5, 6]],
+    }; 3];
+    let mut cursor = epserde::new_aligned_cursor();
+    blocks.serialize(&mut cursor).unwrap();
+    let bytes = cursor.into_inner();
+
+    let eps = <[Block<u16, 2>; 3]>::deserialize_eps(&bytes).unwrap();
+    let eps: &[Block<u16, 2>; 3] = eps;
+    assert_eq!(eps, &blocks);
+}
@@ -0,0 +1,169 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ * SPDX-FileCopyrightText: 2023 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+#![cfg(test)]
+
+use core::marker::PhantomData;
+use core::ops::RangeFull;
+use epserde::deser::DeserType;
+use epserde::prelude::*;
+use epserde::traits::compute_type_hashes;
+use std::fmt::Debug;
+
+/// A unit marker.
+#[derive(Epserde, Debug, Clone, Copy, PartialEq)]
+#[repr(C)]
+#[zero_copy]
+struct Unit;
+
+/// A tuple marker.
+#[derive(Epserde, Debug, Clone, Copy, PartialEq)]
+#[repr(C)]
+#[zero_copy]
+struct Tuple();
+
+/// A type-level placeholder that cannot be instantiated.
+#[derive(Epserde, Debug, Clone, Copy, PartialEq)]
+#[zero_copy]
+enum Never {}
+
+/// A unit enum.
+#[derive(Epserde, Debug, Clone, Copy, PartialEq)]
+#[repr(C)]
+#[zero_copy]
+enum Mode {
+    Fast,
+}
+
+#[derive(Epserde, Debug, Clone, Copy, PartialEq)]
+#[repr(C)]
+#[zero_copy]
+struct Config<M> {
+    a: u8,
+    unit: Unit,
+    tuple: Tuple,
+    empty: (),
+    full: RangeFull,
+    marker: PhantomData<M>,
+    b: u64,
+    mode: Mode,
+}
+
+#[derive(Epserde, Debug, Clone, Copy, PartialEq)]
+#[repr(C)]
+#[zero_copy]
+struct Plain {
+    a: u8,
+    b: u64,
+    mode: Mode,
+}
+
+/// A zero-sized field with alignment 8 moves the following field.
+#[derive(Epserde, Debug, Clone, Copy, PartialEq)]
+#[repr(C)]
+#[zero_copy]
+struct Aligned {
+    a: u8,
+    align: [u64; 0],
+    b: u8,
+}
+
+#[derive(Epserde, Debug, Clone, Copy, PartialEq)]
+#[repr(C)]
+#[zero_copy]
+struct Packed {
+    a: u8,
+    b: u8,
+}
+
+fn config<M>() -> Config<M> {
+    Config {
+        a: 1,
+        unit: Unit,
+        tuple: Tuple(),
+        empty: (),
+        full: ..,
+        marker: PhantomData,
+        b: 2,
+        mode: Mode::Fast,
+    }
+}
+
+/// Round-trip a zero-copy value and a vector of copies of it.
+fn round_trip<T>(value: T)
+where
+    T: Serialize + Deserialize + Copy + PartialEq + Debug,
+    Vec<T>: Serialize + Deserialize,
+    for<'a> DeserType<'a, Vec<T>>: PartialEq<Vec<T>> + Debug,
+{
+    let mut cursor = epserde::new_aligned_cursor();
+    value.serialize(&mut cursor).unwrap();
+    cursor.set_position(0);
+    assert_eq!(T::deserialize_full(&mut cursor).unwrap(), value);
+
+    let values = vec![value; 5];
+    let mut cursor = epserde::new_aligned_cursor();
+    values.serialize(&mut cursor).unwrap();
+    cursor.set_position(0);
+    assert_eq!(<Vec<T>>::deserialize_full(&mut cursor).unwrap(), values);
+    let bytes = cursor.into_inner();
+    assert_eq!(<Vec<T>>::deserialize_eps(&bytes).unwrap(), values);
+}
+
+#[test]
+fn test_zst_fields() {
+    assert_eq!(size_of::<Config<Never>>(), size_of::<Plain>());
+
+    let value = config::<Never>();
+    let mut cursor = epserde::new_aligned_cursor();
+    value.serialize(&mut cursor).unwrap();
+    let bytes = cursor.into_inner();
+    assert_eq!(*<Config<Never>>::deserialize_eps(&bytes).unwrap(), value);
+
+    round_trip(config::<Never>());
+    round_trip(config::<Unit>());
+    round_trip(Aligned {
+        a: 1,
+        align: [],
+        b: 2,
+    });
+}
+
+#[test]
+fn test_zst_slices() {
+    round_trip(());
+    round_trip(..);
+    round_trip(Unit);
+    round_trip(Tuple());
+    round_trip(PhantomData::<Never>);
+    round_trip(Mode::Fast);
+}
+
+#[test]
+fn test_zst_hashes() {
+    // Markers are part of the type hash
+    assert_ne!(
+        compute_type_hashes::<Config<Never>>().0,
+        compute_type_hashes::<Config<Unit>>().0
+    );
+    assert_eq!(
+        compute_type_hashes::<Config<Never>>().1,
+        compute_type_hashes::<Config<Unit>>().1
+    );
+
+    // Zero-sized fields with alignment greater than one change the layout
+    assert_eq!(size_of::<Aligned>(), 16);
+    assert_ne!(
+        compute_type_hashes::<Aligned>().1,
+        compute_type_hashes::<Packed>().1
+    );
+
+    let mut cursor = epserde::new_aligned_cursor();
+    config::<Never>().serialize(&mut cursor).unwrap();
+    let bytes = cursor.into_inner();
+    assert!(<Config<Unit>>::deserialize_eps(&bytes).is_err());
+}
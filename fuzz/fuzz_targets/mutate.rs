@@ -0,0 +1,43 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ * SPDX-FileCopyrightText: 2023 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+#![no_main]
+
+use arbitrary::Arbitrary;
+use epserde::prelude::*;
+use epserde_fuzz::Owned;
+use libfuzzer_sys::fuzz_target;
+
+#[derive(Arbitrary, Debug)]
+struct Input {
+    data: Owned,
+    /// Pairs of positions (modulo the length of the serialization) and bytes.
+    mutations: Vec<(usize, u8)>,
+    /// A length (modulo the length of the serialization) at which to
+    /// truncate the serialization.
+    truncate: Option<usize>,
+}
+
+fuzz_target!(|input: Input| {
+    let mut bytes = Vec::new();
+    input.data.serialize(&mut bytes).unwrap();
+
+    // Valid data must be deserialized correctly
+    assert_eq!(
+        Owned::deserialize_full(&mut &bytes[..]).unwrap(),
+        input.data
+    );
+
+    for (pos, byte) in input.mutations {
+        let len = bytes.len();
+        bytes[pos % len] = byte;
+    }
+    if let Some(len) = input.truncate {
+        bytes.truncate(len % (bytes.len() + 1));
+    }
+    epserde_fuzz::deserialize(&bytes);
+});
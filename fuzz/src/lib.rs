@@ -0,0 +1,68 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ * SPDX-FileCopyrightText: 2023 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+//! Types and helpers shared by the fuzz targets.
+
+use arbitrary::Arbitrary;
+use epserde::prelude::*;
+
+#[derive(Epserde, Arbitrary, Debug, Clone, Copy, PartialEq)]
+#[repr(C)]
+#[zero_copy]
+pub struct Point {
+    pub x: u32,
+    pub flag: bool,
+    pub letter: char,
+}
+
+#[derive(Epserde, Arbitrary, Debug, Clone, Copy, PartialEq)]
+#[repr(C)]
+#[zero_copy]
+pub enum Kind {
+    A,
+    B(u16),
+    C { flag: bool },
+}
+
+#[derive(Epserde, Arbitrary, Debug, Clone, PartialEq)]
+pub enum Tree {
+    Leaf(String),
+    Node(Vec<u8>, Option<Vec<String>>),
+}
+
+#[derive(Epserde, Arbitrary, Debug, Clone, PartialEq)]
+pub struct Data<A, B, C, D> {
+    pub name: A,
+    pub points: B,
+    pub kinds: C,
+    pub trees: D,
+    pub flags: Vec<bool>,
+    pub letters: Vec<char>,
+    pub tuple: (u8, u64),
+    pub array: [Point; 2],
+    pub option: Option<String>,
+}
+
+/// The type deserialized by the fuzz targets.
+pub type Owned = Data<String, Vec<Point>, Box<[Kind]>, Vec<Tree>>;
+
+/// Deserialize `bytes` in all the ways that must never panic, copying
+/// them first to aligned memory.
+pub fn deserialize(bytes: &[u8]) {
+    let mut buf = vec![0_u128; bytes.len().div_ceil(16)];
+    // SAFETY: u128 has no padding and any bit pattern is a valid u8.
+    let aligned =
+        unsafe { core::slice::from_raw_parts_mut(buf.as_mut_ptr() as *mut u8, bytes.len()) };
+    aligned.copy_from_slice(bytes);
+    let _ = Owned::deserialize_eps_untrusted(aligned);
+    let mut backend = SliceWithPos::new(aligned);
+    backend.set_str_policy(StrPolicy::Lazy);
+    backend.set_validation(Validation::Checked);
+    let _ = deser::check_header::<Owned>(&mut backend)
+        .and_then(|()| Owned::_deserialize_eps_inner(&mut backend).map(|_| ()));
+    let _ = Owned::deserialize_full(&mut &bytes[..]);
+}